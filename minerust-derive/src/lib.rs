@@ -1,10 +1,46 @@
+#[cfg(test)]
+#[path = "../test/mod.rs"]
+mod test;
+
+#[doc(hidden)]
+pub use paste;
+
+// Plain integer fields marked with #[varint] or #[varlong] are written with the variable length encoding
+#[doc(hidden)]
+#[macro_export]
+macro_rules! write_field {
+    (varint $value: expr, $buffer: expr) => {
+        ::minerust::network::connection::Writable::write(&::minerust::network::connection::VarI32::from($value), $buffer)
+    };
+    (varlong $value: expr, $buffer: expr) => {
+        ::minerust::network::connection::Writable::write(&::minerust::network::connection::VarI64::from($value), $buffer)
+    };
+    ($value: expr, $buffer: expr) => {
+        ::minerust::network::connection::Writable::write(&$value, $buffer)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! read_field {
+    (varint $type: ty, $buffer: expr) => {
+        <::minerust::network::connection::VarI32 as ::minerust::network::connection::Readable>::read($buffer).map(i32::from)
+    };
+    (varlong $type: ty, $buffer: expr) => {
+        <::minerust::network::connection::VarI64 as ::minerust::network::connection::Readable>::read($buffer).map(i64::from)
+    };
+    ($type: ty, $buffer: expr) => {
+        <$type as ::minerust::network::connection::Readable>::read($buffer)
+    };
+}
+
 #[macro_export]
 macro_rules! protocol {
-    ($name: ident, $literal: expr, $id: expr, $(($packet_name: ident, $packet_id: expr, $direction: ident, $state: ident) => $($value_name: ident: $value_type: ty),*),*) => {
-        paste::paste! {
+    ($name: ident, $literal: expr, $id: expr, $($state: ident $direction: ident { $($packet_name: ident($packet_id: expr) { $($(#[$encoding: ident])? $value_name: ident: $value_type: ty),* $(,)? }),* $(,)? }),* $(,)?) => {
+        $crate::paste::paste! {
             pub struct $name {}
 
-            impl minerust_network::ProtocolVersion for $name {
+            impl ::minerust::network::ProtocolVersion for $name {
                 fn id() -> i32 {
                     $id
                 }
@@ -15,33 +51,116 @@ macro_rules! protocol {
             }
 
             impl $name {
-                pub fn packet_ids() -> Vec<u8> {
-                    let mut vec = Vec::new();
+                const PACKETS: &'static [::minerust::network::PacketDescriptor] = &[$($(
+                    ::minerust::network::PacketDescriptor {
+                        name: stringify!($packet_name),
+                        id: $packet_id,
+                        state: ::minerust::network::PacketState::$state,
+                        direction: ::minerust::network::PacketDirection::$direction,
+                        fields: &[$(
+                            ::minerust::network::FieldDescriptor {
+                                name: stringify!($value_name),
+                                type_name: stringify!($value_type)
+                            },
+                        )*]
+                    },
+                )*)*];
+
+                #[deprecated(note = "use packets() instead, the ids alone are ambiguous across states and directions")]
+                pub fn packet_ids() -> Vec<i32> {
+                    vec![$($($packet_id,)*)*]
+                }
+
+                pub fn packets() -> &'static [::minerust::network::PacketDescriptor] {
+                    Self::PACKETS
+                }
+
+                pub fn packet_by_id(
+                    state: ::minerust::network::PacketState,
+                    direction: ::minerust::network::PacketDirection,
+                    id: i32
+                ) -> Option<&'static ::minerust::network::PacketDescriptor> {
+                    Self::PACKETS.iter().find(|packet| packet.state == state && packet.direction == direction && packet.id == id)
+                }
+            }
+
+            $(
+            #[repr(i32)]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            pub enum [<$state $direction>] {
+                $(
+                $packet_name = $packet_id,
+                )*
+            }
+
+            impl [<$state $direction>] {
+                pub fn from_id(id: i32) -> Option<Self> {
                     $(
-                        vec.push($packet_id);
+                    if id == $packet_id {
+                        return Some(Self::$packet_name)
+                    }
                     )*
-                    vec
+                    None
+                }
+
+                pub fn id(&self) -> i32 {
+                    *self as i32
+                }
+
+                pub fn direction() -> ::minerust::network::PacketDirection {
+                    ::minerust::network::PacketDirection::$direction
+                }
+
+                pub fn state() -> ::minerust::network::PacketState {
+                    ::minerust::network::PacketState::$state
                 }
             }
 
             $(
             pub struct $packet_name {
                 $(
-                $value_name: $value_type,
+                pub $value_name: $value_type,
                 )*
             }
 
-            impl minerust_network::connection::Writable for $packet_name {
-                fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+            impl ::minerust::network::connection::Writable for $packet_name {
+                fn write(&self, mut buffer: ::minerust::network::buffer::Buffer) -> Result<::minerust::network::buffer::Buffer, ::minerust::network::Error> {
                     buffer.write_var_i32($packet_id)?;
                     $(
-                    buffer = self.$value_name.write(buffer)?;
+                    buffer = $crate::write_field!($($encoding)? self.$value_name, buffer)?;
                     )*
-                    Ok(buffer.clone())
+                    Ok(buffer)
+                }
+            }
+
+            // Strict mode rejects trailing bytes, lenient mode skips and records them
+            impl ::minerust::network::connection::Readable for $packet_name {
+                fn read(buffer: &mut ::minerust::network::buffer::Buffer) -> Result<Self, ::minerust::network::Error> {
+                    Self::read_with(buffer, ::minerust::network::decode::DecodeMode::Strict, &::minerust::network::decode::DecodeReport::new())
                 }
             }
 
             impl $packet_name {
+                pub fn read_with(
+                    buffer: &mut ::minerust::network::buffer::Buffer,
+                    mode: ::minerust::network::decode::DecodeMode,
+                    report: &::minerust::network::decode::DecodeReport
+                ) -> Result<Self, ::minerust::network::Error> {
+                    let id = buffer.read_var_i32()?;
+                    if id != $packet_id {
+                        return Err(::minerust::network::Error::IllegalPacket(id, $literal.to_string()))
+                    }
+
+                    let packet = Self {
+                        $(
+                        $value_name: $crate::read_field!($($encoding)? $value_type, buffer)?,
+                        )*
+                    };
+                    mode.check_trailing(buffer, ::minerust::network::PacketState::$state, $packet_id, report)?;
+                    Ok(packet)
+                }
+
+                #[allow(clippy::new_without_default)]
                 pub fn new($($value_name: $value_type,)*) -> Self {
                     Self {
                         $(
@@ -50,18 +169,29 @@ macro_rules! protocol {
                     }
                 }
 
-                pub fn direction() -> minerust_network::PacketDirection {
-                    minerust_network::PacketDirection::$direction
+                pub fn direction() -> ::minerust::network::PacketDirection {
+                    ::minerust::network::PacketDirection::$direction
                 }
 
-                pub fn state() -> minerust_network::PacketState {
-                    minerust_network::PacketState::$state
+                pub fn state() -> ::minerust::network::PacketState {
+                    ::minerust::network::PacketState::$state
                 }
 
                 pub fn id() -> i32 {
-                    $id
+                    $packet_id
                 }
             }
+
+            impl ::minerust::network::Packet for $packet_name {
+                fn state() -> ::minerust::network::PacketState {
+                    ::minerust::network::PacketState::$state
+                }
+
+                fn id() -> i32 {
+                    $packet_id
+                }
+            }
+            )*
             )*
         }
     }
@@ -0,0 +1,174 @@
+use minerust::network::{
+    buffer::Buffer,
+    connection::{Readable, VarI32, Writable},
+    decode::{DecodeIssue, DecodeMode, DecodeReport},
+    ByteOrder, Error, FieldDescriptor, PacketDirection, PacketState, ProtocolVersion
+};
+
+#[allow(dead_code)]
+mod v1_20_1 {
+    use minerust::network::connection::VarI32;
+
+    crate::protocol!(
+        V1_20_1,
+        "1.20.1",
+        763,
+        Handshaking Serverbound {
+            Handshake(0x00) { protocol_version: VarI32, server_address: String, server_port: u16, next_state: VarI32 }
+        },
+        Login Clientbound {
+            Disconnect(0x00) { reason: String },
+            EncryptionRequest(0x01) {},
+            LoginSuccess(0x02) {},
+            SetCompression(0x03) { threshold: VarI32 }
+        }
+    );
+}
+
+#[allow(dead_code)]
+mod v1_20_2 {
+    crate::protocol!(
+        V1_20_2,
+        "1.20.2",
+        764,
+        Configuration Clientbound {
+            FinishConfiguration(0x02) {},
+            KeepAlive(0x03) { #[varlong] id: i64, #[varint] count: i32, raw: i32 }
+        },
+        Configuration Serverbound {
+            AcknowledgeFinishConfiguration(0x02) {}
+        }
+    );
+}
+
+use v1_20_1::*;
+
+#[test]
+#[allow(deprecated)]
+fn test_protocol_version() {
+    assert_eq!(V1_20_1::id(), 763);
+    assert_eq!(V1_20_1::literal(), "1.20.1");
+    assert_eq!(V1_20_1::packet_ids(), vec![0x00, 0x00, 0x01, 0x02, 0x03]);
+}
+
+#[test]
+fn test_packet_id_enum() {
+    assert_eq!(LoginClientbound::from_id(0x01), Some(LoginClientbound::EncryptionRequest));
+    assert_eq!(LoginClientbound::from_id(0x03).map(|packet| packet.id()), Some(0x03));
+    assert_eq!(LoginClientbound::from_id(0x04), None);
+    assert_eq!(HandshakingServerbound::from_id(0x00), Some(HandshakingServerbound::Handshake));
+    assert_eq!(LoginClientbound::state(), PacketState::Login);
+    assert_eq!(LoginClientbound::direction(), PacketDirection::Clientbound);
+}
+
+#[test]
+fn test_packet_write() {
+    assert_eq!(SetCompression::id(), 0x03);
+    let buffer = SetCompression::new(VarI32::new(256)).write(Buffer::empty(true, Some(ByteOrder::BigEndian))).unwrap();
+    assert_eq!(buffer.to_bytes(), vec![0x03, 0x80, 0x02]);
+}
+
+#[test]
+fn test_configuration_state() {
+    assert_eq!(v1_20_2::FinishConfiguration::state(), PacketState::Configuration);
+    assert_eq!(v1_20_2::ConfigurationServerbound::state(), PacketState::Configuration);
+    assert_eq!(v1_20_2::ConfigurationServerbound::direction(), PacketDirection::Serverbound);
+    assert_eq!(PacketState::Configuration.to_string(), "Configuration");
+}
+
+#[test]
+fn test_varint_field_sugar() {
+    let buffer = v1_20_2::KeepAlive::new(300, -1, 1).write(Buffer::empty(true, Some(ByteOrder::BigEndian))).unwrap();
+    assert_eq!(buffer.to_bytes(), vec![0x03, 0xAC, 0x02, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F, 0x00, 0x00, 0x00, 0x01]);
+}
+
+#[test]
+fn test_packet_descriptors() {
+    let packets = V1_20_1::packets();
+    assert_eq!(packets.len(), 5);
+    assert_eq!(packets.iter().map(|packet| packet.id).collect::<Vec<_>>(), vec![0x00, 0x00, 0x01, 0x02, 0x03]);
+
+    let handshake = &packets[0];
+    assert_eq!(handshake.name, "Handshake");
+    assert_eq!(handshake.state, PacketState::Handshaking);
+    assert_eq!(handshake.direction, PacketDirection::Serverbound);
+    assert_eq!(
+        handshake.fields,
+        &[
+            FieldDescriptor {
+                name: "protocol_version",
+                type_name: "VarI32"
+            },
+            FieldDescriptor {
+                name: "server_address",
+                type_name: "String"
+            },
+            FieldDescriptor {
+                name: "server_port",
+                type_name: "u16"
+            },
+            FieldDescriptor {
+                name: "next_state",
+                type_name: "VarI32"
+            }
+        ]
+    );
+    assert!(packets[2].fields.is_empty());
+}
+
+#[test]
+fn test_packet_by_id() {
+    let packet = V1_20_1::packet_by_id(PacketState::Login, PacketDirection::Clientbound, 0x03).unwrap();
+    assert_eq!(packet.name, "SetCompression");
+    assert_eq!(
+        packet.fields,
+        &[FieldDescriptor {
+            name: "threshold",
+            type_name: "VarI32"
+        }]
+    );
+    assert_eq!(
+        V1_20_1::packet_by_id(PacketState::Handshaking, PacketDirection::Serverbound, 0x00).map(|packet| packet.name),
+        Some("Handshake")
+    );
+
+    assert_eq!(V1_20_1::packet_by_id(PacketState::Login, PacketDirection::Clientbound, 0x04), None);
+    assert_eq!(V1_20_1::packet_by_id(PacketState::Login, PacketDirection::Serverbound, 0x00), None);
+    assert_eq!(V1_20_1::packet_by_id(PacketState::Play, PacketDirection::Clientbound, 0x00), None);
+}
+
+#[test]
+fn test_packet_read_padded() {
+    // A Set Compression with two bytes a modded server appended
+    let padded = || Buffer::new(vec![0x03, 0x80, 0x02, 0xCA, 0xFE], false, Some(ByteOrder::BigEndian));
+    assert!(matches!(SetCompression::read(&mut padded()), Err(Error::Decode { offset: 3, .. })));
+
+    let report = DecodeReport::new();
+    let mut buffer = padded();
+    let packet = SetCompression::read_with(&mut buffer, DecodeMode::Lenient, &report).unwrap();
+    assert_eq!(packet.threshold, 256);
+    assert_eq!(buffer.remaining(), 0);
+    assert_eq!(report.count(PacketState::Login, 0x03, DecodeIssue::TrailingBytes), 1);
+
+    let mut buffer = Buffer::new(vec![0x03, 0xAC, 0x02, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F, 0x00, 0x00, 0x00, 0x01], false, Some(ByteOrder::BigEndian));
+    let keep_alive = v1_20_2::KeepAlive::read_with(&mut buffer, DecodeMode::Strict, &report).unwrap();
+    assert_eq!((keep_alive.id, keep_alive.count, keep_alive.raw), (300, -1, 1));
+    assert!(matches!(
+        SetCompression::read(&mut Buffer::new(vec![0x02], false, Some(ByteOrder::BigEndian))),
+        Err(Error::IllegalPacket(0x02, _))
+    ));
+}
+
+// minerust is a dev-dependency with only the network feature
+#[test]
+fn test_codec_standalone() {
+    let handshake = Handshake::new(VarI32::new(763), "localhost".to_string(), 25565, VarI32::new(2));
+    let mut buffer = handshake.write(Buffer::empty(true, Some(ByteOrder::BigEndian))).unwrap();
+    buffer.set_position(0);
+
+    let read = Handshake::read(&mut buffer).unwrap();
+    assert_eq!(read.protocol_version, 763);
+    assert_eq!((read.server_address.as_str(), read.server_port), ("localhost", 25565));
+    assert_eq!(read.next_state, 2);
+    assert_eq!(buffer.remaining(), 0);
+}
@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    auth::{
+        microsoft::{MicrosoftAuthenticator, MinecraftEdition, XSTSError},
+        store::{StoredSession, TokenStore}
+    },
+    web::Error
+};
+
+fn unknown_account(name: &str) -> Error {
+    Error::new(format!("Unable to use account => Unknown account {:?}", name), 31)
+}
+
+pub struct AccountManager<S: TokenStore> {
+    store: S,
+    accounts: BTreeMap<String, StoredSession>,
+    active: Option<String>
+}
+
+impl<S: TokenStore> AccountManager<S> {
+    pub fn new(store: S) -> Result<Self, Error> {
+        let mut accounts = BTreeMap::new();
+        for name in store.accounts()? {
+            if let Some(session) = store.load(&name)? {
+                accounts.insert(name, session);
+            }
+        }
+
+        Ok(Self { store, accounts, active: None })
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    pub fn accounts(&self) -> impl Iterator<Item = &str> {
+        self.accounts.keys().map(String::as_str)
+    }
+
+    pub fn account(&self, name: &str) -> Option<&StoredSession> {
+        self.accounts.get(name)
+    }
+
+    pub fn add_account(&mut self, name: impl Into<String>, session: StoredSession) -> Result<(), Error> {
+        let name = name.into();
+        self.store.save(&name, &session)?;
+        self.accounts.insert(name.clone(), session);
+        self.active.get_or_insert(name);
+        Ok(())
+    }
+
+    pub fn remove_account(&mut self, name: &str) -> Result<Option<StoredSession>, Error> {
+        self.store.delete(name)?;
+        if self.active.as_deref() == Some(name) {
+            self.active = None;
+        }
+        Ok(self.accounts.remove(name))
+    }
+
+    pub fn switch_to(&mut self, name: &str) -> Result<&StoredSession, Error> {
+        let session = self.accounts.get(name).ok_or_else(|| unknown_account(name))?;
+        self.active = Some(name.to_string());
+        Ok(session)
+    }
+
+    pub fn active(&self) -> Option<(&str, &StoredSession)> {
+        let name = self.active.as_deref()?;
+        Some((name, self.accounts.get(name)?))
+    }
+
+    pub async fn refresh(&mut self, name: &str, client_id: &str, port: u16) -> Result<&StoredSession, XSTSError> {
+        let refresh_token = self.accounts.get(name).ok_or_else(|| unknown_account(name))?.refresh_token.clone();
+        let mut authenticator = MicrosoftAuthenticator::new(client_id, port).with_refresh_token(refresh_token);
+
+        let access_token = authenticator.refresh_access_token().await?;
+        let auth_token = authenticator.authenticate(access_token).await?;
+        let xsts_token = authenticator.request_xsts_token(auth_token, MinecraftEdition::Java).await?;
+        let session = StoredSession {
+            session: MicrosoftAuthenticator::authenticate_minecraft(xsts_token).await?,
+            refresh_token: authenticator.refresh_token().map(str::to_string)
+        };
+
+        self.store.save(name, &session)?;
+        self.accounts.insert(name.to_string(), session);
+        Ok(&self.accounts[name])
+    }
+}
@@ -1,10 +1,17 @@
 use serde::Deserialize;
 
+use crate::{
+    auth::microsoft::{AuthError, AuthStep},
+    web::Error
+};
+
 #[derive(Deserialize)]
 pub struct RawAccessToken {
     pub access_token: String,
-    pub expires_in: u16,
-    pub token_type: String
+    pub expires_in: u64,
+    pub token_type: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>
 }
 
 #[derive(Deserialize)]
@@ -12,6 +19,72 @@ pub struct RawSession {
     pub username: String,
     pub roles: Vec<String>,
     pub token_type: String,
-    pub expires_in: u32,
+    pub expires_in: u64,
     pub access_token: String
 }
+
+#[derive(Deserialize)]
+pub struct RawUserHash {
+    #[serde(default)]
+    pub uhs: Option<String>
+}
+
+#[derive(Deserialize)]
+pub struct RawDisplayClaims {
+    #[serde(default)]
+    pub xui: Vec<RawUserHash>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RawXboxToken {
+    pub token: String,
+    #[serde(default)]
+    pub display_claims: Option<RawDisplayClaims>
+}
+
+impl RawXboxToken {
+    pub fn user_hash(&self, stage: AuthStep) -> Result<String, Error> {
+        let user_hash = self.display_claims.as_ref().and_then(|claims| claims.xui.first()).and_then(|claim| claim.uhs.as_deref());
+        match user_hash {
+            Some(user_hash) if !user_hash.is_empty() => Ok(user_hash.to_string()),
+            _ => {
+                let error = AuthError::MissingClaims { stage };
+                Err(Error::new(error.to_string(), 0).with_source(error))
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RawXstsError {
+    #[serde(rename = "Identity", default)]
+    pub identity: String,
+    #[serde(rename = "XErr")]
+    pub error_code: u64,
+    #[serde(rename = "Message", default)]
+    pub message: String,
+    #[serde(rename = "Redirect", default)]
+    pub redirect: String
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum RawXstsResponse {
+    Token(RawXboxToken),
+    Error(RawXstsError)
+}
+
+// Minecraft services answer with error and errorType, Yggdrasil with error and cause
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawServiceError {
+    #[serde(default)]
+    pub error: String,
+    #[serde(default)]
+    pub error_type: String,
+    #[serde(default)]
+    pub error_message: String,
+    #[serde(default)]
+    pub cause: String
+}
@@ -1,28 +1,30 @@
 use std::{
+    collections::HashSet,
     fmt::{Display, Formatter},
+    net::SocketAddr,
     str::FromStr,
-    sync::mpsc,
+    sync::{Arc, Mutex},
     time::Duration
 };
 
-use rand::{distributions::Alphanumeric, Rng};
-use reqwest::header::HeaderName;
-use serde::Deserialize;
+use rand::{distributions::Alphanumeric, thread_rng, Rng, RngCore};
+use reqwest::header::{HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use tokio::spawn;
+use tokio::{spawn, sync::oneshot};
 use uuid::Uuid;
-use warp::{http::HeaderValue, Filter};
+use warp::{http::StatusCode, Filter};
 use webbrowser::open;
 
 use crate::{
     auth::{
-        microsoft::internals::{RawAccessToken, RawSession},
+        microsoft::internals::{RawAccessToken, RawServiceError, RawSession, RawXboxToken, RawXstsResponse},
         Session
     },
-    web::{Error, Requester}
+    web::{Error, ErrorKind, Requester}
 };
 
-mod internals;
+pub(crate) mod internals;
 
 #[derive(Debug, Deserialize)]
 struct Query {
@@ -30,8 +32,49 @@ struct Query {
     state: String
 }
 
-fn random_string() -> String {
-    rand::thread_rng().sample_iter(Alphanumeric).take(15).map(char::from).collect()
+fn random_string(rng: &mut impl RngCore) -> String {
+    rng.sample_iter(Alphanumeric).take(15).map(char::from).collect()
+}
+
+type IssuedStates = Arc<Mutex<HashSet<String>>>;
+
+// A state is only accepted once, which protects against CSRF and replayed redirects
+fn verify_state(issued_states: &IssuedStates, state: &str) -> Result<(), Error> {
+    let mut issued_states = issued_states.lock().expect("Issued states are poisoned");
+    if !issued_states.remove(state) {
+        return Err(Error::new("Unable to request the refresh token => Invalid state".to_string(), 2))
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthEndpoints {
+    pub live: String,
+    pub user_auth: String,
+    pub xsts_auth: String,
+    pub minecraft_services: String
+}
+
+impl Default for AuthEndpoints {
+    fn default() -> Self {
+        Self {
+            live: "https://login.live.com".to_string(),
+            user_auth: "https://user.auth.xboxlive.com".to_string(),
+            xsts_auth: "https://xsts.auth.xboxlive.com".to_string(),
+            minecraft_services: "https://api.minecraftservices.com".to_string()
+        }
+    }
+}
+
+impl AuthEndpoints {
+    pub fn new(live: impl Into<String>, user_auth: impl Into<String>, xsts_auth: impl Into<String>, minecraft_services: impl Into<String>) -> Self {
+        Self {
+            live: live.into(),
+            user_auth: user_auth.into(),
+            xsts_auth: xsts_auth.into(),
+            minecraft_services: minecraft_services.into()
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -44,14 +87,71 @@ pub struct AccessToken {
 pub struct MicrosoftAuthenticator<'a> {
     pub client_id: &'a str,
     pub port: u16,
-    refresh_token: Option<String>
+    refresh_token: Option<String>,
+    endpoints: AuthEndpoints,
+    issued_states: IssuedStates,
+    oauth_server: Option<OAuthServer>
+}
+
+struct OAuthServer {
+    callback: Option<oneshot::Receiver<Query>>,
+    shutdown: Option<oneshot::Sender<()>>
+}
+
+impl OAuthServer {
+    fn start(port: u16, issued_states: IssuedStates) -> Result<Self, Error> {
+        let (sender, callback) = oneshot::channel();
+        let sender = Arc::new(Mutex::new(Some(sender)));
+        let route = warp::get().and(warp::filters::query::query()).map(move |query: Query| {
+            let mut sender = sender.lock().expect("OAuth callback sender is poisoned");
+            if sender.is_none() {
+                return warp::reply::with_status("Successfully received query".to_string(), StatusCode::OK)
+            }
+
+            if let Err(error) = verify_state(&issued_states, &query.state) {
+                return warp::reply::with_status(error.message().to_string(), StatusCode::BAD_REQUEST)
+            }
+            let _ = sender.take().unwrap().send(query);
+            warp::reply::with_status("Successfully received query".to_string(), StatusCode::OK)
+        });
+
+        let (shutdown, signal) = oneshot::channel::<()>();
+        let (_, server) = warp::serve(route)
+            .try_bind_with_graceful_shutdown(SocketAddr::from(([127, 0, 0, 1], port)), async {
+                let _ = signal.await;
+            })
+            .map_err(|error| Error::new(format!("Unable to start OAuth server => {}", error), 14))?;
+
+        spawn(server);
+        Ok(Self {
+            callback: Some(callback),
+            shutdown: Some(shutdown)
+        })
+    }
+
+    async fn receive(&mut self) -> Result<Query, Error> {
+        let callback = self
+            .callback
+            .take()
+            .ok_or_else(|| Error::new("Unable to receive OAuth callback => Callback was already received", 14))?;
+        callback.await.map_err(|_| Error::new("Unable to receive OAuth callback => Server has stopped", 14))
+    }
+}
+
+impl Drop for OAuthServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
 }
 
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub struct AuthToken {
     pub token: String,
     pub user_hash: String,
-    pub token_type: TokenType
+    pub token_type: TokenType,
+    pub edition: Option<MinecraftEdition>
 }
 
 #[derive(Eq, PartialEq, Clone, Debug)]
@@ -60,12 +160,118 @@ pub enum TokenType {
     User
 }
 
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Ownership {
+    Owned,
+    GamePass,
+    Demo,
+    None
+}
+
+impl Ownership {
+    pub fn from_entitlements(json: &Value) -> Result<Self, Error> {
+        let items = match &json["items"] {
+            Value::Array(items) => items,
+            _ => return Err(Error::new("Items array isn't a array".to_string(), 13))
+        };
+
+        if items.is_empty() {
+            return Ok(Ownership::None)
+        }
+
+        // Newer responses mark Game Pass entitlements with a source, older ones only add a Game Pass product
+        let games = items
+            .iter()
+            .filter(|item| matches!(item["name"].as_str(), Some("product_minecraft" | "game_minecraft")))
+            .collect::<Vec<_>>();
+        if games.iter().any(|item| item["source"].as_str().is_some_and(|source| source != "GAMEPASS")) {
+            return Ok(Ownership::Owned)
+        }
+
+        let game_pass = items
+            .iter()
+            .any(|item| item["source"].as_str() == Some("GAMEPASS") || item["name"].as_str().is_some_and(|name| name.starts_with("product_game_pass")));
+        if game_pass {
+            return Ok(Ownership::GamePass)
+        }
+
+        if !games.is_empty() {
+            return Ok(Ownership::Owned)
+        }
+        Ok(Ownership::Demo)
+    }
+
+    pub fn can_play(&self) -> bool {
+        matches!(self, Ownership::Owned | Ownership::GamePass)
+    }
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Default, Debug)]
 pub enum MinecraftEdition {
+    #[default]
     Java,
     Bedrock
 }
 
+impl Session {
+    pub fn require_java(&self, action: &str) -> Result<(), Error> {
+        match self.edition {
+            MinecraftEdition::Java => Ok(()),
+            MinecraftEdition::Bedrock => Err(Error::new(format!("{} => The session was authenticated for Bedrock, but the endpoint is Java only", action), 32))
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum AuthStep {
+    McLogin,
+    MsaToken,
+    XboxUserAuth,
+    XstsAuthorize,
+    McAuthenticate
+}
+
+impl Display for AuthStep {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthStep::McLogin => write!(f, "mc_login"),
+            AuthStep::MsaToken => write!(f, "msa_token"),
+            AuthStep::XboxUserAuth => write!(f, "xbox_user_auth"),
+            AuthStep::XstsAuthorize => write!(f, "xsts_authorize"),
+            AuthStep::McAuthenticate => write!(f, "mc_authenticate")
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum AuthError {
+    // Accounts without a gamertag yet get a token without the user hash
+    MissingClaims { stage: AuthStep }
+}
+
+impl Display for AuthError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthError::MissingClaims { stage } => write!(f, "The {} response has no user hash claim", stage)
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+impl AuthError {
+    pub fn from_error(error: &Error) -> Option<Self> {
+        let mut error = Some(error as &(dyn std::error::Error + 'static));
+        while let Some(current) = error {
+            if let Some(auth_error) = current.downcast_ref::<Self>() {
+                return Some(*auth_error)
+            }
+            error = current.source();
+        }
+        None
+    }
+}
+
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub enum XSTSErrorType {
     NoXboxAccount,
@@ -97,6 +303,80 @@ impl XSTSErrorType {
     }
 }
 
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum AccountErrorType {
+    AccountMigrated,
+    InvalidAppRegistration,
+    Forbidden,
+    NotFound,
+    TooManyRequests
+}
+
+impl Display for AccountErrorType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AccountErrorType::AccountMigrated => write!(f, "The Mojang account was migrated, log in with the Microsoft account instead"),
+            AccountErrorType::InvalidAppRegistration => write!(f, "The client id isn't approved for the Minecraft services, see https://aka.ms/AppRegInfo"),
+            AccountErrorType::Forbidden => write!(f, "The account isn't allowed to log in"),
+            AccountErrorType::NotFound => write!(f, "The account wasn't found"),
+            AccountErrorType::TooManyRequests => write!(f, "Too many login attempts, try again later")
+        }
+    }
+}
+
+impl AccountErrorType {
+    // Migration and app registration errors come back as a plain FORBIDDEN
+    pub fn from_response(response: &str) -> Option<Self> {
+        let error = serde_json::from_str::<RawServiceError>(response).ok()?;
+        if error.cause == "UserMigratedException" || error.error_message.to_lowercase().contains("migrat") {
+            return Some(Self::AccountMigrated)
+        }
+        if error.error_message.starts_with("Invalid app registration") {
+            return Some(Self::InvalidAppRegistration)
+        }
+
+        let error_type = if error.error_type.is_empty() { &error.error } else { &error.error_type };
+        match error_type.as_str() {
+            "FORBIDDEN" | "ForbiddenOperationException" => Some(Self::Forbidden),
+            "NOT_FOUND" | "NotFoundException" => Some(Self::NotFound),
+            "TOO_MANY_REQUESTS" => Some(Self::TooManyRequests),
+            _ => None
+        }
+    }
+
+    pub fn from_error(error: &Error) -> Option<Self> {
+        Self::from_code(error.code())
+    }
+
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            33 => Some(Self::AccountMigrated),
+            34 => Some(Self::InvalidAppRegistration),
+            35 => Some(Self::Forbidden),
+            36 => Some(Self::NotFound),
+            37 => Some(Self::TooManyRequests),
+            _ => None
+        }
+    }
+
+    pub fn code(&self) -> u8 {
+        match self {
+            Self::AccountMigrated => 33,
+            Self::InvalidAppRegistration => 34,
+            Self::Forbidden => 35,
+            Self::NotFound => 36,
+            Self::TooManyRequests => 37
+        }
+    }
+
+    fn map(error: Error, context: &str, code: u8) -> Error {
+        match error.response().and_then(Self::from_response) {
+            Some(error_type) => Error::new(format!("{} => {}", context, error_type), error_type.code()).with_kind(error.kind()).with_source(error),
+            None => error.context(context, code)
+        }
+    }
+}
+
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub struct XSTSTokenError {
     pub identity: u16,
@@ -116,15 +396,16 @@ impl Display for XSTSTokenError {
 pub struct XSTSError {
     token_error: Option<XSTSTokenError>,
     error_text: Option<String>,
-    pub error_code: Option<u8>
+    pub error_code: Option<u8>,
+    source: Option<Error>
 }
 
 impl Display for XSTSError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        if self.token_error.clone().is_none() {
-            write!(f, "{}", self.token_error.clone().unwrap())
-        } else {
-            write!(f, "{}", self.error_text.clone().unwrap())
+        match (&self.token_error, &self.error_text) {
+            (Some(token_error), _) => write!(f, "{}", token_error),
+            (None, Some(error_text)) => write!(f, "{}", error_text),
+            (None, None) => write!(f, "Unknown XSTS error")
         }
     }
 }
@@ -134,7 +415,8 @@ impl XSTSError {
         Self {
             token_error: Some(token_error),
             error_code: None,
-            error_text: None
+            error_text: None,
+            source: None
         }
     }
 
@@ -142,10 +424,12 @@ impl XSTSError {
         Self {
             token_error: None,
             error_code: Some(code),
-            error_text: Some(text)
+            error_text: Some(text),
+            source: None
         }
     }
 
+    #[allow(clippy::result_unit_err)]
     pub fn to_error(&self) -> Result<Error, ()> {
         if self.token_error.is_some() {
             return Err(())
@@ -155,66 +439,184 @@ impl XSTSError {
     }
 }
 
-impl std::error::Error for XSTSError {}
+impl std::error::Error for XSTSError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_ref().map(|source| source as &(dyn std::error::Error + 'static))
+    }
+}
+
+impl From<Error> for XSTSError {
+    fn from(error: Error) -> Self {
+        let normal = Self::normal(error.message().to_string(), error.code());
+        Self { source: Some(error), ..normal }
+    }
+}
+
+impl From<XSTSError> for Error {
+    fn from(error: XSTSError) -> Self {
+        match error.source {
+            Some(source) => source,
+            None => error.to_error().unwrap_or_else(|_| Error::new(format!("Unable to request the XSTS token => {}", error), 39))
+        }
+    }
+}
 
 impl<'a> MicrosoftAuthenticator<'a> {
     pub fn new(client_id: &'a str, port: u16) -> Self {
         Self {
             client_id,
             port,
-            refresh_token: None
+            refresh_token: None,
+            endpoints: AuthEndpoints::default(),
+            issued_states: Arc::new(Mutex::new(HashSet::new())),
+            oauth_server: None
         }
     }
 
-    pub async fn request_refresh_token(&mut self) -> Result<String, Error> {
-        let state = random_string();
+    pub fn with_refresh_token(self, refresh_token: Option<String>) -> Self {
+        Self { refresh_token, ..self }
+    }
+
+    pub fn refresh_token(&self) -> Option<&str> {
+        self.refresh_token.as_deref()
+    }
 
-        open(&format!(
-            "https://login.live.com/oauth20_authorize.srf?client_id={}&response_type=code&redirect_uri=http://127.0.0.1:{}\
+    pub fn with_endpoints(self, endpoints: AuthEndpoints) -> Self {
+        Self { endpoints, ..self }
+    }
+
+    pub fn endpoints(&self) -> &AuthEndpoints {
+        &self.endpoints
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "auth.login", skip_all, fields(edition = ?edition)))]
+    pub async fn login(&mut self, edition: MinecraftEdition) -> Result<Session, Error> {
+        self.run_login(edition).await.map_err(|error| error.within(AuthStep::McLogin))
+    }
+
+    async fn run_login(&mut self, edition: MinecraftEdition) -> Result<Session, Error> {
+        let access_token = self.request_access_token().await.map_err(|error| error.within(AuthStep::MsaToken))?;
+        let auth_token = self.authenticate(access_token).await.map_err(|error| error.within(AuthStep::XboxUserAuth))?;
+        let xsts_token = self
+            .request_xsts_token(auth_token, edition)
+            .await
+            .map_err(|error| Error::from(error).within(AuthStep::XstsAuthorize))?;
+        Self::authenticate_minecraft_with_endpoints(&self.endpoints, xsts_token)
+            .await
+            .map_err(|error| error.within(AuthStep::McAuthenticate))
+    }
+
+    pub fn authorize_url(&self) -> (String, String) {
+        self.authorize_url_with(&mut thread_rng())
+    }
+
+    pub fn authorize_url_with(&self, rng: &mut impl RngCore) -> (String, String) {
+        let state = random_string(rng);
+        self.issued_states.lock().expect("Issued states are poisoned").insert(state.clone());
+
+        let url = format!(
+            "{}/oauth20_authorize.srf?client_id={}&response_type=code&redirect_uri=http://127.0.0.1:{}\
         &scope=XboxLive.signin%20offline_access&state={}&prompt=select_account",
-            self.client_id, self.port, state
-        ))
-        .map_err(|error| Error::new(format!("Unable to prompt refresh token login => {}", error), 1))?;
+            self.endpoints.live, self.client_id, self.port, state
+        );
+        (url, state)
+    }
 
-        let query = Self::start_oauth_server(self.port).await;
-        if query.state != state {
-            return Err(Error::new(
-                format!("Unable to request the refresh token => Illegal response code {} ({} != {})", query.state, query.state, state),
-                2
-            ))
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "auth.exchange_code", skip_all))]
+    pub async fn exchange_code(&mut self, code: &str, state: &str) -> Result<AccessToken, Error> {
+        verify_state(&self.issued_states, state)?;
+        self.redeem_code(code).await
+    }
+
+    pub fn start_callback_server(&mut self) -> Result<(), Error> {
+        if self.oauth_server.is_none() {
+            self.oauth_server = Some(OAuthServer::start(self.port, self.issued_states.clone())?);
         }
+        Ok(())
+    }
+
+    pub fn stop_callback_server(&mut self) {
+        self.oauth_server = None;
+    }
 
-        self.refresh_token = Some(query.code);
-        Ok(self.refresh_token.clone().unwrap())
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "auth.request_authorization_code", skip_all))]
+    pub async fn request_authorization_code(&mut self) -> Result<String, Error> {
+        self.start_callback_server()?;
+        let (url, _) = self.authorize_url();
+        open(&url).map_err(|error| Error::new(format!("Unable to prompt authorization code login => {}", error), 1))?;
+        self.wait_for_callback().await
     }
 
+    pub async fn wait_for_callback(&mut self) -> Result<String, Error> {
+        let server = self
+            .oauth_server
+            .as_mut()
+            .ok_or_else(|| Error::new("Unable to receive OAuth callback => Server isn't running", 14))?;
+        let query = server.receive().await;
+        self.stop_callback_server();
+
+        Ok(query?.code)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "auth.request_access_token", skip_all))]
     pub async fn request_access_token(&mut self) -> Result<AccessToken, Error> {
-        if self.refresh_token.is_none() {
-            self.request_refresh_token().await?;
+        if self.refresh_token.is_some() {
+            return self.refresh_access_token().await
         }
 
-        let query = json!({
+        let code = self.request_authorization_code().await?;
+        self.redeem_code(&code).await
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "auth.refresh_access_token", skip_all))]
+    pub async fn refresh_access_token(&mut self) -> Result<AccessToken, Error> {
+        let refresh_token = self
+            .refresh_token
+            .clone()
+            .ok_or_else(|| Error::new("Unable to refresh access token => No refresh token stored", 5))?;
+        self.redeem(json!({
             "client_id": self.client_id,
-            "code": self.refresh_token,
+            "refresh_token": refresh_token,
+            "grant_type": "refresh_token",
+            "redirect_uri": format!("http://127.0.0.1:{}", self.port)
+        }))
+        .await
+    }
+
+    async fn redeem_code(&mut self, code: &str) -> Result<AccessToken, Error> {
+        self.redeem(json!({
+            "client_id": self.client_id,
+            "code": code,
             "grant_type": "authorization_code",
             "redirect_uri": format!("http://127.0.0.1:{}", self.port)
-        });
+        }))
+        .await
+    }
 
-        let token = Requester::post_str("https://login.live.com/oauth20_token.srf")
+    // Microsoft rotates the refresh token on every redemption, so the old one is only kept if no new one was issued
+    async fn redeem(&mut self, query: Value) -> Result<AccessToken, Error> {
+        let token = Requester::post(format!("{}/oauth20_token.srf", self.endpoints.live))
             .form(&query)
-            .execute()
+            .execute_json::<RawAccessToken>()
             .await
-            .map_err(|error| Error::new(format!("Unable to get access token => {}", error), 3))?;
-
-        let token: RawAccessToken = serde_json::from_str(&token).map_err(|error| Error::new(format!("Unable to parse access token => {}", error), 4))?;
-
+            .map_err(|error| {
+                match error.kind() {
+                    ErrorKind::Decode => error.context("Unable to parse access token", 4),
+                    _ => error.context("Unable to get access token", 3)
+                }
+            })?;
+
+        if token.refresh_token.is_some() {
+            self.refresh_token = token.refresh_token;
+        }
         Ok(AccessToken {
             access_token: token.access_token,
             token_type: token.token_type,
-            expires_in: Duration::from_secs(token.expires_in as u64)
+            expires_in: Duration::from_secs(token.expires_in)
         })
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "auth.authenticate", skip_all))]
     pub async fn authenticate(&self, access_token: AccessToken) -> Result<AuthToken, Error> {
         let json = json!({
             "Properties": {
@@ -226,18 +628,22 @@ impl<'a> MicrosoftAuthenticator<'a> {
             "TokenType": "JWT"
         });
 
-        let requester = Requester::post_str("https://user.auth.xboxlive.com/user/authenticate")
+        let token = Requester::post(format!("{}/user/authenticate", self.endpoints.user_auth))
             .json(&json)
-            .execute()
+            .execute_json::<RawXboxToken>()
             .await
-            .map_err(|error| Error::new(format!("Unable to authenticate => {}", error), 5))?;
-
-        let json: Value = serde_json::from_str(&requester).map_err(|error| Error::new(format!("Unable to parse auth response => {}", error), 6))?;
+            .map_err(|error| {
+                match error.kind() {
+                    ErrorKind::Decode => error.context("Unable to parse auth response", 6),
+                    _ => AccountErrorType::map(error, "Unable to authenticate", 5)
+                }
+            })?;
 
         Ok(AuthToken {
-            token: json["Token"].to_string().replace("\"", ""),
-            user_hash: json["DisplayClaims"]["xui"][0]["uhs"].to_string().replace("\"", ""),
-            token_type: TokenType::User
+            user_hash: token.user_hash(AuthStep::XboxUserAuth).map_err(|error| error.context("Unable to parse auth response", 6))?,
+            token: token.token,
+            token_type: TokenType::User,
+            edition: None
         })
     }
 
@@ -252,6 +658,7 @@ impl<'a> MicrosoftAuthenticator<'a> {
     //    "XErr": 2148916238,
     //    "Message": "",
     //    "Redirect: "https://start.ui.xboxlive.com/AddChildToFamily"
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "auth.request_xsts_token", skip_all, fields(edition = ?edition)))]
     pub async fn request_xsts_token(&self, auth_token: AuthToken, edition: MinecraftEdition) -> Result<AuthToken, XSTSError> {
         let json = json!({
             "Properties": {
@@ -268,78 +675,91 @@ impl<'a> MicrosoftAuthenticator<'a> {
             "TokenType": "JWT"
         });
 
-        let requester = Requester::post_str("https://xsts.auth.xboxlive.com/xsts/authorize")
+        let (_, response) = Requester::post(format!("{}/xsts/authorize", self.endpoints.xsts_auth))
             .json(&json)
-            .execute()
+            .execute_with_status()
             .await
-            .map_err(|error| XSTSError::normal(format!("Unable to authenticate => {}", error), 7))?;
-
-        let json: Value = serde_json::from_str(&requester).map_err(|error| XSTSError::normal(format!("Unable to parse auth response => {}", error), 8))?;
-
-        if json.get("Token").is_none() {
-            return Err(XSTSError::token_error(XSTSTokenError {
-                error_code: json["XErr"].as_u64().unwrap(),
-                error_type: XSTSErrorType::from_u64(json["XErr"].as_u64().unwrap()),
-                redirect: json["Redirect"].to_string(),
-                identity: str::parse::<u16>(&json["Identity"].to_string()).unwrap(),
-                message: json["Message"].to_string()
-            }))
+            .map_err(|error| error.context("Unable to authenticate", 7))?;
+
+        // Refused tokens still carry a JSON body with the reason
+        match serde_json::from_str::<RawXstsResponse>(&response).map_err(|error| Error::from(error).context("Unable to parse auth response", 8))? {
+            RawXstsResponse::Token(token) => {
+                Ok(AuthToken {
+                    user_hash: token.user_hash(AuthStep::XstsAuthorize).map_err(|error| error.context("Unable to parse auth response", 8))?,
+                    token: token.token,
+                    token_type: TokenType::XSLS,
+                    edition: Some(edition)
+                })
+            }
+            RawXstsResponse::Error(error) => {
+                Err(XSTSError::token_error(XSTSTokenError {
+                    identity: error.identity.parse().unwrap_or_default(),
+                    error_code: error.error_code,
+                    error_type: XSTSErrorType::from_u64(error.error_code),
+                    redirect: error.redirect,
+                    message: error.message
+                }))
+            }
         }
-
-        Ok(AuthToken {
-            token: json["Token"].to_string().replace("\"", ""),
-            user_hash: json["DisplayClaims"]["xui"][0]["uhs"].to_string().replace("\"", ""),
-            token_type: TokenType::XSLS
-        })
     }
 
     pub async fn authenticate_minecraft(auth_token: AuthToken) -> Result<Session, Error> {
+        Self::authenticate_minecraft_with_endpoints(&AuthEndpoints::default(), auth_token).await
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "auth.authenticate_minecraft", skip_all))]
+    pub async fn authenticate_minecraft_with_endpoints(endpoints: &AuthEndpoints, auth_token: AuthToken) -> Result<Session, Error> {
         if auth_token.token_type != TokenType::XSLS {
             return Err(Error::new("Unable to authenticate with Minecraft => The specified token isn't a XSLS token".to_string(), 7))
         }
+        if auth_token.edition == Some(MinecraftEdition::Bedrock) {
+            return Err(Error::new(
+                "Unable to authenticate with Minecraft => The XSTS token was issued for Bedrock, but the endpoint is Java only".to_string(),
+                32
+            ))
+        }
 
         let json = json!({ "identityToken": format!("XBL3.0 x={};{}", auth_token.user_hash, auth_token.token) });
 
-        let requester = Requester::post_str("https://api.minecraftservices.com/authentication/login_with_xbox")
+        let session = Requester::post(format!("{}/authentication/login_with_xbox", endpoints.minecraft_services))
             .json(&json)
-            .execute()
+            .execute_json::<RawSession>()
             .await
-            .map_err(|error| Error::new(format!("Unable to authenticate => {}", error), 9))?;
-
-        let session: RawSession = serde_json::from_str(&requester).map_err(|error| Error::new(format!("Unable to parse access token => {}", error), 10))?;
+            .map_err(|error| {
+                match error.kind() {
+                    ErrorKind::Decode => error.context("Unable to parse access token", 10),
+                    _ => AccountErrorType::map(error, "Unable to authenticate", 9)
+                }
+            })?;
 
         Ok(Session {
             token_type: crate::auth::TokenType::from_str(&session.token_type),
-            username: Uuid::from_str(&session.username).unwrap(),
-            expires_in: Duration::from_secs(session.expires_in as u64),
+            username: Uuid::from_str(&session.username).map_err(|error| Error::new(format!("Unable to parse access token => Invalid uuid {} ({})", session.username, error), 10))?,
+            expires_in: Duration::from_secs(session.expires_in),
             roles: session.roles,
-            access_token: session.access_token
+            access_token: session.access_token,
+            edition: MinecraftEdition::Java
         })
     }
 
     pub async fn has_minecraft(session: Session) -> Result<bool, Error> {
-        let requester = Requester::get_str("https://api.minecraftservices.com/entitlements/mcstore")
-            .header(HeaderName::from_str("Authorization"), HeaderValue::from_str(&format!("Bearer {}", session.access_token)))
-            .execute()
-            .await
-            .map_err(|error| Error::new(format!("Unable to authenticate => {}", error), 11))?;
-
-        let json: Value = serde_json::from_str(&requester).map_err(|error| Error::new(format!("Unable to parse auth response => {}", error), 12))?;
-
-        match &json["items"] {
-            Value::Array(values) => Ok(values.len() > 0),
-            _ => Err(Error::new("Items array isn't a array".to_string(), 13))
-        }
+        Ok(Self::ownership(&session).await?.can_play())
     }
 
-    async fn start_oauth_server(port: u16) -> Query {
-        let (sender, receiver) = mpsc::sync_channel(14);
-        let route = warp::get().and(warp::filters::query::query()).map(move |query: Query| {
-            sender.send(query).expect("Unable to send query through sender");
-            "Successfully received query"
-        });
-
-        spawn(warp::serve(route).run(([127, 0, 0, 1], port)));
-        receiver.recv().expect("Channel has hang up")
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "auth.ownership", skip_all))]
+    pub async fn ownership(session: &Session) -> Result<Ownership, Error> {
+        session.require_java("Unable to request the entitlements")?;
+        let json = Requester::get_str("https://api.minecraftservices.com/entitlements/mcstore")
+            .header(HeaderName::from_str("Authorization"), HeaderValue::from_str(&format!("Bearer {}", session.access_token)))
+            .execute_json::<Value>()
+            .await
+            .map_err(|error| {
+                match error.kind() {
+                    ErrorKind::Decode => error.context("Unable to parse auth response", 12),
+                    _ => error.context("Unable to authenticate", 11)
+                }
+            })?;
+
+        Ownership::from_entitlements(&json)
     }
 }
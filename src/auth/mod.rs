@@ -1,27 +1,36 @@
 use std::time::Duration;
 
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::auth::microsoft::MinecraftEdition;
+
 // TODO: Implement legacy authentication with Mojang
 // TODO: Create a facade for all authentications
 
+pub mod accounts;
 pub mod microsoft;
+pub mod store;
 
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
 pub struct Session {
     pub username: Uuid,
     pub roles: Vec<String>,
     pub access_token: String,
     pub token_type: TokenType,
-    pub expires_in: Duration
+    pub expires_in: Duration,
+    // Sessions stored before the edition was recorded are all Java sessions
+    #[serde(default)]
+    pub edition: MinecraftEdition
 }
 
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
 pub enum TokenType {
     Bearer
 }
 
 impl TokenType {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(str: &str) -> TokenType {
         match str {
             "Bearer" => TokenType::Bearer,
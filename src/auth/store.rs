@@ -0,0 +1,159 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf}
+};
+
+use aes::{
+    cipher::{AsyncStreamCipher, KeyIvInit},
+    Aes128
+};
+use hmac::{Hmac, Mac};
+use rand::{thread_rng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::{auth::Session, web::Error};
+
+const MAGIC: &[u8; 4] = b"MRTS";
+const VERSION: u8 = 2;
+const HEADER_LENGTH: usize = MAGIC.len() + 1 + 16;
+const TAG_LENGTH: usize = 32;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+pub struct StoredSession {
+    pub session: Session,
+    pub refresh_token: Option<String>
+}
+
+pub trait TokenStore {
+    fn save(&self, account: &str, session: &StoredSession) -> Result<(), Error>;
+    fn load(&self, account: &str) -> Result<Option<StoredSession>, Error>;
+    fn delete(&self, account: &str) -> Result<(), Error>;
+    fn accounts(&self) -> Result<Vec<String>, Error>;
+}
+
+// Magic, version and IV, followed by the AES-128-CFB8 encrypted JSON and an HMAC-SHA256 tag over everything before it.
+// The cipher and MAC keys are both derived from the store key.
+pub struct FileTokenStore {
+    directory: PathBuf,
+    key: [u8; 16]
+}
+
+impl FileTokenStore {
+    pub fn new(directory: impl Into<PathBuf>, key: [u8; 16]) -> Self {
+        Self { directory: directory.into(), key }
+    }
+
+    pub fn directory(&self) -> &PathBuf {
+        &self.directory
+    }
+
+    fn derive_key(&self, label: &[u8]) -> [u8; 32] {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&self.key).expect("HMAC accepts any key length");
+        mac.update(label);
+        mac.finalize().into_bytes().into()
+    }
+
+    fn cipher_key(&self) -> [u8; 16] {
+        self.derive_key(b"minerust token cipher")[..16].try_into().unwrap()
+    }
+
+    fn mac(&self) -> Hmac<Sha256> {
+        <Hmac<Sha256> as Mac>::new_from_slice(&self.derive_key(b"minerust token mac")).expect("HMAC accepts any key length")
+    }
+
+    fn path(&self, account: &str) -> Result<PathBuf, Error> {
+        if account.is_empty() || !account.chars().all(|character| character.is_ascii_alphanumeric() || character == '-' || character == '_') {
+            return Err(Error::new(format!("Unable to store token => Invalid account name {:?}", account), 23))
+        }
+        Ok(self.directory.join(format!("{}.token", account)))
+    }
+}
+
+impl TokenStore for FileTokenStore {
+    fn save(&self, account: &str, session: &StoredSession) -> Result<(), Error> {
+        let json = serde_json::to_vec(session).map_err(|error| Error::new(format!("Unable to serialize session => {}", error), 24))?;
+        let mut iv = [0; 16];
+        thread_rng().fill_bytes(&mut iv);
+
+        let mut payload = json;
+        cfb8::Encryptor::<Aes128>::new(&self.cipher_key().into(), &iv.into()).encrypt(&mut payload);
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&iv);
+        bytes.extend_from_slice(&payload);
+        let mut mac = self.mac();
+        mac.update(&bytes);
+        bytes.extend_from_slice(&mac.finalize().into_bytes());
+
+        fs::create_dir_all(&self.directory).map_err(|error| Error::new(format!("Unable to create token directory => {}", error), 25))?;
+        write_private(&self.path(account)?, &bytes).map_err(|error| Error::new(format!("Unable to write token => {}", error), 25))
+    }
+
+    fn load(&self, account: &str) -> Result<Option<StoredSession>, Error> {
+        let bytes = match fs::read(self.path(account)?) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(Error::new(format!("Unable to read token => {}", error), 25))
+        };
+
+        if bytes.len() < HEADER_LENGTH + TAG_LENGTH || &bytes[..MAGIC.len()] != MAGIC || bytes[MAGIC.len()] != VERSION {
+            return Err(Error::new("Unable to load token => Unsupported token file", 26))
+        }
+
+        let (authenticated, tag) = bytes.split_at(bytes.len() - TAG_LENGTH);
+        let mut mac = self.mac();
+        mac.update(authenticated);
+        if mac.verify_slice(tag).is_err() {
+            return Err(Error::new("Unable to load token => Wrong key or corrupted token file", 26))
+        }
+
+        let iv: [u8; 16] = authenticated[MAGIC.len() + 1..HEADER_LENGTH].try_into().unwrap();
+        let mut json = authenticated[HEADER_LENGTH..].to_vec();
+        cfb8::Decryptor::<Aes128>::new(&self.cipher_key().into(), &iv.into()).decrypt(&mut json);
+
+        Ok(Some(
+            serde_json::from_slice(&json).map_err(|error| Error::new(format!("Unable to parse session => {}", error), 24))?
+        ))
+    }
+
+    fn delete(&self, account: &str) -> Result<(), Error> {
+        match fs::remove_file(self.path(account)?) {
+            Err(error) if error.kind() != ErrorKind::NotFound => Err(Error::new(format!("Unable to delete token => {}", error), 25)),
+            _ => Ok(())
+        }
+    }
+
+    fn accounts(&self) -> Result<Vec<String>, Error> {
+        let entries = match fs::read_dir(&self.directory) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(Error::new(format!("Unable to list tokens => {}", error), 25))
+        };
+
+        let mut accounts = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(|error| Error::new(format!("Unable to list tokens => {}", error), 25))?.path();
+            if let Some(account) = path.file_name().and_then(|name| name.to_str()).and_then(|name| name.strip_suffix(".token")) {
+                accounts.push(account.to_string());
+            }
+        }
+        accounts.sort_unstable();
+        Ok(accounts)
+    }
+}
+
+// The token files hold live credentials, so they're only readable by the owner
+fn write_private(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut file = options.open(path)?;
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    file.write_all(bytes)
+}
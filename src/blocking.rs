@@ -0,0 +1,71 @@
+use std::{future::Future, sync::OnceLock};
+
+#[cfg(feature = "webapi")]
+use uuid::Uuid;
+
+#[cfg(feature = "auth")]
+use crate::auth::{
+    microsoft::{MicrosoftAuthenticator, MinecraftEdition},
+    Session
+};
+#[cfg(feature = "network")]
+use crate::network::{
+    self,
+    status::{PingResult, StatusResponse},
+    versions::VersionInfo
+};
+#[cfg(feature = "web")]
+use crate::web;
+#[cfg(feature = "webapi")]
+use crate::webapi::{Endpoints, ProfileResponse};
+
+static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+
+// Panics inside an async context like the blocking client of reqwest
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    RUNTIME
+        .get_or_init(|| {
+            tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(1)
+                .thread_name("minerust-blocking")
+                .enable_all()
+                .build()
+                .expect("Unable to build the runtime of the blocking API")
+        })
+        .block_on(future)
+}
+
+#[cfg(feature = "auth")]
+pub fn login(authenticator: &mut MicrosoftAuthenticator<'_>, edition: MinecraftEdition) -> Result<Session, web::Error> {
+    block_on(authenticator.login(edition))
+}
+
+#[cfg(feature = "webapi")]
+pub fn uuid_from_username(username: &str) -> Result<Uuid, web::Error> {
+    uuid_from_username_at(&Endpoints::default(), username)
+}
+
+#[cfg(feature = "webapi")]
+pub fn uuid_from_username_at(endpoints: &Endpoints, username: &str) -> Result<Uuid, web::Error> {
+    block_on(endpoints.uuid_from_username(username))
+}
+
+#[cfg(feature = "webapi")]
+pub fn profile_from_uuid(uuid: Uuid) -> Result<ProfileResponse, web::Error> {
+    profile_from_uuid_at(&Endpoints::default(), uuid)
+}
+
+#[cfg(feature = "webapi")]
+pub fn profile_from_uuid_at(endpoints: &Endpoints, uuid: Uuid) -> Result<ProfileResponse, web::Error> {
+    block_on(endpoints.profile_from_uuid(uuid))
+}
+
+#[cfg(feature = "network")]
+pub fn request_status(host: &str, port: u16, version: &VersionInfo) -> Result<StatusResponse, network::Error> {
+    block_on(network::status::request_status(host, port, version))
+}
+
+#[cfg(feature = "network")]
+pub fn ping(host: &str, port: u16, version: &VersionInfo) -> Result<PingResult, network::Error> {
+    block_on(network::status::ping(host, port, version))
+}
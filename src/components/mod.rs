@@ -1 +1,100 @@
-// TODO: Develop all of this
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Component {
+    #[serde(default)]
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bold: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub italic: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub underlined: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strikethrough: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub obfuscated: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra: Vec<Component>
+}
+
+impl Component {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn color(self, color: impl Into<String>) -> Self {
+        Self { color: Some(color.into()), ..self }
+    }
+
+    pub fn bold(self, bold: bool) -> Self {
+        Self { bold: Some(bold), ..self }
+    }
+
+    pub fn italic(self, italic: bool) -> Self {
+        Self { italic: Some(italic), ..self }
+    }
+
+    pub fn underlined(self, underlined: bool) -> Self {
+        Self {
+            underlined: Some(underlined),
+            ..self
+        }
+    }
+
+    pub fn strikethrough(self, strikethrough: bool) -> Self {
+        Self {
+            strikethrough: Some(strikethrough),
+            ..self
+        }
+    }
+
+    pub fn obfuscated(self, obfuscated: bool) -> Self {
+        Self {
+            obfuscated: Some(obfuscated),
+            ..self
+        }
+    }
+
+    pub fn append(mut self, component: Component) -> Self {
+        self.extra.push(component);
+        self
+    }
+
+    pub fn to_plain(&self) -> String {
+        let mut text = self.text.clone();
+        for component in &self.extra {
+            text.push_str(&component.to_plain());
+        }
+        text
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Components only contain serializable values")
+    }
+}
+
+impl Display for Component {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "{}", self.to_plain())
+    }
+}
+
+impl From<&str> for Component {
+    fn from(text: &str) -> Self {
+        Component::text(text)
+    }
+}
+
+impl From<String> for Component {
+    fn from(text: String) -> Self {
+        Component::text(text)
+    }
+}
@@ -1,3 +1,6 @@
+#[cfg(all(feature = "web", not(any(feature = "native-tls", feature = "rustls-tls"))))]
+compile_error!("The web feature needs a TLS backend, enable either native-tls or rustls-tls");
+
 #[cfg(test)]
 #[path = "../test/mod.rs"]
 mod test;
@@ -16,3 +19,12 @@ pub mod components;
 
 #[cfg(feature = "webapi")]
 pub mod webapi;
+
+#[cfg(any(feature = "network", feature = "webapi"))]
+pub mod profile;
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+
+#[cfg(feature = "metrics")]
+pub mod metrics;
@@ -0,0 +1,50 @@
+pub const CONN_BYTES_TOTAL: &str = "minerust_conn_bytes_total";
+pub const PACKET_SIZE_BYTES: &str = "minerust_packet_size_bytes";
+pub const WEBAPI_REQUESTS_TOTAL: &str = "minerust_webapi_requests_total";
+pub const COMMAND_RATE_LIMIT_TOKENS: &str = "minerust_command_rate_limit_tokens";
+pub const COMMAND_RATE_LIMIT_WAIT_SECONDS: &str = "minerust_command_rate_limit_wait_seconds";
+
+#[cfg(feature = "network")]
+use std::time::Duration;
+
+#[cfg(feature = "network")]
+use crate::network::PacketDirection;
+#[cfg(feature = "web")]
+use crate::web::Error;
+
+#[cfg(feature = "network")]
+fn direction_label(direction: PacketDirection) -> &'static str {
+    match direction {
+        PacketDirection::Serverbound => "serverbound",
+        PacketDirection::Clientbound => "clientbound"
+    }
+}
+
+// After compression and encryption
+#[cfg(feature = "network")]
+pub(crate) fn record_connection_bytes(direction: PacketDirection, bytes: usize) {
+    ::metrics::counter!(CONN_BYTES_TOTAL, "direction" => direction_label(direction)).increment(bytes as u64);
+}
+
+// Before compression
+#[cfg(feature = "network")]
+pub(crate) fn record_packet_size(direction: PacketDirection, bytes: usize) {
+    ::metrics::histogram!(PACKET_SIZE_BYTES, "direction" => direction_label(direction)).record(bytes as f64);
+}
+
+// Tokens of the burst in use after the command and how long it was held back, zero if not at all
+#[cfg(feature = "network")]
+pub(crate) fn record_command_rate_limit(tokens: u32, waited: Duration) {
+    ::metrics::gauge!(COMMAND_RATE_LIMIT_TOKENS).set(tokens as f64);
+    ::metrics::histogram!(COMMAND_RATE_LIMIT_WAIT_SECONDS).record(waited.as_secs_f64());
+}
+
+// Only the host is used, paths can carry names and ids
+#[cfg(feature = "web")]
+pub(crate) fn record_webapi_request(host: &str, result: &Result<(u16, Vec<u8>), Error>) {
+    let status = match result {
+        Ok((status, _)) => status.to_string(),
+        Err(_) => "error".to_string()
+    };
+    ::metrics::counter!(WEBAPI_REQUESTS_TOTAL, "endpoint" => host.to_string(), "status" => status).increment(1);
+}
@@ -1,19 +1,33 @@
-use std::mem::size_of;
+use std::{io::SeekFrom, mem::size_of};
 
 use crate::network::{ByteOrder, Error};
 
-const LAST_SEVEN_BITS: i32 = 0b01111111;
+pub const MAX_STRING_LENGTH: usize = 32767;
+
+const LAST_SEVEN_BITS: u8 = 0b01111111;
 const NEXT_BYTE_EXISTS: u8 = 0b10000000;
-const SEVEN_BITS_SHIFT_MASK: i32 = 0x01ffffff;
+
+fn seek_offset(count: usize) -> Result<i64, Error> {
+    i64::try_from(count).map_err(|_| Error::Other(format!("Unable to seek => {} bytes don't fit into a seek offset", count)))
+}
+
+// Supplementary characters only appear as surrogates in modified UTF-8, so they get a reason of their own
+fn invalid_utf8_reason(invalid: &[u8], error_length: Option<usize>) -> &'static str {
+    match (invalid, error_length) {
+        ([0xED, 0xA0..=0xBF, ..], _) => "UTF-16 surrogate in string, supplementary characters have to be encoded as four bytes",
+        (_, None) => "Truncated UTF-8 sequence at the end of the string",
+        _ => "Invalid UTF-8 in string"
+    }
+}
 
 macro_rules! var_int {
-    ($_type: tt, $read_length: expr) => {
+    ($_type: tt, $unsigned: tt, $read_length: expr) => {
         paste::paste! {
-            pub fn [<write_var_ $_type>](&mut self, mut value: $_type) -> Result<(), Error> {
+            pub fn [<write_var_ $_type>](&mut self, value: $_type) -> Result<(), Error> {
+                let mut value = value as $unsigned;
                 loop {
-                    let mut temp = (value & LAST_SEVEN_BITS) as u8;
+                    let mut temp = value as u8 & LAST_SEVEN_BITS;
                     value >>= 7;
-                    value &= SEVEN_BITS_SHIFT_MASK;
                     if value != 0 {
                         temp |= NEXT_BYTE_EXISTS;
                     }
@@ -27,15 +41,16 @@ macro_rules! var_int {
             }
 
             pub fn [<read_var_ $_type>](&mut self) -> Result<$_type, Error> {
-                let mut value = 0;
+                let offset = self.position;
+                let mut value: $unsigned = 0;
                 for i in 0..$read_length {
                     let read = self.read_u8()?;
-                    value |= ((read & 0b0111_1111) as $_type) << 7 * i;
-                    if value & 0b1000_0000 == 0 {
-                        break;
+                    value |= ((read & LAST_SEVEN_BITS) as $unsigned) << (7 * i);
+                    if read & NEXT_BYTE_EXISTS == 0 {
+                        return Ok(value as $_type)
                     }
                 }
-                Ok(value)
+                Err(Error::Decode { offset, reason: "VarInt too long".to_string() })
             }
         }
     };
@@ -58,10 +73,7 @@ macro_rules! buffer_method {
             }
 
             pub fn [<read_ $_type>](&mut self) -> Result<$_type, Error> {
-                let mut array: [u8; size_of::<$_type>()] = Default::default();
-                for i in 0..size_of::<$_type>() {
-                    array[i] = self.read_u8()?;
-                }
+                let array: [u8; size_of::<$_type>()] = self.read_bytes(size_of::<$_type>())?.try_into().unwrap();
 
                 Ok(if (if let Some(byte_order) = self.byte_order() { byte_order } else { ByteOrder::system_order() }) == ByteOrder::LittleEndian {
                     $_type::from_le_bytes(array)
@@ -116,31 +128,204 @@ impl Buffer {
     }
 
     pub fn read_u8(&mut self) -> Result<u8, Error> {
+        if self.position >= self.data.len() {
+            return Err(Error::OutOfBounds(self.position + 1, self.data.len()))
+        }
+
         self.position += 1;
         Ok(self.data[self.position - 1])
     }
 
+    pub fn write_bool(&mut self, value: bool) -> Result<(), Error> {
+        self.write_u8(value as u8)
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, Error> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => {
+                Err(Error::Decode {
+                    offset: self.position - 1,
+                    reason: format!("Invalid boolean value {}", value)
+                })
+            }
+        }
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        if !self.writable() {
+            return Err(Error::NotWritable("Buffer".to_owned()))
+        }
+
+        self.data.extend_from_slice(bytes);
+        self.position += bytes.len();
+        Ok(())
+    }
+
+    pub fn read_bytes(&mut self, length: usize) -> Result<Vec<u8>, Error> {
+        if self.remaining() < length {
+            return Err(Error::OutOfBounds(self.position + length, self.data.len()))
+        }
+
+        let bytes = self.data[self.position..self.position + length].to_vec();
+        self.position += length;
+        Ok(bytes)
+    }
+
+    pub fn write_byte_array(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.write_var_i32(bytes.len() as i32)?;
+        self.write_bytes(bytes)
+    }
+
+    pub fn read_byte_array(&mut self) -> Result<Vec<u8>, Error> {
+        let length = self.read_length()?;
+        self.read_bytes(length)
+    }
+
+    pub fn read_length(&mut self) -> Result<usize, Error> {
+        let offset = self.position;
+        let length = self.read_var_i32()?;
+        if length < 0 {
+            return Err(Error::Decode {
+                offset,
+                reason: format!("Negative length {}", length)
+            })
+        }
+        Ok(length as usize)
+    }
+
     pub fn write_str(&mut self, string: &str) -> Result<(), Error> {
-        self.write_string(String::from(string))
+        self.write_string_bounded(string, MAX_STRING_LENGTH)
     }
 
     pub fn write_string(&mut self, string: String) -> Result<(), Error> {
-        self.write_var_i32(string.len() as i32)?;
-        for byte in string.bytes() {
-            self.write_u8(byte)?;
+        self.write_string_bounded(&string, MAX_STRING_LENGTH)
+    }
+
+    // Vanilla bounds strings by UTF-16 code units, the length prefix is the UTF-8 byte count
+    pub fn write_string_bounded(&mut self, string: &str, max_length: usize) -> Result<(), Error> {
+        // Checked first, so oversized strings are refused before counting their code units
+        let max_bytes = max_length.saturating_mul(3).min(i32::MAX as usize);
+        if string.len() > max_bytes {
+            return Err(Error::Other(format!("String too big (was {} bytes encoded, max {})", string.len(), max_bytes)))
         }
-        Ok(())
+
+        let length = string.encode_utf16().count();
+        if length > max_length {
+            return Err(Error::Other(format!("String too big (was {} characters, max {})", length, max_length)))
+        }
+
+        self.write_var_i32(string.len() as i32)?;
+        self.write_bytes(string.as_bytes())
     }
 
     pub fn read_string(&mut self) -> Result<String, Error> {
-        let mut bytes = Vec::new();
-        for _ in 0..self.read_var_i32()? {
-            bytes.push(self.read_u8()?);
+        self.read_string_bounded(MAX_STRING_LENGTH)
+    }
+
+    pub fn read_string_bounded(&mut self, max_length: usize) -> Result<String, Error> {
+        let offset = self.position;
+        let length = self.read_length()?;
+        if length > max_length.saturating_mul(3) {
+            return Err(Error::Decode {
+                offset,
+                reason: format!(
+                    "The received encoded string buffer length is longer than maximum allowed ({} > {})",
+                    length,
+                    max_length.saturating_mul(3)
+                )
+            })
         }
-        Ok(unsafe { String::from_utf8_unchecked(bytes) })
+
+        let data_offset = self.position;
+        let string = String::from_utf8(self.read_bytes(length)?).map_err(|error| {
+            let valid = error.utf8_error().valid_up_to();
+            Error::Decode {
+                offset: data_offset + valid,
+                reason: invalid_utf8_reason(&error.as_bytes()[valid..], error.utf8_error().error_len()).to_string()
+            }
+        })?;
+
+        let units = string.encode_utf16().count();
+        if units > max_length {
+            return Err(Error::Decode {
+                offset,
+                reason: format!("The received string length is longer than maximum allowed ({} > {})", units, max_length)
+            })
+        }
+        Ok(string)
     }
 
-    var_int!(i32, 4);
+    // Java's modified UTF-8: NUL takes two bytes, supplementary characters become two 3-byte surrogates
+    pub fn write_java_utf(&mut self, string: &str) -> Result<(), Error> {
+        let mut bytes = Vec::with_capacity(string.len());
+        for unit in string.encode_utf16() {
+            match unit {
+                0x0001..=0x007F => bytes.push(unit as u8),
+                0x0000 | 0x0080..=0x07FF => {
+                    bytes.push(0xC0 | (unit >> 6) as u8);
+                    bytes.push(0x80 | (unit & 0x3F) as u8);
+                }
+                _ => {
+                    bytes.push(0xE0 | (unit >> 12) as u8);
+                    bytes.push(0x80 | ((unit >> 6) & 0x3F) as u8);
+                    bytes.push(0x80 | (unit & 0x3F) as u8);
+                }
+            }
+        }
+
+        if bytes.len() > u16::MAX as usize {
+            return Err(Error::Other(format!("Java UTF string is longer than {} bytes", u16::MAX)))
+        }
+
+        self.write_u16(bytes.len() as u16)?;
+        self.write_bytes(&bytes)
+    }
+
+    pub fn read_java_utf(&mut self) -> Result<String, Error> {
+        let length = self.read_u16()? as usize;
+        let offset = self.position;
+        let bytes = self.read_bytes(length)?;
+        let malformed = || {
+            Error::Decode {
+                offset,
+                reason: "Malformed Java UTF string".to_string()
+            }
+        };
+
+        let mut units = Vec::with_capacity(length);
+        let mut index = 0;
+        while index < bytes.len() {
+            let byte = bytes[index] as u16;
+            let continuation = |offset: usize| {
+                match bytes.get(index + offset) {
+                    Some(byte) if byte & 0xC0 == 0x80 => Ok((byte & 0x3F) as u16),
+                    _ => Err(malformed())
+                }
+            };
+
+            match byte >> 4 {
+                0x0..=0x7 => {
+                    units.push(byte);
+                    index += 1;
+                }
+                0xC | 0xD => {
+                    units.push(((byte & 0x1F) << 6) | continuation(1)?);
+                    index += 2;
+                }
+                0xE => {
+                    units.push(((byte & 0x0F) << 12) | (continuation(1)? << 6) | continuation(2)?);
+                    index += 3;
+                }
+                _ => return Err(malformed())
+            }
+        }
+        String::from_utf16(&units).map_err(|_| malformed())
+    }
+
+    var_int!(i32, u32, 5);
+    var_int!(i64, u64, 10);
 
     buffer_method!(u16);
     buffer_method!(u32);
@@ -155,6 +340,33 @@ impl Buffer {
         self.position = position;
     }
 
+    pub fn seek(&mut self, seek: SeekFrom) -> Result<usize, Error> {
+        let target = match seek {
+            SeekFrom::Start(offset) => offset as i128,
+            SeekFrom::Current(offset) => self.position as i128 + offset as i128,
+            SeekFrom::End(offset) => self.data.len() as i128 + offset as i128
+        };
+
+        if target < 0 {
+            return Err(Error::Other(format!("Unable to seek to negative position {}", target)))
+        }
+
+        if target as usize > self.data.len() {
+            return Err(Error::OutOfBounds(target as usize, self.data.len()))
+        }
+
+        self.position = target as usize;
+        Ok(self.position)
+    }
+
+    pub fn advance(&mut self, count: usize) -> Result<usize, Error> {
+        self.seek(SeekFrom::Current(seek_offset(count)?))
+    }
+
+    pub fn rewind(&mut self, count: usize) -> Result<usize, Error> {
+        self.seek(SeekFrom::Current(-seek_offset(count)?))
+    }
+
     pub fn byte_order(&self) -> Option<ByteOrder> {
         self.order.clone()
     }
@@ -167,6 +379,18 @@ impl Buffer {
         self.position
     }
 
+    pub fn remaining(&self) -> usize {
+        self.data.len().saturating_sub(self.position)
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
         self.data.clone()
     }
@@ -179,4 +403,18 @@ impl Buffer {
         self.data.clear();
         self.position = 0;
     }
+
+    pub fn split_off(&mut self, at: usize) -> Buffer {
+        let tail = Self {
+            data: self.data.split_off(at),
+            ..self.cloned_metadata()
+        };
+        self.position = self.position.min(at);
+        tail
+    }
+
+    pub fn drain_front(&mut self, count: usize) {
+        self.data.drain(..count);
+        self.position = self.position.saturating_sub(count);
+    }
 }
@@ -0,0 +1,128 @@
+use crate::network::{buffer::Buffer, connection::Connection, tracker::SessionState, versions::VersionInfo, ByteOrder, Error, PacketState};
+
+// The first play packet since bundles came with 1.19.4
+pub const BUNDLE_DELIMITER: i32 = 0x00;
+pub const BUNDLE_PROTOCOL: i32 = 762;
+
+// Vanilla clients disconnect once a bundle grows past this many packets
+pub const MAX_BUNDLE_PACKETS: usize = 4096;
+
+pub(crate) fn delimiter() -> Result<Buffer, Error> {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(BUNDLE_DELIMITER)?;
+    Ok(buffer)
+}
+
+#[derive(Debug, Default)]
+pub struct PacketBundle {
+    pub packets: Vec<Buffer>
+}
+
+impl PacketBundle {
+    pub fn len(&self) -> usize {
+        self.packets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packets.is_empty()
+    }
+}
+
+#[derive(Debug)]
+pub enum BundleEvent {
+    Packet(Buffer),
+    Bundle(PacketBundle)
+}
+
+#[derive(Debug)]
+pub struct Bundler {
+    protocol: i32,
+    max_packets: usize,
+    open: Option<Vec<Buffer>>
+}
+
+impl Bundler {
+    pub fn new(version: &VersionInfo) -> Self {
+        Self {
+            protocol: version.protocol,
+            max_packets: MAX_BUNDLE_PACKETS,
+            open: None
+        }
+    }
+
+    pub fn with_max_packets(self, max_packets: usize) -> Self {
+        Self { max_packets, ..self }
+    }
+
+    pub fn protocol(&self) -> i32 {
+        self.protocol
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open.is_some()
+    }
+
+    pub fn offer(&mut self, state: PacketState, mut packet: Buffer) -> Result<Option<BundleEvent>, Error> {
+        if state != PacketState::Play || self.protocol < BUNDLE_PROTOCOL {
+            return Ok(Some(BundleEvent::Packet(packet)))
+        }
+
+        let position = packet.position();
+        let id = packet.read_var_i32()?;
+        packet.set_position(position);
+        if id == BUNDLE_DELIMITER {
+            return Ok(match self.open.take() {
+                Some(packets) => Some(BundleEvent::Bundle(PacketBundle { packets })),
+                None => {
+                    self.open = Some(Vec::new());
+                    None
+                }
+            })
+        }
+
+        let Some(packets) = &mut self.open else { return Ok(Some(BundleEvent::Packet(packet))) };
+        if packets.len() >= self.max_packets {
+            self.open = None;
+            return Err(Error::Other(format!("Unable to read bundle => The bundle has more than {} packets", self.max_packets)))
+        }
+        packets.push(packet);
+        Ok(None)
+    }
+
+    pub fn finish(&mut self) -> Result<(), Error> {
+        match self.open.take() {
+            Some(packets) => Err(Error::Other(format!("Unable to read bundle => The connection closed after {} bundled packets", packets.len()))),
+            None => Ok(())
+        }
+    }
+
+    pub fn read<'a, S>(&mut self, connection: &mut impl Connection<'a, S>) -> Result<BundleEvent, Error> {
+        loop {
+            let packet = match connection.read_packet() {
+                Ok(packet) => packet,
+                Err(error) => {
+                    return Err(match self.open.take() {
+                        Some(packets) => Error::Other(format!("Unable to read bundle => The connection closed after {} bundled packets ({})", packets.len(), error)),
+                        None => error
+                    })
+                }
+            };
+            if let Some(event) = self.offer(connection.state(), packet)? {
+                return Ok(event)
+            }
+        }
+    }
+}
+
+// Everything here is server authoritative and sent again after reconnecting
+impl SessionState for Bundler {
+    type Snapshot = ();
+
+    fn snapshot(&self) {}
+
+    fn restore(&mut self, _snapshot: ()) {}
+
+    fn reset_for_reconnect(&mut self) {
+        self.open = None;
+    }
+}
@@ -0,0 +1,237 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rand::random;
+use rsa::{Pkcs1v15Sign, RsaPrivateKey};
+use sha2::{Digest, Sha256};
+use uuid::{Builder, Uuid};
+
+use crate::network::{buffer::Buffer, connection::Writable, Error};
+
+pub const LAST_SEEN_WINDOW: usize = 20;
+pub const ACKNOWLEDGEMENT_THRESHOLD: i32 = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LastSeenUpdate {
+    pub signatures: Vec<Vec<u8>>,
+    pub offset: i32,
+    pub acknowledged: [u8; 3]
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LastSeenTracker {
+    entries: [Option<Vec<u8>>; LAST_SEEN_WINDOW],
+    tail: usize,
+    offset: i32,
+    last_tracked: Option<Vec<u8>>
+}
+
+impl LastSeenTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_pending(&mut self, signature: &[u8], displayed: bool) -> bool {
+        if self.last_tracked.as_deref() == Some(signature) {
+            return false
+        }
+
+        self.last_tracked = Some(signature.to_vec());
+        self.add_entry(displayed.then(|| signature.to_vec()));
+        true
+    }
+
+    pub fn ignore_pending(&mut self) {
+        self.add_entry(None);
+    }
+
+    fn add_entry(&mut self, entry: Option<Vec<u8>>) {
+        self.entries[self.tail] = entry;
+        self.tail = (self.tail + 1) % LAST_SEEN_WINDOW;
+        self.offset += 1;
+    }
+
+    pub fn offset(&self) -> i32 {
+        self.offset
+    }
+
+    pub fn needs_acknowledgement(&self) -> bool {
+        self.offset > ACKNOWLEDGEMENT_THRESHOLD
+    }
+
+    pub fn take_offset(&mut self) -> i32 {
+        std::mem::take(&mut self.offset)
+    }
+
+    // Bit i of the acknowledged set marks the i-th slot from the oldest
+    pub fn update(&mut self) -> LastSeenUpdate {
+        let offset = self.take_offset();
+        let mut acknowledged = [0; 3];
+        let mut signatures = Vec::new();
+
+        for i in 0..LAST_SEEN_WINDOW {
+            if let Some(signature) = &self.entries[(self.tail + i) % LAST_SEEN_WINDOW] {
+                acknowledged[i / 8] |= 1 << (i % 8);
+                signatures.push(signature.clone());
+            }
+        }
+
+        LastSeenUpdate { signatures, offset, acknowledged }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedChat {
+    pub message: String,
+    pub timestamp: i64,
+    pub salt: i64,
+    pub signature: Vec<u8>,
+    pub offset: i32,
+    pub acknowledged: [u8; 3]
+}
+
+impl Writable for SignedChat {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        buffer.write_str(&self.message)?;
+        buffer.write_i64(self.timestamp)?;
+        buffer.write_i64(self.salt)?;
+        buffer.write_bool(true)?;
+        buffer.write_bytes(&self.signature)?;
+        buffer.write_var_i32(self.offset)?;
+        self.acknowledged.write(buffer)
+    }
+}
+
+// Chat Command since 1.19.3, Signed Chat Command since 1.20.5
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedCommand {
+    pub command: String,
+    pub timestamp: i64,
+    pub salt: i64,
+    pub signatures: Vec<(String, Vec<u8>)>,
+    pub offset: i32,
+    pub acknowledged: [u8; 3]
+}
+
+impl Writable for SignedCommand {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        buffer.write_str(&self.command)?;
+        buffer.write_i64(self.timestamp)?;
+        buffer.write_i64(self.salt)?;
+        buffer.write_var_i32(self.signatures.len() as i32)?;
+        for (name, signature) in &self.signatures {
+            buffer.write_str(name)?;
+            buffer.write_bytes(signature)?;
+        }
+        buffer.write_var_i32(self.offset)?;
+        self.acknowledged.write(buffer)
+    }
+}
+
+pub struct MessageSigner {
+    sender: Uuid,
+    session_id: Uuid,
+    private_key: RsaPrivateKey,
+    expires_at: SystemTime,
+    index: i32,
+    tracker: LastSeenTracker
+}
+
+impl MessageSigner {
+    pub fn new(sender: Uuid, private_key: RsaPrivateKey, expires_at: SystemTime) -> Self {
+        Self {
+            sender,
+            session_id: Builder::from_random_bytes(random()).into_uuid(),
+            private_key,
+            expires_at,
+            index: 0,
+            tracker: LastSeenTracker::new()
+        }
+    }
+
+    #[cfg(feature = "webapi")]
+    pub fn from_certificates(sender: Uuid, certificates: &crate::webapi::PlayerCertificates) -> Result<Self, Error> {
+        Ok(Self::new(sender, certificates.private_key()?, certificates.expires_at()?))
+    }
+
+    pub fn session_id(&self) -> Uuid {
+        self.session_id
+    }
+
+    pub fn is_expired(&self) -> bool {
+        SystemTime::now() >= self.expires_at
+    }
+
+    pub fn tracker(&self) -> &LastSeenTracker {
+        &self.tracker
+    }
+
+    pub fn tracker_mut(&mut self) -> &mut LastSeenTracker {
+        &mut self.tracker
+    }
+
+    pub fn sign(&mut self, message: &str) -> Result<SignedChat, Error> {
+        let (timestamp, salt) = self.prepare()?;
+        let update = self.tracker.update();
+        let signature = self.signature(message, timestamp, salt, &update)?;
+
+        Ok(SignedChat {
+            message: message.to_string(),
+            timestamp: timestamp.as_millis() as i64,
+            salt,
+            signature,
+            offset: update.offset,
+            acknowledged: update.acknowledged
+        })
+    }
+
+    pub fn sign_command(&mut self, command: &str, arguments: &[(String, String)]) -> Result<SignedCommand, Error> {
+        let (timestamp, salt) = self.prepare()?;
+        let update = self.tracker.update();
+        let signatures = arguments
+            .iter()
+            .map(|(name, value)| Ok((name.clone(), self.signature(value, timestamp, salt, &update)?)))
+            .collect::<Result<_, Error>>()?;
+
+        Ok(SignedCommand {
+            command: command.to_string(),
+            timestamp: timestamp.as_millis() as i64,
+            salt,
+            signatures,
+            offset: update.offset,
+            acknowledged: update.acknowledged
+        })
+    }
+
+    fn prepare(&self) -> Result<(Duration, i64), Error> {
+        let now = SystemTime::now();
+        if now >= self.expires_at {
+            return Err(Error::ExpiredCertificate)
+        }
+
+        let timestamp = now.duration_since(UNIX_EPOCH).map_err(|error| Error::Other(error.to_string()))?;
+        Ok((timestamp, random::<i64>()))
+    }
+
+    fn signature(&mut self, message: &str, timestamp: Duration, salt: i64, update: &LastSeenUpdate) -> Result<Vec<u8>, Error> {
+        let mut hasher = Sha256::new();
+        hasher.update(1_i32.to_be_bytes());
+        hasher.update(self.sender.as_bytes());
+        hasher.update(self.session_id.as_bytes());
+        hasher.update(self.index.to_be_bytes());
+        hasher.update(salt.to_be_bytes());
+        hasher.update((timestamp.as_secs() as i64).to_be_bytes());
+        hasher.update((message.len() as i32).to_be_bytes());
+        hasher.update(message.as_bytes());
+        hasher.update((update.signatures.len() as i32).to_be_bytes());
+        for signature in &update.signatures {
+            hasher.update(signature);
+        }
+
+        let signature = self
+            .private_key
+            .sign(Pkcs1v15Sign::new::<Sha256>(), &hasher.finalize())
+            .map_err(|error| Error::Other(format!("Unable to sign chat message => {}", error)))?;
+        self.index += 1;
+        Ok(signature)
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::network::{
+    buffer::Buffer,
+    chunk::{
+        update::{self, BlockChange, SectionBlocksUpdate},
+        ChunkColumn, ChunkSection, SECTION_WIDTH
+    },
+    connection::{Readable, Writable},
+    nbt::Compound,
+    registry_codec::DimensionType,
+    versions::VersionInfo,
+    ByteOrder, Error
+};
+
+pub const REGION_MAGIC: [u8; 4] = *b"MRGN";
+pub const REGION_VERSION: u8 = 1;
+
+pub const DEFAULT_CAPACITY: usize = 4225;
+
+// Vanilla worlds are at most 4064 blocks high
+const MAX_SECTIONS: usize = 254;
+
+// Chunk Data and Unload Chunk, only the chunk formats of 1.18 and later are supported
+fn packet_ids(protocol: i32) -> Option<[i32; 4]> {
+    let (chunk_data, unload_chunk) = match protocol {
+        757..=758 => (0x22, 0x1D),
+        759 => (0x1F, 0x1A),
+        760 => (0x21, 0x1C),
+        761 => (0x20, 0x1B),
+        762..=763 => (0x24, 0x1E),
+        764..=765 => (0x25, 0x1F),
+        766..=767 => (0x27, 0x21),
+        _ => return None
+    };
+    let (block_update, section_blocks_update) = update::packet_ids(protocol)?;
+    Some([chunk_data, unload_chunk, block_update, section_blocks_update])
+}
+
+pub struct ChunkStore {
+    protocol: i32,
+    dimension: DimensionType,
+    capacity: usize,
+    columns: HashMap<(i32, i32), (u64, ChunkColumn)>,
+    recency: BTreeMap<u64, (i32, i32)>,
+    uses: u64
+}
+
+impl ChunkStore {
+    pub fn new(version: &VersionInfo, dimension: DimensionType) -> Self {
+        Self {
+            protocol: version.protocol,
+            dimension,
+            capacity: DEFAULT_CAPACITY,
+            columns: HashMap::new(),
+            recency: BTreeMap::new(),
+            uses: 0
+        }
+    }
+
+    pub fn with_capacity(self, capacity: usize) -> Self {
+        Self { capacity: capacity.max(1), ..self }
+    }
+
+    pub fn protocol(&self) -> i32 {
+        self.protocol
+    }
+
+    pub fn dimension(&self) -> &DimensionType {
+        &self.dimension
+    }
+
+    pub fn set_dimension(&mut self, dimension: DimensionType) {
+        self.dimension = dimension;
+        self.clear();
+    }
+
+    pub fn clear(&mut self) {
+        self.columns.clear();
+        self.recency.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    pub fn contains(&self, x: i32, z: i32) -> bool {
+        self.columns.contains_key(&(x, z))
+    }
+
+    pub fn get(&self, x: i32, z: i32) -> Option<&ChunkColumn> {
+        self.columns.get(&(x, z)).map(|(_, column)| column)
+    }
+
+    pub fn get_mut(&mut self, x: i32, z: i32) -> Option<&mut ChunkColumn> {
+        let use_id = self.next_use();
+        let (used, column) = self.columns.get_mut(&(x, z))?;
+        self.recency.remove(used);
+        self.recency.insert(use_id, (x, z));
+        *used = use_id;
+        Some(column)
+    }
+
+    pub fn block(&self, x: i32, y: i32, z: i32) -> Option<i32> {
+        let width = SECTION_WIDTH as i32;
+        self.get(x.div_euclid(width), z.div_euclid(width))?
+            .get(x.rem_euclid(width) as usize, y, z.rem_euclid(width) as usize)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = &ChunkColumn> {
+        self.columns.values().map(|(_, column)| column)
+    }
+
+    fn next_use(&mut self) -> u64 {
+        self.uses += 1;
+        self.uses
+    }
+
+    pub fn insert(&mut self, column: ChunkColumn) {
+        let position = (column.x, column.z);
+        let use_id = self.next_use();
+        if let Some((used, _)) = self.columns.insert(position, (use_id, column)) {
+            self.recency.remove(&used);
+        }
+        self.recency.insert(use_id, position);
+
+        while self.columns.len() > self.capacity {
+            let Some((_, position)) = self.recency.pop_first() else {
+                break;
+            };
+            self.columns.remove(&position);
+        }
+    }
+
+    pub fn remove(&mut self, x: i32, z: i32) -> Option<ChunkColumn> {
+        let (used, column) = self.columns.remove(&(x, z))?;
+        self.recency.remove(&used);
+        Some(column)
+    }
+
+    pub fn handle(&mut self, packet: &mut Buffer) -> Result<bool, Error> {
+        let ids = packet_ids(self.protocol).ok_or_else(|| Error::Other(format!("Unable to handle chunks => Protocol {} isn't supported", self.protocol)))?;
+        let position = packet.position();
+        let handled = self.read(packet, ids);
+        packet.set_position(position);
+        handled
+    }
+
+    fn read(&mut self, packet: &mut Buffer, [chunk_data, unload_chunk, block_update, section_blocks_update]: [i32; 4]) -> Result<bool, Error> {
+        let id = packet.read_var_i32()?;
+        if id == block_update {
+            self.set_blocks(&[BlockChange::read(packet)?])?;
+            return Ok(true)
+        }
+        if id == section_blocks_update {
+            self.set_blocks(&SectionBlocksUpdate::read_versioned(packet, self.protocol)?.changes().collect::<Vec<_>>())?;
+            return Ok(true)
+        }
+        if id == chunk_data {
+            let column = ChunkColumn::read_versioned(packet, &self.dimension, self.protocol)?;
+            self.insert(column);
+            return Ok(true)
+        }
+        if id != unload_chunk {
+            return Ok(false)
+        }
+
+        // 1.20.2 swapped the coordinates of Unload Chunk
+        let (first, second) = (packet.read_i32()?, packet.read_i32()?);
+        let (x, z) = if self.protocol >= 764 { (second, first) } else { (first, second) };
+        self.remove(x, z);
+        Ok(true)
+    }
+
+    fn set_blocks(&mut self, changes: &[BlockChange]) -> Result<(), Error> {
+        let width = SECTION_WIDTH as i32;
+        let Some(first) = changes.first() else { return Ok(()) };
+        let Some((_, column)) = self.columns.get_mut(&(first.x.div_euclid(width), first.z.div_euclid(width))) else {
+            return Ok(())
+        };
+        let Some(section) = usize::try_from((first.y - column.min_y).div_euclid(width))
+            .ok()
+            .and_then(|section| column.sections.get_mut(section))
+        else {
+            return Ok(())
+        };
+
+        let relative = changes
+            .iter()
+            .map(|change| {
+                (
+                    change.x.rem_euclid(width) as usize,
+                    change.y.rem_euclid(width) as usize,
+                    change.z.rem_euclid(width) as usize,
+                    change.state
+                )
+            })
+            .collect::<Vec<_>>();
+        section.set_blocks(&relative)
+    }
+
+    pub fn export(&self, from: (i32, i32), to: (i32, i32)) -> Result<Vec<u8>, Error> {
+        let (min_x, max_x) = (from.0.min(to.0), from.0.max(to.0));
+        let (min_z, max_z) = (from.1.min(to.1), from.1.max(to.1));
+        let mut columns = self
+            .columns()
+            .filter(|column| (min_x..=max_x).contains(&column.x) && (min_z..=max_z).contains(&column.z))
+            .collect::<Vec<_>>();
+        columns.sort_by_key(|column| (column.x, column.z));
+
+        let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        buffer.write_bytes(&REGION_MAGIC)?;
+        buffer.write_u8(REGION_VERSION)?;
+        buffer.write_var_i32(columns.len() as i32)?;
+        for column in columns {
+            buffer.write_i32(column.x)?;
+            buffer.write_i32(column.z)?;
+            buffer.write_i32(column.min_y)?;
+            buffer.write_var_i32(column.sections.len() as i32)?;
+            buffer = column.sections.iter().try_fold(buffer, |buffer, section| section.write(buffer))?;
+        }
+        Ok(buffer.to_bytes())
+    }
+
+    pub fn import(&mut self, bytes: &[u8]) -> Result<usize, Error> {
+        let columns = read_region(bytes)?;
+        let count = columns.len();
+        columns.into_iter().for_each(|column| self.insert(column));
+        Ok(count)
+    }
+}
+
+pub fn read_region(bytes: &[u8]) -> Result<Vec<ChunkColumn>, Error> {
+    let mut buffer = Buffer::new(bytes.to_vec(), false, Some(ByteOrder::BigEndian));
+    if buffer.read_bytes(REGION_MAGIC.len())? != REGION_MAGIC {
+        return Err(Error::Decode {
+            offset: 0,
+            reason: "Not a region export".to_string()
+        })
+    }
+
+    let version = buffer.read_u8()?;
+    if version != REGION_VERSION {
+        return Err(Error::Decode {
+            offset: REGION_MAGIC.len(),
+            reason: format!("Unsupported region version {}", version)
+        })
+    }
+
+    let count = buffer.read_length()?;
+    let mut columns = Vec::new();
+    for _ in 0..count {
+        let (x, z, min_y) = (buffer.read_i32()?, buffer.read_i32()?, buffer.read_i32()?);
+        let offset = buffer.position();
+        let sections = buffer.read_length()?;
+        if sections > MAX_SECTIONS {
+            return Err(Error::Decode {
+                offset,
+                reason: format!("Too many sections ({} > {})", sections, MAX_SECTIONS)
+            })
+        }
+
+        columns.push(ChunkColumn {
+            x,
+            z,
+            min_y,
+            heightmaps: Compound::new(),
+            sections: (0..sections).map(|_| ChunkSection::read(&mut buffer)).collect::<Result<_, _>>()?,
+            trailing: Vec::new()
+        });
+    }
+
+    if buffer.remaining() > 0 {
+        return Err(Error::Decode {
+            offset: buffer.position(),
+            reason: format!("{} bytes left after the last column", buffer.remaining())
+        })
+    }
+    Ok(columns)
+}
@@ -0,0 +1,635 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    marker::PhantomData
+};
+
+use crate::network::{
+    buffer::Buffer,
+    connection::{Readable, VarI32, Writable},
+    nbt::{Compound, Tag},
+    registry_codec::DimensionType,
+    ByteOrder, Error
+};
+
+pub mod export;
+pub mod update;
+
+pub const SECTION_WIDTH: usize = 16;
+pub const LIGHT_ARRAY_LENGTH: usize = 2048;
+
+const HEIGHTMAP_ENTRIES: usize = SECTION_WIDTH * SECTION_WIDTH;
+
+// Sections carry their own biomes since 1.18. Chunk Data of 1.16.5 and 1.17 (754-756) selects its sections with a bit mask and
+// sends the biomes of the whole column, it isn't supported, so columns are read and written from this protocol on
+pub const SECTION_PROTOCOL: i32 = 757;
+
+pub trait ContainerKind {
+    const ENTRIES: usize;
+    const MIN_INDIRECT_BITS: u8;
+    const MAX_INDIRECT_BITS: u8;
+    const DIRECT_BITS: u8;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockStates;
+
+impl ContainerKind for BlockStates {
+    const ENTRIES: usize = 4096;
+    const MIN_INDIRECT_BITS: u8 = 4;
+    const MAX_INDIRECT_BITS: u8 = 8;
+    const DIRECT_BITS: u8 = 15;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Biomes;
+
+impl ContainerKind for Biomes {
+    const ENTRIES: usize = 64;
+    const MIN_INDIRECT_BITS: u8 = 1;
+    const MAX_INDIRECT_BITS: u8 = 3;
+    const DIRECT_BITS: u8 = 6;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Palette {
+    SingleValue(i32),
+    Indirect(Vec<i32>),
+    Direct
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PalettedContainer<K: ContainerKind> {
+    bits_per_entry: u8,
+    palette: Palette,
+    data: Vec<u64>,
+    kind: PhantomData<K>
+}
+
+// Since 1.16 entries never span two longs
+fn packed_length(entries: usize, bits_per_entry: u8) -> usize {
+    match bits_per_entry {
+        0 => 0,
+        bits => entries.div_ceil(64 / bits as usize)
+    }
+}
+
+fn pack(values: &[u64], bits_per_entry: u8) -> Vec<u64> {
+    let per_long = 64 / bits_per_entry as usize;
+    values
+        .chunks(per_long)
+        .map(|chunk| chunk.iter().enumerate().fold(0, |long, (index, value)| long | (value << (index * bits_per_entry as usize))))
+        .collect()
+}
+
+impl<K: ContainerKind> PalettedContainer<K> {
+    pub fn single(value: i32) -> Self {
+        Self {
+            bits_per_entry: 0,
+            palette: Palette::SingleValue(value),
+            data: Vec::new(),
+            kind: PhantomData
+        }
+    }
+
+    pub fn from_values(values: &[i32]) -> Result<Self, Error> {
+        if values.len() != K::ENTRIES {
+            return Err(Error::Other(format!("Paletted container needs {} entries, got {}", K::ENTRIES, values.len())))
+        }
+
+        if let Some(value) = values.iter().find(|value| **value < 0) {
+            return Err(Error::Other(format!("Invalid palette entry {}", value)))
+        }
+
+        let mut palette = Vec::new();
+        let mut indices = HashMap::new();
+        for value in values {
+            indices.entry(*value).or_insert_with(|| {
+                palette.push(*value);
+                palette.len() as u64 - 1
+            });
+        }
+
+        if palette.len() == 1 {
+            return Ok(Self::single(palette[0]))
+        }
+
+        let bits = (usize::BITS - (palette.len() - 1).leading_zeros()) as u8;
+        if bits <= K::MAX_INDIRECT_BITS {
+            let bits_per_entry = bits.max(K::MIN_INDIRECT_BITS);
+            let data = pack(&values.iter().map(|value| indices[value]).collect::<Vec<_>>(), bits_per_entry);
+            return Ok(Self {
+                bits_per_entry,
+                palette: Palette::Indirect(palette),
+                data,
+                kind: PhantomData
+            })
+        }
+
+        let bits_per_entry = values.iter().map(|value| (u32::BITS - value.leading_zeros()) as u8).max().unwrap_or(0).max(K::DIRECT_BITS);
+        Ok(Self {
+            bits_per_entry,
+            palette: Palette::Direct,
+            data: pack(&values.iter().map(|value| *value as u64).collect::<Vec<_>>(), bits_per_entry),
+            kind: PhantomData
+        })
+    }
+
+    pub fn bits_per_entry(&self) -> u8 {
+        self.bits_per_entry
+    }
+
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    pub fn data(&self) -> &[u64] {
+        &self.data
+    }
+
+    pub fn get(&self, index: usize) -> Option<i32> {
+        if index >= K::ENTRIES {
+            return None
+        }
+
+        let raw = match self.bits_per_entry {
+            0 => 0,
+            bits => {
+                let per_long = 64 / bits as usize;
+                let long = self.data.get(index / per_long)?;
+                (long >> ((index % per_long) * bits as usize)) & ((1 << bits) - 1)
+            }
+        };
+
+        match &self.palette {
+            Palette::SingleValue(value) => Some(*value),
+            Palette::Indirect(palette) => palette.get(raw as usize).copied(),
+            Palette::Direct => Some(raw as i32)
+        }
+    }
+
+    pub fn values(&self) -> Vec<i32> {
+        (0..K::ENTRIES).map_while(|index| self.get(index)).collect()
+    }
+}
+
+impl<K: ContainerKind> Writable for PalettedContainer<K> {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        buffer.write_u8(self.bits_per_entry)?;
+        match &self.palette {
+            Palette::SingleValue(value) => buffer.write_var_i32(*value)?,
+            Palette::Indirect(palette) => buffer = palette.iter().map(|value| VarI32::new(*value)).collect::<Vec<_>>().write(buffer)?,
+            Palette::Direct => {}
+        }
+        self.data.write(buffer)
+    }
+}
+
+// Vanilla widens indirect palettes to the minimum size
+impl<K: ContainerKind> Readable for PalettedContainer<K> {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        let bits_per_entry = buffer.read_u8()?;
+        let (bits_per_entry, palette) = match bits_per_entry {
+            0 => (0, Palette::SingleValue(buffer.read_var_i32()?)),
+            bits if bits <= K::MAX_INDIRECT_BITS => {
+                (
+                    bits.max(K::MIN_INDIRECT_BITS),
+                    Palette::Indirect(Vec::<VarI32>::read(buffer)?.into_iter().map(i32::from).collect())
+                )
+            }
+            bits if bits <= 32 => (bits, Palette::Direct),
+            bits => {
+                return Err(Error::Decode {
+                    offset: buffer.position() - 1,
+                    reason: format!("Invalid bits per entry {}", bits)
+                })
+            }
+        };
+
+        let offset = buffer.position();
+        let data = Vec::<u64>::read(buffer)?;
+        let expected = packed_length(K::ENTRIES, bits_per_entry);
+        if data.len() != expected {
+            return Err(Error::Decode {
+                offset,
+                reason: format!("Expected {} longs of paletted data, got {}", expected, data.len())
+            })
+        }
+
+        Ok(Self {
+            bits_per_entry,
+            palette,
+            data,
+            kind: PhantomData
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkSection {
+    pub block_count: i16,
+    pub block_states: PalettedContainer<BlockStates>,
+    pub biomes: PalettedContainer<Biomes>
+}
+
+impl ChunkSection {
+    // Vanilla leaves cave and void air out of the block count as well
+    pub fn new(block_states: PalettedContainer<BlockStates>, biomes: PalettedContainer<Biomes>) -> Self {
+        Self {
+            block_count: block_states.values().iter().filter(|state| **state != 0).count() as i16,
+            block_states,
+            biomes
+        }
+    }
+
+    pub fn empty() -> Self {
+        Self::new(PalettedContainer::single(0), PalettedContainer::single(0))
+    }
+
+    // Blocks are stored in y, z, x order
+    pub fn get(&self, x: usize, y: usize, z: usize) -> Option<i32> {
+        if x >= SECTION_WIDTH || y >= SECTION_WIDTH || z >= SECTION_WIDTH {
+            return None
+        }
+        self.block_states.get((y * SECTION_WIDTH + z) * SECTION_WIDTH + x)
+    }
+
+    pub fn set_blocks(&mut self, changes: &[(usize, usize, usize, i32)]) -> Result<(), Error> {
+        let mut states = self.block_states.values();
+        for (x, y, z, state) in changes {
+            if *x >= SECTION_WIDTH || *y >= SECTION_WIDTH || *z >= SECTION_WIDTH {
+                return Err(Error::Other(format!("Block {} {} {} is outside of the section", x, y, z)))
+            }
+            states[(y * SECTION_WIDTH + z) * SECTION_WIDTH + x] = *state;
+        }
+
+        *self = Self::new(PalettedContainer::from_values(&states)?, self.biomes.clone());
+        Ok(())
+    }
+}
+
+impl Writable for ChunkSection {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        buffer.write_i16(self.block_count)?;
+        let buffer = self.block_states.write(buffer)?;
+        self.biomes.write(buffer)
+    }
+}
+
+impl Readable for ChunkSection {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        Ok(Self {
+            block_count: buffer.read_i16()?,
+            block_states: PalettedContainer::read(buffer)?,
+            biomes: PalettedContainer::read(buffer)?
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkColumn {
+    pub x: i32,
+    pub z: i32,
+    pub min_y: i32,
+    pub heightmaps: Compound,
+    pub sections: Vec<ChunkSection>,
+    trailing: Vec<u8>
+}
+
+impl ChunkColumn {
+    pub fn new(x: i32, z: i32, dimension: &DimensionType, heightmaps: Compound) -> Self {
+        Self {
+            x,
+            z,
+            min_y: dimension.min_y,
+            heightmaps,
+            sections: vec![ChunkSection::empty(); dimension.section_count()],
+            trailing: Vec::new()
+        }
+    }
+
+    pub fn get(&self, x: usize, y: i32, z: usize) -> Option<i32> {
+        let relative = y.checked_sub(self.min_y).filter(|relative| *relative >= 0)? as usize;
+        self.sections.get(relative / SECTION_WIDTH)?.get(x, relative % SECTION_WIDTH, z)
+    }
+
+    pub fn read_versioned(buffer: &mut Buffer, dimension: &DimensionType, protocol: i32) -> Result<Self, Error> {
+        if protocol < SECTION_PROTOCOL {
+            return Err(Error::Other(format!(
+                "Unable to read chunk => Protocol {} predates 1.18, chunks are supported from protocol {}",
+                protocol, SECTION_PROTOCOL
+            )))
+        }
+
+        let x = buffer.read_i32()?;
+        let z = buffer.read_i32()?;
+        let heightmaps = match protocol {
+            protocol if protocol >= 764 => Compound::read(buffer)?,
+            _ => Compound::read_named(buffer)?.1
+        };
+
+        let offset = buffer.position();
+        let mut data = Buffer::new(buffer.read_byte_array()?, false, buffer.byte_order());
+        let sections = (0..dimension.section_count()).map(|_| ChunkSection::read(&mut data)).collect::<Result<_, _>>().map_err(|error| {
+            Error::Decode {
+                offset: offset + data.position(),
+                reason: format!("Invalid chunk section => {}", error)
+            }
+        })?;
+
+        let trailing = buffer.read_bytes(buffer.remaining())?;
+        Ok(Self {
+            x,
+            z,
+            min_y: dimension.min_y,
+            heightmaps,
+            sections,
+            trailing
+        })
+    }
+
+    pub fn write_versioned(&self, mut buffer: Buffer, protocol: i32) -> Result<Buffer, Error> {
+        if protocol < SECTION_PROTOCOL {
+            return Err(Error::Other(format!(
+                "Unable to write chunk => Protocol {} predates 1.18, chunks are supported from protocol {}",
+                protocol, SECTION_PROTOCOL
+            )))
+        }
+
+        buffer.write_i32(self.x)?;
+        buffer.write_i32(self.z)?;
+        let mut buffer = match protocol {
+            protocol if protocol >= 764 => self.heightmaps.write(buffer)?,
+            _ => {
+                self.heightmaps.write_named("", &mut buffer)?;
+                buffer
+            }
+        };
+
+        let data = self
+            .sections
+            .iter()
+            .try_fold(Buffer::empty(true, Some(ByteOrder::BigEndian)), |data, section| section.write(data))?;
+        buffer.write_byte_array(&data.to_bytes())?;
+        if !self.trailing.is_empty() {
+            buffer.write_bytes(&self.trailing)?;
+            return Ok(buffer)
+        }
+
+        buffer.write_var_i32(0)?;
+        LightData::default().write_versioned(buffer, protocol)
+    }
+}
+
+impl ChunkColumn {
+    // The trust edges flag was dropped with 1.20
+    pub fn light(&self, protocol: i32) -> Result<LightData, Error> {
+        if self.trailing.is_empty() {
+            return Ok(LightData::default())
+        }
+
+        let mut buffer = Buffer::new(self.trailing.clone(), false, Some(ByteOrder::BigEndian));
+        for _ in 0..buffer.read_length()? {
+            buffer.read_u8()?;
+            buffer.read_i16()?;
+            buffer.read_var_i32()?;
+            match buffer.read_u8()? {
+                0 => {}
+                _ if protocol >= 764 => {
+                    buffer.rewind(1)?;
+                    Compound::read(&mut buffer)?;
+                }
+                _ => {
+                    buffer.rewind(1)?;
+                    Compound::read_named(&mut buffer)?;
+                }
+            }
+        }
+        LightData::read_versioned(&mut buffer, protocol)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heightmaps {
+    min_y: i32,
+    height: u32,
+    bits_per_entry: u8,
+    heightmaps: BTreeMap<String, Vec<u32>>
+}
+
+impl Heightmaps {
+    pub fn new(dimension: &DimensionType) -> Self {
+        Self {
+            min_y: dimension.min_y,
+            height: dimension.height.max(0) as u32,
+            bits_per_entry: (u32::BITS - (dimension.height.max(0) as u32).leading_zeros()) as u8,
+            heightmaps: BTreeMap::new()
+        }
+    }
+
+    pub fn from_nbt(compound: &Compound, dimension: &DimensionType) -> Result<Self, Error> {
+        let mut heightmaps = Self::new(dimension);
+        let expected = packed_length(HEIGHTMAP_ENTRIES, heightmaps.bits_per_entry);
+        for (name, tag) in compound.iter() {
+            let data = match tag {
+                Tag::LongArray(data) if data.len() == expected => data,
+                Tag::LongArray(data) => return Err(Error::Other(format!("Heightmap {} needs {} longs, got {}", name, expected, data.len()))),
+                tag => return Err(Error::Other(format!("Heightmap {} isn't a long array (tag type {})", name, tag.id())))
+            };
+
+            let per_long = 64 / heightmaps.bits_per_entry as usize;
+            let mask = (1 << heightmaps.bits_per_entry) - 1;
+            let heights = (0..HEIGHTMAP_ENTRIES)
+                .map(|index| ((data[index / per_long] as u64 >> ((index % per_long) * heightmaps.bits_per_entry as usize)) & mask) as u32)
+                .collect();
+            heightmaps.heightmaps.insert(name.to_string(), heights);
+        }
+        Ok(heightmaps)
+    }
+
+    pub fn to_nbt(&self) -> Compound {
+        self.heightmaps.iter().fold(Compound::new(), |compound, (name, heights)| {
+            let data = pack(&heights.iter().map(|height| *height as u64).collect::<Vec<_>>(), self.bits_per_entry);
+            compound.with(name.as_str(), Tag::LongArray(data.into_iter().map(|long| long as i64).collect()))
+        })
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, heights: &[i32]) -> Result<(), Error> {
+        if heights.len() != HEIGHTMAP_ENTRIES {
+            return Err(Error::Other(format!("Heightmap needs {} heights, got {}", HEIGHTMAP_ENTRIES, heights.len())))
+        }
+
+        let heights = heights
+            .iter()
+            .map(|height| {
+                u32::try_from(*height as i64 - self.min_y as i64)
+                    .ok()
+                    .filter(|height| *height <= self.height)
+                    .ok_or_else(|| Error::Other(format!("Height {} is outside of the dimension", height)))
+            })
+            .collect::<Result<_, _>>()?;
+        self.heightmaps.insert(name.into(), heights);
+        Ok(())
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.heightmaps.keys().map(String::as_str)
+    }
+
+    pub fn get(&self, name: &str, x: usize, z: usize) -> Option<i32> {
+        if x >= SECTION_WIDTH || z >= SECTION_WIDTH {
+            return None
+        }
+        Some(*self.heightmaps.get(name)?.get(z * SECTION_WIDTH + x)? as i32 + self.min_y)
+    }
+
+    pub fn motion_blocking(&self, x: usize, z: usize) -> Option<i32> {
+        self.get("MOTION_BLOCKING", x, z)
+    }
+
+    pub fn world_surface(&self, x: usize, z: usize) -> Option<i32> {
+        self.get("WORLD_SURFACE", x, z)
+    }
+}
+
+fn read_bit_set(buffer: &mut Buffer) -> Result<BTreeSet<usize>, Error> {
+    let longs = Vec::<u64>::read(buffer)?;
+    Ok((0..longs.len() * 64).filter(|bit| longs[bit / 64] & (1 << (bit % 64)) != 0).collect())
+}
+
+fn write_bit_set(buffer: Buffer, bits: impl Iterator<Item = usize>) -> Result<Buffer, Error> {
+    let mut longs = Vec::new();
+    for bit in bits {
+        if longs.len() <= bit / 64 {
+            longs.resize(bit / 64 + 1, 0u64);
+        }
+        longs[bit / 64] |= 1 << (bit % 64);
+    }
+    longs.write(buffer)
+}
+
+// Light sections start one section below the world and end one above it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LightLayer {
+    pub sections: BTreeMap<usize, Vec<u8>>,
+    pub empty: BTreeSet<usize>
+}
+
+impl LightLayer {
+    pub fn insert(&mut self, section: usize, light: Vec<u8>) -> Result<(), Error> {
+        if light.len() != LIGHT_ARRAY_LENGTH {
+            return Err(Error::Other(format!("Light array needs {} bytes, got {}", LIGHT_ARRAY_LENGTH, light.len())))
+        }
+        self.empty.remove(&section);
+        self.sections.insert(section, light);
+        Ok(())
+    }
+
+    // y, z, x order with the lower nibble first
+    pub fn get(&self, section: usize, x: usize, y: usize, z: usize) -> Option<u8> {
+        if x >= SECTION_WIDTH || y >= SECTION_WIDTH || z >= SECTION_WIDTH {
+            return None
+        }
+
+        match self.sections.get(&section) {
+            Some(light) => {
+                let index = (y * SECTION_WIDTH + z) * SECTION_WIDTH + x;
+                Some((light[index / 2] >> ((index % 2) * 4)) & 0xF)
+            }
+            None if self.empty.contains(&section) => Some(0),
+            None => None
+        }
+    }
+
+    fn read_arrays(&mut self, buffer: &mut Buffer, mask: BTreeSet<usize>) -> Result<(), Error> {
+        let offset = buffer.position();
+        let count = buffer.read_length()?;
+        if count != mask.len() {
+            return Err(Error::Decode {
+                offset,
+                reason: format!("Light mask has {} sections, got {} arrays", mask.len(), count)
+            })
+        }
+
+        for section in mask {
+            let offset = buffer.position();
+            let light = buffer.read_byte_array()?;
+            if light.len() != LIGHT_ARRAY_LENGTH {
+                return Err(Error::Decode {
+                    offset,
+                    reason: format!("Light array needs {} bytes, got {}", LIGHT_ARRAY_LENGTH, light.len())
+                })
+            }
+            self.sections.insert(section, light);
+        }
+        Ok(())
+    }
+
+    fn write_arrays(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        buffer.write_var_i32(self.sections.len() as i32)?;
+        self.sections.values().try_for_each(|light| buffer.write_byte_array(light))?;
+        Ok(buffer)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LightData {
+    pub trust_edges: bool,
+    pub sky: LightLayer,
+    pub block: LightLayer
+}
+
+impl LightData {
+    pub fn sky_light(&self, section: usize, x: usize, y: usize, z: usize) -> Option<u8> {
+        self.sky.get(section, x, y, z)
+    }
+
+    pub fn block_light(&self, section: usize, x: usize, y: usize, z: usize) -> Option<u8> {
+        self.block.get(section, x, y, z)
+    }
+
+    pub fn read_versioned(buffer: &mut Buffer, protocol: i32) -> Result<Self, Error> {
+        let trust_edges = protocol < 763 && buffer.read_bool()?;
+        let sky_mask = read_bit_set(buffer)?;
+        let block_mask = read_bit_set(buffer)?;
+        let mut light = Self {
+            trust_edges,
+            sky: LightLayer {
+                sections: BTreeMap::new(),
+                empty: read_bit_set(buffer)?
+            },
+            block: LightLayer {
+                sections: BTreeMap::new(),
+                empty: read_bit_set(buffer)?
+            }
+        };
+
+        light.sky.read_arrays(buffer, sky_mask)?;
+        light.block.read_arrays(buffer, block_mask)?;
+        Ok(light)
+    }
+
+    pub fn write_versioned(&self, mut buffer: Buffer, protocol: i32) -> Result<Buffer, Error> {
+        if protocol < 763 {
+            buffer.write_bool(self.trust_edges)?;
+        }
+
+        let buffer = write_bit_set(buffer, self.sky.sections.keys().copied())?;
+        let buffer = write_bit_set(buffer, self.block.sections.keys().copied())?;
+        let buffer = write_bit_set(buffer, self.sky.empty.iter().copied())?;
+        let buffer = write_bit_set(buffer, self.block.empty.iter().copied())?;
+        let buffer = self.sky.write_arrays(buffer)?;
+        self.block.write_arrays(buffer)
+    }
+}
+
+impl Writable for LightData {
+    fn write(&self, buffer: Buffer) -> Result<Buffer, Error> {
+        self.write_versioned(buffer, i32::MAX)
+    }
+}
+
+impl Readable for LightData {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        Self::read_versioned(buffer, i32::MAX)
+    }
+}
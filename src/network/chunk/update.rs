@@ -0,0 +1,130 @@
+use crate::network::{
+    buffer::Buffer,
+    chunk::SECTION_WIDTH,
+    connection::{Readable, Writable},
+    Error
+};
+
+// 1.20 dropped the flag that told the client to skip light updates
+const LIGHT_FLAG_PROTOCOL: i32 = 763;
+
+// Block Update and Section Blocks Update
+pub fn packet_ids(protocol: i32) -> Option<(i32, i32)> {
+    Some(match protocol {
+        754 => (0x0B, 0x3B),
+        755..=758 => (0x0C, 0x3F),
+        759 => (0x09, 0x3D),
+        760 => (0x09, 0x40),
+        761 => (0x09, 0x3F),
+        762..=763 => (0x0A, 0x43),
+        764 => (0x09, 0x45),
+        765 => (0x09, 0x47),
+        766..=767 => (0x09, 0x49),
+        _ => return None
+    })
+}
+
+// Block positions pack x and z into 26 bits and y into the lowest 12
+pub fn pack_block_position(x: i32, y: i32, z: i32) -> i64 {
+    ((x as i64 & 0x3FF_FFFF) << 38) | ((z as i64 & 0x3FF_FFFF) << 12) | (y as i64 & 0xFFF)
+}
+
+pub fn unpack_block_position(packed: i64) -> (i32, i32, i32) {
+    ((packed >> 38) as i32, (packed << 52 >> 52) as i32, (packed << 26 >> 38) as i32)
+}
+
+// Section positions pack x and z into 22 bits and y into the lowest 20
+pub fn pack_section_position(x: i32, y: i32, z: i32) -> i64 {
+    ((x as i64 & 0x3F_FFFF) << 42) | ((z as i64 & 0x3F_FFFF) << 20) | (y as i64 & 0xF_FFFF)
+}
+
+pub fn unpack_section_position(packed: i64) -> (i32, i32, i32) {
+    ((packed >> 42) as i32, (packed << 44 >> 44) as i32, (packed << 22 >> 42) as i32)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockChange {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub state: i32
+}
+
+impl Writable for BlockChange {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        buffer.write_i64(pack_block_position(self.x, self.y, self.z))?;
+        buffer.write_var_i32(self.state)?;
+        Ok(buffer)
+    }
+}
+
+impl Readable for BlockChange {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        let (x, y, z) = unpack_block_position(buffer.read_i64()?);
+        Ok(Self {
+            x,
+            y,
+            z,
+            state: buffer.read_var_i32()?
+        })
+    }
+}
+
+// The state sits above the lowest 12 bits, which hold x, z and y with 4 bits each
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionBlocksUpdate {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub blocks: Vec<i64>
+}
+
+impl SectionBlocksUpdate {
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z, blocks: Vec::new() }
+    }
+
+    pub fn push(&mut self, x: usize, y: usize, z: usize, state: i32) -> Result<(), Error> {
+        if x >= SECTION_WIDTH || y >= SECTION_WIDTH || z >= SECTION_WIDTH {
+            return Err(Error::Other(format!("Block {} {} {} is outside of the section", x, y, z)))
+        }
+        self.blocks.push(((state as i64) << 12) | ((x as i64) << 8) | ((z as i64) << 4) | y as i64);
+        Ok(())
+    }
+
+    pub fn changes(&self) -> impl Iterator<Item = BlockChange> + '_ {
+        let width = SECTION_WIDTH as i32;
+        self.blocks.iter().map(move |block| {
+            BlockChange {
+                x: self.x * width + (block >> 8 & 0xF) as i32,
+                y: self.y * width + (block & 0xF) as i32,
+                z: self.z * width + (block >> 4 & 0xF) as i32,
+                state: (block >> 12) as i32
+            }
+        })
+    }
+
+    pub fn read_versioned(buffer: &mut Buffer, protocol: i32) -> Result<Self, Error> {
+        let (x, y, z) = unpack_section_position(buffer.read_i64()?);
+        if protocol < LIGHT_FLAG_PROTOCOL {
+            buffer.read_bool()?;
+        }
+
+        let count = buffer.read_length()?;
+        let blocks = (0..count).map(|_| buffer.read_var_i64()).collect::<Result<_, _>>()?;
+        Ok(Self { x, y, z, blocks })
+    }
+
+    pub fn write_versioned(&self, mut buffer: Buffer, protocol: i32) -> Result<Buffer, Error> {
+        buffer.write_i64(pack_section_position(self.x, self.y, self.z))?;
+        if protocol < LIGHT_FLAG_PROTOCOL {
+            buffer.write_bool(false)?;
+        }
+
+        buffer.write_var_i32(self.blocks.len() as i32)?;
+        for block in &self.blocks {
+            buffer.write_var_i64(*block)?;
+        }
+        Ok(buffer)
+    }
+}
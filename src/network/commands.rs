@@ -0,0 +1,432 @@
+use std::collections::{BTreeSet, HashSet};
+
+use crate::network::{buffer::Buffer, connection::Readable, decode::UnknownVariant, Error};
+
+pub const MAX_DEPTH: usize = 64;
+
+const TYPE_MASK: u8 = 0x03;
+const EXECUTABLE: u8 = 0x04;
+const HAS_REDIRECT: u8 = 0x08;
+const HAS_SUGGESTIONS: u8 = 0x10;
+
+const HAS_MIN: u8 = 0x01;
+const HAS_MAX: u8 = 0x02;
+
+// 1.19 replaced the parser identifiers with their index among the parsers of the version
+const PARSERS: &[(&str, i32, i32)] = &[
+    ("brigadier:bool", 759, i32::MAX),
+    ("brigadier:float", 759, i32::MAX),
+    ("brigadier:double", 759, i32::MAX),
+    ("brigadier:integer", 759, i32::MAX),
+    ("brigadier:long", 759, i32::MAX),
+    ("brigadier:string", 759, i32::MAX),
+    ("minecraft:entity", 759, i32::MAX),
+    ("minecraft:game_profile", 759, i32::MAX),
+    ("minecraft:block_pos", 759, i32::MAX),
+    ("minecraft:column_pos", 759, i32::MAX),
+    ("minecraft:vec3", 759, i32::MAX),
+    ("minecraft:vec2", 759, i32::MAX),
+    ("minecraft:block_state", 759, i32::MAX),
+    ("minecraft:block_predicate", 759, i32::MAX),
+    ("minecraft:item_stack", 759, i32::MAX),
+    ("minecraft:item_predicate", 759, i32::MAX),
+    ("minecraft:color", 759, i32::MAX),
+    ("minecraft:component", 759, i32::MAX),
+    ("minecraft:style", 765, i32::MAX),
+    ("minecraft:message", 759, i32::MAX),
+    ("minecraft:nbt_compound_tag", 759, i32::MAX),
+    ("minecraft:nbt_tag", 759, i32::MAX),
+    ("minecraft:nbt_path", 759, i32::MAX),
+    ("minecraft:objective", 759, i32::MAX),
+    ("minecraft:objective_criteria", 759, i32::MAX),
+    ("minecraft:operation", 759, i32::MAX),
+    ("minecraft:particle", 759, i32::MAX),
+    ("minecraft:angle", 759, i32::MAX),
+    ("minecraft:rotation", 759, i32::MAX),
+    ("minecraft:scoreboard_slot", 759, i32::MAX),
+    ("minecraft:score_holder", 759, i32::MAX),
+    ("minecraft:swizzle", 759, i32::MAX),
+    ("minecraft:team", 759, i32::MAX),
+    ("minecraft:item_slot", 759, i32::MAX),
+    ("minecraft:item_slots", 766, i32::MAX),
+    ("minecraft:resource_location", 759, i32::MAX),
+    ("minecraft:mob_effect", 759, 760),
+    ("minecraft:function", 759, i32::MAX),
+    ("minecraft:entity_anchor", 759, i32::MAX),
+    ("minecraft:int_range", 759, i32::MAX),
+    ("minecraft:float_range", 759, i32::MAX),
+    ("minecraft:item_enchantment", 759, 760),
+    ("minecraft:entity_summon", 759, 760),
+    ("minecraft:dimension", 759, i32::MAX),
+    ("minecraft:gamemode", 761, i32::MAX),
+    ("minecraft:time", 759, i32::MAX),
+    ("minecraft:resource_or_tag", 759, i32::MAX),
+    ("minecraft:resource_or_tag_key", 761, i32::MAX),
+    ("minecraft:resource", 759, i32::MAX),
+    ("minecraft:resource_key", 761, i32::MAX),
+    ("minecraft:template_mirror", 759, i32::MAX),
+    ("minecraft:template_rotation", 759, i32::MAX),
+    ("minecraft:heightmap", 762, i32::MAX),
+    ("minecraft:loot_table", 766, i32::MAX),
+    ("minecraft:loot_predicate", 766, i32::MAX),
+    ("minecraft:loot_modifier", 766, i32::MAX),
+    ("minecraft:uuid", 759, i32::MAX)
+];
+
+fn parser_name(protocol: i32, id: i32) -> Option<&'static str> {
+    let parsers = PARSERS.iter().filter(|(_, since, until)| (*since..=*until).contains(&protocol));
+    usize::try_from(id).ok().and_then(|index| parsers.clone().nth(index)).map(|(name, ..)| *name)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringMode {
+    SingleWord,
+    QuotablePhrase,
+    GreedyPhrase
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Parser {
+    Bool,
+    Float { min: Option<f32>, max: Option<f32> },
+    Double { min: Option<f64>, max: Option<f64> },
+    Integer { min: Option<i32>, max: Option<i32> },
+    Long { min: Option<i64>, max: Option<i64> },
+    String(StringMode),
+    Entity { single: bool, players_only: bool },
+    ScoreHolder { multiple: bool },
+    GameProfile,
+    BlockPos,
+    Vec3,
+    Message,
+    ResourceLocation,
+    Time { min: i32 },
+    Resource { parser: String, registry: String },
+    Other(String),
+    Unknown(i32)
+}
+
+impl UnknownVariant for Parser {
+    fn is_unknown(&self) -> bool {
+        matches!(self, Self::Unknown(_))
+    }
+}
+
+impl Parser {
+    fn read_range<T>(buffer: &mut Buffer, read: impl Fn(&mut Buffer) -> Result<T, Error>) -> Result<(Option<T>, Option<T>), Error> {
+        let flags = buffer.read_u8()?;
+        let min = if flags & HAS_MIN != 0 { Some(read(buffer)?) } else { None };
+        let max = if flags & HAS_MAX != 0 { Some(read(buffer)?) } else { None };
+        Ok((min, max))
+    }
+
+    fn read_named(buffer: &mut Buffer, name: &str, protocol: i32) -> Result<Self, Error> {
+        Ok(match name {
+            "brigadier:bool" => Parser::Bool,
+            "brigadier:float" => {
+                let (min, max) = Self::read_range(buffer, |buffer| Ok(f32::from_bits(buffer.read_u32()?)))?;
+                Parser::Float { min, max }
+            }
+            "brigadier:double" => {
+                let (min, max) = Self::read_range(buffer, |buffer| Ok(f64::from_bits(buffer.read_u64()?)))?;
+                Parser::Double { min, max }
+            }
+            "brigadier:integer" => {
+                let (min, max) = Self::read_range(buffer, Buffer::read_i32)?;
+                Parser::Integer { min, max }
+            }
+            "brigadier:long" => {
+                let (min, max) = Self::read_range(buffer, Buffer::read_i64)?;
+                Parser::Long { min, max }
+            }
+            "brigadier:string" => {
+                let offset = buffer.position();
+                Parser::String(match buffer.read_var_i32()? {
+                    0 => StringMode::SingleWord,
+                    1 => StringMode::QuotablePhrase,
+                    2 => StringMode::GreedyPhrase,
+                    mode => {
+                        return Err(Error::Decode {
+                            offset,
+                            reason: format!("Invalid string mode {}", mode)
+                        })
+                    }
+                })
+            }
+            "minecraft:entity" => {
+                let flags = buffer.read_u8()?;
+                Parser::Entity {
+                    single: flags & 0x01 != 0,
+                    players_only: flags & 0x02 != 0
+                }
+            }
+            "minecraft:score_holder" => {
+                Parser::ScoreHolder {
+                    multiple: buffer.read_u8()? & 0x01 != 0
+                }
+            }
+            "minecraft:game_profile" => Parser::GameProfile,
+            "minecraft:block_pos" => Parser::BlockPos,
+            "minecraft:vec3" => Parser::Vec3,
+            "minecraft:message" => Parser::Message,
+            "minecraft:resource_location" => Parser::ResourceLocation,
+            // The minimum duration was added together with /tick in 1.20.3
+            "minecraft:time" if protocol >= 765 => Parser::Time { min: buffer.read_i32()? },
+            "minecraft:time" => Parser::Time { min: 0 },
+            "minecraft:resource" | "minecraft:resource_key" | "minecraft:resource_or_tag" | "minecraft:resource_or_tag_key" => {
+                Parser::Resource {
+                    parser: name.to_string(),
+                    registry: buffer.read_string()?
+                }
+            }
+            name => Parser::Other(name.to_string())
+        })
+    }
+
+    // Parsers are sent by their identifier before 1.19
+    pub fn read_versioned(buffer: &mut Buffer, protocol: i32) -> Result<Self, Error> {
+        if protocol < 759 {
+            let name = buffer.read_string()?;
+            return Self::read_named(buffer, &name, protocol)
+        }
+
+        let id = buffer.read_var_i32()?;
+        match parser_name(protocol, id) {
+            Some(name) => Self::read_named(buffer, name, protocol),
+            None => Ok(Parser::Unknown(id))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind {
+    Root,
+    Literal(String),
+    Argument { name: String, parser: Parser, suggestions: Option<String> }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandNode {
+    pub kind: NodeKind,
+    pub executable: bool,
+    children: Vec<usize>,
+    redirect: Option<usize>
+}
+
+impl CommandNode {
+    pub fn name(&self) -> Option<&str> {
+        match &self.kind {
+            NodeKind::Root => None,
+            NodeKind::Literal(name) | NodeKind::Argument { name, .. } => Some(name)
+        }
+    }
+
+    pub fn is_literal(&self) -> bool {
+        matches!(self.kind, NodeKind::Literal(_))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandTree {
+    nodes: Vec<CommandNode>,
+    root: usize
+}
+
+impl CommandTree {
+    pub fn read_versioned(buffer: &mut Buffer, protocol: i32) -> Result<Self, Error> {
+        let count = buffer.read_length()?;
+        let mut nodes = Vec::with_capacity(count.min(buffer.remaining()));
+        let mut references = Vec::new();
+        for _ in 0..count {
+            let flags = buffer.read_u8()?;
+            let children = (0..buffer.read_length()?)
+                .map(|_| Ok((buffer.position(), buffer.read_var_i32()?)))
+                .collect::<Result<Vec<_>, Error>>()?;
+            let redirect = if flags & HAS_REDIRECT != 0 {
+                Some((buffer.position(), buffer.read_var_i32()?))
+            } else {
+                None
+            };
+
+            let offset = buffer.position();
+            let kind = match flags & TYPE_MASK {
+                0 => NodeKind::Root,
+                1 => NodeKind::Literal(buffer.read_string()?),
+                2 => {
+                    NodeKind::Argument {
+                        name: buffer.read_string()?,
+                        parser: Parser::read_versioned(buffer, protocol)?,
+                        suggestions: if flags & HAS_SUGGESTIONS != 0 { Some(buffer.read_string()?) } else { None }
+                    }
+                }
+                kind => {
+                    return Err(Error::Decode {
+                        offset: offset - 1,
+                        reason: format!("Invalid command node type {}", kind)
+                    })
+                }
+            };
+
+            references.extend(children.iter().chain(redirect.iter()).copied());
+            nodes.push(CommandNode {
+                kind,
+                executable: flags & EXECUTABLE != 0,
+                children: children.into_iter().map(|(_, child)| child as usize).collect(),
+                redirect: redirect.map(|(_, redirect)| redirect as usize)
+            });
+        }
+
+        let root_offset = buffer.position();
+        let root = buffer.read_var_i32()?;
+        for (offset, index) in references.into_iter().chain([(root_offset, root)]) {
+            if index < 0 || index as usize >= nodes.len() {
+                return Err(Error::Decode {
+                    offset,
+                    reason: format!("Command node index {} is out of bounds for {} nodes", index, nodes.len())
+                })
+            }
+        }
+        Ok(Self { nodes, root: root as usize })
+    }
+
+    pub fn root(&self) -> &CommandNode {
+        &self.nodes[self.root]
+    }
+
+    pub fn nodes(&self) -> &[CommandNode] {
+        &self.nodes
+    }
+
+    pub fn children<'a>(&'a self, node: &'a CommandNode) -> impl Iterator<Item = &'a CommandNode> {
+        node.children.iter().map(|child| &self.nodes[*child])
+    }
+
+    pub fn redirect(&self, node: &CommandNode) -> Option<&CommandNode> {
+        node.redirect.map(|redirect| &self.nodes[redirect])
+    }
+
+    fn continuations(&self, node: usize) -> Vec<usize> {
+        let mut visited = HashSet::new();
+        let mut current = node;
+        while self.nodes[current].children.is_empty() && visited.len() < MAX_DEPTH && visited.insert(current) {
+            match self.nodes[current].redirect {
+                Some(redirect) => current = redirect,
+                None => return Vec::new()
+            }
+        }
+        self.nodes[current].children.clone()
+    }
+
+    fn walk(&self, words: &[&str]) -> Vec<usize> {
+        let mut current = vec![self.root];
+        for word in words.iter().take(MAX_DEPTH) {
+            let next = current
+                .iter()
+                .flat_map(|node| self.continuations(*node))
+                .filter(|child| {
+                    match &self.nodes[*child].kind {
+                        NodeKind::Literal(name) => name == word,
+                        NodeKind::Argument { .. } => true,
+                        NodeKind::Root => false
+                    }
+                })
+                .collect::<BTreeSet<_>>();
+            current = next.into_iter().collect();
+        }
+        current
+    }
+
+    pub fn suggest(&self, prefix: &str) -> Vec<String> {
+        let mut words = prefix.split(' ').collect::<Vec<_>>();
+        let partial = words.pop().unwrap_or_default();
+        if words.len() > MAX_DEPTH {
+            return Vec::new()
+        }
+
+        self.walk(&words)
+            .into_iter()
+            .flat_map(|node| self.continuations(node))
+            .filter_map(|child| {
+                match &self.nodes[child].kind {
+                    NodeKind::Literal(name) if name.starts_with(partial) => Some(name.clone()),
+                    _ => None
+                }
+            })
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    pub fn signable_arguments(&self, command: &str) -> Vec<(String, String)> {
+        self.parse(self.root, command, 0).unwrap_or_default()
+    }
+
+    // Like brigadier, a matching literal hides the arguments next to it
+    fn parse(&self, node: usize, input: &str, depth: usize) -> Option<Vec<(String, String)>> {
+        if input.is_empty() {
+            return Some(Vec::new())
+        }
+        if depth >= MAX_DEPTH {
+            return None
+        }
+
+        let children = self.continuations(node);
+        let literal = children.iter().find_map(|child| {
+            match &self.nodes[*child].kind {
+                NodeKind::Literal(name) => input.strip_prefix(name.as_str()).and_then(next_word).map(|rest| (*child, rest)),
+                _ => None
+            }
+        });
+        if let Some((child, rest)) = literal {
+            return self.parse(child, rest, depth + 1)
+        }
+
+        children.into_iter().find_map(|child| {
+            match &self.nodes[child].kind {
+                NodeKind::Argument { name, parser: Parser::Message, .. } => Some(vec![(name.clone(), input.to_string())]),
+                NodeKind::Argument { parser, .. } => self.parse(child, skip_argument(input, parser)?, depth + 1),
+                _ => None
+            }
+        })
+    }
+}
+
+fn next_word(rest: &str) -> Option<&str> {
+    match rest.strip_prefix(' ') {
+        Some(rest) => Some(rest),
+        None => rest.is_empty().then_some(rest)
+    }
+}
+
+fn skip_argument<'a>(input: &'a str, parser: &Parser) -> Option<&'a str> {
+    let words = match parser {
+        Parser::String(StringMode::GreedyPhrase) => return Some(""),
+        Parser::String(StringMode::QuotablePhrase) if input.starts_with('"') => {
+            let mut escaped = false;
+            let end = input.char_indices().skip(1).find(|(_, char)| {
+                let end = !escaped && *char == '"';
+                escaped = !escaped && *char == '\\';
+                end
+            })?;
+            return next_word(&input[end.0 + 1..])
+        }
+        Parser::BlockPos | Parser::Vec3 => 3,
+        Parser::Other(name) if matches!(name.as_str(), "minecraft:column_pos" | "minecraft:vec2" | "minecraft:rotation") => 2,
+        _ => 1
+    };
+
+    let mut rest = input;
+    for _ in 0..words {
+        if rest.is_empty() {
+            return None
+        }
+        rest = rest.split_once(' ').map(|(_, rest)| rest).unwrap_or_default();
+    }
+    Some(rest)
+}
+
+impl Readable for CommandTree {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        Self::read_versioned(buffer, i32::MAX)
+    }
+}
@@ -0,0 +1,183 @@
+use std::{
+    io::{self, ErrorKind},
+    net::Shutdown,
+    sync::Arc
+};
+
+use socket2::SockRef;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream
+};
+
+use crate::network::{
+    buffer::Buffer,
+    connection::{
+        codec::ProtocolCodec,
+        machine::{DecodedPacket, ProtocolMachine},
+        stats::PacketStats,
+        unknown_protocol_version, Pipeline, Role, VersionedWritable, Writable, READ_CHUNK_LENGTH
+    },
+    cookie::CookieJar,
+    decode::{DecodeMode, DecodeReport},
+    ByteOrder, Error, PacketDirection, PacketState
+};
+
+pub struct AsyncSocketConnection<'a, R: Role> {
+    machine: ProtocolMachine<'a, R>,
+    socket: TcpStream
+}
+
+impl<'a, R: Role> AsyncSocketConnection<'a, R> {
+    pub fn new(socket: TcpStream, pipeline: Pipeline<'a>) -> Self {
+        Self {
+            machine: ProtocolMachine::new(pipeline),
+            socket
+        }
+    }
+
+    pub async fn write<T: Writable>(&mut self, packet: T) -> Result<usize, Error> {
+        let length = self.machine.queue_outgoing(packet)?;
+        self.flush().await?;
+        Ok(length)
+    }
+
+    pub async fn read_packet(&mut self) -> Result<Buffer, Error> {
+        let (_, buffer) = self.read_frame().await?;
+        Ok(buffer)
+    }
+
+    pub async fn read_decoded(&mut self) -> Result<DecodedPacket, Error> {
+        let (state, mut buffer) = self.read_frame().await?;
+        let id = buffer.read_var_i32()?;
+        buffer.reset();
+        Ok(DecodedPacket { state, id, buffer })
+    }
+
+    async fn read_frame(&mut self) -> Result<(PacketState, Buffer), Error> {
+        loop {
+            if let Some(frame) = self.machine.poll_frame()? {
+                self.flush().await?;
+                return Ok(frame)
+            }
+
+            let mut read = [0; READ_CHUNK_LENGTH];
+            let size = self.socket.read(&mut read).await?;
+            if size == 0 {
+                return Err(io::Error::from(ErrorKind::UnexpectedEof).into())
+            }
+            self.machine.feed_incoming(&read[..size]);
+        }
+    }
+
+    pub fn state(&self) -> PacketState {
+        self.machine.state()
+    }
+
+    pub fn set_state(&mut self, state: PacketState) {
+        self.machine.set_state(state);
+    }
+
+    pub fn protocol_version(&self) -> Option<i32> {
+        self.codec().protocol_version()
+    }
+
+    pub fn set_protocol_version(&mut self, protocol_version: Option<i32>) {
+        self.codec_mut().set_protocol_version(protocol_version);
+    }
+
+    pub fn require_protocol_version(&self, operation: &str) -> Result<i32, Error> {
+        self.codec().protocol_version().ok_or_else(|| unknown_protocol_version(operation))
+    }
+
+    pub async fn write_versioned(&mut self, packet: &dyn VersionedWritable) -> Result<usize, Error> {
+        let protocol = self.require_protocol_version("write versioned packet")?;
+        self.write(packet.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), protocol)?).await
+    }
+
+    pub fn read_direction() -> PacketDirection {
+        R::read_direction()
+    }
+
+    pub fn write_direction() -> PacketDirection {
+        R::write_direction()
+    }
+
+    pub fn enable_encryption(&mut self, shared_secret: &[u8; 16]) {
+        self.machine.enable_encryption(shared_secret);
+    }
+
+    pub fn enable_compression(&mut self, threshold: i32) {
+        self.codec_mut().enable_compression(threshold);
+    }
+
+    pub fn disable_compression(&mut self) {
+        self.codec_mut().disable_compression();
+    }
+
+    pub fn set_packet_stats(&mut self, packet_stats: Option<Arc<PacketStats>>) {
+        self.codec_mut().set_packet_stats(packet_stats);
+    }
+
+    pub fn packet_stats(&self) -> Option<&Arc<PacketStats>> {
+        self.codec().packet_stats()
+    }
+
+    pub fn set_cookie_jar(&mut self, cookie_jar: Option<Arc<CookieJar>>) {
+        self.codec_mut().set_cookie_jar(cookie_jar);
+    }
+
+    pub fn cookie_jar(&self) -> Option<&Arc<CookieJar>> {
+        self.codec().cookie_jar()
+    }
+
+    pub fn set_decode_mode(&mut self, decode_mode: DecodeMode) {
+        self.codec_mut().set_decode_mode(decode_mode);
+    }
+
+    pub fn decode_mode(&self) -> DecodeMode {
+        self.codec().decode_mode()
+    }
+
+    pub fn decode_report(&self) -> &Arc<DecodeReport> {
+        self.codec().decode_report()
+    }
+
+    pub fn pipeline(&self) -> &Pipeline<'a> {
+        self.codec().pipeline()
+    }
+
+    pub fn machine(&self) -> &ProtocolMachine<'a, R> {
+        &self.machine
+    }
+
+    pub fn machine_mut(&mut self) -> &mut ProtocolMachine<'a, R> {
+        &mut self.machine
+    }
+
+    pub fn codec(&self) -> &ProtocolCodec<'a, R> {
+        self.machine.codec()
+    }
+
+    pub fn codec_mut(&mut self) -> &mut ProtocolCodec<'a, R> {
+        self.machine.codec_mut()
+    }
+
+    pub fn socket(&self) -> &TcpStream {
+        &self.socket
+    }
+
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        if self.machine.has_outgoing() {
+            self.socket.write_all(&self.machine.take_outgoing()).await?;
+            self.socket.flush().await?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, R: Role> Drop for AsyncSocketConnection<'a, R> {
+    fn drop(&mut self) {
+        let _ = SockRef::from(&self.socket).shutdown(Shutdown::Write);
+    }
+}
@@ -0,0 +1,259 @@
+use std::{marker::PhantomData, mem, sync::Arc};
+
+#[cfg(feature = "compression")]
+use crate::network::connection::pipeline::compression::{CompressionDecoder, CompressionEncoder};
+#[cfg(feature = "encryption")]
+use crate::network::encryption::Cipher;
+use crate::network::{
+    buffer::Buffer,
+    connection::{stats::PacketStats, Pipeline, Role, Writable, MAXIMUM_FRAME_LENGTH},
+    cookie::CookieJar,
+    decode::{DecodeMode, DecodeReport},
+    ByteOrder, Error, PacketDirection, PacketState
+};
+
+pub struct ProtocolCodec<'a, R: Role> {
+    packet_state: PacketState,
+    protocol_version: Option<i32>,
+    pipeline: Pipeline<'a>,
+    #[cfg(feature = "encryption")]
+    cipher: Option<Cipher>,
+    packet_stats: Option<Arc<PacketStats>>,
+    cookie_jar: Option<Arc<CookieJar>>,
+    decode_mode: DecodeMode,
+    decode_report: Arc<DecodeReport>,
+    input: Vec<u8>,
+    // Frames are only decrypted once decoded, so the ones from before encryption stay readable
+    decrypted: usize,
+    output: Vec<u8>,
+    role: PhantomData<R>
+}
+
+impl<'a, R: Role> ProtocolCodec<'a, R> {
+    pub fn new(pipeline: Pipeline<'a>) -> Self {
+        Self {
+            packet_state: PacketState::Handshaking,
+            protocol_version: None,
+            pipeline,
+            #[cfg(feature = "encryption")]
+            cipher: None,
+            packet_stats: None,
+            cookie_jar: None,
+            decode_mode: DecodeMode::Strict,
+            decode_report: Arc::new(DecodeReport::new()),
+            input: Vec::new(),
+            decrypted: 0,
+            output: Vec::new(),
+            role: PhantomData
+        }
+    }
+
+    pub fn encode<T: Writable>(&mut self, packet: T) -> Result<usize, Error> {
+        let mut buffer = packet.write(Buffer::empty(true, Some(ByteOrder::BigEndian)))?;
+        if let Some(packet_stats) = &self.packet_stats {
+            packet_stats.record_buffer(self.packet_state, R::write_direction(), &mut buffer);
+        }
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_packet_size(R::write_direction(), buffer.len());
+
+        #[cfg_attr(not(feature = "encryption"), allow(unused_mut))]
+        let mut bytes = self.pipeline.encode(buffer)?.to_bytes();
+        #[cfg(feature = "encryption")]
+        if let Some(cipher) = &mut self.cipher {
+            cipher.encrypt(&mut bytes);
+        }
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_connection_bytes(R::write_direction(), bytes.len());
+        self.output.extend_from_slice(&bytes);
+        Ok(bytes.len())
+    }
+
+    pub fn take_output(&mut self) -> Vec<u8> {
+        mem::take(&mut self.output)
+    }
+
+    pub fn has_output(&self) -> bool {
+        !self.output.is_empty()
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.input.extend_from_slice(bytes);
+    }
+
+    pub fn buffered(&self) -> usize {
+        self.input.len()
+    }
+
+    pub fn take_input(&mut self) -> Vec<u8> {
+        self.decrypt_to(self.input.len());
+        self.decrypted = 0;
+        mem::take(&mut self.input)
+    }
+
+    pub fn take_input_up_to(&mut self, length: usize) -> Vec<u8> {
+        let end = length.min(self.input.len());
+        self.decrypt_to(end);
+        self.decrypted -= end;
+        self.input.drain(..end).collect()
+    }
+
+    pub fn decode(&mut self) -> Result<Option<Buffer>, Error> {
+        let mut length = 0;
+        let mut header = 0;
+        loop {
+            if header == self.input.len() {
+                return Ok(None)
+            }
+
+            self.decrypt_to(header + 1);
+            let byte = self.input[header];
+            length |= ((byte & 0b0111_1111) as i32) << (7 * header);
+            header += 1;
+            if byte & 0b1000_0000 == 0 {
+                break;
+            }
+
+            if header == 3 {
+                return Err(Error::Decode {
+                    offset: header - 1,
+                    reason: "Frame length is longer than 3 bytes".to_string()
+                })
+            }
+        }
+
+        if length > MAXIMUM_FRAME_LENGTH {
+            return Err(Error::Decode {
+                offset: 0,
+                reason: format!("Frame length {} exceeds the maximum of {}", length, MAXIMUM_FRAME_LENGTH)
+            })
+        }
+
+        let end = header + length as usize;
+        if self.input.len() < end {
+            return Ok(None)
+        }
+
+        // The frame reuses the allocation of the input, only the bytes after it are copied out
+        self.decrypt_to(end);
+        let remainder = self.input.split_off(end);
+        let mut frame = mem::replace(&mut self.input, remainder);
+        frame.drain(..header);
+        self.decrypted -= end;
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_connection_bytes(R::read_direction(), end);
+
+        let mut buffer = self.pipeline.decode(Buffer::new(frame, true, Some(ByteOrder::BigEndian)))?;
+        buffer.reset();
+        if let Some(packet_stats) = &self.packet_stats {
+            packet_stats.record_buffer(self.packet_state, R::read_direction(), &mut buffer);
+        }
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_packet_size(R::read_direction(), buffer.len());
+
+        let response = match &self.cookie_jar {
+            Some(cookie_jar) => cookie_jar.handle(self.packet_state, R::read_direction(), &mut buffer, self.decode_mode, &self.decode_report)?,
+            None => None
+        };
+        if let Some(response) = response {
+            self.encode(response)?;
+        }
+        Ok(Some(buffer))
+    }
+
+    fn decrypt_to(&mut self, end: usize) {
+        if self.decrypted >= end {
+            return
+        }
+
+        #[cfg(feature = "encryption")]
+        if let Some(cipher) = &mut self.cipher {
+            cipher.decrypt(&mut self.input[self.decrypted..end]);
+        }
+        self.decrypted = end;
+    }
+
+    pub fn state(&self) -> PacketState {
+        self.packet_state
+    }
+
+    pub fn set_state(&mut self, state: PacketState) {
+        self.packet_state = state;
+    }
+
+    pub fn protocol_version(&self) -> Option<i32> {
+        self.protocol_version
+    }
+
+    pub fn set_protocol_version(&mut self, protocol_version: Option<i32>) {
+        self.protocol_version = protocol_version;
+    }
+
+    pub fn read_direction() -> PacketDirection {
+        R::read_direction()
+    }
+
+    pub fn write_direction() -> PacketDirection {
+        R::write_direction()
+    }
+
+    #[cfg(feature = "encryption")]
+    pub fn enable_encryption(&mut self, shared_secret: &[u8; 16]) {
+        self.cipher = Some(Cipher::new(shared_secret));
+    }
+
+    #[cfg(feature = "encryption")]
+    pub fn is_encrypted(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    // Set Compression may arrive again, a negative threshold turns compression off
+    #[cfg(feature = "compression")]
+    pub fn enable_compression(&mut self, threshold: i32) {
+        self.disable_compression();
+        if threshold < 0 {
+            return
+        }
+
+        self.pipeline = mem::take(&mut self.pipeline)
+            .add_first_encoder(CompressionEncoder::new(threshold), Some("compression"))
+            .add_first_decoder(CompressionDecoder::new(threshold), Some("decompression"));
+    }
+
+    #[cfg(feature = "compression")]
+    pub fn disable_compression(&mut self) {
+        self.pipeline.remove_encoder("compression");
+        self.pipeline.remove_decoder("decompression");
+    }
+
+    pub fn set_packet_stats(&mut self, packet_stats: Option<Arc<PacketStats>>) {
+        self.packet_stats = packet_stats;
+    }
+
+    pub fn packet_stats(&self) -> Option<&Arc<PacketStats>> {
+        self.packet_stats.as_ref()
+    }
+
+    pub fn set_cookie_jar(&mut self, cookie_jar: Option<Arc<CookieJar>>) {
+        self.cookie_jar = cookie_jar;
+    }
+
+    pub fn cookie_jar(&self) -> Option<&Arc<CookieJar>> {
+        self.cookie_jar.as_ref()
+    }
+
+    pub fn set_decode_mode(&mut self, decode_mode: DecodeMode) {
+        self.decode_mode = decode_mode;
+    }
+
+    pub fn decode_mode(&self) -> DecodeMode {
+        self.decode_mode
+    }
+
+    pub fn decode_report(&self) -> &Arc<DecodeReport> {
+        &self.decode_report
+    }
+
+    pub fn pipeline(&self) -> &Pipeline<'a> {
+        &self.pipeline
+    }
+}
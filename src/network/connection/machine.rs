@@ -0,0 +1,157 @@
+use crate::network::{
+    buffer::Buffer,
+    connection::{codec::ProtocolCodec, Pipeline, Role, Writable},
+    handshake::{Handshake, NextState, HANDSHAKE},
+    ByteOrder, Error, PacketDirection, PacketState
+};
+
+const CLIENTBOUND_LOGIN_SUCCESS: i32 = 0x02;
+#[cfg(feature = "compression")]
+const CLIENTBOUND_SET_COMPRESSION: i32 = 0x03;
+const SERVERBOUND_LOGIN_ACKNOWLEDGED: i32 = 0x03;
+
+// The configuration state was added with 1.20.2
+const CONFIGURATION_PROTOCOL: i32 = 764;
+
+// Serverbound Acknowledge Finish Configuration
+fn finish_configuration_id(protocol: i32) -> i32 {
+    match protocol >= 766 {
+        true => 0x03,
+        false => 0x02
+    }
+}
+
+// Serverbound Acknowledge Configuration
+fn acknowledge_configuration_id(protocol: i32) -> Option<i32> {
+    match protocol {
+        764..=765 => Some(0x0B),
+        766..=767 => Some(0x0C),
+        _ => None
+    }
+}
+
+fn next_state(direction: PacketDirection, state: PacketState, protocol: i32, id: i32) -> Option<PacketState> {
+    match (direction, state, id) {
+        (PacketDirection::Serverbound, PacketState::Login, SERVERBOUND_LOGIN_ACKNOWLEDGED) if protocol >= CONFIGURATION_PROTOCOL => Some(PacketState::Configuration),
+        (PacketDirection::Serverbound, PacketState::Configuration, id) if id == finish_configuration_id(protocol) => Some(PacketState::Play),
+        (PacketDirection::Serverbound, PacketState::Play, id) if Some(id) == acknowledge_configuration_id(protocol) => Some(PacketState::Configuration),
+        (PacketDirection::Clientbound, PacketState::Login, CLIENTBOUND_LOGIN_SUCCESS) if protocol < CONFIGURATION_PROTOCOL => Some(PacketState::Play),
+        _ => None
+    }
+}
+
+#[derive(Debug)]
+pub struct DecodedPacket {
+    pub state: PacketState,
+    pub id: i32,
+    pub buffer: Buffer
+}
+
+pub struct ProtocolMachine<'a, R: Role> {
+    codec: ProtocolCodec<'a, R>
+}
+
+impl<'a, R: Role> ProtocolMachine<'a, R> {
+    pub fn new(pipeline: Pipeline<'a>) -> Self {
+        Self {
+            codec: ProtocolCodec::new(pipeline)
+        }
+    }
+
+    pub fn feed_incoming(&mut self, bytes: &[u8]) {
+        self.codec.feed(bytes);
+    }
+
+    pub fn poll_packet(&mut self) -> Result<Option<DecodedPacket>, Error> {
+        let Some((state, mut buffer)) = self.poll_frame()? else { return Ok(None) };
+        let id = buffer.read_var_i32()?;
+        buffer.reset();
+        Ok(Some(DecodedPacket { state, id, buffer }))
+    }
+
+    pub fn poll_frame(&mut self) -> Result<Option<(PacketState, Buffer)>, Error> {
+        let Some(mut buffer) = self.codec.decode()? else { return Ok(None) };
+        let state = self.codec.state();
+        self.follow(R::read_direction(), &mut buffer);
+        Ok(Some((state, buffer)))
+    }
+
+    pub fn queue_outgoing<T: Writable>(&mut self, packet: T) -> Result<usize, Error> {
+        let buffer = packet.write(Buffer::empty(true, Some(ByteOrder::BigEndian)))?;
+        let mut packet = Buffer::new(buffer.to_bytes(), false, Some(ByteOrder::BigEndian));
+
+        // The packet still goes out in the state and with the compression it was written in
+        let length = self.codec.encode(buffer)?;
+        self.follow(R::write_direction(), &mut packet);
+        Ok(length)
+    }
+
+    pub fn take_outgoing(&mut self) -> Vec<u8> {
+        self.codec.take_output()
+    }
+
+    pub fn has_outgoing(&self) -> bool {
+        self.codec.has_output()
+    }
+
+    fn follow(&mut self, direction: PacketDirection, packet: &mut Buffer) {
+        let state = self.codec.state();
+        let Ok(id) = packet.read_var_i32() else {
+            packet.reset();
+            return
+        };
+        match (direction, state, id) {
+            (PacketDirection::Serverbound, PacketState::Handshaking, HANDSHAKE) => {
+                packet.reset();
+                if let Ok(handshake) = Handshake::read(packet) {
+                    self.codec.set_protocol_version(Some(handshake.protocol_version));
+                    self.codec.set_state(match handshake.next_state {
+                        NextState::Status => PacketState::Status,
+                        NextState::Login | NextState::Transfer => PacketState::Login
+                    });
+                }
+            }
+            #[cfg(feature = "compression")]
+            (PacketDirection::Clientbound, PacketState::Login, CLIENTBOUND_SET_COMPRESSION) => {
+                if let Ok(threshold) = packet.read_var_i32() {
+                    self.codec.enable_compression(threshold);
+                }
+            }
+            _ => {
+                if let Some(next_state) = next_state(direction, state, self.protocol(), id) {
+                    self.codec.set_state(next_state);
+                }
+            }
+        }
+        packet.reset();
+    }
+
+    #[cfg(feature = "encryption")]
+    pub fn enable_encryption(&mut self, shared_secret: &[u8; 16]) {
+        self.codec.enable_encryption(shared_secret);
+    }
+
+    pub fn state(&self) -> PacketState {
+        self.codec.state()
+    }
+
+    pub fn set_state(&mut self, state: PacketState) {
+        self.codec.set_state(state);
+    }
+
+    pub fn protocol(&self) -> i32 {
+        self.codec.protocol_version().unwrap_or(0)
+    }
+
+    pub fn set_protocol(&mut self, protocol: i32) {
+        self.codec.set_protocol_version(Some(protocol));
+    }
+
+    pub fn codec(&self) -> &ProtocolCodec<'a, R> {
+        &self.codec
+    }
+
+    pub fn codec_mut(&mut self) -> &mut ProtocolCodec<'a, R> {
+        &mut self.codec
+    }
+}
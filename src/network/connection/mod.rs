@@ -1,52 +1,199 @@
-use std::{mem::size_of, time::Duration};
+use std::{
+    any::type_name,
+    fmt::{Display, Formatter},
+    ops::{Add, Deref, Sub},
+    sync::Arc,
+    time::Duration
+};
 
 use uuid::Uuid;
 
-use crate::network::{buffer::Buffer, ByteOrder, Error, PacketDirection, PacketState};
+use crate::network::{
+    buffer::Buffer,
+    bundle::{delimiter, BUNDLE_PROTOCOL, MAX_BUNDLE_PACKETS},
+    decode::{DecodeMode, DecodeReport},
+    ByteOrder, Error, PacketDirection, PacketState
+};
 
+#[cfg(feature = "std-net")]
+pub mod async_socket;
+pub mod codec;
+pub mod machine;
 pub mod pipeline;
+#[cfg(feature = "std-net")]
 pub mod socket;
+pub mod stats;
+
+pub const MAXIMUM_FRAME_LENGTH: i32 = 2097151;
+
+pub(crate) fn unknown_protocol_version(operation: &str) -> Error {
+    Error::Other(format!("Unable to {} => The protocol version of the connection isn't known yet", operation))
+}
+
+#[cfg(feature = "std-net")]
+pub(crate) const READ_CHUNK_LENGTH: usize = 4096;
 
 pub trait Connection<'a, S> {
     fn new(object: S, pipeline: Pipeline<'a>) -> Self;
 
     fn write<T: Writable>(&mut self, packet: T) -> Result<usize, Error>;
     fn read_buffer(&mut self, timeout: Option<Duration>, order: ByteOrder) -> Result<(Buffer, Duration), Error>;
+    fn read_packet(&mut self) -> Result<Buffer, Error>;
 
     fn state(&self) -> PacketState;
-    fn bound() -> PacketDirection;
+    fn set_state(&mut self, state: PacketState);
+    fn read_direction() -> PacketDirection;
+    fn write_direction() -> PacketDirection;
+
+    fn protocol_version(&self) -> Option<i32>;
+    fn set_protocol_version(&mut self, protocol_version: Option<i32>);
+
+    fn decode_mode(&self) -> DecodeMode;
+    fn set_decode_mode(&mut self, decode_mode: DecodeMode);
+    fn decode_report(&self) -> &Arc<DecodeReport>;
+
+    fn require_protocol_version(&self, operation: &str) -> Result<i32, Error> {
+        self.protocol_version().ok_or_else(|| unknown_protocol_version(operation))
+    }
+
+    fn write_versioned(&mut self, packet: &dyn VersionedWritable) -> Result<usize, Error> {
+        let protocol = self.require_protocol_version("write versioned packet")?;
+        self.write(packet.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), protocol)?)
+    }
+
+    fn write_bundle(&mut self, packets: &[&dyn Writable]) -> Result<usize, Error>
+    where
+        Self: Sized
+    {
+        if Self::write_direction() != PacketDirection::Clientbound || self.state() != PacketState::Play {
+            return Err(Error::Other(format!(
+                "Unable to write bundle => Bundles are clientbound play packets, not {} {:?}",
+                Self::write_direction(),
+                self.state()
+            )))
+        }
+        let protocol = self.require_protocol_version("write bundle")?;
+        if protocol < BUNDLE_PROTOCOL {
+            return Err(Error::Other(format!("Unable to write bundle => Protocol {} has no bundles, they came with 1.19.4", protocol)))
+        }
+        if packets.len() > MAX_BUNDLE_PACKETS {
+            return Err(Error::Other(format!(
+                "Unable to write bundle => {} packets are more than the {} of a bundle",
+                packets.len(),
+                MAX_BUNDLE_PACKETS
+            )))
+        }
+
+        let mut buffers = vec![delimiter()?];
+        for packet in packets {
+            buffers.push(packet.write(Buffer::empty(true, Some(ByteOrder::BigEndian)))?);
+        }
+        buffers.push(delimiter()?);
+        buffers.into_iter().try_fold(0, |length, buffer| Ok(length + self.write(buffer)?))
+    }
+}
+
+pub trait Role {
+    fn read_direction() -> PacketDirection;
+    fn write_direction() -> PacketDirection;
+}
+
+pub struct Client;
+
+impl Role for Client {
+    fn read_direction() -> PacketDirection {
+        PacketDirection::Clientbound
+    }
+
+    fn write_direction() -> PacketDirection {
+        PacketDirection::Serverbound
+    }
+}
+
+pub struct Server;
+
+impl Role for Server {
+    fn read_direction() -> PacketDirection {
+        PacketDirection::Serverbound
+    }
+
+    fn write_direction() -> PacketDirection {
+        PacketDirection::Clientbound
+    }
 }
 
+pub trait PipelineStage {
+    fn process(&mut self, buffer: Buffer) -> Result<Buffer, Error>;
+}
+
+impl<T: Writable> PipelineStage for T {
+    fn process(&mut self, buffer: Buffer) -> Result<Buffer, Error> {
+        self.write(buffer)
+    }
+}
+
+type Stage<'a> = (Option<&'a str>, Box<dyn PipelineStage + Send>);
+
 pub struct Pipeline<'a> {
-    encoder_pipeline: Vec<(Option<&'a str>, Box<dyn Writable>)>,
-    decoder_pipeline: Vec<(Option<&'a str>, Box<dyn Writable>)>
+    encoder_pipeline: Vec<Stage<'a>>,
+    decoder_pipeline: Vec<Stage<'a>>
 }
 
 impl<'a> Pipeline<'a> {
-    pub fn add_last_encoder(mut self, encoder: impl Writable + 'static, name: Option<&'a str>) -> Self {
+    pub fn add_last_encoder(mut self, encoder: impl PipelineStage + Send + 'static, name: Option<&'a str>) -> Self {
         self.encoder_pipeline.push((name, Box::new(encoder)));
         self
     }
 
-    pub fn add_last_decoder(mut self, decoder: impl Writable + 'static, name: Option<&'a str>) -> Self {
+    pub fn add_last_decoder(mut self, decoder: impl PipelineStage + Send + 'static, name: Option<&'a str>) -> Self {
         self.decoder_pipeline.push((name, Box::new(decoder)));
         self
     }
 
-    pub fn encode(&self, buffer: Buffer) -> Result<Buffer, Error> {
-        let mut cloned_buffer = buffer.clone();
-        for (_, encoder) in &self.encoder_pipeline {
-            cloned_buffer = encoder.write(cloned_buffer)?;
-        }
-        Ok(cloned_buffer)
+    pub fn add_first_encoder(mut self, encoder: impl PipelineStage + Send + 'static, name: Option<&'a str>) -> Self {
+        self.encoder_pipeline.insert(0, (name, Box::new(encoder)));
+        self
     }
 
-    pub fn decode(&self, buffer: Buffer) -> Result<Buffer, Error> {
-        let mut cloned_buffer = buffer.clone();
-        for (_, decoder) in &self.decoder_pipeline {
-            cloned_buffer = decoder.write(cloned_buffer)?;
-        }
-        Ok(cloned_buffer)
+    pub fn add_first_decoder(mut self, decoder: impl PipelineStage + Send + 'static, name: Option<&'a str>) -> Self {
+        self.decoder_pipeline.insert(0, (name, Box::new(decoder)));
+        self
+    }
+
+    pub fn remove_encoder(&mut self, name: &str) -> bool {
+        let length = self.encoder_pipeline.len();
+        self.encoder_pipeline.retain(|(stage_name, _)| *stage_name != Some(name));
+        self.encoder_pipeline.len() != length
+    }
+
+    pub fn remove_decoder(&mut self, name: &str) -> bool {
+        let length = self.decoder_pipeline.len();
+        self.decoder_pipeline.retain(|(stage_name, _)| *stage_name != Some(name));
+        self.decoder_pipeline.len() != length
+    }
+
+    pub fn has_encoder(&self, name: &str) -> bool {
+        self.encoder_pipeline.iter().any(|(stage_name, _)| *stage_name == Some(name))
+    }
+
+    pub fn has_decoder(&self, name: &str) -> bool {
+        self.decoder_pipeline.iter().any(|(stage_name, _)| *stage_name == Some(name))
+    }
+
+    pub fn encoder_names(&self) -> Vec<Option<&'a str>> {
+        self.encoder_pipeline.iter().map(|(name, _)| *name).collect()
+    }
+
+    pub fn decoder_names(&self) -> Vec<Option<&'a str>> {
+        self.decoder_pipeline.iter().map(|(name, _)| *name).collect()
+    }
+
+    pub fn encode(&mut self, buffer: Buffer) -> Result<Buffer, Error> {
+        self.encoder_pipeline.iter_mut().try_fold(buffer, |buffer, (_, encoder)| encoder.process(buffer))
+    }
+
+    pub fn decode(&mut self, buffer: Buffer) -> Result<Buffer, Error> {
+        self.decoder_pipeline.iter_mut().try_fold(buffer, |buffer, (_, decoder)| decoder.process(buffer))
     }
 }
 
@@ -59,14 +206,61 @@ impl<'a> Pipeline<'a> {
     }
 }
 
+impl<'a> Default for Pipeline<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub trait Writable {
     fn write(&self, buffer: Buffer) -> Result<Buffer, Error>;
 }
 
+pub trait VersionedWritable {
+    fn write_versioned(&self, buffer: Buffer, protocol: i32) -> Result<Buffer, Error>;
+}
+
+pub trait VersionedReadable: Sized {
+    fn read_versioned(buffer: &mut Buffer, protocol: i32) -> Result<Self, Error>;
+
+    fn decode_versioned(buffer: &mut Buffer, protocol: i32) -> Result<Self, Error> {
+        decode_with(buffer, |buffer| Self::read_versioned(buffer, protocol))
+    }
+}
+
 pub trait Readable {
-    fn read(buffer: Buffer) -> Result<Self, Error>
+    fn read(buffer: &mut Buffer) -> Result<Self, Error>
     where
         Self: Sized;
+
+    fn decode(buffer: &mut Buffer) -> Result<Self, Error>
+    where
+        Self: Sized
+    {
+        decode_with(buffer, Self::read)
+    }
+}
+
+fn decode_with<T>(buffer: &mut Buffer, read: impl FnOnce(&mut Buffer) -> Result<T, Error>) -> Result<T, Error> {
+    let value = read(buffer).map_err(|error| {
+        match error {
+            Error::OutOfBounds(end, length) => {
+                Error::Decode {
+                    offset: buffer.position(),
+                    reason: format!("Unexpected end of {}, needed {} bytes but only {} are available", type_name::<T>(), end, length)
+                }
+            }
+            error => error
+        }
+    })?;
+
+    if buffer.remaining() > 0 {
+        return Err(Error::Decode {
+            offset: buffer.position(),
+            reason: format!("{} bytes left over after reading {}", buffer.remaining(), type_name::<T>())
+        })
+    }
+    Ok(value)
 }
 
 macro_rules! define_type_io {
@@ -80,8 +274,8 @@ macro_rules! define_type_io {
             }
 
             impl Readable for $_type {
-                fn read(mut buffer: Buffer) -> Result<Self, Error> {
-                    Ok(buffer.[<read_ $_type>]()?)
+                fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+                    buffer.[<read_ $_type>]()
                 }
             }
         }
@@ -91,10 +285,17 @@ macro_rules! define_type_io {
 macro_rules! define_var_int {
     ($_type: tt) => {
         paste::paste! {
+            #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
             pub struct [<Var $_type:upper>] {
                 pub value: $_type
             }
 
+            impl [<Var $_type:upper>] {
+                pub const fn new(value: $_type) -> Self {
+                    Self { value }
+                }
+            }
+
             impl Writable for [<Var $_type:upper>] {
                 fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
                     buffer.[<write_var_ $_type>](self.value)?;
@@ -103,15 +304,70 @@ macro_rules! define_var_int {
             }
 
             impl Readable for [<Var $_type:upper>] {
-                fn read(mut buffer: Buffer) -> Result<Self, Error> {
+                fn read(buffer: &mut Buffer) -> Result<Self, Error> {
                     Ok(Self { value: buffer.[<read_var_ $_type>]()? })
                 }
             }
+
+            impl From<$_type> for [<Var $_type:upper>] {
+                fn from(value: $_type) -> Self {
+                    Self { value }
+                }
+            }
+
+            impl From<[<Var $_type:upper>]> for $_type {
+                fn from(value: [<Var $_type:upper>]) -> Self {
+                    value.value
+                }
+            }
+
+            impl Deref for [<Var $_type:upper>] {
+                type Target = $_type;
+
+                fn deref(&self) -> &Self::Target {
+                    &self.value
+                }
+            }
+
+            impl PartialEq<$_type> for [<Var $_type:upper>] {
+                fn eq(&self, other: &$_type) -> bool {
+                    self.value == *other
+                }
+            }
+
+            impl PartialOrd<$_type> for [<Var $_type:upper>] {
+                fn partial_cmp(&self, other: &$_type) -> Option<std::cmp::Ordering> {
+                    self.value.partial_cmp(other)
+                }
+            }
+
+            impl Display for [<Var $_type:upper>] {
+                fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+                    Display::fmt(&self.value, formatter)
+                }
+            }
+
+            impl<T: Into<$_type>> Add<T> for [<Var $_type:upper>] {
+                type Output = Self;
+
+                fn add(self, other: T) -> Self {
+                    Self { value: self.value + other.into() }
+                }
+            }
+
+            impl<T: Into<$_type>> Sub<T> for [<Var $_type:upper>] {
+                type Output = Self;
+
+                fn sub(self, other: T) -> Self {
+                    Self { value: self.value - other.into() }
+                }
+            }
         }
     };
 }
 
 define_var_int!(i32);
+define_var_int!(i64);
 
 define_type_io!(u8);
 define_type_io!(u16);
@@ -133,7 +389,7 @@ impl Writable for Uuid {
 }
 
 impl Readable for Uuid {
-    fn read(mut buffer: Buffer) -> Result<Self, Error>
+    fn read(buffer: &mut Buffer) -> Result<Self, Error>
     where
         Self: Sized
     {
@@ -154,24 +410,56 @@ impl<T: Writable> Writable for Vec<T> {
 }
 
 impl<T: Readable> Readable for Vec<T> {
-    fn read(mut buffer: Buffer) -> Result<Self, Error>
+    fn read(buffer: &mut Buffer) -> Result<Self, Error>
     where
         Self: Sized
     {
-        let length = buffer.read_var_i32()?;
-        if length < 0 {
-            return Err(Error::Other("Unable to read array with negative length!".to_string()))
-        }
-
+        let length = buffer.read_length()?;
         let mut vector: Vec<T> = Vec::new();
         for _ in 0..length {
-            vector.push(T::read(buffer.clone())?);
-            buffer.set_position(buffer.position() + size_of::<T>());
+            vector.push(T::read(buffer)?);
         }
         Ok(vector)
     }
 }
 
+impl<T: Writable, const N: usize> Writable for [T; N] {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        for element in self {
+            buffer = element.write(buffer)?;
+        }
+        Ok(buffer)
+    }
+}
+
+// Fixed arrays carry no length prefix
+impl<T: Readable, const N: usize> Readable for [T; N] {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error>
+    where
+        Self: Sized
+    {
+        let mut error = None;
+        let elements = core::array::from_fn(|_| {
+            match error {
+                Some(_) => None,
+                None => T::read(buffer).map_err(|err| error = Some(err)).ok()
+            }
+        });
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(elements.map(Option::unwrap))
+        }
+    }
+}
+
+impl Writable for Buffer {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        buffer.write_bytes(&self.to_bytes())?;
+        Ok(buffer)
+    }
+}
+
 impl Writable for String {
     fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
         buffer.write_string(self.clone())?;
@@ -180,7 +468,7 @@ impl Writable for String {
 }
 
 impl Readable for String {
-    fn read(mut buffer: Buffer) -> Result<Self, Error>
+    fn read(buffer: &mut Buffer) -> Result<Self, Error>
     where
         Self: Sized
     {
@@ -0,0 +1,177 @@
+use std::{
+    io::{Read, Write},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc
+    }
+};
+
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+
+use crate::network::{buffer::Buffer, connection::Writable, Error};
+
+const MAXIMUM_UNCOMPRESSED_LENGTH: i32 = 8388608;
+
+#[derive(Debug, Default)]
+pub struct CompressionStatistics {
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+    packets_compressed: AtomicU64,
+    packets_uncompressed: AtomicU64
+}
+
+impl CompressionStatistics {
+    fn record(&self, bytes_in: usize, bytes_out: usize, compressed: bool) {
+        self.bytes_in.fetch_add(bytes_in as u64, Ordering::Relaxed);
+        self.bytes_out.fetch_add(bytes_out as u64, Ordering::Relaxed);
+        if compressed {
+            self.packets_compressed.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.packets_uncompressed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn bytes_in(&self) -> u64 {
+        self.bytes_in.load(Ordering::Relaxed)
+    }
+
+    pub fn bytes_out(&self) -> u64 {
+        self.bytes_out.load(Ordering::Relaxed)
+    }
+
+    pub fn packets_compressed(&self) -> u64 {
+        self.packets_compressed.load(Ordering::Relaxed)
+    }
+
+    pub fn packets_uncompressed(&self) -> u64 {
+        self.packets_uncompressed.load(Ordering::Relaxed)
+    }
+
+    pub fn compression_ratio(&self) -> f64 {
+        let bytes_in = self.bytes_in();
+        if bytes_in == 0 {
+            return 1.0
+        }
+        self.bytes_out() as f64 / bytes_in as f64
+    }
+}
+
+pub struct CompressionEncoder {
+    threshold: i32,
+    statistics: Option<Arc<CompressionStatistics>>
+}
+
+impl Writable for CompressionEncoder {
+    fn write(&self, buffer: Buffer) -> Result<Buffer, Error> {
+        let buffer_bytes = buffer.to_bytes();
+
+        let mut copied_buffer = buffer.cloned_metadata();
+        let compressed = buffer_bytes.len() as i32 >= self.threshold;
+        if compressed {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&buffer_bytes)?;
+            copied_buffer.write_var_i32(buffer_bytes.len() as i32)?;
+            copied_buffer.write_bytes(&encoder.finish()?)?;
+        } else {
+            copied_buffer.write_var_i32(0)?;
+            copied_buffer.write_bytes(&buffer_bytes)?;
+        }
+
+        if let Some(statistics) = &self.statistics {
+            statistics.record(buffer_bytes.len(), copied_buffer.len(), compressed);
+        }
+        Ok(copied_buffer)
+    }
+}
+
+impl CompressionEncoder {
+    pub fn new(threshold: i32) -> Self {
+        Self { threshold, statistics: None }
+    }
+
+    pub fn with_statistics(threshold: i32) -> Self {
+        Self {
+            threshold,
+            statistics: Some(Arc::new(CompressionStatistics::default()))
+        }
+    }
+
+    pub fn threshold(&self) -> i32 {
+        self.threshold
+    }
+
+    pub fn statistics(&self) -> Option<Arc<CompressionStatistics>> {
+        self.statistics.clone()
+    }
+
+    pub fn compression_ratio(&self) -> Option<f64> {
+        self.statistics.as_ref().map(|statistics| statistics.compression_ratio())
+    }
+
+    pub fn packets_compressed(&self) -> Option<u64> {
+        self.statistics.as_ref().map(|statistics| statistics.packets_compressed())
+    }
+
+    pub fn packets_uncompressed(&self) -> Option<u64> {
+        self.statistics.as_ref().map(|statistics| statistics.packets_uncompressed())
+    }
+}
+
+pub struct CompressionDecoder {
+    threshold: i32
+}
+
+impl Writable for CompressionDecoder {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        let data_length = buffer.read_var_i32()?;
+        let offset = buffer.position();
+        let compressed_bytes = buffer.read_bytes(buffer.remaining())?;
+
+        let mut copied_buffer = buffer.cloned_metadata();
+        if data_length == 0 {
+            copied_buffer.write_bytes(&compressed_bytes)?;
+            copied_buffer.reset();
+            return Ok(copied_buffer)
+        }
+
+        if data_length < self.threshold {
+            return Err(Error::Decode {
+                offset: 0,
+                reason: format!("Badly compressed packet => Size of {} is below the threshold of {}", data_length, self.threshold)
+            })
+        }
+
+        if data_length > MAXIMUM_UNCOMPRESSED_LENGTH {
+            return Err(Error::Decode {
+                offset: 0,
+                reason: format!(
+                    "Badly compressed packet => Size of {} is larger than the protocol maximum of {}",
+                    data_length, MAXIMUM_UNCOMPRESSED_LENGTH
+                )
+            })
+        }
+
+        let mut bytes = Vec::with_capacity(data_length as usize);
+        ZlibDecoder::new(compressed_bytes.as_slice()).take(data_length as u64 + 1).read_to_end(&mut bytes)?;
+        if bytes.len() != data_length as usize {
+            return Err(Error::Decode {
+                offset,
+                reason: format!("Badly compressed packet => Expected {} bytes but got {} bytes", data_length, bytes.len())
+            })
+        }
+
+        copied_buffer.write_bytes(&bytes)?;
+        copied_buffer.reset();
+        Ok(copied_buffer)
+    }
+}
+
+impl CompressionDecoder {
+    pub fn new(threshold: i32) -> Self {
+        Self { threshold }
+    }
+
+    pub fn threshold(&self) -> i32 {
+        self.threshold
+    }
+}
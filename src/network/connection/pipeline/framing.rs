@@ -1,5 +1,12 @@
-use crate::network::{buffer::Buffer, connection::Writable, Error};
+use std::mem;
 
+use crate::network::{
+    buffer::Buffer,
+    connection::{Writable, MAXIMUM_FRAME_LENGTH},
+    ByteOrder, Error
+};
+
+#[derive(Default)]
 pub struct FrameEncoder {}
 
 impl Writable for FrameEncoder {
@@ -19,3 +26,68 @@ impl FrameEncoder {
         Self {}
     }
 }
+
+pub struct FrameDecoder {
+    pending: Buffer
+}
+
+impl Default for FrameDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        Self {
+            pending: Buffer::empty(true, Some(ByteOrder::BigEndian))
+        }
+    }
+
+    pub fn extend(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        let position = self.pending.position();
+        self.pending.write_bytes(bytes)?;
+        self.pending.set_position(position);
+        Ok(())
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.remaining()
+    }
+
+    pub fn next_frame(&mut self) -> Result<Option<Buffer>, Error> {
+        let start = self.pending.position();
+        let length = match self.pending.read_var_i32() {
+            Ok(length) => length,
+            Err(Error::OutOfBounds(..)) if self.pending.len() - start < 3 => {
+                self.pending.set_position(start);
+                return Ok(None)
+            }
+            Err(Error::OutOfBounds(..)) => return Err(self.length_error(start, "Frame length is longer than 3 bytes".to_string())),
+            Err(error) => return Err(error)
+        };
+
+        let prefix = self.pending.position() - start;
+        if prefix > 3 {
+            return Err(self.length_error(start, "Frame length is longer than 3 bytes".to_string()))
+        }
+
+        if !(0..=MAXIMUM_FRAME_LENGTH).contains(&length) {
+            return Err(self.length_error(start, format!("Frame length {} exceeds the maximum of {}", length, MAXIMUM_FRAME_LENGTH)))
+        }
+
+        if self.pending.remaining() < length as usize {
+            self.pending.rewind(prefix)?;
+            return Ok(None)
+        }
+
+        self.pending.drain_front(self.pending.position());
+        let tail = self.pending.split_off(length as usize);
+        Ok(Some(mem::replace(&mut self.pending, tail)))
+    }
+
+    fn length_error(&mut self, start: usize, reason: String) -> Error {
+        self.pending.set_position(start);
+        Error::Decode { offset: start, reason }
+    }
+}
@@ -1 +1,3 @@
+#[cfg(feature = "compression")]
+pub mod compression;
 pub mod framing;
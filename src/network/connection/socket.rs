@@ -1,53 +1,51 @@
 use std::{
-    io::{Read, Write},
-    net::TcpStream,
+    io::{self, ErrorKind, Read, Write},
+    net::{Shutdown, TcpStream},
+    sync::Arc,
     time::{Duration, SystemTime}
 };
 
 use crate::network::{
     buffer::Buffer,
-    connection::{Connection, Pipeline, Writable},
+    connection::{
+        codec::ProtocolCodec,
+        machine::ProtocolMachine,
+        pipeline::{
+            compression::{CompressionDecoder, CompressionEncoder},
+            framing::FrameEncoder
+        },
+        stats::PacketStats,
+        Connection, Pipeline, Role, Writable, READ_CHUNK_LENGTH
+    },
+    cookie::CookieJar,
+    decode::{DecodeMode, DecodeReport},
     ByteOrder, Error, PacketDirection, PacketState
 };
 
-pub struct SocketConnection<'a> {
-    packet_state: PacketState,
-    pipeline: Pipeline<'a>,
+pub struct SocketConnection<'a, R: Role> {
+    machine: ProtocolMachine<'a, R>,
     socket: TcpStream
 }
 
-impl<'a> Connection<'a, TcpStream> for SocketConnection<'a> {
+impl<'a, R: Role> Connection<'a, TcpStream> for SocketConnection<'a, R> {
     fn new(object: TcpStream, pipeline: Pipeline<'a>) -> Self {
         SocketConnection {
-            socket: object,
-            pipeline,
-            packet_state: PacketState::Handshaking
+            machine: ProtocolMachine::new(pipeline),
+            socket: object
         }
     }
 
     fn write<T: Writable>(&mut self, packet: T) -> Result<usize, Error> {
-        let buffer = Buffer::empty(true, None);
-
-        match packet.write(buffer) {
-            Ok(buffer) => {
-                match self.pipeline.encode(buffer) {
-                    Ok(buffer) => {
-                        match self.socket.write(&*buffer.to_bytes()) {
-                            Ok(size) => {
-                                self.socket.flush().unwrap();
-                                Ok(size)
-                            }
-                            Err(error) => Err(Error::Other(error.to_string()))
-                        }
-                    }
-                    Err(error) => Err(Error::Other(error.to_string()))
-                }
-            }
-            Err(error) => Err(Error::Other(error.to_string()))
-        }
+        let length = self.machine.queue_outgoing(packet)?;
+        self.flush()?;
+        Ok(length)
     }
 
     fn read_buffer(&mut self, timeout: Option<Duration>, order: ByteOrder) -> Result<(Buffer, Duration), Error> {
+        if self.codec().buffered() > 0 {
+            return Ok((Buffer::new(self.codec_mut().take_input(), true, Some(order)), Duration::ZERO))
+        }
+
         let socket_timeout = self.get_timeout()?;
 
         if timeout.is_some() {
@@ -55,19 +53,16 @@ impl<'a> Connection<'a, TcpStream> for SocketConnection<'a> {
         }
 
         let mut read = [0; 1024];
-        let mut bytes = Vec::new();
         let time = SystemTime::now();
         match self.socket.read(&mut read) {
-            Ok(size) => {
-                bytes = read[0..size].to_vec();
-            }
+            Ok(size) => self.machine.feed_incoming(&read[0..size]),
             Err(error) => {
                 if socket_timeout.is_some() {
                     self.set_timeout(socket_timeout)?;
                 }
                 return Err(Error::NotReadable(error.to_string()))
             }
-        }
+        };
 
         let reached_timeout = time.elapsed().map_err(|error| Error::Other(error.to_string()))?;
 
@@ -75,19 +70,77 @@ impl<'a> Connection<'a, TcpStream> for SocketConnection<'a> {
             self.set_timeout(socket_timeout)?;
         }
 
-        Ok((Buffer::new(bytes, true, Some(order)), reached_timeout))
+        Ok((Buffer::new(self.codec_mut().take_input(), true, Some(order)), reached_timeout))
+    }
+
+    fn read_packet(&mut self) -> Result<Buffer, Error> {
+        loop {
+            if let Some((_, buffer)) = self.machine.poll_frame()? {
+                self.flush()?;
+                return Ok(buffer)
+            }
+
+            let mut read = [0; READ_CHUNK_LENGTH];
+            let size = self.socket.read(&mut read)?;
+            if size == 0 {
+                return Err(io::Error::from(ErrorKind::UnexpectedEof).into())
+            }
+            self.machine.feed_incoming(&read[..size]);
+        }
     }
 
     fn state(&self) -> PacketState {
-        self.packet_state
+        self.machine.state()
+    }
+
+    fn set_state(&mut self, state: PacketState) {
+        self.machine.set_state(state);
+    }
+
+    fn protocol_version(&self) -> Option<i32> {
+        self.codec().protocol_version()
     }
 
-    fn bound() -> PacketDirection {
-        PacketDirection::Clientbound
+    fn set_protocol_version(&mut self, protocol_version: Option<i32>) {
+        self.codec_mut().set_protocol_version(protocol_version);
+    }
+
+    fn decode_mode(&self) -> DecodeMode {
+        self.codec().decode_mode()
+    }
+
+    fn set_decode_mode(&mut self, decode_mode: DecodeMode) {
+        self.codec_mut().set_decode_mode(decode_mode);
+    }
+
+    fn decode_report(&self) -> &Arc<DecodeReport> {
+        self.codec().decode_report()
+    }
+
+    fn read_direction() -> PacketDirection {
+        R::read_direction()
+    }
+
+    fn write_direction() -> PacketDirection {
+        R::write_direction()
     }
 }
 
-impl<'a> SocketConnection<'a> {
+impl<'a, R: Role> SocketConnection<'a, R> {
+    pub fn read_exact_buffer(&mut self, length: usize, order: ByteOrder) -> Result<Buffer, Error> {
+        let mut bytes = self.codec_mut().take_input_up_to(length);
+        while bytes.len() < length {
+            let mut read = [0; READ_CHUNK_LENGTH];
+            let size = self.socket.read(&mut read)?;
+            if size == 0 {
+                return Err(io::Error::from(ErrorKind::UnexpectedEof).into())
+            }
+            self.machine.feed_incoming(&read[..size]);
+            bytes.extend(self.codec_mut().take_input_up_to(length - bytes.len()));
+        }
+        Ok(Buffer::new(bytes, true, Some(order)))
+    }
+
     pub fn set_timeout(&self, timeout: Option<Duration>) -> Result<(), Error> {
         Ok(self.socket.set_read_timeout(timeout)?)
     }
@@ -96,4 +149,128 @@ impl<'a> SocketConnection<'a> {
         let socket_timeout = self.socket.read_timeout()?;
         Ok(socket_timeout)
     }
+
+    pub fn enable_encryption(&mut self, shared_secret: &[u8; 16]) {
+        self.machine.enable_encryption(shared_secret);
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.codec().is_encrypted()
+    }
+
+    pub fn enable_compression(&mut self, threshold: i32) {
+        self.codec_mut().enable_compression(threshold);
+    }
+
+    pub fn disable_compression(&mut self) {
+        self.codec_mut().disable_compression();
+    }
+
+    pub fn set_packet_stats(&mut self, packet_stats: Option<Arc<PacketStats>>) {
+        self.codec_mut().set_packet_stats(packet_stats);
+    }
+
+    pub fn packet_stats(&self) -> Option<&Arc<PacketStats>> {
+        self.codec().packet_stats()
+    }
+
+    pub fn set_cookie_jar(&mut self, cookie_jar: Option<Arc<CookieJar>>) {
+        self.codec_mut().set_cookie_jar(cookie_jar);
+    }
+
+    pub fn cookie_jar(&self) -> Option<&Arc<CookieJar>> {
+        self.codec().cookie_jar()
+    }
+
+    pub fn pipeline(&self) -> &Pipeline<'a> {
+        self.codec().pipeline()
+    }
+
+    pub fn machine(&self) -> &ProtocolMachine<'a, R> {
+        &self.machine
+    }
+
+    pub fn machine_mut(&mut self) -> &mut ProtocolMachine<'a, R> {
+        &mut self.machine
+    }
+
+    pub fn codec(&self) -> &ProtocolCodec<'a, R> {
+        self.machine.codec()
+    }
+
+    pub fn codec_mut(&mut self) -> &mut ProtocolCodec<'a, R> {
+        self.machine.codec_mut()
+    }
+
+    pub fn socket(&self) -> &TcpStream {
+        &self.socket
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        if self.machine.has_outgoing() {
+            self.socket.write_all(&self.machine.take_outgoing())?;
+            self.socket.flush()?;
+        }
+        Ok(())
+    }
+}
+
+// Compression runs before framing, the framed bytes are encrypted as a whole
+#[derive(Clone, Default)]
+pub struct ConnectionBuilder {
+    framing: bool,
+    compression: Option<i32>,
+    encryption: Option<[u8; 16]>
+}
+
+impl ConnectionBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_framing(self) -> Self {
+        Self { framing: true, ..self }
+    }
+
+    pub fn with_compression(self, threshold: i32) -> Self {
+        Self {
+            compression: Some(threshold).filter(|threshold| *threshold >= 0),
+            ..self
+        }
+    }
+
+    pub fn with_encryption(self, shared_secret: [u8; 16]) -> Self {
+        Self {
+            encryption: Some(shared_secret),
+            ..self
+        }
+    }
+
+    pub fn pipeline<'a>(&self) -> Pipeline<'a> {
+        let mut pipeline = Pipeline::new();
+        if let Some(threshold) = self.compression {
+            pipeline = pipeline
+                .add_last_encoder(CompressionEncoder::new(threshold), Some("compression"))
+                .add_last_decoder(CompressionDecoder::new(threshold), Some("decompression"));
+        }
+        if self.framing {
+            pipeline = pipeline.add_last_encoder(FrameEncoder::new(), Some("framing"));
+        }
+        pipeline
+    }
+
+    pub fn build<'a, R: Role>(&self, socket: TcpStream) -> SocketConnection<'a, R> {
+        let mut connection = SocketConnection::new(socket, self.pipeline());
+        if let Some(shared_secret) = &self.encryption {
+            connection.enable_encryption(shared_secret);
+        }
+        connection
+    }
+}
+
+impl<'a, R: Role> Drop for SocketConnection<'a, R> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+        let _ = self.socket.shutdown(Shutdown::Write);
+    }
 }
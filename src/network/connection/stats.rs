@@ -0,0 +1,174 @@
+use std::{
+    fmt::{Display, Formatter},
+    sync::atomic::{AtomicU64, Ordering}
+};
+
+use crate::network::{buffer::Buffer, PacketDirection, PacketState};
+
+const STATES: [PacketState; 5] = [PacketState::Handshaking, PacketState::Login, PacketState::Status, PacketState::Configuration, PacketState::Play];
+const DIRECTIONS: [PacketDirection; 2] = [PacketDirection::Clientbound, PacketDirection::Serverbound];
+const PACKET_IDS: usize = 256;
+
+type NameResolver = fn(PacketState, PacketDirection, i32) -> Option<&'static str>;
+
+#[derive(Debug)]
+struct PacketCounter {
+    count: AtomicU64,
+    bytes: AtomicU64,
+    min: AtomicU64,
+    max: AtomicU64
+}
+
+impl Default for PacketCounter {
+    fn default() -> Self {
+        Self {
+            count: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            min: AtomicU64::new(u64::MAX),
+            max: AtomicU64::new(0)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PacketStatLine {
+    pub state: PacketState,
+    pub direction: PacketDirection,
+    pub id: i32,
+    pub name: Option<&'static str>,
+    pub count: u64,
+    pub total_bytes: u64,
+    pub min_bytes: u64,
+    pub max_bytes: u64,
+    pub mean_bytes: f64
+}
+
+#[derive(Debug)]
+pub struct PacketStats {
+    counters: Box<[PacketCounter]>,
+    unknown: AtomicU64,
+    names: Option<NameResolver>
+}
+
+impl Default for PacketStats {
+    fn default() -> Self {
+        Self {
+            counters: (0..STATES.len() * DIRECTIONS.len() * PACKET_IDS).map(|_| PacketCounter::default()).collect(),
+            unknown: AtomicU64::new(0),
+            names: None
+        }
+    }
+}
+
+impl PacketStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_names(names: NameResolver) -> Self {
+        Self {
+            names: Some(names),
+            ..Self::default()
+        }
+    }
+
+    pub fn record(&self, state: PacketState, direction: PacketDirection, id: i32, size: usize) {
+        if !(0..PACKET_IDS as i32).contains(&id) {
+            self.unknown.fetch_add(1, Ordering::Relaxed);
+            return
+        }
+
+        let counter = &self.counters[index(state, direction, id)];
+        counter.count.fetch_add(1, Ordering::Relaxed);
+        counter.bytes.fetch_add(size as u64, Ordering::Relaxed);
+        counter.min.fetch_min(size as u64, Ordering::Relaxed);
+        counter.max.fetch_max(size as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_buffer(&self, state: PacketState, direction: PacketDirection, buffer: &mut Buffer) {
+        let position = buffer.position();
+        buffer.set_position(0);
+        match buffer.read_var_i32() {
+            Ok(id) => self.record(state, direction, id, buffer.len()),
+            Err(_) => {
+                self.unknown.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        buffer.set_position(position);
+    }
+
+    pub fn unknown(&self) -> u64 {
+        self.unknown.load(Ordering::Relaxed)
+    }
+
+    pub fn get(&self, state: PacketState, direction: PacketDirection, id: i32) -> Option<PacketStatLine> {
+        if !(0..PACKET_IDS as i32).contains(&id) {
+            return None
+        }
+
+        let counter = &self.counters[index(state, direction, id)];
+        let count = counter.count.load(Ordering::Relaxed);
+        if count == 0 {
+            return None
+        }
+
+        let total_bytes = counter.bytes.load(Ordering::Relaxed);
+        Some(PacketStatLine {
+            state,
+            direction,
+            id,
+            name: self.names.and_then(|names| names(state, direction, id)),
+            count,
+            total_bytes,
+            min_bytes: counter.min.load(Ordering::Relaxed),
+            max_bytes: counter.max.load(Ordering::Relaxed),
+            mean_bytes: total_bytes as f64 / count as f64
+        })
+    }
+
+    pub fn report(&self, top_n: usize) -> Vec<PacketStatLine> {
+        let mut lines = Vec::new();
+        for state in STATES {
+            for direction in DIRECTIONS {
+                lines.extend((0..PACKET_IDS as i32).filter_map(|id| self.get(state, direction, id)));
+            }
+        }
+
+        lines.sort_by(|first, second| second.total_bytes.cmp(&first.total_bytes).then(second.count.cmp(&first.count)));
+        lines.truncate(top_n);
+        lines
+    }
+}
+
+impl Display for PacketStats {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            formatter,
+            "{:<12} {:<12} {:>5} {:<32} {:>10} {:>12} {:>8} {:>8} {:>10}",
+            "State", "Direction", "Id", "Name", "Count", "Bytes", "Min", "Max", "Mean"
+        )?;
+
+        for line in self.report(usize::MAX) {
+            writeln!(
+                formatter,
+                "{:<12} {:<12} {:>#5x} {:<32} {:>10} {:>12} {:>8} {:>8} {:>10.1}",
+                line.state.to_string(),
+                line.direction.to_string(),
+                line.id,
+                line.name.unwrap_or("-"),
+                line.count,
+                line.total_bytes,
+                line.min_bytes,
+                line.max_bytes,
+                line.mean_bytes
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn index(state: PacketState, direction: PacketDirection, id: i32) -> usize {
+    let state = STATES.iter().position(|other| *other == state).unwrap();
+    let direction = DIRECTIONS.iter().position(|other| *other == direction).unwrap();
+    (state * DIRECTIONS.len() + direction) * PACKET_IDS + id as usize
+}
@@ -0,0 +1,494 @@
+use rand::random;
+use uuid::{Builder, Uuid};
+
+use crate::{
+    components::Component,
+    network::{
+        buffer::Buffer,
+        connection::{Connection, Readable, Writable},
+        nbt::{Compound, Tag},
+        versions::VersionInfo,
+        ByteOrder, Error
+    }
+};
+
+pub const MAX_SIDEBAR_LINES: usize = 15;
+
+const SIDEBAR_POSITION: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlayPacket {
+    BossBar,
+    DisplayObjective,
+    UpdateObjectives,
+    UpdateScore,
+    ResetScore
+}
+
+// Reset Score was split off Update Score with 1.20.3
+fn packet(packet: PlayPacket, protocol: i32) -> Result<Buffer, Error> {
+    let ids = match protocol {
+        754 => [0x0C, 0x43, 0x4A, 0x4D, -1],
+        755..=758 => [0x0D, 0x4C, 0x53, 0x56, -1],
+        759 => [0x0A, 0x4C, 0x53, 0x56, -1],
+        760 => [0x0A, 0x4F, 0x56, 0x59, -1],
+        761 => [0x0A, 0x4D, 0x54, 0x57, -1],
+        762..=763 => [0x0B, 0x51, 0x58, 0x5B, -1],
+        764 => [0x0A, 0x53, 0x5A, 0x5D, -1],
+        765 => [0x0A, 0x55, 0x5C, 0x5F, 0x42],
+        766..=767 => [0x0A, 0x57, 0x5E, 0x61, 0x44],
+        protocol => return Err(Error::Other(format!("Unable to write {:?} => Protocol {} isn't supported", packet, protocol)))
+    };
+
+    let id = match packet {
+        PlayPacket::BossBar => ids[0],
+        PlayPacket::DisplayObjective => ids[1],
+        PlayPacket::UpdateObjectives => ids[2],
+        PlayPacket::UpdateScore => ids[3],
+        PlayPacket::ResetScore => ids[4]
+    };
+    if id < 0 {
+        return Err(Error::Other(format!("Unable to write {:?} => Protocol {} doesn't have this packet", packet, protocol)))
+    }
+
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(id)?;
+    Ok(buffer)
+}
+
+// Components are JSON strings until 1.20.3 and nameless network NBT since
+pub(crate) fn write_component(buffer: Buffer, component: &Component, protocol: i32) -> Result<Buffer, Error> {
+    if protocol >= 765 {
+        return Compound::from(component).write(buffer)
+    }
+
+    let mut buffer = buffer;
+    buffer.write_string(component.to_json())?;
+    Ok(buffer)
+}
+
+pub(crate) fn read_component(buffer: &mut Buffer, protocol: i32) -> Result<Component, Error> {
+    if protocol >= 765 {
+        return Ok(Component::from(&Tag::read(buffer)?))
+    }
+
+    let offset = buffer.position();
+    let decode_error = |error: serde_json::Error| {
+        Error::Decode {
+            offset,
+            reason: format!("Invalid component => {}", error)
+        }
+    };
+    match serde_json::from_str(&buffer.read_string()?).map_err(decode_error)? {
+        serde_json::Value::String(text) => Ok(Component::text(text)),
+        value => serde_json::from_value(value).map_err(decode_error)
+    }
+}
+
+fn send<'a, S>(connection: &mut impl Connection<'a, S>, buffer: Buffer) -> Result<(), Error> {
+    connection.write(buffer)?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BossBarColor {
+    Pink,
+    Blue,
+    Red,
+    Green,
+    Yellow,
+    Purple,
+    White
+}
+
+impl BossBarColor {
+    pub fn id(&self) -> i32 {
+        match self {
+            BossBarColor::Pink => 0,
+            BossBarColor::Blue => 1,
+            BossBarColor::Red => 2,
+            BossBarColor::Green => 3,
+            BossBarColor::Yellow => 4,
+            BossBarColor::Purple => 5,
+            BossBarColor::White => 6
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BossBarStyle {
+    Progress,
+    Notched6,
+    Notched10,
+    Notched12,
+    Notched20
+}
+
+impl BossBarStyle {
+    pub fn id(&self) -> i32 {
+        match self {
+            BossBarStyle::Progress => 0,
+            BossBarStyle::Notched6 => 1,
+            BossBarStyle::Notched10 => 2,
+            BossBarStyle::Notched12 => 3,
+            BossBarStyle::Notched20 => 4
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BossBar {
+    uuid: Uuid,
+    protocol: i32,
+    title: Component,
+    progress: f32,
+    color: BossBarColor,
+    style: BossBarStyle,
+    removed: bool
+}
+
+impl BossBar {
+    pub fn create<'a, S>(connection: &mut impl Connection<'a, S>, version: &VersionInfo, title: impl Into<Component>, color: BossBarColor, style: BossBarStyle) -> Result<Self, Error> {
+        Self::create_with_protocol(connection, version.protocol, title, color, style)
+    }
+
+    pub fn show<'a, S>(connection: &mut impl Connection<'a, S>, title: impl Into<Component>, color: BossBarColor, style: BossBarStyle) -> Result<Self, Error> {
+        let protocol = connection.require_protocol_version("show boss bar")?;
+        Self::create_with_protocol(connection, protocol, title, color, style)
+    }
+
+    fn create_with_protocol<'a, S>(connection: &mut impl Connection<'a, S>, protocol: i32, title: impl Into<Component>, color: BossBarColor, style: BossBarStyle) -> Result<Self, Error> {
+        let boss_bar = Self {
+            uuid: Builder::from_random_bytes(random()).into_uuid(),
+            protocol,
+            title: title.into(),
+            progress: 1.0,
+            color,
+            style,
+            removed: false
+        };
+        boss_bar.resend(connection)?;
+        Ok(boss_bar)
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    pub fn title(&self) -> &Component {
+        &self.title
+    }
+
+    pub fn progress(&self) -> f32 {
+        self.progress
+    }
+
+    pub fn is_removed(&self) -> bool {
+        self.removed
+    }
+
+    // The client rejects progress outside of 0 to 1
+    pub fn set_progress<'a, S>(&mut self, connection: &mut impl Connection<'a, S>, progress: f32) -> Result<(), Error> {
+        let progress = progress.clamp(0.0, 1.0);
+        if self.progress == progress {
+            return Ok(())
+        }
+
+        self.progress = progress;
+        let mut buffer = self.action(2)?;
+        buffer.write_u32(progress.to_bits())?;
+        self.send(connection, buffer)
+    }
+
+    pub fn set_title<'a, S>(&mut self, connection: &mut impl Connection<'a, S>, title: impl Into<Component>) -> Result<(), Error> {
+        let title = title.into();
+        if self.title == title {
+            return Ok(())
+        }
+
+        self.title = title;
+        let buffer = write_component(self.action(3)?, &self.title, self.protocol)?;
+        self.send(connection, buffer)
+    }
+
+    pub fn set_style<'a, S>(&mut self, connection: &mut impl Connection<'a, S>, color: BossBarColor, style: BossBarStyle) -> Result<(), Error> {
+        if self.color == color && self.style == style {
+            return Ok(())
+        }
+
+        self.color = color;
+        self.style = style;
+        let mut buffer = self.action(4)?;
+        buffer.write_var_i32(color.id())?;
+        buffer.write_var_i32(style.id())?;
+        self.send(connection, buffer)
+    }
+
+    pub fn remove<'a, S>(&mut self, connection: &mut impl Connection<'a, S>) -> Result<(), Error> {
+        let buffer = self.action(1)?;
+        self.send(connection, buffer)?;
+        self.removed = true;
+        Ok(())
+    }
+
+    pub fn resend<'a, S>(&self, connection: &mut impl Connection<'a, S>) -> Result<(), Error> {
+        if self.removed {
+            return Ok(())
+        }
+
+        let mut buffer = write_component(self.action(0)?, &self.title, self.protocol)?;
+        buffer.write_u32(self.progress.to_bits())?;
+        buffer.write_var_i32(self.color.id())?;
+        buffer.write_var_i32(self.style.id())?;
+        buffer.write_u8(0)?;
+        send(connection, buffer)
+    }
+
+    fn action(&self, action: i32) -> Result<Buffer, Error> {
+        let mut buffer = self.uuid.write(packet(PlayPacket::BossBar, self.protocol)?)?;
+        buffer.write_var_i32(action)?;
+        Ok(buffer)
+    }
+
+    fn send<'a, S>(&self, connection: &mut impl Connection<'a, S>, buffer: Buffer) -> Result<(), Error> {
+        match self.removed {
+            true => Ok(()),
+            false => send(connection, buffer)
+        }
+    }
+}
+
+// Since 1.20.3 the line is the display name, older versions show it as score holder which has to be unique
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sidebar {
+    objective: String,
+    protocol: i32,
+    title: Component,
+    lines: Vec<Option<Component>>,
+    removed: bool
+}
+
+impl Sidebar {
+    pub fn create<'a, S>(connection: &mut impl Connection<'a, S>, version: &VersionInfo, title: impl Into<Component>) -> Result<Self, Error> {
+        Self::create_with_protocol(connection, version.protocol, title)
+    }
+
+    pub fn show<'a, S>(connection: &mut impl Connection<'a, S>, title: impl Into<Component>) -> Result<Self, Error> {
+        let protocol = connection.require_protocol_version("show sidebar")?;
+        Self::create_with_protocol(connection, protocol, title)
+    }
+
+    fn create_with_protocol<'a, S>(connection: &mut impl Connection<'a, S>, protocol: i32, title: impl Into<Component>) -> Result<Self, Error> {
+        let sidebar = Self {
+            objective: format!("minerust{:08x}", random::<u32>()),
+            protocol,
+            title: title.into(),
+            lines: vec![None; MAX_SIDEBAR_LINES],
+            removed: false
+        };
+        sidebar.resend(connection)?;
+        Ok(sidebar)
+    }
+
+    pub fn objective(&self) -> &str {
+        &self.objective
+    }
+
+    pub fn title(&self) -> &Component {
+        &self.title
+    }
+
+    pub fn line(&self, index: usize) -> Option<&Component> {
+        self.lines.get(index)?.as_ref()
+    }
+
+    pub fn is_removed(&self) -> bool {
+        self.removed
+    }
+
+    pub fn set_title<'a, S>(&mut self, connection: &mut impl Connection<'a, S>, title: impl Into<Component>) -> Result<(), Error> {
+        let title = title.into();
+        if self.title == title {
+            return Ok(())
+        }
+
+        self.title = title;
+        if !self.removed {
+            send(connection, self.objective_packet(2)?)?;
+        }
+        Ok(())
+    }
+
+    pub fn set_line<'a, S>(&mut self, connection: &mut impl Connection<'a, S>, index: usize, line: impl Into<Component>) -> Result<(), Error> {
+        self.update_line(connection, index, Some(line.into()))
+    }
+
+    pub fn remove_line<'a, S>(&mut self, connection: &mut impl Connection<'a, S>, index: usize) -> Result<(), Error> {
+        self.update_line(connection, index, None)
+    }
+
+    pub fn remove<'a, S>(&mut self, connection: &mut impl Connection<'a, S>) -> Result<(), Error> {
+        send(connection, self.objective_packet(1)?)?;
+        self.removed = true;
+        Ok(())
+    }
+
+    pub fn resend<'a, S>(&self, connection: &mut impl Connection<'a, S>) -> Result<(), Error> {
+        if self.removed {
+            return Ok(())
+        }
+
+        send(connection, self.objective_packet(0)?)?;
+        let mut display = packet(PlayPacket::DisplayObjective, self.protocol)?;
+        if self.protocol >= 764 {
+            display.write_var_i32(SIDEBAR_POSITION as i32)?;
+        } else {
+            display.write_u8(SIDEBAR_POSITION)?;
+        }
+        display.write_str(&self.objective)?;
+        send(connection, display)?;
+
+        for (index, line) in self.lines.iter().enumerate() {
+            if let Some(line) = line {
+                send(connection, self.score_packet(index, line)?)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn update_line<'a, S>(&mut self, connection: &mut impl Connection<'a, S>, index: usize, line: Option<Component>) -> Result<(), Error> {
+        if index >= MAX_SIDEBAR_LINES {
+            return Err(Error::Other(format!("Invalid sidebar line {} => The sidebar only shows {} lines", index, MAX_SIDEBAR_LINES)))
+        }
+
+        if self.lines[index] == line {
+            return Ok(())
+        }
+
+        let previous = std::mem::replace(&mut self.lines[index], line);
+        if self.removed {
+            return Ok(())
+        }
+
+        match (&previous, &self.lines[index]) {
+            (_, Some(line)) if self.protocol >= 765 => send(connection, self.score_packet(index, line)?),
+            (None, None) => Ok(()),
+            (Some(previous), line) => {
+                send(connection, self.reset_packet(index, previous)?)?;
+                match line {
+                    Some(line) => send(connection, self.score_packet(index, line)?),
+                    None => Ok(())
+                }
+            }
+            (None, Some(line)) => send(connection, self.score_packet(index, line)?)
+        }
+    }
+
+    fn objective_packet(&self, mode: u8) -> Result<Buffer, Error> {
+        let mut buffer = packet(PlayPacket::UpdateObjectives, self.protocol)?;
+        buffer.write_str(&self.objective)?;
+        buffer.write_u8(mode)?;
+        if mode == 1 {
+            return Ok(buffer)
+        }
+
+        let mut buffer = write_component(buffer, &self.title, self.protocol)?;
+        buffer.write_var_i32(0)?;
+        if self.protocol >= 765 {
+            buffer.write_bool(true)?;
+            buffer.write_var_i32(0)?;
+        }
+        Ok(buffer)
+    }
+
+    fn holder(&self, index: usize, line: &Component) -> String {
+        match self.protocol {
+            765.. => format!("line{}", index),
+            _ => format!("{}\u{a7}{:x}", legacy_text(line), index)
+        }
+    }
+
+    fn score_packet(&self, index: usize, line: &Component) -> Result<Buffer, Error> {
+        let mut buffer = packet(PlayPacket::UpdateScore, self.protocol)?;
+        buffer.write_str(&self.holder(index, line))?;
+        let score = -(index as i32);
+        if self.protocol >= 765 {
+            buffer.write_str(&self.objective)?;
+            buffer.write_var_i32(score)?;
+            buffer.write_bool(true)?;
+            let mut buffer = write_component(buffer, line, self.protocol)?;
+            buffer.write_bool(false)?;
+            return Ok(buffer)
+        }
+
+        buffer.write_u8(0)?;
+        buffer.write_str(&self.objective)?;
+        buffer.write_var_i32(score)?;
+        Ok(buffer)
+    }
+
+    fn reset_packet(&self, index: usize, line: &Component) -> Result<Buffer, Error> {
+        if self.protocol >= 765 {
+            let mut buffer = packet(PlayPacket::ResetScore, self.protocol)?;
+            buffer.write_str(&self.holder(index, line))?;
+            buffer.write_bool(true)?;
+            buffer.write_str(&self.objective)?;
+            return Ok(buffer)
+        }
+
+        let mut buffer = packet(PlayPacket::UpdateScore, self.protocol)?;
+        buffer.write_str(&self.holder(index, line))?;
+        buffer.write_u8(1)?;
+        buffer.write_str(&self.objective)?;
+        Ok(buffer)
+    }
+}
+
+fn legacy_code(color: &str) -> Option<char> {
+    let colors = [
+        "black",
+        "dark_blue",
+        "dark_green",
+        "dark_aqua",
+        "dark_red",
+        "dark_purple",
+        "gold",
+        "gray",
+        "dark_gray",
+        "blue",
+        "green",
+        "aqua",
+        "red",
+        "light_purple",
+        "yellow",
+        "white"
+    ];
+    let index = colors.iter().position(|name| *name == color)?;
+    char::from_digit(index as u32, 16)
+}
+
+fn legacy_text(component: &Component) -> String {
+    let mut text = String::new();
+    if let Some(code) = component.color.as_deref().and_then(legacy_code) {
+        text.push('\u{a7}');
+        text.push(code);
+    }
+
+    let styles = [
+        (component.obfuscated, 'k'),
+        (component.bold, 'l'),
+        (component.strikethrough, 'm'),
+        (component.underlined, 'n'),
+        (component.italic, 'o')
+    ];
+    for (_, code) in styles.iter().filter(|(style, _)| *style == Some(true)) {
+        text.push('\u{a7}');
+        text.push(*code);
+    }
+
+    text.push_str(&component.text);
+    for extra in &component.extra {
+        text.push_str(&legacy_text(extra));
+    }
+    text
+}
@@ -0,0 +1,343 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard, PoisonError}
+};
+#[cfg(feature = "std-net")]
+use std::{net::TcpStream, sync::Arc};
+
+use crate::network::{
+    buffer::Buffer,
+    connection::{Readable, Writable},
+    decode::{DecodeMode, DecodeReport},
+    types::Identifier,
+    versions::VersionInfo,
+    ByteOrder, Error, PacketDirection, PacketState
+};
+#[cfg(feature = "std-net")]
+use crate::network::{
+    connection::{async_socket::AsyncSocketConnection, socket::SocketConnection, Client, Connection, Pipeline},
+    handshake::{Handshake, NextState}
+};
+
+pub const MAX_COOKIE_LENGTH: usize = 5120;
+
+// Cookies and transfers came with 1.20.5
+pub const COOKIE_PROTOCOL: i32 = 766;
+
+// The ids below are only known up to 1.21.1
+pub const LATEST_COOKIE_PROTOCOL: i32 = 767;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CookiePacket {
+    CookieRequest,
+    StoreCookie,
+    Transfer,
+    CookieResponse
+}
+
+impl CookiePacket {
+    pub fn direction(&self) -> PacketDirection {
+        match self {
+            CookiePacket::CookieResponse => PacketDirection::Serverbound,
+            _ => PacketDirection::Clientbound
+        }
+    }
+
+    // Servers may only store cookies and transfer in configuration and play
+    pub fn id(&self, state: PacketState, protocol: i32) -> Result<Option<i32>, Error> {
+        if protocol < COOKIE_PROTOCOL {
+            return Ok(None)
+        }
+        if protocol > LATEST_COOKIE_PROTOCOL {
+            return Err(Error::Other(format!(
+                "Unable to get the id of {:?} => Protocol {} is newer than the latest known protocol {}",
+                self, protocol, LATEST_COOKIE_PROTOCOL
+            )))
+        }
+
+        Ok(match (state, self) {
+            (PacketState::Login, CookiePacket::CookieRequest) => Some(0x05),
+            (PacketState::Login, CookiePacket::CookieResponse) => Some(0x04),
+            (PacketState::Configuration, CookiePacket::CookieRequest) => Some(0x00),
+            (PacketState::Configuration, CookiePacket::StoreCookie) => Some(0x0A),
+            (PacketState::Configuration, CookiePacket::Transfer) => Some(0x0B),
+            (PacketState::Configuration, CookiePacket::CookieResponse) => Some(0x01),
+            (PacketState::Play, CookiePacket::CookieRequest) => Some(0x16),
+            (PacketState::Play, CookiePacket::StoreCookie) => Some(0x6B),
+            (PacketState::Play, CookiePacket::Transfer) => Some(0x73),
+            (PacketState::Play, CookiePacket::CookieResponse) => Some(0x11),
+            _ => None
+        })
+    }
+
+    pub fn from_id(state: PacketState, direction: PacketDirection, protocol: i32, id: i32) -> Result<Option<Self>, Error> {
+        for packet in [CookiePacket::CookieRequest, CookiePacket::StoreCookie, CookiePacket::Transfer, CookiePacket::CookieResponse] {
+            if packet.direction() == direction && packet.id(state, protocol)? == Some(id) {
+                return Ok(Some(packet))
+            }
+        }
+        Ok(None)
+    }
+}
+
+pub trait CookieMessage: Readable + Writable {
+    const PACKET: CookiePacket;
+
+    fn encode(&self, state: PacketState, protocol: i32) -> Result<Buffer, Error> {
+        let id = Self::PACKET
+            .id(state, protocol)?
+            .ok_or_else(|| Error::Other(format!("{:?} isn't available in the {} state of protocol {}", Self::PACKET, state, protocol)))?;
+
+        let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        buffer.write_var_i32(id)?;
+        self.write(buffer)
+    }
+}
+
+fn check_payload(payload: &[u8]) -> Result<(), Error> {
+    if payload.len() > MAX_COOKIE_LENGTH {
+        return Err(Error::Other(format!("Cookie payload too big (was {} bytes, max {})", payload.len(), MAX_COOKIE_LENGTH)))
+    }
+    Ok(())
+}
+
+fn read_payload(buffer: &mut Buffer) -> Result<Vec<u8>, Error> {
+    let offset = buffer.position();
+    let length = buffer.read_length()?;
+    if length > MAX_COOKIE_LENGTH {
+        return Err(Error::Decode {
+            offset,
+            reason: format!("Cookie payload too big (was {} bytes, max {})", length, MAX_COOKIE_LENGTH)
+        })
+    }
+    buffer.read_bytes(length)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreCookie {
+    pub key: Identifier,
+    pub payload: Vec<u8>
+}
+
+impl CookieMessage for StoreCookie {
+    const PACKET: CookiePacket = CookiePacket::StoreCookie;
+}
+
+impl Writable for StoreCookie {
+    fn write(&self, buffer: Buffer) -> Result<Buffer, Error> {
+        check_payload(&self.payload)?;
+        let mut buffer = self.key.write(buffer)?;
+        buffer.write_byte_array(&self.payload)?;
+        Ok(buffer)
+    }
+}
+
+impl Readable for StoreCookie {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        Ok(Self {
+            key: Identifier::read(buffer)?,
+            payload: read_payload(buffer)?
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CookieRequest {
+    pub key: Identifier
+}
+
+impl CookieMessage for CookieRequest {
+    const PACKET: CookiePacket = CookiePacket::CookieRequest;
+}
+
+impl Writable for CookieRequest {
+    fn write(&self, buffer: Buffer) -> Result<Buffer, Error> {
+        self.key.write(buffer)
+    }
+}
+
+impl Readable for CookieRequest {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        Ok(Self { key: Identifier::read(buffer)? })
+    }
+}
+
+// A missing payload means no cookie is stored for the key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CookieResponse {
+    pub key: Identifier,
+    pub payload: Option<Vec<u8>>
+}
+
+impl CookieMessage for CookieResponse {
+    const PACKET: CookiePacket = CookiePacket::CookieResponse;
+}
+
+impl Writable for CookieResponse {
+    fn write(&self, buffer: Buffer) -> Result<Buffer, Error> {
+        let mut buffer = self.key.write(buffer)?;
+        buffer.write_bool(self.payload.is_some())?;
+        if let Some(payload) = &self.payload {
+            check_payload(payload)?;
+            buffer.write_byte_array(payload)?;
+        }
+        Ok(buffer)
+    }
+}
+
+impl Readable for CookieResponse {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        Ok(Self {
+            key: Identifier::read(buffer)?,
+            payload: if buffer.read_bool()? { Some(read_payload(buffer)?) } else { None }
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transfer {
+    pub host: String,
+    pub port: u16
+}
+
+impl CookieMessage for Transfer {
+    const PACKET: CookiePacket = CookiePacket::Transfer;
+}
+
+impl Writable for Transfer {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        buffer.write_str(&self.host)?;
+        buffer.write_var_i32(self.port as i32)?;
+        Ok(buffer)
+    }
+}
+
+impl Readable for Transfer {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        let host = buffer.read_string()?;
+        let offset = buffer.position();
+        let port = buffer.read_var_i32()?;
+        Ok(Self {
+            host,
+            port: u16::try_from(port).map_err(|_| {
+                Error::Decode {
+                    offset,
+                    reason: format!("Invalid transfer port {}", port)
+                }
+            })?
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct CookieJar {
+    protocol: i32,
+    cookies: Mutex<HashMap<Identifier, Vec<u8>>>
+}
+
+impl CookieJar {
+    pub fn new(version: &VersionInfo) -> Self {
+        Self {
+            protocol: version.protocol,
+            cookies: Mutex::new(HashMap::new())
+        }
+    }
+
+    pub fn protocol(&self) -> i32 {
+        self.protocol
+    }
+
+    pub fn store(&self, key: Identifier, payload: Vec<u8>) -> Result<(), Error> {
+        check_payload(&payload)?;
+        self.cookies().insert(key, payload);
+        Ok(())
+    }
+
+    pub fn get(&self, key: &Identifier) -> Option<Vec<u8>> {
+        self.cookies().get(key).cloned()
+    }
+
+    pub fn remove(&self, key: &Identifier) -> Option<Vec<u8>> {
+        self.cookies().remove(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cookies().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cookies().is_empty()
+    }
+
+    pub fn respond(&self, request: &CookieRequest) -> CookieResponse {
+        CookieResponse {
+            key: request.key.clone(),
+            payload: self.get(&request.key)
+        }
+    }
+
+    pub fn handle(&self, state: PacketState, direction: PacketDirection, packet: &mut Buffer, mode: DecodeMode, report: &DecodeReport) -> Result<Option<Buffer>, Error> {
+        if direction != PacketDirection::Clientbound || self.protocol < COOKIE_PROTOCOL {
+            return Ok(None)
+        }
+
+        let position = packet.position();
+        let id = packet.read_var_i32()?;
+        let response = match CookiePacket::from_id(state, direction, self.protocol, id)? {
+            Some(CookiePacket::StoreCookie) => {
+                let cookie = StoreCookie::read(packet)?;
+                mode.check_trailing(packet, state, id, report)?;
+                self.store(cookie.key, cookie.payload)?;
+                None
+            }
+            Some(CookiePacket::CookieRequest) => {
+                let request = CookieRequest::read(packet)?;
+                mode.check_trailing(packet, state, id, report)?;
+                Some(self.respond(&request).encode(state, self.protocol)?)
+            }
+            _ => None
+        };
+        packet.set_position(position);
+        Ok(response)
+    }
+
+    fn cookies(&self) -> MutexGuard<'_, HashMap<Identifier, Vec<u8>>> {
+        self.cookies.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(feature = "std-net")]
+fn transfer_handshake(transfer: &Transfer, cookie_jar: &CookieJar) -> Result<Handshake, Error> {
+    if cookie_jar.protocol < COOKIE_PROTOCOL {
+        return Err(Error::Other(format!("Unable to follow transfer => Protocol {} doesn't support transfers", cookie_jar.protocol)))
+    }
+
+    Ok(Handshake {
+        protocol_version: cookie_jar.protocol,
+        server_address: transfer.host.clone(),
+        server_port: transfer.port,
+        next_state: NextState::Transfer
+    })
+}
+
+#[cfg(feature = "std-net")]
+pub fn follow_transfer<'a>(transfer: &Transfer, cookie_jar: Arc<CookieJar>, pipeline: Pipeline<'a>) -> Result<SocketConnection<'a, Client>, Error> {
+    let handshake = transfer_handshake(transfer, &cookie_jar)?;
+    let mut connection = SocketConnection::<Client>::new(TcpStream::connect((transfer.host.as_str(), transfer.port))?, pipeline);
+    connection.write(handshake)?;
+    connection.set_state(PacketState::Login);
+    connection.set_protocol_version(Some(cookie_jar.protocol));
+    connection.set_cookie_jar(Some(cookie_jar));
+    Ok(connection)
+}
+
+#[cfg(feature = "std-net")]
+pub async fn follow_transfer_async<'a>(transfer: &Transfer, cookie_jar: Arc<CookieJar>, pipeline: Pipeline<'a>) -> Result<AsyncSocketConnection<'a, Client>, Error> {
+    let handshake = transfer_handshake(transfer, &cookie_jar)?;
+    let mut connection = AsyncSocketConnection::<Client>::new(tokio::net::TcpStream::connect((transfer.host.as_str(), transfer.port)).await?, pipeline);
+    connection.write(handshake).await?;
+    connection.set_state(PacketState::Login);
+    connection.set_protocol_version(Some(cookie_jar.protocol));
+    connection.set_cookie_jar(Some(cookie_jar));
+    Ok(connection)
+}
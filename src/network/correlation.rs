@@ -0,0 +1,283 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    future::Future,
+    marker::PhantomData,
+    sync::{mpsc, Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration
+};
+
+use thiserror::Error;
+use tokio::sync::oneshot::{self, Receiver, Sender};
+
+use crate::network::{buffer::Buffer, connection::machine::DecodedPacket, Error as NetworkError, Packet, PacketDescriptor, PacketState};
+
+#[derive(Debug, Error)]
+pub enum AwaitError {
+    #[error("Timeout => No awaited packet arrived within {0:?}")]
+    Timeout(Duration),
+    #[error("Closed => The connection closed before an awaited packet arrived")]
+    Closed,
+    #[error("Decode Error => {0}")]
+    Decode(NetworkError)
+}
+
+#[derive(Debug)]
+enum WaiterSender {
+    Buffer(Sender<Buffer>),
+    Decoded(Sender<DecodedPacket>),
+    Blocking(mpsc::SyncSender<DecodedPacket>)
+}
+
+impl WaiterSender {
+    fn is_closed(&self) -> bool {
+        match self {
+            Self::Buffer(sender) => sender.is_closed(),
+            Self::Decoded(sender) => sender.is_closed(),
+            Self::Blocking(_) => false
+        }
+    }
+
+    fn send(self, state: PacketState, id: i32, buffer: Buffer) -> Result<(), Buffer> {
+        match self {
+            Self::Buffer(sender) => sender.send(buffer),
+            Self::Decoded(sender) => sender.send(DecodedPacket { state, id, buffer }).map_err(|packet| packet.buffer),
+            Self::Blocking(sender) => {
+                sender.try_send(DecodedPacket { state, id, buffer }).map_err(|error| {
+                    match error {
+                        mpsc::TrySendError::Full(packet) | mpsc::TrySendError::Disconnected(packet) => packet.buffer
+                    }
+                })
+            }
+        }
+    }
+}
+
+type Waiter = Arc<Mutex<Option<WaiterSender>>>;
+
+fn is_closed(waiter: &Waiter) -> bool {
+    waiter.lock().unwrap_or_else(PoisonError::into_inner).as_ref().is_none_or(WaiterSender::is_closed)
+}
+
+#[derive(Debug, Default)]
+pub struct ResponseCorrelator {
+    waiters: HashMap<(PacketState, i32), VecDeque<Waiter>>,
+    closed: bool
+}
+
+impl ResponseCorrelator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn expect(&mut self, state: PacketState, id: i32) -> Receiver<Buffer> {
+        let (sender, receiver) = oneshot::channel();
+        self.register(&[(state, id)], WaiterSender::Buffer(sender));
+        receiver
+    }
+
+    pub fn expect_any(&mut self, packets: &[(PacketState, i32)]) -> Receiver<DecodedPacket> {
+        let (sender, receiver) = oneshot::channel();
+        self.register(packets, WaiterSender::Decoded(sender));
+        receiver
+    }
+
+    pub fn expect_any_blocking(&mut self, packets: &[(PacketState, i32)]) -> mpsc::Receiver<DecodedPacket> {
+        self.register_blocking(packets).0
+    }
+
+    fn register_blocking(&mut self, packets: &[(PacketState, i32)]) -> (mpsc::Receiver<DecodedPacket>, Waiter) {
+        let (sender, receiver) = mpsc::sync_channel(1);
+        (receiver, self.register(packets, WaiterSender::Blocking(sender)))
+    }
+
+    // Closed waiters are dropped here as well, so waits that timed out don't pile up for packets that never come
+    fn register(&mut self, packets: &[(PacketState, i32)], sender: WaiterSender) -> Waiter {
+        let waiter = Arc::new(Mutex::new(Some(sender)));
+        if self.closed {
+            waiter.lock().unwrap_or_else(PoisonError::into_inner).take();
+            return waiter
+        }
+
+        self.prune();
+        for key in packets {
+            self.waiters.entry(*key).or_default().push_back(waiter.clone());
+        }
+        waiter
+    }
+
+    pub fn prune(&mut self) {
+        self.waiters.retain(|_, waiters| {
+            waiters.retain(|waiter| !is_closed(waiter));
+            !waiters.is_empty()
+        });
+    }
+
+    pub fn is_waiting(&self, state: PacketState, id: i32) -> bool {
+        self.waiters.get(&(state, id)).is_some_and(|waiters| waiters.iter().any(|waiter| !is_closed(waiter)))
+    }
+
+    pub fn waiting(&self) -> usize {
+        self.waiters.values().flatten().filter(|waiter| !is_closed(waiter)).map(Arc::as_ptr).collect::<HashSet<_>>().len()
+    }
+
+    // Blocking waits hold their waiter as well, so the senders are taken out to end them
+    pub fn clear(&mut self) {
+        for waiter in self.waiters.drain().flat_map(|(_, waiters)| waiters) {
+            waiter.lock().unwrap_or_else(PoisonError::into_inner).take();
+        }
+    }
+
+    pub fn close(&mut self) {
+        self.closed = true;
+        self.clear();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    // For read loops, a failed read or EOF closes the correlator so waiters don't sit out their timeout
+    pub fn offer_read(&mut self, state: PacketState, read: Result<Buffer, NetworkError>) -> Result<Option<Buffer>, NetworkError> {
+        match read {
+            Ok(packet) => Ok(self.offer(state, packet)),
+            Err(error) => {
+                self.close();
+                Err(error)
+            }
+        }
+    }
+
+    pub fn offer(&mut self, state: PacketState, mut packet: Buffer) -> Option<Buffer> {
+        let position = packet.position();
+        let id = packet.read_var_i32();
+        packet.set_position(position);
+        let Ok(id) = id else { return Some(packet) };
+
+        let key = (state, id);
+        let Some(waiters) = self.waiters.get_mut(&key) else { return Some(packet) };
+        loop {
+            let Some(waiter) = waiters.pop_front() else {
+                self.waiters.remove(&key);
+                return Some(packet)
+            };
+            let Some(sender) = waiter.lock().unwrap_or_else(PoisonError::into_inner).take() else {
+                continue
+            };
+            match sender.send(state, id, packet) {
+                Ok(()) => break,
+                Err(returned) => packet = returned
+            }
+        }
+
+        if waiters.is_empty() {
+            self.waiters.remove(&key);
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SharedCorrelator(Arc<Mutex<ResponseCorrelator>>);
+
+impl SharedCorrelator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, ResponseCorrelator> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn offer(&self, state: PacketState, packet: Buffer) -> Option<Buffer> {
+        self.lock().offer(state, packet)
+    }
+
+    pub fn offer_read(&self, state: PacketState, read: Result<Buffer, NetworkError>) -> Result<Option<Buffer>, NetworkError> {
+        self.lock().offer_read(state, read)
+    }
+
+    pub fn close(&self) {
+        self.lock().close();
+    }
+
+    pub fn await_packet<P: Packet>(&self, timeout: Duration) -> impl Future<Output = Result<P, AwaitError>> {
+        let receiver = self.lock().expect_any(&[(P::state(), P::id())]);
+        async move { read_packet(receive(receiver, timeout).await?) }
+    }
+
+    pub fn await_any(&self, packets: &[PacketDescriptor], timeout: Duration) -> impl Future<Output = Result<DecodedPacket, AwaitError>> {
+        let receiver = self.lock().expect_any(&keys(packets));
+        receive(receiver, timeout)
+    }
+
+    pub fn await_packet_blocking<P: Packet>(&self, timeout: Duration) -> BlockingAwait<P> {
+        BlockingAwait::new(self.lock().register_blocking(&[(P::state(), P::id())]), timeout)
+    }
+
+    pub fn await_any_blocking(&self, packets: &[PacketDescriptor], timeout: Duration) -> BlockingAwait<DecodedPacket> {
+        BlockingAwait::new(self.lock().register_blocking(&keys(packets)), timeout)
+    }
+}
+
+fn keys(packets: &[PacketDescriptor]) -> Vec<(PacketState, i32)> {
+    packets.iter().map(|packet| (packet.state, packet.id)).collect()
+}
+
+async fn receive(receiver: Receiver<DecodedPacket>, timeout: Duration) -> Result<DecodedPacket, AwaitError> {
+    match tokio::time::timeout(timeout, receiver).await {
+        Ok(Ok(packet)) => Ok(packet),
+        Ok(Err(_)) => Err(AwaitError::Closed),
+        Err(_) => Err(AwaitError::Timeout(timeout))
+    }
+}
+
+fn read_packet<P: Packet>(mut packet: DecodedPacket) -> Result<P, AwaitError> {
+    P::read(&mut packet.buffer).map_err(AwaitError::Decode)
+}
+
+// Dropping it closes its waiter, mpsc senders can't tell that their receiver is gone
+#[derive(Debug)]
+pub struct BlockingAwait<T> {
+    receiver: mpsc::Receiver<DecodedPacket>,
+    waiter: Waiter,
+    timeout: Duration,
+    packet: PhantomData<T>
+}
+
+impl<T> BlockingAwait<T> {
+    fn new((receiver, waiter): (mpsc::Receiver<DecodedPacket>, Waiter), timeout: Duration) -> Self {
+        Self {
+            receiver,
+            waiter,
+            timeout,
+            packet: PhantomData
+        }
+    }
+
+    fn receive(&self) -> Result<DecodedPacket, AwaitError> {
+        self.receiver.recv_timeout(self.timeout).map_err(|error| {
+            match error {
+                mpsc::RecvTimeoutError::Timeout => AwaitError::Timeout(self.timeout),
+                mpsc::RecvTimeoutError::Disconnected => AwaitError::Closed
+            }
+        })
+    }
+}
+
+impl<T> Drop for BlockingAwait<T> {
+    fn drop(&mut self) {
+        self.waiter.lock().unwrap_or_else(PoisonError::into_inner).take();
+    }
+}
+
+impl<P: Packet> BlockingAwait<P> {
+    pub fn wait(self) -> Result<P, AwaitError> {
+        read_packet(self.receive()?)
+    }
+}
+
+impl BlockingAwait<DecodedPacket> {
+    pub fn wait(self) -> Result<DecodedPacket, AwaitError> {
+        self.receive()
+    }
+}
@@ -0,0 +1,119 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, PoisonError}
+};
+
+use crate::network::{buffer::Buffer, Error, PacketState};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DecodeMode {
+    #[default]
+    Strict,
+    Lenient
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DecodeIssue {
+    TrailingBytes,
+    UnknownVariant
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeIssueLine {
+    pub state: PacketState,
+    pub id: i32,
+    pub issue: DecodeIssue,
+    pub count: u64
+}
+
+pub trait UnknownVariant {
+    fn is_unknown(&self) -> bool;
+}
+
+#[derive(Debug, Default)]
+pub struct DecodeReport {
+    issues: Mutex<HashMap<(PacketState, i32, DecodeIssue), u64>>
+}
+
+impl DecodeReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, state: PacketState, id: i32, issue: DecodeIssue) {
+        *self.issues.lock().unwrap_or_else(PoisonError::into_inner).entry((state, id, issue)).or_default() += 1;
+    }
+
+    pub fn count(&self, state: PacketState, id: i32, issue: DecodeIssue) -> u64 {
+        self.issues.lock().unwrap_or_else(PoisonError::into_inner).get(&(state, id, issue)).copied().unwrap_or(0)
+    }
+
+    pub fn lines(&self) -> Vec<DecodeIssueLine> {
+        let mut lines = self
+            .issues
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|((state, id, issue), count)| {
+                DecodeIssueLine {
+                    state: *state,
+                    id: *id,
+                    issue: *issue,
+                    count: *count
+                }
+            })
+            .collect::<Vec<_>>();
+        lines.sort_by_key(|line| (line.id, line.issue, line.state.to_string()));
+        lines
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.issues.lock().unwrap_or_else(PoisonError::into_inner).is_empty()
+    }
+
+    pub fn clear(&self) {
+        self.issues.lock().unwrap_or_else(PoisonError::into_inner).clear();
+    }
+}
+
+impl DecodeMode {
+    pub fn check_trailing(self, packet: &mut Buffer, state: PacketState, id: i32, report: &DecodeReport) -> Result<(), Error> {
+        let remaining = packet.remaining();
+        if remaining == 0 {
+            return Ok(())
+        }
+
+        match self {
+            Self::Strict => {
+                Err(Error::Decode {
+                    offset: packet.position(),
+                    reason: format!("{} trailing bytes after packet 0x{:02X} in state {}", remaining, id, state)
+                })
+            }
+            Self::Lenient => {
+                report.record(state, id, DecodeIssue::TrailingBytes);
+                packet.set_position(packet.len());
+                Ok(())
+            }
+        }
+    }
+
+    pub fn check_variant<T: UnknownVariant>(self, value: T, packet: &Buffer, state: PacketState, id: i32, report: &DecodeReport) -> Result<T, Error> {
+        if !value.is_unknown() {
+            return Ok(value)
+        }
+
+        match self {
+            Self::Strict => {
+                Err(Error::Decode {
+                    offset: packet.position(),
+                    reason: format!("Unknown enum value in packet 0x{:02X} in state {}", id, state)
+                })
+            }
+            Self::Lenient => {
+                report.record(state, id, DecodeIssue::UnknownVariant);
+                Ok(value)
+            }
+        }
+    }
+}
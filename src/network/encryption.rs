@@ -0,0 +1,109 @@
+use aes::{
+    cipher::{generic_array::GenericArray, BlockDecryptMut, BlockEncryptMut, KeyIvInit},
+    Aes128
+};
+use rand::{thread_rng, CryptoRng, RngCore};
+use rsa::{
+    pkcs8::{DecodePublicKey, EncodePublicKey},
+    Pkcs1v15Encrypt, RsaPrivateKey, RsaPublicKey
+};
+use sha1::{Digest, Sha1};
+
+use crate::network::Error;
+
+pub struct Cipher {
+    encryptor: cfb8::Encryptor<Aes128>,
+    decryptor: cfb8::Decryptor<Aes128>
+}
+
+impl Cipher {
+    pub fn new(shared_secret: &[u8; 16]) -> Self {
+        Self {
+            encryptor: cfb8::Encryptor::new(shared_secret.into(), shared_secret.into()),
+            decryptor: cfb8::Decryptor::new(shared_secret.into(), shared_secret.into())
+        }
+    }
+
+    pub fn encrypt(&mut self, data: &mut [u8]) {
+        for byte in data.iter_mut() {
+            self.encryptor.encrypt_block_mut(GenericArray::from_mut_slice(std::slice::from_mut(byte)));
+        }
+    }
+
+    pub fn decrypt(&mut self, data: &mut [u8]) {
+        for byte in data.iter_mut() {
+            self.decryptor.decrypt_block_mut(GenericArray::from_mut_slice(std::slice::from_mut(byte)));
+        }
+    }
+}
+
+pub fn generate_shared_secret() -> [u8; 16] {
+    generate_shared_secret_with(&mut thread_rng())
+}
+
+pub fn generate_shared_secret_with(rng: &mut (impl RngCore + CryptoRng)) -> [u8; 16] {
+    let mut shared_secret = [0; 16];
+    rng.fill_bytes(&mut shared_secret);
+    shared_secret
+}
+
+pub fn generate_key_pair(bits: usize) -> Result<RsaPrivateKey, Error> {
+    generate_key_pair_with(&mut thread_rng(), bits)
+}
+
+pub fn generate_key_pair_with(rng: &mut (impl RngCore + CryptoRng), bits: usize) -> Result<RsaPrivateKey, Error> {
+    RsaPrivateKey::new(rng, bits).map_err(|error| Error::Other(format!("Unable to generate RSA key pair => {}", error)))
+}
+
+pub fn public_key_der(private_key: &RsaPrivateKey) -> Result<Vec<u8>, Error> {
+    Ok(private_key
+        .to_public_key()
+        .to_public_key_der()
+        .map_err(|error| Error::Other(format!("Unable to encode public key => {}", error)))?
+        .into_vec())
+}
+
+pub fn rsa_encrypt(public_key_der: &[u8], data: &[u8]) -> Result<Vec<u8>, Error> {
+    let public_key = RsaPublicKey::from_public_key_der(public_key_der).map_err(|error| Error::Other(format!("Unable to parse public key => {}", error)))?;
+    public_key
+        .encrypt(&mut thread_rng(), Pkcs1v15Encrypt, data)
+        .map_err(|error| Error::Other(format!("Unable to encrypt data => {}", error)))
+}
+
+pub fn rsa_decrypt(private_key: &RsaPrivateKey, data: &[u8]) -> Result<Vec<u8>, Error> {
+    private_key
+        .decrypt(Pkcs1v15Encrypt, data)
+        .map_err(|error| Error::Other(format!("Unable to decrypt data => {}", error)))
+}
+
+pub fn server_hash(server_id: &str, shared_secret: &[u8], public_key_der: &[u8]) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(server_id.as_bytes());
+    hasher.update(shared_secret);
+    hasher.update(public_key_der);
+    minecraft_digest(hasher.finalize().into())
+}
+
+// Minecraft prints the digest as a signed two's complement number in hex
+pub fn minecraft_digest(mut hash: [u8; 20]) -> String {
+    let negative = hash[0] & 0x80 != 0;
+    if negative {
+        let mut carry = true;
+        for byte in hash.iter_mut().rev() {
+            *byte = !*byte;
+            if carry {
+                let (value, overflow) = byte.overflowing_add(1);
+                *byte = value;
+                carry = overflow;
+            }
+        }
+    }
+
+    let digest = hash.iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+    let digest = digest.trim_start_matches('0');
+    if negative {
+        format!("-{}", digest)
+    } else {
+        digest.to_string()
+    }
+}
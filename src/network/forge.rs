@@ -0,0 +1,210 @@
+use serde_json::Value;
+
+use crate::network::{buffer::Buffer, ByteOrder, Error};
+
+// Forge 1.13 and newer wraps its login handshake into login plugin requests
+pub const LOGIN_WRAPPER_CHANNEL: &str = "fml:loginwrapper";
+pub const HANDSHAKE_CHANNEL: &str = "fml:handshake";
+
+// Mods that are only needed on the server are sent with this marker instead of a version
+pub const IGNORE_SERVER_ONLY: &str =
+    "OHNOES\u{1F631}\u{1F631}\u{1F631}\u{1F631}\u{1F631}\u{1F631}\u{1F631}\u{1F631}\u{1F631}\u{1F631}\u{1F631}\u{1F631}\u{1F631}\u{1F631}\u{1F631}\u{1F631}\u{1F631}";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForgeMod {
+    pub id: String,
+    pub version: Option<String>
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForgeChannel {
+    pub name: String,
+    pub version: String,
+    pub required: bool
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ForgeStatus {
+    Legacy {
+        mod_type: String,
+        mods: Vec<ForgeMod>
+    },
+    ForgeData {
+        network_version: i32,
+        mods: Vec<ForgeMod>,
+        channels: Vec<ForgeChannel>,
+        truncated: bool
+    },
+    Raw(Value)
+}
+
+impl ForgeStatus {
+    pub fn from_status_json(status: &Value) -> Option<Self> {
+        if let Some(forge_data) = status.get("forgeData") {
+            return Some(Self::from_forge_data(forge_data))
+        }
+        status.get("modinfo").map(Self::from_modinfo)
+    }
+
+    pub(crate) fn from_forge_data(forge_data: &Value) -> Self {
+        Self::read_forge_data(forge_data).unwrap_or_else(|| Self::Raw(forge_data.clone()))
+    }
+
+    pub(crate) fn from_modinfo(modinfo: &Value) -> Self {
+        Self::read_modinfo(modinfo).unwrap_or_else(|| Self::Raw(modinfo.clone()))
+    }
+
+    fn read_modinfo(modinfo: &Value) -> Option<Self> {
+        let mods = modinfo
+            .get("modList")?
+            .as_array()?
+            .iter()
+            .map(|entry| {
+                Some(ForgeMod {
+                    id: entry.get("modid")?.as_str()?.to_string(),
+                    version: entry.get("version").and_then(Value::as_str).map(str::to_string)
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self::Legacy {
+            mod_type: modinfo.get("type")?.as_str()?.to_string(),
+            mods
+        })
+    }
+
+    fn read_forge_data(forge_data: &Value) -> Option<Self> {
+        let network_version = i32::try_from(forge_data.get("fmlNetworkVersion")?.as_i64()?).ok()?;
+        if let Some(packed) = forge_data.get("d").and_then(Value::as_str) {
+            let (truncated, mods, channels) = read_packed(&mut decode_packed(packed).ok()?).ok()?;
+            return Some(Self::ForgeData {
+                network_version,
+                mods,
+                channels,
+                truncated
+            })
+        }
+
+        let mods = forge_data
+            .get("mods")?
+            .as_array()?
+            .iter()
+            .map(|entry| {
+                Some(ForgeMod {
+                    id: entry.get("modId")?.as_str()?.to_string(),
+                    version: entry.get("modmarker").and_then(Value::as_str).filter(|marker| *marker != IGNORE_SERVER_ONLY).map(str::to_string)
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let channels = forge_data
+            .get("channels")?
+            .as_array()?
+            .iter()
+            .map(|entry| {
+                Some(ForgeChannel {
+                    name: entry.get("res")?.as_str()?.to_string(),
+                    version: entry.get("version")?.as_str()?.to_string(),
+                    required: entry.get("required").and_then(Value::as_bool).unwrap_or(false)
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self::ForgeData {
+            network_version,
+            mods,
+            channels,
+            truncated: forge_data.get("truncated").and_then(Value::as_bool).unwrap_or(false)
+        })
+    }
+
+    pub fn mods(&self) -> &[ForgeMod] {
+        match self {
+            Self::Legacy { mods, .. } | Self::ForgeData { mods, .. } => mods,
+            Self::Raw(_) => &[]
+        }
+    }
+
+    pub fn channels(&self) -> &[ForgeChannel] {
+        match self {
+            Self::ForgeData { channels, .. } => channels,
+            Self::Legacy { .. } | Self::Raw(_) => &[]
+        }
+    }
+
+    pub fn is_truncated(&self) -> bool {
+        matches!(self, Self::ForgeData { truncated: true, .. })
+    }
+}
+
+// 15 bits per char keep them below the surrogates, the first two chars are the byte length
+pub fn decode_packed(packed: &str) -> Result<Buffer, Error> {
+    let chars = packed.encode_utf16().map(|char| u32::from(char & 0x7FFF)).collect::<Vec<_>>();
+    let [low, high, chars @ ..] = chars.as_slice() else {
+        return Err(Error::Other("Unable to decode forge data => Missing the length".to_string()))
+    };
+    let length = (low | (high << 15)) as usize;
+    if chars.len() * 15 < length * 8 {
+        return Err(Error::Other(format!("Unable to decode forge data => {} chars can't hold {} bytes", chars.len(), length)))
+    }
+
+    let mut bytes = Vec::with_capacity(length);
+    let (mut bits, mut bit_count) = (0u32, 0);
+    for char in chars {
+        bits |= char << bit_count;
+        bit_count += 15;
+        while bit_count >= 8 && bytes.len() < length {
+            bytes.push(bits as u8);
+            bits >>= 8;
+            bit_count -= 8;
+        }
+    }
+    Ok(Buffer::new(bytes, false, Some(ByteOrder::BigEndian)))
+}
+
+pub fn encode_packed(bytes: &[u8]) -> String {
+    let mut chars = vec![(bytes.len() & 0x7FFF) as u16, ((bytes.len() >> 15) & 0x7FFF) as u16];
+    let (mut bits, mut bit_count) = (0u32, 0);
+    for byte in bytes {
+        bits |= u32::from(*byte) << bit_count;
+        bit_count += 8;
+        if bit_count >= 15 {
+            chars.push((bits & 0x7FFF) as u16);
+            bits >>= 15;
+            bit_count -= 15;
+        }
+    }
+    if bit_count > 0 {
+        chars.push((bits & 0x7FFF) as u16);
+    }
+    String::from_utf16_lossy(&chars)
+}
+
+// Server only mods set the lowest bit of the channel count and have no version
+fn read_packed(buffer: &mut Buffer) -> Result<(bool, Vec<ForgeMod>, Vec<ForgeChannel>), Error> {
+    let truncated = buffer.read_bool()?;
+    let mut mods = Vec::new();
+    let mut channels = Vec::new();
+    for _ in 0..buffer.read_u16()? {
+        let flags = buffer.read_var_i32()?;
+        let id = buffer.read_string()?;
+        let version = match flags & 0x01 != 0 {
+            true => None,
+            false => Some(buffer.read_string()?)
+        };
+        for _ in 0..flags >> 1 {
+            channels.push(ForgeChannel {
+                name: format!("{}:{}", id, buffer.read_string()?),
+                version: buffer.read_string()?,
+                required: buffer.read_bool()?
+            });
+        }
+        mods.push(ForgeMod { id, version });
+    }
+
+    for _ in 0..buffer.read_var_i32()? {
+        channels.push(ForgeChannel {
+            name: buffer.read_string()?,
+            version: buffer.read_string()?,
+            required: buffer.read_bool()?
+        });
+    }
+    Ok((truncated, mods, channels))
+}
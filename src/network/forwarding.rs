@@ -0,0 +1,204 @@
+use std::net::IpAddr;
+
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::{
+    network::{
+        buffer::Buffer,
+        connection::{Readable, Writable},
+        login::{GameProfile, CLIENTBOUND_LOGIN_PLUGIN_REQUEST, SERVERBOUND_LOGIN_PLUGIN_RESPONSE},
+        plugin::PluginMessage,
+        types::Identifier,
+        ByteOrder, Error
+    },
+    profile::Property
+};
+
+pub const VELOCITY_CHANNEL: &str = "velocity:player_info";
+pub const VELOCITY_DEFAULT_VERSION: u8 = 1;
+// Versions 2 and 3 forward the chat signing key of 1.19, which isn't supported
+pub const VELOCITY_LAZY_SESSION_VERSION: u8 = 4;
+pub const SIGNATURE_LENGTH: usize = 32;
+
+const SEPARATOR: char = '\0';
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForwardedPlayer {
+    pub address: IpAddr,
+    pub profile: GameProfile,
+    pub properties: Vec<Property>
+}
+
+impl ForwardedPlayer {
+    pub fn new(address: IpAddr, profile: GameProfile, properties: Vec<Property>) -> Self {
+        Self { address, profile, properties }
+    }
+
+    // BungeeCord appends the address, the UUID without dashes and the properties, separated by NUL
+    pub fn to_legacy_address(&self, host: &str) -> String {
+        let mut address = format!("{}{}{}{}{}", host, SEPARATOR, self.address, SEPARATOR, self.profile.id.simple());
+        if !self.properties.is_empty() {
+            let properties = self
+                .properties
+                .iter()
+                .map(|property| {
+                    let mut value = json!({ "name": property.name, "value": property.value });
+                    if let Some(signature) = &property.signature {
+                        value["signature"] = Value::String(signature.clone());
+                    }
+                    value
+                })
+                .collect::<Vec<_>>();
+            address = format!("{}{}{}", address, SEPARATOR, Value::Array(properties));
+        }
+        address
+    }
+
+    pub fn from_legacy_address(address: &str, name: impl Into<String>) -> Result<(String, Self), Error> {
+        let invalid = |reason: String| Error::Decode { offset: 0, reason };
+        let parts = address.split(SEPARATOR).collect::<Vec<_>>();
+        let [host, player_address, id, properties @ ..] = parts.as_slice() else {
+            return Err(invalid("Server address isn't forwarded, is legacy forwarding enabled on the proxy?".to_string()))
+        };
+
+        let properties = match properties {
+            [] => Vec::new(),
+            [properties] => serde_json::from_str(properties).map_err(|error| invalid(format!("Invalid forwarded properties => {}", error)))?,
+            _ => return Err(invalid(format!("Forwarded server address has {} parts, at most 4 are expected", parts.len())))
+        };
+
+        Ok((
+            host.to_string(),
+            Self {
+                address: player_address
+                    .parse()
+                    .map_err(|error| invalid(format!("Invalid forwarded address {} => {}", player_address, error)))?,
+                profile: GameProfile::new(Uuid::parse_str(id).map_err(|error| invalid(format!("Invalid forwarded UUID {} => {}", id, error)))?, name),
+                properties
+            }
+        ))
+    }
+
+    pub fn to_velocity_data(&self, secret: &[u8], version: u8) -> Result<Vec<u8>, Error> {
+        let mut data = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        data.write_var_i32(version as i32)?;
+        data.write_string(self.address.to_string())?;
+        let mut data = self.profile.id.write(data)?;
+        data.write_str(&self.profile.name)?;
+        let data = self.properties.write(data)?.to_bytes();
+
+        let mut signed = mac(secret)?.chain_update(&data).finalize().into_bytes().to_vec();
+        signed.extend(data);
+        Ok(signed)
+    }
+
+    pub fn from_velocity_data(signed: &[u8], secret: &[u8]) -> Result<(u8, Self), Error> {
+        if signed.len() < SIGNATURE_LENGTH {
+            return Err(Error::Decode {
+                offset: signed.len(),
+                reason: "Forwarded player info is shorter than its signature".to_string()
+            })
+        }
+
+        let (signature, data) = signed.split_at(SIGNATURE_LENGTH);
+        mac(secret)?.chain_update(data).verify_slice(signature).map_err(|_| {
+            Error::Decode {
+                offset: 0,
+                reason: "Invalid signature of the forwarded player info, do the forwarding secrets match?".to_string()
+            }
+        })?;
+
+        let mut data = Buffer::new(data.to_vec(), false, Some(ByteOrder::BigEndian));
+        let version = match data.read_var_i32()? {
+            1 => VELOCITY_DEFAULT_VERSION,
+            4 => VELOCITY_LAZY_SESSION_VERSION,
+            version => {
+                return Err(Error::Decode {
+                    offset: SIGNATURE_LENGTH,
+                    reason: format!("Unsupported forwarding version {}", version)
+                })
+            }
+        };
+
+        let offset = SIGNATURE_LENGTH + data.position();
+        let address = data.read_string()?;
+        Ok((
+            version,
+            Self {
+                address: address.parse().map_err(|error| {
+                    Error::Decode {
+                        offset,
+                        reason: format!("Invalid forwarded address {} => {}", address, error)
+                    }
+                })?,
+                profile: GameProfile::new(Uuid::read(&mut data)?, data.read_string()?),
+                properties: Vec::read(&mut data)?
+            }
+        ))
+    }
+}
+
+fn mac(secret: &[u8]) -> Result<Hmac<Sha256>, Error> {
+    Hmac::new_from_slice(secret).map_err(|error| Error::Other(format!("Invalid forwarding secret => {}", error)))
+}
+
+pub fn velocity_request(message_id: i32, version: u8) -> Result<Buffer, Error> {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(CLIENTBOUND_LOGIN_PLUGIN_REQUEST)?;
+    buffer.write_var_i32(message_id)?;
+    PluginMessage {
+        channel: Identifier::parse(VELOCITY_CHANNEL)?,
+        data: vec![version]
+    }
+    .write(buffer)
+}
+
+pub fn answer_velocity_request(packet: &mut Buffer, secret: &[u8], player: &ForwardedPlayer) -> Result<Option<Buffer>, Error> {
+    let id = packet.read_var_i32()?;
+    if id != CLIENTBOUND_LOGIN_PLUGIN_REQUEST {
+        return Err(Error::IllegalPacket(id, "Login".to_string()))
+    }
+
+    let message_id = packet.read_var_i32()?;
+    let request = PluginMessage::read(packet)?;
+    if request.channel.to_string() != VELOCITY_CHANNEL {
+        return Ok(None)
+    }
+
+    let mut response = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    response.write_var_i32(SERVERBOUND_LOGIN_PLUGIN_RESPONSE)?;
+    response.write_var_i32(message_id)?;
+    response.write_bool(true)?;
+    response.write_bytes(&player.to_velocity_data(secret, answered_version(&request.data))?)?;
+    Ok(Some(response))
+}
+
+fn answered_version(request: &[u8]) -> u8 {
+    match request.first() {
+        Some(requested) if *requested >= VELOCITY_LAZY_SESSION_VERSION => VELOCITY_LAZY_SESSION_VERSION,
+        _ => VELOCITY_DEFAULT_VERSION
+    }
+}
+
+pub fn velocity_login_handler(secret: Vec<u8>, player: ForwardedPlayer) -> impl FnMut(&[u8]) -> Result<Option<Vec<u8>>, Error> + Send + 'static {
+    move |request| Ok(Some(player.to_velocity_data(&secret, answered_version(request))?))
+}
+
+pub fn read_velocity_response(packet: &mut Buffer, message_id: i32, secret: &[u8]) -> Result<(u8, ForwardedPlayer), Error> {
+    let id = packet.read_var_i32()?;
+    if id != SERVERBOUND_LOGIN_PLUGIN_RESPONSE {
+        return Err(Error::IllegalPacket(id, "Login".to_string()))
+    }
+
+    let response_id = packet.read_var_i32()?;
+    if response_id != message_id {
+        return Err(Error::Other(format!("Unexpected login plugin response {}, expected {}", response_id, message_id)))
+    }
+    if !packet.read_bool()? {
+        return Err(Error::Other("The proxy didn't forward the player, is modern forwarding enabled?".to_string()))
+    }
+    ForwardedPlayer::from_velocity_data(&packet.read_bytes(packet.remaining())?, secret)
+}
@@ -0,0 +1,68 @@
+use crate::network::{buffer::Buffer, connection::Writable, Error};
+
+pub const HANDSHAKE: i32 = 0x00;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NextState {
+    Status,
+    Login,
+    Transfer
+}
+
+impl NextState {
+    pub fn id(&self) -> i32 {
+        match self {
+            NextState::Status => 1,
+            NextState::Login => 2,
+            NextState::Transfer => 3
+        }
+    }
+
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            1 => Some(NextState::Status),
+            2 => Some(NextState::Login),
+            3 => Some(NextState::Transfer),
+            _ => None
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Handshake {
+    pub protocol_version: i32,
+    pub server_address: String,
+    pub server_port: u16,
+    pub next_state: NextState
+}
+
+impl Handshake {
+    pub fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        let id = buffer.read_var_i32()?;
+        if id != HANDSHAKE {
+            return Err(Error::IllegalPacket(id, "Handshaking".to_string()))
+        }
+
+        let protocol_version = buffer.read_var_i32()?;
+        let server_address = buffer.read_string()?;
+        let server_port = buffer.read_u16()?;
+        let next_state = buffer.read_var_i32()?;
+        Ok(Self {
+            protocol_version,
+            server_address,
+            server_port,
+            next_state: NextState::from_id(next_state).ok_or_else(|| Error::Other(format!("Invalid next state {} in handshake", next_state)))?
+        })
+    }
+}
+
+impl Writable for Handshake {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        buffer.write_var_i32(HANDSHAKE)?;
+        buffer.write_var_i32(self.protocol_version)?;
+        buffer.write_str(&self.server_address)?;
+        buffer.write_u16(self.server_port)?;
+        buffer.write_var_i32(self.next_state.id())?;
+        Ok(buffer)
+    }
+}
@@ -0,0 +1,235 @@
+use crate::network::{
+    buffer::Buffer,
+    connection::{Readable, Writable},
+    nbt::{Compound, Tag},
+    types::data_component_names,
+    Error
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ComponentKind {
+    CustomData,
+    MaxDamage,
+    Damage,
+    Unbreakable,
+    CustomName,
+    Lore,
+    Enchantments,
+    Other(i32)
+}
+
+const TYPED_KINDS: [(ComponentKind, &str); 7] = [
+    (ComponentKind::CustomData, "custom_data"),
+    (ComponentKind::MaxDamage, "max_damage"),
+    (ComponentKind::Damage, "damage"),
+    (ComponentKind::Unbreakable, "unbreakable"),
+    (ComponentKind::CustomName, "custom_name"),
+    (ComponentKind::Lore, "lore"),
+    (ComponentKind::Enchantments, "enchantments")
+];
+
+impl ComponentKind {
+    pub fn from_id(id: i32, protocol: i32) -> Self {
+        let name = usize::try_from(id).ok().and_then(|index| data_component_names(protocol).get(index));
+        TYPED_KINDS.iter().find(|(_, typed)| Some(typed) == name).map(|(kind, _)| *kind).unwrap_or(Self::Other(id))
+    }
+
+    pub fn id(&self, protocol: i32) -> Result<i32, Error> {
+        let name = match self {
+            Self::Other(id) => return Ok(*id),
+            kind => TYPED_KINDS.iter().find(|(typed, _)| typed == kind).map(|(_, name)| *name).expect("Every typed kind has a name")
+        };
+        data_component_names(protocol)
+            .iter()
+            .position(|known| *known == name)
+            .map(|id| id as i32)
+            .ok_or_else(|| Error::Other(format!("Unable to write data component => {} isn't known for protocol {}", name, protocol)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComponentValue {
+    CustomData(Compound),
+    MaxDamage(i32),
+    Damage(i32),
+    Unbreakable { show_in_tooltip: bool },
+    CustomName(Tag),
+    Lore(Vec<Tag>),
+    Enchantments { levels: Vec<(i32, i32)>, show_in_tooltip: bool },
+    Raw(Vec<u8>)
+}
+
+// Components with nested structures are missing here, stacks carrying them can't be read
+#[derive(Debug, Clone, Copy)]
+enum Shape {
+    Unit,
+    Bool,
+    Int,
+    VarInt,
+    Nbt,
+    IntBool,
+    Enchantments
+}
+
+fn shape(name: &str) -> Option<Shape> {
+    Some(match name {
+        "hide_additional_tooltip" | "hide_tooltip" | "creative_slot_lock" | "fire_resistant" => Shape::Unit,
+        "enchantment_glint_override" => Shape::Bool,
+        "map_color" => Shape::Int,
+        "max_stack_size" | "rarity" | "custom_model_data" | "repair_cost" | "map_id" => Shape::VarInt,
+        "item_name" | "intangible_projectile" => Shape::Nbt,
+        "dyed_color" => Shape::IntBool,
+        "stored_enchantments" => Shape::Enchantments,
+        _ => return None
+    })
+}
+
+impl ComponentValue {
+    pub fn read(buffer: &mut Buffer, kind: ComponentKind, protocol: i32) -> Result<Self, Error> {
+        Ok(match kind {
+            ComponentKind::CustomData => Self::CustomData(Compound::read(buffer)?),
+            ComponentKind::MaxDamage => Self::MaxDamage(buffer.read_var_i32()?),
+            ComponentKind::Damage => Self::Damage(buffer.read_var_i32()?),
+            ComponentKind::Unbreakable => {
+                Self::Unbreakable {
+                    show_in_tooltip: buffer.read_bool()?
+                }
+            }
+            ComponentKind::CustomName => Self::CustomName(Tag::read(buffer)?),
+            ComponentKind::Lore => Self::Lore((0..buffer.read_var_i32()?).map(|_| Tag::read(buffer)).collect::<Result<_, _>>()?),
+            ComponentKind::Enchantments => {
+                let (levels, show_in_tooltip) = read_enchantments(buffer)?;
+                Self::Enchantments { levels, show_in_tooltip }
+            }
+            ComponentKind::Other(id) => {
+                let offset = buffer.position();
+                let name = usize::try_from(id).ok().and_then(|index| data_component_names(protocol).get(index));
+                let Some(shape) = name.and_then(|name| shape(name)) else {
+                    return Err(Error::Decode {
+                        offset,
+                        reason: format!("Unable to read slot => The length of data component {} isn't known for protocol {}", id, protocol)
+                    })
+                };
+                skip(buffer, shape)?;
+                let end = buffer.position();
+                buffer.set_position(offset);
+                Self::Raw(buffer.read_bytes(end - offset)?)
+            }
+        })
+    }
+
+    pub fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        match self {
+            Self::CustomData(compound) => return compound.write(buffer),
+            Self::MaxDamage(value) | Self::Damage(value) => buffer.write_var_i32(*value)?,
+            Self::Unbreakable { show_in_tooltip } => buffer.write_bool(*show_in_tooltip)?,
+            Self::CustomName(name) => return name.write(buffer),
+            Self::Lore(lines) => {
+                buffer.write_var_i32(lines.len() as i32)?;
+                for line in lines {
+                    buffer = line.write(buffer)?;
+                }
+            }
+            Self::Enchantments { levels, show_in_tooltip } => {
+                buffer.write_var_i32(levels.len() as i32)?;
+                for (enchantment, level) in levels {
+                    buffer.write_var_i32(*enchantment)?;
+                    buffer.write_var_i32(*level)?;
+                }
+                buffer.write_bool(*show_in_tooltip)?;
+            }
+            Self::Raw(bytes) => buffer.write_bytes(bytes)?
+        }
+        Ok(buffer)
+    }
+}
+
+fn read_enchantments(buffer: &mut Buffer) -> Result<(Vec<(i32, i32)>, bool), Error> {
+    let levels = (0..buffer.read_var_i32()?)
+        .map(|_| Ok((buffer.read_var_i32()?, buffer.read_var_i32()?)))
+        .collect::<Result<_, Error>>()?;
+    Ok((levels, buffer.read_bool()?))
+}
+
+fn skip(buffer: &mut Buffer, shape: Shape) -> Result<(), Error> {
+    match shape {
+        Shape::Unit => {}
+        Shape::Bool => {
+            buffer.read_bool()?;
+        }
+        Shape::Int => {
+            buffer.read_i32()?;
+        }
+        Shape::VarInt => {
+            buffer.read_var_i32()?;
+        }
+        Shape::Nbt => {
+            Tag::read(buffer)?;
+        }
+        Shape::IntBool => {
+            buffer.read_i32()?;
+            buffer.read_bool()?;
+        }
+        Shape::Enchantments => {
+            read_enchantments(buffer)?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ComponentPatch {
+    pub added: Vec<(ComponentKind, ComponentValue)>,
+    pub removed: Vec<ComponentKind>
+}
+
+impl ComponentPatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    pub fn with(mut self, kind: ComponentKind, value: ComponentValue) -> Self {
+        self.added.retain(|(added, _)| *added != kind);
+        self.added.push((kind, value));
+        self
+    }
+
+    pub fn without(mut self, kind: ComponentKind) -> Self {
+        self.added.retain(|(added, _)| *added != kind);
+        self.removed.push(kind);
+        self
+    }
+
+    pub fn get(&self, kind: ComponentKind) -> Option<&ComponentValue> {
+        self.added.iter().find(|(added, _)| *added == kind).map(|(_, value)| value)
+    }
+
+    pub fn read(buffer: &mut Buffer, protocol: i32) -> Result<Self, Error> {
+        let (added, removed) = (buffer.read_var_i32()?, buffer.read_var_i32()?);
+        let added = (0..added)
+            .map(|_| {
+                let kind = ComponentKind::from_id(buffer.read_var_i32()?, protocol);
+                Ok((kind, ComponentValue::read(buffer, kind, protocol)?))
+            })
+            .collect::<Result<_, Error>>()?;
+        let removed = (0..removed).map(|_| Ok(ComponentKind::from_id(buffer.read_var_i32()?, protocol))).collect::<Result<_, Error>>()?;
+        Ok(Self { added, removed })
+    }
+
+    pub fn write(&self, mut buffer: Buffer, protocol: i32) -> Result<Buffer, Error> {
+        buffer.write_var_i32(self.added.len() as i32)?;
+        buffer.write_var_i32(self.removed.len() as i32)?;
+        for (kind, value) in &self.added {
+            buffer.write_var_i32(kind.id(protocol)?)?;
+            buffer = value.write(buffer)?;
+        }
+        for kind in &self.removed {
+            buffer.write_var_i32(kind.id(protocol)?)?;
+        }
+        Ok(buffer)
+    }
+}
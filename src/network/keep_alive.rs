@@ -0,0 +1,238 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant}
+};
+
+use crate::network::{buffer::Buffer, connection::Connection, versions::VersionInfo, ByteOrder, Error, PacketState};
+
+// Smoothed like the round trip time of TCP
+pub const DEFAULT_SMOOTHING: f64 = 0.125;
+pub const DEFAULT_WINDOW: usize = 16;
+pub const DEFAULT_STALE_AFTER: Duration = Duration::from_secs(60);
+
+// Also sent in configuration since 1.20.2
+fn packet_ids(state: PacketState, protocol: i32) -> Option<(i32, i32)> {
+    Some(match (state, protocol) {
+        (PacketState::Play, 754) => (0x1F, 0x10),
+        (PacketState::Play, 755..=758) => (0x21, 0x0F),
+        (PacketState::Play, 759) => (0x1E, 0x11),
+        (PacketState::Play, 760) => (0x20, 0x12),
+        (PacketState::Play, 761) => (0x1F, 0x11),
+        (PacketState::Play, 762..=763) => (0x23, 0x12),
+        (PacketState::Play, 764) => (0x24, 0x14),
+        (PacketState::Play, 765) => (0x24, 0x15),
+        (PacketState::Play, 766..=767) => (0x26, 0x18),
+        (PacketState::Configuration, 764..=765) => (0x03, 0x03),
+        (PacketState::Configuration, 766..=767) => (0x04, 0x04),
+        _ => return None
+    })
+}
+
+// Added with 1.17, also sent in configuration since 1.20.2
+fn ping_packet_ids(state: PacketState, protocol: i32) -> Option<(i32, i32)> {
+    Some(match (state, protocol) {
+        (PacketState::Play, 755..=758) => (0x30, 0x1D),
+        (PacketState::Play, 759) => (0x2D, 0x1F),
+        (PacketState::Play, 760) => (0x2F, 0x20),
+        (PacketState::Play, 761) => (0x2E, 0x1F),
+        (PacketState::Play, 762..=763) => (0x32, 0x20),
+        (PacketState::Play, 764) => (0x33, 0x23),
+        (PacketState::Play, 765) => (0x33, 0x24),
+        (PacketState::Play, 766..=767) => (0x35, 0x27),
+        (PacketState::Configuration, 764..=765) => (0x04, 0x04),
+        (PacketState::Configuration, 766..=767) => (0x05, 0x05),
+        _ => return None
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct LatencyEstimator {
+    smoothing: f64,
+    window: usize,
+    stale_after: Duration,
+    average: Option<f64>,
+    samples: VecDeque<Duration>,
+    last_sample: Option<Instant>
+}
+
+impl Default for LatencyEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyEstimator {
+    pub fn new() -> Self {
+        Self {
+            smoothing: DEFAULT_SMOOTHING,
+            window: DEFAULT_WINDOW,
+            stale_after: DEFAULT_STALE_AFTER,
+            average: None,
+            samples: VecDeque::new(),
+            last_sample: None
+        }
+    }
+
+    pub fn with_smoothing(self, smoothing: f64) -> Self {
+        Self {
+            smoothing: smoothing.clamp(f64::EPSILON, 1.0),
+            ..self
+        }
+    }
+
+    pub fn with_window(self, window: usize) -> Self {
+        Self { window: window.max(1), ..self }
+    }
+
+    pub fn with_stale_after(self, stale_after: Duration) -> Self {
+        Self { stale_after, ..self }
+    }
+
+    pub fn record(&mut self, sample: Duration, now: Instant) {
+        let millis = sample.as_secs_f64() * 1000.0;
+        self.average = Some(match self.average {
+            Some(average) => average + self.smoothing * (millis - average),
+            None => millis
+        });
+
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+        self.last_sample = Some(now);
+    }
+
+    pub fn is_stale_at(&self, now: Instant) -> bool {
+        self.last_sample.is_none_or(|last_sample| now.saturating_duration_since(last_sample) > self.stale_after)
+    }
+
+    pub fn latency_at(&self, now: Instant) -> Option<Duration> {
+        self.average.filter(|_| !self.is_stale_at(now)).map(|average| Duration::from_secs_f64(average / 1000.0))
+    }
+
+    pub fn min_at(&self, now: Instant) -> Option<Duration> {
+        self.samples.iter().min().copied().filter(|_| !self.is_stale_at(now))
+    }
+
+    pub fn max_at(&self, now: Instant) -> Option<Duration> {
+        self.samples.iter().max().copied().filter(|_| !self.is_stale_at(now))
+    }
+
+    pub fn latency(&self) -> Option<Duration> {
+        self.latency_at(Instant::now())
+    }
+
+    pub fn min(&self) -> Option<Duration> {
+        self.min_at(Instant::now())
+    }
+
+    pub fn max(&self) -> Option<Duration> {
+        self.max_at(Instant::now())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KeepAlive {
+    protocol: i32,
+    reported: LatencyEstimator,
+    turnaround: LatencyEstimator,
+    last_id: Option<i64>,
+    answer_pings: bool,
+    last_ping: Option<i32>
+}
+
+impl KeepAlive {
+    pub fn new(version: &VersionInfo) -> Self {
+        Self {
+            protocol: version.protocol,
+            reported: LatencyEstimator::new(),
+            turnaround: LatencyEstimator::new(),
+            last_id: None,
+            answer_pings: true,
+            last_ping: None
+        }
+    }
+
+    pub fn with_answer_pings(self, answer_pings: bool) -> Self {
+        Self { answer_pings, ..self }
+    }
+
+    pub fn with_estimator(self, estimator: LatencyEstimator) -> Self {
+        Self {
+            reported: estimator.clone(),
+            turnaround: estimator,
+            ..self
+        }
+    }
+
+    pub fn protocol(&self) -> i32 {
+        self.protocol
+    }
+
+    pub fn last_id(&self) -> Option<i64> {
+        self.last_id
+    }
+
+    pub fn last_ping(&self) -> Option<i32> {
+        self.last_ping
+    }
+
+    pub fn reported(&self) -> &LatencyEstimator {
+        &self.reported
+    }
+
+    pub fn turnaround(&self) -> &LatencyEstimator {
+        &self.turnaround
+    }
+
+    pub fn latency(&self) -> Option<Duration> {
+        self.reported.latency()
+    }
+
+    // Negative latencies are sent for players that haven't answered a keep alive yet
+    pub fn record_reported(&mut self, latency: i32, now: Instant) {
+        if let Ok(latency) = u64::try_from(latency) {
+            self.reported.record(Duration::from_millis(latency), now);
+        }
+    }
+
+    pub fn handle<'a, S>(&mut self, connection: &mut impl Connection<'a, S>, state: PacketState, packet: &mut Buffer, received: Instant) -> Result<bool, Error> {
+        if self.answer_pings && self.answer_ping(connection, state, packet)? {
+            return Ok(true)
+        }
+        let Some((clientbound, serverbound)) = packet_ids(state, self.protocol) else {
+            return Ok(false)
+        };
+
+        let position = packet.position();
+        let id = packet.read_var_i32().and_then(|id| if id == clientbound { packet.read_i64().map(Some) } else { Ok(None) });
+        packet.set_position(position);
+        let Some(id) = id? else { return Ok(false) };
+
+        let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        buffer.write_var_i32(serverbound)?;
+        buffer.write_i64(id)?;
+        connection.write(buffer)?;
+
+        let now = Instant::now();
+        self.turnaround.record(now.saturating_duration_since(received), now);
+        self.last_id = Some(id);
+        Ok(true)
+    }
+
+    fn answer_ping<'a, S>(&mut self, connection: &mut impl Connection<'a, S>, state: PacketState, packet: &mut Buffer) -> Result<bool, Error> {
+        let Some((ping, pong)) = ping_packet_ids(state, self.protocol) else { return Ok(false) };
+
+        let position = packet.position();
+        let id = packet.read_var_i32().and_then(|id| if id == ping { packet.read_i32().map(Some) } else { Ok(None) });
+        packet.set_position(position);
+        let Some(id) = id? else { return Ok(false) };
+
+        let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        buffer.write_var_i32(pong)?;
+        buffer.write_i32(id)?;
+        connection.write(buffer)?;
+        self.last_ping = Some(id);
+        Ok(true)
+    }
+}
@@ -0,0 +1,361 @@
+use std::{collections::HashMap, net::TcpStream};
+
+use crate::{
+    auth::Session,
+    network::{
+        buffer::Buffer,
+        connection::{async_socket::AsyncSocketConnection, socket::SocketConnection, Client, Connection, Pipeline, Readable},
+        convenience::read_component,
+        encryption::{generate_shared_secret, rsa_encrypt, server_hash},
+        login::{
+            block_on, GameProfile, Handshake, LoginSuccess, NextState, CLIENTBOUND_DISCONNECT, CLIENTBOUND_ENCRYPTION_REQUEST, CLIENTBOUND_LOGIN_PLUGIN_REQUEST,
+            CLIENTBOUND_LOGIN_SUCCESS, CLIENTBOUND_SET_COMPRESSION, MAX_USERNAME_LENGTH, SERVERBOUND_ENCRYPTION_RESPONSE, SERVERBOUND_LOGIN_ACKNOWLEDGED,
+            SERVERBOUND_LOGIN_PLUGIN_RESPONSE, SERVERBOUND_LOGIN_START
+        },
+        plugin::PluginMessage,
+        resource_pack::{ResourcePackPolicy, ResourcePacks},
+        status::select_version,
+        types::Identifier,
+        versions::{VersionInfo, VersionSelection},
+        ByteOrder, Error, PacketState
+    },
+    webapi::{ApiOperation, Endpoints}
+};
+
+#[derive(Debug)]
+pub enum LoginAction {
+    Send(Buffer),
+    JoinSession(String),
+    EnableEncryption([u8; 16]),
+    EnableCompression(i32),
+    // Since 1.20.2 the login success is acknowledged and followed by the configuration state
+    EnterConfiguration,
+    Finish(LoginSuccess)
+}
+
+// Vanilla refuses login plugin payloads larger than this
+pub const MAX_LOGIN_PLUGIN_PAYLOAD: usize = 1048576;
+
+type LoginPluginHandler = Box<dyn FnMut(&[u8]) -> Result<Option<Vec<u8>>, Error> + Send>;
+
+fn configuration_ids(protocol: i32) -> Option<[i32; 9]> {
+    Some(match protocol {
+        764..=765 => [0x01, 0x02, 0x03, 0x04, -1, 0x02, 0x03, 0x04, -1],
+        766..=767 => [0x02, 0x03, 0x04, 0x05, 0x0E, 0x03, 0x04, 0x05, 0x07],
+        _ => return None
+    })
+}
+
+// Shared with the relay, which answers the request for its upstream leg
+pub(crate) fn answer_encryption_request(packet: &mut Buffer, protocol: i32) -> Result<Vec<LoginAction>, Error> {
+    let server_id = packet.read_string()?;
+    let public_key = packet.read_byte_array()?;
+    let verify_token = packet.read_byte_array()?;
+    let should_authenticate = if protocol >= 766 { packet.read_bool()? } else { true };
+
+    let shared_secret = generate_shared_secret();
+    let mut response = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    response.write_var_i32(SERVERBOUND_ENCRYPTION_RESPONSE)?;
+    response.write_byte_array(&rsa_encrypt(&public_key, &shared_secret)?)?;
+    if protocol == 759 || protocol == 760 {
+        response.write_bool(true)?;
+    }
+    response.write_byte_array(&rsa_encrypt(&public_key, &verify_token)?)?;
+
+    let mut actions = Vec::new();
+    if should_authenticate {
+        actions.push(LoginAction::JoinSession(server_hash(&server_id, &shared_secret, &public_key)));
+    }
+    actions.push(LoginAction::Send(response));
+    actions.push(LoginAction::EnableEncryption(shared_secret));
+    Ok(actions)
+}
+
+pub struct ClientLogin<'p> {
+    profile: &'p GameProfile,
+    version: VersionInfo,
+    plugin_handlers: HashMap<String, LoginPluginHandler>,
+    configuring: Option<LoginSuccess>,
+    resource_packs: ResourcePacks
+}
+
+impl<'p> ClientLogin<'p> {
+    pub fn new(profile: &'p GameProfile, version: &VersionInfo) -> Result<Self, Error> {
+        Ok(Self {
+            profile,
+            version: *version,
+            plugin_handlers: HashMap::new(),
+            configuring: None,
+            resource_packs: ResourcePacks::new(version)
+        })
+    }
+
+    pub fn resource_pack_policy(self, policy: ResourcePackPolicy) -> Self {
+        Self {
+            resource_packs: self.resource_packs.with_policy(policy),
+            ..self
+        }
+    }
+
+    pub fn register(self, channel: &str, handler: impl FnMut(&[u8]) -> Result<Option<Vec<u8>>, Error> + Send + 'static) -> Result<Self, Error> {
+        let mut plugin_handlers = self.plugin_handlers;
+        plugin_handlers.insert(Identifier::parse(channel)?.to_string(), Box::new(handler));
+        Ok(Self { plugin_handlers, ..self })
+    }
+
+    pub fn handshake(&self, address: &str, port: u16) -> Handshake {
+        Handshake {
+            protocol_version: self.version.protocol,
+            server_address: address.to_string(),
+            server_port: port,
+            next_state: NextState::Login
+        }
+    }
+
+    pub fn login_start(&self) -> Result<Buffer, Error> {
+        let protocol = self.version.protocol;
+        let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        buffer.write_var_i32(SERVERBOUND_LOGIN_START)?;
+        buffer.write_string_bounded(&self.profile.name, MAX_USERNAME_LENGTH)?;
+        if protocol == 759 || protocol == 760 {
+            buffer.write_bool(false)?;
+        }
+
+        if (760..764).contains(&protocol) {
+            buffer.write_bool(true)?;
+        }
+
+        if protocol >= 760 {
+            let bits = self.profile.id.as_u64_pair();
+            buffer.write_u64(bits.0)?;
+            buffer.write_u64(bits.1)?;
+        }
+        Ok(buffer)
+    }
+
+    pub fn handle(&mut self, mut packet: Buffer) -> Result<Vec<LoginAction>, Error> {
+        if self.configuring.is_some() {
+            return self.handle_configuration(packet)
+        }
+
+        let protocol = self.version.protocol;
+        match packet.read_var_i32()? {
+            CLIENTBOUND_DISCONNECT => Err(Error::Disconnected(packet.read_string()?)),
+            CLIENTBOUND_ENCRYPTION_REQUEST => answer_encryption_request(&mut packet, protocol),
+            CLIENTBOUND_SET_COMPRESSION => Ok(vec![LoginAction::EnableCompression(packet.read_var_i32()?)]),
+            CLIENTBOUND_LOGIN_SUCCESS if protocol >= 764 => {
+                self.configuring = Some(LoginSuccess::read_body(&mut packet, protocol)?);
+                Ok(vec![LoginAction::Send(Self::packet(SERVERBOUND_LOGIN_ACKNOWLEDGED)?), LoginAction::EnterConfiguration])
+            }
+            CLIENTBOUND_LOGIN_SUCCESS => Ok(vec![LoginAction::Finish(LoginSuccess::read_body(&mut packet, protocol)?)]),
+            CLIENTBOUND_LOGIN_PLUGIN_REQUEST => Ok(vec![LoginAction::Send(self.answer_plugin_request(&mut packet)?)]),
+            id => Err(Error::IllegalPacket(id, self.version.to_string()))
+        }
+    }
+
+    // Only what the server waits for is answered, the client knows no data packs
+    fn handle_configuration(&mut self, mut packet: Buffer) -> Result<Vec<LoginAction>, Error> {
+        if let Some(responses) = self.resource_packs.respond(PacketState::Configuration, &mut packet)? {
+            return Ok(responses.into_iter().map(LoginAction::Send).collect())
+        }
+
+        let protocol = self.version.protocol;
+        let [disconnect, finish, keep_alive, ping, known_packs, acknowledge_finish, keep_alive_response, pong, known_packs_response] =
+            configuration_ids(protocol).ok_or_else(|| Error::IllegalPacket(-1, self.version.to_string()))?;
+
+        let id = packet.read_var_i32()?;
+        let response = match id {
+            _ if id == disconnect => return Err(self.resource_packs.explain(Error::Disconnected(read_component(&mut packet, protocol)?.to_json()))),
+            _ if id == finish => {
+                let login_success = self.configuring.take().ok_or_else(|| Error::IllegalPacket(id, self.version.to_string()))?;
+                return Ok(vec![LoginAction::Send(Self::packet(acknowledge_finish)?), LoginAction::Finish(login_success)])
+            }
+            _ if id == keep_alive => {
+                let mut response = Self::packet(keep_alive_response)?;
+                response.write_i64(packet.read_i64()?)?;
+                response
+            }
+            _ if id == ping => {
+                let mut response = Self::packet(pong)?;
+                response.write_i32(packet.read_i32()?)?;
+                response
+            }
+            _ if id == known_packs => {
+                let mut response = Self::packet(known_packs_response)?;
+                response.write_var_i32(0)?;
+                response
+            }
+            _ => return Ok(Vec::new())
+        };
+        Ok(vec![LoginAction::Send(response)])
+    }
+
+    fn packet(id: i32) -> Result<Buffer, Error> {
+        let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        buffer.write_var_i32(id)?;
+        Ok(buffer)
+    }
+
+    // The server waits for an answer to every request
+    fn answer_plugin_request(&mut self, packet: &mut Buffer) -> Result<Buffer, Error> {
+        let message_id = packet.read_var_i32()?;
+        let offset = packet.position();
+        let request = PluginMessage::read(packet)?;
+        if request.data.len() > MAX_LOGIN_PLUGIN_PAYLOAD {
+            return Err(Error::Decode {
+                offset,
+                reason: format!(
+                    "Login plugin request on {} carries {} bytes, at most {} are allowed",
+                    request.channel,
+                    request.data.len(),
+                    MAX_LOGIN_PLUGIN_PAYLOAD
+                )
+            })
+        }
+
+        let data = match self.plugin_handlers.get_mut(&request.channel.to_string()) {
+            Some(handler) => handler(&request.data)?,
+            None => None
+        };
+
+        let mut response = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        response.write_var_i32(SERVERBOUND_LOGIN_PLUGIN_RESPONSE)?;
+        response.write_var_i32(message_id)?;
+        response.write_bool(data.is_some())?;
+        if let Some(data) = data {
+            if data.len() > MAX_LOGIN_PLUGIN_PAYLOAD {
+                return Err(Error::Other(format!(
+                    "Unable to answer login plugin request on {} => {} bytes exceed the limit of {}",
+                    request.channel,
+                    data.len(),
+                    MAX_LOGIN_PLUGIN_PAYLOAD
+                )))
+            }
+            response.write_bytes(&data)?;
+        }
+        Ok(response)
+    }
+}
+
+pub fn join_server<'a>(
+    address: &str,
+    port: u16,
+    session: &Session,
+    profile: &GameProfile,
+    version: impl Into<VersionSelection>,
+    pipeline_factory: impl FnOnce() -> Pipeline<'a>
+) -> Result<(SocketConnection<'a, Client>, LoginSuccess), Error> {
+    join_server_with_endpoints(&Endpoints::default(), address, port, session, profile, version, pipeline_factory)
+}
+
+pub fn join_server_with_endpoints<'a>(
+    endpoints: &Endpoints,
+    address: &str,
+    port: u16,
+    session: &Session,
+    profile: &GameProfile,
+    version: impl Into<VersionSelection>,
+    pipeline_factory: impl FnOnce() -> Pipeline<'a>
+) -> Result<(SocketConnection<'a, Client>, LoginSuccess), Error> {
+    let version = block_on(select_version(address, port, &version.into()))??;
+    join_server_with_login(endpoints, ClientLogin::new(profile, &version)?, address, port, session, pipeline_factory)
+}
+
+pub fn join_server_with_login<'a>(
+    endpoints: &Endpoints,
+    mut login: ClientLogin<'_>,
+    address: &str,
+    port: u16,
+    session: &Session,
+    pipeline_factory: impl FnOnce() -> Pipeline<'a>
+) -> Result<(SocketConnection<'a, Client>, LoginSuccess), Error> {
+    let profile = login.profile;
+    let mut connection = SocketConnection::<Client>::new(TcpStream::connect((address, port))?, pipeline_factory());
+    connection.write(login.handshake(address, port))?;
+    connection.set_state(PacketState::Login);
+    connection.set_protocol_version(Some(login.version.protocol));
+    connection.write(login.login_start()?)?;
+
+    loop {
+        for action in login.handle(connection.read_packet()?)? {
+            match action {
+                LoginAction::Send(packet) => {
+                    connection.write(packet)?;
+                }
+                LoginAction::JoinSession(server_hash) => {
+                    block_on(endpoints.join_server(&session.access_token, profile.id, &server_hash))?.map_err(|error| error.within(ApiOperation::JoinServer))?;
+                }
+                LoginAction::EnableEncryption(shared_secret) => connection.enable_encryption(&shared_secret),
+                LoginAction::EnableCompression(threshold) => connection.enable_compression(threshold),
+                LoginAction::EnterConfiguration => connection.set_state(PacketState::Configuration),
+                LoginAction::Finish(login_success) => {
+                    connection.set_state(PacketState::Play);
+                    return Ok((connection, login_success))
+                }
+            }
+        }
+    }
+}
+
+pub async fn join_server_async<'a>(
+    address: &str,
+    port: u16,
+    session: &Session,
+    profile: &GameProfile,
+    version: impl Into<VersionSelection>,
+    pipeline_factory: impl FnOnce() -> Pipeline<'a>
+) -> Result<(AsyncSocketConnection<'a, Client>, LoginSuccess), Error> {
+    join_server_async_with_endpoints(&Endpoints::default(), address, port, session, profile, version, pipeline_factory).await
+}
+
+pub async fn join_server_async_with_endpoints<'a>(
+    endpoints: &Endpoints,
+    address: &str,
+    port: u16,
+    session: &Session,
+    profile: &GameProfile,
+    version: impl Into<VersionSelection>,
+    pipeline_factory: impl FnOnce() -> Pipeline<'a>
+) -> Result<(AsyncSocketConnection<'a, Client>, LoginSuccess), Error> {
+    let version = select_version(address, port, &version.into()).await?;
+    join_server_async_with_login(endpoints, ClientLogin::new(profile, &version)?, address, port, session, pipeline_factory).await
+}
+
+pub async fn join_server_async_with_login<'a>(
+    endpoints: &Endpoints,
+    mut login: ClientLogin<'_>,
+    address: &str,
+    port: u16,
+    session: &Session,
+    pipeline_factory: impl FnOnce() -> Pipeline<'a>
+) -> Result<(AsyncSocketConnection<'a, Client>, LoginSuccess), Error> {
+    let profile = login.profile;
+    let mut connection = AsyncSocketConnection::<Client>::new(tokio::net::TcpStream::connect((address, port)).await?, pipeline_factory());
+    connection.write(login.handshake(address, port)).await?;
+    connection.set_state(PacketState::Login);
+    connection.set_protocol_version(Some(login.version.protocol));
+    connection.write(login.login_start()?).await?;
+
+    loop {
+        for action in login.handle(connection.read_packet().await?)? {
+            match action {
+                LoginAction::Send(packet) => {
+                    connection.write(packet).await?;
+                }
+                LoginAction::JoinSession(server_hash) => {
+                    endpoints
+                        .join_server(&session.access_token, profile.id, &server_hash)
+                        .await
+                        .map_err(|error| error.within(ApiOperation::JoinServer))?
+                }
+                LoginAction::EnableEncryption(shared_secret) => connection.enable_encryption(&shared_secret),
+                LoginAction::EnableCompression(threshold) => connection.enable_compression(threshold),
+                LoginAction::EnterConfiguration => connection.set_state(PacketState::Configuration),
+                LoginAction::Finish(login_success) => {
+                    connection.set_state(PacketState::Play);
+                    return Ok((connection, login_success))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::{future::Future, thread};
+
+use uuid::Uuid;
+
+use crate::{
+    network::{
+        buffer::Buffer,
+        connection::{Readable, VersionedReadable, VersionedWritable, Writable},
+        Error
+    },
+    profile::{read_login_properties, write_login_properties, Property},
+    webapi::ProfileResponse
+};
+
+mod client;
+mod server;
+
+pub(crate) use client::answer_encryption_request;
+pub use client::{
+    join_server, join_server_async, join_server_async_with_endpoints, join_server_async_with_login, join_server_with_endpoints, join_server_with_login, ClientLogin, LoginAction,
+    MAX_LOGIN_PLUGIN_PAYLOAD
+};
+pub use server::{accept_login, accept_login_after_handshake, offline_uuid, read_handshake, ServerLoginConfig, VerifiedPlayer};
+
+pub use crate::network::{
+    handshake::{Handshake, NextState, HANDSHAKE},
+    types::MAX_USERNAME_LENGTH
+};
+
+pub const CLIENTBOUND_DISCONNECT: i32 = 0x00;
+pub const CLIENTBOUND_ENCRYPTION_REQUEST: i32 = 0x01;
+pub const CLIENTBOUND_LOGIN_SUCCESS: i32 = 0x02;
+pub const CLIENTBOUND_SET_COMPRESSION: i32 = 0x03;
+pub const CLIENTBOUND_LOGIN_PLUGIN_REQUEST: i32 = 0x04;
+
+pub const SERVERBOUND_LOGIN_START: i32 = 0x00;
+pub const SERVERBOUND_ENCRYPTION_RESPONSE: i32 = 0x01;
+pub const SERVERBOUND_LOGIN_PLUGIN_RESPONSE: i32 = 0x02;
+pub const SERVERBOUND_LOGIN_ACKNOWLEDGED: i32 = 0x03;
+
+// The session server needs async reqwest, so blocking logins call it on a runtime of their own
+pub(crate) fn block_on<F: Future + Send>(future: F) -> Result<F::Output, Error>
+where
+    F::Output: Send
+{
+    thread::scope(|scope| {
+        scope
+            .spawn(|| Ok(tokio::runtime::Builder::new_current_thread().enable_all().build()?.block_on(future)))
+            .join()
+            .map_err(|_| Error::Other("Session server thread panicked".to_string()))?
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameProfile {
+    pub id: Uuid,
+    pub name: String
+}
+
+impl GameProfile {
+    pub fn new(id: Uuid, name: impl Into<String>) -> Self {
+        Self { id, name: name.into() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoginSuccess {
+    pub uuid: Uuid,
+    pub username: String,
+    pub properties: Vec<Property>
+}
+
+impl LoginSuccess {
+    pub(crate) fn read_body(buffer: &mut Buffer, protocol: i32) -> Result<Self, Error> {
+        let uuid = Uuid::read(buffer)?;
+        let username = buffer.read_string_bounded(MAX_USERNAME_LENGTH)?;
+        let properties = read_login_properties(buffer, protocol)?;
+
+        // Strict error handling, only sent by 1.20.5 and 1.21
+        if (766..=767).contains(&protocol) {
+            buffer.read_bool()?;
+        }
+
+        Ok(Self { uuid, username, properties })
+    }
+}
+
+impl VersionedWritable for LoginSuccess {
+    fn write_versioned(&self, mut buffer: Buffer, protocol: i32) -> Result<Buffer, Error> {
+        buffer.write_var_i32(CLIENTBOUND_LOGIN_SUCCESS)?;
+        let mut buffer = self.uuid.write(buffer)?;
+        buffer.write_string_bounded(&self.username, MAX_USERNAME_LENGTH)?;
+        let mut buffer = write_login_properties(&self.properties, buffer, protocol)?;
+
+        if (766..=767).contains(&protocol) {
+            buffer.write_bool(false)?;
+        }
+        Ok(buffer)
+    }
+}
+
+impl VersionedReadable for LoginSuccess {
+    fn read_versioned(buffer: &mut Buffer, protocol: i32) -> Result<Self, Error> {
+        let id = buffer.read_var_i32()?;
+        if id != CLIENTBOUND_LOGIN_SUCCESS {
+            return Err(Error::IllegalPacket(id, "Login".to_string()))
+        }
+        Self::read_body(buffer, protocol)
+    }
+}
+
+// The session server sends the id without dashes
+pub(crate) fn parse_profile_id(id: &str) -> Result<Uuid, Error> {
+    Uuid::parse_str(id).map_err(|error| Error::Other(format!("Unable to parse profile id => {}", error)))
+}
+
+impl TryFrom<ProfileResponse> for LoginSuccess {
+    type Error = Error;
+
+    fn try_from(profile: ProfileResponse) -> Result<Self, Error> {
+        Ok(Self {
+            uuid: parse_profile_id(&profile.id)?,
+            username: profile.name,
+            properties: profile.properties
+        })
+    }
+}
+
+impl From<LoginSuccess> for ProfileResponse {
+    fn from(login_success: LoginSuccess) -> Self {
+        Self {
+            id: login_success.uuid.simple().to_string(),
+            name: login_success.username,
+            properties: login_success.properties
+        }
+    }
+}
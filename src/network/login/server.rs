@@ -0,0 +1,256 @@
+use md5::{Digest, Md5};
+use rand::{thread_rng, RngCore};
+use rsa::RsaPrivateKey;
+use serde_json::json;
+use uuid::{Builder, Uuid};
+
+use crate::{
+    network::{
+        buffer::Buffer,
+        connection::{socket::SocketConnection, Connection, Server, VersionedWritable},
+        encryption::{generate_key_pair, public_key_der, rsa_decrypt, server_hash},
+        login::{
+            block_on, parse_profile_id, Handshake, LoginSuccess, NextState, CLIENTBOUND_DISCONNECT, CLIENTBOUND_ENCRYPTION_REQUEST, CLIENTBOUND_SET_COMPRESSION, MAX_USERNAME_LENGTH,
+            SERVERBOUND_ENCRYPTION_RESPONSE, SERVERBOUND_LOGIN_ACKNOWLEDGED, SERVERBOUND_LOGIN_START
+        },
+        ByteOrder, Error, PacketState
+    },
+    profile::Property,
+    webapi::{ApiOperation, Endpoints, ProfileResponse}
+};
+
+type Veto = Box<dyn Fn(&VerifiedPlayer) -> Option<String> + Send + Sync>;
+
+// Clientbound Finish Configuration and serverbound Acknowledge Finish Configuration
+fn finish_configuration_ids(protocol: i32) -> Option<(i32, i32)> {
+    match protocol {
+        764..=765 => Some((0x02, 0x02)),
+        766..=767 => Some((0x03, 0x03)),
+        _ => None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VerifiedPlayer {
+    pub name: String,
+    pub uuid: Uuid,
+    pub properties: Vec<Property>
+}
+
+impl TryFrom<ProfileResponse> for VerifiedPlayer {
+    type Error = Error;
+
+    fn try_from(profile: ProfileResponse) -> Result<Self, Error> {
+        Ok(Self {
+            uuid: parse_profile_id(&profile.id)?,
+            name: profile.name,
+            properties: profile.properties
+        })
+    }
+}
+
+impl From<VerifiedPlayer> for LoginSuccess {
+    fn from(player: VerifiedPlayer) -> Self {
+        Self {
+            uuid: player.uuid,
+            username: player.name,
+            properties: player.properties
+        }
+    }
+}
+
+pub struct ServerLoginConfig {
+    online_mode: bool,
+    compression_threshold: Option<i32>,
+    endpoints: Endpoints,
+    key_pair: Option<(RsaPrivateKey, Vec<u8>)>,
+    veto: Option<Veto>,
+    configuration: Vec<Buffer>
+}
+
+impl ServerLoginConfig {
+    pub fn new(online_mode: bool) -> Result<Self, Error> {
+        let key_pair = if online_mode {
+            let private_key = generate_key_pair(1024)?;
+            let public_key = public_key_der(&private_key)?;
+            Some((private_key, public_key))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            online_mode,
+            compression_threshold: None,
+            endpoints: Endpoints::default(),
+            key_pair,
+            veto: None,
+            configuration: Vec::new()
+        })
+    }
+
+    pub fn compression_threshold(self, threshold: i32) -> Self {
+        Self {
+            compression_threshold: Some(threshold),
+            ..self
+        }
+    }
+
+    pub fn endpoints(self, endpoints: Endpoints) -> Self {
+        Self { endpoints, ..self }
+    }
+
+    pub fn veto(self, veto: impl Fn(&VerifiedPlayer) -> Option<String> + Send + Sync + 'static) -> Self {
+        Self { veto: Some(Box::new(veto)), ..self }
+    }
+
+    // Sent in the configuration state of 1.20.2+ before it's finished, e.g. the registry data
+    pub fn configuration(self, packets: Vec<Buffer>) -> Self {
+        Self { configuration: packets, ..self }
+    }
+
+    pub fn online_mode(&self) -> bool {
+        self.online_mode
+    }
+}
+
+pub fn offline_uuid(name: &str) -> Uuid {
+    let hash = Md5::digest(format!("OfflinePlayer:{}", name).as_bytes());
+    Builder::from_md5_bytes(hash.into()).into_uuid()
+}
+
+pub fn read_handshake(connection: &mut SocketConnection<'_, Server>) -> Result<Handshake, Error> {
+    let handshake = Handshake::read(&mut connection.read_packet()?)?;
+    connection.set_protocol_version(Some(handshake.protocol_version));
+    Ok(handshake)
+}
+
+pub fn accept_login(connection: &mut SocketConnection<'_, Server>, config: &ServerLoginConfig) -> Result<VerifiedPlayer, Error> {
+    let handshake = read_handshake(connection)?;
+    if handshake.next_state == NextState::Status {
+        return Err(Error::Other("Unable to accept login => The client requested the status".to_string()))
+    }
+
+    accept_login_after_handshake(connection, &handshake, config)
+}
+
+pub fn accept_login_after_handshake(connection: &mut SocketConnection<'_, Server>, handshake: &Handshake, config: &ServerLoginConfig) -> Result<VerifiedPlayer, Error> {
+    let protocol = handshake.protocol_version;
+    connection.set_state(PacketState::Login);
+
+    let mut login_start = connection.read_packet()?;
+    let id = login_start.read_var_i32()?;
+    if id != SERVERBOUND_LOGIN_START {
+        return Err(Error::IllegalPacket(id, protocol.to_string()))
+    }
+
+    let name = login_start.read_string_bounded(MAX_USERNAME_LENGTH)?;
+    if protocol >= 764 && finish_configuration_ids(protocol).is_none() {
+        return Err(disconnect(connection, &format!("The configuration state of protocol {} isn't supported", protocol)))
+    }
+
+    let player = match &config.key_pair {
+        Some((private_key, public_key)) => {
+            let mut verify_token = [0; 4];
+            thread_rng().fill_bytes(&mut verify_token);
+
+            let mut encryption_request = packet(CLIENTBOUND_ENCRYPTION_REQUEST)?;
+            encryption_request.write_str("")?;
+            encryption_request.write_byte_array(public_key)?;
+            encryption_request.write_byte_array(&verify_token)?;
+            if protocol >= 766 {
+                encryption_request.write_bool(true)?;
+            }
+            connection.write(encryption_request)?;
+
+            let mut encryption_response = connection.read_packet()?;
+            let id = encryption_response.read_var_i32()?;
+            if id != SERVERBOUND_ENCRYPTION_RESPONSE {
+                return Err(Error::IllegalPacket(id, protocol.to_string()))
+            }
+
+            let shared_secret = rsa_decrypt(private_key, &encryption_response.read_byte_array()?)?;
+            if (protocol == 759 || protocol == 760) && !encryption_response.read_bool()? {
+                return Err(disconnect(connection, "Message signatures instead of verify tokens are not supported"))
+            }
+
+            if rsa_decrypt(private_key, &encryption_response.read_byte_array()?)? != verify_token {
+                return Err(disconnect(connection, "Invalid verify token"))
+            }
+
+            let shared_secret: [u8; 16] = shared_secret
+                .try_into()
+                .map_err(|_| Error::Other("Unable to accept login => The shared secret isn't 16 bytes long".to_string()))?;
+            connection.enable_encryption(&shared_secret);
+
+            let hash = server_hash("", &shared_secret, public_key);
+            let profile = match block_on(config.endpoints.has_joined(&name, &hash))?.map_err(|error| error.within(ApiOperation::HasJoined))? {
+                Some(profile) => profile,
+                None => return Err(disconnect(connection, "Failed to verify username!"))
+            };
+
+            VerifiedPlayer::try_from(profile)?
+        }
+        None => {
+            VerifiedPlayer {
+                uuid: offline_uuid(&name),
+                name,
+                properties: Vec::new()
+            }
+        }
+    };
+
+    if let Some(reason) = config.veto.as_ref().and_then(|veto| veto(&player)) {
+        disconnect(connection, &reason);
+        return Err(Error::Rejected(reason))
+    }
+
+    if let Some(threshold) = config.compression_threshold {
+        let mut set_compression = packet(CLIENTBOUND_SET_COMPRESSION)?;
+        set_compression.write_var_i32(threshold)?;
+        connection.write(set_compression)?;
+        connection.enable_compression(threshold);
+    }
+
+    let login_success = LoginSuccess::from(player.clone());
+    connection.write(login_success.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), protocol)?)?;
+    if let Some((finish, acknowledge_finish)) = finish_configuration_ids(protocol) {
+        configure(connection, protocol, &config.configuration, finish, acknowledge_finish)?;
+    }
+    connection.set_state(PacketState::Play);
+    Ok(player)
+}
+
+// The client answers its own configuration packets (client information, brand, known packs) which are skipped here
+fn configure(connection: &mut SocketConnection<'_, Server>, protocol: i32, packets: &[Buffer], finish: i32, acknowledge_finish: i32) -> Result<(), Error> {
+    let id = connection.read_packet()?.read_var_i32()?;
+    if id != SERVERBOUND_LOGIN_ACKNOWLEDGED {
+        return Err(Error::IllegalPacket(id, protocol.to_string()))
+    }
+    connection.set_state(PacketState::Configuration);
+
+    for packet in packets {
+        connection.write(packet.clone())?;
+    }
+    connection.write(self::packet(finish)?)?;
+    while connection.read_packet()?.read_var_i32()? != acknowledge_finish {}
+    Ok(())
+}
+
+fn packet(id: i32) -> Result<Buffer, Error> {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(id)?;
+    Ok(buffer)
+}
+
+fn disconnect(connection: &mut SocketConnection<'_, Server>, reason: &str) -> Error {
+    let mut disconnect = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    let written = disconnect
+        .write_var_i32(CLIENTBOUND_DISCONNECT)
+        .and_then(|_| disconnect.write_string(json!({ "text": reason }).to_string()))
+        .and_then(|_| connection.write(disconnect));
+
+    match written {
+        Ok(_) => Error::Disconnected(reason.to_string()),
+        Err(error) => error
+    }
+}
@@ -0,0 +1,303 @@
+use crate::{
+    components::Component,
+    network::{
+        buffer::Buffer,
+        convenience::{read_component, write_component},
+        Error
+    }
+};
+
+pub const MAP_WIDTH: usize = 128;
+
+// 1.17 dropped the tracking position flag and made the icon list optional
+const OPTIONAL_ICONS_PROTOCOL: i32 = 755;
+
+// Every color index is a base color times four plus one of these shades
+pub const SHADE_MULTIPLIERS: [u32; 4] = [180, 220, 255, 135];
+
+// New base colors were only ever appended, so the table fits older versions as well
+const VANILLA_BASE_COLORS: [u32; 62] = [
+    0x000000, 0x7FB238, 0xF7E9A3, 0xC7C7C7, 0xFF0000, 0xA0A0FF, 0xA7A7A7, 0x007C00, 0xFFFFFF, 0xA4A8B8, 0x976D4D, 0x707070, 0x4040FF, 0x8F7748, 0xFFFCF5, 0xD87F33, 0xB24CD8, 0x6699D8,
+    0xE5E533, 0x7FCC19, 0xF27FA5, 0x4C4C4C, 0x999999, 0x4C7F99, 0x7F3FB2, 0x334CB2, 0x664C33, 0x667F33, 0x993333, 0x191919, 0xFAEE4D, 0x5CDBD5, 0x4A80FF, 0x00D93A, 0x815631, 0x700200,
+    0xD1B1A1, 0x9F5224, 0x95576C, 0x706C8A, 0xBA8524, 0x677535, 0xA04D4E, 0x392923, 0x876B62, 0x575C5C, 0x7A4958, 0x4C3E5C, 0x4C3223, 0x4C522A, 0x8E3C2E, 0x251610, 0xBD3031, 0x943F61,
+    0x5C191D, 0x167E86, 0x3A8E8C, 0x562C3E, 0x14B485, 0x646464, 0xD8AF93, 0x7FA796
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapPalette {
+    base_colors: Vec<[u8; 3]>
+}
+
+impl MapPalette {
+    pub fn new(base_colors: Vec<[u8; 3]>) -> Self {
+        Self { base_colors }
+    }
+
+    pub fn vanilla() -> Self {
+        Self::new(VANILLA_BASE_COLORS.iter().map(|color| [(color >> 16) as u8, (color >> 8) as u8, *color as u8]).collect())
+    }
+
+    pub fn base_colors(&self) -> &[[u8; 3]] {
+        &self.base_colors
+    }
+
+    pub fn rgba(&self, index: u8) -> [u8; 4] {
+        let base = index as usize / 4;
+        let Some(color) = self.base_colors.get(base).filter(|_| base != 0) else { return [0; 4] };
+        let shade = |channel: u8| (channel as u32 * SHADE_MULTIPLIERS[index as usize % 4] / 255) as u8;
+        [shade(color[0]), shade(color[1]), shade(color[2]), 255]
+    }
+}
+
+impl Default for MapPalette {
+    fn default() -> Self {
+        Self::vanilla()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapIcon {
+    pub kind: i32,
+    pub x: i8,
+    pub z: i8,
+    // In sixteenths of a full turn
+    pub direction: u8,
+    pub display_name: Option<Component>
+}
+
+impl MapIcon {
+    fn read_versioned(buffer: &mut Buffer, protocol: i32) -> Result<Self, Error> {
+        Ok(Self {
+            kind: buffer.read_var_i32()?,
+            x: buffer.read_i8()?,
+            z: buffer.read_i8()?,
+            direction: buffer.read_u8()?,
+            display_name: match buffer.read_bool()? {
+                true => Some(read_component(buffer, protocol)?),
+                false => None
+            }
+        })
+    }
+
+    fn write_versioned(&self, mut buffer: Buffer, protocol: i32) -> Result<Buffer, Error> {
+        buffer.write_var_i32(self.kind)?;
+        buffer.write_i8(self.x)?;
+        buffer.write_i8(self.z)?;
+        buffer.write_u8(self.direction)?;
+        buffer.write_bool(self.display_name.is_some())?;
+        match &self.display_name {
+            Some(display_name) => write_component(buffer, display_name, protocol),
+            None => Ok(buffer)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapPatch {
+    pub columns: u8,
+    pub rows: u8,
+    pub x: u8,
+    pub z: u8,
+    pub colors: Vec<u8>
+}
+
+impl MapPatch {
+    pub fn new(columns: u8, rows: u8, x: u8, z: u8, colors: Vec<u8>) -> Self {
+        Self { columns, rows, x, z, colors }
+    }
+
+    pub fn full(colors: Vec<u8>) -> Self {
+        Self::new(MAP_WIDTH as u8, MAP_WIDTH as u8, 0, 0, colors)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.x == 0 && self.z == 0 && self.columns as usize == MAP_WIDTH && self.rows as usize == MAP_WIDTH && self.colors.len() >= MAP_WIDTH * MAP_WIDTH
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapData {
+    pub map_id: i32,
+    pub scale: u8,
+    // Only sent up to 1.16.5
+    pub tracking_position: bool,
+    pub locked: bool,
+    // None keeps the icons the client already has
+    pub icons: Option<Vec<MapIcon>>,
+    pub patch: Option<MapPatch>
+}
+
+impl MapData {
+    pub fn new(map_id: i32, scale: u8) -> Self {
+        Self {
+            map_id,
+            scale,
+            tracking_position: false,
+            locked: false,
+            icons: None,
+            patch: None
+        }
+    }
+
+    pub fn with_icons(self, icons: Vec<MapIcon>) -> Self {
+        Self { icons: Some(icons), ..self }
+    }
+
+    pub fn with_patch(self, patch: MapPatch) -> Self {
+        Self { patch: Some(patch), ..self }
+    }
+
+    pub fn with_locked(self, locked: bool) -> Self {
+        Self { locked, ..self }
+    }
+
+    pub fn read_versioned(buffer: &mut Buffer, protocol: i32) -> Result<Self, Error> {
+        let map_id = buffer.read_var_i32()?;
+        let scale = buffer.read_u8()?;
+        let tracking_position = protocol < OPTIONAL_ICONS_PROTOCOL && buffer.read_bool()?;
+        let locked = buffer.read_bool()?;
+
+        let icons = match protocol < OPTIONAL_ICONS_PROTOCOL || buffer.read_bool()? {
+            true => Some((0..buffer.read_length()?).map(|_| MapIcon::read_versioned(buffer, protocol)).collect::<Result<_, _>>()?),
+            false => None
+        };
+
+        let columns = buffer.read_u8()?;
+        let patch = match columns {
+            0 => None,
+            columns => {
+                let (rows, x, z) = (buffer.read_u8()?, buffer.read_u8()?, buffer.read_u8()?);
+                Some(MapPatch::new(columns, rows, x, z, buffer.read_byte_array()?))
+            }
+        };
+
+        Ok(Self {
+            map_id,
+            scale,
+            tracking_position,
+            locked,
+            icons,
+            patch
+        })
+    }
+
+    pub fn write_versioned(&self, mut buffer: Buffer, protocol: i32) -> Result<Buffer, Error> {
+        buffer.write_var_i32(self.map_id)?;
+        buffer.write_u8(self.scale)?;
+        if protocol < OPTIONAL_ICONS_PROTOCOL {
+            buffer.write_bool(self.tracking_position)?;
+        }
+        buffer.write_bool(self.locked)?;
+
+        match (&self.icons, protocol < OPTIONAL_ICONS_PROTOCOL) {
+            (None, true) => buffer.write_var_i32(0)?,
+            (None, false) => buffer.write_bool(false)?,
+            (Some(icons), optional) => {
+                if !optional {
+                    buffer.write_bool(true)?;
+                }
+                buffer.write_var_i32(icons.len() as i32)?;
+                for icon in icons {
+                    buffer = icon.write_versioned(buffer, protocol)?;
+                }
+            }
+        }
+
+        match &self.patch {
+            Some(patch) if patch.columns > 0 => {
+                buffer.write_u8(patch.columns)?;
+                buffer.write_u8(patch.rows)?;
+                buffer.write_u8(patch.x)?;
+                buffer.write_u8(patch.z)?;
+                buffer.write_byte_array(&patch.colors)?;
+            }
+            _ => buffer.write_u8(0)?
+        }
+        Ok(buffer)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapCanvas {
+    colors: Vec<u8>,
+    icons: Vec<MapIcon>,
+    scale: u8,
+    locked: bool,
+    complete: bool
+}
+
+impl Default for MapCanvas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MapCanvas {
+    pub fn new() -> Self {
+        Self {
+            colors: vec![0; MAP_WIDTH * MAP_WIDTH],
+            icons: Vec::new(),
+            scale: 0,
+            locked: false,
+            complete: false
+        }
+    }
+
+    pub fn apply(&mut self, data: &MapData) {
+        self.scale = data.scale;
+        self.locked = data.locked;
+        if let Some(icons) = &data.icons {
+            self.icons = icons.clone();
+        }
+        if let Some(patch) = &data.patch {
+            self.apply_patch(patch);
+        }
+    }
+
+    // Servers may send patches past the border or with too few colors
+    pub fn apply_patch(&mut self, patch: &MapPatch) {
+        let columns = patch.columns as usize;
+        for row in 0..patch.rows as usize {
+            let z = patch.z as usize + row;
+            for column in 0..columns {
+                let x = patch.x as usize + column;
+                let Some(color) = patch.colors.get(row * columns + column) else { return };
+                if x < MAP_WIDTH && z < MAP_WIDTH {
+                    self.colors[z * MAP_WIDTH + x] = *color;
+                }
+            }
+        }
+        self.complete |= patch.is_full();
+    }
+
+    pub fn pixel(&self, x: usize, z: usize) -> Option<u8> {
+        if x >= MAP_WIDTH || z >= MAP_WIDTH {
+            return None
+        }
+        Some(self.colors[z * MAP_WIDTH + x])
+    }
+
+    pub fn colors(&self) -> &[u8] {
+        &self.colors
+    }
+
+    pub fn icons(&self) -> &[MapIcon] {
+        &self.icons
+    }
+
+    pub fn scale(&self) -> u8 {
+        self.scale
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    pub fn to_rgba(&self, palette: &MapPalette) -> Vec<u8> {
+        self.colors.iter().flat_map(|color| palette.rgba(*color)).collect()
+    }
+}
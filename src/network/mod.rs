@@ -1,5 +1,41 @@
 pub mod buffer;
+pub mod bundle;
+pub mod chat;
+pub mod chunk;
+pub mod commands;
 pub mod connection;
+pub mod convenience;
+pub mod cookie;
+#[cfg(feature = "std-net")]
+pub mod correlation;
+pub mod decode;
+#[cfg(feature = "encryption")]
+pub mod encryption;
+pub mod forge;
+#[cfg(all(feature = "auth", feature = "webapi", feature = "std-net"))]
+pub mod forwarding;
+pub mod handshake;
+pub mod item;
+pub mod keep_alive;
+#[cfg(all(feature = "auth", feature = "webapi", feature = "std-net"))]
+pub mod login;
+pub mod map;
+pub mod nbt;
+pub mod plugin;
+#[cfg(feature = "std-net")]
+pub mod proxy;
+pub mod registry_codec;
+pub mod resource_pack;
+pub mod session;
+pub mod snbt;
+pub mod statistics;
+pub mod status;
+pub mod tags;
+#[cfg(feature = "std-net")]
+pub mod ticker;
+pub mod tracker;
+pub mod types;
+pub mod versions;
 
 use std::{
     fmt::{Display, Formatter},
@@ -35,10 +71,23 @@ pub enum Error {
     NotReadable(String),
     #[error("Invalid Packet Error => No packet {0} for the version {1} available!")]
     IllegalPacket(i32, String),
+    #[error("Decode Error => {reason} at byte {offset}")]
+    Decode { offset: usize, reason: String },
+    #[error("Disconnected => {0}")]
+    Disconnected(String),
+    #[error("Rejected => {0}")]
+    Rejected(String),
+    #[error("Unsupported Version => The server runs {name} (protocol {protocol}), which isn't in the version registry")]
+    UnsupportedVersion { protocol: i32, name: String },
+    #[error("Expired Certificate => The chat signing key has expired, refresh the player certificates")]
+    ExpiredCertificate,
     #[error("{0}")]
     Other(String),
     #[error("Io Error: {0}")]
-    IoError(#[from] io::Error)
+    IoError(#[from] io::Error),
+    #[cfg(feature = "web")]
+    #[error("Web Error: {0}")]
+    WebError(#[from] crate::web::Error)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -56,11 +105,12 @@ impl Display for PacketDirection {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PacketState {
     Handshaking,
     Login,
     Status,
+    Configuration,
     Play
 }
 
@@ -70,12 +120,33 @@ impl Display for PacketState {
             PacketState::Handshaking => write!(formatter, "Handshaking"),
             PacketState::Login => write!(formatter, "Login"),
             PacketState::Status => write!(formatter, "Status"),
+            PacketState::Configuration => write!(formatter, "Configuration"),
             PacketState::Play => write!(formatter, "Play")
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldDescriptor {
+    pub name: &'static str,
+    pub type_name: &'static str
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PacketDescriptor {
+    pub name: &'static str,
+    pub id: i32,
+    pub state: PacketState,
+    pub direction: PacketDirection,
+    pub fields: &'static [FieldDescriptor]
+}
+
 pub trait ProtocolVersion {
     fn id() -> i32;
     fn literal() -> &'static str;
 }
+
+pub trait Packet: connection::Readable {
+    fn state() -> PacketState;
+    fn id() -> i32;
+}
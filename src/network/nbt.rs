@@ -0,0 +1,395 @@
+use crate::{
+    components::Component,
+    network::{
+        buffer::Buffer,
+        connection::{Readable, Writable},
+        Error
+    }
+};
+
+pub const MAXIMUM_DEPTH: usize = 512;
+
+const TAG_END: u8 = 0;
+const TAG_BYTE: u8 = 1;
+const TAG_SHORT: u8 = 2;
+const TAG_INT: u8 = 3;
+const TAG_LONG: u8 = 4;
+const TAG_FLOAT: u8 = 5;
+const TAG_DOUBLE: u8 = 6;
+const TAG_BYTE_ARRAY: u8 = 7;
+const TAG_STRING: u8 = 8;
+const TAG_LIST: u8 = 9;
+const TAG_COMPOUND: u8 = 10;
+const TAG_INT_ARRAY: u8 = 11;
+const TAG_LONG_ARRAY: u8 = 12;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<u8>),
+    String(String),
+    List(Vec<Tag>),
+    Compound(Compound),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>)
+}
+
+impl Tag {
+    pub fn id(&self) -> u8 {
+        match self {
+            Tag::Byte(_) => TAG_BYTE,
+            Tag::Short(_) => TAG_SHORT,
+            Tag::Int(_) => TAG_INT,
+            Tag::Long(_) => TAG_LONG,
+            Tag::Float(_) => TAG_FLOAT,
+            Tag::Double(_) => TAG_DOUBLE,
+            Tag::ByteArray(_) => TAG_BYTE_ARRAY,
+            Tag::String(_) => TAG_STRING,
+            Tag::List(_) => TAG_LIST,
+            Tag::Compound(_) => TAG_COMPOUND,
+            Tag::IntArray(_) => TAG_INT_ARRAY,
+            Tag::LongArray(_) => TAG_LONG_ARRAY
+        }
+    }
+
+    // Numeric tags are converted like vanilla does
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Tag::Byte(value) => Some(*value as i64),
+            Tag::Short(value) => Some(*value as i64),
+            Tag::Int(value) => Some(*value as i64),
+            Tag::Long(value) => Some(*value),
+            Tag::Float(value) => Some(*value as i64),
+            Tag::Double(value) => Some(*value as i64),
+            _ => None
+        }
+    }
+
+    pub fn as_i32(&self) -> Option<i32> {
+        self.as_i64().map(|value| value as i32)
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Tag::Float(value) => Some(*value as f64),
+            Tag::Double(value) => Some(*value),
+            tag => tag.as_i64().map(|value| value as f64)
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        self.as_i64().map(|value| value != 0)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(value) => Some(value),
+            _ => None
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Tag]> {
+        match self {
+            Tag::List(list) => Some(list),
+            _ => None
+        }
+    }
+
+    pub fn as_compound(&self) -> Option<&Compound> {
+        match self {
+            Tag::Compound(compound) => Some(compound),
+            _ => None
+        }
+    }
+
+    // Only lists and compounds recurse, so deep nesting can't exhaust the stack
+    fn read_payload(buffer: &mut Buffer, id: u8, depth: usize) -> Result<Self, Error> {
+        if depth > MAXIMUM_DEPTH {
+            return Err(depth_error(buffer))
+        }
+
+        match id {
+            TAG_LIST => {
+                let element = buffer.read_u8()?;
+                let length = read_array_length(buffer)?;
+                let mut list = Vec::new();
+                for _ in 0..length {
+                    list.push(Self::read_payload(buffer, element, depth + 1)?);
+                }
+                Ok(Tag::List(list))
+            }
+            TAG_COMPOUND => Ok(Tag::Compound(Compound::read_payload(buffer, depth + 1)?)),
+            id => Self::read_value(buffer, id)
+        }
+    }
+
+    #[inline(never)]
+    fn read_value(buffer: &mut Buffer, id: u8) -> Result<Self, Error> {
+        Ok(match id {
+            TAG_BYTE => Tag::Byte(buffer.read_i8()?),
+            TAG_SHORT => Tag::Short(buffer.read_i16()?),
+            TAG_INT => Tag::Int(buffer.read_i32()?),
+            TAG_LONG => Tag::Long(buffer.read_i64()?),
+            TAG_FLOAT => Tag::Float(f32::from_bits(buffer.read_u32()?)),
+            TAG_DOUBLE => Tag::Double(f64::from_bits(buffer.read_u64()?)),
+            TAG_BYTE_ARRAY => {
+                let length = read_array_length(buffer)?;
+                Tag::ByteArray(buffer.read_bytes(length)?)
+            }
+            TAG_STRING => Tag::String(buffer.read_java_utf()?),
+            TAG_INT_ARRAY => {
+                let length = read_array_length(buffer)?;
+                Tag::IntArray((0..length).map(|_| buffer.read_i32()).collect::<Result<_, _>>()?)
+            }
+            TAG_LONG_ARRAY => {
+                let length = read_array_length(buffer)?;
+                Tag::LongArray((0..length).map(|_| buffer.read_i64()).collect::<Result<_, _>>()?)
+            }
+            id => {
+                return Err(Error::Decode {
+                    offset: buffer.position(),
+                    reason: format!("Invalid NBT tag type {}", id)
+                })
+            }
+        })
+    }
+
+    fn write_payload(&self, buffer: &mut Buffer) -> Result<(), Error> {
+        match self {
+            Tag::Byte(value) => buffer.write_i8(*value),
+            Tag::Short(value) => buffer.write_i16(*value),
+            Tag::Int(value) => buffer.write_i32(*value),
+            Tag::Long(value) => buffer.write_i64(*value),
+            Tag::Float(value) => buffer.write_u32(value.to_bits()),
+            Tag::Double(value) => buffer.write_u64(value.to_bits()),
+            Tag::ByteArray(bytes) => {
+                buffer.write_i32(bytes.len() as i32)?;
+                buffer.write_bytes(bytes)
+            }
+            Tag::String(value) => buffer.write_java_utf(value),
+            Tag::List(list) => {
+                let element = list.first().map(Tag::id).unwrap_or(TAG_END);
+                if list.iter().any(|tag| tag.id() != element) {
+                    return Err(Error::Other("NBT list elements must share one tag type".to_string()))
+                }
+
+                buffer.write_u8(element)?;
+                buffer.write_i32(list.len() as i32)?;
+                list.iter().try_for_each(|tag| tag.write_payload(buffer))
+            }
+            Tag::Compound(compound) => compound.write_payload(buffer),
+            Tag::IntArray(values) => {
+                buffer.write_i32(values.len() as i32)?;
+                values.iter().try_for_each(|value| buffer.write_i32(*value))
+            }
+            Tag::LongArray(values) => {
+                buffer.write_i32(values.len() as i32)?;
+                values.iter().try_for_each(|value| buffer.write_i64(*value))
+            }
+        }
+    }
+}
+
+#[cold]
+fn depth_error(buffer: &Buffer) -> Error {
+    Error::Decode {
+        offset: buffer.position(),
+        reason: format!("NBT is nested deeper than {} levels", MAXIMUM_DEPTH)
+    }
+}
+
+fn read_array_length(buffer: &mut Buffer) -> Result<usize, Error> {
+    let offset = buffer.position();
+    let length = buffer.read_i32()?;
+    if length < 0 || length as usize > buffer.remaining() {
+        return Err(Error::Decode {
+            offset,
+            reason: format!("Invalid NBT array length {}", length)
+        })
+    }
+    Ok(length as usize)
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Compound(Vec<(String, Tag)>);
+
+impl Compound {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn with(mut self, name: impl Into<String>, tag: Tag) -> Self {
+        self.insert(name, tag);
+        self
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, tag: Tag) {
+        let name = name.into();
+        match self.0.iter_mut().find(|(key, _)| *key == name) {
+            Some((_, value)) => *value = tag,
+            None => self.0.push((name, tag))
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        self.0.iter().find(|(key, _)| key == name).map(|(_, tag)| tag)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Tag)> {
+        self.0.iter().map(|(name, tag)| (name.as_str(), tag))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    // Before 1.20.2 the root compound of network NBT carries a name like NBT files do
+    pub fn read_named(buffer: &mut Buffer) -> Result<(String, Self), Error> {
+        expect_compound(buffer)?;
+        let name = buffer.read_java_utf()?;
+        Ok((name, Self::read_payload(buffer, 0)?))
+    }
+
+    pub fn write_named(&self, name: &str, buffer: &mut Buffer) -> Result<(), Error> {
+        buffer.write_u8(TAG_COMPOUND)?;
+        buffer.write_java_utf(name)?;
+        self.write_payload(buffer)
+    }
+
+    fn read_payload(buffer: &mut Buffer, depth: usize) -> Result<Self, Error> {
+        let mut compound = Self::new();
+        loop {
+            let id = buffer.read_u8()?;
+            if id == TAG_END {
+                return Ok(compound)
+            }
+
+            let name = buffer.read_java_utf()?;
+            compound.0.push((name, Tag::read_payload(buffer, id, depth)?));
+        }
+    }
+
+    fn write_payload(&self, buffer: &mut Buffer) -> Result<(), Error> {
+        for (name, tag) in &self.0 {
+            buffer.write_u8(tag.id())?;
+            buffer.write_java_utf(name)?;
+            tag.write_payload(buffer)?;
+        }
+        buffer.write_u8(TAG_END)
+    }
+}
+
+fn expect_compound(buffer: &mut Buffer) -> Result<(), Error> {
+    match buffer.read_u8()? {
+        TAG_COMPOUND => Ok(()),
+        id => {
+            Err(Error::Decode {
+                offset: buffer.position() - 1,
+                reason: format!("Expected a NBT compound, got tag type {}", id)
+            })
+        }
+    }
+}
+
+// Since 1.20.2 the root compound of network NBT is nameless
+impl Writable for Compound {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        buffer.write_u8(TAG_COMPOUND)?;
+        self.write_payload(&mut buffer)?;
+        Ok(buffer)
+    }
+}
+
+impl Readable for Compound {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        expect_compound(buffer)?;
+        Self::read_payload(buffer, 0)
+    }
+}
+
+// Since 1.20.3 network NBT may have any tag as its root, e.g. a plain string for text components
+impl Writable for Tag {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        buffer.write_u8(self.id())?;
+        self.write_payload(&mut buffer)?;
+        Ok(buffer)
+    }
+}
+
+impl Readable for Tag {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        let id = buffer.read_u8()?;
+        Self::read_payload(buffer, id, 0)
+    }
+}
+
+// Since 1.20.3 components are network NBT with booleans written as bytes
+impl From<&Component> for Compound {
+    fn from(component: &Component) -> Self {
+        let mut compound = Compound::new().with("text", Tag::String(component.text.clone()));
+        if let Some(color) = &component.color {
+            compound.insert("color", Tag::String(color.clone()));
+        }
+
+        let styles = [
+            ("bold", component.bold),
+            ("italic", component.italic),
+            ("underlined", component.underlined),
+            ("strikethrough", component.strikethrough),
+            ("obfuscated", component.obfuscated)
+        ];
+        for (name, style) in styles {
+            if let Some(style) = style {
+                compound.insert(name, Tag::Byte(style as i8));
+            }
+        }
+
+        if !component.extra.is_empty() {
+            compound.insert("extra", Tag::List(component.extra.iter().map(|extra| Tag::Compound(extra.into())).collect()));
+        }
+        compound
+    }
+}
+
+impl From<&Tag> for Component {
+    fn from(tag: &Tag) -> Self {
+        match tag {
+            Tag::String(text) => Component::text(text.clone()),
+            Tag::List(list) => {
+                let mut components = list.iter().map(Component::from);
+                let first = components.next().unwrap_or_default();
+                components.fold(first, Component::append)
+            }
+            Tag::Compound(compound) => {
+                let style = |name| compound.get(name).and_then(Tag::as_bool);
+                Component {
+                    text: compound
+                        .get("text")
+                        .map(|text| text.as_str().map(str::to_string).unwrap_or_else(|| text.to_snbt()))
+                        .unwrap_or_default(),
+                    color: compound.get("color").and_then(Tag::as_str).map(str::to_string),
+                    bold: style("bold"),
+                    italic: style("italic"),
+                    underlined: style("underlined"),
+                    strikethrough: style("strikethrough"),
+                    obfuscated: style("obfuscated"),
+                    extra: compound
+                        .get("extra")
+                        .and_then(Tag::as_list)
+                        .map(|extra| extra.iter().map(Component::from).collect())
+                        .unwrap_or_default()
+                }
+            }
+            tag => Component::text(tag.to_snbt())
+        }
+    }
+}
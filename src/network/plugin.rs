@@ -0,0 +1,184 @@
+use std::{any::Any, collections::HashMap};
+
+use crate::network::{
+    buffer::Buffer,
+    connection::{Readable, Writable},
+    types::Identifier,
+    ByteOrder, Error
+};
+
+pub const BRAND_CHANNEL: &str = "minecraft:brand";
+pub const BUNGEECORD_CHANNEL: &str = "bungeecord:main";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginMessage {
+    pub channel: Identifier,
+    pub data: Vec<u8>
+}
+
+impl PluginMessage {
+    pub fn encode<T: Writable>(channel: &str, message: &T) -> Result<Self, Error> {
+        Ok(Self {
+            channel: Identifier::parse(channel)?,
+            data: message.write(Buffer::empty(true, Some(ByteOrder::BigEndian)))?.to_bytes()
+        })
+    }
+
+    pub fn decode<T: Readable>(&self) -> Result<T, Error> {
+        T::read(&mut Buffer::new(self.data.clone(), false, Some(ByteOrder::BigEndian)))
+    }
+}
+
+impl Writable for PluginMessage {
+    fn write(&self, buffer: Buffer) -> Result<Buffer, Error> {
+        let mut buffer = self.channel.write(buffer)?;
+        buffer.write_bytes(&self.data)?;
+        Ok(buffer)
+    }
+}
+
+impl Readable for PluginMessage {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        let channel = Identifier::parse(&buffer.read_string()?)?;
+        let remaining = buffer.remaining();
+        Ok(Self {
+            channel,
+            data: buffer.read_bytes(remaining)?
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum PluginPayload {
+    Typed(Box<dyn Any + Send>),
+    Raw(Vec<u8>)
+}
+
+impl PluginPayload {
+    pub fn downcast<T: 'static>(self) -> Option<T> {
+        match self {
+            PluginPayload::Typed(payload) => payload.downcast().ok().map(|payload| *payload),
+            PluginPayload::Raw(_) => None
+        }
+    }
+}
+
+type PayloadDecoder = fn(Buffer) -> Result<Box<dyn Any + Send>, Error>;
+
+pub struct PluginChannels {
+    decoders: HashMap<Identifier, PayloadDecoder>
+}
+
+impl PluginChannels {
+    pub fn new() -> Self {
+        Self { decoders: HashMap::new() }
+    }
+
+    pub fn with_defaults() -> Self {
+        let mut channels = Self::new();
+        channels.decoders.insert(Identifier::new("minecraft", "brand").unwrap(), decode::<BrandMessage>);
+        channels.decoders.insert(Identifier::new("bungeecord", "main").unwrap(), decode::<BungeeCordMessage>);
+        channels
+    }
+
+    pub fn register<T: Readable + Send + 'static>(mut self, channel: &str) -> Result<Self, Error> {
+        self.decoders.insert(Identifier::parse(channel)?, decode::<T>);
+        Ok(self)
+    }
+
+    pub fn is_registered(&self, channel: &Identifier) -> bool {
+        self.decoders.contains_key(channel)
+    }
+
+    pub fn decode(&self, message: &PluginMessage) -> Result<PluginPayload, Error> {
+        match self.decoders.get(&message.channel) {
+            Some(decoder) => Ok(PluginPayload::Typed(decoder(Buffer::new(message.data.clone(), false, Some(ByteOrder::BigEndian)))?)),
+            None => Ok(PluginPayload::Raw(message.data.clone()))
+        }
+    }
+}
+
+impl Default for PluginChannels {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
+
+fn decode<T: Readable + Send + 'static>(mut buffer: Buffer) -> Result<Box<dyn Any + Send>, Error> {
+    Ok(Box::new(T::read(&mut buffer)?))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrandMessage(pub String);
+
+impl Writable for BrandMessage {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        buffer.write_str(&self.0)?;
+        Ok(buffer)
+    }
+}
+
+impl Readable for BrandMessage {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        Ok(Self(buffer.read_string()?))
+    }
+}
+
+// The BungeeCord channel uses Java's modified UTF-8
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BungeeCordMessage {
+    Connect(String),
+    ConnectOther { player: String, server: String },
+    GetServer(Option<String>),
+    Other { subchannel: String, data: Vec<u8> }
+}
+
+impl Writable for BungeeCordMessage {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        match self {
+            BungeeCordMessage::Connect(server) => {
+                buffer.write_java_utf("Connect")?;
+                buffer.write_java_utf(server)?;
+            }
+            BungeeCordMessage::ConnectOther { player, server } => {
+                buffer.write_java_utf("ConnectOther")?;
+                buffer.write_java_utf(player)?;
+                buffer.write_java_utf(server)?;
+            }
+            BungeeCordMessage::GetServer(server) => {
+                buffer.write_java_utf("GetServer")?;
+                if let Some(server) = server {
+                    buffer.write_java_utf(server)?;
+                }
+            }
+            BungeeCordMessage::Other { subchannel, data } => {
+                buffer.write_java_utf(subchannel)?;
+                buffer.write_bytes(data)?;
+            }
+        }
+        Ok(buffer)
+    }
+}
+
+impl Readable for BungeeCordMessage {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        let subchannel = buffer.read_java_utf()?;
+        Ok(match subchannel.as_str() {
+            "Connect" => BungeeCordMessage::Connect(buffer.read_java_utf()?),
+            "ConnectOther" => {
+                BungeeCordMessage::ConnectOther {
+                    player: buffer.read_java_utf()?,
+                    server: buffer.read_java_utf()?
+                }
+            }
+            "GetServer" => BungeeCordMessage::GetServer(if buffer.remaining() > 0 { Some(buffer.read_java_utf()?) } else { None }),
+            _ => {
+                let remaining = buffer.remaining();
+                BungeeCordMessage::Other {
+                    subchannel,
+                    data: buffer.read_bytes(remaining)?
+                }
+            }
+        })
+    }
+}
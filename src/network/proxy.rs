@@ -0,0 +1,179 @@
+use std::io;
+
+use tokio::select;
+#[cfg(all(feature = "auth", feature = "webapi"))]
+use uuid::Uuid;
+
+use crate::network::{
+    buffer::Buffer,
+    connection::{async_socket::AsyncSocketConnection, machine::DecodedPacket, Client, Role, Server, READ_CHUNK_LENGTH},
+    Error, PacketDirection, PacketState
+};
+#[cfg(all(feature = "auth", feature = "webapi"))]
+use crate::{
+    auth::Session,
+    network::login::{answer_encryption_request, GameProfile, LoginAction},
+    webapi::{ApiOperation, Endpoints}
+};
+
+const CLIENTBOUND_ENCRYPTION_REQUEST: i32 = 0x01;
+
+#[derive(Debug, Clone)]
+pub enum RelayAction {
+    Forward,
+    Drop,
+    Replace(Buffer)
+}
+
+// The account the relay logs into online mode servers with, the client leg stays unencrypted
+#[cfg(all(feature = "auth", feature = "webapi"))]
+#[derive(Debug, Clone)]
+pub struct RelayAccount {
+    endpoints: Endpoints,
+    access_token: String,
+    profile_id: Uuid
+}
+
+#[cfg(all(feature = "auth", feature = "webapi"))]
+impl RelayAccount {
+    pub fn new(session: &Session, profile: &GameProfile) -> Self {
+        Self {
+            endpoints: Endpoints::default(),
+            access_token: session.access_token.clone(),
+            profile_id: profile.id
+        }
+    }
+
+    pub fn endpoints(self, endpoints: Endpoints) -> Self {
+        Self { endpoints, ..self }
+    }
+
+    async fn answer_encryption(&self, server: &mut AsyncSocketConnection<'_, Client>, packet: &mut Buffer) -> Result<(), Error> {
+        let protocol = server.require_protocol_version("relay encryption")?;
+        packet.read_var_i32()?;
+        for action in answer_encryption_request(packet, protocol)? {
+            match action {
+                LoginAction::JoinSession(server_hash) => {
+                    self.endpoints
+                        .join_server(&self.access_token, self.profile_id, &server_hash)
+                        .await
+                        .map_err(|error| error.within(ApiOperation::JoinServer))?
+                }
+                LoginAction::Send(response) => {
+                    server.write(response).await?;
+                }
+                LoginAction::EnableEncryption(shared_secret) => server.enable_encryption(&shared_secret),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+enum Upstream {
+    Offline,
+    #[cfg(all(feature = "auth", feature = "webapi"))]
+    Online(RelayAccount)
+}
+
+// Servers requesting encryption are refused, relay_online logs into them
+pub async fn relay<'a, 'b>(
+    client: AsyncSocketConnection<'a, Server>,
+    server: AsyncSocketConnection<'b, Client>,
+    inspector: impl FnMut(PacketDirection, PacketState, &mut Buffer) -> RelayAction
+) -> Result<(), Error> {
+    relay_upstream(client, server, Upstream::Offline, inspector).await
+}
+
+// Encryption requests are answered by the relay itself, so the inspector still sees plain packets
+#[cfg(all(feature = "auth", feature = "webapi"))]
+pub async fn relay_online<'a, 'b>(
+    client: AsyncSocketConnection<'a, Server>,
+    server: AsyncSocketConnection<'b, Client>,
+    account: RelayAccount,
+    inspector: impl FnMut(PacketDirection, PacketState, &mut Buffer) -> RelayAction
+) -> Result<(), Error> {
+    relay_upstream(client, server, Upstream::Online(account), inspector).await
+}
+
+// Waiting for readability is cancel safe and the bytes are read synchronously afterwards, partial frames
+// stay buffered in the relay's connections no matter which leg wins the select
+fn receive<R: Role>(connection: &mut AsyncSocketConnection<'_, R>, chunk: &mut [u8]) -> Result<bool, Error> {
+    match connection.socket().try_read(chunk) {
+        Ok(0) => Ok(false),
+        Ok(size) => {
+            connection.machine_mut().feed_incoming(&chunk[..size]);
+            Ok(true)
+        }
+        Err(error) if error.kind() == io::ErrorKind::WouldBlock => Ok(true),
+        Err(error) => Err(error.into())
+    }
+}
+
+async fn relay_upstream<'a, 'b>(
+    mut client: AsyncSocketConnection<'a, Server>,
+    mut server: AsyncSocketConnection<'b, Client>,
+    upstream: Upstream,
+    mut inspector: impl FnMut(PacketDirection, PacketState, &mut Buffer) -> RelayAction
+) -> Result<(), Error> {
+    let mut chunk = [0; READ_CHUNK_LENGTH];
+    loop {
+        let (direction, packet) = if let Some(packet) = client.machine_mut().poll_packet()? {
+            (PacketDirection::Serverbound, packet)
+        } else if let Some(packet) = server.machine_mut().poll_packet()? {
+            (PacketDirection::Clientbound, packet)
+        } else {
+            let direction = select! {
+                readable = client.socket().readable() => readable.map(|_| PacketDirection::Serverbound)?,
+                readable = server.socket().readable() => readable.map(|_| PacketDirection::Clientbound)?
+            };
+
+            let open = match direction {
+                PacketDirection::Serverbound => receive(&mut client, &mut chunk)?,
+                PacketDirection::Clientbound => receive(&mut server, &mut chunk)?
+            };
+            if !open {
+                return Ok(())
+            }
+            continue
+        };
+
+        let DecodedPacket { state, id, mut buffer } = packet;
+        if (direction, state, id) == (PacketDirection::Clientbound, PacketState::Login, CLIENTBOUND_ENCRYPTION_REQUEST) {
+            match &upstream {
+                Upstream::Offline => {
+                    return Err(Error::Rejected(
+                        "Unable to relay => The server requested encryption, online mode servers need a relay account".to_string()
+                    ))
+                }
+                #[cfg(all(feature = "auth", feature = "webapi"))]
+                Upstream::Online(account) => {
+                    account.answer_encryption(&mut server, &mut buffer).await?;
+                    continue
+                }
+            }
+        }
+
+        let forwarded = match inspector(direction, state, &mut buffer) {
+            RelayAction::Forward => Some(buffer),
+            RelayAction::Drop => None,
+            RelayAction::Replace(replacement) => Some(replacement)
+        };
+
+        if let Some(forwarded) = forwarded {
+            match direction {
+                PacketDirection::Serverbound => server.write(forwarded).await?,
+                PacketDirection::Clientbound => client.write(forwarded).await?
+            };
+        }
+
+        let (state, protocol_version) = match direction {
+            PacketDirection::Serverbound => (client.state(), client.protocol_version()),
+            PacketDirection::Clientbound => (server.state(), server.protocol_version())
+        };
+        client.set_state(state);
+        server.set_state(state);
+        client.set_protocol_version(protocol_version);
+        server.set_protocol_version(protocol_version);
+    }
+}
@@ -0,0 +1,470 @@
+use crate::network::{
+    buffer::Buffer,
+    connection::{Readable, VersionedReadable, VersionedWritable, Writable},
+    nbt::{Compound, Tag},
+    types::Identifier,
+    Error
+};
+
+pub const DIMENSION_TYPE_REGISTRY: &str = "minecraft:dimension_type";
+pub const BIOME_REGISTRY: &str = "minecraft:worldgen/biome";
+pub const CHAT_TYPE_REGISTRY: &str = "minecraft:chat_type";
+pub const MAX_REGISTRY_ENTRIES: usize = 65536;
+
+// Since 1.20.5 every registry comes in its own Registry Data packet
+pub const REGISTRY_DATA_PROTOCOL: i32 = 766;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegistryEntry {
+    pub name: Identifier,
+    pub id: i32,
+    pub element: Compound
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DimensionType {
+    pub min_y: i32,
+    pub height: i32,
+    pub logical_height: i32,
+    pub has_skylight: bool,
+    pub has_ceiling: bool,
+    pub ultrawarm: bool,
+    pub natural: bool,
+    pub coordinate_scale: f64,
+    pub ambient_light: f32,
+    pub fixed_time: Option<i64>,
+    pub effects: String
+}
+
+impl DimensionType {
+    // Only min_y and height are needed to place chunk sections
+    pub fn from_compound(element: &Compound) -> Option<Self> {
+        let height = element.get("height")?.as_i32()?;
+        Some(Self {
+            min_y: element.get("min_y")?.as_i32()?,
+            height,
+            logical_height: element.get("logical_height").and_then(Tag::as_i32).unwrap_or(height),
+            has_skylight: element.get("has_skylight").and_then(Tag::as_bool).unwrap_or(true),
+            has_ceiling: element.get("has_ceiling").and_then(Tag::as_bool).unwrap_or(false),
+            ultrawarm: element.get("ultrawarm").and_then(Tag::as_bool).unwrap_or(false),
+            natural: element.get("natural").and_then(Tag::as_bool).unwrap_or(true),
+            coordinate_scale: element.get("coordinate_scale").and_then(Tag::as_f64).unwrap_or(1.0),
+            ambient_light: element.get("ambient_light").and_then(Tag::as_f64).unwrap_or(0.0) as f32,
+            fixed_time: element.get("fixed_time").and_then(Tag::as_i64),
+            effects: element.get("effects").and_then(Tag::as_str).unwrap_or("minecraft:overworld").to_string()
+        })
+    }
+
+    pub fn section_count(&self) -> usize {
+        (self.height / 16).max(0) as usize
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Biome {
+    pub name: Identifier,
+    pub id: i32,
+    pub has_precipitation: bool,
+    pub temperature: f32,
+    pub downfall: f32
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatType {
+    pub name: Identifier,
+    pub id: i32,
+    pub translation_key: String,
+    pub parameters: Vec<String>
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RegistryCodec {
+    root: Compound
+}
+
+impl RegistryCodec {
+    pub fn new(root: Compound) -> Self {
+        Self { root }
+    }
+
+    pub fn root(&self) -> &Compound {
+        &self.root
+    }
+
+    pub fn registries(&self) -> impl Iterator<Item = &str> {
+        self.root.iter().map(|(name, _)| name)
+    }
+
+    // Leaves out entries without an element, i.e. those a 1.20.5+ client takes from a known pack
+    pub fn entries(&self, registry: &str) -> Vec<RegistryEntry> {
+        let entries = self
+            .root
+            .get(registry)
+            .and_then(Tag::as_compound)
+            .and_then(|registry| registry.get("value"))
+            .and_then(Tag::as_list);
+        entries
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|entry| {
+                let entry = entry.as_compound()?;
+                Some(RegistryEntry {
+                    name: Identifier::parse(entry.get("name")?.as_str()?).ok()?,
+                    id: entry.get("id")?.as_i32()?,
+                    element: entry.get("element")?.as_compound()?.clone()
+                })
+            })
+            .collect()
+    }
+
+    pub fn dimension_type(&self, identifier: &Identifier) -> Option<DimensionType> {
+        let entry = self.entries(DIMENSION_TYPE_REGISTRY).into_iter().find(|entry| entry.name == *identifier)?;
+        DimensionType::from_compound(&entry.element)
+    }
+
+    pub fn dimension_types(&self) -> Vec<(Identifier, DimensionType)> {
+        self.entries(DIMENSION_TYPE_REGISTRY)
+            .into_iter()
+            .filter_map(|entry| Some((entry.name, DimensionType::from_compound(&entry.element)?)))
+            .collect()
+    }
+
+    pub fn biomes(&self) -> Vec<Biome> {
+        self.entries(BIOME_REGISTRY)
+            .into_iter()
+            .map(|entry| {
+                Biome {
+                    has_precipitation: entry.element.get("has_precipitation").and_then(Tag::as_bool).unwrap_or(true),
+                    temperature: entry.element.get("temperature").and_then(Tag::as_f64).unwrap_or(0.5) as f32,
+                    downfall: entry.element.get("downfall").and_then(Tag::as_f64).unwrap_or(0.5) as f32,
+                    name: entry.name,
+                    id: entry.id
+                }
+            })
+            .collect()
+    }
+
+    pub fn chat_types(&self) -> Vec<ChatType> {
+        self.entries(CHAT_TYPE_REGISTRY)
+            .into_iter()
+            .filter_map(|entry| {
+                let chat = entry.element.get("chat")?.as_compound()?;
+                Some(ChatType {
+                    translation_key: chat.get("translation_key")?.as_str()?.to_string(),
+                    parameters: chat
+                        .get("parameters")
+                        .and_then(Tag::as_list)
+                        .unwrap_or_default()
+                        .iter()
+                        .filter_map(|parameter| parameter.as_str().map(str::to_string))
+                        .collect(),
+                    name: entry.name,
+                    id: entry.id
+                })
+            })
+            .collect()
+    }
+
+    // Splits the codec into the Registry Data packets of 1.20.5+, in the order of its registries
+    pub fn registry_data(&self) -> Result<Vec<RegistryData>, Error> {
+        self.root
+            .iter()
+            .map(|(registry, tag)| {
+                let values = tag.as_compound().and_then(|registry| registry.get("value")).and_then(Tag::as_list).unwrap_or_default();
+                let entries = values
+                    .iter()
+                    .filter_map(Tag::as_compound)
+                    .map(|entry| {
+                        let id = entry
+                            .get("name")
+                            .and_then(Tag::as_str)
+                            .ok_or_else(|| Error::Other(format!("Unable to split registry codec => Entry of {} has no name", registry)))?;
+                        Ok(RegistryDataEntry {
+                            id: Identifier::parse(id)?,
+                            data: entry.get("element").and_then(Tag::as_compound).cloned()
+                        })
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                Ok(RegistryData {
+                    registry: Identifier::parse(registry)?,
+                    entries
+                })
+            })
+            .collect()
+    }
+
+    // Vanilla clients also expect the damage type registry and friends
+    pub fn vanilla() -> Self {
+        let monster_spawn_light_level = Tag::Compound(
+            Compound::new()
+                .with("type", string("minecraft:uniform"))
+                .with("value", Tag::Compound(Compound::new().with("min_inclusive", Tag::Int(0)).with("max_inclusive", Tag::Int(7))))
+        );
+
+        let overworld = Compound::new()
+            .with("piglin_safe", Tag::Byte(0))
+            .with("natural", Tag::Byte(1))
+            .with("ambient_light", Tag::Float(0.0))
+            .with("monster_spawn_block_light_limit", Tag::Int(0))
+            .with("infiniburn", string("#minecraft:infiniburn_overworld"))
+            .with("respawn_anchor_works", Tag::Byte(0))
+            .with("has_skylight", Tag::Byte(1))
+            .with("bed_works", Tag::Byte(1))
+            .with("effects", string("minecraft:overworld"))
+            .with("has_raids", Tag::Byte(1))
+            .with("logical_height", Tag::Int(384))
+            .with("coordinate_scale", Tag::Double(1.0))
+            .with("monster_spawn_light_level", monster_spawn_light_level.clone())
+            .with("min_y", Tag::Int(-64))
+            .with("ultrawarm", Tag::Byte(0))
+            .with("has_ceiling", Tag::Byte(0))
+            .with("height", Tag::Int(384));
+
+        let nether = Compound::new()
+            .with("piglin_safe", Tag::Byte(1))
+            .with("natural", Tag::Byte(0))
+            .with("ambient_light", Tag::Float(0.1))
+            .with("monster_spawn_block_light_limit", Tag::Int(15))
+            .with("infiniburn", string("#minecraft:infiniburn_nether"))
+            .with("respawn_anchor_works", Tag::Byte(1))
+            .with("has_skylight", Tag::Byte(0))
+            .with("bed_works", Tag::Byte(0))
+            .with("effects", string("minecraft:the_nether"))
+            .with("fixed_time", Tag::Long(18000))
+            .with("has_raids", Tag::Byte(0))
+            .with("logical_height", Tag::Int(128))
+            .with("coordinate_scale", Tag::Double(8.0))
+            .with("monster_spawn_light_level", Tag::Int(7))
+            .with("min_y", Tag::Int(0))
+            .with("ultrawarm", Tag::Byte(1))
+            .with("has_ceiling", Tag::Byte(1))
+            .with("height", Tag::Int(256));
+
+        let end = Compound::new()
+            .with("piglin_safe", Tag::Byte(0))
+            .with("natural", Tag::Byte(0))
+            .with("ambient_light", Tag::Float(0.0))
+            .with("monster_spawn_block_light_limit", Tag::Int(0))
+            .with("infiniburn", string("#minecraft:infiniburn_end"))
+            .with("respawn_anchor_works", Tag::Byte(0))
+            .with("has_skylight", Tag::Byte(0))
+            .with("bed_works", Tag::Byte(0))
+            .with("effects", string("minecraft:the_end"))
+            .with("fixed_time", Tag::Long(6000))
+            .with("has_raids", Tag::Byte(1))
+            .with("logical_height", Tag::Int(256))
+            .with("coordinate_scale", Tag::Double(1.0))
+            .with("monster_spawn_light_level", monster_spawn_light_level)
+            .with("min_y", Tag::Int(0))
+            .with("ultrawarm", Tag::Byte(0))
+            .with("has_ceiling", Tag::Byte(0))
+            .with("height", Tag::Int(256));
+
+        let mood_sound = Compound::new()
+            .with("tick_delay", Tag::Int(6000))
+            .with("offset", Tag::Double(2.0))
+            .with("sound", string("minecraft:ambient.cave"))
+            .with("block_search_extent", Tag::Int(8));
+        let plains = Compound::new()
+            .with("has_precipitation", Tag::Byte(1))
+            .with("temperature", Tag::Float(0.8))
+            .with("downfall", Tag::Float(0.4))
+            .with(
+                "effects",
+                Tag::Compound(
+                    Compound::new()
+                        .with("sky_color", Tag::Int(7907327))
+                        .with("water_fog_color", Tag::Int(329011))
+                        .with("fog_color", Tag::Int(12638463))
+                        .with("water_color", Tag::Int(4159204))
+                        .with("mood_sound", Tag::Compound(mood_sound))
+                )
+            );
+
+        let chat = Compound::new().with("chat", decoration("chat.type.text")).with("narration", decoration("chat.type.text.narrate"));
+
+        Self::new(
+            Compound::new()
+                .with(
+                    DIMENSION_TYPE_REGISTRY,
+                    registry(
+                        DIMENSION_TYPE_REGISTRY,
+                        vec![("minecraft:overworld", overworld), ("minecraft:the_nether", nether), ("minecraft:the_end", end)]
+                    )
+                )
+                .with(BIOME_REGISTRY, registry(BIOME_REGISTRY, vec![("minecraft:plains", plains)]))
+                .with(CHAT_TYPE_REGISTRY, registry(CHAT_TYPE_REGISTRY, vec![("minecraft:chat", chat)]))
+        )
+    }
+}
+
+fn string(value: &str) -> Tag {
+    Tag::String(value.to_string())
+}
+
+fn decoration(translation_key: &str) -> Tag {
+    Tag::Compound(
+        Compound::new()
+            .with("translation_key", string(translation_key))
+            .with("parameters", Tag::List(vec![string("sender"), string("content")]))
+    )
+}
+
+fn registry(name: &str, entries: Vec<(&str, Compound)>) -> Tag {
+    let entries = entries
+        .into_iter()
+        .enumerate()
+        .map(|(id, (name, element))| Tag::Compound(Compound::new().with("name", string(name)).with("id", Tag::Int(id as i32)).with("element", Tag::Compound(element))))
+        .collect();
+    Tag::Compound(Compound::new().with("type", string(name)).with("value", Tag::List(entries)))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegistryDataEntry {
+    pub id: Identifier,
+    // None if the client already knows the entry from a known pack
+    pub data: Option<Compound>
+}
+
+// A single Registry Data packet of 1.20.5+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegistryData {
+    pub registry: Identifier,
+    pub entries: Vec<RegistryDataEntry>
+}
+
+impl Readable for RegistryData {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        let registry = Identifier::read(buffer)?;
+        let offset = buffer.position();
+        let count = buffer.read_length()?;
+        if count > MAX_REGISTRY_ENTRIES {
+            return Err(Error::Decode {
+                offset,
+                reason: format!("Too many registry entries ({} > {})", count, MAX_REGISTRY_ENTRIES)
+            })
+        }
+
+        let entries = (0..count)
+            .map(|_| {
+                Ok(RegistryDataEntry {
+                    id: Identifier::read(buffer)?,
+                    data: match buffer.read_bool()? {
+                        true => Some(Compound::read(buffer)?),
+                        false => None
+                    }
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(Self { registry, entries })
+    }
+}
+
+impl Writable for RegistryData {
+    fn write(&self, buffer: Buffer) -> Result<Buffer, Error> {
+        if self.entries.len() > MAX_REGISTRY_ENTRIES {
+            return Err(Error::Other(format!(
+                "Unable to write registry data => Too many entries in {} ({} > {})",
+                self.registry,
+                self.entries.len(),
+                MAX_REGISTRY_ENTRIES
+            )))
+        }
+
+        let mut buffer = self.registry.write(buffer)?;
+        buffer.write_var_i32(self.entries.len() as i32)?;
+        for entry in &self.entries {
+            buffer = entry.id.write(buffer)?;
+            buffer.write_bool(entry.data.is_some())?;
+            if let Some(data) = &entry.data {
+                buffer = data.write(buffer)?;
+            }
+        }
+        Ok(buffer)
+    }
+}
+
+// Collects the Registry Data packets of the configuration state into a codec, the ids are the positions in each packet
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RegistryCodecBuilder {
+    root: Compound
+}
+
+impl RegistryCodecBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // A registry sent twice replaces the earlier one
+    pub fn push(&mut self, data: RegistryData) {
+        let registry = data.registry.to_string();
+        let entries = data
+            .entries
+            .into_iter()
+            .enumerate()
+            .map(|(id, entry)| {
+                let mut compound = Compound::new().with("name", string(&entry.id.to_string())).with("id", Tag::Int(id as i32));
+                if let Some(data) = entry.data {
+                    compound.insert("element", Tag::Compound(data));
+                }
+                Tag::Compound(compound)
+            })
+            .collect();
+        self.root
+            .insert(registry.clone(), Tag::Compound(Compound::new().with("type", string(&registry)).with("value", Tag::List(entries))));
+    }
+
+    pub fn build(self) -> RegistryCodec {
+        RegistryCodec::new(self.root)
+    }
+}
+
+impl Writable for RegistryCodec {
+    fn write(&self, buffer: Buffer) -> Result<Buffer, Error> {
+        self.root.write(buffer)
+    }
+}
+
+impl Readable for RegistryCodec {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        Ok(Self::new(Compound::read(buffer)?))
+    }
+}
+
+// A named root compound until 1.20.1, network NBT since 1.20.2 and one packet per registry since 1.20.5
+impl VersionedReadable for RegistryCodec {
+    fn read_versioned(buffer: &mut Buffer, protocol: i32) -> Result<Self, Error> {
+        if protocol >= REGISTRY_DATA_PROTOCOL {
+            let mut builder = RegistryCodecBuilder::new();
+            builder.push(RegistryData::read(buffer)?);
+            return Ok(builder.build())
+        }
+
+        Ok(Self::new(match protocol >= 764 {
+            true => Compound::read(buffer)?,
+            false => Compound::read_named(buffer)?.1
+        }))
+    }
+}
+
+impl VersionedWritable for RegistryCodec {
+    fn write_versioned(&self, mut buffer: Buffer, protocol: i32) -> Result<Buffer, Error> {
+        if protocol >= REGISTRY_DATA_PROTOCOL {
+            let mut packets = self.registry_data()?;
+            if packets.len() != 1 {
+                return Err(Error::Other(format!(
+                    "Unable to write registry codec => Protocol {} sends every registry on its own, use registry_data for the {} registries",
+                    protocol,
+                    packets.len()
+                )))
+            }
+            return packets.remove(0).write(buffer)
+        }
+
+        if protocol >= 764 {
+            return self.root.write(buffer)
+        }
+        self.root.write_named("", &mut buffer)?;
+        Ok(buffer)
+    }
+}
@@ -0,0 +1,212 @@
+use sha1::{Digest, Sha1};
+use uuid::Uuid;
+
+use crate::{
+    components::Component,
+    network::{buffer::Buffer, connection::Connection, convenience::read_component, versions::VersionInfo, ByteOrder, Error, PacketState}
+};
+
+// Vanilla clients refuse to download larger packs
+pub const MAX_PACK_SIZE: usize = 250 * 1024 * 1024;
+
+// Also sent in configuration since 1.20.2
+fn packet_ids(state: PacketState, protocol: i32) -> Option<(i32, i32)> {
+    Some(match (state, protocol) {
+        (PacketState::Play, 754) => (0x38, 0x21),
+        (PacketState::Play, 755..=758) => (0x3C, 0x21),
+        (PacketState::Play, 759) => (0x3A, 0x23),
+        (PacketState::Play, 760) => (0x3D, 0x24),
+        (PacketState::Play, 761) => (0x3C, 0x24),
+        (PacketState::Play, 762..=763) => (0x40, 0x24),
+        (PacketState::Play, 764) => (0x42, 0x27),
+        (PacketState::Play, 765) => (0x44, 0x28),
+        (PacketState::Play, 766..=767) => (0x46, 0x2B),
+        (PacketState::Configuration, 764) => (0x06, 0x05),
+        (PacketState::Configuration, 765) => (0x07, 0x05),
+        (PacketState::Configuration, 766..=767) => (0x09, 0x06),
+        _ => return None
+    })
+}
+
+// Downloaded and the statuses after it came with 1.20.3
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourcePackStatus {
+    SuccessfullyLoaded = 0,
+    Declined = 1,
+    FailedDownload = 2,
+    Accepted = 3,
+    Downloaded = 4,
+    InvalidUrl = 5,
+    FailedReload = 6,
+    Discarded = 7
+}
+
+// Packs have an id since 1.20.3, forced and the prompt came with 1.17
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourcePackRequest {
+    pub id: Option<Uuid>,
+    pub url: String,
+    pub hash: String,
+    pub forced: bool,
+    pub prompt: Option<Component>
+}
+
+impl ResourcePackRequest {
+    pub fn read(buffer: &mut Buffer, protocol: i32) -> Result<Self, Error> {
+        let id = match protocol >= 765 {
+            true => Some(Uuid::from_u64_pair(buffer.read_u64()?, buffer.read_u64()?)),
+            false => None
+        };
+        let (url, hash) = (buffer.read_string()?, buffer.read_string()?);
+        if protocol < 755 {
+            return Ok(Self {
+                id,
+                url,
+                hash,
+                forced: false,
+                prompt: None
+            })
+        }
+
+        let forced = buffer.read_bool()?;
+        let prompt = match buffer.read_bool()? {
+            true => Some(read_component(buffer, protocol)?),
+            false => None
+        };
+        Ok(Self { id, url, hash, forced, prompt })
+    }
+
+    pub fn verify(&self, pack: &[u8]) -> bool {
+        self.hash.is_empty() || Sha1::digest(pack).iter().map(|byte| format!("{:02x}", byte)).collect::<String>() == self.hash.to_ascii_lowercase()
+    }
+
+    #[cfg(feature = "web")]
+    pub async fn download(&self) -> Result<Vec<u8>, crate::web::Error> {
+        use crate::web::{Error as WebError, Requester};
+
+        let (status, pack) = Requester::get(self.url.clone()).max_response_bytes(MAX_PACK_SIZE).execute_bytes().await?;
+        if !(200..300).contains(&status) {
+            return Err(WebError::new(format!("Unable to download resource pack => Server responded with {}", status), 40))
+        }
+        if !self.verify(&pack) {
+            return Err(WebError::new(format!("Unable to download resource pack => The SHA-1 doesn't match {}", self.hash), 41))
+        }
+        Ok(pack)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourcePackAction {
+    Accept,
+    Decline,
+    FailDownload
+}
+
+type ResourcePackPrompt = Box<dyn FnMut(&ResourcePackRequest) -> ResourcePackAction + Send>;
+
+#[derive(Default)]
+pub enum ResourcePackPolicy {
+    #[default]
+    AcceptAndSucceed,
+    Decline,
+    Prompt(ResourcePackPrompt)
+}
+
+impl ResourcePackPolicy {
+    pub fn prompt(prompt: impl FnMut(&ResourcePackRequest) -> ResourcePackAction + Send + 'static) -> Self {
+        Self::Prompt(Box::new(prompt))
+    }
+
+    fn decide(&mut self, request: &ResourcePackRequest) -> ResourcePackAction {
+        match self {
+            Self::AcceptAndSucceed => ResourcePackAction::Accept,
+            Self::Decline => ResourcePackAction::Decline,
+            Self::Prompt(prompt) => prompt(request)
+        }
+    }
+}
+
+pub struct ResourcePacks {
+    protocol: i32,
+    policy: ResourcePackPolicy,
+    declined_forced: Option<ResourcePackRequest>
+}
+
+impl ResourcePacks {
+    pub fn new(version: &VersionInfo) -> Self {
+        Self {
+            protocol: version.protocol,
+            policy: ResourcePackPolicy::default(),
+            declined_forced: None
+        }
+    }
+
+    pub fn with_policy(self, policy: ResourcePackPolicy) -> Self {
+        Self { policy, ..self }
+    }
+
+    pub fn protocol(&self) -> i32 {
+        self.protocol
+    }
+
+    pub fn declined_forced(&self) -> Option<&ResourcePackRequest> {
+        self.declined_forced.as_ref()
+    }
+
+    pub fn respond(&mut self, state: PacketState, packet: &mut Buffer) -> Result<Option<Vec<Buffer>>, Error> {
+        let Some((clientbound, serverbound)) = packet_ids(state, self.protocol) else {
+            return Ok(None)
+        };
+
+        let position = packet.position();
+        let request = packet.read_var_i32().and_then(|id| {
+            if id == clientbound {
+                ResourcePackRequest::read(packet, self.protocol).map(Some)
+            } else {
+                Ok(None)
+            }
+        });
+        packet.set_position(position);
+        let Some(request) = request? else { return Ok(None) };
+
+        let action = self.policy.decide(&request);
+        let statuses: &[ResourcePackStatus] = match action {
+            ResourcePackAction::Accept if self.protocol >= 765 => &[ResourcePackStatus::Accepted, ResourcePackStatus::Downloaded, ResourcePackStatus::SuccessfullyLoaded],
+            ResourcePackAction::Accept => &[ResourcePackStatus::Accepted, ResourcePackStatus::SuccessfullyLoaded],
+            ResourcePackAction::Decline => &[ResourcePackStatus::Declined],
+            ResourcePackAction::FailDownload => &[ResourcePackStatus::Accepted, ResourcePackStatus::FailedDownload]
+        };
+        let responses = statuses.iter().map(|status| Self::response(serverbound, request.id, *status)).collect::<Result<Vec<_>, _>>()?;
+        if action != ResourcePackAction::Accept && request.forced {
+            self.declined_forced = Some(request);
+        }
+        Ok(Some(responses))
+    }
+
+    fn response(id: i32, pack: Option<Uuid>, status: ResourcePackStatus) -> Result<Buffer, Error> {
+        let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        buffer.write_var_i32(id)?;
+        if let Some(pack) = pack {
+            let bits = pack.as_u64_pair();
+            buffer.write_u64(bits.0)?;
+            buffer.write_u64(bits.1)?;
+        }
+        buffer.write_var_i32(status as i32)?;
+        Ok(buffer)
+    }
+
+    pub fn handle<'a, S>(&mut self, connection: &mut impl Connection<'a, S>, state: PacketState, packet: &mut Buffer) -> Result<bool, Error> {
+        let Some(responses) = self.respond(state, packet)? else { return Ok(false) };
+        for response in responses {
+            connection.write(response)?;
+        }
+        Ok(true)
+    }
+
+    pub fn explain(&self, error: Error) -> Error {
+        match (error, &self.declined_forced) {
+            (Error::Disconnected(reason), Some(request)) => Error::Disconnected(format!("{} (after the forced resource pack {} wasn't loaded)", reason, request.url)),
+            (error, _) => error
+        }
+    }
+}
@@ -0,0 +1,544 @@
+use std::{
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH}
+};
+
+use thiserror::Error as ThisError;
+
+use crate::network::{
+    buffer::Buffer,
+    chat::{MessageSigner, SignedCommand},
+    commands::CommandTree,
+    connection::{Connection, Writable},
+    tracker::SessionState,
+    versions::VersionInfo,
+    ByteOrder, Error
+};
+
+pub const RELATIVE_X: u8 = 0x01;
+pub const RELATIVE_Y: u8 = 0x02;
+pub const RELATIVE_Z: u8 = 0x04;
+pub const RELATIVE_YAW: u8 = 0x08;
+pub const RELATIVE_PITCH: u8 = 0x10;
+
+// Vanilla servers reject moves of more than 10 blocks per packet
+pub const MAX_MOVE_DISTANCE: f64 = 10.0;
+pub const MOVE_INTERVAL: Duration = Duration::from_millis(50);
+
+const CONFIRM_TELEPORTATION: i32 = 0x00;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SessionPacket {
+    SynchronizePosition,
+    InitializeBorder,
+    BorderCenter,
+    BorderLerpSize,
+    BorderSize,
+    Commands
+}
+
+const PACKETS: [SessionPacket; 6] = [
+    SessionPacket::SynchronizePosition,
+    SessionPacket::InitializeBorder,
+    SessionPacket::BorderCenter,
+    SessionPacket::BorderLerpSize,
+    SessionPacket::BorderSize,
+    SessionPacket::Commands
+];
+
+// 1.16.5 sends every border change as an action of one packet
+fn packet_ids(protocol: i32) -> Option<[i32; 6]> {
+    Some(match protocol {
+        754 => [0x34, 0x3D, 0x3D, 0x3D, 0x3D, 0x10],
+        755..=758 => [0x38, 0x20, 0x42, 0x43, 0x44, 0x12],
+        759 => [0x36, 0x1D, 0x41, 0x42, 0x43, 0x0F],
+        760 => [0x39, 0x1F, 0x44, 0x45, 0x46, 0x0F],
+        761 => [0x38, 0x1E, 0x43, 0x44, 0x45, 0x0E],
+        762..=763 => [0x3C, 0x22, 0x47, 0x48, 0x49, 0x10],
+        764 => [0x3E, 0x23, 0x49, 0x4A, 0x4B, 0x11],
+        765 => [0x3E, 0x23, 0x4B, 0x4C, 0x4D, 0x11],
+        766..=767 => [0x40, 0x25, 0x4D, 0x4E, 0x4F, 0x11],
+        _ => return None
+    })
+}
+
+fn packet_id(packet: SessionPacket, protocol: i32) -> Result<i32, Error> {
+    let ids = packet_ids(protocol).ok_or_else(|| Error::Other(format!("Unable to handle {:?} => Protocol {} isn't supported", packet, protocol)))?;
+    Ok(ids[PACKETS.iter().position(|candidate| *candidate == packet).unwrap()])
+}
+
+fn move_id(protocol: i32) -> Result<i32, Error> {
+    Ok(match protocol {
+        754 => 0x12,
+        755..=758 => 0x11,
+        759 => 0x13,
+        760 => 0x14,
+        761 => 0x13,
+        762..=763 => 0x14,
+        764 => 0x16,
+        765 => 0x17,
+        766..=767 => 0x1A,
+        _ => return Err(Error::Other(format!("Unable to move => Protocol {} isn't supported", protocol)))
+    })
+}
+
+// Chat Command since 1.19, Signed Chat Command comes right after it since 1.20.5
+fn command_id(protocol: i32) -> Result<i32, Error> {
+    Ok(match protocol {
+        754..=759 => 0x03,
+        760..=767 => 0x04,
+        _ => return Err(Error::Other(format!("Unable to send command => Protocol {} isn't supported", protocol)))
+    })
+}
+
+// Vanilla counts 20 ticks of spam per command, takes one off per tick and kicks above 200
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandRateLimit {
+    pub burst: u32,
+    pub interval: Duration
+}
+
+impl Default for CommandRateLimit {
+    fn default() -> Self {
+        Self {
+            burst: 10,
+            interval: Duration::from_secs(1)
+        }
+    }
+}
+
+#[derive(Debug, ThisError)]
+pub enum CommandError {
+    #[error("Unsigned Command => /{0} has message arguments the server wants signed, but no certificates are loaded")]
+    MissingCertificates(String),
+    #[error("Signing Error => {0}")]
+    Signing(Error),
+    #[error("Transport Error => {0}")]
+    Transport(Error)
+}
+
+impl From<Error> for CommandError {
+    fn from(error: Error) -> Self {
+        Self::Transport(error)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayerTeleport {
+    pub position: (f64, f64, f64),
+    pub yaw: f32,
+    pub pitch: f32,
+    pub relative: u8,
+    pub teleport_id: i32,
+    pub dismount: bool
+}
+
+impl PlayerTeleport {
+    // The dismount flag was only sent from 1.17 until 1.19.3
+    pub fn read_versioned(buffer: &mut Buffer, protocol: i32) -> Result<Self, Error> {
+        let position = (f64::from_bits(buffer.read_u64()?), f64::from_bits(buffer.read_u64()?), f64::from_bits(buffer.read_u64()?));
+        let (yaw, pitch) = (f32::from_bits(buffer.read_u32()?), f32::from_bits(buffer.read_u32()?));
+        let relative = buffer.read_u8()?;
+        let teleport_id = buffer.read_var_i32()?;
+        Ok(Self {
+            position,
+            yaw,
+            pitch,
+            relative,
+            teleport_id,
+            dismount: (755..=761).contains(&protocol) && buffer.read_bool()?
+        })
+    }
+
+    pub fn write_versioned(&self, protocol: i32) -> Result<Buffer, Error> {
+        let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        buffer.write_var_i32(packet_id(SessionPacket::SynchronizePosition, protocol)?)?;
+        [self.position.0, self.position.1, self.position.2]
+            .into_iter()
+            .try_for_each(|value| buffer.write_u64(value.to_bits()))?;
+        buffer.write_u32(self.yaw.to_bits())?;
+        buffer.write_u32(self.pitch.to_bits())?;
+        buffer.write_u8(self.relative)?;
+        buffer.write_var_i32(self.teleport_id)?;
+        if (755..=761).contains(&protocol) {
+            buffer.write_bool(self.dismount)?;
+        }
+        Ok(buffer)
+    }
+
+    pub fn apply(&self, (x, y, z): (f64, f64, f64), (yaw, pitch): (f32, f32)) -> ((f64, f64, f64), (f32, f32)) {
+        let relative = |flag: u8, current: f64, value: f64| if self.relative & flag != 0 { current + value } else { value };
+        (
+            (
+                relative(RELATIVE_X, x, self.position.0),
+                relative(RELATIVE_Y, y, self.position.1),
+                relative(RELATIVE_Z, z, self.position.2)
+            ),
+            (
+                relative(RELATIVE_YAW, yaw as f64, self.yaw as f64) as f32,
+                relative(RELATIVE_PITCH, pitch as f64, self.pitch as f64) as f32
+            )
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldBorder {
+    pub center: (f64, f64),
+    pub diameter: f64,
+    pub target_diameter: f64,
+    pub lerp_time: i64,
+    pub portal_teleport_boundary: i32,
+    pub warning_blocks: i32,
+    pub warning_time: i32
+}
+
+impl Default for WorldBorder {
+    fn default() -> Self {
+        Self {
+            center: (0.0, 0.0),
+            diameter: 59_999_968.0,
+            target_diameter: 59_999_968.0,
+            lerp_time: 0,
+            portal_teleport_boundary: 29_999_984,
+            warning_blocks: 5,
+            warning_time: 15
+        }
+    }
+}
+
+impl WorldBorder {
+    pub fn contains(&self, x: f64, z: f64) -> bool {
+        let radius = self.diameter.min(self.target_diameter) / 2.0;
+        (x - self.center.0).abs() <= radius && (z - self.center.1).abs() <= radius
+    }
+
+    fn read_lerp(&mut self, buffer: &mut Buffer) -> Result<(), Error> {
+        self.diameter = f64::from_bits(buffer.read_u64()?);
+        self.target_diameter = f64::from_bits(buffer.read_u64()?);
+        self.lerp_time = buffer.read_var_i64()?;
+        Ok(())
+    }
+
+    fn read_size(&mut self, buffer: &mut Buffer) -> Result<(), Error> {
+        self.diameter = f64::from_bits(buffer.read_u64()?);
+        self.target_diameter = self.diameter;
+        self.lerp_time = 0;
+        Ok(())
+    }
+
+    fn read_center(&mut self, buffer: &mut Buffer) -> Result<(), Error> {
+        self.center = (f64::from_bits(buffer.read_u64()?), f64::from_bits(buffer.read_u64()?));
+        Ok(())
+    }
+
+    // The warning time came before the warning blocks until 1.17 split the packet up
+    fn read_initialize(&mut self, buffer: &mut Buffer, protocol: i32) -> Result<(), Error> {
+        self.read_center(buffer)?;
+        self.read_lerp(buffer)?;
+        self.portal_teleport_boundary = buffer.read_var_i32()?;
+        let (first, second) = (buffer.read_var_i32()?, buffer.read_var_i32()?);
+        (self.warning_blocks, self.warning_time) = if protocol == 754 { (second, first) } else { (first, second) };
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionSnapshot {
+    pub position: Option<(f64, f64, f64)>,
+    pub rotation: (f32, f32)
+}
+
+#[derive(Debug, Clone)]
+pub struct ClientSession {
+    protocol: i32,
+    position: Option<(f64, f64, f64)>,
+    position_hint: Option<(f64, f64, f64)>,
+    rotation: (f32, f32),
+    on_ground: bool,
+    world_border: Option<WorldBorder>,
+    last_move: Option<Instant>,
+    commands: Option<CommandTree>,
+    enforces_secure_chat: bool,
+    command_rate_limit: Option<CommandRateLimit>,
+    command_spam_until: Option<Instant>
+}
+
+impl ClientSession {
+    pub fn new(version: &VersionInfo) -> Self {
+        Self {
+            protocol: version.protocol,
+            position: None,
+            position_hint: None,
+            rotation: (0.0, 0.0),
+            on_ground: false,
+            world_border: None,
+            last_move: None,
+            commands: None,
+            enforces_secure_chat: false,
+            command_rate_limit: Some(CommandRateLimit::default()),
+            command_spam_until: None
+        }
+    }
+
+    pub fn with_command_rate_limit(self, command_rate_limit: Option<CommandRateLimit>) -> Self {
+        Self { command_rate_limit, ..self }
+    }
+
+    pub fn protocol(&self) -> i32 {
+        self.protocol
+    }
+
+    pub fn position(&self) -> Option<(f64, f64, f64)> {
+        self.position
+    }
+
+    pub fn position_hint(&self) -> Option<(f64, f64, f64)> {
+        self.position_hint
+    }
+
+    pub fn rotation(&self) -> (f32, f32) {
+        self.rotation
+    }
+
+    pub fn on_ground(&self) -> bool {
+        self.on_ground
+    }
+
+    pub fn set_on_ground(&mut self, on_ground: bool) {
+        self.on_ground = on_ground;
+    }
+
+    pub fn world_border(&self) -> Option<&WorldBorder> {
+        self.world_border.as_ref()
+    }
+
+    pub fn commands(&self) -> Option<&CommandTree> {
+        self.commands.as_ref()
+    }
+
+    pub fn enforces_secure_chat(&self) -> bool {
+        self.enforces_secure_chat
+    }
+
+    // Announced in Login (play) since 1.20.5 and in Server Data before
+    pub fn set_enforces_secure_chat(&mut self, enforces_secure_chat: bool) {
+        self.enforces_secure_chat = enforces_secure_chat;
+    }
+
+    fn border(&mut self) -> &mut WorldBorder {
+        self.world_border.get_or_insert_with(WorldBorder::default)
+    }
+
+    pub fn handle<'a, S>(&mut self, connection: &mut impl Connection<'a, S>, packet: &mut Buffer) -> Result<bool, Error> {
+        let position = packet.position();
+        let handled = self.read(connection, packet);
+        packet.set_position(position);
+        handled
+    }
+
+    fn read<'a, S>(&mut self, connection: &mut impl Connection<'a, S>, packet: &mut Buffer) -> Result<bool, Error> {
+        let ids = packet_ids(self.protocol).ok_or_else(|| Error::Other(format!("Unable to handle session packets => Protocol {} isn't supported", self.protocol)))?;
+        let id = packet.read_var_i32()?;
+        let session_packet = match PACKETS.iter().zip(ids).find(|(_, packet_id)| *packet_id == id) {
+            Some((session_packet, _)) => *session_packet,
+            None => return Ok(false)
+        };
+
+        let protocol = self.protocol;
+        match session_packet {
+            SessionPacket::SynchronizePosition => {
+                let teleport = PlayerTeleport::read_versioned(packet, protocol)?;
+                self.teleport(connection, &teleport)?;
+            }
+            SessionPacket::Commands => self.commands = Some(CommandTree::read_versioned(packet, protocol)?),
+            _ if protocol == 754 => {
+                let offset = packet.position();
+                match packet.read_var_i32()? {
+                    0 => self.border().read_size(packet)?,
+                    1 => self.border().read_lerp(packet)?,
+                    2 => self.border().read_center(packet)?,
+                    3 => self.border().read_initialize(packet, protocol)?,
+                    4 => self.border().warning_time = packet.read_var_i32()?,
+                    5 => self.border().warning_blocks = packet.read_var_i32()?,
+                    action => {
+                        return Err(Error::Decode {
+                            offset,
+                            reason: format!("Invalid world border action {}", action)
+                        })
+                    }
+                }
+            }
+            SessionPacket::InitializeBorder => self.border().read_initialize(packet, protocol)?,
+            SessionPacket::BorderCenter => self.border().read_center(packet)?,
+            SessionPacket::BorderLerpSize => self.border().read_lerp(packet)?,
+            SessionPacket::BorderSize => self.border().read_size(packet)?
+        }
+        Ok(true)
+    }
+
+    pub fn teleport<'a, S>(&mut self, connection: &mut impl Connection<'a, S>, teleport: &PlayerTeleport) -> Result<i32, Error> {
+        let (position, rotation) = teleport.apply(self.position.unwrap_or_default(), self.rotation);
+        self.position = Some(position);
+        self.position_hint = None;
+        self.rotation = rotation;
+
+        let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        buffer.write_var_i32(CONFIRM_TELEPORTATION)?;
+        buffer.write_var_i32(teleport.teleport_id)?;
+        connection.write(buffer)?;
+
+        let mut buffer = self.move_packet(move_id(self.protocol)? + 1, position)?;
+        buffer.write_u32(rotation.0.to_bits())?;
+        buffer.write_u32(rotation.1.to_bits())?;
+        buffer.write_bool(self.on_ground)?;
+        connection.write(buffer)?;
+        self.last_move = Some(Instant::now());
+        Ok(teleport.teleport_id)
+    }
+
+    fn move_packet(&self, id: i32, (x, y, z): (f64, f64, f64)) -> Result<Buffer, Error> {
+        let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        buffer.write_var_i32(id)?;
+        [x, y, z].into_iter().try_for_each(|value| buffer.write_u64(value.to_bits()))?;
+        Ok(buffer)
+    }
+
+    pub fn move_to<'a, S>(&mut self, connection: &mut impl Connection<'a, S>, x: f64, y: f64, z: f64) -> Result<(), Error> {
+        let start = self.position.ok_or_else(|| Error::Other("Unable to move => The server didn't send a position yet".to_string()))?;
+        let distance = ((x - start.0).powi(2) + (y - start.1).powi(2) + (z - start.2).powi(2)).sqrt();
+        let steps = (distance / MAX_MOVE_DISTANCE).ceil().max(1.0) as u32;
+
+        for step in 1..=steps {
+            let progress = step as f64 / steps as f64;
+            let position = match step {
+                step if step == steps => (x, y, z),
+                _ => (start.0 + (x - start.0) * progress, start.1 + (y - start.1) * progress, start.2 + (z - start.2) * progress)
+            };
+
+            if let Some(elapsed) = self.last_move.map(|last_move| last_move.elapsed()).filter(|elapsed| *elapsed < MOVE_INTERVAL) {
+                thread::sleep(MOVE_INTERVAL - elapsed);
+            }
+            let mut buffer = self.move_packet(move_id(self.protocol)?, position)?;
+            buffer.write_bool(self.on_ground)?;
+            connection.write(buffer)?;
+            self.last_move = Some(Instant::now());
+            self.position = Some(position);
+        }
+        Ok(())
+    }
+
+    // 1.19 and 1.19.2 used a signature scheme of their own, commands are always sent unsigned there
+    pub fn send_command<'a, S>(&mut self, connection: &mut impl Connection<'a, S>, command: &str, signer: Option<&mut MessageSigner>) -> Result<(), CommandError> {
+        let command = command.strip_prefix('/').unwrap_or(command);
+        let arguments = match (self.protocol, &self.commands) {
+            (761.., Some(commands)) => commands.signable_arguments(command),
+            _ => Vec::new()
+        };
+        let signed = match signer {
+            Some(signer) if !arguments.is_empty() => Some(signer.sign_command(command, &arguments).map_err(CommandError::Signing)?),
+            None if !arguments.is_empty() && self.enforces_secure_chat => return Err(CommandError::MissingCertificates(command.to_string())),
+            _ => None
+        };
+
+        let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        match (self.protocol, signed) {
+            (protocol, Some(signed)) => {
+                buffer.write_var_i32(command_id(protocol)? + i32::from(protocol >= 766))?;
+                buffer = signed.write(buffer)?;
+            }
+            (754..=758, None) => {
+                buffer.write_var_i32(command_id(self.protocol)?)?;
+                buffer.write_str(&format!("/{}", command))?;
+            }
+            (protocol @ 759..=760, None) => {
+                buffer.write_var_i32(command_id(protocol)?)?;
+                buffer.write_str(command)?;
+                buffer.write_i64(timestamp()?)?;
+                buffer.write_i64(0)?;
+                buffer.write_var_i32(0)?;
+                buffer.write_bool(false)?;
+                // No last seen messages and no last received message
+                if protocol == 760 {
+                    buffer.write_var_i32(0)?;
+                    buffer.write_bool(false)?;
+                }
+            }
+            (protocol @ 761..=765, None) => {
+                let unsigned = SignedCommand {
+                    command: command.to_string(),
+                    timestamp: timestamp()?,
+                    salt: 0,
+                    signatures: Vec::new(),
+                    offset: 0,
+                    acknowledged: [0; 3]
+                };
+                buffer.write_var_i32(command_id(protocol)?)?;
+                buffer = unsigned.write(buffer)?;
+            }
+            (protocol, None) => {
+                buffer.write_var_i32(command_id(protocol)?)?;
+                buffer.write_str(command)?;
+            }
+        }
+
+        self.wait_for_command();
+        connection.write(buffer)?;
+        Ok(())
+    }
+
+    fn wait_for_command(&mut self) {
+        let Some(limit) = self.command_rate_limit else { return };
+        let now = Instant::now();
+        let spam_until = self.command_spam_until.filter(|spam_until| *spam_until > now).unwrap_or(now) + limit.interval;
+        let wait = (spam_until - now).checked_sub(limit.interval * limit.burst);
+        if let Some(wait) = wait {
+            thread::sleep(wait);
+        }
+        self.command_spam_until = Some(spam_until);
+
+        #[cfg(feature = "metrics")]
+        {
+            let tokens = (spam_until - now).as_nanos().div_ceil(limit.interval.as_nanos().max(1)).min(limit.burst as u128) as u32;
+            crate::metrics::record_command_rate_limit(tokens, wait.unwrap_or_default());
+        }
+    }
+}
+
+fn timestamp() -> Result<i64, Error> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map_err(|error| Error::Other(error.to_string()))?;
+    Ok(timestamp.as_millis() as i64)
+}
+
+impl SessionState for ClientSession {
+    type Snapshot = SessionSnapshot;
+
+    fn reset_for_reconnect(&mut self) {
+        self.position = None;
+        self.position_hint = None;
+        self.rotation = (0.0, 0.0);
+        self.on_ground = false;
+        self.world_border = None;
+        self.last_move = None;
+        self.commands = None;
+        self.enforces_secure_chat = false;
+        self.command_spam_until = None;
+    }
+
+    fn snapshot(&self) -> SessionSnapshot {
+        SessionSnapshot {
+            position: self.position.or(self.position_hint),
+            rotation: self.rotation
+        }
+    }
+
+    // The server teleports the player on join, a known position wins over the hint
+    fn restore(&mut self, snapshot: SessionSnapshot) {
+        if self.position.is_some() {
+            return
+        }
+
+        self.position_hint = snapshot.position;
+        self.rotation = snapshot.rotation;
+    }
+}
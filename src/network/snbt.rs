@@ -0,0 +1,421 @@
+use thiserror::Error as ThisError;
+
+use crate::network::{
+    nbt::{Compound, Tag, MAXIMUM_DEPTH},
+    Error
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, ThisError)]
+#[error("SNBT Error => {reason} at position {position}")]
+pub struct SnbtError {
+    pub position: usize,
+    pub reason: String
+}
+
+impl From<SnbtError> for Error {
+    fn from(error: SnbtError) -> Self {
+        Error::Decode {
+            offset: error.position,
+            reason: error.reason
+        }
+    }
+}
+
+impl Tag {
+    pub fn from_snbt(snbt: &str) -> Result<Self, SnbtError> {
+        let mut parser = Parser { input: snbt, position: 0 };
+        let tag = parser.read_value(0)?;
+        parser.skip_whitespace();
+        if parser.position < snbt.len() {
+            return Err(parser.error("Trailing data found"))
+        }
+        Ok(tag)
+    }
+
+    pub fn to_snbt(&self) -> String {
+        let mut snbt = String::new();
+        self.write_snbt(&mut snbt);
+        snbt
+    }
+
+    fn write_snbt(&self, snbt: &mut String) {
+        match self {
+            Tag::Byte(value) => snbt.push_str(&format!("{}b", value)),
+            Tag::Short(value) => snbt.push_str(&format!("{}s", value)),
+            Tag::Int(value) => snbt.push_str(&value.to_string()),
+            Tag::Long(value) => snbt.push_str(&format!("{}L", value)),
+            Tag::Float(value) => snbt.push_str(&format!("{}f", value)),
+            Tag::Double(value) => snbt.push_str(&format!("{}d", value)),
+            Tag::ByteArray(values) => write_array(snbt, 'B', values.iter().map(|value| format!("{}B", *value as i8))),
+            Tag::String(value) => quote(snbt, value),
+            Tag::List(list) => {
+                snbt.push('[');
+                for (index, tag) in list.iter().enumerate() {
+                    if index > 0 {
+                        snbt.push(',');
+                    }
+                    tag.write_snbt(snbt);
+                }
+                snbt.push(']');
+            }
+            Tag::Compound(compound) => compound.write_snbt(snbt),
+            Tag::IntArray(values) => write_array(snbt, 'I', values.iter().map(i32::to_string)),
+            Tag::LongArray(values) => write_array(snbt, 'L', values.iter().map(|value| format!("{}L", value)))
+        }
+    }
+}
+
+impl Compound {
+    pub fn from_snbt(snbt: &str) -> Result<Self, SnbtError> {
+        match Tag::from_snbt(snbt)? {
+            Tag::Compound(compound) => Ok(compound),
+            _ => {
+                Err(SnbtError {
+                    position: 0,
+                    reason: "Expected a compound".to_string()
+                })
+            }
+        }
+    }
+
+    pub fn to_snbt(&self) -> String {
+        let mut snbt = String::new();
+        self.write_snbt(&mut snbt);
+        snbt
+    }
+
+    fn write_snbt(&self, snbt: &mut String) {
+        snbt.push('{');
+        for (index, (name, tag)) in self.iter().enumerate() {
+            if index > 0 {
+                snbt.push(',');
+            }
+
+            match name.chars().all(is_unquoted) && !name.is_empty() {
+                true => snbt.push_str(name),
+                false => quote(snbt, name)
+            }
+            snbt.push(':');
+            tag.write_snbt(snbt);
+        }
+        snbt.push('}');
+    }
+}
+
+fn write_array(snbt: &mut String, kind: char, values: impl Iterator<Item = String>) {
+    snbt.push_str(&format!("[{};{}]", kind, values.collect::<Vec<_>>().join(",")));
+}
+
+// Like vanilla, the quote that doesn't appear first is used
+fn quote(snbt: &mut String, value: &str) {
+    let quote = match value.chars().find(|character| *character == '"' || *character == '\'') {
+        Some('"') => '\'',
+        _ => '"'
+    };
+
+    snbt.push(quote);
+    for character in value.chars() {
+        if character == quote || character == '\\' {
+            snbt.push('\\');
+        }
+        snbt.push(character);
+    }
+    snbt.push(quote);
+}
+
+fn is_unquoted(character: char) -> bool {
+    matches!(character, '0'..='9' | 'A'..='Z' | 'a'..='z' | '_' | '-' | '.' | '+')
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, reason: impl Into<String>) -> SnbtError {
+        SnbtError {
+            position: self.position,
+            reason: reason.into()
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.input[self.position..].chars().nth(offset)
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let character = self.peek()?;
+        self.position += character.len_utf8();
+        Some(character)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), SnbtError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(character) if character == expected => {
+                self.next();
+                Ok(())
+            }
+            _ => Err(self.error(format!("Expected '{}'", expected)))
+        }
+    }
+
+    // Trailing separators are accepted like vanilla does
+    fn separator(&mut self, end: char) -> Result<bool, SnbtError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(',') => {
+                self.next();
+                self.skip_whitespace();
+                Ok(self.peek() != Some(end))
+            }
+            Some(character) if character == end => Ok(false),
+            _ => Err(self.error(format!("Expected ',' or '{}'", end)))
+        }
+    }
+
+    fn read_value(&mut self, depth: usize) -> Result<Tag, SnbtError> {
+        if depth > MAXIMUM_DEPTH {
+            return Err(self.error(format!("SNBT is nested deeper than {} levels", MAXIMUM_DEPTH)))
+        }
+
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => Ok(Tag::Compound(self.read_compound(depth + 1)?)),
+            Some('[') if self.peek_at(2) == Some(';') && !matches!(self.peek_at(1), Some('"' | '\'')) => self.read_array(),
+            Some('[') => self.read_list(depth + 1),
+            _ => self.read_primitive()
+        }
+    }
+
+    fn read_compound(&mut self, depth: usize) -> Result<Compound, SnbtError> {
+        self.expect('{')?;
+        let mut compound = Compound::new();
+        self.skip_whitespace();
+        while self.peek() != Some('}') {
+            let position = self.position;
+            let quoted = matches!(self.peek(), Some('"' | '\''));
+            let key = self.read_string()?;
+            if key.is_empty() && !quoted {
+                return Err(SnbtError {
+                    position,
+                    reason: "Expected key".to_string()
+                })
+            }
+
+            self.expect(':')?;
+            compound.insert(key, self.read_value(depth)?);
+            if !self.separator('}')? {
+                break;
+            }
+        }
+        self.expect('}')?;
+        Ok(compound)
+    }
+
+    fn read_list(&mut self, depth: usize) -> Result<Tag, SnbtError> {
+        self.expect('[')?;
+        let mut list: Vec<Tag> = Vec::new();
+        self.skip_whitespace();
+        while self.peek() != Some(']') {
+            let position = self.position;
+            let tag = self.read_value(depth)?;
+            if let Some(first) = list.first().filter(|first| first.id() != tag.id()) {
+                return Err(SnbtError {
+                    position,
+                    reason: format!("Can't insert tag type {} into a list of tag type {}", tag.id(), first.id())
+                })
+            }
+
+            list.push(tag);
+            if !self.separator(']')? {
+                break;
+            }
+        }
+        self.expect(']')?;
+        Ok(Tag::List(list))
+    }
+
+    fn read_array(&mut self) -> Result<Tag, SnbtError> {
+        self.expect('[')?;
+        let position = self.position;
+        let kind = self.next();
+        self.next();
+
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        while self.peek() != Some(']') {
+            let position = self.position;
+            let tag = self.read_primitive()?;
+            values.push((position, tag));
+            if !self.separator(']')? {
+                break;
+            }
+        }
+        self.expect(']')?;
+
+        let mismatch = |(position, tag): &(usize, Tag), kind: char| {
+            SnbtError {
+                position: *position,
+                reason: format!("Can't insert tag type {} into a {} array", tag.id(), kind)
+            }
+        };
+
+        match kind {
+            Some('B') => {
+                values
+                    .iter()
+                    .map(|value| {
+                        match value.1 {
+                            Tag::Byte(byte) => Ok(byte as u8),
+                            _ => Err(mismatch(value, 'B'))
+                        }
+                    })
+                    .collect::<Result<_, _>>()
+                    .map(Tag::ByteArray)
+            }
+            Some('I') => {
+                values
+                    .iter()
+                    .map(|value| {
+                        match value.1 {
+                            Tag::Int(int) => Ok(int),
+                            _ => Err(mismatch(value, 'I'))
+                        }
+                    })
+                    .collect::<Result<_, _>>()
+                    .map(Tag::IntArray)
+            }
+            Some('L') => {
+                values
+                    .iter()
+                    .map(|value| {
+                        match value.1 {
+                            Tag::Long(long) => Ok(long),
+                            _ => Err(mismatch(value, 'L'))
+                        }
+                    })
+                    .collect::<Result<_, _>>()
+                    .map(Tag::LongArray)
+            }
+            kind => {
+                Err(SnbtError {
+                    position,
+                    reason: format!("Invalid array type {:?}", kind.unwrap_or_default())
+                })
+            }
+        }
+    }
+
+    fn read_primitive(&mut self) -> Result<Tag, SnbtError> {
+        self.skip_whitespace();
+        if matches!(self.peek(), Some('"' | '\'')) {
+            return Ok(Tag::String(self.read_quoted()?))
+        }
+
+        let token = self.read_unquoted();
+        if token.is_empty() {
+            return Err(self.error("Expected value"))
+        }
+        Ok(infer(token))
+    }
+
+    fn read_string(&mut self) -> Result<String, SnbtError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('"' | '\'') => self.read_quoted(),
+            _ => Ok(self.read_unquoted().to_string())
+        }
+    }
+
+    fn read_unquoted(&mut self) -> &'a str {
+        let start = self.position;
+        while self.peek().is_some_and(is_unquoted) {
+            self.next();
+        }
+        &self.input[start..self.position]
+    }
+
+    fn read_quoted(&mut self) -> Result<String, SnbtError> {
+        let quote = self.next();
+        let mut string = String::new();
+        loop {
+            match self.next() {
+                Some('\\') => {
+                    let position = self.position;
+                    match self.next() {
+                        Some(character) if character == '\\' || Some(character) == quote => string.push(character),
+                        _ => {
+                            return Err(SnbtError {
+                                position,
+                                reason: "Invalid escape sequence".to_string()
+                            })
+                        }
+                    }
+                }
+                Some(character) if Some(character) == quote => return Ok(string),
+                Some(character) => string.push(character),
+                None => return Err(self.error("Unclosed quoted string"))
+            }
+        }
+    }
+}
+
+fn is_integer(token: &str) -> bool {
+    let digits = token.strip_prefix(['+', '-']).unwrap_or(token);
+    digits == "0" || (digits.starts_with(|character: char| matches!(character, '1'..='9')) && digits.chars().all(|character| character.is_ascii_digit()))
+}
+
+fn is_decimal(token: &str, dot_required: bool) -> bool {
+    let token = token.strip_prefix(['+', '-']).unwrap_or(token);
+    let (mantissa, exponent) = match token.find(['e', 'E']) {
+        Some(index) => (&token[..index], Some(&token[index + 1..])),
+        None => (token, None)
+    };
+
+    let mantissa = match mantissa.split_once('.') {
+        Some((integer, fraction)) => integer.chars().chain(fraction.chars()).all(|character| character.is_ascii_digit()) && !(integer.is_empty() && fraction.is_empty()),
+        None => !dot_required && !mantissa.is_empty() && mantissa.chars().all(|character| character.is_ascii_digit())
+    };
+
+    let exponent = exponent.is_none_or(|exponent| {
+        let digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        !digits.is_empty() && digits.chars().all(|character| character.is_ascii_digit())
+    });
+    mantissa && exponent
+}
+
+// Anything that doesn't parse as a number stays a string, overflowing numbers included
+fn infer(token: &str) -> Tag {
+    if token.eq_ignore_ascii_case("true") {
+        return Tag::Byte(1)
+    }
+
+    if token.eq_ignore_ascii_case("false") {
+        return Tag::Byte(0)
+    }
+
+    let (body, suffix) = token.split_at(token.len() - 1);
+    let tag = match suffix.to_ascii_lowercase().as_str() {
+        "b" if is_integer(body) => body.parse().ok().map(Tag::Byte),
+        "s" if is_integer(body) => body.parse().ok().map(Tag::Short),
+        "l" if is_integer(body) => body.parse().ok().map(Tag::Long),
+        "f" if is_decimal(body, false) => body.parse().ok().map(Tag::Float),
+        "d" if is_decimal(body, false) => body.parse().ok().map(Tag::Double),
+        _ if is_integer(token) => token.parse().ok().map(Tag::Int),
+        _ if is_decimal(token, true) => token.parse().ok().map(Tag::Double),
+        _ => None
+    };
+    tag.unwrap_or_else(|| Tag::String(token.to_string()))
+}
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use crate::network::{
+    buffer::Buffer,
+    connection::{Readable, Writable},
+    decode::UnknownVariant,
+    Error
+};
+#[cfg(feature = "std-net")]
+use crate::network::{connection::Connection, correlation::ResponseCorrelator, versions::VersionInfo, ByteOrder, PacketState};
+
+// The action of Client Command that asks for the statistics, 0 respawns instead
+pub const REQUEST_STATISTICS_ACTION: i32 = 1;
+
+#[cfg(feature = "std-net")]
+fn packet_ids(protocol: i32) -> Option<(i32, i32)> {
+    Some(match protocol {
+        754 => (0x04, 0x06),
+        755..=758 => (0x04, 0x07),
+        759 => (0x06, 0x04),
+        760 => (0x07, 0x04),
+        761 => (0x06, 0x04),
+        762..=763 => (0x07, 0x05),
+        764..=765 => (0x08, 0x04),
+        766..=767 => (0x09, 0x04),
+        _ => return None
+    })
+}
+
+// The stat types kept their order since 1.13
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatisticCategory {
+    Mined,
+    Crafted,
+    Used,
+    Broken,
+    PickedUp,
+    Dropped,
+    Killed,
+    KilledBy,
+    Custom,
+    Unknown(i32)
+}
+
+impl UnknownVariant for StatisticCategory {
+    fn is_unknown(&self) -> bool {
+        matches!(self, Self::Unknown(_))
+    }
+}
+
+impl StatisticCategory {
+    pub fn from_id(id: i32) -> Self {
+        match id {
+            0 => Self::Mined,
+            1 => Self::Crafted,
+            2 => Self::Used,
+            3 => Self::Broken,
+            4 => Self::PickedUp,
+            5 => Self::Dropped,
+            6 => Self::Killed,
+            7 => Self::KilledBy,
+            8 => Self::Custom,
+            id => Self::Unknown(id)
+        }
+    }
+
+    pub fn id(&self) -> i32 {
+        match self {
+            Self::Mined => 0,
+            Self::Crafted => 1,
+            Self::Used => 2,
+            Self::Broken => 3,
+            Self::PickedUp => 4,
+            Self::Dropped => 5,
+            Self::Killed => 6,
+            Self::KilledBy => 7,
+            Self::Custom => 8,
+            Self::Unknown(id) => *id
+        }
+    }
+
+    pub fn registry(&self) -> Option<&'static str> {
+        match self {
+            Self::Mined => Some("minecraft:block"),
+            Self::Crafted | Self::Used | Self::Broken | Self::PickedUp | Self::Dropped => Some("minecraft:item"),
+            Self::Killed | Self::KilledBy => Some("minecraft:entity_type"),
+            Self::Custom => Some("minecraft:custom_stat"),
+            Self::Unknown(_) => None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Statistics {
+    values: HashMap<(StatisticCategory, i32), i32>
+}
+
+impl Statistics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn get(&self, category: StatisticCategory, statistic: i32) -> Option<i32> {
+        self.values.get(&(category, statistic)).copied()
+    }
+
+    pub fn insert(&mut self, category: StatisticCategory, statistic: i32, value: i32) {
+        self.values.insert((category, statistic), value);
+    }
+
+    pub fn category(&self, category: StatisticCategory) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.values
+            .iter()
+            .filter(move |((candidate, _), _)| *candidate == category)
+            .map(|((_, statistic), value)| (*statistic, *value))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (StatisticCategory, i32, i32)> + '_ {
+        self.values.iter().map(|((category, statistic), value)| (*category, *statistic, *value))
+    }
+}
+
+impl Writable for Statistics {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        buffer.write_var_i32(self.values.len() as i32)?;
+        for (category, statistic, value) in self.iter() {
+            buffer.write_var_i32(category.id())?;
+            buffer.write_var_i32(statistic)?;
+            buffer.write_var_i32(value)?;
+        }
+        Ok(buffer)
+    }
+}
+
+impl Readable for Statistics {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        let mut statistics = Self::new();
+        for _ in 0..buffer.read_length()? {
+            let category = StatisticCategory::from_id(buffer.read_var_i32()?);
+            statistics.insert(category, buffer.read_var_i32()?, buffer.read_var_i32()?);
+        }
+        Ok(statistics)
+    }
+}
+
+#[cfg(feature = "std-net")]
+pub fn request_statistics<'a, S, C: Connection<'a, S>>(
+    connection: &mut C,
+    version: &VersionInfo,
+    mut unrelated: impl FnMut(&mut C, Buffer) -> Result<(), Error>
+) -> Result<Statistics, Error> {
+    let (client_command, award_statistics) =
+        packet_ids(version.protocol).ok_or_else(|| Error::Other(format!("Unable to request statistics => Protocol {} isn't supported", version.protocol)))?;
+
+    let mut correlator = ResponseCorrelator::new();
+    let mut response = correlator.expect(PacketState::Play, award_statistics);
+
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(client_command)?;
+    buffer.write_var_i32(REQUEST_STATISTICS_ACTION)?;
+    connection.write(buffer)?;
+
+    loop {
+        if let Some(packet) = correlator.offer_read(PacketState::Play, connection.read_packet())? {
+            unrelated(connection, packet)?;
+            continue;
+        }
+
+        let mut packet = response.try_recv().map_err(|error| Error::Other(error.to_string()))?;
+        packet.read_var_i32()?;
+        let statistics = Statistics::read(&mut packet)?;
+
+        let (mode, report) = (connection.decode_mode(), connection.decode_report());
+        for (category, _, _) in statistics.iter() {
+            mode.check_variant(category, &packet, PacketState::Play, award_statistics, report)?;
+        }
+        mode.check_trailing(&mut packet, PacketState::Play, award_statistics, report)?;
+        return Ok(statistics)
+    }
+}
@@ -0,0 +1,130 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use uuid::Uuid;
+
+use crate::{
+    components::Component,
+    network::{
+        status::{PlayerSample, StatusPlayers, StatusResponse, StatusVersion, DEFAULT_SAMPLE_LIMIT},
+        versions::VersionInfo,
+        Error
+    }
+};
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+const FAVICON_SIZE: u32 = 64;
+
+#[derive(Debug, Clone)]
+pub struct StatusResponseBuilder {
+    version: StatusVersion,
+    players: StatusPlayers,
+    sample_limit: usize,
+    description: Component,
+    favicon: Option<Vec<u8>>,
+    enforces_secure_chat: bool
+}
+
+impl Default for StatusResponseBuilder {
+    fn default() -> Self {
+        let version = VersionInfo::latest();
+        Self {
+            version: StatusVersion {
+                name: version.name.to_string(),
+                protocol: version.protocol
+            },
+            players: StatusPlayers {
+                max: 20,
+                online: 0,
+                sample: Vec::new()
+            },
+            sample_limit: DEFAULT_SAMPLE_LIMIT,
+            description: Component::default(),
+            favicon: None,
+            enforces_secure_chat: false
+        }
+    }
+}
+
+impl StatusResponseBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn version(self, name: impl Into<String>, protocol: i32) -> Self {
+        Self {
+            version: StatusVersion { name: name.into(), protocol },
+            ..self
+        }
+    }
+
+    pub fn players(self, online: i32, max: i32) -> Self {
+        Self {
+            players: StatusPlayers { online, max, ..self.players },
+            ..self
+        }
+    }
+
+    pub fn sample(mut self, players: &[(&str, Uuid)]) -> Self {
+        self.players.sample = players.iter().map(|(name, id)| PlayerSample::new(*name, *id)).collect();
+        self
+    }
+
+    pub fn sample_limit(self, sample_limit: usize) -> Self {
+        Self { sample_limit, ..self }
+    }
+
+    pub fn description(self, description: impl Into<Component>) -> Self {
+        Self {
+            description: description.into(),
+            ..self
+        }
+    }
+
+    pub fn favicon_from_png(self, png: &[u8]) -> Self {
+        Self {
+            favicon: Some(png.to_vec()),
+            ..self
+        }
+    }
+
+    pub fn enforces_secure_chat(self, enforces_secure_chat: bool) -> Self {
+        Self { enforces_secure_chat, ..self }
+    }
+
+    pub fn build(mut self) -> Result<StatusResponse, Error> {
+        let favicon = match &self.favicon {
+            Some(png) => {
+                let (width, height) = png_dimensions(png)?;
+                if width != FAVICON_SIZE || height != FAVICON_SIZE {
+                    return Err(Error::Other(format!("Favicon must be {0}x{0} pixels, but is {1}x{2}", FAVICON_SIZE, width, height)))
+                }
+                Some(format!("data:image/png;base64,{}", STANDARD.encode(png)))
+            }
+            None => None
+        };
+
+        self.players.sample.truncate(self.sample_limit);
+        Ok(StatusResponse {
+            version: self.version,
+            players: Some(self.players),
+            description: Some(serde_json::to_value(&self.description).map_err(|error| Error::Other(format!("Unable to serialize description => {}", error)))?),
+            favicon,
+            enforces_secure_chat: self.enforces_secure_chat,
+            modinfo: None,
+            forge_data: None,
+            latency: None
+        })
+    }
+
+    pub fn build_json(self) -> Result<String, Error> {
+        self.build()?.to_json()
+    }
+}
+
+// The IHDR chunk always comes first
+fn png_dimensions(png: &[u8]) -> Result<(u32, u32), Error> {
+    if png.len() < 24 || png[..8] != PNG_SIGNATURE || &png[12..16] != b"IHDR" {
+        return Err(Error::Other("Favicon isn't a valid PNG image".to_string()))
+    }
+
+    Ok((u32::from_be_bytes([png[16], png[17], png[18], png[19]]), u32::from_be_bytes([png[20], png[21], png[22], png[23]])))
+}
@@ -0,0 +1,81 @@
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream
+};
+
+use crate::network::Error;
+
+const PING_PROTOCOL: u8 = 74;
+const PING_CHANNEL: &str = "MC|PingHost";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegacyStatus {
+    pub protocol: Option<i32>,
+    pub version: Option<String>,
+    pub motd: String,
+    pub online_players: i32,
+    pub max_players: i32
+}
+
+impl LegacyStatus {
+    pub fn parse(response: &str) -> Result<Self, Error> {
+        let invalid = || Error::Other(format!("Invalid legacy ping response {:?}", response));
+        if let Some(response) = response.strip_prefix("\u{a7}1\0") {
+            let fields = response.split('\0').collect::<Vec<_>>();
+            if fields.len() != 5 {
+                return Err(invalid())
+            }
+
+            return Ok(Self {
+                protocol: Some(fields[0].parse().map_err(|_| invalid())?),
+                version: Some(fields[1].to_string()),
+                motd: fields[2].to_string(),
+                online_players: fields[3].parse().map_err(|_| invalid())?,
+                max_players: fields[4].parse().map_err(|_| invalid())?
+            })
+        }
+
+        let mut fields = response.rsplitn(3, '\u{a7}');
+        let max_players = fields.next().and_then(|field| field.parse().ok()).ok_or_else(invalid)?;
+        let online_players = fields.next().and_then(|field| field.parse().ok()).ok_or_else(invalid)?;
+        Ok(Self {
+            protocol: None,
+            version: None,
+            motd: fields.next().ok_or_else(invalid)?.to_string(),
+            online_players,
+            max_players
+        })
+    }
+}
+
+pub async fn legacy_ping(host: &str, port: u16) -> Result<LegacyStatus, Error> {
+    let mut request = vec![0xFE, 0x01, 0xFA];
+    write_utf16(&mut request, PING_CHANNEL);
+    request.extend_from_slice(&((7 + host.encode_utf16().count() * 2) as u16).to_be_bytes());
+    request.push(PING_PROTOCOL);
+    write_utf16(&mut request, host);
+    request.extend_from_slice(&(port as i32).to_be_bytes());
+
+    let mut socket = TcpStream::connect((host, port)).await?;
+    socket.write_all(&request).await?;
+    socket.flush().await?;
+
+    let id = socket.read_u8().await?;
+    if id != 0xFF {
+        return Err(Error::IllegalPacket(id as i32, "Legacy".to_string()))
+    }
+
+    let length = socket.read_u16().await? as usize;
+    let mut bytes = vec![0; length * 2];
+    socket.read_exact(&mut bytes).await?;
+
+    let characters = bytes.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect::<Vec<_>>();
+    LegacyStatus::parse(&String::from_utf16(&characters).map_err(|_| Error::Other("Legacy ping response isn't valid UTF-16".to_string()))?)
+}
+
+fn write_utf16(bytes: &mut Vec<u8>, string: &str) {
+    bytes.extend_from_slice(&(string.encode_utf16().count() as u16).to_be_bytes());
+    for character in string.encode_utf16() {
+        bytes.extend_from_slice(&character.to_be_bytes());
+    }
+}
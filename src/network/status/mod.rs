@@ -0,0 +1,503 @@
+#[cfg(feature = "std-net")]
+use std::io;
+use std::{fmt::Formatter, net::IpAddr, time::Duration};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+#[cfg(feature = "std-net")]
+use hickory_resolver::{error::ResolveErrorKind, TokioAsyncResolver};
+#[cfg(feature = "std-net")]
+use rand::random;
+use serde::{
+    de::{DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize
+};
+use serde_json::Value;
+#[cfg(feature = "std-net")]
+use tokio::{
+    net::TcpStream,
+    time::{timeout_at, Instant}
+};
+use uuid::Uuid;
+
+#[cfg(feature = "std-net")]
+use crate::network::{
+    buffer::Buffer,
+    connection::{async_socket::AsyncSocketConnection, pipeline::framing::FrameEncoder, Client, Pipeline},
+    handshake::{Handshake, NextState},
+    versions::VersionSelection,
+    ByteOrder, PacketState
+};
+use crate::network::{forge::ForgeStatus, versions::VersionInfo, Error};
+
+mod builder;
+#[cfg(feature = "std-net")]
+pub mod legacy;
+#[cfg(feature = "std-net")]
+mod probe;
+#[cfg(feature = "std-net")]
+pub mod query;
+
+pub use builder::StatusResponseBuilder;
+#[cfg(feature = "std-net")]
+pub use probe::{probe, Probe, ProbeOptions, ServerProbe};
+
+pub const DEFAULT_PORT: u16 = 25565;
+pub const DEFAULT_SAMPLE_LIMIT: usize = 100;
+
+// Launchers send -1 while they don't know the version yet
+pub const PROBE_VERSION: VersionInfo = VersionInfo::new("unknown", -1);
+
+pub const SERVERBOUND_STATUS_REQUEST: i32 = 0x00;
+pub const SERVERBOUND_PING_REQUEST: i32 = 0x01;
+
+pub const CLIENTBOUND_STATUS_RESPONSE: i32 = 0x00;
+pub const CLIENTBOUND_PONG_RESPONSE: i32 = 0x01;
+
+pub const DEFAULT_STATUS_TIMEOUT: Duration = Duration::from_secs(5);
+pub const DEFAULT_PONG_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StatusVersion {
+    pub name: String,
+    pub protocol: i32
+}
+
+// Ad servers fill the sample with text and ids that aren't UUIDs
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "RawPlayerSample", into = "RawPlayerSample")]
+pub struct PlayerSample {
+    pub name: String,
+    pub id: Option<Uuid>,
+    pub raw_id: String
+}
+
+impl PlayerSample {
+    pub fn new(name: impl Into<String>, id: Uuid) -> Self {
+        Self {
+            name: name.into(),
+            id: Some(id),
+            raw_id: id.to_string()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct RawPlayerSample {
+    name: String,
+    id: String
+}
+
+impl From<RawPlayerSample> for PlayerSample {
+    fn from(sample: RawPlayerSample) -> Self {
+        Self {
+            name: sample.name,
+            id: Uuid::parse_str(&sample.id).ok(),
+            raw_id: sample.id
+        }
+    }
+}
+
+impl From<PlayerSample> for RawPlayerSample {
+    fn from(sample: PlayerSample) -> Self {
+        Self {
+            name: sample.name,
+            id: sample.raw_id
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StatusPlayers {
+    pub max: i32,
+    pub online: i32,
+    #[serde(default)]
+    pub sample: Vec<PlayerSample>
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StatusResponse {
+    pub version: StatusVersion,
+    pub players: Option<StatusPlayers>,
+    pub description: Option<Value>,
+    pub favicon: Option<String>,
+    #[serde(rename = "enforcesSecureChat", default)]
+    pub enforces_secure_chat: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modinfo: Option<Value>,
+    #[serde(rename = "forgeData", skip_serializing_if = "Option::is_none")]
+    pub forge_data: Option<Value>,
+    #[serde(skip)]
+    pub latency: Option<Duration>
+}
+
+impl StatusResponse {
+    pub fn parse(json: &str) -> Result<Self, Error> {
+        Self::parse_with_sample_limit(json, DEFAULT_SAMPLE_LIMIT)
+    }
+
+    pub fn parse_with_sample_limit(json: &str, sample_limit: usize) -> Result<Self, Error> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let status = StatusSeed { sample_limit }
+            .deserialize(&mut deserializer)
+            .and_then(|status| deserializer.end().map(|_| status))
+            .map_err(|error| Error::Other(format!("Unable to parse status response => {}", error)))?;
+        Ok(status)
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string(self).map_err(|error| Error::Other(format!("Unable to serialize status response => {}", error)))
+    }
+
+    pub fn motd(&self) -> String {
+        let mut motd = String::new();
+        if let Some(description) = &self.description {
+            flatten_component(description, &mut motd);
+        }
+        motd
+    }
+
+    pub fn favicon_bytes(&self) -> Option<Vec<u8>> {
+        let favicon = self.favicon.as_ref()?;
+        STANDARD.decode(favicon.strip_prefix("data:image/png;base64,").unwrap_or(favicon).replace('\n', "")).ok()
+    }
+
+    pub fn forge(&self) -> Option<ForgeStatus> {
+        match (&self.forge_data, &self.modinfo) {
+            (Some(forge_data), _) => Some(ForgeStatus::from_forge_data(forge_data)),
+            (None, Some(modinfo)) => Some(ForgeStatus::from_modinfo(modinfo)),
+            (None, None) => None
+        }
+    }
+}
+
+// Servers can stuff arbitrarily many entries into players.sample
+struct StatusSeed {
+    sample_limit: usize
+}
+
+impl<'de> DeserializeSeed<'de> for StatusSeed {
+    type Value = StatusResponse;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for StatusSeed {
+    type Value = StatusResponse;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a status response")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let (mut version, mut players, mut description, mut favicon, mut enforces_secure_chat) = (None, None, None, None, false);
+        let (mut modinfo, mut forge_data) = (None, None);
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "version" => version = Some(map.next_value()?),
+                "players" => players = map.next_value_seed(PlayersSeed { sample_limit: self.sample_limit })?,
+                "description" => description = map.next_value()?,
+                "favicon" => favicon = map.next_value()?,
+                "enforcesSecureChat" => enforces_secure_chat = map.next_value()?,
+                "modinfo" => modinfo = map.next_value()?,
+                "forgeData" => forge_data = map.next_value()?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        Ok(StatusResponse {
+            version: version.ok_or_else(|| serde::de::Error::missing_field("version"))?,
+            players,
+            description,
+            favicon,
+            enforces_secure_chat,
+            modinfo,
+            forge_data,
+            latency: None
+        })
+    }
+}
+
+struct PlayersSeed {
+    sample_limit: usize
+}
+
+impl<'de> DeserializeSeed<'de> for PlayersSeed {
+    type Value = Option<StatusPlayers>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_option(self)
+    }
+}
+
+impl<'de> Visitor<'de> for PlayersSeed {
+    type Value = Option<StatusPlayers>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("the players of a status response")
+    }
+
+    fn visit_none<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let (mut max, mut online, mut sample) = (None, None, Vec::new());
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "max" => max = Some(map.next_value()?),
+                "online" => online = Some(map.next_value()?),
+                "sample" => sample = map.next_value_seed(SampleSeed { sample_limit: self.sample_limit })?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        Ok(Some(StatusPlayers {
+            max: max.ok_or_else(|| serde::de::Error::missing_field("max"))?,
+            online: online.ok_or_else(|| serde::de::Error::missing_field("online"))?,
+            sample
+        }))
+    }
+}
+
+struct SampleSeed {
+    sample_limit: usize
+}
+
+impl<'de> DeserializeSeed<'de> for SampleSeed {
+    type Value = Vec<PlayerSample>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for SampleSeed {
+    type Value = Vec<PlayerSample>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a players sample")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut sample = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(self.sample_limit));
+        while sample.len() < self.sample_limit {
+            match seq.next_element()? {
+                Some(entry) => sample.push(entry),
+                None => return Ok(sample)
+            }
+        }
+
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(sample)
+    }
+}
+
+fn flatten_component(component: &Value, text: &mut String) {
+    match component {
+        Value::String(string) => text.push_str(string),
+        Value::Array(components) => components.iter().for_each(|component| flatten_component(component, text)),
+        Value::Object(object) => {
+            if let Some(Value::String(string)) = object.get("text") {
+                text.push_str(string);
+            }
+
+            if let Some(extra) = object.get("extra") {
+                flatten_component(extra, text);
+            }
+        }
+        _ => {}
+    }
+}
+
+pub fn parse_address(address: &str) -> Result<(String, Option<u16>), Error> {
+    if address.parse::<IpAddr>().is_ok() {
+        return Ok((address.to_string(), None))
+    }
+
+    match address.rsplit_once(':') {
+        Some((host, port)) => {
+            let port = port.parse().map_err(|_| Error::Other(format!("Invalid port in address {}", address)))?;
+            Ok((host.trim_start_matches('[').trim_end_matches(']').to_string(), Some(port)))
+        }
+        None => Ok((address.to_string(), None))
+    }
+}
+
+#[cfg(feature = "std-net")]
+pub async fn resolve_srv(host: &str) -> Result<Option<(String, u16)>, Error> {
+    if host.parse::<IpAddr>().is_ok() {
+        return Ok(None)
+    }
+
+    let resolver = TokioAsyncResolver::tokio_from_system_conf().map_err(|error| Error::Other(format!("Unable to create resolver => {}", error)))?;
+    match resolver.srv_lookup(format!("_minecraft._tcp.{}", host)).await {
+        Ok(lookup) => Ok(lookup.iter().next().map(|record| (record.target().to_utf8().trim_end_matches('.').to_string(), record.port()))),
+        Err(error) if matches!(error.kind(), ResolveErrorKind::NoRecordsFound { .. }) => Ok(None),
+        Err(error) => Err(Error::Other(format!("Unable to resolve SRV record for {} => {}", host, error)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PingResult {
+    pub status: StatusResponse,
+    pub latency: Duration
+}
+
+#[cfg(feature = "std-net")]
+async fn connect(host: &str, port: u16, version: &VersionInfo) -> Result<AsyncSocketConnection<'static, Client>, Error> {
+    let pipeline = Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing"));
+    let mut connection = AsyncSocketConnection::<Client>::new(TcpStream::connect((host, port)).await?, pipeline);
+    connection
+        .write(Handshake {
+            protocol_version: version.protocol,
+            server_address: host.to_string(),
+            server_port: port,
+            next_state: NextState::Status
+        })
+        .await?;
+    connection.set_state(PacketState::Status);
+    connection.set_protocol_version(Some(version.protocol));
+    connection.write(packet(SERVERBOUND_STATUS_REQUEST)?).await?;
+    Ok(connection)
+}
+
+#[cfg(feature = "std-net")]
+fn ping_packet(payload: i64) -> Result<Buffer, Error> {
+    let mut ping = packet(SERVERBOUND_PING_REQUEST)?;
+    ping.write_i64(payload)?;
+    Ok(ping)
+}
+
+#[cfg(feature = "std-net")]
+pub async fn detect_version(address: &str) -> Result<VersionInfo, Error> {
+    let (host, port) = parse_address(address)?;
+    let (host, port) = match port {
+        Some(port) => (host, port),
+        None => resolve_srv(&host).await?.unwrap_or((host, DEFAULT_PORT))
+    };
+    select_version(&host, port, &VersionSelection::Auto).await
+}
+
+#[cfg(feature = "std-net")]
+pub async fn select_version(host: &str, port: u16, selection: &VersionSelection) -> Result<VersionInfo, Error> {
+    let reported = match selection.needs_probe() {
+        true => Some(request_status(host, port, &PROBE_VERSION).await?.version),
+        false => None
+    };
+    selection.select(reported.as_ref())
+}
+
+#[cfg(feature = "std-net")]
+pub async fn request_status(host: &str, port: u16, version: &VersionInfo) -> Result<StatusResponse, Error> {
+    request_status_with_timeouts(host, port, version, DEFAULT_STATUS_TIMEOUT, DEFAULT_PONG_TIMEOUT).await
+}
+
+// Some servers answer the ping first or put both answers into one segment
+#[cfg(feature = "std-net")]
+pub async fn request_status_with_timeouts(host: &str, port: u16, version: &VersionInfo, status_timeout: Duration, pong_timeout: Duration) -> Result<StatusResponse, Error> {
+    let mut connection = connect(host, port, version).await?;
+    let payload = random::<i64>();
+    let start = Instant::now();
+    connection.write(ping_packet(payload)?).await?;
+
+    // The pong deadline starts with the status response, a slow status shouldn't eat the time of the pong
+    let (mut status, mut status_at, mut latency) = (None::<StatusResponse>, start, None);
+    loop {
+        let deadline = match (&status, latency) {
+            (None, _) => start + status_timeout,
+            (Some(_), None) => status_at + pong_timeout,
+            (Some(_), Some(_)) => break
+        };
+
+        let mut response = match timeout_at(deadline, connection.read_packet()).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(Error::IoError(error))) if status.is_none() && error.kind() == io::ErrorKind::UnexpectedEof => {
+                return Err(Error::Disconnected(format!("{}:{} closed the connection before sending a status response", host, port)))
+            }
+            Ok(Err(error)) if status.is_none() => return Err(error),
+            Err(_) if status.is_none() => return Err(Error::IoError(io::ErrorKind::TimedOut.into())),
+            _ => break
+        };
+
+        match response.read_var_i32()? {
+            CLIENTBOUND_STATUS_RESPONSE if status.is_none() => {
+                status = Some(StatusResponse::parse(&response.read_string()?)?);
+                status_at = Instant::now();
+            }
+            CLIENTBOUND_PONG_RESPONSE if latency.is_none() => {
+                if response.read_i64()? != payload {
+                    return Err(Error::Other("Pong payload doesn't match the ping payload".to_string()))
+                }
+                latency = Some(start.elapsed());
+            }
+            id => return Err(Error::IllegalPacket(id, version.to_string()))
+        }
+    }
+
+    Ok(StatusResponse {
+        latency,
+        ..status.expect("Status response is always read before leaving the loop")
+    })
+}
+
+#[cfg(feature = "std-net")]
+pub async fn ping(host: &str, port: u16, version: &VersionInfo) -> Result<PingResult, Error> {
+    ping_with_timeouts(host, port, version, DEFAULT_STATUS_TIMEOUT, DEFAULT_PONG_TIMEOUT).await
+}
+
+// Pongs carrying another payload are stale
+#[cfg(feature = "std-net")]
+pub async fn ping_with_timeouts(host: &str, port: u16, version: &VersionInfo, status_timeout: Duration, pong_timeout: Duration) -> Result<PingResult, Error> {
+    let mut connection = connect(host, port, version).await?;
+    let mut response = read_before(&mut connection, Instant::now() + status_timeout, host, port, "a status response").await?;
+    let status = match response.read_var_i32()? {
+        CLIENTBOUND_STATUS_RESPONSE => StatusResponse::parse(&response.read_string()?)?,
+        id => return Err(Error::IllegalPacket(id, version.to_string()))
+    };
+
+    let payload = random::<i64>();
+    let start = Instant::now();
+    connection.write(ping_packet(payload)?).await?;
+    loop {
+        let mut response = read_before(&mut connection, start + pong_timeout, host, port, "a pong").await?;
+        match response.read_var_i32()? {
+            CLIENTBOUND_PONG_RESPONSE if response.read_i64()? == payload => {
+                let latency = start.elapsed();
+                return Ok(PingResult {
+                    status: StatusResponse { latency: Some(latency), ..status },
+                    latency
+                })
+            }
+            CLIENTBOUND_PONG_RESPONSE => {}
+            id => return Err(Error::IllegalPacket(id, version.to_string()))
+        }
+    }
+}
+
+#[cfg(feature = "std-net")]
+async fn read_before(connection: &mut AsyncSocketConnection<'_, Client>, deadline: Instant, host: &str, port: u16, expected: &str) -> Result<Buffer, Error> {
+    match timeout_at(deadline, connection.read_packet()).await {
+        Ok(Err(Error::IoError(error))) if error.kind() == io::ErrorKind::UnexpectedEof => {
+            Err(Error::Disconnected(format!("{}:{} closed the connection before sending {}", host, port, expected)))
+        }
+        Ok(result) => result,
+        Err(_) => Err(Error::IoError(io::ErrorKind::TimedOut.into()))
+    }
+}
+
+#[cfg(feature = "std-net")]
+fn packet(id: i32) -> Result<Buffer, Error> {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(id)?;
+    Ok(buffer)
+}
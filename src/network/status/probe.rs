@@ -0,0 +1,185 @@
+use std::{future::Future, io, time::Duration};
+
+use tokio::time::{timeout_at, Instant};
+
+use crate::network::{
+    status::{
+        legacy::{legacy_ping, LegacyStatus},
+        parse_address,
+        query::{query, QueryResponse},
+        request_status, resolve_srv, StatusResponse, DEFAULT_PORT
+    },
+    versions::VersionInfo,
+    Error
+};
+#[cfg(feature = "webapi")]
+use crate::webapi::{is_server_blocked, Endpoints};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Probe {
+    Srv,
+    Status,
+    Legacy,
+    Query,
+    Blocklist
+}
+
+#[derive(Debug, Clone)]
+pub struct ProbeOptions {
+    version: VersionInfo,
+    timeout: Duration,
+    legacy_fallback: bool,
+    query: bool,
+    query_port: Option<u16>,
+    blocklist: bool,
+    #[cfg(feature = "webapi")]
+    endpoints: Endpoints
+}
+
+impl Default for ProbeOptions {
+    fn default() -> Self {
+        Self {
+            version: *VersionInfo::latest(),
+            timeout: Duration::from_secs(5),
+            legacy_fallback: false,
+            query: false,
+            query_port: None,
+            blocklist: false,
+            #[cfg(feature = "webapi")]
+            endpoints: Endpoints::default()
+        }
+    }
+}
+
+impl ProbeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn version(self, version: &VersionInfo) -> Self {
+        Self { version: *version, ..self }
+    }
+
+    pub fn timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+
+    pub fn legacy_fallback(self, legacy_fallback: bool) -> Self {
+        Self { legacy_fallback, ..self }
+    }
+
+    pub fn query(self, query: bool) -> Self {
+        Self { query, ..self }
+    }
+
+    pub fn query_port(self, query_port: u16) -> Self {
+        Self {
+            query_port: Some(query_port),
+            ..self
+        }
+    }
+
+    pub fn blocklist(self, blocklist: bool) -> Self {
+        Self { blocklist, ..self }
+    }
+
+    #[cfg(feature = "webapi")]
+    pub fn endpoints(self, endpoints: Endpoints) -> Self {
+        Self { endpoints, ..self }
+    }
+}
+
+#[derive(Debug)]
+pub struct ServerProbe {
+    pub host: String,
+    pub port: u16,
+    pub status: Option<StatusResponse>,
+    pub latency: Option<Duration>,
+    pub legacy: Option<LegacyStatus>,
+    pub query: Option<QueryResponse>,
+    pub blocked: Option<bool>,
+    pub errors: Vec<(Probe, Error)>
+}
+
+impl ServerProbe {
+    pub fn error(&self, probe: Probe) -> Option<&Error> {
+        self.errors.iter().find(|(kind, _)| *kind == probe).map(|(_, error)| error)
+    }
+}
+
+pub async fn probe(address: &str, options: ProbeOptions) -> Result<ServerProbe, Error> {
+    let deadline = Instant::now() + options.timeout;
+    let (requested, port) = parse_address(address)?;
+
+    let mut errors = Vec::new();
+    let (host, port) = match port {
+        Some(port) => (requested.clone(), port),
+        None => {
+            match within(deadline, resolve_srv(&requested)).await {
+                Ok(Some(target)) => target,
+                Ok(None) => (requested.clone(), DEFAULT_PORT),
+                Err(error) => {
+                    errors.push((Probe::Srv, error));
+                    (requested.clone(), DEFAULT_PORT)
+                }
+            }
+        }
+    };
+
+    let status = async {
+        let status = within(deadline, request_status(&host, port, &options.version)).await;
+        let legacy = match status.is_err() && options.legacy_fallback {
+            true => Some(within(deadline, legacy_ping(&host, port)).await),
+            false => None
+        };
+        (status, legacy)
+    };
+
+    let query = async {
+        match options.query {
+            true => Some(within(deadline, query(&host, options.query_port.unwrap_or(port), options.timeout)).await),
+            false => None
+        }
+    };
+
+    let blocked = async {
+        match options.blocklist {
+            true => Some(within(deadline, blocked(&[&requested, &host], &options)).await),
+            false => None
+        }
+    };
+
+    let ((status, legacy), query, blocked) = tokio::join!(status, query, blocked);
+    let status = collect(status, Probe::Status, &mut errors);
+
+    Ok(ServerProbe {
+        latency: status.as_ref().and_then(|status| status.latency),
+        status,
+        legacy: legacy.and_then(|legacy| collect(legacy, Probe::Legacy, &mut errors)),
+        query: query.and_then(|query| collect(query, Probe::Query, &mut errors)),
+        blocked: blocked.and_then(|blocked| collect(blocked, Probe::Blocklist, &mut errors)),
+        host,
+        port,
+        errors
+    })
+}
+
+fn collect<T>(result: Result<T, Error>, probe: Probe, errors: &mut Vec<(Probe, Error)>) -> Option<T> {
+    result.map_err(|error| errors.push((probe, error))).ok()
+}
+
+async fn within<T>(deadline: Instant, future: impl Future<Output = Result<T, Error>>) -> Result<T, Error> {
+    timeout_at(deadline, future).await.map_err(|_| Error::IoError(io::ErrorKind::TimedOut.into()))?
+}
+
+// Like vanilla, both the address the user typed and the SRV target are checked
+#[cfg(feature = "webapi")]
+async fn blocked(hosts: &[&str], options: &ProbeOptions) -> Result<bool, Error> {
+    let blocked_servers = options.endpoints.blocked_servers().await?;
+    Ok(hosts.iter().any(|host| is_server_blocked(host, &blocked_servers)))
+}
+
+#[cfg(not(feature = "webapi"))]
+async fn blocked(_hosts: &[&str], _options: &ProbeOptions) -> Result<bool, Error> {
+    Err(Error::Other("The blocklist check requires the webapi feature".to_string()))
+}
@@ -0,0 +1,102 @@
+use std::{collections::HashMap, time::Duration};
+
+use rand::random;
+use tokio::{net::UdpSocket, time::timeout};
+
+use crate::network::Error;
+
+const MAGIC: [u8; 2] = [0xFE, 0xFD];
+const HANDSHAKE: u8 = 9;
+const STAT: u8 = 0;
+const SESSION_MASK: i32 = 0x0F0F0F0F;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryResponse {
+    pub values: HashMap<String, String>,
+    pub players: Vec<String>
+}
+
+impl QueryResponse {
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let mut strings = bytes.split(|byte| *byte == 0).map(|string| String::from_utf8_lossy(string).into_owned());
+        let invalid = || Error::Other("Invalid query response".to_string());
+
+        // The key-value section starts with the constant "splitnum\0\x80\0" padding
+        if strings.next().ok_or_else(invalid)? != "splitnum" {
+            return Err(invalid())
+        }
+        strings.next().ok_or_else(invalid)?;
+
+        let mut values = HashMap::new();
+        loop {
+            let key = strings.next().ok_or_else(invalid)?;
+            if key.is_empty() {
+                break;
+            }
+            values.insert(key, strings.next().ok_or_else(invalid)?);
+        }
+
+        // The player section starts with the constant "\x01player_\0\0" padding
+        strings.next().ok_or_else(invalid)?;
+        strings.next().ok_or_else(invalid)?;
+
+        let players = strings.take_while(|player| !player.is_empty()).collect();
+        Ok(Self { values, players })
+    }
+
+    pub fn motd(&self) -> Option<&str> {
+        self.values.get("hostname").map(String::as_str)
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.values.get("version").map(String::as_str)
+    }
+
+    pub fn online_players(&self) -> Option<i32> {
+        self.values.get("numplayers").and_then(|players| players.parse().ok())
+    }
+
+    pub fn max_players(&self) -> Option<i32> {
+        self.values.get("maxplayers").and_then(|players| players.parse().ok())
+    }
+}
+
+pub async fn query(host: &str, port: u16, duration: Duration) -> Result<QueryResponse, Error> {
+    timeout(duration, query_without_timeout(host, port))
+        .await
+        .map_err(|_| Error::IoError(std::io::ErrorKind::TimedOut.into()))?
+}
+
+async fn query_without_timeout(host: &str, port: u16) -> Result<QueryResponse, Error> {
+    let socket = UdpSocket::bind(if host.contains(':') { "[::]:0" } else { "0.0.0.0:0" }).await?;
+    socket.connect((host, port)).await?;
+    let session_id = random::<i32>() & SESSION_MASK;
+
+    socket.send(&request(HANDSHAKE, session_id, &[])).await?;
+    let handshake = receive(&socket, HANDSHAKE, session_id).await?;
+    let token = String::from_utf8_lossy(handshake.split(|byte| *byte == 0).next().unwrap_or_default())
+        .parse::<i32>()
+        .map_err(|_| Error::Other("Invalid query challenge token".to_string()))?;
+
+    let mut payload = token.to_be_bytes().to_vec();
+    payload.extend_from_slice(&[0; 4]);
+    socket.send(&request(STAT, session_id, &payload)).await?;
+    QueryResponse::parse(&receive(&socket, STAT, session_id).await?)
+}
+
+fn request(kind: u8, session_id: i32, payload: &[u8]) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.push(kind);
+    bytes.extend_from_slice(&session_id.to_be_bytes());
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+async fn receive(socket: &UdpSocket, kind: u8, session_id: i32) -> Result<Vec<u8>, Error> {
+    let mut bytes = vec![0; u16::MAX as usize];
+    let length = socket.recv(&mut bytes).await?;
+    if length < 5 || bytes[0] != kind || bytes[1..5] != session_id.to_be_bytes() {
+        return Err(Error::Other("Invalid query response header".to_string()))
+    }
+    Ok(bytes[5..length].to_vec())
+}
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use crate::network::{
+    buffer::Buffer,
+    connection::{Readable, Writable},
+    types::Identifier,
+    Error, PacketState
+};
+
+pub const MAX_REGISTRIES: usize = 256;
+pub const MAX_TAGS: usize = 16384;
+pub const MAX_ENTRIES: usize = 65536;
+
+// 1.16.5 sent the tags of these registries in this order without their identifiers
+const LEGACY_REGISTRIES: [&str; 4] = ["block", "item", "fluid", "entity_type"];
+const REGISTRY_IDENTIFIERS_PROTOCOL: i32 = 755;
+
+// Also sent in configuration since 1.20.2
+pub fn packet_id(state: PacketState, protocol: i32) -> Option<i32> {
+    Some(match (state, protocol) {
+        (PacketState::Play, 754) => 0x5B,
+        (PacketState::Play, 755..=756) => 0x66,
+        (PacketState::Play, 757..=758) => 0x67,
+        (PacketState::Play, 759) => 0x68,
+        (PacketState::Play, 760) => 0x6B,
+        (PacketState::Play, 761) => 0x6A,
+        (PacketState::Play, 762..=763) => 0x6E,
+        (PacketState::Play, 764) => 0x70,
+        (PacketState::Play, 765) => 0x74,
+        (PacketState::Play, 766..=767) => 0x78,
+        (PacketState::Configuration, 764) => 0x08,
+        (PacketState::Configuration, 765) => 0x09,
+        (PacketState::Configuration, 766..=767) => 0x0D,
+        _ => return None
+    })
+}
+
+fn read_count(buffer: &mut Buffer, max: usize, name: &str) -> Result<usize, Error> {
+    let offset = buffer.position();
+    let count = buffer.read_length()?;
+    if count > max {
+        return Err(Error::Decode {
+            offset,
+            reason: format!("Too many {} ({} > {})", name, count, max)
+        })
+    }
+    Ok(count)
+}
+
+fn write_count(buffer: &mut Buffer, count: usize, max: usize, name: &str) -> Result<(), Error> {
+    if count > max {
+        return Err(Error::Other(format!("Unable to write tags => Too many {} ({} > {})", name, count, max)))
+    }
+    buffer.write_var_i32(count as i32)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagRegistry {
+    registries: HashMap<Identifier, HashMap<Identifier, Vec<i32>>>
+}
+
+impl TagRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn registries(&self) -> &HashMap<Identifier, HashMap<Identifier, Vec<i32>>> {
+        &self.registries
+    }
+
+    pub fn registry(&self, registry: &Identifier) -> Option<&HashMap<Identifier, Vec<i32>>> {
+        self.registries.get(registry)
+    }
+
+    pub fn tag(&self, registry: &Identifier, tag: &Identifier) -> Option<&[i32]> {
+        self.registries.get(registry)?.get(tag).map(Vec::as_slice)
+    }
+
+    pub fn contains(&self, registry: &Identifier, tag: &Identifier, id: i32) -> bool {
+        self.tag(registry, tag).is_some_and(|ids| ids.contains(&id))
+    }
+
+    pub fn insert(&mut self, registry: Identifier, tag: Identifier, ids: Vec<i32>) {
+        self.registries.entry(registry).or_default().insert(tag, ids);
+    }
+
+    pub fn apply(&mut self, update: TagRegistry) {
+        self.registries.extend(update.registries);
+    }
+
+    pub fn read_versioned(buffer: &mut Buffer, protocol: i32) -> Result<Self, Error> {
+        let mut registries = HashMap::new();
+        if protocol < REGISTRY_IDENTIFIERS_PROTOCOL {
+            for registry in LEGACY_REGISTRIES {
+                registries.insert(Identifier::new(Identifier::DEFAULT_NAMESPACE, registry)?, Self::read_tags(buffer)?);
+            }
+            return Ok(Self { registries })
+        }
+
+        for _ in 0..read_count(buffer, MAX_REGISTRIES, "registries")? {
+            let registry = Identifier::read(buffer)?;
+            registries.insert(registry, Self::read_tags(buffer)?);
+        }
+        Ok(Self { registries })
+    }
+
+    fn read_tags(buffer: &mut Buffer) -> Result<HashMap<Identifier, Vec<i32>>, Error> {
+        let count = read_count(buffer, MAX_TAGS, "tags")?;
+        let mut tags = HashMap::with_capacity(count);
+        for _ in 0..count {
+            let tag = Identifier::read(buffer)?;
+            let ids = (0..read_count(buffer, MAX_ENTRIES, "tag entries")?)
+                .map(|_| buffer.read_var_i32())
+                .collect::<Result<Vec<_>, _>>()?;
+            tags.insert(tag, ids);
+        }
+        Ok(tags)
+    }
+
+    pub fn write_versioned(&self, mut buffer: Buffer, protocol: i32) -> Result<Buffer, Error> {
+        if protocol < REGISTRY_IDENTIFIERS_PROTOCOL {
+            if let Some(registry) = self.registries.keys().find(|registry| !Self::is_legacy(registry)) {
+                return Err(Error::Other(format!("Unable to write tags => Protocol {} can't send tags of {}", protocol, registry)))
+            }
+
+            let empty = HashMap::new();
+            for registry in LEGACY_REGISTRIES {
+                let tags = self.registries.get(&Identifier::new(Identifier::DEFAULT_NAMESPACE, registry)?).unwrap_or(&empty);
+                buffer = Self::write_tags(buffer, tags)?;
+            }
+            return Ok(buffer)
+        }
+
+        write_count(&mut buffer, self.registries.len(), MAX_REGISTRIES, "registries")?;
+        for (registry, tags) in &self.registries {
+            buffer = registry.write(buffer)?;
+            buffer = Self::write_tags(buffer, tags)?;
+        }
+        Ok(buffer)
+    }
+
+    fn write_tags(mut buffer: Buffer, tags: &HashMap<Identifier, Vec<i32>>) -> Result<Buffer, Error> {
+        write_count(&mut buffer, tags.len(), MAX_TAGS, "tags")?;
+        for (tag, ids) in tags {
+            buffer = tag.write(buffer)?;
+            write_count(&mut buffer, ids.len(), MAX_ENTRIES, "tag entries")?;
+            for id in ids {
+                buffer.write_var_i32(*id)?;
+            }
+        }
+        Ok(buffer)
+    }
+
+    fn is_legacy(registry: &Identifier) -> bool {
+        registry.namespace() == Identifier::DEFAULT_NAMESPACE && LEGACY_REGISTRIES.contains(&registry.path())
+    }
+}
+
+impl Readable for TagRegistry {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        Self::read_versioned(buffer, i32::MAX)
+    }
+}
+
+impl Writable for TagRegistry {
+    fn write(&self, buffer: Buffer) -> Result<Buffer, Error> {
+        self.write_versioned(buffer, i32::MAX)
+    }
+}
@@ -0,0 +1,183 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc
+    },
+    thread,
+    time::{Duration, Instant}
+};
+
+use tokio::{
+    select,
+    time::{interval_at, MissedTickBehavior}
+};
+
+use crate::network::{
+    buffer::Buffer,
+    connection::{async_socket::AsyncSocketConnection, Role},
+    Error
+};
+
+pub const TICK: Duration = Duration::from_millis(50);
+pub const DEFAULT_BEHIND_THRESHOLD: Duration = Duration::from_millis(250);
+
+type Callback<C> = Box<dyn FnMut(u64, &mut C) -> Result<(), Error> + Send>;
+type BehindCallback = Box<dyn FnMut(u64, Duration) + Send>;
+
+#[derive(Debug, Clone, Default)]
+pub struct StopHandle(Arc<AtomicBool>);
+
+impl StopHandle {
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+pub struct Ticker<C> {
+    period: Duration,
+    missed_tick_behavior: MissedTickBehavior,
+    behind_threshold: Duration,
+    callbacks: Vec<(u64, Callback<C>)>,
+    on_behind: Option<BehindCallback>,
+    start: Option<Instant>,
+    stop: StopHandle
+}
+
+impl<C> Default for Ticker<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> Ticker<C> {
+    pub fn new() -> Self {
+        Self {
+            period: TICK,
+            missed_tick_behavior: MissedTickBehavior::Burst,
+            behind_threshold: DEFAULT_BEHIND_THRESHOLD,
+            callbacks: Vec::new(),
+            on_behind: None,
+            start: None,
+            stop: StopHandle::default()
+        }
+    }
+
+    pub fn with_period(self, period: Duration) -> Self {
+        Self { period, ..self }
+    }
+
+    pub fn with_missed_tick_behavior(self, missed_tick_behavior: MissedTickBehavior) -> Self {
+        Self { missed_tick_behavior, ..self }
+    }
+
+    pub fn with_behind_threshold(self, behind_threshold: Duration) -> Self {
+        Self { behind_threshold, ..self }
+    }
+
+    pub fn on_behind(self, on_behind: impl FnMut(u64, Duration) + Send + 'static) -> Self {
+        Self {
+            on_behind: Some(Box::new(on_behind)),
+            ..self
+        }
+    }
+
+    pub fn every(mut self, ticks: u64, callback: impl FnMut(u64, &mut C) -> Result<(), Error> + Send + 'static) -> Self {
+        self.callbacks.push((ticks.max(1), Box::new(callback)));
+        self
+    }
+
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    pub fn stop_handle(&self) -> StopHandle {
+        self.stop.clone()
+    }
+
+    pub fn tick(&mut self, scheduled: Instant, now: Instant, connection: &mut C) -> Result<u64, Error> {
+        let start = *self.start.get_or_insert(scheduled);
+        let tick = (scheduled.saturating_duration_since(start).as_nanos() / self.period.as_nanos()) as u64;
+
+        let lag = now.saturating_duration_since(scheduled);
+        if lag > self.behind_threshold {
+            if let Some(on_behind) = &mut self.on_behind {
+                on_behind(tick, lag);
+            }
+        }
+
+        for (interval, callback) in &mut self.callbacks {
+            if tick.is_multiple_of(*interval) {
+                callback(tick, connection)?;
+            }
+        }
+        Ok(tick)
+    }
+
+    pub fn next_deadline(&self, scheduled: Instant, now: Instant) -> Instant {
+        let next = scheduled + self.period;
+        if now <= next {
+            return next
+        }
+
+        match self.missed_tick_behavior {
+            MissedTickBehavior::Delay => now + self.period,
+            MissedTickBehavior::Skip => {
+                let missed = (now.duration_since(next).as_nanos() / self.period.as_nanos()) as u32;
+                next + self.period * (missed + 1)
+            }
+            _ => next
+        }
+    }
+
+    pub fn run_blocking(&mut self, connection: &mut C) -> Result<(), Error> {
+        let mut scheduled = Instant::now();
+        while !self.stop.is_stopped() {
+            let now = Instant::now();
+            if scheduled > now {
+                thread::sleep(scheduled - now);
+            }
+
+            self.tick(scheduled, Instant::now(), connection)?;
+            scheduled = self.next_deadline(scheduled, Instant::now());
+        }
+        Ok(())
+    }
+}
+
+// Callbacks can't await, so they queue packets with the codec
+impl<'a, R: Role> Ticker<AsyncSocketConnection<'a, R>> {
+    pub async fn run(&mut self, connection: &mut AsyncSocketConnection<'a, R>) -> Result<(), Error> {
+        let mut interval = interval_at(tokio::time::Instant::now(), self.period);
+        interval.set_missed_tick_behavior(self.missed_tick_behavior);
+        while !self.stop.is_stopped() {
+            let scheduled = interval.tick().await;
+            self.tick(scheduled.into_std(), Instant::now(), connection)?;
+            connection.flush().await?;
+        }
+        Ok(())
+    }
+
+    pub async fn run_with_reads(
+        &mut self,
+        connection: &mut AsyncSocketConnection<'a, R>,
+        mut on_packet: impl FnMut(Buffer, &mut AsyncSocketConnection<'a, R>) -> Result<(), Error>
+    ) -> Result<(), Error> {
+        let mut interval = interval_at(tokio::time::Instant::now(), self.period);
+        interval.set_missed_tick_behavior(self.missed_tick_behavior);
+        while !self.stop.is_stopped() {
+            // Reading is cancel safe as long as the connection has no output left
+            select! {
+                scheduled = interval.tick() => {
+                    self.tick(scheduled.into_std(), Instant::now(), connection)?;
+                }
+                packet = connection.read_packet() => on_packet(packet?, connection)?
+            }
+            connection.flush().await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use crate::network::{
+    buffer::Buffer,
+    chunk::{
+        export::ChunkStore,
+        update::{packet_ids, unpack_section_position, BlockChange, SectionBlocksUpdate},
+        SECTION_WIDTH
+    },
+    connection::Readable,
+    versions::VersionInfo,
+    Error
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockRegion {
+    pub min: (i32, i32, i32),
+    pub max: (i32, i32, i32)
+}
+
+impl BlockRegion {
+    pub fn new(from: (i32, i32, i32), to: (i32, i32, i32)) -> Self {
+        Self {
+            min: (from.0.min(to.0), from.1.min(to.1), from.2.min(to.2)),
+            max: (from.0.max(to.0), from.1.max(to.1), from.2.max(to.2))
+        }
+    }
+
+    pub fn section(x: i32, y: i32, z: i32) -> Self {
+        let width = SECTION_WIDTH as i32;
+        Self::new((x * width, y * width, z * width), (x * width + width - 1, y * width + width - 1, z * width + width - 1))
+    }
+
+    pub fn contains(&self, x: i32, y: i32, z: i32) -> bool {
+        (self.min.0..=self.max.0).contains(&x) && (self.min.1..=self.max.1).contains(&y) && (self.min.2..=self.max.2).contains(&z)
+    }
+
+    pub fn intersects(&self, other: &BlockRegion) -> bool {
+        self.min.0 <= other.max.0 && other.min.0 <= self.max.0 && self.min.1 <= other.max.1 && other.min.1 <= self.max.1 && self.min.2 <= other.max.2 && other.min.2 <= self.max.2
+    }
+
+    pub fn union(&self, other: &BlockRegion) -> Self {
+        Self::new(
+            (self.min.0.min(other.min.0), self.min.1.min(other.min.1), self.min.2.min(other.min.2)),
+            (self.max.0.max(other.max.0), self.max.1.max(other.max.1), self.max.2.max(other.max.2))
+        )
+    }
+}
+
+type BlockCallback = Box<dyn FnMut((i32, i32, i32), Option<i32>, i32) + Send>;
+
+pub struct BlockWatcher {
+    protocol: i32,
+    regions: Vec<(usize, BlockRegion, BlockCallback)>,
+    bounds: Option<BlockRegion>,
+    next_id: usize
+}
+
+impl BlockWatcher {
+    pub fn new(version: &VersionInfo) -> Self {
+        Self {
+            protocol: version.protocol,
+            regions: Vec::new(),
+            bounds: None,
+            next_id: 0
+        }
+    }
+
+    pub fn protocol(&self) -> i32 {
+        self.protocol
+    }
+
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    pub fn watch(&mut self, region: BlockRegion, callback: impl FnMut((i32, i32, i32), Option<i32>, i32) + Send + 'static) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.regions.push((id, region, Box::new(callback)));
+        self.bounds = Some(self.bounds.map_or(region, |bounds| bounds.union(&region)));
+        id
+    }
+
+    pub fn unwatch(&mut self, id: usize) -> bool {
+        let len = self.regions.len();
+        self.regions.retain(|(candidate, _, _)| *candidate != id);
+        if self.regions.len() == len {
+            return false
+        }
+        self.bounds = self.regions.iter().map(|(_, region, _)| *region).reduce(|bounds, region| bounds.union(&region));
+        true
+    }
+
+    pub fn handle(&mut self, packet: &mut Buffer, store: Option<&ChunkStore>) -> Result<bool, Error> {
+        let (block_update, section_blocks_update) =
+            packet_ids(self.protocol).ok_or_else(|| Error::Other(format!("Unable to watch blocks => Protocol {} isn't supported", self.protocol)))?;
+        let position = packet.position();
+        let handled = self.read(packet, store, block_update, section_blocks_update);
+        packet.set_position(position);
+        handled
+    }
+
+    fn read(&mut self, packet: &mut Buffer, store: Option<&ChunkStore>, block_update: i32, section_blocks_update: i32) -> Result<bool, Error> {
+        let id = packet.read_var_i32()?;
+        if id != block_update && id != section_blocks_update {
+            return Ok(false)
+        }
+        let Some(bounds) = self.bounds else { return Ok(true) };
+
+        if id == block_update {
+            let change = BlockChange::read(packet)?;
+            if bounds.contains(change.x, change.y, change.z) {
+                self.notify(change, store);
+            }
+            return Ok(true)
+        }
+
+        let (x, y, z) = unpack_section_position(packet.read_i64()?);
+        if !bounds.intersects(&BlockRegion::section(x, y, z)) {
+            return Ok(true)
+        }
+
+        packet.rewind(8)?;
+        let update = SectionBlocksUpdate::read_versioned(packet, self.protocol)?;
+        for change in update.changes().filter(|change| bounds.contains(change.x, change.y, change.z)) {
+            self.notify(change, store);
+        }
+        Ok(true)
+    }
+
+    // The server resends blocks whose change the client predicted
+    fn notify(&mut self, change: BlockChange, store: Option<&ChunkStore>) {
+        let old = store.and_then(|store| store.block(change.x, change.y, change.z));
+        if old == Some(change.state) {
+            return
+        }
+
+        for (_, region, callback) in &mut self.regions {
+            if region.contains(change.x, change.y, change.z) {
+                callback((change.x, change.y, change.z), old, change.state);
+            }
+        }
+    }
+}
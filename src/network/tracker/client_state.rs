@@ -0,0 +1,290 @@
+use std::sync::Arc;
+
+use crate::network::{
+    buffer::Buffer,
+    connection::Connection,
+    decode::{DecodeMode, DecodeReport, UnknownVariant},
+    tracker::SessionState,
+    versions::VersionInfo,
+    ByteOrder, Error, PacketState
+};
+
+pub const ABILITY_INVULNERABLE: u8 = 0x01;
+pub const ABILITY_FLYING: u8 = 0x02;
+pub const ABILITY_ALLOW_FLYING: u8 = 0x04;
+pub const ABILITY_INSTANT_BREAK: u8 = 0x08;
+
+// Set Simulation Distance came with 1.18
+fn packet_ids(protocol: i32) -> Option<[i32; 6]> {
+    Some(match protocol {
+        754 => [0x0D, 0x30, 0x3F, 0x41, -1, 0x1A],
+        755..=756 => [0x0E, 0x32, 0x48, 0x4A, -1, 0x19],
+        757..=758 => [0x0E, 0x32, 0x48, 0x4A, 0x57, 0x19],
+        759 => [0x0B, 0x2F, 0x47, 0x49, 0x57, 0x1B],
+        760 => [0x0B, 0x31, 0x4A, 0x4C, 0x5A, 0x1C],
+        761 => [0x0B, 0x30, 0x49, 0x4B, 0x58, 0x1B],
+        762..=763 => [0x0C, 0x34, 0x4D, 0x4F, 0x5C, 0x1C],
+        764 => [0x0B, 0x36, 0x4F, 0x51, 0x5E, 0x1F],
+        765 => [0x0B, 0x36, 0x51, 0x53, 0x60, 0x20],
+        766..=767 => [0x0B, 0x38, 0x53, 0x55, 0x62, 0x23],
+        _ => return None
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Peaceful,
+    Easy,
+    Normal,
+    Hard,
+    Unknown(u8)
+}
+
+impl UnknownVariant for Difficulty {
+    fn is_unknown(&self) -> bool {
+        matches!(self, Self::Unknown(_))
+    }
+}
+
+impl Difficulty {
+    pub fn from_id(id: u8) -> Self {
+        match id {
+            0 => Self::Peaceful,
+            1 => Self::Easy,
+            2 => Self::Normal,
+            3 => Self::Hard,
+            id => Self::Unknown(id)
+        }
+    }
+
+    pub fn id(&self) -> u8 {
+        match self {
+            Self::Peaceful => 0,
+            Self::Easy => 1,
+            Self::Normal => 2,
+            Self::Hard => 3,
+            Self::Unknown(id) => *id
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Abilities {
+    pub invulnerable: bool,
+    pub flying: bool,
+    pub allow_flying: bool,
+    pub instant_break: bool,
+    pub flying_speed: f32,
+    pub walking_speed: f32
+}
+
+impl Default for Abilities {
+    fn default() -> Self {
+        Self::from_flags(0, 0.05, 0.1)
+    }
+}
+
+impl Abilities {
+    pub fn from_flags(flags: u8, flying_speed: f32, walking_speed: f32) -> Self {
+        Self {
+            invulnerable: flags & ABILITY_INVULNERABLE != 0,
+            flying: flags & ABILITY_FLYING != 0,
+            allow_flying: flags & ABILITY_ALLOW_FLYING != 0,
+            instant_break: flags & ABILITY_INSTANT_BREAK != 0,
+            flying_speed,
+            walking_speed
+        }
+    }
+
+    pub fn flags(&self) -> u8 {
+        [
+            (self.invulnerable, ABILITY_INVULNERABLE),
+            (self.flying, ABILITY_FLYING),
+            (self.allow_flying, ABILITY_ALLOW_FLYING),
+            (self.instant_break, ABILITY_INSTANT_BREAK)
+        ]
+        .into_iter()
+        .filter(|(set, _)| *set)
+        .fold(0, |flags, (_, flag)| flags | flag)
+    }
+
+    pub fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        let flags = buffer.read_u8()?;
+        Ok(Self::from_flags(flags, f32::from_bits(buffer.read_u32()?), f32::from_bits(buffer.read_u32()?)))
+    }
+
+    pub fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        buffer.write_u8(self.flags())?;
+        buffer.write_u32(self.flying_speed.to_bits())?;
+        buffer.write_u32(self.walking_speed.to_bits())?;
+        Ok(buffer)
+    }
+
+    // The server only looks at the flying flag
+    pub fn write_serverbound(&self, protocol: i32) -> Result<Buffer, Error> {
+        let [.., serverbound] = packet_ids(protocol).ok_or_else(|| Error::Other(format!("Unable to write abilities => Protocol {} isn't supported", protocol)))?;
+        let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        buffer.write_var_i32(serverbound)?;
+        buffer.write_u8(self.flags() & ABILITY_FLYING)?;
+        Ok(buffer)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StateChange {
+    Difficulty { difficulty: Difficulty, locked: bool },
+    Abilities(Abilities),
+    HeldItem(u8),
+    ViewDistance(i32),
+    SimulationDistance(i32)
+}
+
+type StateChangeCallback = Box<dyn FnMut(&StateChange) + Send>;
+
+pub struct ClientStateMirror {
+    protocol: i32,
+    difficulty: Option<(Difficulty, bool)>,
+    abilities: Option<Abilities>,
+    held_item: Option<u8>,
+    view_distance: Option<i32>,
+    simulation_distance: Option<i32>,
+    on_change: Option<StateChangeCallback>,
+    decode_mode: DecodeMode,
+    decode_report: Arc<DecodeReport>
+}
+
+impl ClientStateMirror {
+    pub fn new(version: &VersionInfo) -> Self {
+        Self {
+            protocol: version.protocol,
+            difficulty: None,
+            abilities: None,
+            held_item: None,
+            view_distance: None,
+            simulation_distance: None,
+            on_change: None,
+            decode_mode: DecodeMode::Strict,
+            decode_report: Arc::new(DecodeReport::new())
+        }
+    }
+
+    pub fn on_change(self, on_change: impl FnMut(&StateChange) + Send + 'static) -> Self {
+        Self {
+            on_change: Some(Box::new(on_change)),
+            ..self
+        }
+    }
+
+    // Usually the mode and report of the connection the packets are read from
+    pub fn decoding(self, decode_mode: DecodeMode, decode_report: Arc<DecodeReport>) -> Self {
+        Self { decode_mode, decode_report, ..self }
+    }
+
+    pub fn protocol(&self) -> i32 {
+        self.protocol
+    }
+
+    pub fn difficulty(&self) -> Option<Difficulty> {
+        self.difficulty.map(|(difficulty, _)| difficulty)
+    }
+
+    pub fn is_difficulty_locked(&self) -> bool {
+        self.difficulty.is_some_and(|(_, locked)| locked)
+    }
+
+    pub fn abilities(&self) -> Option<&Abilities> {
+        self.abilities.as_ref()
+    }
+
+    pub fn held_item(&self) -> Option<u8> {
+        self.held_item
+    }
+
+    pub fn view_distance(&self) -> Option<i32> {
+        self.view_distance
+    }
+
+    pub fn simulation_distance(&self) -> Option<i32> {
+        self.simulation_distance
+    }
+
+    pub fn handle(&mut self, packet: &mut Buffer) -> Result<bool, Error> {
+        let Some(ids) = packet_ids(self.protocol) else { return Ok(false) };
+
+        let position = packet.position();
+        let change = self.read(packet, ids);
+        packet.set_position(position);
+
+        match change? {
+            Some(change) => {
+                self.apply(change);
+                Ok(true)
+            }
+            None => Ok(false)
+        }
+    }
+
+    fn read(&self, packet: &mut Buffer, [difficulty, abilities, held_item, view_distance, simulation_distance, _]: [i32; 6]) -> Result<Option<StateChange>, Error> {
+        let id = packet.read_var_i32()?;
+        let change = match id {
+            _ if id == difficulty => {
+                let difficulty = Difficulty::from_id(packet.read_u8()?);
+                StateChange::Difficulty {
+                    difficulty: self.decode_mode.check_variant(difficulty, packet, PacketState::Play, id, &self.decode_report)?,
+                    locked: packet.read_bool()?
+                }
+            }
+            _ if id == abilities => StateChange::Abilities(Abilities::read(packet)?),
+            _ if id == held_item => StateChange::HeldItem(packet.read_u8()?),
+            _ if id == view_distance => StateChange::ViewDistance(packet.read_var_i32()?),
+            _ if id == simulation_distance => StateChange::SimulationDistance(packet.read_var_i32()?),
+            _ => return Ok(None)
+        };
+        self.decode_mode.check_trailing(packet, PacketState::Play, id, &self.decode_report)?;
+        Ok(Some(change))
+    }
+
+    pub fn apply(&mut self, change: StateChange) {
+        let changed = match change {
+            StateChange::Difficulty { difficulty, locked } => self.difficulty.replace((difficulty, locked)) != Some((difficulty, locked)),
+            StateChange::Abilities(abilities) => self.abilities.replace(abilities) != Some(abilities),
+            StateChange::HeldItem(slot) => self.held_item.replace(slot) != Some(slot),
+            StateChange::ViewDistance(distance) => self.view_distance.replace(distance) != Some(distance),
+            StateChange::SimulationDistance(distance) => self.simulation_distance.replace(distance) != Some(distance)
+        };
+
+        if let (true, Some(on_change)) = (changed, &mut self.on_change) {
+            on_change(&change);
+        }
+    }
+
+    // The server kicks players that fly without being allowed to
+    pub fn set_flying<'a, S>(&mut self, connection: &mut impl Connection<'a, S>, flying: bool) -> Result<(), Error> {
+        let abilities = match self.abilities {
+            Some(abilities) if abilities.allow_flying => Abilities { flying, ..abilities },
+            _ => return Err(Error::Other("Unable to toggle flight => The server doesn't allow flying".to_string()))
+        };
+
+        let protocol = connection.require_protocol_version("toggle flight")?;
+        connection.write(abilities.write_serverbound(protocol)?)?;
+        self.apply(StateChange::Abilities(abilities));
+        Ok(())
+    }
+}
+
+// Everything here is server authoritative and sent again after reconnecting
+impl SessionState for ClientStateMirror {
+    type Snapshot = ();
+
+    fn snapshot(&self) {}
+
+    fn restore(&mut self, _snapshot: ()) {}
+
+    fn reset_for_reconnect(&mut self) {
+        self.difficulty = None;
+        self.abilities = None;
+        self.held_item = None;
+        self.view_distance = None;
+        self.simulation_distance = None;
+    }
+}
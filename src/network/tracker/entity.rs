@@ -0,0 +1,445 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::network::{
+    buffer::Buffer,
+    connection::{Readable, Writable},
+    tracker::SessionState,
+    versions::VersionInfo,
+    ByteOrder, Error
+};
+
+// Relative moves are fixed point numbers with 12 fractional bits
+const DELTA_SCALE: f64 = 4096.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EntityPacket {
+    SpawnEntity,
+    SpawnLivingEntity,
+    SpawnPlayer,
+    Position,
+    PositionRotation,
+    Rotation,
+    Teleport,
+    Remove,
+    Respawn
+}
+
+const PACKETS: [EntityPacket; 9] = [
+    EntityPacket::SpawnEntity,
+    EntityPacket::SpawnLivingEntity,
+    EntityPacket::SpawnPlayer,
+    EntityPacket::Position,
+    EntityPacket::PositionRotation,
+    EntityPacket::Rotation,
+    EntityPacket::Teleport,
+    EntityPacket::Remove,
+    EntityPacket::Respawn
+];
+
+// Living entities are spawned like all others since 1.19 and players since 1.20.2
+fn packet_ids(protocol: i32) -> Option<[i32; 9]> {
+    Some(match protocol {
+        754 => [0x00, 0x02, 0x04, 0x27, 0x28, 0x29, 0x56, 0x36, 0x39],
+        755..=756 => [0x00, 0x02, 0x04, 0x29, 0x2A, 0x2B, 0x61, 0x3A, 0x3D],
+        757..=758 => [0x00, 0x02, 0x04, 0x29, 0x2A, 0x2B, 0x62, 0x3A, 0x3D],
+        759 => [0x00, -1, 0x02, 0x26, 0x27, 0x28, 0x63, 0x38, 0x3B],
+        760 => [0x00, -1, 0x02, 0x28, 0x29, 0x2A, 0x66, 0x3B, 0x3E],
+        761 => [0x00, -1, 0x02, 0x27, 0x28, 0x29, 0x64, 0x3A, 0x3D],
+        762..=763 => [0x01, -1, 0x03, 0x2B, 0x2C, 0x2D, 0x68, 0x3E, 0x41],
+        764 => [0x01, -1, -1, 0x2C, 0x2D, 0x2E, 0x6B, 0x40, 0x43],
+        765 => [0x01, -1, -1, 0x2C, 0x2D, 0x2E, 0x6D, 0x40, 0x45],
+        766..=767 => [0x01, -1, -1, 0x2E, 0x2F, 0x30, 0x70, 0x42, 0x47],
+        _ => return None
+    })
+}
+
+fn packet_id(packet: EntityPacket, protocol: i32) -> Result<i32, Error> {
+    let ids = packet_ids(protocol).ok_or_else(|| Error::Other(format!("Unable to handle {:?} => Protocol {} isn't supported", packet, protocol)))?;
+    match ids[PACKETS.iter().position(|candidate| *candidate == packet).unwrap()] {
+        -1 => Err(Error::Other(format!("Unable to write {:?} => Protocol {} doesn't have this packet", packet, protocol))),
+        id => Ok(id)
+    }
+}
+
+fn player_type(protocol: i32) -> Option<i32> {
+    match protocol {
+        764 => Some(122),
+        765 => Some(124),
+        766..=767 => Some(128),
+        _ => None
+    }
+}
+
+fn read_angle(buffer: &mut Buffer) -> Result<f32, Error> {
+    Ok(buffer.read_u8()? as f32 * 360.0 / 256.0)
+}
+
+fn write_angle(buffer: &mut Buffer, angle: f32) -> Result<(), Error> {
+    buffer.write_u8((angle * 256.0 / 360.0).floor() as i32 as u8)
+}
+
+fn read_position(buffer: &mut Buffer) -> Result<(f64, f64, f64), Error> {
+    Ok((f64::from_bits(buffer.read_u64()?), f64::from_bits(buffer.read_u64()?), f64::from_bits(buffer.read_u64()?)))
+}
+
+fn write_position(buffer: &mut Buffer, (x, y, z): (f64, f64, f64)) -> Result<(), Error> {
+    [x, y, z].into_iter().try_for_each(|value| buffer.write_u64(value.to_bits()))
+}
+
+// Vanilla adds the delta in fixed point, so rounding errors don't carry over
+fn apply_delta(value: f64, delta: i16) -> f64 {
+    match delta {
+        0 => value,
+        delta => ((value * DELTA_SCALE).round() as i64 + delta as i64) as f64 / DELTA_SCALE
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntityKind {
+    Player,
+    Type(i32)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntityUpdate {
+    Spawn {
+        id: i32,
+        uuid: Uuid,
+        kind: EntityKind,
+        position: (f64, f64, f64),
+        yaw: f32,
+        pitch: f32
+    },
+    Move {
+        id: i32,
+        delta: Option<(i16, i16, i16)>,
+        rotation: Option<(f32, f32)>,
+        on_ground: bool
+    },
+    Teleport {
+        id: i32,
+        position: (f64, f64, f64),
+        yaw: f32,
+        pitch: f32,
+        on_ground: bool
+    },
+    Remove(Vec<i32>),
+    Respawn
+}
+
+impl EntityUpdate {
+    pub fn read_versioned(buffer: &mut Buffer, protocol: i32) -> Result<Option<Self>, Error> {
+        let ids = packet_ids(protocol).ok_or_else(|| Error::Other(format!("Unable to handle entity packets => Protocol {} isn't supported", protocol)))?;
+        let id = buffer.read_var_i32()?;
+        let packet = match PACKETS.iter().zip(ids).find(|(_, packet_id)| *packet_id >= 0 && *packet_id == id) {
+            Some((packet, _)) => *packet,
+            None => return Ok(None)
+        };
+
+        Ok(Some(match packet {
+            EntityPacket::SpawnEntity | EntityPacket::SpawnLivingEntity => {
+                let id = buffer.read_var_i32()?;
+                let uuid = Uuid::read(buffer)?;
+                let kind = match buffer.read_var_i32()? {
+                    kind if Some(kind) == player_type(protocol) => EntityKind::Player,
+                    kind => EntityKind::Type(kind)
+                };
+                let position = read_position(buffer)?;
+
+                // Living entities had the yaw first
+                let (yaw, pitch) = match packet {
+                    EntityPacket::SpawnLivingEntity => (read_angle(buffer)?, read_angle(buffer)?),
+                    _ => {
+                        let pitch = read_angle(buffer)?;
+                        (read_angle(buffer)?, pitch)
+                    }
+                };
+                EntityUpdate::Spawn {
+                    id,
+                    uuid,
+                    kind,
+                    position,
+                    yaw,
+                    pitch
+                }
+            }
+            EntityPacket::SpawnPlayer => {
+                EntityUpdate::Spawn {
+                    id: buffer.read_var_i32()?,
+                    uuid: Uuid::read(buffer)?,
+                    kind: EntityKind::Player,
+                    position: read_position(buffer)?,
+                    yaw: read_angle(buffer)?,
+                    pitch: read_angle(buffer)?
+                }
+            }
+            EntityPacket::Position | EntityPacket::PositionRotation | EntityPacket::Rotation => {
+                let id = buffer.read_var_i32()?;
+                let delta = match packet {
+                    EntityPacket::Rotation => None,
+                    _ => Some((buffer.read_i16()?, buffer.read_i16()?, buffer.read_i16()?))
+                };
+                let rotation = match packet {
+                    EntityPacket::Position => None,
+                    _ => Some((read_angle(buffer)?, read_angle(buffer)?))
+                };
+                EntityUpdate::Move {
+                    id,
+                    delta,
+                    rotation,
+                    on_ground: buffer.read_bool()?
+                }
+            }
+            EntityPacket::Teleport => {
+                EntityUpdate::Teleport {
+                    id: buffer.read_var_i32()?,
+                    position: read_position(buffer)?,
+                    yaw: read_angle(buffer)?,
+                    pitch: read_angle(buffer)?,
+                    on_ground: buffer.read_bool()?
+                }
+            }
+            // 1.17 removed a single entity per packet, 1.17.1 went back to the list
+            EntityPacket::Remove if protocol == 755 => EntityUpdate::Remove(vec![buffer.read_var_i32()?]),
+            EntityPacket::Remove => {
+                let count = buffer.read_length()?;
+                EntityUpdate::Remove((0..count).map(|_| buffer.read_var_i32()).collect::<Result<_, _>>()?)
+            }
+            EntityPacket::Respawn => EntityUpdate::Respawn
+        }))
+    }
+
+    pub fn write_versioned(&self, protocol: i32) -> Result<Buffer, Error> {
+        let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        match self {
+            EntityUpdate::Spawn {
+                id,
+                uuid,
+                kind,
+                position,
+                yaw,
+                pitch
+            } => {
+                let player = *kind == EntityKind::Player && player_type(protocol).is_none();
+                buffer.write_var_i32(packet_id(if player { EntityPacket::SpawnPlayer } else { EntityPacket::SpawnEntity }, protocol)?)?;
+                buffer.write_var_i32(*id)?;
+                let mut buffer = uuid.write(buffer)?;
+                match (kind, player) {
+                    (_, true) => {
+                        write_position(&mut buffer, *position)?;
+                        write_angle(&mut buffer, *yaw)?;
+                        write_angle(&mut buffer, *pitch)?;
+                        return Ok(buffer)
+                    }
+                    (EntityKind::Player, false) => buffer.write_var_i32(player_type(protocol).unwrap())?,
+                    (EntityKind::Type(kind), false) => buffer.write_var_i32(*kind)?
+                }
+
+                write_position(&mut buffer, *position)?;
+                write_angle(&mut buffer, *pitch)?;
+                write_angle(&mut buffer, *yaw)?;
+                if protocol >= 759 {
+                    write_angle(&mut buffer, *yaw)?;
+                    buffer.write_var_i32(0)?;
+                } else {
+                    buffer.write_i32(0)?;
+                }
+                (0..3).try_for_each(|_| buffer.write_i16(0))?;
+                Ok(buffer)
+            }
+            EntityUpdate::Move { id, delta, rotation, on_ground } => {
+                let packet = match (delta, rotation) {
+                    (Some(_), None) => EntityPacket::Position,
+                    (Some(_), Some(_)) => EntityPacket::PositionRotation,
+                    (None, Some(_)) => EntityPacket::Rotation,
+                    (None, None) => return Err(Error::Other("Unable to write entity move => Neither delta nor rotation is set".to_string()))
+                };
+                buffer.write_var_i32(packet_id(packet, protocol)?)?;
+                buffer.write_var_i32(*id)?;
+                if let Some((x, y, z)) = delta {
+                    [*x, *y, *z].into_iter().try_for_each(|value| buffer.write_i16(value))?;
+                }
+                if let Some((yaw, pitch)) = rotation {
+                    write_angle(&mut buffer, *yaw)?;
+                    write_angle(&mut buffer, *pitch)?;
+                }
+                buffer.write_bool(*on_ground)?;
+                Ok(buffer)
+            }
+            EntityUpdate::Teleport {
+                id,
+                position,
+                yaw,
+                pitch,
+                on_ground
+            } => {
+                buffer.write_var_i32(packet_id(EntityPacket::Teleport, protocol)?)?;
+                buffer.write_var_i32(*id)?;
+                write_position(&mut buffer, *position)?;
+                write_angle(&mut buffer, *yaw)?;
+                write_angle(&mut buffer, *pitch)?;
+                buffer.write_bool(*on_ground)?;
+                Ok(buffer)
+            }
+            EntityUpdate::Remove(ids) => {
+                buffer.write_var_i32(packet_id(EntityPacket::Remove, protocol)?)?;
+                if protocol == 755 {
+                    return match ids.as_slice() {
+                        [id] => buffer.write_var_i32(*id).map(|_| buffer),
+                        _ => Err(Error::Other("Unable to write entity removal => Protocol 755 removes exactly one entity per packet".to_string()))
+                    }
+                }
+                buffer.write_var_i32(ids.len() as i32)?;
+                ids.iter().try_for_each(|id| buffer.write_var_i32(*id))?;
+                Ok(buffer)
+            }
+            EntityUpdate::Respawn => Err(Error::Other("Unable to write respawn => Only received respawns are tracked".to_string()))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackedEntity {
+    pub uuid: Uuid,
+    pub kind: EntityKind,
+    pub position: (f64, f64, f64),
+    pub yaw: f32,
+    pub pitch: f32,
+    pub on_ground: bool
+}
+
+impl TrackedEntity {
+    pub fn distance_squared(&self, (x, y, z): (f64, f64, f64)) -> f64 {
+        (self.position.0 - x).powi(2) + (self.position.1 - y).powi(2) + (self.position.2 - z).powi(2)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EntityTracker {
+    protocol: i32,
+    entities: HashMap<i32, TrackedEntity>
+}
+
+impl EntityTracker {
+    pub fn new(version: &VersionInfo) -> Self {
+        Self {
+            protocol: version.protocol,
+            entities: HashMap::new()
+        }
+    }
+
+    pub fn protocol(&self) -> i32 {
+        self.protocol
+    }
+
+    pub fn entities(&self) -> &HashMap<i32, TrackedEntity> {
+        &self.entities
+    }
+
+    pub fn get(&self, id: i32) -> Option<&TrackedEntity> {
+        self.entities.get(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    pub fn nearest(&self, kind: EntityKind, origin: (f64, f64, f64)) -> Option<(i32, &TrackedEntity)> {
+        self.entities
+            .iter()
+            .filter(|(_, entity)| entity.kind == kind)
+            .min_by(|(_, first), (_, second)| first.distance_squared(origin).total_cmp(&second.distance_squared(origin)))
+            .map(|(id, entity)| (*id, entity))
+    }
+
+    pub fn handle(&mut self, packet: &mut Buffer) -> Result<bool, Error> {
+        let position = packet.position();
+        let update = EntityUpdate::read_versioned(packet, self.protocol);
+        packet.set_position(position);
+
+        match update? {
+            Some(update) => {
+                self.apply(update);
+                Ok(true)
+            }
+            None => Ok(false)
+        }
+    }
+
+    pub fn apply(&mut self, update: EntityUpdate) {
+        match update {
+            EntityUpdate::Spawn {
+                id,
+                uuid,
+                kind,
+                position,
+                yaw,
+                pitch
+            } => {
+                self.entities.insert(
+                    id,
+                    TrackedEntity {
+                        uuid,
+                        kind,
+                        position,
+                        yaw,
+                        pitch,
+                        on_ground: false
+                    }
+                );
+            }
+            EntityUpdate::Move { id, delta, rotation, on_ground } => {
+                if let Some(entity) = self.entities.get_mut(&id) {
+                    if let Some((x, y, z)) = delta {
+                        let (position_x, position_y, position_z) = entity.position;
+                        entity.position = (apply_delta(position_x, x), apply_delta(position_y, y), apply_delta(position_z, z));
+                    }
+                    if let Some((yaw, pitch)) = rotation {
+                        entity.yaw = yaw;
+                        entity.pitch = pitch;
+                    }
+                    entity.on_ground = on_ground;
+                }
+            }
+            EntityUpdate::Teleport {
+                id,
+                position,
+                yaw,
+                pitch,
+                on_ground
+            } => {
+                if let Some(entity) = self.entities.get_mut(&id) {
+                    entity.position = position;
+                    entity.yaw = yaw;
+                    entity.pitch = pitch;
+                    entity.on_ground = on_ground;
+                }
+            }
+            EntityUpdate::Remove(ids) => {
+                for id in ids {
+                    self.entities.remove(&id);
+                }
+            }
+            EntityUpdate::Respawn => self.entities.clear()
+        }
+    }
+}
+
+// Everything here is server authoritative and sent again after reconnecting
+impl SessionState for EntityTracker {
+    type Snapshot = ();
+
+    fn snapshot(&self) {}
+
+    fn restore(&mut self, _snapshot: ()) {}
+
+    fn reset_for_reconnect(&mut self) {
+        self.entities.clear();
+    }
+}
@@ -0,0 +1,411 @@
+use crate::{
+    components::Component,
+    network::{
+        buffer::Buffer,
+        connection::Connection,
+        convenience::{read_component, write_component},
+        tracker::SessionState,
+        types::Slot,
+        versions::VersionInfo,
+        ByteOrder, Error
+    }
+};
+
+pub const PLAYER_WINDOW: u8 = 0;
+pub const PLAYER_INVENTORY_SIZE: usize = 46;
+pub const CARRIED_SLOT: i16 = -1;
+pub const OUTSIDE_SLOT: i16 = -999;
+
+pub const CLICK_PICKUP: i32 = 0;
+
+// Without an item registry every item stacks up to 64, the server corrects it with Set Slot
+const MAX_STACK_SIZE: i32 = 64;
+// Windows end with the main inventory and the hotbar, slots 9 to 44 of the player inventory
+const SHARED_SLOTS: usize = 36;
+const SHARED_OFFSET: usize = 9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InventoryPacket {
+    OpenScreen,
+    SetContent,
+    SetSlot,
+    Close
+}
+
+const PACKETS: [InventoryPacket; 4] = [InventoryPacket::OpenScreen, InventoryPacket::SetContent, InventoryPacket::SetSlot, InventoryPacket::Close];
+
+// Older versions have no state id to click with
+fn packet_ids(protocol: i32) -> Option<[i32; 4]> {
+    Some(match protocol {
+        756..=758 => [0x2E, 0x14, 0x16, 0x13],
+        759 => [0x2B, 0x11, 0x13, 0x10],
+        760 => [0x2D, 0x11, 0x13, 0x10],
+        761 => [0x2C, 0x10, 0x12, 0x0F],
+        762..=763 => [0x30, 0x12, 0x14, 0x11],
+        764..=765 => [0x31, 0x13, 0x15, 0x12],
+        766..=767 => [0x33, 0x13, 0x15, 0x12],
+        _ => return None
+    })
+}
+
+fn packet_id(packet: InventoryPacket, protocol: i32) -> Result<i32, Error> {
+    let ids = packet_ids(protocol).ok_or_else(|| Error::Other(format!("Unable to handle {:?} => Protocol {} isn't supported", packet, protocol)))?;
+    Ok(ids[PACKETS.iter().position(|candidate| *candidate == packet).unwrap()])
+}
+
+fn click_id(protocol: i32) -> Result<i32, Error> {
+    Ok(match protocol {
+        756..=758 => 0x08,
+        759 => 0x0A,
+        760 => 0x0B,
+        761 => 0x0A,
+        762..=763 => 0x0B,
+        764..=765 => 0x0D,
+        766..=767 => 0x0E,
+        _ => return Err(Error::Other(format!("Unable to click => Protocol {} isn't supported", protocol)))
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InventoryUpdate {
+    Open { window_id: u8, kind: i32, title: Component },
+    Content { window_id: u8, state_id: i32, slots: Vec<Slot>, carried: Slot },
+    Slot { window_id: i8, state_id: i32, slot: i16, item: Slot },
+    Close { window_id: u8 }
+}
+
+impl InventoryUpdate {
+    pub fn read_versioned(buffer: &mut Buffer, protocol: i32) -> Result<Option<Self>, Error> {
+        let ids = packet_ids(protocol).ok_or_else(|| Error::Other(format!("Unable to handle inventory packets => Protocol {} isn't supported", protocol)))?;
+        let id = buffer.read_var_i32()?;
+        let packet = match PACKETS.iter().zip(ids).find(|(_, packet_id)| *packet_id == id) {
+            Some((packet, _)) => *packet,
+            None => return Ok(None)
+        };
+
+        Ok(Some(match packet {
+            InventoryPacket::OpenScreen => {
+                let offset = buffer.position();
+                let window_id = buffer.read_var_i32()?;
+                InventoryUpdate::Open {
+                    window_id: u8::try_from(window_id).map_err(|_| {
+                        Error::Decode {
+                            offset,
+                            reason: format!("Invalid window id {}", window_id)
+                        }
+                    })?,
+                    kind: buffer.read_var_i32()?,
+                    title: read_component(buffer, protocol)?
+                }
+            }
+            InventoryPacket::SetContent => {
+                let window_id = buffer.read_u8()?;
+                let state_id = buffer.read_var_i32()?;
+                let count = buffer.read_length()?;
+                InventoryUpdate::Content {
+                    window_id,
+                    state_id,
+                    slots: (0..count).map(|_| Slot::read_versioned(buffer, protocol)).collect::<Result<_, _>>()?,
+                    carried: Slot::read_versioned(buffer, protocol)?
+                }
+            }
+            InventoryPacket::SetSlot => {
+                InventoryUpdate::Slot {
+                    window_id: buffer.read_i8()?,
+                    state_id: buffer.read_var_i32()?,
+                    slot: buffer.read_i16()?,
+                    item: Slot::read_versioned(buffer, protocol)?
+                }
+            }
+            InventoryPacket::Close => InventoryUpdate::Close { window_id: buffer.read_u8()? }
+        }))
+    }
+
+    pub fn write_versioned(&self, protocol: i32) -> Result<Buffer, Error> {
+        let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        match self {
+            InventoryUpdate::Open { window_id, kind, title } => {
+                buffer.write_var_i32(packet_id(InventoryPacket::OpenScreen, protocol)?)?;
+                buffer.write_var_i32(*window_id as i32)?;
+                buffer.write_var_i32(*kind)?;
+                write_component(buffer, title, protocol)
+            }
+            InventoryUpdate::Content {
+                window_id,
+                state_id,
+                slots,
+                carried
+            } => {
+                buffer.write_var_i32(packet_id(InventoryPacket::SetContent, protocol)?)?;
+                buffer.write_u8(*window_id)?;
+                buffer.write_var_i32(*state_id)?;
+                buffer.write_var_i32(slots.len() as i32)?;
+                let buffer = slots.iter().try_fold(buffer, |buffer, slot| slot.write_versioned(buffer, protocol))?;
+                carried.write_versioned(buffer, protocol)
+            }
+            InventoryUpdate::Slot { window_id, state_id, slot, item } => {
+                buffer.write_var_i32(packet_id(InventoryPacket::SetSlot, protocol)?)?;
+                buffer.write_i8(*window_id)?;
+                buffer.write_var_i32(*state_id)?;
+                buffer.write_i16(*slot)?;
+                item.write_versioned(buffer, protocol)
+            }
+            InventoryUpdate::Close { window_id } => {
+                buffer.write_var_i32(packet_id(InventoryPacket::Close, protocol)?)?;
+                buffer.write_u8(*window_id)?;
+                Ok(buffer)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Window {
+    pub id: u8,
+    pub kind: i32,
+    pub title: Component,
+    pub slots: Vec<Slot>
+}
+
+impl Window {
+    pub fn container_size(&self) -> usize {
+        self.slots.len().saturating_sub(SHARED_SLOTS)
+    }
+}
+
+fn pickup(slot: &Slot, carried: &Slot, button: i8) -> (Slot, Slot) {
+    match (slot.is_empty(), carried.is_empty()) {
+        (true, true) => (slot.clone(), carried.clone()),
+        (true, false) => {
+            let amount = if button == 0 { carried.count } else { 1 };
+            (carried.clone().with_count(amount), carried.clone().with_count(carried.count - amount))
+        }
+        (false, true) => {
+            let amount = if button == 0 { slot.count } else { (slot.count + 1) / 2 };
+            (slot.clone().with_count(slot.count - amount), slot.clone().with_count(amount))
+        }
+        (false, false) if slot.is_same_item(carried) => {
+            let amount = if button == 0 { carried.count } else { 1 }.min(MAX_STACK_SIZE - slot.count).max(0);
+            (slot.clone().with_count(slot.count + amount), carried.clone().with_count(carried.count - amount))
+        }
+        (false, false) => (carried.clone(), slot.clone())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InventorySnapshot {
+    pub inventory: Vec<Slot>,
+    pub window: Option<Window>,
+    pub carried: Slot
+}
+
+#[derive(Debug, Clone)]
+pub struct InventoryTracker {
+    protocol: i32,
+    inventory: Vec<Slot>,
+    window: Option<Window>,
+    carried: Slot,
+    state_id: i32
+}
+
+impl InventoryTracker {
+    pub fn new(version: &VersionInfo) -> Self {
+        Self {
+            protocol: version.protocol,
+            inventory: vec![Slot::empty(); PLAYER_INVENTORY_SIZE],
+            window: None,
+            carried: Slot::empty(),
+            state_id: 0
+        }
+    }
+
+    pub fn protocol(&self) -> i32 {
+        self.protocol
+    }
+
+    pub fn inventory(&self) -> &[Slot] {
+        &self.inventory
+    }
+
+    pub fn window(&self) -> Option<&Window> {
+        self.window.as_ref()
+    }
+
+    pub fn carried(&self) -> &Slot {
+        &self.carried
+    }
+
+    pub fn state_id(&self) -> i32 {
+        self.state_id
+    }
+
+    pub fn slots(&self) -> &[Slot] {
+        match &self.window {
+            Some(window) => &window.slots,
+            None => &self.inventory
+        }
+    }
+
+    fn window_id(&self) -> u8 {
+        self.window.as_ref().map(|window| window.id).unwrap_or(PLAYER_WINDOW)
+    }
+
+    fn set_slot(&mut self, window_id: u8, slot: usize, item: Slot) {
+        match &mut self.window {
+            Some(window) if window.id == window_id => {
+                let container_size = window.container_size();
+                if let Some(target) = window.slots.get_mut(slot) {
+                    *target = item.clone();
+                    if slot >= container_size {
+                        self.inventory[SHARED_OFFSET + slot - container_size] = item;
+                    }
+                }
+            }
+            _ if window_id == PLAYER_WINDOW => {
+                if let Some(target) = self.inventory.get_mut(slot) {
+                    *target = item.clone();
+                }
+                if let Some(window) = self.window.as_mut().filter(|_| (SHARED_OFFSET..SHARED_OFFSET + SHARED_SLOTS).contains(&slot)) {
+                    let container_size = window.container_size();
+                    if let Some(target) = window.slots.get_mut(container_size + slot - SHARED_OFFSET) {
+                        *target = item;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn handle(&mut self, packet: &mut Buffer) -> Result<bool, Error> {
+        let position = packet.position();
+        let update = InventoryUpdate::read_versioned(packet, self.protocol);
+        packet.set_position(position);
+
+        match update? {
+            Some(update) => {
+                self.apply(update);
+                Ok(true)
+            }
+            None => Ok(false)
+        }
+    }
+
+    pub fn apply(&mut self, update: InventoryUpdate) {
+        match update {
+            InventoryUpdate::Open { window_id, kind, title } => {
+                self.window = Some(Window {
+                    id: window_id,
+                    kind,
+                    title,
+                    slots: Vec::new()
+                });
+            }
+            InventoryUpdate::Content {
+                window_id,
+                state_id,
+                slots,
+                carried
+            } => {
+                self.state_id = state_id;
+                self.carried = carried;
+                if let Some(window) = self.window.as_mut().filter(|window| window.id == window_id) {
+                    window.slots = vec![Slot::empty(); slots.len()];
+                }
+                for (index, slot) in slots.into_iter().enumerate() {
+                    self.set_slot(window_id, index, slot);
+                }
+            }
+            InventoryUpdate::Slot {
+                window_id: -1,
+                slot: CARRIED_SLOT,
+                state_id,
+                item
+            } => {
+                self.state_id = state_id;
+                self.carried = item;
+            }
+            // Window -2 always targets the player inventory
+            InventoryUpdate::Slot { window_id, state_id, slot, item } => {
+                self.state_id = state_id;
+                if let Ok(slot) = usize::try_from(slot) {
+                    self.set_slot(if window_id == -2 { PLAYER_WINDOW } else { window_id as u8 }, slot, item);
+                }
+            }
+            InventoryUpdate::Close { window_id } => {
+                if self.window_id() == window_id {
+                    self.window = None;
+                }
+            }
+        }
+    }
+
+    pub fn click<'a, S>(&mut self, connection: &mut impl Connection<'a, S>, slot: i16, button: i8, mode: i32) -> Result<(), Error> {
+        if mode != CLICK_PICKUP {
+            return Err(Error::Other(format!("Unable to click => Mode {} isn't supported", mode)))
+        }
+        if button != 0 && button != 1 {
+            return Err(Error::Other(format!("Unable to click => Button {} doesn't exist for the pickup mode", button)))
+        }
+
+        let (changed, carried) = match slot {
+            OUTSIDE_SLOT => (None, self.carried.clone().with_count(if button == 0 { 0 } else { self.carried.count - 1 })),
+            slot => {
+                let current = usize::try_from(slot)
+                    .ok()
+                    .and_then(|index| self.slots().get(index))
+                    .ok_or_else(|| Error::Other(format!("Unable to click => Window {} has no slot {}", self.window_id(), slot)))?;
+                let (item, carried) = pickup(current, &self.carried, button);
+                ((item != *current).then_some((slot, item)), carried)
+            }
+        };
+
+        let protocol = connection.require_protocol_version("click")?;
+        let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        buffer.write_var_i32(click_id(protocol)?)?;
+        buffer.write_u8(self.window_id())?;
+        buffer.write_var_i32(self.state_id)?;
+        buffer.write_i16(slot)?;
+        buffer.write_i8(button)?;
+        buffer.write_var_i32(mode)?;
+        buffer.write_var_i32(changed.is_some() as i32)?;
+        if let Some((slot, item)) = &changed {
+            buffer.write_i16(*slot)?;
+            buffer = item.write_versioned(buffer, protocol)?;
+        }
+        connection.write(carried.write_versioned(buffer, protocol)?)?;
+
+        if let Some((slot, item)) = changed {
+            self.set_slot(self.window_id(), slot as usize, item);
+        }
+        self.carried = carried;
+        Ok(())
+    }
+}
+
+impl SessionState for InventoryTracker {
+    type Snapshot = InventorySnapshot;
+
+    fn reset_for_reconnect(&mut self) {
+        self.inventory = vec![Slot::empty(); PLAYER_INVENTORY_SIZE];
+        self.window = None;
+        self.carried = Slot::empty();
+        self.state_id = 0;
+    }
+
+    fn snapshot(&self) -> InventorySnapshot {
+        InventorySnapshot {
+            inventory: self.inventory.clone(),
+            window: self.window.clone(),
+            carried: self.carried.clone()
+        }
+    }
+
+    // Only the player inventory is shown until the server sends it again, windows close when disconnecting
+    fn restore(&mut self, snapshot: InventorySnapshot) {
+        if self.state_id != 0 || self.window.is_some() {
+            return
+        }
+
+        self.inventory = snapshot.inventory;
+    }
+}
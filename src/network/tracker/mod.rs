@@ -0,0 +1,27 @@
+mod block;
+mod client_state;
+mod entity;
+mod inventory;
+mod server_info;
+mod tab_list;
+
+pub use block::{BlockRegion, BlockWatcher};
+pub use client_state::{Abilities, ClientStateMirror, Difficulty, StateChange, ABILITY_ALLOW_FLYING, ABILITY_FLYING, ABILITY_INSTANT_BREAK, ABILITY_INVULNERABLE};
+pub use entity::{EntityKind, EntityTracker, EntityUpdate, TrackedEntity};
+pub use inventory::{InventorySnapshot, InventoryTracker, InventoryUpdate, Window, CARRIED_SLOT, CLICK_PICKUP, OUTSIDE_SLOT, PLAYER_INVENTORY_SIZE, PLAYER_WINDOW};
+pub use server_info::{ServerData, ServerInfo};
+pub use tab_list::{
+    PlayerEntry, PlayerInfoEntry, PlayerInfoPacket, TabList, ACTION_ADD_PLAYER, ACTION_INITIALIZE_CHAT, ACTION_UPDATE_DISPLAY_NAME, ACTION_UPDATE_GAME_MODE, ACTION_UPDATE_LATENCY,
+    ACTION_UPDATE_LISTED
+};
+
+// Reconnecting resets server authoritative data, snapshots carry over what's only kept as a hint
+pub trait SessionState {
+    type Snapshot;
+
+    fn reset_for_reconnect(&mut self);
+
+    fn snapshot(&self) -> Self::Snapshot;
+
+    fn restore(&mut self, snapshot: Self::Snapshot);
+}
@@ -0,0 +1,260 @@
+use std::{ops::RangeInclusive, sync::Arc};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::{
+    components::Component,
+    network::{
+        buffer::Buffer,
+        connection::Readable,
+        convenience::{read_component, write_component},
+        decode::{DecodeMode, DecodeReport},
+        plugin::{BrandMessage, PluginMessage, BRAND_CHANNEL},
+        tracker::SessionState,
+        versions::VersionInfo,
+        ByteOrder, Error, PacketState
+    }
+};
+
+const ICON_PREFIX: &str = "data:image/png;base64,";
+
+// 1.19.4 sends the MOTD unconditionally and the icon as raw bytes instead of a data URL
+const ICON_BYTES_PROTOCOL: i32 = 762;
+
+// The secure chat flag was added with 1.19.2 and moved to Login (play) with 1.20.5
+const SECURE_CHAT_PROTOCOLS: RangeInclusive<i32> = 760..=765;
+
+// Server Data was added to play with 1.19, plugin messages are also sent in configuration since 1.20.2
+fn packet_ids(state: PacketState, protocol: i32) -> Option<(i32, Option<i32>)> {
+    Some(match (state, protocol) {
+        (PacketState::Play, 754) => (0x17, None),
+        (PacketState::Play, 755..=758) => (0x18, None),
+        (PacketState::Play, 759) => (0x15, Some(0x3F)),
+        (PacketState::Play, 760) => (0x16, Some(0x42)),
+        (PacketState::Play, 761) => (0x15, Some(0x41)),
+        (PacketState::Play, 762..=763) => (0x17, Some(0x45)),
+        (PacketState::Play, 764) => (0x18, Some(0x47)),
+        (PacketState::Play, 765) => (0x18, Some(0x49)),
+        (PacketState::Play, 766..=767) => (0x19, Some(0x4B)),
+        (PacketState::Configuration, 764..=765) => (0x00, None),
+        (PacketState::Configuration, 766..=767) => (0x01, None),
+        _ => return None
+    })
+}
+
+fn unsupported(protocol: i32) -> Error {
+    Error::Other(format!("Unable to handle server data => Protocol {} doesn't send it", protocol))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerData {
+    pub motd: Option<Component>,
+    pub icon: Option<Vec<u8>>,
+    pub enforces_secure_chat: bool
+}
+
+impl ServerData {
+    pub fn read_versioned(buffer: &mut Buffer, protocol: i32) -> Result<Self, Error> {
+        let motd = match protocol >= ICON_BYTES_PROTOCOL || buffer.read_bool()? {
+            true => Some(read_component(buffer, protocol)?),
+            false => None
+        };
+
+        let icon = match buffer.read_bool()? {
+            true if protocol >= ICON_BYTES_PROTOCOL => Some(buffer.read_byte_array()?),
+            true => {
+                let offset = buffer.position();
+                let icon = buffer.read_string()?;
+                Some(STANDARD.decode(icon.strip_prefix(ICON_PREFIX).unwrap_or(&icon).replace('\n', "")).map_err(|error| {
+                    Error::Decode {
+                        offset,
+                        reason: format!("Invalid server icon => {}", error)
+                    }
+                })?)
+            }
+            false => None
+        };
+
+        // Chat previews only existed in 1.19 and 1.19.2
+        if protocol <= 760 {
+            buffer.read_bool()?;
+        }
+        let enforces_secure_chat = SECURE_CHAT_PROTOCOLS.contains(&protocol) && buffer.read_bool()?;
+        Ok(Self { motd, icon, enforces_secure_chat })
+    }
+
+    pub fn write_versioned(&self, protocol: i32) -> Result<Buffer, Error> {
+        let (_, id) = packet_ids(PacketState::Play, protocol).ok_or_else(|| unsupported(protocol))?;
+        let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        buffer.write_var_i32(id.ok_or_else(|| unsupported(protocol))?)?;
+
+        match &self.motd {
+            Some(motd) if protocol >= ICON_BYTES_PROTOCOL => buffer = write_component(buffer, motd, protocol)?,
+            None if protocol >= ICON_BYTES_PROTOCOL => buffer = write_component(buffer, &Component::text(""), protocol)?,
+            motd => {
+                buffer.write_bool(motd.is_some())?;
+                if let Some(motd) = motd {
+                    buffer = write_component(buffer, motd, protocol)?;
+                }
+            }
+        }
+
+        buffer.write_bool(self.icon.is_some())?;
+        match &self.icon {
+            Some(icon) if protocol >= ICON_BYTES_PROTOCOL => buffer.write_byte_array(icon)?,
+            Some(icon) => buffer.write_string(format!("{}{}", ICON_PREFIX, STANDARD.encode(icon)))?,
+            None => {}
+        }
+
+        if protocol <= 760 {
+            buffer.write_bool(false)?;
+        }
+        if SECURE_CHAT_PROTOCOLS.contains(&protocol) {
+            buffer.write_bool(self.enforces_secure_chat)?;
+        }
+        Ok(buffer)
+    }
+}
+
+type BrandCallback = Box<dyn FnMut(Option<&str>, &str) + Send>;
+type ServerDataCallback = Box<dyn FnMut(Option<&ServerData>, &ServerData) + Send>;
+
+pub struct ServerInfo {
+    protocol: i32,
+    brand: Option<String>,
+    server_data: Option<ServerData>,
+    on_brand: Option<BrandCallback>,
+    on_server_data: Option<ServerDataCallback>,
+    decode_mode: DecodeMode,
+    decode_report: Arc<DecodeReport>
+}
+
+impl ServerInfo {
+    pub fn new(version: &VersionInfo) -> Self {
+        Self {
+            protocol: version.protocol,
+            brand: None,
+            server_data: None,
+            on_brand: None,
+            on_server_data: None,
+            decode_mode: DecodeMode::Strict,
+            decode_report: Arc::new(DecodeReport::new())
+        }
+    }
+
+    pub fn on_brand(self, on_brand: impl FnMut(Option<&str>, &str) + Send + 'static) -> Self {
+        Self {
+            on_brand: Some(Box::new(on_brand)),
+            ..self
+        }
+    }
+
+    pub fn on_server_data(self, on_server_data: impl FnMut(Option<&ServerData>, &ServerData) + Send + 'static) -> Self {
+        Self {
+            on_server_data: Some(Box::new(on_server_data)),
+            ..self
+        }
+    }
+
+    // Usually the mode and report of the connection the packets are read from
+    pub fn decoding(self, decode_mode: DecodeMode, decode_report: Arc<DecodeReport>) -> Self {
+        Self { decode_mode, decode_report, ..self }
+    }
+
+    pub fn protocol(&self) -> i32 {
+        self.protocol
+    }
+
+    pub fn brand(&self) -> Option<&str> {
+        self.brand.as_deref()
+    }
+
+    pub fn server_data(&self) -> Option<&ServerData> {
+        self.server_data.as_ref()
+    }
+
+    pub fn motd(&self) -> Option<&Component> {
+        self.server_data.as_ref().and_then(|server_data| server_data.motd.as_ref())
+    }
+
+    pub fn icon(&self) -> Option<&[u8]> {
+        self.server_data.as_ref().and_then(|server_data| server_data.icon.as_deref())
+    }
+
+    // Servers before 1.19.1 never enforce secure chat
+    pub fn enforces_secure_chat(&self) -> bool {
+        self.server_data.as_ref().is_some_and(|server_data| server_data.enforces_secure_chat)
+    }
+
+    // Proxies put their own name in front of the brand
+    pub fn is_modded_brand(&self) -> bool {
+        self.brand.as_deref().is_some_and(|brand| !brand.eq_ignore_ascii_case("vanilla"))
+    }
+
+    pub fn handle(&mut self, state: PacketState, packet: &mut Buffer) -> Result<bool, Error> {
+        let Some((plugin_message, server_data)) = packet_ids(state, self.protocol) else {
+            return Ok(false)
+        };
+
+        let position = packet.position();
+        let handled = self.read(state, packet, plugin_message, server_data);
+        packet.set_position(position);
+        handled
+    }
+
+    fn read(&mut self, state: PacketState, packet: &mut Buffer, plugin_message: i32, server_data: Option<i32>) -> Result<bool, Error> {
+        let id = packet.read_var_i32()?;
+        if Some(id) == server_data {
+            let server_data = ServerData::read_versioned(packet, self.protocol)?;
+            self.decode_mode.check_trailing(packet, state, id, &self.decode_report)?;
+            self.apply_server_data(server_data);
+            return Ok(true)
+        }
+        if id != plugin_message {
+            return Ok(false)
+        }
+
+        let message = PluginMessage::read(packet)?;
+        if message.channel.to_string() != BRAND_CHANNEL {
+            return Ok(false)
+        }
+        self.apply_brand(message.decode::<BrandMessage>()?.0);
+        Ok(true)
+    }
+
+    pub fn apply_brand(&mut self, brand: String) {
+        if self.brand.as_ref() == Some(&brand) {
+            return
+        }
+
+        if let Some(on_brand) = &mut self.on_brand {
+            on_brand(self.brand.as_deref(), &brand);
+        }
+        self.brand = Some(brand);
+    }
+
+    pub fn apply_server_data(&mut self, server_data: ServerData) {
+        if self.server_data.as_ref() == Some(&server_data) {
+            return
+        }
+
+        if let Some(on_server_data) = &mut self.on_server_data {
+            on_server_data(self.server_data.as_ref(), &server_data);
+        }
+        self.server_data = Some(server_data);
+    }
+}
+
+// Everything here is server authoritative and sent again after reconnecting
+impl SessionState for ServerInfo {
+    type Snapshot = ();
+
+    fn snapshot(&self) {}
+
+    fn restore(&mut self, _snapshot: ()) {}
+
+    fn reset_for_reconnect(&mut self) {
+        self.brand = None;
+        self.server_data = None;
+    }
+}
@@ -0,0 +1,453 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::{
+    components::Component,
+    network::{
+        buffer::Buffer,
+        connection::{Readable, Writable},
+        convenience::{read_component, write_component},
+        tracker::SessionState,
+        types::{GameMode, MAX_USERNAME_LENGTH},
+        versions::VersionInfo,
+        ByteOrder, Error
+    },
+    profile::Property
+};
+
+pub const ACTION_ADD_PLAYER: u8 = 0x01;
+pub const ACTION_INITIALIZE_CHAT: u8 = 0x02;
+pub const ACTION_UPDATE_GAME_MODE: u8 = 0x04;
+pub const ACTION_UPDATE_LISTED: u8 = 0x08;
+pub const ACTION_UPDATE_LATENCY: u8 = 0x10;
+pub const ACTION_UPDATE_DISPLAY_NAME: u8 = 0x20;
+
+const LEGACY_ADD_ACTIONS: u8 = ACTION_ADD_PLAYER | ACTION_UPDATE_GAME_MODE | ACTION_UPDATE_LISTED | ACTION_UPDATE_LATENCY | ACTION_UPDATE_DISPLAY_NAME;
+
+// Player Info Update replaced the action enum of Player Info with a bitfield in 1.19.3
+const BITFIELD_PROTOCOL: i32 = 761;
+
+// Player Info Remove was split off with 1.19.3
+fn packet_ids(protocol: i32) -> Option<(i32, Option<i32>)> {
+    match protocol {
+        754 => Some((0x32, None)),
+        755..=758 => Some((0x36, None)),
+        759 => Some((0x34, None)),
+        760 => Some((0x37, None)),
+        761 => Some((0x36, Some(0x35))),
+        762..=763 => Some((0x3A, Some(0x39))),
+        764 => Some((0x3C, Some(0x3B))),
+        765..=767 => Some((0x3E, Some(0x3D))),
+        _ => None
+    }
+}
+
+fn unsupported(protocol: i32) -> Error {
+    Error::Other(format!("Unable to handle player info => Protocol {} isn't supported", protocol))
+}
+
+fn read_game_mode(buffer: &mut Buffer) -> Result<GameMode, Error> {
+    let offset = buffer.position();
+    let id = buffer.read_var_i32()?;
+    u8::try_from(id).ok().and_then(GameMode::from_id).ok_or_else(|| {
+        Error::Decode {
+            offset,
+            reason: format!("Invalid game mode {}", id)
+        }
+    })
+}
+
+// Chat sessions and the 1.19 signature data are skipped
+fn skip_signature_data(buffer: &mut Buffer, with_session_id: bool) -> Result<(), Error> {
+    if buffer.read_bool()? {
+        if with_session_id {
+            Uuid::read(buffer)?;
+        }
+        buffer.read_i64()?;
+        for _ in 0..2 {
+            let length = buffer.read_length()?;
+            buffer.read_bytes(length)?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlayerInfoEntry {
+    pub uuid: Uuid,
+    pub name: Option<String>,
+    pub properties: Vec<Property>,
+    pub game_mode: Option<GameMode>,
+    pub listed: Option<bool>,
+    pub latency: Option<i32>,
+    pub display_name: Option<Option<Component>>
+}
+
+impl PlayerInfoEntry {
+    pub fn new(uuid: Uuid) -> Self {
+        Self { uuid, ..Self::default() }
+    }
+
+    fn read_actions(buffer: &mut Buffer, actions: u8, protocol: i32) -> Result<Self, Error> {
+        let mut entry = Self::new(Uuid::read(buffer)?);
+        if actions & ACTION_ADD_PLAYER != 0 {
+            entry.name = Some(buffer.read_string_bounded(MAX_USERNAME_LENGTH)?);
+            entry.properties = Vec::read(buffer)?;
+        }
+        if actions & ACTION_INITIALIZE_CHAT != 0 {
+            skip_signature_data(buffer, true)?;
+        }
+        if actions & ACTION_UPDATE_GAME_MODE != 0 {
+            entry.game_mode = Some(read_game_mode(buffer)?);
+        }
+        if actions & ACTION_UPDATE_LISTED != 0 {
+            entry.listed = Some(buffer.read_bool()?);
+        }
+        if actions & ACTION_UPDATE_LATENCY != 0 {
+            entry.latency = Some(buffer.read_var_i32()?);
+        }
+        if actions & ACTION_UPDATE_DISPLAY_NAME != 0 {
+            entry.display_name = Some(if buffer.read_bool()? { Some(read_component(buffer, protocol)?) } else { None });
+        }
+        Ok(entry)
+    }
+
+    fn write_actions(&self, buffer: Buffer, actions: u8, protocol: i32) -> Result<Buffer, Error> {
+        let mut buffer = self.uuid.write(buffer)?;
+        if actions & ACTION_ADD_PLAYER != 0 {
+            buffer.write_string_bounded(self.name.as_deref().unwrap_or_default(), MAX_USERNAME_LENGTH)?;
+            buffer = self.properties.write(buffer)?;
+        }
+        if actions & ACTION_INITIALIZE_CHAT != 0 {
+            buffer.write_bool(false)?;
+        }
+        if actions & ACTION_UPDATE_GAME_MODE != 0 {
+            buffer.write_var_i32(self.game_mode.unwrap_or(GameMode::Survival).id() as i32)?;
+        }
+        if actions & ACTION_UPDATE_LISTED != 0 {
+            buffer.write_bool(self.listed.unwrap_or(true))?;
+        }
+        if actions & ACTION_UPDATE_LATENCY != 0 {
+            buffer.write_var_i32(self.latency.unwrap_or_default())?;
+        }
+        if actions & ACTION_UPDATE_DISPLAY_NAME != 0 {
+            let display_name = self.display_name.as_ref().and_then(Option::as_ref);
+            buffer.write_bool(display_name.is_some())?;
+            if let Some(display_name) = display_name {
+                buffer = write_component(buffer, display_name, protocol)?;
+            }
+        }
+        Ok(buffer)
+    }
+
+    // The legacy add action has the same fields in another order
+    fn read_legacy_add(buffer: &mut Buffer, protocol: i32) -> Result<Self, Error> {
+        let mut entry = Self::new(Uuid::read(buffer)?);
+        entry.name = Some(buffer.read_string_bounded(MAX_USERNAME_LENGTH)?);
+        entry.properties = Vec::read(buffer)?;
+        entry.game_mode = Some(read_game_mode(buffer)?);
+        entry.latency = Some(buffer.read_var_i32()?);
+        entry.display_name = Some(if buffer.read_bool()? { Some(read_component(buffer, protocol)?) } else { None });
+        entry.listed = Some(true);
+        if protocol >= 759 {
+            skip_signature_data(buffer, false)?;
+        }
+        Ok(entry)
+    }
+
+    fn write_legacy_add(&self, buffer: Buffer, protocol: i32) -> Result<Buffer, Error> {
+        let mut buffer = self.uuid.write(buffer)?;
+        buffer.write_string_bounded(self.name.as_deref().unwrap_or_default(), MAX_USERNAME_LENGTH)?;
+        let mut buffer = self.properties.write(buffer)?;
+        buffer.write_var_i32(self.game_mode.unwrap_or(GameMode::Survival).id() as i32)?;
+        buffer.write_var_i32(self.latency.unwrap_or_default())?;
+        let display_name = self.display_name.as_ref().and_then(Option::as_ref);
+        buffer.write_bool(display_name.is_some())?;
+        if let Some(display_name) = display_name {
+            buffer = write_component(buffer, display_name, protocol)?;
+        }
+        if protocol >= 759 {
+            buffer.write_bool(false)?;
+        }
+        Ok(buffer)
+    }
+}
+
+// Before 1.19.3 every packet had exactly one action
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlayerInfoPacket {
+    Update { actions: u8, entries: Vec<PlayerInfoEntry> },
+    Remove(Vec<Uuid>)
+}
+
+impl PlayerInfoPacket {
+    pub fn is_player_info(id: i32, protocol: i32) -> bool {
+        packet_ids(protocol).is_some_and(|(update, remove)| id == update || Some(id) == remove)
+    }
+
+    pub fn read_versioned(buffer: &mut Buffer, protocol: i32) -> Result<Option<Self>, Error> {
+        let (update, remove) = packet_ids(protocol).ok_or_else(|| unsupported(protocol))?;
+        let id = buffer.read_var_i32()?;
+        if Some(id) == remove {
+            return Ok(Some(PlayerInfoPacket::Remove(Vec::read(buffer)?)))
+        }
+        if id != update {
+            return Ok(None)
+        }
+
+        if protocol >= BITFIELD_PROTOCOL {
+            let actions = buffer.read_u8()?;
+            let count = buffer.read_length()?;
+            let entries = (0..count).map(|_| PlayerInfoEntry::read_actions(buffer, actions, protocol)).collect::<Result<_, _>>()?;
+            return Ok(Some(PlayerInfoPacket::Update { actions, entries }))
+        }
+
+        let offset = buffer.position();
+        let action = buffer.read_var_i32()?;
+        let count = buffer.read_length()?;
+        let actions = match action {
+            0 => LEGACY_ADD_ACTIONS,
+            1 => ACTION_UPDATE_GAME_MODE,
+            2 => ACTION_UPDATE_LATENCY,
+            3 => ACTION_UPDATE_DISPLAY_NAME,
+            4 => return Ok(Some(PlayerInfoPacket::Remove((0..count).map(|_| Uuid::read(buffer)).collect::<Result<_, _>>()?))),
+            action => {
+                return Err(Error::Decode {
+                    offset,
+                    reason: format!("Invalid player info action {}", action)
+                })
+            }
+        };
+
+        let entries = (0..count)
+            .map(|_| {
+                match action {
+                    0 => PlayerInfoEntry::read_legacy_add(buffer, protocol),
+                    _ => PlayerInfoEntry::read_actions(buffer, actions, protocol)
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Some(PlayerInfoPacket::Update { actions, entries }))
+    }
+
+    pub fn write_versioned(&self, protocol: i32) -> Result<Buffer, Error> {
+        let (update, remove) = packet_ids(protocol).ok_or_else(|| unsupported(protocol))?;
+        let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        match (self, remove) {
+            (PlayerInfoPacket::Remove(players), Some(remove)) => {
+                buffer.write_var_i32(remove)?;
+                players.write(buffer)
+            }
+            (PlayerInfoPacket::Remove(players), None) => {
+                buffer.write_var_i32(update)?;
+                buffer.write_var_i32(4)?;
+                players.write(buffer)
+            }
+            (PlayerInfoPacket::Update { actions, entries }, Some(_)) => {
+                buffer.write_var_i32(update)?;
+                buffer.write_u8(*actions)?;
+                buffer.write_var_i32(entries.len() as i32)?;
+                entries.iter().try_fold(buffer, |buffer, entry| entry.write_actions(buffer, *actions, protocol))
+            }
+            (PlayerInfoPacket::Update { actions, entries }, None) => {
+                let action = match *actions {
+                    LEGACY_ADD_ACTIONS => 0,
+                    ACTION_UPDATE_GAME_MODE => 1,
+                    ACTION_UPDATE_LATENCY => 2,
+                    ACTION_UPDATE_DISPLAY_NAME => 3,
+                    actions => {
+                        return Err(Error::Other(format!(
+                            "Unable to write player info => Actions {:#04x} aren't available for protocol {}",
+                            actions, protocol
+                        )))
+                    }
+                };
+
+                buffer.write_var_i32(update)?;
+                buffer.write_var_i32(action)?;
+                buffer.write_var_i32(entries.len() as i32)?;
+                entries.iter().try_fold(buffer, |buffer, entry| {
+                    match action {
+                        0 => entry.write_legacy_add(buffer, protocol),
+                        _ => entry.write_actions(buffer, *actions, protocol)
+                    }
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerEntry {
+    pub uuid: Uuid,
+    pub name: String,
+    pub properties: Vec<Property>,
+    pub game_mode: GameMode,
+    pub latency: i32,
+    pub display_name: Option<Component>,
+    pub listed: bool
+}
+
+impl PlayerEntry {
+    fn new(uuid: Uuid, name: String, properties: Vec<Property>) -> Self {
+        Self {
+            uuid,
+            name,
+            properties,
+            game_mode: GameMode::Survival,
+            latency: 0,
+            display_name: None,
+            listed: false
+        }
+    }
+
+    fn update(&mut self, entry: PlayerInfoEntry) {
+        if let Some(game_mode) = entry.game_mode {
+            self.game_mode = game_mode;
+        }
+        if let Some(listed) = entry.listed {
+            self.listed = listed;
+        }
+        if let Some(latency) = entry.latency {
+            self.latency = latency;
+        }
+        if let Some(display_name) = entry.display_name {
+            self.display_name = display_name;
+        }
+    }
+}
+
+type JoinCallback = Box<dyn FnMut(&PlayerEntry) + Send>;
+type UpdateCallback = Box<dyn FnMut(&PlayerEntry, &PlayerEntry) + Send>;
+
+pub struct TabList {
+    protocol: i32,
+    players: HashMap<Uuid, PlayerEntry>,
+    on_join: Option<JoinCallback>,
+    on_leave: Option<JoinCallback>,
+    on_update: Option<UpdateCallback>
+}
+
+impl TabList {
+    pub fn new(version: &VersionInfo) -> Self {
+        Self {
+            protocol: version.protocol,
+            players: HashMap::new(),
+            on_join: None,
+            on_leave: None,
+            on_update: None
+        }
+    }
+
+    pub fn on_join(self, on_join: impl FnMut(&PlayerEntry) + Send + 'static) -> Self {
+        Self {
+            on_join: Some(Box::new(on_join)),
+            ..self
+        }
+    }
+
+    pub fn on_leave(self, on_leave: impl FnMut(&PlayerEntry) + Send + 'static) -> Self {
+        Self {
+            on_leave: Some(Box::new(on_leave)),
+            ..self
+        }
+    }
+
+    pub fn on_update(self, on_update: impl FnMut(&PlayerEntry, &PlayerEntry) + Send + 'static) -> Self {
+        Self {
+            on_update: Some(Box::new(on_update)),
+            ..self
+        }
+    }
+
+    pub fn protocol(&self) -> i32 {
+        self.protocol
+    }
+
+    pub fn players(&self) -> &HashMap<Uuid, PlayerEntry> {
+        &self.players
+    }
+
+    pub fn get(&self, uuid: &Uuid) -> Option<&PlayerEntry> {
+        self.players.get(uuid)
+    }
+
+    pub fn len(&self) -> usize {
+        self.players.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.players.is_empty()
+    }
+
+    pub fn handle(&mut self, packet: &mut Buffer) -> Result<bool, Error> {
+        let position = packet.position();
+        let player_info = PlayerInfoPacket::read_versioned(packet, self.protocol);
+        packet.set_position(position);
+
+        match player_info? {
+            Some(player_info) => {
+                self.apply(player_info);
+                Ok(true)
+            }
+            None => Ok(false)
+        }
+    }
+
+    pub fn apply(&mut self, packet: PlayerInfoPacket) {
+        match packet {
+            PlayerInfoPacket::Update { actions, entries } => {
+                for entry in entries {
+                    self.update(actions, entry);
+                }
+            }
+            PlayerInfoPacket::Remove(players) => {
+                for uuid in players {
+                    if let (Some(player), Some(on_leave)) = (self.players.remove(&uuid), &mut self.on_leave) {
+                        on_leave(&player);
+                    }
+                }
+            }
+        }
+    }
+
+    fn update(&mut self, actions: u8, mut entry: PlayerInfoEntry) {
+        if let Some(player) = self.players.get_mut(&entry.uuid) {
+            let previous = player.clone();
+            player.update(entry);
+            if let Some(on_update) = self.on_update.as_mut().filter(|_| *player != previous) {
+                on_update(&previous, player);
+            }
+            return
+        }
+
+        if actions & ACTION_ADD_PLAYER == 0 {
+            return
+        }
+
+        let mut player = PlayerEntry::new(entry.uuid, entry.name.take().unwrap_or_default(), std::mem::take(&mut entry.properties));
+        player.update(entry);
+        if let Some(on_join) = &mut self.on_join {
+            on_join(&player);
+        }
+        self.players.insert(player.uuid, player);
+    }
+}
+
+// Everything here is server authoritative and sent again after reconnecting
+impl SessionState for TabList {
+    type Snapshot = ();
+
+    fn snapshot(&self) {}
+
+    fn restore(&mut self, _snapshot: ()) {}
+
+    fn reset_for_reconnect(&mut self) {
+        for (_, player) in self.players.drain() {
+            if let Some(on_leave) = &mut self.on_leave {
+                on_leave(&player);
+            }
+        }
+    }
+}
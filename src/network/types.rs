@@ -0,0 +1,755 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    str::FromStr
+};
+
+use crate::network::{
+    buffer::Buffer,
+    connection::{Readable, VersionedReadable, VersionedWritable, Writable},
+    decode::UnknownVariant,
+    item::ComponentPatch,
+    nbt::Compound,
+    Error
+};
+
+// Exported for enums outside of this crate that are sent as a single byte
+#[macro_export]
+macro_rules! protocol_enum_u8 {
+    ($(#[$meta: meta])* $visibility: vis enum $name: ident { $($variant: ident = $id: literal),* $(,)? }) => {
+        $(#[$meta])*
+        #[repr(u8)]
+        $visibility enum $name {
+            $($variant = $id),*
+        }
+
+        impl $name {
+            pub fn id(&self) -> u8 {
+                *self as u8
+            }
+
+            pub fn from_id(id: u8) -> Option<Self> {
+                match id {
+                    $($id => Some(Self::$variant),)*
+                    _ => None
+                }
+            }
+        }
+
+        impl $crate::network::connection::Writable for $name {
+            fn write(&self, mut buffer: $crate::network::buffer::Buffer) -> Result<$crate::network::buffer::Buffer, $crate::network::Error> {
+                buffer.write_u8(self.id())?;
+                Ok(buffer)
+            }
+        }
+
+        impl $crate::network::connection::Readable for $name {
+            fn read(buffer: &mut $crate::network::buffer::Buffer) -> Result<Self, $crate::network::Error> {
+                let offset = buffer.position();
+                let id = buffer.read_u8()?;
+                Self::from_id(id).ok_or_else(|| $crate::network::Error::Decode {
+                    offset,
+                    reason: format!("Invalid {} {}", stringify!($name), id)
+                })
+            }
+        }
+    };
+}
+
+pub const DAY_LENGTH: i64 = 24000;
+
+pub const MAX_USERNAME_LENGTH: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorldTime {
+    pub age: i64,
+    pub time_of_day: i64
+}
+
+impl WorldTime {
+    // A negative time stops the daylight cycle, vanilla sends -1 instead of -0
+    pub fn new(age: i64, time_of_day: i64, daylight_cycle_frozen: bool) -> Self {
+        let time_of_day = time_of_day.abs();
+        Self {
+            age,
+            time_of_day: match daylight_cycle_frozen {
+                true if time_of_day == 0 => -1,
+                true => -time_of_day,
+                false => time_of_day
+            }
+        }
+    }
+
+    pub fn is_daylight_cycle_frozen(&self) -> bool {
+        self.time_of_day < 0
+    }
+
+    pub fn day_time(&self) -> i64 {
+        self.time_of_day.abs() % DAY_LENGTH
+    }
+
+    pub fn day(&self) -> i64 {
+        self.time_of_day.abs() / DAY_LENGTH
+    }
+}
+
+impl Writable for WorldTime {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        buffer.write_i64(self.age)?;
+        buffer.write_i64(self.time_of_day)?;
+        Ok(buffer)
+    }
+}
+
+impl Readable for WorldTime {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        Ok(Self {
+            age: buffer.read_i64()?,
+            time_of_day: buffer.read_i64()?
+        })
+    }
+}
+
+protocol_enum_u8! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum GameMode {
+        Survival = 0,
+        Creative = 1,
+        Adventure = 2,
+        Spectator = 3
+    }
+}
+
+// The previous game mode uses -1 for none
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteGameMode(pub Option<GameMode>);
+
+impl Writable for ByteGameMode {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        buffer.write_u8(self.0.map(|game_mode| game_mode.id()).unwrap_or(0xFF))?;
+        Ok(buffer)
+    }
+}
+
+impl Readable for ByteGameMode {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        match buffer.read_u8()? {
+            0xFF => Ok(Self(None)),
+            id => Ok(Self(Some(GameMode::from_id(id).ok_or_else(|| Error::Other(format!("Invalid game mode {}", id)))?)))
+        }
+    }
+}
+
+// -1.0 keeps the current game mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FloatGameMode(pub Option<GameMode>);
+
+impl FloatGameMode {
+    pub fn from_value(value: f32) -> Result<Self, Error> {
+        if value == -1.0 {
+            return Ok(Self(None))
+        }
+
+        if value.fract() != 0.0 || !(0.0..=3.0).contains(&value) {
+            return Err(Error::Other(format!("Invalid game mode {}", value)))
+        }
+        Ok(Self(GameMode::from_id(value as u8)))
+    }
+
+    pub fn value(&self) -> f32 {
+        self.0.map(|game_mode| game_mode.id() as f32).unwrap_or(-1.0)
+    }
+}
+
+impl Writable for FloatGameMode {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        buffer.write_u32(self.value().to_bits())?;
+        Ok(buffer)
+    }
+}
+
+impl Readable for FloatGameMode {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        Self::from_value(f32::from_bits(buffer.read_u32()?))
+    }
+}
+
+protocol_enum_u8! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum Difficulty {
+        Peaceful = 0,
+        Easy = 1,
+        Normal = 2,
+        Hard = 3
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Identifier {
+    namespace: String,
+    path: String
+}
+
+impl Identifier {
+    pub const DEFAULT_NAMESPACE: &'static str = "minecraft";
+
+    pub fn new(namespace: impl Into<String>, path: impl Into<String>) -> Result<Self, Error> {
+        let (namespace, path) = (namespace.into(), path.into());
+        if namespace.is_empty() || !namespace.chars().all(|character| matches!(character, 'a'..='z' | '0'..='9' | '.' | '-' | '_')) {
+            return Err(Error::Other(format!("Invalid identifier namespace {:?}", namespace)))
+        }
+
+        if path.is_empty() || !path.chars().all(|character| matches!(character, 'a'..='z' | '0'..='9' | '.' | '-' | '_' | '/')) {
+            return Err(Error::Other(format!("Invalid identifier path {:?}", path)))
+        }
+        Ok(Self { namespace, path })
+    }
+
+    pub fn parse(identifier: &str) -> Result<Self, Error> {
+        match identifier.split_once(':') {
+            Some((namespace, path)) => Self::new(namespace, path),
+            None => Self::new(Self::DEFAULT_NAMESPACE, identifier)
+        }
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl Display for Identifier {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "{}:{}", self.namespace, self.path)
+    }
+}
+
+impl FromStr for Identifier {
+    type Err = Error;
+
+    fn from_str(identifier: &str) -> Result<Self, Self::Err> {
+        Self::parse(identifier)
+    }
+}
+
+impl Writable for Identifier {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        buffer.write_string(self.to_string())?;
+        Ok(buffer)
+    }
+}
+
+impl Readable for Identifier {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        Self::parse(&buffer.read_string()?)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemainingBytes(pub Vec<u8>);
+
+impl Writable for RemainingBytes {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        buffer.write_bytes(&self.0)?;
+        Ok(buffer)
+    }
+}
+
+impl Readable for RemainingBytes {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        let remaining = buffer.remaining();
+        Ok(Self(buffer.read_bytes(remaining)?))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JavaUtf(pub String);
+
+impl Writable for JavaUtf {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        buffer.write_java_utf(&self.0)?;
+        Ok(buffer)
+    }
+}
+
+impl Readable for JavaUtf {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        Ok(Self(buffer.read_java_utf()?))
+    }
+}
+
+// Up to 1.20.4 a stack carries NBT, since 1.20.5 a patch of data components
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Slot {
+    pub item: i32,
+    pub count: i32,
+    pub nbt: Option<Compound>,
+    pub components: ComponentPatch
+}
+
+impl Slot {
+    pub fn new(item: i32, count: i32) -> Self {
+        Self { item, count, ..Self::default() }.normalized()
+    }
+
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count <= 0
+    }
+
+    pub fn with_nbt(self, nbt: Compound) -> Self {
+        Self { nbt: Some(nbt), ..self }
+    }
+
+    pub fn with_components(self, components: ComponentPatch) -> Self {
+        Self { components, ..self }
+    }
+
+    pub fn with_count(self, count: i32) -> Self {
+        Self { count, ..self }.normalized()
+    }
+
+    pub fn is_same_item(&self, other: &Slot) -> bool {
+        self.item == other.item && self.nbt == other.nbt && self.components == other.components
+    }
+
+    fn normalized(self) -> Self {
+        match self.is_empty() {
+            true => Self::empty(),
+            false => self
+        }
+    }
+
+    pub fn read_versioned(buffer: &mut Buffer, protocol: i32) -> Result<Self, Error> {
+        if protocol >= 766 {
+            let count = buffer.read_var_i32()?;
+            if count <= 0 {
+                return Ok(Self::empty())
+            }
+
+            let item = buffer.read_var_i32()?;
+            return Ok(Self::new(item, count).with_components(ComponentPatch::read(buffer, protocol)?))
+        }
+
+        if !buffer.read_bool()? {
+            return Ok(Self::empty())
+        }
+        let item = buffer.read_var_i32()?;
+        let count = buffer.read_i8()? as i32;
+        let nbt = match buffer.read_u8()? {
+            0 => None,
+            _ if protocol >= 764 => {
+                buffer.rewind(1)?;
+                Some(Compound::read(buffer)?)
+            }
+            _ => {
+                buffer.rewind(1)?;
+                Some(Compound::read_named(buffer)?.1)
+            }
+        };
+        Ok(Self {
+            item,
+            count,
+            nbt,
+            ..Self::default()
+        }
+        .normalized())
+    }
+
+    pub fn write_versioned(&self, mut buffer: Buffer, protocol: i32) -> Result<Buffer, Error> {
+        if protocol >= 766 {
+            if self.is_empty() {
+                buffer.write_var_i32(0)?;
+                return Ok(buffer)
+            }
+            if self.nbt.is_some() {
+                return Err(Error::Other(format!("Unable to write slot => Protocol {} replaced NBT with data components", protocol)))
+            }
+
+            buffer.write_var_i32(self.count)?;
+            buffer.write_var_i32(self.item)?;
+            return self.components.write(buffer, protocol)
+        }
+
+        buffer.write_bool(!self.is_empty())?;
+        if self.is_empty() {
+            return Ok(buffer)
+        }
+        if !self.components.is_empty() {
+            return Err(Error::Other(format!("Unable to write slot => Protocol {} doesn't know data components", protocol)))
+        }
+        buffer.write_var_i32(self.item)?;
+        buffer.write_i8(i8::try_from(self.count).map_err(|_| Error::Other(format!("Unable to write slot => Count {} doesn't fit in a byte", self.count)))?)?;
+        match &self.nbt {
+            Some(nbt) if protocol >= 764 => nbt.write(buffer),
+            Some(nbt) => {
+                nbt.write_named("", &mut buffer)?;
+                Ok(buffer)
+            }
+            None => {
+                buffer.write_u8(0)?;
+                Ok(buffer)
+            }
+        }
+    }
+}
+
+// Slots have no plain Readable and Writable, their layout changed too often to guess the protocol
+impl VersionedWritable for Slot {
+    fn write_versioned(&self, buffer: Buffer, protocol: i32) -> Result<Buffer, Error> {
+        Slot::write_versioned(self, buffer, protocol)
+    }
+}
+
+impl VersionedReadable for Slot {
+    fn read_versioned(buffer: &mut Buffer, protocol: i32) -> Result<Self, Error> {
+        Slot::read_versioned(buffer, protocol)
+    }
+}
+
+const LEADING_SOUNDS: &[&str] = &[
+    "entity.allay.ambient_with_item",
+    "entity.allay.ambient_without_item",
+    "entity.allay.death",
+    "entity.allay.hurt",
+    "entity.allay.item_given",
+    "entity.allay.item_taken",
+    "entity.allay.item_thrown",
+    "ambient.cave",
+    "ambient.basalt_deltas.additions",
+    "ambient.basalt_deltas.loop",
+    "ambient.basalt_deltas.mood"
+];
+
+const SOUNDS: &[(i32, &[&str])] = &[(765, LEADING_SOUNDS), (766, LEADING_SOUNDS), (767, LEADING_SOUNDS)];
+
+const LEADING_PARTICLES: &[&str] = &[
+    "angry_villager",
+    "block",
+    "block_marker",
+    "bubble",
+    "cloud",
+    "crit",
+    "damage_indicator",
+    "dragon_breath",
+    "dripping_lava",
+    "falling_lava",
+    "landing_lava",
+    "dripping_water",
+    "falling_water",
+    "dust",
+    "dust_color_transition",
+    "effect",
+    "elder_guardian",
+    "enchanted_hit",
+    "enchant",
+    "end_rod",
+    "entity_effect",
+    "explosion_emitter",
+    "explosion"
+];
+
+// 1.20.5 removed ambient_entity_effect, which shifted every particle after it
+const PARTICLES: &[(i32, &[&str])] = &[
+    (
+        765,
+        &[
+            "ambient_entity_effect",
+            "angry_villager",
+            "block",
+            "block_marker",
+            "bubble",
+            "cloud",
+            "crit",
+            "damage_indicator",
+            "dragon_breath",
+            "dripping_lava",
+            "falling_lava",
+            "landing_lava",
+            "dripping_water",
+            "falling_water",
+            "dust",
+            "dust_color_transition",
+            "effect",
+            "elder_guardian",
+            "enchanted_hit",
+            "enchant",
+            "end_rod",
+            "entity_effect",
+            "explosion_emitter",
+            "explosion"
+        ]
+    ),
+    (766, LEADING_PARTICLES),
+    (767, LEADING_PARTICLES)
+];
+
+// Components after map_id differ between 1.20.5 and 1.21
+const LEADING_DATA_COMPONENTS: &[&str] = &[
+    "custom_data",
+    "max_stack_size",
+    "max_damage",
+    "damage",
+    "unbreakable",
+    "custom_name",
+    "item_name",
+    "lore",
+    "rarity",
+    "enchantments",
+    "can_place_on",
+    "can_break",
+    "attribute_modifiers",
+    "custom_model_data",
+    "hide_additional_tooltip",
+    "hide_tooltip",
+    "repair_cost",
+    "creative_slot_lock",
+    "enchantment_glint_override",
+    "intangible_projectile",
+    "food",
+    "fire_resistant",
+    "tool",
+    "stored_enchantments",
+    "dyed_color",
+    "map_color",
+    "map_id"
+];
+
+const DATA_COMPONENTS: &[(i32, &[&str])] = &[(766, LEADING_DATA_COMPONENTS), (767, LEADING_DATA_COMPONENTS)];
+
+fn embedded_names(tables: &[(i32, &'static [&'static str])], protocol: i32) -> Option<&'static [&'static str]> {
+    tables.iter().find(|(table_protocol, _)| *table_protocol == protocol).map(|(_, names)| *names)
+}
+
+pub(crate) fn data_component_names(protocol: i32) -> &'static [&'static str] {
+    embedded_names(DATA_COMPONENTS, protocol).unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdMap {
+    names: HashMap<i32, Identifier>,
+    ids: HashMap<Identifier, i32>
+}
+
+impl IdMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_names(names: &[&str]) -> Result<Self, Error> {
+        let mut map = Self::new();
+        for (id, name) in names.iter().enumerate() {
+            map.insert(id as i32, Identifier::parse(name)?);
+        }
+        Ok(map)
+    }
+
+    pub fn sounds(protocol: i32) -> Result<Self, Error> {
+        Self::embedded(SOUNDS, "sound event", protocol)
+    }
+
+    pub fn particles(protocol: i32) -> Result<Self, Error> {
+        Self::embedded(PARTICLES, "particle type", protocol)
+    }
+
+    pub fn data_components(protocol: i32) -> Result<Self, Error> {
+        Self::embedded(DATA_COMPONENTS, "data component type", protocol)
+    }
+
+    fn embedded(tables: &[(i32, &'static [&'static str])], registry: &str, protocol: i32) -> Result<Self, Error> {
+        match embedded_names(tables, protocol) {
+            Some(names) => Ok(Self::from_names(names).expect("Embedded registry names are valid identifiers")),
+            None => Err(Error::Other(format!("Unable to load {} ids => Protocol {} has no embedded table", registry, protocol)))
+        }
+    }
+
+    pub fn insert(&mut self, id: i32, name: Identifier) {
+        if let Some(previous) = self.names.insert(id, name.clone()) {
+            self.ids.remove(&previous);
+        }
+        self.ids.insert(name, id);
+    }
+
+    pub fn name(&self, id: i32) -> Option<&Identifier> {
+        self.names.get(&id)
+    }
+
+    pub fn id(&self, name: &Identifier) -> Option<i32> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SoundEvent {
+    Known { id: i32, name: Identifier },
+    Unknown(i32),
+    Custom { name: Identifier, fixed_range: Option<f32> }
+}
+
+impl UnknownVariant for SoundEvent {
+    fn is_unknown(&self) -> bool {
+        matches!(self, Self::Unknown(_))
+    }
+}
+
+impl SoundEvent {
+    pub fn from_id(id: i32, sounds: &IdMap) -> Self {
+        match sounds.name(id) {
+            Some(name) => Self::Known { id, name: name.clone() },
+            None => Self::Unknown(id)
+        }
+    }
+
+    pub fn id(&self) -> Option<i32> {
+        match self {
+            Self::Known { id, .. } | Self::Unknown(id) => Some(*id),
+            Self::Custom { .. } => None
+        }
+    }
+
+    pub fn name(&self) -> Option<&Identifier> {
+        match self {
+            Self::Known { name, .. } | Self::Custom { name, .. } => Some(name),
+            Self::Unknown(_) => None
+        }
+    }
+
+    // Sent shifted by one, 0 means the sound event follows inline
+    pub fn read_with(buffer: &mut Buffer, sounds: &IdMap) -> Result<Self, Error> {
+        match buffer.read_var_i32()? {
+            0 => {
+                let name = Identifier::read(buffer)?;
+                let fixed_range = match buffer.read_bool()? {
+                    true => Some(f32::from_bits(buffer.read_u32()?)),
+                    false => None
+                };
+                Ok(Self::Custom { name, fixed_range })
+            }
+            id => Ok(Self::from_id(id - 1, sounds))
+        }
+    }
+}
+
+impl Writable for SoundEvent {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        match self {
+            Self::Known { id, .. } | Self::Unknown(id) => buffer.write_var_i32(id + 1)?,
+            Self::Custom { name, fixed_range } => {
+                buffer.write_var_i32(0)?;
+                buffer = name.write(buffer)?;
+                buffer.write_bool(fixed_range.is_some())?;
+                if let Some(fixed_range) = fixed_range {
+                    buffer.write_u32(fixed_range.to_bits())?;
+                }
+            }
+        }
+        Ok(buffer)
+    }
+}
+
+impl Readable for SoundEvent {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        Self::read_with(buffer, &IdMap::new())
+    }
+}
+
+impl VersionedWritable for SoundEvent {
+    fn write_versioned(&self, buffer: Buffer, protocol: i32) -> Result<Buffer, Error> {
+        match self {
+            Self::Known { id, name } => {
+                let id = IdMap::sounds(protocol)?.id(name).unwrap_or(*id);
+                Self::Known { id, name: name.clone() }.write(buffer)
+            }
+            sound => sound.write(buffer)
+        }
+    }
+}
+
+impl VersionedReadable for SoundEvent {
+    fn read_versioned(buffer: &mut Buffer, protocol: i32) -> Result<Self, Error> {
+        Self::read_with(buffer, &IdMap::sounds(protocol)?)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParticleType {
+    Known { id: i32, name: Identifier },
+    Unknown(i32),
+    Named(Identifier)
+}
+
+impl UnknownVariant for ParticleType {
+    fn is_unknown(&self) -> bool {
+        matches!(self, Self::Unknown(_))
+    }
+}
+
+impl ParticleType {
+    pub fn from_id(id: i32, particles: &IdMap) -> Self {
+        match particles.name(id) {
+            Some(name) => Self::Known { id, name: name.clone() },
+            None => Self::Unknown(id)
+        }
+    }
+
+    pub fn id(&self) -> Option<i32> {
+        match self {
+            Self::Known { id, .. } | Self::Unknown(id) => Some(*id),
+            Self::Named(_) => None
+        }
+    }
+
+    pub fn name(&self) -> Option<&Identifier> {
+        match self {
+            Self::Known { name, .. } | Self::Named(name) => Some(name),
+            Self::Unknown(_) => None
+        }
+    }
+
+    pub fn resolve(self, particles: &IdMap) -> Self {
+        match self {
+            Self::Named(name) => {
+                match particles.id(&name) {
+                    Some(id) => Self::Known { id, name },
+                    None => Self::Named(name)
+                }
+            }
+            Self::Unknown(id) => Self::from_id(id, particles),
+            known => known
+        }
+    }
+
+    pub fn read_with(buffer: &mut Buffer, particles: &IdMap) -> Result<Self, Error> {
+        Ok(Self::from_id(buffer.read_var_i32()?, particles))
+    }
+}
+
+impl Writable for ParticleType {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        match self {
+            Self::Known { id, .. } | Self::Unknown(id) => buffer.write_var_i32(*id)?,
+            Self::Named(name) => return Err(Error::Other(format!("Unable to write particle => {} wasn't resolved to an id", name)))
+        }
+        Ok(buffer)
+    }
+}
+
+impl Readable for ParticleType {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        Self::read_with(buffer, &IdMap::new())
+    }
+}
@@ -0,0 +1,120 @@
+use std::{
+    fmt::{Display, Formatter},
+    ops::RangeInclusive
+};
+
+use crate::network::{status::StatusVersion, Error};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VersionInfo {
+    pub name: &'static str,
+    pub protocol: i32
+}
+
+impl VersionInfo {
+    pub const fn new(name: &'static str, protocol: i32) -> Self {
+        Self { name, protocol }
+    }
+
+    pub fn from_protocol(protocol: i32) -> Option<&'static VersionInfo> {
+        VERSIONS.iter().find(|version| version.protocol == protocol)
+    }
+
+    pub fn from_name(name: &str) -> Option<&'static VersionInfo> {
+        VERSIONS.iter().find(|version| version.name == name)
+    }
+
+    pub fn latest() -> &'static VersionInfo {
+        &VERSIONS[VERSIONS.len() - 1]
+    }
+}
+
+impl Display for VersionInfo {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "{} ({})", self.name, self.protocol)
+    }
+}
+
+pub const V1_16_5: VersionInfo = VersionInfo::new("1.16.5", 754);
+pub const V1_17_1: VersionInfo = VersionInfo::new("1.17.1", 756);
+pub const V1_18_2: VersionInfo = VersionInfo::new("1.18.2", 758);
+pub const V1_19: VersionInfo = VersionInfo::new("1.19", 759);
+pub const V1_19_2: VersionInfo = VersionInfo::new("1.19.2", 760);
+pub const V1_19_3: VersionInfo = VersionInfo::new("1.19.3", 761);
+pub const V1_19_4: VersionInfo = VersionInfo::new("1.19.4", 762);
+pub const V1_20_1: VersionInfo = VersionInfo::new("1.20.1", 763);
+pub const V1_20_2: VersionInfo = VersionInfo::new("1.20.2", 764);
+pub const V1_20_4: VersionInfo = VersionInfo::new("1.20.4", 765);
+pub const V1_20_6: VersionInfo = VersionInfo::new("1.20.6", 766);
+pub const V1_21_1: VersionInfo = VersionInfo::new("1.21.1", 767);
+
+pub const VERSIONS: &[VersionInfo] = &[V1_16_5, V1_17_1, V1_18_2, V1_19, V1_19_2, V1_19_3, V1_19_4, V1_20_1, V1_20_2, V1_20_4, V1_20_6, V1_21_1];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionSelection {
+    Auto,
+    Exact(i32),
+    // Used as is, even if it isn't in the version registry
+    Custom(VersionInfo),
+    PreferRange(RangeInclusive<i32>)
+}
+
+impl VersionSelection {
+    pub fn needs_probe(&self) -> bool {
+        !matches!(self, Self::Exact(_) | Self::Custom(_))
+    }
+
+    pub fn select(&self, reported: Option<&StatusVersion>) -> Result<VersionInfo, Error> {
+        let reported_version = || {
+            let reported = reported.ok_or_else(|| Error::Other("Unable to select a version => The server didn't report its version".to_string()))?;
+            VersionInfo::from_protocol(reported.protocol).copied().ok_or_else(|| {
+                Error::UnsupportedVersion {
+                    protocol: reported.protocol,
+                    name: reported.name.clone()
+                }
+            })
+        };
+
+        match self {
+            Self::Auto => reported_version(),
+            Self::Exact(protocol) => {
+                VersionInfo::from_protocol(*protocol).copied().ok_or_else(|| {
+                    Error::Other(format!(
+                        "Unable to select a version => Protocol {} isn't in the version registry, select a custom VersionInfo to use it anyway",
+                        protocol
+                    ))
+                })
+            }
+            Self::Custom(version) => Ok(*version),
+            Self::PreferRange(range) => {
+                match reported_version() {
+                    Ok(version) if range.contains(&version.protocol) => Ok(version),
+                    _ => {
+                        VERSIONS
+                            .iter()
+                            .rev()
+                            .find(|version| range.contains(&version.protocol))
+                            .copied()
+                            .ok_or_else(|| Error::Other(format!("Unable to select a version => None of the protocols {} to {} is supported", range.start(), range.end())))
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl From<&VersionInfo> for VersionSelection {
+    fn from(version: &VersionInfo) -> Self {
+        Self::from(*version)
+    }
+}
+
+// Versions that aren't in the registry, e.g. snapshots, are used as they are
+impl From<VersionInfo> for VersionSelection {
+    fn from(version: VersionInfo) -> Self {
+        match VersionInfo::from_protocol(version.protocol) {
+            Some(known) if *known == version => Self::Exact(version.protocol),
+            _ => Self::Custom(version)
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use serde::Deserialize;
+
+#[cfg(feature = "network")]
+use crate::network::{
+    buffer::Buffer,
+    connection::{Readable, Writable},
+    Error
+};
+
+// Login Success only carries the properties since 1.19
+pub const LOGIN_PROPERTIES_PROTOCOL: i32 = 759;
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Property {
+    pub name: String,
+    pub value: String,
+    pub signature: Option<String>
+}
+
+#[cfg(feature = "network")]
+impl Writable for Property {
+    fn write(&self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        buffer.write_str(&self.name)?;
+        buffer.write_str(&self.value)?;
+        buffer.write_bool(self.signature.is_some())?;
+        if let Some(signature) = &self.signature {
+            buffer.write_str(signature)?;
+        }
+        Ok(buffer)
+    }
+}
+
+#[cfg(feature = "network")]
+impl Readable for Property {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        Ok(Self {
+            name: buffer.read_string()?,
+            value: buffer.read_string()?,
+            signature: if buffer.read_bool()? { Some(buffer.read_string()?) } else { None }
+        })
+    }
+}
+
+#[cfg(feature = "network")]
+pub fn read_login_properties(buffer: &mut Buffer, protocol: i32) -> Result<Vec<Property>, Error> {
+    match protocol >= LOGIN_PROPERTIES_PROTOCOL {
+        true => Vec::read(buffer),
+        false => Ok(Vec::new())
+    }
+}
+
+#[cfg(feature = "network")]
+pub fn write_login_properties(properties: &[Property], mut buffer: Buffer, protocol: i32) -> Result<Buffer, Error> {
+    if protocol < LOGIN_PROPERTIES_PROTOCOL {
+        return Ok(buffer)
+    }
+
+    buffer.write_var_i32(properties.len() as i32)?;
+    properties.iter().try_fold(buffer, |buffer, property| property.write(buffer))
+}
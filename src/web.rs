@@ -1,22 +1,101 @@
-use std::fmt::{Display, Formatter};
+#[cfg(feature = "tracing")]
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::{
+    fmt::{Display, Formatter},
+    sync::{PoisonError, RwLock},
+    time::Duration
+};
 
 use reqwest::{
-    header::{HeaderName, InvalidHeaderName, InvalidHeaderValue},
-    Client, RequestBuilder
+    header::{HeaderMap, HeaderName, HeaderValue, InvalidHeaderName, InvalidHeaderValue, ACCEPT, CONTENT_TYPE},
+    Certificate, Client, RequestBuilder, Url
 };
+use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value;
-use warp::http::HeaderValue;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    Timeout,
+    Connect,
+    Request,
+    Status,
+    Body,
+    Decode,
+    Other
+}
 
 #[derive(Debug)]
 pub struct Error {
     message: String,
-    code: u8
+    code: u8,
+    kind: ErrorKind,
+    response: Option<String>,
+    operation: Option<String>,
+    source: Option<Box<dyn std::error::Error + Send + Sync>>
 }
 
 impl Error {
     #[inline]
     pub fn new(message: impl Into<String>, code: u8) -> Self {
-        Self { message: message.into(), code }
+        Self {
+            message: message.into(),
+            code,
+            kind: ErrorKind::Other,
+            response: None,
+            operation: None,
+            source: None
+        }
+    }
+
+    pub fn context(self, message: impl Into<String>, code: u8) -> Self {
+        Self {
+            message: format!("{} => {}", message.into(), self.message),
+            code,
+            kind: self.kind,
+            response: self.response,
+            operation: self.operation,
+            source: self.source
+        }
+    }
+
+    pub fn within(self, operation: impl Display) -> Self {
+        Self {
+            message: self.message.clone(),
+            code: self.code,
+            kind: self.kind,
+            response: self.response.clone(),
+            operation: Some(operation.to_string()),
+            source: Some(Box::new(self))
+        }
+    }
+
+    pub fn with_kind(self, kind: ErrorKind) -> Self {
+        Self { kind, ..self }
+    }
+
+    pub fn with_source(self, source: impl std::error::Error + Send + Sync + 'static) -> Self {
+        Self {
+            source: Some(Box::new(source)),
+            ..self
+        }
+    }
+
+    pub fn operations(&self) -> Vec<&str> {
+        let mut operations = Vec::new();
+        let mut error = Some(self);
+        while let Some(Self {
+            operation: Some(operation), source, ..
+        }) = error
+        {
+            operations.push(operation.as_str());
+            error = source.as_ref().and_then(|source| source.downcast_ref::<Self>());
+        }
+        operations
+    }
+
+    #[inline]
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
     }
 
     #[inline]
@@ -28,68 +107,398 @@ impl Error {
     pub fn code(&self) -> u8 {
         self.code
     }
+
+    #[inline]
+    pub fn response(&self) -> Option<&str> {
+        self.response.as_deref()
+    }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}:{}", self.message, self.code)
+        match (&self.operation, &self.source) {
+            (Some(operation), Some(source)) => write!(f, "{}: {}", operation, source),
+            _ => write!(f, "{}:{}", self.message, self.code)
+        }
     }
 }
 
-pub struct Requester {
-    request_builder: RequestBuilder
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_ref().map(|source| source.as_ref() as &(dyn std::error::Error + 'static))
+    }
 }
 
-impl Requester {
-    pub fn get_str(url: &'static str) -> Self {
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
         Self {
-            request_builder: Client::new().get(url)
+            message: error.to_string(),
+            code: 0,
+            kind: ErrorKind::Decode,
+            response: None,
+            operation: None,
+            source: Some(Box::new(error))
         }
     }
+}
 
-    pub fn get(url: String) -> Self {
+impl From<reqwest::Error> for Error {
+    fn from(error: reqwest::Error) -> Self {
+        let kind = if error.is_timeout() {
+            ErrorKind::Timeout
+        } else if error.is_connect() {
+            ErrorKind::Connect
+        } else if error.is_status() {
+            ErrorKind::Status
+        } else if error.is_decode() {
+            ErrorKind::Decode
+        } else if error.is_body() {
+            ErrorKind::Body
+        } else if error.is_request() {
+            ErrorKind::Request
+        } else {
+            ErrorKind::Other
+        };
+
+        Self {
+            message: error.to_string(),
+            code: 0,
+            kind,
+            response: None,
+            operation: None,
+            source: Some(Box::new(error))
+        }
+    }
+}
+
+const MAXIMUM_BODY_LENGTH: usize = 256;
+
+fn truncate(body: &str) -> String {
+    match body.char_indices().nth(MAXIMUM_BODY_LENGTH) {
+        Some((index, _)) => format!("{}...", &body[..index]),
+        None => body.to_string()
+    }
+}
+
+#[cfg(feature = "tracing")]
+static SLOW_CALL_THRESHOLD: AtomicU64 = AtomicU64::new(2000);
+
+#[cfg(feature = "tracing")]
+pub fn set_slow_call_threshold(threshold: Duration) {
+    SLOW_CALL_THRESHOLD.store(threshold.as_millis() as u64, Ordering::Relaxed);
+}
+
+#[cfg(feature = "tracing")]
+pub fn slow_call_threshold() -> Duration {
+    Duration::from_millis(SLOW_CALL_THRESHOLD.load(Ordering::Relaxed))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsBackend {
+    #[cfg(feature = "native-tls")]
+    Native,
+    #[cfg(feature = "rustls-tls")]
+    Rustls
+}
+
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    root_certificates: Vec<Certificate>,
+    built_in_root_certificates: bool,
+    tls_backend: Option<TlsBackend>
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClientConfig {
+    pub fn new() -> Self {
         Self {
-            request_builder: Client::new().get(url)
+            root_certificates: Vec::new(),
+            built_in_root_certificates: true,
+            tls_backend: None
         }
     }
 
-    pub fn post_str(url: &'static str) -> Self {
+    pub fn root_certificate_pem(mut self, pem: &[u8]) -> Result<Self, Error> {
+        self.root_certificates
+            .push(Certificate::from_pem(pem).map_err(|error| Error::from(error).context("Unable to parse root certificate", 29))?);
+        Ok(self)
+    }
+
+    pub fn root_certificate_der(mut self, der: &[u8]) -> Result<Self, Error> {
+        self.root_certificates
+            .push(Certificate::from_der(der).map_err(|error| Error::from(error).context("Unable to parse root certificate", 29))?);
+        Ok(self)
+    }
+
+    pub fn built_in_root_certificates(self, built_in_root_certificates: bool) -> Self {
+        Self { built_in_root_certificates, ..self }
+    }
+
+    pub fn tls_backend(self, tls_backend: TlsBackend) -> Self {
         Self {
-            request_builder: Client::new().post(url)
+            tls_backend: Some(tls_backend),
+            ..self
         }
     }
 
+    pub fn build(self) -> Result<Client, Error> {
+        let builder = match self.tls_backend {
+            #[cfg(feature = "native-tls")]
+            Some(TlsBackend::Native) => Client::builder().use_native_tls(),
+            #[cfg(feature = "rustls-tls")]
+            Some(TlsBackend::Rustls) => Client::builder().use_rustls_tls(),
+            None => Client::builder()
+        };
+
+        let builder = self
+            .root_certificates
+            .into_iter()
+            .fold(builder.tls_built_in_root_certs(self.built_in_root_certificates), |builder, certificate| {
+                builder.add_root_certificate(certificate)
+            });
+
+        builder.build().map_err(|error| Error::from(error).context("Unable to build HTTP client", 30))
+    }
+}
+
+pub const DEFAULT_MAX_RESPONSE_BYTES: usize = 16 * 1024 * 1024;
+
+static CLIENT: RwLock<Option<Client>> = RwLock::new(None);
+
+pub fn configure_client(config: ClientConfig) -> Result<(), Error> {
+    let client = config.build()?;
+    *CLIENT.write().unwrap_or_else(PoisonError::into_inner) = Some(client);
+    Ok(())
+}
+
+fn client() -> Client {
+    if let Some(client) = CLIENT.read().unwrap_or_else(PoisonError::into_inner).as_ref() {
+        return client.clone()
+    }
+    CLIENT.write().unwrap_or_else(PoisonError::into_inner).get_or_insert_with(Client::new).clone()
+}
+
+pub struct Requester {
+    request_builder: RequestBuilder,
+    host: String,
+    max_response_bytes: usize,
+    // Applied once when sending, so they replace the ones set by form and json instead of being sent twice
+    content_type: Option<String>,
+    accept: Option<String>
+}
+
+impl Requester {
+    fn new(request_builder: RequestBuilder, url: &str) -> Self {
+        Self {
+            request_builder,
+            host: Url::parse(url).ok().and_then(|url| url.host_str().map(str::to_string)).unwrap_or_default(),
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            content_type: None,
+            accept: None
+        }
+    }
+
+    pub fn get_str(url: &'static str) -> Self {
+        Self::new(client().get(url), url)
+    }
+
+    pub fn get(url: String) -> Self {
+        Self::new(client().get(&url), &url)
+    }
+
+    pub fn post(url: String) -> Self {
+        Self::new(client().post(&url), &url)
+    }
+
+    pub fn post_str(url: &'static str) -> Self {
+        Self::new(client().post(url), url)
+    }
+
     pub fn form(self, string: &Value) -> Self {
         Self {
-            request_builder: self.request_builder.form(string)
+            request_builder: self.request_builder.form(string),
+            ..self
         }
     }
 
     pub fn body_str(self, string: &'static str) -> Self {
         Self {
-            request_builder: self.request_builder.body(string)
+            request_builder: self.request_builder.body(string),
+            ..self
         }
     }
 
     pub fn body(self, string: String) -> Self {
         Self {
-            request_builder: self.request_builder.body(string)
+            request_builder: self.request_builder.body(string),
+            ..self
         }
     }
 
+    // Some Mojang endpoints answer with HTML unless JSON is asked for
     pub fn json(self, string: &Value) -> Self {
         Self {
-            request_builder: self.request_builder.json(string)
+            request_builder: self.request_builder.json(string),
+            accept: self.accept.or_else(|| Some("application/json".to_string())),
+            ..self
+        }
+    }
+
+    pub fn content_type(self, content_type: &str) -> Self {
+        Self {
+            content_type: Some(content_type.to_string()),
+            ..self
+        }
+    }
+
+    pub fn accept(self, accept: &str) -> Self {
+        Self {
+            accept: Some(accept.to_string()),
+            ..self
+        }
+    }
+
+    pub fn query<T: Serialize + ?Sized>(self, query: &T) -> Self {
+        Self {
+            request_builder: self.request_builder.query(query),
+            ..self
         }
     }
 
     pub fn header(self, name: Result<HeaderName, InvalidHeaderName>, value: Result<HeaderValue, InvalidHeaderValue>) -> Self {
         Self {
-            request_builder: self.request_builder.header(name.unwrap(), value.unwrap())
+            request_builder: self.request_builder.header(name.unwrap(), value.unwrap()),
+            ..self
+        }
+    }
+
+    pub fn timeout(self, timeout: Duration) -> Self {
+        Self {
+            request_builder: self.request_builder.timeout(timeout),
+            ..self
         }
     }
 
-    pub async fn execute(self) -> Result<String, reqwest::Error> {
-        self.request_builder.send().await?.text().await
+    pub fn max_response_bytes(self, max_response_bytes: usize) -> Self {
+        Self { max_response_bytes, ..self }
     }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub async fn execute(self) -> Result<String, Error> {
+        Ok(self.execute_with_status().await?.1)
+    }
+
+    pub async fn execute_json<T: DeserializeOwned>(self) -> Result<T, Error> {
+        let (status, response) = self.execute_with_status().await?;
+        parse_json(status, response)
+    }
+
+    pub async fn execute_with_status(self) -> Result<(u16, String), Error> {
+        let (status, body) = self.execute_bytes().await?;
+        Ok((status, String::from_utf8_lossy(&body).into_owned()))
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    pub async fn execute_bytes(self) -> Result<(u16, Vec<u8>), Error> {
+        self.send().await
+    }
+
+    // URLs and bodies can carry tokens, so they are never recorded
+    #[cfg(feature = "tracing")]
+    pub async fn execute_bytes(self) -> Result<(u16, Vec<u8>), Error> {
+        use tracing::{field::Empty, Instrument};
+
+        let span = tracing::info_span!("web.request", host = %self.host, attempt = 1_u32, status = Empty, elapsed_ms = Empty);
+        let start = std::time::Instant::now();
+        let result = self.send().instrument(span.clone()).await;
+        let elapsed = start.elapsed();
+
+        let _entered = span.enter();
+        span.record("elapsed_ms", elapsed.as_millis() as u64);
+        match &result {
+            Ok((status, _)) => {
+                span.record("status", status);
+            }
+            Err(error) => tracing::error!(kind = ?error.kind(), code = error.code(), "Request failed")
+        }
+
+        if elapsed > slow_call_threshold() {
+            tracing::warn!(elapsed_ms = elapsed.as_millis() as u64, threshold_ms = slow_call_threshold().as_millis() as u64, "Slow call");
+        }
+        result
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    async fn send(self) -> Result<(u16, Vec<u8>), Error> {
+        self.receive().await
+    }
+
+    #[cfg(feature = "metrics")]
+    async fn send(self) -> Result<(u16, Vec<u8>), Error> {
+        let host = self.host.clone();
+        let result = self.receive().await;
+        crate::metrics::record_webapi_request(&host, &result);
+        result
+    }
+
+    async fn receive(self) -> Result<(u16, Vec<u8>), Error> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in [(CONTENT_TYPE, self.content_type), (ACCEPT, self.accept)] {
+            if let Some(value) = value {
+                let header = HeaderValue::from_str(&value).map_err(|_| Error::new(format!("Unable to send request => Invalid {} header {:?}", name, value), 0))?;
+                headers.insert(name, header);
+            }
+        }
+
+        let mut response = self.request_builder.headers(headers).send().await?;
+        let status = response.status().as_u16();
+        if response.content_length().is_some_and(|length| length > self.max_response_bytes as u64) {
+            return Err(response_too_large(self.max_response_bytes))
+        }
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if body.len() + chunk.len() > self.max_response_bytes {
+                return Err(response_too_large(self.max_response_bytes))
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok((status, body))
+    }
+}
+
+fn response_too_large(max_response_bytes: usize) -> Error {
+    Error {
+        kind: ErrorKind::Body,
+        ..Error::new(format!("Unable to read response => The body exceeds the limit of {} bytes", max_response_bytes), 38)
+    }
+}
+
+pub(crate) fn parse_json<T: DeserializeOwned>(status: u16, response: String) -> Result<T, Error> {
+    if !(200..300).contains(&status) {
+        return Err(Error {
+            message: format!("Server responded with {} ({})", status, truncate(&response)),
+            code: 0,
+            kind: ErrorKind::Status,
+            response: Some(response),
+            operation: None,
+            source: None
+        })
+    }
+
+    serde_json::from_str(&response).map_err(|error| {
+        let error = Error::from(error);
+        Error {
+            message: format!("{} in response {}", error.message, truncate(&response)),
+            ..error
+        }
+    })
 }
@@ -1,12 +1,51 @@
-use std::str::FromStr;
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    str::FromStr,
+    time::SystemTime
+};
 
-use reqwest::header::HeaderName;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::DateTime;
+use reqwest::header::{HeaderName, HeaderValue};
+use rsa::{pkcs8::DecodePrivateKey, RsaPrivateKey};
 use serde::Deserialize;
-use serde_json::Value;
+use serde_json::json;
+use sha1::{Digest, Sha1};
 use uuid::Uuid;
-use warp::http::HeaderValue;
 
-use crate::web::{Error, Requester};
+pub use crate::profile::Property;
+use crate::web::{Error, ErrorKind, Requester, DEFAULT_MAX_RESPONSE_BYTES};
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum ApiOperation {
+    UsernameLookup,
+    ProfileLookup,
+    BlockedServers,
+    JoinServer,
+    HasJoined,
+    PlayerCertificates,
+    PlayerAttributes
+}
+
+impl Display for ApiOperation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiOperation::UsernameLookup => write!(f, "username_lookup"),
+            ApiOperation::ProfileLookup => write!(f, "profile_lookup"),
+            ApiOperation::BlockedServers => write!(f, "blocked_servers"),
+            ApiOperation::JoinServer => write!(f, "join_server"),
+            ApiOperation::HasJoined => write!(f, "has_joined"),
+            ApiOperation::PlayerCertificates => write!(f, "player_certificates"),
+            ApiOperation::PlayerAttributes => write!(f, "player_attributes")
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ProfileName {
+    id: String
+}
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct ProfileResponse {
@@ -16,10 +55,96 @@ pub struct ProfileResponse {
 }
 
 #[derive(Deserialize, Debug, Clone)]
-pub struct Property {
-    pub name: String,
-    pub value: String,
-    pub signature: Option<String>
+#[serde(rename_all = "camelCase")]
+pub struct PlayerCertificates {
+    pub key_pair: KeyPair,
+    pub public_key_signature_v2: String,
+    pub expires_at: String,
+    pub refreshed_after: String
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyPair {
+    pub private_key: String,
+    pub public_key: String
+}
+
+pub fn is_valid_username(username: &str) -> bool {
+    (1..=16).contains(&username.len()) && username.bytes().all(|byte| byte.is_ascii_alphanumeric() || byte == b'_')
+}
+
+// Mojang labels the keys as RSA keys, but the bodies are PKCS#8 and X.509
+fn pem_body(pem: &str) -> Result<Vec<u8>, Error> {
+    let body = pem.lines().filter(|line| !line.starts_with("-----")).collect::<String>();
+    STANDARD.decode(body).map_err(|error| Error::new(format!("Unable to decode certificate key => {}", error), 28))
+}
+
+fn parse_timestamp(timestamp: &str) -> Result<SystemTime, Error> {
+    Ok(DateTime::parse_from_rfc3339(timestamp)
+        .map_err(|error| Error::new(format!("Unable to parse certificate timestamp => {}", error), 28))?
+        .into())
+}
+
+impl PlayerCertificates {
+    pub fn private_key(&self) -> Result<RsaPrivateKey, Error> {
+        RsaPrivateKey::from_pkcs8_der(&pem_body(&self.key_pair.private_key)?).map_err(|error| Error::new(format!("Unable to parse certificate private key => {}", error), 28))
+    }
+
+    pub fn public_key_der(&self) -> Result<Vec<u8>, Error> {
+        pem_body(&self.key_pair.public_key)
+    }
+
+    pub fn public_key_signature(&self) -> Result<Vec<u8>, Error> {
+        STANDARD
+            .decode(&self.public_key_signature_v2)
+            .map_err(|error| Error::new(format!("Unable to decode certificate signature => {}", error), 28))
+    }
+
+    pub fn expires_at(&self) -> Result<SystemTime, Error> {
+        parse_timestamp(&self.expires_at)
+    }
+
+    pub fn refreshed_after(&self) -> Result<SystemTime, Error> {
+        parse_timestamp(&self.refreshed_after)
+    }
+}
+
+#[derive(Deserialize)]
+struct RawPrivilege {
+    enabled: bool
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPrivileges {
+    online_chat: RawPrivilege,
+    multiplayer_server: RawPrivilege,
+    multiplayer_realms: RawPrivilege,
+    telemetry: RawPrivilege
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawBannedScope {
+    ban_id: String,
+    expires: i64,
+    reason: String,
+    reason_message: Option<String>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawBanStatus {
+    #[serde(default)]
+    banned_scopes: HashMap<String, RawBannedScope>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPlayerAttributes {
+    privileges: RawPrivileges,
+    ban_status: Option<RawBanStatus>
 }
 
 #[derive(Eq, PartialEq, Debug, Clone)]
@@ -74,93 +199,232 @@ impl BanReason {
     }
 }
 
-pub async fn uuid_from_username(username: &'static str) -> Result<Uuid, Error> {
-    let response = Requester::get(format!("https://api.mojang.com/users/profiles/minecraft/{}", username.clone()))
-        .execute()
-        .await
-        .map_err(|error| Error::new(format!("Unable to send uuid2username request => {}", error), 15))?;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoints {
+    pub api: String,
+    pub session_server: String,
+    pub minecraft_services: String,
+    pub max_response_bytes: usize
+}
 
-    if response.is_empty() {
-        return Err(Error::new(format!("The user {} doesn't exists!", username), 15))
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            api: "https://api.mojang.com".to_string(),
+            session_server: "https://sessionserver.mojang.com".to_string(),
+            minecraft_services: "https://api.minecraftservices.com".to_string(),
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES
+        }
     }
-
-    Ok(Uuid::from_str(
-        serde_json::from_str::<Value>(&response).expect("Unable to parse response")["id"]
-            .as_str()
-            .expect("Unable to find id object")
-    )
-    .expect("Unable to create uuid"))
 }
 
-pub async fn profile_from_uuid(uuid: Uuid) -> Result<ProfileResponse, Error> {
-    let response = Requester::get(format!("https://sessionserver.mojang.com/session/minecraft/profile/{}", uuid))
-        .execute()
-        .await
-        .map_err(|error| Error::new(format!("Unable to send uuid to profile request => {}", error), 15))?;
+impl Endpoints {
+    pub fn new(api: impl Into<String>, session_server: impl Into<String>, minecraft_services: impl Into<String>) -> Self {
+        Self {
+            api: api.into(),
+            session_server: session_server.into(),
+            minecraft_services: minecraft_services.into(),
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES
+        }
+    }
 
-    let response = serde_json::from_str::<ProfileResponse>(&response).map_err(|_| Error::new(format!("Unable to parse response"), 16))?;
+    pub fn with_max_response_bytes(self, max_response_bytes: usize) -> Self {
+        Self { max_response_bytes, ..self }
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "webapi.uuid_from_username", skip_all, fields(username = %username)))]
+    pub async fn uuid_from_username(&self, username: &str) -> Result<Uuid, Error> {
+        let (status, response) = Requester::get(format!("{}/users/profiles/minecraft/{}", self.api, username))
+            .max_response_bytes(self.max_response_bytes)
+            .execute_with_status()
+            .await
+            .map_err(|error| error.context(format!("Unable to get uuid of {}", username), 15))?;
+
+        // Mojang answers unknown names with 204 on older deployments and 404 on newer ones
+        if let 204 | 404 = status {
+            return Err(Error::new(format!("Unable to get uuid => The user {} doesn't exist", username), 15))
+        }
+
+        let profile = crate::web::parse_json::<ProfileName>(status, response).map_err(|error| error.context(format!("Unable to get uuid of {}", username), 15))?;
+
+        Uuid::from_str(&profile.id).map_err(|error| Error::new(format!("Unable to parse uuid {} => {}", profile.id, error), 15))
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "webapi.profile_from_uuid", skip_all, fields(uuid = %uuid)))]
+    pub async fn profile_from_uuid(&self, uuid: Uuid) -> Result<ProfileResponse, Error> {
+        Requester::get(format!("{}/session/minecraft/profile/{}", self.session_server, uuid))
+            .max_response_bytes(self.max_response_bytes)
+            .execute_json::<ProfileResponse>()
+            .await
+            .map_err(|error| {
+                match error.kind() {
+                    ErrorKind::Decode => error.context("Unable to parse response", 16),
+                    _ => error.context("Unable to send uuid to profile request", 15)
+                }
+            })
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "webapi.blocked_servers", skip_all))]
+    pub async fn blocked_servers(&self) -> Result<Vec<String>, Error> {
+        let response = Requester::get(format!("{}/blockedservers", self.session_server))
+            .max_response_bytes(self.max_response_bytes)
+            .execute()
+            .await
+            .map_err(|error| error.context("Unable to send uuid to profile request", 17))?;
+
+        Ok(response
+            .lines()
+            .map(str::trim)
+            .filter(|hash| hash.len() == 40 && hash.chars().all(|character| character.is_ascii_hexdigit()))
+            .map(str::to_ascii_lowercase)
+            .collect())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "webapi.join_server", skip_all, fields(profile = %selected_profile)))]
+    pub async fn join_server(&self, access_token: &str, selected_profile: Uuid, server_hash: &str) -> Result<(), Error> {
+        let json = json!({
+            "accessToken": access_token,
+            "selectedProfile": selected_profile.simple().to_string(),
+            "serverId": server_hash
+        });
+
+        let (status, response) = Requester::post(format!("{}/session/minecraft/join", self.session_server))
+            .max_response_bytes(self.max_response_bytes)
+            .json(&json)
+            .execute_with_status()
+            .await
+            .map_err(|error| error.context("Unable to send join server request", 20))?;
+
+        if !(200..300).contains(&status) {
+            return Err(Error::new(format!("Unable to join server => Session server responded with {} ({})", status, response), 20))
+        }
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "webapi.has_joined", skip_all, fields(username = %username)))]
+    pub async fn has_joined(&self, username: &str, server_hash: &str) -> Result<Option<ProfileResponse>, Error> {
+        if !is_valid_username(username) {
+            return Err(Error::new(format!("Unable to verify join => Invalid username {:?}", username), 21))
+        }
+
+        let (status, response) = Requester::get(format!("{}/session/minecraft/hasJoined", self.session_server))
+            .query(&[("username", username), ("serverId", server_hash)])
+            .max_response_bytes(self.max_response_bytes)
+            .execute_with_status()
+            .await
+            .map_err(|error| error.context("Unable to send has joined request", 21))?;
+
+        if status == 204 || response.is_empty() {
+            return Ok(None)
+        }
+
+        if !(200..300).contains(&status) {
+            return Err(Error::new(format!("Unable to verify join => Session server responded with {} ({})", status, response), 21))
+        }
 
-    Ok(response)
+        let profile = serde_json::from_str::<ProfileResponse>(&response).map_err(|error| Error::from(error).context("Unable to parse has joined response", 22))?;
+        if profile.name != username {
+            return Err(Error::new(
+                format!("Unable to verify join => Session server returned the profile of {} instead of {}", profile.name, username),
+                21
+            ))
+        }
+        Ok(Some(profile))
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "webapi.player_certificates", skip_all))]
+    pub async fn player_certificates(&self, access_token: &str) -> Result<PlayerCertificates, Error> {
+        let (status, response) = Requester::post(format!("{}/player/certificates", self.minecraft_services))
+            .max_response_bytes(self.max_response_bytes)
+            .header(HeaderName::from_str("Authorization"), HeaderValue::from_str(&format!("Bearer {}", access_token)))
+            .execute_with_status()
+            .await
+            .map_err(|error| error.context("Unable to send player certificates request", 27))?;
+
+        if !(200..300).contains(&status) {
+            return Err(Error::new(
+                format!("Unable to fetch player certificates => Services responded with {} ({})", status, response),
+                27
+            ))
+        }
+
+        serde_json::from_str::<PlayerCertificates>(&response).map_err(|error| Error::from(error).context("Unable to parse player certificates", 28))
+    }
+}
+
+pub async fn uuid_from_username(username: &str) -> Result<Uuid, Error> {
+    Endpoints::default().uuid_from_username(username).await
+}
+
+pub async fn profile_from_uuid(uuid: Uuid) -> Result<ProfileResponse, Error> {
+    Endpoints::default().profile_from_uuid(uuid).await
 }
 
 pub async fn blocked_servers() -> Result<Vec<String>, Error> {
-    let response = Requester::get_str("https://sessionserver.mojang.com/blockedservers")
-        .execute()
-        .await
-        .map_err(|error| Error::new(format!("Unable to send uuid to profile request => {}", error), 17))?;
+    Endpoints::default().blocked_servers().await
+}
+
+// Mojang matches the full address and every wildcard parent
+pub fn is_server_blocked(address: &str, blocked_servers: &[String]) -> bool {
+    let address = address.to_lowercase();
+    let is_ip = address.parse::<std::net::Ipv4Addr>().is_ok();
+    let parts = address.split('.').collect::<Vec<_>>();
 
-    let mut blocked_servers = Vec::new();
-    for hash in response.split("\n") {
-        blocked_servers.push(hash.to_string());
+    let mut candidates = vec![address.clone()];
+    for i in 1..parts.len() {
+        candidates.push(match is_ip {
+            true => format!("{}.*", parts[..parts.len() - i].join(".")),
+            false => format!("*.{}", parts[i..].join("."))
+        });
     }
-    Ok(blocked_servers)
+
+    candidates.iter().any(|candidate| {
+        let hash = Sha1::digest(candidate.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+        blocked_servers.iter().any(|blocked| blocked.trim() == hash)
+    })
 }
 
+#[cfg_attr(feature = "tracing", tracing::instrument(name = "webapi.player_attributes", skip_all))]
 pub async fn player_attributes(access_token: String) -> Result<PlayerAttributes, Error> {
     let response = Requester::get_str("https://api.minecraftservices.com/player/attributes")
         .header(HeaderName::from_str("Authentication"), HeaderValue::from_str(&format!("Bearer {}", access_token)))
-        .execute()
+        .execute_json::<RawPlayerAttributes>()
         .await
-        .map_err(|error| Error::new(format!("Unable to send player attributes request => {}", error), 18))?;
-
-    println!("{}", response.clone());
-
-    let response = serde_json::from_str::<Value>(&response).map_err(|_| Error::new("Unable to parse response from player attributes endpoint", 19))?;
-
-    let online_chat: Privilege = Privilege {
-        name: "onlineChat",
-        enabled: response["privileges"]["onlineChat"]["enabled"].as_bool().unwrap()
-    };
-    let multiplayer_server: Privilege = Privilege {
-        name: "multiplayerServer",
-        enabled: response["privileges"]["multiplayerServer"]["enabled"].as_bool().unwrap()
-    };
-    let multiplayer_realms: Privilege = Privilege {
-        name: "multiplayerRealms",
-        enabled: response["privileges"]["multiplayerRealms"]["enabled"].as_bool().unwrap()
-    };
-    let telemetry: Privilege = Privilege {
-        name: "telemetry",
-        enabled: response["privileges"]["telemetry"]["enabled"].as_bool().unwrap()
-    };
-
-    let mut ban_status = None;
-    if response.get("banStatus").is_some() && response.get("bannedScopes").is_some() && response.get("MULTIPLAYER").is_some() {
-        let scope = &response["banStatus"]["bannedScopes"]["MULTIPLAYER"];
-        ban_status = Some(BanStatus {
-            reason: BanReason::from_string(scope["reason"].to_string()),
-            ban_id: scope["banId"].to_string(),
-            expires: scope["expires"].as_i64().unwrap(),
-            reason_message: if scope.get("reasonMessage").is_some() {
-                Some(scope.get("reasonMessage").unwrap().to_string())
-            } else {
-                None
+        .map_err(|error| {
+            match error.kind() {
+                ErrorKind::Decode => error.context("Unable to parse response from player attributes endpoint", 19),
+                _ => error.context("Unable to send player attributes request", 18)
             }
-        });
-    }
+        })?;
 
-    Ok(PlayerAttributes {
-        ban_status,
-        privileges: vec![online_chat, multiplayer_server, multiplayer_realms, telemetry]
-    })
+    let privileges = response.privileges;
+    let privileges = vec![
+        Privilege {
+            name: "onlineChat",
+            enabled: privileges.online_chat.enabled
+        },
+        Privilege {
+            name: "multiplayerServer",
+            enabled: privileges.multiplayer_server.enabled
+        },
+        Privilege {
+            name: "multiplayerRealms",
+            enabled: privileges.multiplayer_realms.enabled
+        },
+        Privilege {
+            name: "telemetry",
+            enabled: privileges.telemetry.enabled
+        },
+    ];
+
+    let ban_status = response.ban_status.and_then(|mut ban_status| ban_status.banned_scopes.remove("MULTIPLAYER")).map(|scope| {
+        BanStatus {
+            reason: BanReason::from_string(scope.reason),
+            ban_id: scope.ban_id,
+            expires: scope.expires,
+            reason_message: scope.reason_message
+        }
+    });
+
+    Ok(PlayerAttributes { ban_status, privileges })
 }
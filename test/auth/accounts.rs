@@ -0,0 +1,53 @@
+use std::{env, fs, time::Duration};
+
+use uuid::Uuid;
+
+use crate::auth::{
+    accounts::AccountManager,
+    microsoft::MinecraftEdition,
+    store::{FileTokenStore, StoredSession, TokenStore},
+    Session, TokenType
+};
+
+fn stored_session(id: u128) -> StoredSession {
+    StoredSession {
+        session: Session {
+            username: Uuid::from_u128(id),
+            roles: Vec::new(),
+            access_token: format!("access_token_{}", id),
+            token_type: TokenType::Bearer,
+            expires_in: Duration::from_secs(86400),
+            edition: MinecraftEdition::Java
+        },
+        refresh_token: Some(format!("refresh_token_{}", id))
+    }
+}
+
+#[test]
+fn test_switch_between_accounts() {
+    let directory = env::temp_dir().join(format!("minerust-{}", rand::random::<u64>()));
+    let mut accounts = AccountManager::new(FileTokenStore::new(&directory, [7; 16])).unwrap();
+    assert_eq!(accounts.accounts().count(), 0);
+    assert!(accounts.active().is_none());
+
+    accounts.add_account("main", stored_session(1)).unwrap();
+    accounts.add_account("alt", stored_session(2)).unwrap();
+    assert_eq!(accounts.accounts().collect::<Vec<_>>(), vec!["alt", "main"]);
+    assert_eq!(accounts.active(), Some(("main", &stored_session(1))));
+
+    assert_eq!(accounts.switch_to("alt").unwrap(), &stored_session(2));
+    assert_eq!(accounts.active().unwrap().1.session.username, Uuid::from_u128(2));
+    assert_eq!(accounts.switch_to("unknown").unwrap_err().code(), 31);
+    assert_eq!(accounts.active().unwrap().0, "alt");
+
+    let mut reloaded = AccountManager::new(FileTokenStore::new(&directory, [7; 16])).unwrap();
+    assert_eq!(reloaded.accounts().collect::<Vec<_>>(), vec!["alt", "main"]);
+    assert_eq!(reloaded.account("main"), Some(&stored_session(1)));
+    assert!(reloaded.active().is_none());
+
+    reloaded.switch_to("main").unwrap();
+    assert_eq!(reloaded.remove_account("main").unwrap(), Some(stored_session(1)));
+    assert!(reloaded.active().is_none());
+    assert_eq!(reloaded.store().accounts().unwrap(), vec!["alt"]);
+    fs::remove_dir_all(directory).unwrap();
+}
@@ -0,0 +1,415 @@
+use std::{
+    error::Error as StdError,
+    net::TcpListener,
+    time::{Duration, Instant}
+};
+
+use rand::{
+    rngs::{mock::StepRng, StdRng},
+    SeedableRng
+};
+use uuid::Uuid;
+
+use crate::{
+    auth::{
+        microsoft::{
+            internals::{RawAccessToken, RawSession, RawXstsResponse},
+            AccountErrorType, AuthEndpoints, AuthError, AuthStep, AuthToken, MicrosoftAuthenticator, MinecraftEdition, Ownership, TokenType, XSTSError
+        },
+        Session
+    },
+    test::mock::{MockResponse, MockServer},
+    web::{Error, ErrorKind, Requester}
+};
+
+#[test]
+fn test_authorize_url() {
+    let authenticator = MicrosoftAuthenticator::new("00000000-4c12-9f2c-0000-000000000000", 25585);
+    let (url, state) = authenticator.authorize_url();
+
+    assert!(url.starts_with("https://login.live.com/oauth20_authorize.srf?"));
+    assert!(url.contains("client_id=00000000-4c12-9f2c-0000-000000000000"));
+    assert!(url.contains("redirect_uri=http://127.0.0.1:25585"));
+    assert!(url.contains("scope=XboxLive.signin%20offline_access"));
+    assert!(url.contains(&format!("state={}", state)));
+    assert_ne!(authenticator.authorize_url().1, state);
+}
+
+#[test]
+fn test_authorize_url_seeded_state() {
+    let authenticator = MicrosoftAuthenticator::new("client_id", 25585);
+    let (url, state) = authenticator.authorize_url_with(&mut StepRng::new(0, 1 << 26));
+    assert_eq!(state, "ABCDEFGHIJKLMNO");
+    assert!(url.contains("state=ABCDEFGHIJKLMNO"));
+
+    let seeded = |seed| authenticator.authorize_url_with(&mut StdRng::seed_from_u64(seed)).1;
+    assert_eq!(seeded(7), seeded(7));
+    assert_ne!(seeded(7), seeded(8));
+}
+
+#[tokio::test]
+async fn test_exchange_code_unknown_state() {
+    let mut authenticator = MicrosoftAuthenticator::new("client_id", 25585);
+    let (_, state) = authenticator.authorize_url();
+
+    let error = authenticator.exchange_code("code", "unknown").await.unwrap_err();
+    assert_eq!(error.code(), 2);
+    assert_eq!(error.message(), "Unable to request the refresh token => Invalid state");
+    assert!(!error.message().contains(&state));
+}
+
+#[tokio::test]
+async fn test_exchange_code_then_refresh() {
+    let server = MockServer::builder()
+        .post(
+            "/oauth20_token.srf",
+            MockResponse::ok(r#"{ "access_token": "live_token", "expires_in": 86400, "token_type": "bearer", "refresh_token": "refresh_token" }"#)
+        )
+        .start();
+    let mut authenticator = MicrosoftAuthenticator::new("client_id", 25585).with_endpoints(AuthEndpoints::new(server.url(), server.url(), server.url(), server.url()));
+    assert_eq!(authenticator.refresh_access_token().await.unwrap_err().code(), 5);
+    assert!(server.requests().is_empty());
+
+    let (_, state) = authenticator.authorize_url();
+    assert_eq!(authenticator.exchange_code("authorization_code", &state).await.unwrap().access_token, "live_token");
+    assert_eq!(authenticator.refresh_token(), Some("refresh_token"));
+    assert_eq!(authenticator.refresh_access_token().await.unwrap().access_token, "live_token");
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].body.contains("grant_type=authorization_code"));
+    assert!(requests[0].body.contains("code=authorization_code"));
+    assert!(requests[1].body.contains("grant_type=refresh_token"));
+    assert!(requests[1].body.contains("refresh_token=refresh_token"));
+    assert!(!requests[1].body.contains("code="));
+}
+
+#[tokio::test]
+async fn test_refresh_keeps_token_without_rotation() {
+    let server = MockServer::builder()
+        .post(
+            "/oauth20_token.srf",
+            MockResponse::ok(r#"{ "access_token": "live_token", "expires_in": 86400, "token_type": "bearer" }"#)
+        )
+        .start();
+    let mut authenticator = MicrosoftAuthenticator::new("client_id", 25585)
+        .with_refresh_token(Some("stored_refresh_token".to_string()))
+        .with_endpoints(AuthEndpoints::new(server.url(), server.url(), server.url(), server.url()));
+
+    authenticator.refresh_access_token().await.unwrap();
+    authenticator.refresh_access_token().await.unwrap();
+    assert_eq!(authenticator.refresh_token(), Some("stored_refresh_token"));
+    assert!(server.requests().iter().all(|request| request.body.contains("refresh_token=stored_refresh_token")));
+}
+
+#[tokio::test]
+async fn test_callback_rejects_mismatched_state() {
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let mut authenticator = MicrosoftAuthenticator::new("client_id", port);
+    authenticator.start_callback_server().unwrap();
+    let (_, state) = authenticator.authorize_url();
+
+    let forged = reqwest::get(format!("http://127.0.0.1:{}/?code=forged_code&state=forged", port)).await.unwrap();
+    assert_eq!(forged.status(), reqwest::StatusCode::BAD_REQUEST);
+    let body = forged.text().await.unwrap();
+    assert!(!body.contains(&state));
+    assert_eq!(body, "Unable to request the refresh token => Invalid state");
+
+    let redirect = tokio::spawn(Requester::get(format!("http://127.0.0.1:{}/?code=authorization_code&state={}", port, state)).execute());
+    let code = tokio::time::timeout(Duration::from_secs(5), authenticator.wait_for_callback()).await.unwrap().unwrap();
+    assert_eq!(code, "authorization_code");
+    redirect.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_callback_server_released_on_drop() {
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let mut authenticator = MicrosoftAuthenticator::new("client_id", port);
+    authenticator.start_callback_server().unwrap();
+    assert!(TcpListener::bind(("127.0.0.1", port)).is_err());
+    drop(authenticator);
+
+    let deadline = Instant::now() + Duration::from_secs(1);
+    while TcpListener::bind(("127.0.0.1", port)).is_err() {
+        assert!(Instant::now() < deadline, "Callback server still holds port {}", port);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_callback_on_current_thread_runtime() {
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let mut authenticator = MicrosoftAuthenticator::new("client_id", port);
+    authenticator.start_callback_server().unwrap();
+    let (_, state) = authenticator.authorize_url();
+
+    let redirect = tokio::spawn(Requester::get(format!("http://127.0.0.1:{}/?code=authorization_code&state={}", port, state)).execute());
+    let code = tokio::time::timeout(Duration::from_secs(5), authenticator.wait_for_callback()).await.unwrap().unwrap();
+    assert_eq!(code, "authorization_code");
+    assert_eq!(redirect.await.unwrap().unwrap(), "Successfully received query");
+    assert!(authenticator.wait_for_callback().await.is_err());
+}
+
+#[test]
+fn test_expires_in_above_u16() {
+    let token: RawAccessToken = serde_json::from_str(r#"{ "access_token": "token", "expires_in": 86400, "token_type": "bearer" }"#).unwrap();
+    assert_eq!(token.expires_in, 86400);
+
+    let session: RawSession =
+        serde_json::from_str(r#"{ "username": "abe18c25-73dc-4f18-8638-adb604cb1d03", "roles": [], "token_type": "Bearer", "expires_in": 5000000000, "access_token": "token" }"#).unwrap();
+    assert_eq!(session.expires_in, 5000000000);
+}
+
+fn ownership(json: &str) -> Ownership {
+    Ownership::from_entitlements(&serde_json::from_str(json).unwrap()).unwrap()
+}
+
+#[test]
+fn test_ownership() {
+    assert_eq!(ownership(include_str!("../../fixtures/auth/microsoft/entitlements_owned.json")), Ownership::Owned);
+    assert_eq!(ownership(include_str!("../../fixtures/auth/microsoft/entitlements_game_pass.json")), Ownership::GamePass);
+    assert_eq!(
+        ownership(r#"{ "items": [ { "name": "game_minecraft", "source": "PURCHASE" }, { "name": "product_game_pass_ultimate" } ] }"#),
+        Ownership::Owned
+    );
+    assert_eq!(ownership(r#"{ "items": [ { "name": "game_minecraft", "source": "GAMEPASS" } ] }"#), Ownership::GamePass);
+    assert_eq!(ownership(r#"{ "items": [ { "name": "product_minecraft_bedrock" } ] }"#), Ownership::Demo);
+    assert_eq!(ownership(r#"{ "items": [] }"#), Ownership::None);
+    assert!(Ownership::from_entitlements(&serde_json::json!({ "items": "none" })).is_err());
+}
+
+#[tokio::test]
+async fn test_bedrock_session_rejected_by_java_endpoints() {
+    let session = Session {
+        username: Uuid::nil(),
+        roles: Vec::new(),
+        access_token: "access_token".to_string(),
+        token_type: crate::auth::TokenType::Bearer,
+        expires_in: Duration::from_secs(86400),
+        edition: MinecraftEdition::Bedrock
+    };
+    assert_eq!(MicrosoftAuthenticator::ownership(&session).await.unwrap_err().code(), 32);
+    assert_eq!(MicrosoftAuthenticator::has_minecraft(session.clone()).await.unwrap_err().code(), 32);
+    assert!(Session {
+        edition: MinecraftEdition::Java,
+        ..session
+    }
+    .require_java("Unable to request the entitlements")
+    .is_ok());
+
+    let token = AuthToken {
+        token: "token".to_string(),
+        user_hash: "userhash".to_string(),
+        token_type: TokenType::XSLS,
+        edition: Some(MinecraftEdition::Bedrock)
+    };
+    assert_eq!(MicrosoftAuthenticator::authenticate_minecraft(token).await.unwrap_err().code(), 32);
+
+    let json = r#"{"username":"00000000-0000-0000-0000-000000000000","roles":[],"access_token":"token","token_type":"Bearer","expires_in":{"secs":60,"nanos":0}}"#;
+    assert_eq!(serde_json::from_str::<Session>(json).unwrap().edition, MinecraftEdition::Java);
+}
+
+#[test]
+fn test_account_errors() {
+    let migrated = r#"{"error":"ForbiddenOperationException","errorMessage":"Invalid credentials. Account migrated, use email as username.","cause":"UserMigratedException"}"#;
+    assert_eq!(AccountErrorType::from_response(migrated), Some(AccountErrorType::AccountMigrated));
+    let app_registration = r#"{"path":"/authentication/login_with_xbox","errorMessage":"Invalid app registration, see https://aka.ms/AppRegInfo for more information"}"#;
+    assert_eq!(AccountErrorType::from_response(app_registration), Some(AccountErrorType::InvalidAppRegistration));
+    let forbidden = r#"{"path":"/authentication/login_with_xbox","errorType":"FORBIDDEN","error":"FORBIDDEN","errorMessage":"Forbidden","developerMessage":"Forbidden"}"#;
+    assert_eq!(AccountErrorType::from_response(forbidden), Some(AccountErrorType::Forbidden));
+    let not_found = r#"{"path":"/minecraft/profile","errorType":"NOT_FOUND","error":"NOT_FOUND","errorMessage":"The server has not found anything matching the request URI","developerMessage":"The server has not found anything matching the request URI"}"#;
+    assert_eq!(AccountErrorType::from_response(not_found), Some(AccountErrorType::NotFound));
+    let too_many_requests = r#"{"path":"/authentication/login_with_xbox","errorType":"TOO_MANY_REQUESTS","error":"TOO_MANY_REQUESTS"}"#;
+    assert_eq!(AccountErrorType::from_response(too_many_requests), Some(AccountErrorType::TooManyRequests));
+
+    assert_eq!(AccountErrorType::from_response(r#"{"error":"INTERNAL_SERVER_ERROR"}"#), None);
+    assert_eq!(AccountErrorType::from_response("Bad Gateway"), None);
+    assert_eq!(AccountErrorType::from_response(""), None);
+
+    assert_eq!(AccountErrorType::from_code(AccountErrorType::AccountMigrated.code()), Some(AccountErrorType::AccountMigrated));
+    assert_eq!(
+        AccountErrorType::from_error(&Error::new("Unable to authenticate => The account wasn't found", 36)),
+        Some(AccountErrorType::NotFound)
+    );
+    assert_eq!(AccountErrorType::from_error(&Error::new("Unable to authenticate => connection refused", 9)), None);
+}
+
+#[tokio::test]
+async fn test_account_error_keeps_source() {
+    let server = MockServer::builder()
+        .post(
+            "/authentication/login_with_xbox",
+            MockResponse::new(403, r#"{"path":"/authentication/login_with_xbox","errorType":"FORBIDDEN","error":"FORBIDDEN"}"#)
+        )
+        .start();
+    let token = AuthToken {
+        token: "token".to_string(),
+        user_hash: "userhash".to_string(),
+        token_type: TokenType::XSLS,
+        edition: Some(MinecraftEdition::Java)
+    };
+
+    let error = MicrosoftAuthenticator::authenticate_minecraft_with_endpoints(&AuthEndpoints::new(server.url(), server.url(), server.url(), server.url()), token)
+        .await
+        .unwrap_err();
+    assert_eq!(AccountErrorType::from_error(&error), Some(AccountErrorType::Forbidden));
+    assert_eq!(error.kind(), ErrorKind::Status);
+    let source = error.source().unwrap().downcast_ref::<Error>().unwrap();
+    assert_eq!(
+        source.response(),
+        Some(r#"{"path":"/authentication/login_with_xbox","errorType":"FORBIDDEN","error":"FORBIDDEN"}"#)
+    );
+}
+
+#[test]
+fn test_xsts_response() {
+    let token = r#"{"IssueInstant":"2020-12-07T19:52:08.4463796Z","Token":"token","DisplayClaims":{"xui":[{"uhs":"userhash"}]}}"#;
+    match serde_json::from_str::<RawXstsResponse>(token).unwrap() {
+        RawXstsResponse::Token(token) => {
+            assert_eq!(token.token, "token");
+            assert_eq!(token.user_hash(AuthStep::XstsAuthorize).unwrap(), "userhash");
+        }
+        RawXstsResponse::Error(_) => panic!("Token response parsed as error")
+    }
+
+    let error = r#"{"Identity":"0","XErr":2148916238,"Message":"","Redirect":"https://start.ui.xboxlive.com/AddChildToFamily"}"#;
+    match serde_json::from_str::<RawXstsResponse>(error).unwrap() {
+        RawXstsResponse::Error(error) => {
+            assert_eq!(error.error_code, 2148916238);
+            assert_eq!(error.redirect, "https://start.ui.xboxlive.com/AddChildToFamily");
+        }
+        RawXstsResponse::Token(_) => panic!("Error response parsed as token")
+    }
+
+    let error = XSTSError::from(Error::new("Unable to authenticate => connection refused", 7));
+    assert_eq!(error.error_code, Some(7));
+    assert_eq!(error.to_string(), "Unable to authenticate => connection refused");
+}
+
+#[test]
+fn test_missing_claims() {
+    let responses = [
+        r#"{"Token":"token"}"#,
+        r#"{"Token":"token","DisplayClaims":{}}"#,
+        r#"{"Token":"token","DisplayClaims":{"xui":[]}}"#,
+        r#"{"Token":"token","DisplayClaims":{"xui":[{"uhs":null}]}}"#
+    ];
+    for response in responses {
+        let RawXstsResponse::Token(token) = serde_json::from_str::<RawXstsResponse>(response).unwrap() else {
+            panic!("Token response parsed as error")
+        };
+        let error = token.user_hash(AuthStep::XboxUserAuth).unwrap_err();
+        assert_eq!(error.message(), "The xbox_user_auth response has no user hash claim");
+        assert_eq!(AuthError::from_error(&error), Some(AuthError::MissingClaims { stage: AuthStep::XboxUserAuth }));
+    }
+
+    let RawXstsResponse::Token(token) = serde_json::from_str::<RawXstsResponse>(r#"{"Token":"token","DisplayClaims":{"xui":[{"uhs":"user\"hash"}]}}"#).unwrap() else {
+        panic!("Token response parsed as error")
+    };
+    assert_eq!(token.user_hash(AuthStep::XboxUserAuth).unwrap(), "user\"hash");
+}
+
+fn login_server(xsts: MockResponse) -> MockServer {
+    MockServer::builder()
+        .post(
+            "/oauth20_token.srf",
+            MockResponse::ok(r#"{ "access_token": "live_token", "expires_in": 86400, "token_type": "bearer" }"#)
+        )
+        .post(
+            "/user/authenticate",
+            MockResponse::ok(r#"{ "Token": "user_token", "DisplayClaims": { "xui": [ { "uhs": "userhash" } ] } }"#)
+        )
+        .post("/xsts/authorize", xsts)
+        .post(
+            "/authentication/login_with_xbox",
+            MockResponse::ok(r#"{ "username": "abe18c25-73dc-4f18-8638-adb604cb1d03", "roles": [], "token_type": "Bearer", "expires_in": 86400, "access_token": "minecraft_token" }"#)
+        )
+        .start()
+}
+
+#[tokio::test]
+async fn test_login_full_chain() {
+    let server = login_server(MockResponse::ok(r#"{ "Token": "xsts_token", "DisplayClaims": { "xui": [ { "uhs": "userhash" } ] } }"#));
+    let mut authenticator = MicrosoftAuthenticator::new("client_id", 25585)
+        .with_refresh_token(Some("stored_refresh_token".to_string()))
+        .with_endpoints(AuthEndpoints::new(server.url(), server.url(), server.url(), server.url()));
+
+    let session = authenticator.login(MinecraftEdition::Java).await.unwrap();
+    assert_eq!(session.username, Uuid::parse_str("abe18c25-73dc-4f18-8638-adb604cb1d03").unwrap());
+    assert_eq!(session.access_token, "minecraft_token");
+    assert_eq!(session.expires_in, Duration::from_secs(86400));
+    assert_eq!(session.edition, MinecraftEdition::Java);
+
+    let requests = server.requests();
+    let paths = requests.iter().map(|request| request.path.as_str()).collect::<Vec<_>>();
+    assert_eq!(paths, vec!["/oauth20_token.srf", "/user/authenticate", "/xsts/authorize", "/authentication/login_with_xbox"]);
+    assert!(requests[0].body.contains("grant_type=refresh_token"));
+    assert!(requests[0].body.contains("refresh_token=stored_refresh_token"));
+    assert!(requests[1].body.contains("d=live_token"));
+    assert!(requests[2].body.contains("user_token"));
+    assert!(requests[3].body.contains("XBL3.0 x=userhash;xsts_token"));
+
+    authenticator.login(MinecraftEdition::Java).await.unwrap();
+    let requests = server.requests();
+    assert_eq!(requests.len(), 8);
+    assert!(requests[4].body.contains("refresh_token=stored_refresh_token"));
+    assert!(!requests[4].body.contains("code="));
+}
+
+#[tokio::test]
+async fn test_login_refused_xsts_token() {
+    let server = login_server(MockResponse::new(
+        401,
+        r#"{"Identity":"0","XErr":2148916233,"Message":"","Redirect":"https://start.ui.xboxlive.com/CreateAccount"}"#
+    ));
+    let mut authenticator = MicrosoftAuthenticator::new("client_id", 25585)
+        .with_refresh_token(Some("stored_refresh_token".to_string()))
+        .with_endpoints(AuthEndpoints::new(server.url(), server.url(), server.url(), server.url()));
+
+    let error = authenticator.login(MinecraftEdition::Java).await.unwrap_err();
+    assert_eq!(error.code(), 39);
+    assert!(error.message().contains("doesn't have a Xbox account"));
+    assert_eq!(server.requests().len(), 3);
+
+    assert_eq!(Error::from(XSTSError::normal("Unable to authenticate".to_string(), 7)).code(), 7);
+}
+
+#[tokio::test]
+async fn test_login_error_context() {
+    let server = login_server(MockResponse::new(502, "Bad Gateway"));
+    let mut authenticator = MicrosoftAuthenticator::new("client_id", 25585)
+        .with_refresh_token(Some("stored_refresh_token".to_string()))
+        .with_endpoints(AuthEndpoints::new(server.url(), server.url(), server.url(), server.url()));
+
+    let error = authenticator.login(MinecraftEdition::Java).await.unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "mc_login: xsts_authorize: Unable to parse auth response => expected value at line 1 column 1:8"
+    );
+    assert_eq!(error.operations(), vec![AuthStep::McLogin.to_string(), AuthStep::XstsAuthorize.to_string()]);
+
+    assert_eq!((error.message(), error.code()), ("Unable to parse auth response => expected value at line 1 column 1", 8));
+    let xsts = error.source().unwrap().downcast_ref::<Error>().unwrap();
+    assert_eq!(xsts.operations(), vec!["xsts_authorize"]);
+    let root = xsts.source().unwrap().downcast_ref::<Error>().unwrap();
+    assert!(root.operations().is_empty());
+    assert_eq!(root.to_string(), "Unable to parse auth response => expected value at line 1 column 1:8");
+    assert!(root.source().unwrap().downcast_ref::<serde_json::Error>().is_some());
+}
+
+#[tokio::test]
+async fn test_login_missing_claims() {
+    let server = login_server(MockResponse::ok(r#"{ "Token": "xsts_token", "DisplayClaims": { "xui": [] } }"#));
+    let mut authenticator = MicrosoftAuthenticator::new("client_id", 25585)
+        .with_refresh_token(Some("stored_refresh_token".to_string()))
+        .with_endpoints(AuthEndpoints::new(server.url(), server.url(), server.url(), server.url()));
+
+    let error = authenticator.login(MinecraftEdition::Java).await.unwrap_err();
+    assert_eq!(AuthError::from_error(&error), Some(AuthError::MissingClaims { stage: AuthStep::XstsAuthorize }));
+    assert_eq!(error.code(), 8);
+    assert_eq!(
+        error.to_string(),
+        "mc_login: xsts_authorize: Unable to parse auth response => The xsts_authorize response has no user hash claim:8"
+    );
+    assert_eq!(server.requests().len(), 3);
+}
@@ -0,0 +1,3 @@
+pub mod accounts;
+pub mod microsoft;
+pub mod store;
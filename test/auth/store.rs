@@ -0,0 +1,88 @@
+use std::{env, fs, time::Duration};
+
+use uuid::Uuid;
+
+use crate::auth::{
+    microsoft::MinecraftEdition,
+    store::{FileTokenStore, StoredSession, TokenStore},
+    Session, TokenType
+};
+
+fn store(key: [u8; 16]) -> FileTokenStore {
+    FileTokenStore::new(env::temp_dir().join(format!("minerust-{}", rand::random::<u64>())), key)
+}
+
+fn stored_session() -> StoredSession {
+    StoredSession {
+        session: Session {
+            username: Uuid::from_u128(1),
+            roles: vec!["role".to_string()],
+            access_token: "access_token".to_string(),
+            token_type: TokenType::Bearer,
+            expires_in: Duration::from_secs(86400),
+            edition: MinecraftEdition::Java
+        },
+        refresh_token: Some("M.R3_BAY.refresh_token".to_string())
+    }
+}
+
+#[test]
+fn test_file_token_store_round_trip() {
+    let store = store([7; 16]);
+    assert_eq!(store.load("account").unwrap(), None);
+
+    store.save("account", &stored_session()).unwrap();
+    let bytes = fs::read(store.directory().join("account.token")).unwrap();
+    assert!(!bytes.windows(13).any(|window| window == b"refresh_token"));
+    assert_eq!(store.load("account").unwrap(), Some(stored_session()));
+
+    store.delete("account").unwrap();
+    assert_eq!(store.load("account").unwrap(), None);
+    store.delete("account").unwrap();
+    fs::remove_dir_all(store.directory()).unwrap();
+}
+
+#[test]
+fn test_file_token_store_wrong_key() {
+    let store = store([7; 16]);
+    store.save("account", &stored_session()).unwrap();
+
+    let other = FileTokenStore::new(store.directory(), [8; 16]);
+    assert_eq!(other.load("account").unwrap_err().code(), 26);
+    assert_eq!(store.save("../account", &stored_session()).unwrap_err().code(), 23);
+    fs::remove_dir_all(store.directory()).unwrap();
+}
+
+#[test]
+fn test_file_token_store_rejects_tampering() {
+    let store = store([7; 16]);
+    store.save("account", &stored_session()).unwrap();
+    let path = store.directory().join("account.token");
+    let bytes = fs::read(&path).unwrap();
+
+    for index in [25, bytes.len() / 2, bytes.len() - 1] {
+        let mut tampered = bytes.clone();
+        tampered[index] ^= 1;
+        fs::write(&path, tampered).unwrap();
+        let error = store.load("account").unwrap_err();
+        assert_eq!(error.code(), 26);
+        assert_eq!(error.message(), "Unable to load token => Wrong key or corrupted token file");
+    }
+    fs::remove_dir_all(store.directory()).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_file_token_store_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let store = store([7; 16]);
+    fs::create_dir_all(store.directory()).unwrap();
+    let path = store.directory().join("account.token");
+    fs::write(&path, b"").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+    store.save("account", &stored_session()).unwrap();
+    assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    fs::remove_dir_all(store.directory()).unwrap();
+}
@@ -0,0 +1,29 @@
+use uuid::Uuid;
+
+use crate::{
+    blocking::{profile_from_uuid_at, uuid_from_username_at},
+    test::mock::{MockResponse, MockServer},
+    webapi::Endpoints
+};
+
+#[test]
+fn test_blocking_uuid_from_username() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let server = runtime.block_on(async {
+        MockServer::builder()
+            .get("/users/profiles/minecraft/Notch", MockResponse::ok(r#"{"id":"069a79f444e94726a5befca90e38aaf5","name":"Notch"}"#))
+            .get(
+                "/session/minecraft/profile/069a79f4-44e9-4726-a5be-fca90e38aaf5",
+                MockResponse::ok(r#"{"id":"069a79f444e94726a5befca90e38aaf5","name":"Notch","properties":[]}"#)
+            )
+            .start()
+    });
+    let endpoints = Endpoints::new(server.url(), server.url(), server.url());
+
+    let uuid = uuid_from_username_at(&endpoints, "Notch").unwrap();
+    assert_eq!(uuid, Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap());
+    assert_eq!(profile_from_uuid_at(&endpoints, uuid).unwrap().name, "Notch");
+
+    let error = uuid_from_username_at(&endpoints, "jeb_").unwrap_err();
+    assert_eq!(error.code(), 15);
+}
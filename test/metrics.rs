@@ -0,0 +1,150 @@
+use std::{net::TcpListener, time::Duration};
+
+use metrics_util::{
+    debugging::{DebugValue, DebuggingRecorder, Snapshotter},
+    MetricKind
+};
+
+use crate::{
+    metrics::{COMMAND_RATE_LIMIT_TOKENS, COMMAND_RATE_LIMIT_WAIT_SECONDS, CONN_BYTES_TOTAL, PACKET_SIZE_BYTES, WEBAPI_REQUESTS_TOTAL},
+    network::{
+        connection::{codec::ProtocolCodec, pipeline::framing::FrameEncoder, Client, Pipeline, Server},
+        session::{ClientSession, CommandRateLimit},
+        versions::V1_20_4
+    },
+    test::mock::{packet, MockConnection, MockResponse, MockServer},
+    web::Requester
+};
+
+type Labels = Vec<(String, String)>;
+type Series = (String, MetricKind, Labels, DebugValue);
+
+// Histograms are drained by every snapshot
+fn snapshot(snapshotter: &Snapshotter) -> Vec<Series> {
+    let mut series = snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .map(|(key, _, _, value)| {
+            let mut labels = key.key().labels().map(|label| (label.key().to_string(), label.value().to_string())).collect::<Vec<_>>();
+            labels.sort();
+            (key.key().name().to_string(), key.kind(), labels, value)
+        })
+        .collect::<Vec<_>>();
+    series.sort_by(|left, right| (&left.0, &left.2).cmp(&(&right.0, &right.2)));
+    series
+}
+
+fn series<'a>(snapshot: &'a [Series], name: &str) -> Vec<(MetricKind, Labels, &'a DebugValue)> {
+    snapshot
+        .iter()
+        .filter(|(candidate, ..)| candidate == name)
+        .map(|(_, kind, labels, value)| (*kind, labels.clone(), value))
+        .collect()
+}
+
+fn direction(direction: &str) -> Labels {
+    vec![("direction".to_string(), direction.to_string())]
+}
+
+#[test]
+fn test_connection_metrics() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    metrics::with_local_recorder(&recorder, || {
+        let mut client = ProtocolCodec::<Client>::new(Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing")));
+        let mut server = ProtocolCodec::<Server>::new(Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing")));
+
+        client.encode(packet(&[1, 2, 3])).unwrap();
+        client.encode(packet(&[4; 200])).unwrap();
+        server.feed(&client.take_output());
+        while server.decode().unwrap().is_some() {}
+
+        server.encode(packet(&[5])).unwrap();
+        client.feed(&server.take_output());
+        client.decode().unwrap().unwrap();
+    });
+
+    let snapshot = snapshot(&snapshotter);
+    // Frames of 200 bytes and more take a second byte for the length
+    assert_eq!(
+        series(&snapshot, CONN_BYTES_TOTAL),
+        vec![
+            (MetricKind::Counter, direction("clientbound"), &DebugValue::Counter(2 * 2)),
+            (MetricKind::Counter, direction("serverbound"), &DebugValue::Counter(2 * (4 + 202)))
+        ]
+    );
+
+    let sizes = series(&snapshot, PACKET_SIZE_BYTES);
+    assert_eq!(sizes.len(), 2);
+    for (kind, labels, value) in sizes {
+        assert_eq!(kind, MetricKind::Histogram);
+        let DebugValue::Histogram(values) = value else { panic!("Expected a histogram") };
+        let mut values = values.iter().map(|value| value.into_inner()).collect::<Vec<_>>();
+        values.sort_by(f64::total_cmp);
+        match labels == direction("serverbound") {
+            true => assert_eq!(values, vec![3.0, 3.0, 200.0, 200.0]),
+            false => assert_eq!(values, vec![1.0, 1.0])
+        }
+    }
+}
+
+#[test]
+fn test_command_rate_limit_metrics() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    metrics::with_local_recorder(&recorder, || {
+        let mut connection = MockConnection::client();
+        let mut session = ClientSession::new(&V1_20_4).with_command_rate_limit(Some(CommandRateLimit {
+            burst: 2,
+            interval: Duration::from_millis(50)
+        }));
+        for _ in 0..4 {
+            session.send_command(&mut connection, "gamemode creative", None).unwrap();
+        }
+    });
+
+    let snapshot = snapshot(&snapshotter);
+    assert_eq!(series(&snapshot, COMMAND_RATE_LIMIT_TOKENS), vec![(MetricKind::Gauge, vec![], &DebugValue::Gauge(2.0.into()))]);
+
+    let waits = series(&snapshot, COMMAND_RATE_LIMIT_WAIT_SECONDS);
+    let [(MetricKind::Histogram, _, DebugValue::Histogram(values))] = waits.as_slice() else {
+        panic!("Expected a histogram")
+    };
+    let values = values.iter().map(|value| value.into_inner()).collect::<Vec<_>>();
+    // The burst goes out at once, the rest waits for the spam to go down
+    assert_eq!(values[..2], [0.0, 0.0]);
+    assert!(values[2..].iter().all(|wait| *wait >= 0.04), "{:?}", values);
+}
+
+#[tokio::test]
+async fn test_webapi_metrics() {
+    let server = MockServer::builder()
+        .get("/profile", MockResponse::ok("{}"))
+        .get("/missing", MockResponse::new(404, "Not Found"))
+        .start();
+    let unused = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().port()
+    };
+
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let _guard = metrics::set_default_local_recorder(&recorder);
+
+    Requester::get(format!("{}/profile", server.url())).execute().await.unwrap();
+    Requester::get(format!("{}/profile?name=Notch", server.url())).execute().await.unwrap();
+    Requester::get(format!("{}/missing", server.url())).execute_with_status().await.unwrap();
+    Requester::get(format!("http://localhost:{}/refused", unused)).execute().await.unwrap_err();
+
+    let snapshot = snapshot(&snapshotter);
+    let label = |endpoint: &str, status: &str| vec![("endpoint".to_string(), endpoint.to_string()), ("status".to_string(), status.to_string())];
+    assert_eq!(
+        series(&snapshot, WEBAPI_REQUESTS_TOTAL),
+        vec![
+            (MetricKind::Counter, label("127.0.0.1", "200"), &DebugValue::Counter(2)),
+            (MetricKind::Counter, label("127.0.0.1", "404"), &DebugValue::Counter(1)),
+            (MetricKind::Counter, label("localhost", "error"), &DebugValue::Counter(1))
+        ]
+    );
+}
@@ -0,0 +1,102 @@
+use std::{collections::VecDeque, marker::PhantomData, sync::Arc, time::Duration};
+
+use crate::network::{
+    buffer::Buffer,
+    connection::{Client, Connection, Pipeline, Role, Server, Writable},
+    decode::{DecodeMode, DecodeReport},
+    ByteOrder, Error, PacketDirection, PacketState
+};
+
+pub struct MockConnection<R: Role = Server> {
+    pub packets: Vec<Buffer>,
+    pub incoming: VecDeque<Buffer>,
+    state: PacketState,
+    protocol_version: Option<i32>,
+    decode_mode: DecodeMode,
+    decode_report: Arc<DecodeReport>,
+    role: PhantomData<R>
+}
+
+impl MockConnection<Client> {
+    pub fn client() -> Self {
+        Self::new((), Pipeline::new())
+    }
+}
+
+impl MockConnection<Server> {
+    pub fn server() -> Self {
+        Self::new((), Pipeline::new())
+    }
+}
+
+impl<R: Role> MockConnection<R> {
+    pub fn take(&mut self) -> Vec<Buffer> {
+        std::mem::take(&mut self.packets)
+    }
+}
+
+impl<R: Role> Connection<'static, ()> for MockConnection<R> {
+    fn new(_: (), _: Pipeline<'static>) -> Self {
+        Self {
+            packets: Vec::new(),
+            incoming: VecDeque::new(),
+            state: PacketState::Play,
+            protocol_version: None,
+            decode_mode: DecodeMode::Strict,
+            decode_report: Arc::new(DecodeReport::new()),
+            role: PhantomData
+        }
+    }
+
+    fn write<T: Writable>(&mut self, packet: T) -> Result<usize, Error> {
+        let mut buffer = packet.write(Buffer::empty(true, Some(ByteOrder::BigEndian)))?;
+        let length = buffer.len();
+        buffer.reset();
+        self.packets.push(buffer);
+        Ok(length)
+    }
+
+    fn read_buffer(&mut self, _: Option<Duration>, _: ByteOrder) -> Result<(Buffer, Duration), Error> {
+        Err(Error::NotReadable("MockConnection".to_string()))
+    }
+
+    fn read_packet(&mut self) -> Result<Buffer, Error> {
+        self.incoming.pop_front().ok_or_else(|| Error::NotReadable("MockConnection".to_string()))
+    }
+
+    fn state(&self) -> PacketState {
+        self.state
+    }
+
+    fn set_state(&mut self, state: PacketState) {
+        self.state = state;
+    }
+
+    fn protocol_version(&self) -> Option<i32> {
+        self.protocol_version
+    }
+
+    fn set_protocol_version(&mut self, protocol_version: Option<i32>) {
+        self.protocol_version = protocol_version;
+    }
+
+    fn decode_mode(&self) -> DecodeMode {
+        self.decode_mode
+    }
+
+    fn set_decode_mode(&mut self, decode_mode: DecodeMode) {
+        self.decode_mode = decode_mode;
+    }
+
+    fn decode_report(&self) -> &Arc<DecodeReport> {
+        &self.decode_report
+    }
+
+    fn read_direction() -> PacketDirection {
+        R::read_direction()
+    }
+
+    fn write_direction() -> PacketDirection {
+        R::write_direction()
+    }
+}
@@ -0,0 +1,15 @@
+#[cfg(feature = "std-net")]
+mod connection;
+#[cfg(feature = "network")]
+mod packet;
+#[cfg(feature = "web")]
+mod server;
+
+#[cfg(feature = "std-net")]
+pub use connection::MockConnection;
+#[cfg(feature = "network")]
+pub use packet::round_trip;
+#[cfg(feature = "std-net")]
+pub use packet::{feed, packet};
+#[cfg(feature = "web")]
+pub use server::{MockResponse, MockServer};
@@ -0,0 +1,30 @@
+#[cfg(feature = "std-net")]
+use crate::network::Error;
+use crate::network::{
+    buffer::Buffer,
+    connection::{Readable, Writable},
+    ByteOrder
+};
+
+#[cfg(feature = "std-net")]
+pub fn packet(bytes: &[u8]) -> Buffer {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_bytes(bytes).unwrap();
+    buffer
+}
+
+// Hands a packet to a tracker from its start, which has to leave it there for the next handler
+#[cfg(feature = "std-net")]
+pub fn feed(mut packet: Buffer, handle: impl FnOnce(&mut Buffer) -> Result<bool, Error>) -> bool {
+    packet.reset();
+    let handled = handle(&mut packet).unwrap();
+    assert_eq!(packet.position(), 0);
+    handled
+}
+
+pub fn round_trip<T: Writable + Readable>(value: &T) -> (Vec<u8>, T) {
+    let mut buffer = value.write(Buffer::empty(true, Some(ByteOrder::BigEndian))).unwrap();
+    let bytes = buffer.to_bytes();
+    buffer.reset();
+    (bytes, T::decode(&mut buffer).unwrap())
+}
@@ -0,0 +1,141 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration
+};
+
+use tokio::sync::oneshot;
+use warp::{
+    http::{HeaderMap, Method, StatusCode},
+    hyper::body::Bytes,
+    path::FullPath,
+    Filter
+};
+
+#[derive(Clone, Debug)]
+pub struct MockResponse {
+    pub status: u16,
+    pub body: String,
+    pub delay: Option<Duration>
+}
+
+impl MockResponse {
+    pub fn new(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: body.into(),
+            delay: None
+        }
+    }
+
+    pub fn ok(body: impl Into<String>) -> Self {
+        Self::new(200, body)
+    }
+
+    pub fn delay(self, delay: Duration) -> Self {
+        Self { delay: Some(delay), ..self }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MockRequest {
+    #[cfg(all(feature = "webapi", feature = "network"))]
+    pub method: Method,
+    #[cfg(feature = "auth")]
+    pub path: String,
+    #[cfg(feature = "webapi")]
+    pub query: String,
+    pub headers: HeaderMap,
+    pub body: String
+}
+
+pub struct MockServer {
+    address: SocketAddr,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+    _shutdown: oneshot::Sender<()>
+}
+
+impl MockServer {
+    pub fn builder() -> MockServerBuilder {
+        MockServerBuilder { routes: HashMap::new() }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+pub struct MockServerBuilder {
+    routes: HashMap<(Method, String), MockResponse>
+}
+
+impl MockServerBuilder {
+    pub fn route(mut self, method: Method, path: impl Into<String>, response: MockResponse) -> Self {
+        self.routes.insert((method, path.into()), response);
+        self
+    }
+
+    pub fn get(self, path: impl Into<String>, response: MockResponse) -> Self {
+        self.route(Method::GET, path, response)
+    }
+
+    pub fn post(self, path: impl Into<String>, response: MockResponse) -> Self {
+        self.route(Method::POST, path, response)
+    }
+
+    pub fn start(self) -> MockServer {
+        let routes = Arc::new(self.routes);
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded_requests = requests.clone();
+        let query = warp::query::raw().or(warp::any().map(String::new)).unify();
+        let filter = warp::method()
+            .and(warp::path::full())
+            .and(query)
+            .and(warp::header::headers_cloned())
+            .and(warp::body::bytes())
+            .then(move |method: Method, path: FullPath, query: String, headers: HeaderMap, body: Bytes| {
+                #[cfg(not(feature = "webapi"))]
+                let _ = query;
+                recorded_requests.lock().unwrap().push(MockRequest {
+                    #[cfg(all(feature = "webapi", feature = "network"))]
+                    method: method.clone(),
+                    #[cfg(feature = "auth")]
+                    path: path.as_str().to_string(),
+                    #[cfg(feature = "webapi")]
+                    query,
+                    headers,
+                    body: String::from_utf8_lossy(&body).to_string()
+                });
+
+                let response = routes.get(&(method, path.as_str().to_string())).cloned();
+                async move {
+                    match response {
+                        Some(response) => {
+                            if let Some(delay) = response.delay {
+                                tokio::time::sleep(delay).await;
+                            }
+                            warp::reply::with_status(response.body, StatusCode::from_u16(response.status).unwrap())
+                        }
+                        None => warp::reply::with_status(String::new(), StatusCode::NOT_FOUND)
+                    }
+                }
+            });
+
+        let (sender, receiver) = oneshot::channel::<()>();
+        let (address, server) = warp::serve(filter).bind_with_graceful_shutdown(([127, 0, 0, 1], 0), async {
+            receiver.await.ok();
+        });
+        tokio::spawn(server);
+
+        MockServer {
+            address,
+            requests,
+            _shutdown: sender
+        }
+    }
+}
@@ -1,2 +1,26 @@
+#[cfg(feature = "auth")]
+pub mod auth;
+
+#[cfg(all(feature = "blocking", feature = "webapi"))]
+pub mod blocking;
+
+#[cfg(any(feature = "web", feature = "network"))]
+pub mod mock;
+
+#[cfg(all(feature = "metrics", feature = "std-net", feature = "web"))]
+pub mod metrics;
+
+#[cfg(feature = "std-net")]
+pub mod network;
+
+#[cfg(feature = "network")]
+pub mod profile;
+
+#[cfg(all(feature = "auth", feature = "webapi", feature = "std-net"))]
+pub mod transcript;
+
+#[cfg(feature = "web")]
+pub mod web;
+
 #[cfg(feature = "webapi")]
 pub mod webapi;
@@ -0,0 +1,294 @@
+use std::io::SeekFrom;
+
+use crate::network::{
+    buffer::{Buffer, MAX_STRING_LENGTH},
+    connection::Readable,
+    types::JavaUtf,
+    ByteOrder, Error
+};
+
+#[test]
+fn test_var_i32_five_bytes() {
+    let mut buffer = Buffer::new(vec![0xff, 0xff, 0xff, 0xff, 0x0f], false, None);
+    assert_eq!(buffer.read_var_i32().unwrap(), -1);
+    assert_eq!(buffer.position(), 5);
+
+    let mut buffer = Buffer::new(vec![0xff, 0xff, 0xff, 0xff, 0x07], false, None);
+    assert_eq!(buffer.read_var_i32().unwrap(), i32::MAX);
+
+    let mut buffer = Buffer::new(vec![0x80, 0x80, 0x80, 0x80, 0x08], false, None);
+    assert_eq!(buffer.read_var_i32().unwrap(), i32::MIN);
+}
+
+#[test]
+fn test_var_i32_round_trip() {
+    for value in [0, 1, 127, 128, 255, 25565, 2097151, i32::MAX, -1, i32::MIN] {
+        let mut buffer = Buffer::empty(true, None);
+        buffer.write_var_i32(value).unwrap();
+        buffer.reset();
+        assert_eq!(buffer.read_var_i32().unwrap(), value);
+    }
+}
+
+#[test]
+fn test_var_i32_too_long() {
+    let mut buffer = Buffer::new(vec![0xff, 0xff, 0xff, 0xff, 0xff, 0x01], false, None);
+    match buffer.read_var_i32() {
+        Err(Error::Decode { offset, reason }) => {
+            assert_eq!(offset, 0);
+            assert_eq!(reason, "VarInt too long");
+        }
+        result => panic!("Expected VarInt too long error, got {:?}", result)
+    }
+}
+
+#[test]
+fn test_var_i64_round_trip() {
+    for value in [0, 1, 127, 128, 25565, i32::MAX as i64, i64::MAX, -1, i64::MIN] {
+        let mut buffer = Buffer::empty(true, None);
+        buffer.write_var_i64(value).unwrap();
+        buffer.reset();
+        assert_eq!(buffer.read_var_i64().unwrap(), value);
+    }
+
+    let mut buffer = Buffer::empty(true, None);
+    buffer.write_var_i64(-1).unwrap();
+    assert_eq!(buffer.to_bytes(), vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]);
+}
+
+fn java_utf(string: &str) -> Vec<u8> {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_java_utf(string).unwrap();
+    let bytes = buffer.to_bytes();
+
+    buffer.reset();
+    assert_eq!(buffer.read_java_utf().unwrap(), string);
+    bytes
+}
+
+// The expected bytes are the output of DataOutputStream.writeUTF on a JVM
+#[test]
+fn test_java_utf() {
+    assert_eq!(java_utf("Connect"), b"\x00\x07Connect");
+    assert_eq!(java_utf("\u{e9}\u{20ac}"), vec![0x00, 0x05, 0xC3, 0xA9, 0xE2, 0x82, 0xAC]);
+    assert_eq!(java_utf("\u{1F600}"), vec![0x00, 0x06, 0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80]);
+    assert_eq!(java_utf("a\0b"), vec![0x00, 0x04, 0x61, 0xC0, 0x80, 0x62]);
+}
+
+#[test]
+fn test_java_utf_length_limit() {
+    let bytes = java_utf(&"\u{20ac}".repeat(21845));
+    assert_eq!(bytes[..2], [0xFF, 0xFF]);
+    assert_eq!(bytes.len(), 65537);
+
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    assert!(buffer.write_java_utf(&"a".repeat(65536)).is_err());
+    assert!(buffer.write_java_utf(&"\0".repeat(32768)).is_err());
+}
+
+#[test]
+fn test_java_utf_malformed() {
+    assert!(Buffer::new(vec![0x00, 0x02, 0xC3, 0x29], false, Some(ByteOrder::BigEndian)).read_java_utf().is_err());
+    assert!(Buffer::new(vec![0x00, 0x03, 0xED, 0xA0, 0xBD], false, Some(ByteOrder::BigEndian)).read_java_utf().is_err());
+    assert_eq!(
+        JavaUtf::read(&mut Buffer::new(vec![0x00, 0x02, 0xC0, 0x80], false, Some(ByteOrder::BigEndian))).unwrap(),
+        JavaUtf("\0".to_string())
+    );
+}
+
+#[test]
+fn test_decode_offsets() {
+    let mut buffer = Buffer::new(vec![0x01, 0x02, 0x61, 0xFF], false, None);
+    buffer.read_u8().unwrap();
+    assert!(matches!(buffer.read_string(), Err(Error::Decode { offset: 3, .. })));
+
+    let mut buffer = Buffer::new(vec![0x00, 0x02], false, None);
+    assert!(matches!(buffer.read_bool(), Ok(false)));
+    assert!(matches!(buffer.read_bool(), Err(Error::Decode { offset: 1, .. })));
+
+    let mut buffer = Buffer::new(vec![0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F], false, None);
+    buffer.read_u8().unwrap();
+    assert!(matches!(buffer.read_byte_array(), Err(Error::Decode { offset: 1, .. })));
+}
+
+#[test]
+fn test_write_string_bounded() {
+    let mut buffer = Buffer::empty(true, None);
+    buffer.write_str(&"\u{20ac}".repeat(MAX_STRING_LENGTH)).unwrap();
+    assert!(buffer.write_str(&"\u{20ac}".repeat(MAX_STRING_LENGTH + 1)).is_err());
+
+    assert!(buffer.write_string_bounded(&"\u{1F600}".repeat(8), 16).is_ok());
+    assert!(buffer.write_string_bounded(&"\u{1F600}".repeat(9), 16).is_err());
+
+    let error = buffer.write_string_bounded(&"\u{20ac}".repeat(6), 5).unwrap_err();
+    assert!(error.to_string().contains("was 18 bytes encoded, max 15"));
+    let error = buffer.write_string_bounded(&"\u{1F600}".repeat(3), 5).unwrap_err();
+    assert!(error.to_string().contains("was 6 characters, max 5"));
+}
+
+#[test]
+fn test_string_length_prefix_is_bytes() {
+    let mut buffer = Buffer::empty(true, None);
+    buffer.write_string_bounded(&"\u{20ac}".repeat(16), 16).unwrap();
+    buffer.reset();
+    assert_eq!(buffer.read_var_i32().unwrap(), 48);
+    buffer.reset();
+    assert_eq!(buffer.read_string_bounded(16).unwrap(), "\u{20ac}".repeat(16));
+
+    let mut buffer = Buffer::empty(true, None);
+    buffer.write_string_bounded(&"\u{1F600}".repeat(8), 16).unwrap();
+    let mut over = Buffer::empty(true, None);
+    over.write_str(&format!("{}a", "\u{1F600}".repeat(8))).unwrap();
+    buffer.reset();
+    over.reset();
+    assert_eq!((buffer.read_var_i32().unwrap(), over.read_var_i32().unwrap()), (32, 33));
+    buffer.reset();
+    over.reset();
+    assert_eq!(buffer.read_string_bounded(16).unwrap().chars().count(), 8);
+    assert!(matches!(over.read_string_bounded(16), Err(Error::Decode { offset: 0, .. })));
+
+    let mixed = "abcde\u{e9}\u{20ac}\u{1F600}\u{1F600}";
+    let mut buffer = Buffer::empty(true, None);
+    buffer.write_string_bounded(mixed, 11).unwrap();
+    assert!(buffer.write_string_bounded(mixed, 10).is_err());
+    buffer.reset();
+    assert_eq!(buffer.read_var_i32().unwrap() as usize, mixed.len());
+    buffer.reset();
+    assert_eq!(buffer.read_string_bounded(11).unwrap(), mixed);
+
+    buffer.reset();
+    assert_eq!(buffer.read_string_bounded(usize::MAX).unwrap(), mixed);
+}
+
+#[test]
+fn test_string_supplementary_characters() {
+    for string in [
+        "\u{1F600}",
+        "a\u{1F44D}b",
+        "\u{1F3F3}\u{FE0F}\u{200D}\u{1F308}",
+        "\u{1D11E}\u{10348}",
+        "\u{10FFFF}",
+        "\u{FFFF}\u{10000}"
+    ] {
+        let mut buffer = Buffer::empty(true, None);
+        buffer.write_str(string).unwrap();
+        buffer.reset();
+        assert_eq!(buffer.read_var_i32().unwrap() as usize, string.len());
+        assert_eq!(buffer.read_bytes(string.len()).unwrap(), string.as_bytes());
+        buffer.reset();
+        assert_eq!(buffer.read_string().unwrap(), string);
+        assert_eq!(buffer.remaining(), 0);
+    }
+}
+
+#[test]
+fn test_string_malformed_supplementary_characters() {
+    // U+1F600 as the surrogates D83D DE00 in modified UTF-8, like Java's DataOutputStream writes it
+    let mut buffer = Buffer::new(vec![0x07, 0x61, 0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80], false, None);
+    match buffer.read_string() {
+        Err(Error::Decode { offset, reason }) => {
+            assert_eq!(offset, 2);
+            assert_eq!(reason, "UTF-16 surrogate in string, supplementary characters have to be encoded as four bytes");
+        }
+        result => panic!("Expected decode error, got {:?}", result)
+    }
+
+    let mut buffer = Buffer::new(vec![0x04, 0x61, 0xF0, 0x9F, 0x98, 0x80], false, None);
+    match buffer.read_string() {
+        Err(Error::Decode { offset, reason }) => {
+            assert_eq!(offset, 2);
+            assert_eq!(reason, "Truncated UTF-8 sequence at the end of the string");
+        }
+        result => panic!("Expected decode error, got {:?}", result)
+    }
+}
+
+#[test]
+fn test_read_string_bounded() {
+    let mut buffer = Buffer::empty(true, None);
+    buffer.write_str(&"\u{20ac}".repeat(MAX_STRING_LENGTH)).unwrap();
+    buffer.reset();
+    assert_eq!(buffer.read_string().unwrap().encode_utf16().count(), MAX_STRING_LENGTH);
+
+    let mut buffer = Buffer::empty(true, None);
+    buffer.write_var_i32(MAX_STRING_LENGTH as i32 * 3 + 1).unwrap();
+    buffer.reset();
+    assert!(matches!(buffer.read_string(), Err(Error::Decode { offset: 0, .. })));
+
+    let mut buffer = Buffer::empty(true, None);
+    buffer.write_string_bounded(&"\u{20ac}".repeat(17), 17).unwrap();
+    buffer.reset();
+    assert!(matches!(buffer.read_string_bounded(16), Err(Error::Decode { offset: 0, .. })));
+    buffer.reset();
+    assert_eq!(buffer.read_string_bounded(17).unwrap(), "\u{20ac}".repeat(17));
+
+    let mut buffer = Buffer::empty(true, None);
+    buffer.write_str(&"a".repeat(17)).unwrap();
+    buffer.reset();
+    match buffer.read_string_bounded(16) {
+        Err(Error::Decode { offset, reason }) => {
+            assert_eq!(offset, 0);
+            assert_eq!(reason, "The received string length is longer than maximum allowed (17 > 16)");
+        }
+        result => panic!("Expected decode error, got {:?}", result)
+    }
+}
+
+#[test]
+fn test_seek() {
+    let mut buffer = Buffer::new(vec![0x00, 0x01, 0x02, 0x03, 0x04, 0x05], false, None);
+    assert_eq!(buffer.advance(4).unwrap(), 4);
+    assert_eq!(buffer.read_u8().unwrap(), 0x04);
+    assert_eq!(buffer.rewind(4).unwrap(), 1);
+    assert_eq!(buffer.read_u8().unwrap(), 0x01);
+
+    assert_eq!(buffer.seek(SeekFrom::End(-1)).unwrap(), 5);
+    assert_eq!(buffer.seek(SeekFrom::Start(6)).unwrap(), 6);
+    assert_eq!(buffer.remaining(), 0);
+}
+
+#[test]
+fn test_seek_out_of_range() {
+    let mut buffer = Buffer::new(vec![0x00, 0x01, 0x02], false, None);
+    buffer.advance(1).unwrap();
+    assert!(matches!(buffer.advance(3), Err(Error::OutOfBounds(4, 3))));
+    assert!(matches!(buffer.seek(SeekFrom::Start(4)), Err(Error::OutOfBounds(4, 3))));
+    assert!(buffer.rewind(2).is_err());
+    assert!(matches!(buffer.advance(usize::MAX), Err(Error::Other(_))));
+    assert!(matches!(buffer.rewind(usize::MAX), Err(Error::Other(_))));
+    assert_eq!(buffer.position(), 1);
+}
+
+#[test]
+fn test_split_off() {
+    let mut buffer = Buffer::new(vec![1, 2, 3, 4, 5], true, Some(ByteOrder::LittleEndian));
+    buffer.advance(4).unwrap();
+
+    let mut tail = buffer.split_off(2);
+    assert_eq!(buffer.to_bytes(), vec![1, 2]);
+    assert_eq!(buffer.position(), 2);
+    assert_eq!(tail.to_bytes(), vec![3, 4, 5]);
+    assert_eq!(tail.position(), 0);
+    assert_eq!(tail.byte_order(), Some(ByteOrder::LittleEndian));
+    assert!(tail.writable());
+    assert_eq!(tail.read_u8().unwrap(), 3);
+
+    let empty = buffer.split_off(2);
+    assert!(empty.is_empty());
+    assert_eq!(buffer.len(), 2);
+}
+
+#[test]
+fn test_drain_front() {
+    let mut buffer = Buffer::new(vec![1, 2, 3, 4, 5], true, Some(ByteOrder::BigEndian));
+    buffer.advance(3).unwrap();
+
+    buffer.drain_front(2);
+    assert_eq!(buffer.to_bytes(), vec![3, 4, 5]);
+    assert_eq!(buffer.position(), 1);
+    assert_eq!(buffer.read_u8().unwrap(), 4);
+
+    buffer.drain_front(3);
+    assert!(buffer.is_empty());
+    assert_eq!(buffer.position(), 0);
+}
@@ -0,0 +1,186 @@
+use std::{
+    net::{TcpListener, TcpStream},
+    thread::{self, JoinHandle}
+};
+
+use uuid::Uuid;
+
+use crate::network::{
+    buffer::Buffer,
+    bundle::{BundleEvent, Bundler, MAX_BUNDLE_PACKETS},
+    connection::{pipeline::framing::FrameEncoder, socket::SocketConnection, Client, Connection, Pipeline, Server, Writable},
+    tracker::{EntityKind, EntityTracker, EntityUpdate},
+    versions::{V1_19_2, V1_19_3, V1_20_4},
+    ByteOrder, Error, PacketState
+};
+
+fn spawn() -> Buffer {
+    let mut packet = EntityUpdate::Spawn {
+        id: 7,
+        uuid: Uuid::from_u128(7),
+        kind: EntityKind::Type(5),
+        position: (0.0, 64.0, 0.0),
+        yaw: 0.0,
+        pitch: 0.0
+    }
+    .write_versioned(V1_20_4.protocol)
+    .unwrap();
+    packet.reset();
+    packet
+}
+
+fn teleport() -> Buffer {
+    let mut packet = EntityUpdate::Teleport {
+        id: 7,
+        position: (10.0, 70.0, -10.0),
+        yaw: 0.0,
+        pitch: 0.0,
+        on_ground: true
+    }
+    .write_versioned(V1_20_4.protocol)
+    .unwrap();
+    packet.reset();
+    packet
+}
+
+fn packet(id: i32) -> Buffer {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(id).unwrap();
+    buffer.reset();
+    buffer
+}
+
+fn connect(server: impl FnOnce(SocketConnection<'static, Server>) + Send + 'static) -> (SocketConnection<'static, Client>, JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let mut connection = SocketConnection::<Server>::new(listener.accept().unwrap().0, Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing")));
+        connection.set_state(PacketState::Play);
+        connection.set_protocol_version(Some(V1_20_4.protocol));
+        server(connection)
+    });
+    let mut connection = SocketConnection::<Client>::new(TcpStream::connect(address).unwrap(), Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing")));
+    connection.set_state(PacketState::Play);
+    (connection, server)
+}
+
+#[test]
+fn test_bundled_spawn_is_atomic() {
+    let mut bundler = Bundler::new(&V1_20_4);
+    let mut tracker = EntityTracker::new(&V1_20_4);
+    let mut observed = Vec::new();
+    for packet in [packet(0x00), spawn(), teleport(), packet(0x00), packet(0x3F)] {
+        match bundler.offer(PacketState::Play, packet).unwrap() {
+            Some(BundleEvent::Bundle(mut bundle)) => {
+                assert_eq!(bundle.len(), 2);
+                for packet in &mut bundle.packets {
+                    assert!(tracker.handle(packet).unwrap());
+                }
+                observed.push(tracker.get(7).map(|entity| entity.position));
+            }
+            Some(BundleEvent::Packet(mut packet)) => {
+                assert!(!tracker.handle(&mut packet).unwrap());
+                observed.push(tracker.get(7).map(|entity| entity.position));
+            }
+            None => assert!(tracker.is_empty())
+        }
+    }
+    assert_eq!(observed, vec![Some((10.0, 70.0, -10.0)), Some((10.0, 70.0, -10.0))]);
+    assert!(!bundler.is_open());
+    bundler.finish().unwrap();
+}
+
+#[test]
+fn test_write_and_read_bundle() {
+    let (mut connection, server) = connect(|mut connection| {
+        let (spawn, teleport) = (spawn(), teleport());
+        connection.write_bundle(&[&spawn, &teleport]).unwrap();
+        connection.write(packet(0x3F)).unwrap();
+    });
+
+    let mut bundler = Bundler::new(&V1_20_4);
+    let mut tracker = EntityTracker::new(&V1_20_4);
+    let BundleEvent::Bundle(mut bundle) = bundler.read(&mut connection).unwrap() else {
+        panic!("Expected a bundle")
+    };
+    bundle.packets.iter_mut().for_each(|packet| assert!(tracker.handle(packet).unwrap()));
+    assert_eq!(tracker.get(7).unwrap().position, (10.0, 70.0, -10.0));
+
+    let BundleEvent::Packet(mut packet) = bundler.read(&mut connection).unwrap() else {
+        panic!("Expected a packet")
+    };
+    assert_eq!(packet.read_var_i32().unwrap(), 0x3F);
+    assert!(bundler.read(&mut connection).is_err());
+    server.join().unwrap();
+}
+
+#[test]
+fn test_unterminated_bundle() {
+    let (mut connection, server) = connect(|mut connection| {
+        connection.write(packet(0x00)).unwrap();
+        connection.write(spawn()).unwrap();
+    });
+    server.join().unwrap();
+
+    let mut bundler = Bundler::new(&V1_20_4);
+    let Err(Error::Other(reason)) = bundler.read(&mut connection) else {
+        panic!("Expected the bundle to fail")
+    };
+    assert!(reason.starts_with("Unable to read bundle => The connection closed after 1 bundled packets"));
+    assert!(!bundler.is_open());
+
+    let mut bundler = Bundler::new(&V1_20_4);
+    bundler.offer(PacketState::Play, packet(0x00)).unwrap();
+    assert!(bundler.finish().is_err());
+}
+
+#[test]
+fn test_oversized_bundle() {
+    let mut bundler = Bundler::new(&V1_20_4).with_max_packets(2);
+    assert!(bundler.offer(PacketState::Play, packet(0x00)).unwrap().is_none());
+    assert!(bundler.offer(PacketState::Play, spawn()).unwrap().is_none());
+    assert!(bundler.offer(PacketState::Play, teleport()).unwrap().is_none());
+    assert!(matches!(bundler.offer(PacketState::Play, packet(0x3F)), Err(Error::Other(_))));
+    assert!(!bundler.is_open());
+
+    let (mut connection, server) = connect(|mut connection| {
+        let packets = vec![packet(0x3F); MAX_BUNDLE_PACKETS + 1];
+        let packets = packets.iter().map(|packet| packet as &dyn Writable).collect::<Vec<_>>();
+        assert!(matches!(connection.write_bundle(&packets), Err(Error::Other(_))));
+        connection.write_bundle(&packets[..MAX_BUNDLE_PACKETS]).unwrap();
+    });
+    assert!(connection.write_bundle(&[&packet(0x3F)]).is_err());
+    server.join().unwrap();
+
+    let mut bundler = Bundler::new(&V1_20_4);
+    let BundleEvent::Bundle(bundle) = bundler.read(&mut connection).unwrap() else {
+        panic!("Expected a bundle")
+    };
+    assert_eq!(bundle.len(), MAX_BUNDLE_PACKETS);
+}
+
+#[test]
+fn test_bundles_need_play_and_1_19_4() {
+    // 0x00 is Spawn Entity before 1.19.4 and a regular packet outside of play
+    let mut bundler = Bundler::new(&V1_19_2);
+    assert!(matches!(bundler.offer(PacketState::Play, packet(0x00)).unwrap(), Some(BundleEvent::Packet(_))));
+    let mut bundler = Bundler::new(&V1_20_4);
+    assert!(matches!(bundler.offer(PacketState::Configuration, packet(0x00)).unwrap(), Some(BundleEvent::Packet(_))));
+    assert!(!bundler.is_open());
+
+    let (_, server) = connect(|mut connection| {
+        let spawn = spawn();
+        connection.set_protocol_version(None);
+        let Err(Error::Other(reason)) = connection.write_bundle(&[&spawn]) else {
+            panic!("Expected the bundle to fail")
+        };
+        assert!(reason.contains("The protocol version of the connection isn't known yet"));
+
+        connection.set_protocol_version(Some(V1_19_3.protocol));
+        let Err(Error::Other(reason)) = connection.write_bundle(&[&spawn]) else {
+            panic!("Expected the bundle to fail")
+        };
+        assert_eq!(reason, "Unable to write bundle => Protocol 761 has no bundles, they came with 1.19.4");
+    });
+    server.join().unwrap();
+}
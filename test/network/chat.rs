@@ -0,0 +1,103 @@
+use std::time::{Duration, SystemTime};
+
+use rsa::{Pkcs1v15Sign, RsaPrivateKey};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::network::{
+    chat::{LastSeenTracker, MessageSigner, SignedChat, ACKNOWLEDGEMENT_THRESHOLD},
+    encryption::generate_key_pair,
+    Error
+};
+
+fn signer(private_key: &RsaPrivateKey, expires_in: Duration) -> MessageSigner {
+    MessageSigner::new(Uuid::from_u128(1), private_key.clone(), SystemTime::now() + expires_in)
+}
+
+fn payload(signer: &MessageSigner, index: i32, chat: &SignedChat, last_seen: &[Vec<u8>]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(1_i32.to_be_bytes());
+    hasher.update(Uuid::from_u128(1).as_bytes());
+    hasher.update(signer.session_id().as_bytes());
+    hasher.update(index.to_be_bytes());
+    hasher.update(chat.salt.to_be_bytes());
+    hasher.update((chat.timestamp / 1000).to_be_bytes());
+    hasher.update((chat.message.len() as i32).to_be_bytes());
+    hasher.update(chat.message.as_bytes());
+    hasher.update((last_seen.len() as i32).to_be_bytes());
+    for signature in last_seen {
+        hasher.update(signature);
+    }
+    hasher.finalize().to_vec()
+}
+
+#[test]
+fn test_signature_verifies() {
+    let private_key = generate_key_pair(1024).unwrap();
+    let public_key = private_key.to_public_key();
+    let mut signer = signer(&private_key, Duration::from_secs(3600));
+
+    let first = signer.sign("Hello").unwrap();
+    public_key.verify(Pkcs1v15Sign::new::<Sha256>(), &payload(&signer, 0, &first, &[]), &first.signature).unwrap();
+    assert_eq!(first.offset, 0);
+
+    signer.tracker_mut().add_pending(&first.signature, true);
+    let second = signer.sign("World").unwrap();
+    public_key
+        .verify(
+            Pkcs1v15Sign::new::<Sha256>(),
+            &payload(&signer, 1, &second, std::slice::from_ref(&first.signature)),
+            &second.signature
+        )
+        .unwrap();
+    assert_eq!(second.offset, 1);
+    assert_eq!(second.acknowledged, [0, 0, 0b1000]);
+}
+
+#[test]
+fn test_sign_expired() {
+    let mut signer = signer(&generate_key_pair(1024).unwrap(), Duration::ZERO);
+    assert!(signer.is_expired());
+    assert!(matches!(signer.sign("Hello"), Err(Error::ExpiredCertificate)));
+}
+
+#[test]
+fn test_last_seen_update() {
+    let mut tracker = LastSeenTracker::new();
+    assert!(tracker.add_pending(&[1], true));
+    assert!(!tracker.add_pending(&[1], true));
+    assert!(tracker.add_pending(&[2], false));
+    tracker.ignore_pending();
+    assert!(tracker.add_pending(&[3], true));
+    assert_eq!(tracker.offset(), 4);
+
+    let update = tracker.update();
+    assert_eq!(update.offset, 4);
+    assert_eq!(update.signatures, vec![vec![1], vec![3]]);
+    assert_eq!(update.acknowledged, [0, 0, 0b1001]);
+
+    let update = tracker.update();
+    assert_eq!(update.offset, 0);
+    assert_eq!(update.signatures, vec![vec![1], vec![3]]);
+}
+
+#[test]
+fn test_last_seen_window() {
+    let mut tracker = LastSeenTracker::new();
+    for signature in 0..=20 {
+        tracker.add_pending(&[signature], true);
+    }
+
+    let update = tracker.update();
+    assert_eq!(update.signatures.len(), 20);
+    assert_eq!(update.signatures[0], vec![1]);
+    assert_eq!(update.signatures[19], vec![20]);
+    assert_eq!(update.acknowledged, [0xFF, 0xFF, 0x0F]);
+
+    for signature in 0..=ACKNOWLEDGEMENT_THRESHOLD as u8 {
+        tracker.add_pending(&[signature], false);
+    }
+    assert!(tracker.needs_acknowledgement());
+    assert_eq!(tracker.take_offset(), 65);
+    assert!(!tracker.needs_acknowledgement());
+}
@@ -0,0 +1,113 @@
+use crate::{
+    network::{
+        buffer::Buffer,
+        chunk::{
+            export::{read_region, ChunkStore, REGION_MAGIC},
+            BlockStates, ChunkColumn, ChunkSection, PalettedContainer
+        },
+        nbt::Compound,
+        registry_codec::{DimensionType, RegistryCodec},
+        types::Identifier,
+        versions::{VersionInfo, V1_19_4, V1_20_6},
+        ByteOrder, Error
+    },
+    test::mock::feed
+};
+
+const STONE: i32 = 1;
+const GLASS: i32 = 187;
+
+fn overworld() -> DimensionType {
+    RegistryCodec::vanilla().dimension_type(&Identifier::parse("minecraft:overworld").unwrap()).unwrap()
+}
+
+fn chunk_packet(version: &VersionInfo, id: i32, x: i32, z: i32) -> Buffer {
+    let mut column = ChunkColumn::new(x, z, &overworld(), Compound::new());
+    for section in &mut column.sections[..4] {
+        *section = ChunkSection::new(PalettedContainer::single(STONE), PalettedContainer::single(0));
+    }
+    let mut states = vec![0; 4096];
+    states[x.rem_euclid(16) as usize + z.rem_euclid(16) as usize * 16] = GLASS;
+    column.sections[4] = ChunkSection::new(PalettedContainer::<BlockStates>::from_values(&states).unwrap(), PalettedContainer::single(0));
+
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(id).unwrap();
+    let mut buffer = column.write_versioned(buffer, version.protocol).unwrap();
+    buffer.reset();
+    buffer
+}
+
+fn unload_packet(id: i32, first: i32, second: i32) -> Buffer {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(id).unwrap();
+    buffer.write_i32(first).unwrap();
+    buffer.write_i32(second).unwrap();
+    buffer.reset();
+    buffer
+}
+
+#[test]
+fn test_export_round_trip() {
+    let mut store = ChunkStore::new(&V1_20_6, overworld());
+    for x in -1..=1 {
+        for z in -1..=1 {
+            assert!(feed(chunk_packet(&V1_20_6, 0x27, x, z), |packet| store.handle(packet)));
+        }
+    }
+    // Unload Chunk sends z before x since 1.20.2
+    assert!(feed(unload_packet(0x21, 1, 0), |packet| store.handle(packet)));
+    assert_eq!(store.len(), 8);
+    assert!(!store.contains(0, 1) && store.contains(1, 0));
+
+    assert_eq!(store.block(-16, -64, -16), Some(STONE));
+    assert_eq!(store.block(-1, 0, -1), Some(GLASS));
+    assert_eq!(store.block(17, 0, 0), Some(GLASS));
+    assert_eq!(store.block(17, 1, 0), Some(0));
+    assert_eq!(store.block(0, 0, 20), None);
+    assert_eq!(store.block(40, 0, 0), None);
+
+    let bytes = store.export((1, 1), (0, -1)).unwrap();
+    assert_eq!(&bytes[..5], b"MRGN\x01");
+    let mut imported = ChunkStore::new(&V1_20_6, overworld());
+    assert_eq!(imported.import(&bytes).unwrap(), 5);
+    for x in -16..32 {
+        for z in -16..32 {
+            for y in [-64, -1, 0, 1, 319] {
+                let expected = if x < 0 || (0..16).contains(&x) && z >= 16 { None } else { store.block(x, y, z) };
+                assert_eq!(imported.block(x, y, z), expected, "block at {} {} {}", x, y, z);
+            }
+        }
+    }
+    assert_eq!(imported.get(1, 1).unwrap().sections, store.get(1, 1).unwrap().sections);
+    assert!(imported.get(1, 1).unwrap().heightmaps.is_empty());
+}
+
+#[test]
+fn test_store_capacity() {
+    let mut store = ChunkStore::new(&V1_19_4, overworld()).with_capacity(2);
+    assert!(feed(chunk_packet(&V1_19_4, 0x24, 0, 0), |packet| store.handle(packet)));
+    assert!(feed(chunk_packet(&V1_19_4, 0x24, 1, 0), |packet| store.handle(packet)));
+    store.get_mut(0, 0).unwrap();
+    assert!(feed(chunk_packet(&V1_19_4, 0x24, 2, 0), |packet| store.handle(packet)));
+
+    // 1.19.4 still sends x before z when unloading
+    assert!(store.contains(0, 0) && !store.contains(1, 0) && store.contains(2, 0));
+    assert!(feed(unload_packet(0x1E, 2, 0), |packet| store.handle(packet)));
+    assert_eq!(store.len(), 1);
+
+    let mut other = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    other.write_var_i32(0x25).unwrap();
+    other.reset();
+    assert!(!store.handle(&mut other).unwrap());
+}
+
+#[test]
+fn test_read_region_errors() {
+    assert!(matches!(read_region(b"ANVL\x01\x00"), Err(Error::Decode { offset: 0, .. })));
+    assert!(matches!(read_region(b"MRGN\x02\x00"), Err(Error::Decode { offset: 4, .. })));
+    assert!(read_region(b"MRGN\x01\x00").unwrap().is_empty());
+
+    let mut bytes = REGION_MAGIC.to_vec();
+    bytes.extend_from_slice(&[0x01, 0x00, 0xFF]);
+    assert!(matches!(read_region(&bytes), Err(Error::Decode { offset: 6, .. })));
+}
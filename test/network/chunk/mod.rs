@@ -0,0 +1,284 @@
+pub mod export;
+pub mod update;
+
+use crate::{
+    network::{
+        buffer::Buffer,
+        chunk::{Biomes, BlockStates, ChunkColumn, ChunkSection, Heightmaps, LightData, Palette, PalettedContainer, LIGHT_ARRAY_LENGTH},
+        connection::{Readable, VarI32, Writable},
+        nbt::{Compound, Tag},
+        registry_codec::{DimensionType, RegistryCodec},
+        types::Identifier,
+        versions::{V1_17_1, V1_18_2, V1_19_4, V1_20_6},
+        ByteOrder, Error
+    },
+    test::mock::round_trip
+};
+
+#[test]
+fn test_single_value_round_trip() {
+    let container = PalettedContainer::<BlockStates>::from_values(&[9; 4096]).unwrap();
+    assert_eq!(container.bits_per_entry(), 0);
+    assert_eq!(container.palette(), &Palette::SingleValue(9));
+
+    let (bytes, read) = round_trip(&container);
+    assert_eq!(bytes, vec![0x00, 0x09, 0x00]);
+    assert_eq!(read, container);
+    assert_eq!(read.get(4095), Some(9));
+    assert_eq!(read.get(4096), None);
+}
+
+#[test]
+fn test_indirect_round_trip() {
+    let values = (0..4096).map(|index| [0, 1, 33, 2][index % 4]).collect::<Vec<_>>();
+    let container = PalettedContainer::<BlockStates>::from_values(&values).unwrap();
+    assert_eq!(container.bits_per_entry(), 4);
+    assert_eq!(container.palette(), &Palette::Indirect(vec![0, 1, 33, 2]));
+    assert_eq!(container.data().len(), 256);
+    assert_eq!(container.data()[0], 0x3210_3210_3210_3210);
+
+    let (bytes, read) = round_trip(&container);
+    assert_eq!(&bytes[..7], &[0x04, 0x04, 0x00, 0x01, 0x21, 0x02, 0x80]);
+    assert_eq!(read, container);
+    assert_eq!(read.values(), values);
+
+    let biomes = (0..64).map(|index| index % 5).collect::<Vec<_>>();
+    let container = PalettedContainer::<Biomes>::from_values(&biomes).unwrap();
+    assert_eq!(container.bits_per_entry(), 3);
+    assert_eq!(container.data().len(), 4);
+    assert_eq!(round_trip(&container).1.values(), biomes);
+}
+
+#[test]
+fn test_direct_round_trip() {
+    let values = (0..4096).map(|index| index * 7).collect::<Vec<_>>();
+    let container = PalettedContainer::<BlockStates>::from_values(&values).unwrap();
+    assert_eq!(container.bits_per_entry(), 15);
+    assert_eq!(container.palette(), &Palette::Direct);
+    assert_eq!(container.data().len(), 1024);
+
+    let (bytes, read) = round_trip(&container);
+    assert_eq!(&bytes[..3], &[0x0F, 0x80, 0x08]);
+    assert_eq!(read, container);
+    assert_eq!(read.get(100), Some(700));
+    assert_eq!(read.values(), values);
+}
+
+#[test]
+fn test_indirect_widened_to_minimum() {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_u8(1).unwrap();
+    buffer = vec![VarI32::new(5), VarI32::new(6)].write(buffer).unwrap();
+    buffer = vec![0x1u64; 256].write(buffer).unwrap();
+    buffer.reset();
+
+    let container = PalettedContainer::<BlockStates>::decode(&mut buffer).unwrap();
+    assert_eq!(container.bits_per_entry(), 4);
+    assert_eq!(container.get(0), Some(6));
+    assert_eq!(container.get(1), Some(5));
+}
+
+#[test]
+fn test_wrong_data_length() {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_u8(15).unwrap();
+    buffer = vec![0u64; 3].write(buffer).unwrap();
+    buffer.reset();
+
+    match PalettedContainer::<BlockStates>::read(&mut buffer) {
+        Err(Error::Decode { offset, .. }) => assert_eq!(offset, 1),
+        result => panic!("Unexpected result {:?}", result)
+    }
+}
+
+const BEDROCK: i32 = 79;
+const STONE: i32 = 1;
+const GRASS_BLOCK: i32 = 9;
+const CHEST: i32 = 2954;
+
+fn overworld() -> DimensionType {
+    RegistryCodec::vanilla().dimension_type(&Identifier::parse("minecraft:overworld").unwrap()).unwrap()
+}
+
+fn flat_chunk_packet() -> Buffer {
+    let mut states = vec![STONE; 4096];
+    states[..256].fill(BEDROCK);
+    states[15 * 256..].fill(GRASS_BLOCK);
+    states[15 * 256 + 3 * 16 + 5] = CHEST;
+
+    let mut data = ChunkSection::new(PalettedContainer::from_values(&states).unwrap(), PalettedContainer::single(40))
+        .write(Buffer::empty(true, Some(ByteOrder::BigEndian)))
+        .unwrap();
+    for _ in 1..24 {
+        data = ChunkSection::empty().write(data).unwrap();
+    }
+
+    let mut packet = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    packet.write_i32(-3).unwrap();
+    packet.write_i32(7).unwrap();
+    let heightmaps = Compound::new().with("MOTION_BLOCKING", Tag::LongArray(vec![(0..7).fold(0, |long, index| long | (16 << (index * 9))); 37]));
+    let mut packet = heightmaps.write(packet).unwrap();
+    packet.write_byte_array(&data.to_bytes()).unwrap();
+
+    packet.write_var_i32(1).unwrap();
+    packet.write_u8(0x53).unwrap();
+    packet.write_i16(-49).unwrap();
+    packet.write_var_i32(1).unwrap();
+    let mut packet = Compound::new().with("Items", Tag::List(Vec::new())).write(packet).unwrap();
+
+    for mask in [vec![0b110u64], vec![0b1u64], vec![0b1u64], vec![0u64]] {
+        packet = mask.write(packet).unwrap();
+    }
+    for arrays in [2, 1] {
+        packet.write_var_i32(arrays).unwrap();
+        for _ in 0..arrays {
+            packet.write_byte_array(&[0xFF; 2048]).unwrap();
+        }
+    }
+    packet.reset();
+    packet
+}
+
+#[test]
+fn test_decode_chunk_packet() {
+    let mut packet = flat_chunk_packet();
+    let column = ChunkColumn::read_versioned(&mut packet, &overworld(), V1_20_6.protocol).unwrap();
+    assert_eq!(packet.remaining(), 0);
+    assert_eq!((column.x, column.z, column.sections.len()), (-3, 7, 24));
+    assert_eq!(column.heightmaps.get("MOTION_BLOCKING").unwrap().id(), 12);
+
+    assert_eq!(column.get(0, -64, 0), Some(BEDROCK));
+    assert_eq!(column.get(15, -64, 15), Some(BEDROCK));
+    assert_eq!(column.get(8, -60, 2), Some(STONE));
+    assert_eq!(column.get(4, -49, 4), Some(GRASS_BLOCK));
+    assert_eq!(column.get(5, -49, 3), Some(CHEST));
+    assert_eq!(column.get(5, -48, 3), Some(0));
+    assert_eq!(column.get(0, 319, 0), Some(0));
+    assert_eq!(column.get(0, 320, 0), None);
+    assert_eq!(column.get(0, -65, 0), None);
+    assert_eq!(column.sections[0].block_count, 4096);
+    assert_eq!(column.sections[0].biomes.get(0), Some(40));
+
+    let written = column.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), V1_20_6.protocol).unwrap();
+    assert_eq!(written.to_bytes(), flat_chunk_packet().to_bytes());
+
+    // Chunks before 1.18 have another layout
+    assert!(ChunkColumn::read_versioned(&mut flat_chunk_packet(), &overworld(), V1_17_1.protocol).is_err());
+    assert!(column.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), V1_17_1.protocol).is_err());
+}
+
+#[test]
+fn test_single_palette_section_round_trip() {
+    let section = ChunkSection::new(PalettedContainer::single(STONE), PalettedContainer::single(1));
+    assert_eq!(section.block_count, 4096);
+
+    let (bytes, read) = round_trip(&section);
+    assert_eq!(bytes, vec![0x10, 0x00, 0x00, 0x01, 0x00, 0x00, 0x01, 0x00]);
+    assert_eq!(read, section);
+    assert_eq!(read.get(15, 15, 15), Some(STONE));
+    assert_eq!(read.get(16, 0, 0), None);
+}
+
+#[test]
+fn test_new_column_round_trip() {
+    let mut dimension = overworld();
+    dimension.min_y = 0;
+    dimension.height = 32;
+
+    let mut column = ChunkColumn::new(1, 2, &dimension, Compound::new());
+    column.sections[1] = ChunkSection::new(PalettedContainer::single(STONE), PalettedContainer::single(0));
+    for version in [V1_18_2, V1_20_6] {
+        let mut buffer = column.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), version.protocol).unwrap();
+        buffer.reset();
+        let read = ChunkColumn::read_versioned(&mut buffer, &dimension, version.protocol).unwrap();
+        assert_eq!((read.get(3, 15, 3), read.get(3, 16, 3)), (Some(0), Some(STONE)));
+        assert_eq!(read.sections, column.sections);
+    }
+}
+
+#[test]
+fn test_heightmaps() {
+    let dimension = overworld();
+    let heights = (0..256).map(|index| index - 64).collect::<Vec<_>>();
+    let mut heightmaps = Heightmaps::new(&dimension);
+    heightmaps.insert("MOTION_BLOCKING", &heights).unwrap();
+    assert!(heightmaps.insert("WORLD_SURFACE", &[321; 256]).is_err());
+    assert!(heightmaps.insert("WORLD_SURFACE", &[-65; 256]).is_err());
+    assert!(heightmaps.insert("WORLD_SURFACE", &[0; 255]).is_err());
+
+    // 384 blocks need 9 bits, so 7 heights share a long and the last long only holds 4
+    let compound = heightmaps.to_nbt();
+    let data = match compound.get("MOTION_BLOCKING") {
+        Some(Tag::LongArray(data)) => data,
+        tag => panic!("Unexpected tag {:?}", tag)
+    };
+    assert_eq!(data.len(), 37);
+    assert_eq!(data[0], (0..7).fold(0, |long, index| long | (index << (index * 9))));
+
+    let read = Heightmaps::from_nbt(&compound, &dimension).unwrap();
+    assert_eq!(read, heightmaps);
+    assert_eq!(read.motion_blocking(0, 0), Some(-64));
+    assert_eq!(read.motion_blocking(15, 15), Some(191));
+    assert_eq!(read.motion_blocking(3, 1), Some(-45));
+    assert_eq!(read.motion_blocking(16, 0), None);
+    assert_eq!(read.world_surface(0, 0), None);
+
+    let truncated = Compound::new().with("MOTION_BLOCKING", Tag::LongArray(vec![0; 36]));
+    assert!(Heightmaps::from_nbt(&truncated, &dimension).is_err());
+    let wrong_type = Compound::new().with("MOTION_BLOCKING", Tag::IntArray(vec![0; 37]));
+    assert!(Heightmaps::from_nbt(&wrong_type, &dimension).is_err());
+}
+
+#[test]
+fn test_chunk_packet_light() {
+    let mut packet = flat_chunk_packet();
+    let column = ChunkColumn::read_versioned(&mut packet, &overworld(), V1_20_6.protocol).unwrap();
+    let heightmaps = Heightmaps::from_nbt(&column.heightmaps, &overworld()).unwrap();
+    assert_eq!(heightmaps.motion_blocking(0, 0), Some(-48));
+
+    let light = column.light(V1_20_6.protocol).unwrap();
+    assert_eq!(light.sky.sections.keys().copied().collect::<Vec<_>>(), vec![1, 2]);
+    assert_eq!(light.sky_light(2, 15, 15, 15), Some(15));
+    assert_eq!(light.sky_light(0, 0, 0, 0), Some(0));
+    assert_eq!(light.sky_light(3, 0, 0, 0), None);
+    assert_eq!(light.block_light(0, 7, 0, 0), Some(15));
+}
+
+#[test]
+fn test_light_data_edge_cases() {
+    let mut light = LightData::default();
+    let mut nibbles = vec![0; LIGHT_ARRAY_LENGTH];
+    nibbles[0] = 0x5A;
+    light.sky.insert(1, nibbles).unwrap();
+    light.sky.empty.insert(2);
+    light.block.empty.insert(70);
+    assert!(light.block.insert(0, vec![0; LIGHT_ARRAY_LENGTH - 1]).is_err());
+
+    let (_, read) = round_trip(&light);
+    assert_eq!(read, light);
+    assert_eq!((read.sky_light(1, 0, 0, 0), read.sky_light(1, 1, 0, 0)), (Some(0xA), Some(0x5)));
+    assert_eq!(read.sky_light(2, 4, 4, 4), Some(0));
+    assert_eq!(read.block_light(70, 0, 0, 0), Some(0));
+    assert_eq!(read.sky_light(1, 16, 0, 0), None);
+
+    let empty = LightData::default();
+    let mut buffer = empty.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), V1_19_4.protocol).unwrap();
+    assert_eq!(buffer.to_bytes(), vec![0; 7]);
+    buffer.reset();
+    assert_eq!(LightData::read_versioned(&mut buffer, V1_19_4.protocol).unwrap(), empty);
+
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer = vec![0b1u64].write(buffer).unwrap();
+    (0..3).for_each(|_| buffer.write_var_i32(0).unwrap());
+    buffer.write_var_i32(1).unwrap();
+    buffer.write_byte_array(&[0; 2047]).unwrap();
+    buffer.reset();
+    assert!(matches!(LightData::read(&mut buffer), Err(Error::Decode { offset: 13, .. })));
+
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer = vec![0b11u64].write(buffer).unwrap();
+    (0..3).for_each(|_| buffer.write_var_i32(0).unwrap());
+    buffer.write_var_i32(1).unwrap();
+    buffer.reset();
+    assert!(matches!(LightData::read(&mut buffer), Err(Error::Decode { offset: 12, .. })));
+}
@@ -0,0 +1,64 @@
+use crate::network::{
+    buffer::Buffer,
+    chunk::update::{pack_block_position, pack_section_position, unpack_block_position, unpack_section_position, BlockChange, SectionBlocksUpdate},
+    connection::{Readable, Writable},
+    versions::{V1_19_4, V1_20_6},
+    ByteOrder
+};
+
+const GLASS: i32 = 187;
+
+#[test]
+fn test_position_packing() {
+    assert_eq!(pack_section_position(-1, 4, 2), 0xFFFF_FC00_0020_0004_u64 as i64);
+    assert_eq!(unpack_section_position(0xFFFF_FC00_0020_0004_u64 as i64), (-1, 4, 2));
+    assert_eq!(unpack_section_position(pack_section_position(2_097_151, -524_288, -2_097_152)), (2_097_151, -524_288, -2_097_152));
+
+    assert_eq!(pack_block_position(-13, 79, 37), 0xFFFF_FCC0_0002_504F_u64 as i64);
+    assert_eq!(unpack_block_position(0xFFFF_FCC0_0002_504F_u64 as i64), (-13, 79, 37));
+    assert_eq!(unpack_block_position(pack_block_position(-1, -64, -1)), (-1, -64, -1));
+
+    let mut buffer = BlockChange {
+        x: -13,
+        y: -64,
+        z: 37,
+        state: GLASS
+    }
+    .write(Buffer::empty(true, Some(ByteOrder::BigEndian)))
+    .unwrap();
+    buffer.reset();
+    assert_eq!(
+        BlockChange::read(&mut buffer).unwrap(),
+        BlockChange {
+            x: -13,
+            y: -64,
+            z: 37,
+            state: GLASS
+        }
+    );
+}
+
+#[test]
+fn test_section_blocks_update_decoding() {
+    // The state sits above x, z and y with 4 bits each
+    let mut bytes = 0xFFFF_FC00_0020_0004_u64.to_be_bytes().to_vec();
+    bytes.extend([0x01, 0xDF, 0xE6, 0x2E]);
+    let update = SectionBlocksUpdate::read_versioned(&mut Buffer::new(bytes.clone(), false, Some(ByteOrder::BigEndian)), V1_20_6.protocol).unwrap();
+    assert_eq!(update.blocks, vec![0xBB35F]);
+    assert_eq!(update.changes().collect::<Vec<_>>(), vec![BlockChange { x: -13, y: 79, z: 37, state: GLASS }]);
+
+    let mut expected = SectionBlocksUpdate::new(-1, 4, 2);
+    expected.push(3, 15, 5, GLASS).unwrap();
+    assert!(expected.push(16, 0, 0, GLASS).is_err());
+    assert_eq!(expected, update);
+    assert_eq!(
+        expected.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), V1_20_6.protocol).unwrap().to_bytes(),
+        bytes
+    );
+
+    // Before 1.20 a light flag follows the section position
+    let mut buffer = expected.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), V1_19_4.protocol).unwrap();
+    assert_eq!(buffer.len(), bytes.len() + 1);
+    buffer.reset();
+    assert_eq!(SectionBlocksUpdate::read_versioned(&mut buffer, V1_19_4.protocol).unwrap(), expected);
+}
@@ -0,0 +1,206 @@
+use crate::network::{
+    buffer::Buffer,
+    commands::{CommandTree, NodeKind, Parser, StringMode},
+    connection::Readable,
+    versions::{V1_16_5, V1_20_4},
+    ByteOrder, Error
+};
+
+enum Node<'a> {
+    Root(&'a [i32]),
+    Literal(&'a str, bool, &'a [i32], Option<i32>),
+    Argument(&'a str, i32, &'a [u8], &'a [i32])
+}
+
+fn write_tree(nodes: &[Node], root: i32) -> Buffer {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(nodes.len() as i32).unwrap();
+    for node in nodes {
+        let (flags, children, redirect) = match node {
+            Node::Root(children) => (0, children, None),
+            Node::Literal(_, executable, children, redirect) => (1 | if *executable { 0x04 } else { 0 } | if redirect.is_some() { 0x08 } else { 0 }, children, *redirect),
+            Node::Argument(_, _, _, children) => (2 | 0x04, children, None)
+        };
+        buffer.write_u8(flags).unwrap();
+        buffer.write_var_i32(children.len() as i32).unwrap();
+        children.iter().for_each(|child| buffer.write_var_i32(*child).unwrap());
+        if let Some(redirect) = redirect {
+            buffer.write_var_i32(redirect).unwrap();
+        }
+        match node {
+            Node::Root(_) => {}
+            Node::Literal(name, ..) => buffer.write_str(name).unwrap(),
+            Node::Argument(name, parser, properties, _) => {
+                buffer.write_str(name).unwrap();
+                buffer.write_var_i32(*parser).unwrap();
+                buffer.write_bytes(properties).unwrap();
+            }
+        }
+    }
+    buffer.write_var_i32(root).unwrap();
+    buffer.reset();
+    buffer
+}
+
+// Hand-written after a few vanilla commands, not taken from a server
+fn sample_tree() -> Buffer {
+    write_tree(
+        &[
+            Node::Root(&[1, 6, 9, 14, 16, 19, 22]),
+            Node::Literal("gamemode", false, &[2, 3, 4, 5], None),
+            Node::Literal("survival", true, &[], None),
+            Node::Literal("creative", true, &[], None),
+            Node::Literal("adventure", true, &[], None),
+            Node::Literal("spectator", true, &[], None),
+            Node::Literal("gamerule", false, &[7], None),
+            Node::Literal("doDaylightCycle", true, &[8], None),
+            Node::Argument("value", 0, &[], &[]),
+            Node::Literal("execute", false, &[10, 12], None),
+            Node::Literal("as", false, &[11], None),
+            Node::Argument("targets", 6, &[0x00], &[]),
+            Node::Literal("run", false, &[], Some(0)),
+            Node::Literal("unused", false, &[], None),
+            Node::Literal("give", false, &[15], None),
+            Node::Argument("count", 3, &[0x03, 0, 0, 0, 1, 0, 0, 0, 99], &[]),
+            Node::Literal("say", false, &[17], None),
+            Node::Argument("message", 19, &[], &[]),
+            Node::Argument("tick", 41, &[0, 0, 0, 1], &[]),
+            Node::Literal("time", false, &[18, 20], None),
+            Node::Literal("set", false, &[21], None),
+            Node::Argument("structure", 43, b"\x1cminecraft:worldgen/structure", &[]),
+            Node::Literal("teammsg", false, &[23], None),
+            Node::Argument("message", 5, &[0x02], &[])
+        ],
+        0
+    )
+}
+
+#[test]
+fn test_parse_sample_tree() {
+    let mut buffer = sample_tree();
+    let tree = CommandTree::read_versioned(&mut buffer, V1_20_4.protocol).unwrap();
+    assert_eq!(buffer.remaining(), 0);
+    assert_eq!(tree.nodes().len(), 24);
+    assert_eq!(tree.root().kind, NodeKind::Root);
+    assert_eq!(
+        tree.children(tree.root()).filter_map(|node| node.name()).collect::<Vec<_>>(),
+        vec!["gamemode", "gamerule", "execute", "give", "say", "time", "teammsg"]
+    );
+
+    let parsers = tree
+        .nodes()
+        .iter()
+        .filter_map(|node| {
+            match &node.kind {
+                NodeKind::Argument { name, parser, .. } => Some((name.as_str(), parser.clone())),
+                _ => None
+            }
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        parsers,
+        vec![
+            ("value", Parser::Bool),
+            ("targets", Parser::Entity { single: false, players_only: false }),
+            ("count", Parser::Integer { min: Some(1), max: Some(99) }),
+            ("message", Parser::Message),
+            ("tick", Parser::Time { min: 1 }),
+            (
+                "structure",
+                Parser::Resource {
+                    parser: "minecraft:resource_or_tag_key".to_string(),
+                    registry: "minecraft:worldgen/structure".to_string()
+                }
+            ),
+            ("message", Parser::String(StringMode::GreedyPhrase))
+        ]
+    );
+
+    let run = &tree.nodes()[12];
+    assert_eq!(tree.redirect(run), Some(tree.root()));
+    assert!(tree.nodes()[2].executable);
+}
+
+#[test]
+fn test_suggest() {
+    let tree = CommandTree::read_versioned(&mut sample_tree(), V1_20_4.protocol).unwrap();
+    assert_eq!(tree.suggest("game"), vec!["gamemode", "gamerule"]);
+    assert_eq!(tree.suggest("gamemode "), vec!["adventure", "creative", "spectator", "survival"]);
+    assert_eq!(tree.suggest("gamemode s"), vec!["spectator", "survival"]);
+    assert_eq!(tree.suggest("gamerule doDaylightCycle "), Vec::<String>::new());
+    assert!(tree.suggest("unknown ").is_empty());
+    assert_eq!(tree.suggest(""), vec!["execute", "gamemode", "gamerule", "give", "say", "teammsg", "time"]);
+
+    assert_eq!(tree.suggest("execute "), vec!["as", "run"]);
+    assert_eq!(tree.suggest("execute run g"), vec!["gamemode", "gamerule", "give"]);
+    assert_eq!(tree.suggest("execute run execute run time "), vec!["set"]);
+    assert!(tree.suggest(&"execute run ".repeat(100)).is_empty());
+}
+
+#[test]
+fn test_cyclic_redirects() {
+    let tree = CommandTree::read_versioned(
+        &mut write_tree(&[Node::Root(&[1]), Node::Literal("a", false, &[], Some(2)), Node::Literal("b", false, &[], Some(1))], 0),
+        V1_20_4.protocol
+    )
+    .unwrap();
+    assert!(tree.suggest("a ").is_empty());
+    assert_eq!(tree.suggest("a"), vec!["a"]);
+}
+
+#[test]
+fn test_invalid_trees() {
+    let mut buffer = write_tree(&[Node::Root(&[3])], 0);
+    assert!(matches!(CommandTree::read(&mut buffer), Err(Error::Decode { offset: 3, .. })));
+
+    let mut buffer = write_tree(&[Node::Root(&[])], 1);
+    assert!(matches!(CommandTree::read(&mut buffer), Err(Error::Decode { offset: 3, .. })));
+
+    let mut buffer = write_tree(&[Node::Root(&[1]), Node::Argument("custom", 200, &[], &[])], 0);
+    let tree = CommandTree::read(&mut buffer).unwrap();
+    assert!(matches!(&tree.nodes()[1].kind, NodeKind::Argument { parser: Parser::Unknown(200), .. }));
+}
+
+#[test]
+fn test_legacy_parser_identifiers() {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(2).unwrap();
+    buffer.write_u8(0).unwrap();
+    buffer.write_var_i32(1).unwrap();
+    buffer.write_var_i32(1).unwrap();
+    buffer.write_u8(2 | 0x04 | 0x10).unwrap();
+    buffer.write_var_i32(0).unwrap();
+    buffer.write_str("amount").unwrap();
+    buffer.write_str("brigadier:double").unwrap();
+    buffer.write_u8(0x01).unwrap();
+    buffer.write_u64(f64::to_bits(0.5)).unwrap();
+    buffer.write_str("minecraft:ask_server").unwrap();
+    buffer.write_var_i32(0).unwrap();
+    buffer.reset();
+
+    let tree = CommandTree::read_versioned(&mut buffer, V1_16_5.protocol).unwrap();
+    assert_eq!(
+        tree.nodes()[1].kind,
+        NodeKind::Argument {
+            name: "amount".to_string(),
+            parser: Parser::Double { min: Some(0.5), max: None },
+            suggestions: Some("minecraft:ask_server".to_string())
+        }
+    );
+}
+
+#[test]
+fn test_signable_arguments() {
+    let tree = CommandTree::read_versioned(&mut sample_tree(), V1_20_4.protocol).unwrap();
+    let message = |text: &str| vec![("message".to_string(), text.to_string())];
+    assert_eq!(tree.signable_arguments("say Hello there"), message("Hello there"));
+    assert_eq!(tree.signable_arguments("execute run execute run say hi"), message("hi"));
+
+    assert!(tree.signable_arguments("teammsg hi there").is_empty());
+    assert!(tree.signable_arguments("gamemode creative").is_empty());
+    assert!(tree.signable_arguments("say").is_empty());
+    assert!(tree.signable_arguments("sayhi").is_empty());
+    assert!(tree.signable_arguments("unknown say hi").is_empty());
+    assert!(tree.signable_arguments("execute as @a run say hi").is_empty());
+    assert!(tree.signable_arguments(&"execute run ".repeat(100)).is_empty());
+}
@@ -0,0 +1,62 @@
+use crate::{
+    network::{
+        connection::{codec::ProtocolCodec, pipeline::framing::FrameEncoder, Client, Pipeline, Server},
+        Error
+    },
+    test::mock::packet
+};
+
+fn codecs() -> (ProtocolCodec<'static, Client>, ProtocolCodec<'static, Server>) {
+    (
+        ProtocolCodec::new(Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing"))),
+        ProtocolCodec::new(Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing")))
+    )
+}
+
+#[test]
+fn test_codec_in_memory() {
+    let (mut client, mut server) = codecs();
+    client.enable_compression(64);
+    server.enable_compression(64);
+
+    assert_eq!(client.encode(packet(&[1, 2, 3])).unwrap(), 5);
+    client.encode(packet(&[7; 300])).unwrap();
+    let bytes = client.take_output();
+    assert!(!client.has_output());
+
+    let mut packets = Vec::new();
+    for byte in bytes {
+        server.feed(&[byte]);
+        if let Some(packet) = server.decode().unwrap() {
+            packets.push(packet.to_bytes());
+        }
+    }
+    assert_eq!(packets, vec![vec![1, 2, 3], vec![7; 300]]);
+    assert_eq!(server.buffered(), 0);
+    assert!(server.decode().unwrap().is_none());
+}
+
+#[test]
+fn test_codec_encryption_after_buffered_frame() {
+    let (mut client, mut server) = codecs();
+    client.encode(packet(&[1])).unwrap();
+    client.enable_encryption(&[9; 16]);
+    client.encode(packet(&[2, 2])).unwrap();
+    client.encode(packet(&[3, 3, 3])).unwrap();
+
+    server.feed(&client.take_output());
+    assert_eq!(server.decode().unwrap().unwrap().to_bytes(), vec![1]);
+    server.enable_encryption(&[9; 16]);
+    assert_eq!(server.decode().unwrap().unwrap().to_bytes(), vec![2, 2]);
+    assert_eq!(server.take_input(), vec![3, 3, 3, 3]);
+    assert_eq!(server.buffered(), 0);
+}
+
+#[test]
+fn test_codec_frame_length_too_long() {
+    let (_, mut server) = codecs();
+    server.feed(&[0xFF, 0xFF]);
+    assert!(server.decode().unwrap().is_none());
+    server.feed(&[0xFF, 0x01]);
+    assert!(matches!(server.decode(), Err(Error::Decode { offset: 2, .. })));
+}
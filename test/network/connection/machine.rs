@@ -0,0 +1,86 @@
+use crate::{
+    network::{
+        connection::{
+            machine::{DecodedPacket, ProtocolMachine},
+            pipeline::framing::FrameEncoder,
+            Client, Pipeline, Role, Server
+        },
+        handshake::{Handshake, NextState},
+        PacketState
+    },
+    test::mock::packet
+};
+
+fn machine<R: Role>() -> ProtocolMachine<'static, R> {
+    ProtocolMachine::new(Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing")))
+}
+
+fn shuttle<A: Role, B: Role>(from: &mut ProtocolMachine<'static, A>, to: &mut ProtocolMachine<'static, B>) -> Vec<DecodedPacket> {
+    let mut packets = Vec::new();
+    for bytes in from.take_outgoing().chunks(3) {
+        to.feed_incoming(bytes);
+        while let Some(packet) = to.poll_packet().unwrap() {
+            packets.push(packet);
+        }
+    }
+    packets
+}
+
+#[test]
+fn test_machine_session() {
+    let (mut client, mut server) = (machine::<Client>(), machine::<Server>());
+
+    let handshake = Handshake {
+        protocol_version: 764,
+        server_address: "localhost".to_string(),
+        server_port: 25565,
+        next_state: NextState::Login
+    };
+    client.queue_outgoing(handshake).unwrap();
+    client.queue_outgoing(packet(&[0x00, 0x01, b'a'])).unwrap();
+    assert_eq!((client.state(), client.protocol()), (PacketState::Login, 764));
+
+    let packets = shuttle(&mut client, &mut server);
+    assert_eq!(
+        packets.iter().map(|packet| (packet.state, packet.id)).collect::<Vec<_>>(),
+        vec![(PacketState::Handshaking, 0x00), (PacketState::Login, 0x00)]
+    );
+    assert_eq!(packets[1].buffer.to_bytes(), vec![0x00, 0x01, b'a']);
+    assert_eq!((server.state(), server.protocol()), (PacketState::Login, 764));
+
+    server.queue_outgoing(packet(&[0x03, 0x10])).unwrap();
+    server.enable_encryption(&[3; 16]);
+    server.queue_outgoing(packet(&[0x02, 0x07])).unwrap();
+    client.feed_incoming(&server.take_outgoing());
+    assert_eq!(client.poll_packet().unwrap().unwrap().id, 0x03);
+    client.enable_encryption(&[3; 16]);
+    assert_eq!(client.poll_packet().unwrap().unwrap().buffer.to_bytes(), vec![0x02, 0x07]);
+    assert!(client.codec().is_encrypted());
+    // 1.20.2 stays in login until the client acknowledged the login success
+    assert_eq!(client.state(), PacketState::Login);
+
+    client.queue_outgoing(packet(&[0x03])).unwrap();
+    client.queue_outgoing(packet(&[0x02])).unwrap();
+    let packets = shuttle(&mut client, &mut server);
+    assert_eq!(
+        packets.iter().map(|packet| packet.state).collect::<Vec<_>>(),
+        vec![PacketState::Login, PacketState::Configuration]
+    );
+    assert_eq!((client.state(), server.state()), (PacketState::Play, PacketState::Play));
+
+    server.queue_outgoing(packet(&[9; 300])).unwrap();
+    let packets = shuttle(&mut server, &mut client);
+    assert_eq!(packets[0].buffer.to_bytes(), vec![9; 300]);
+    assert!(!client.has_outgoing());
+}
+
+#[test]
+fn test_machine_protocol_version() {
+    let mut machine = machine::<Server>();
+    assert_eq!((machine.protocol(), machine.codec().protocol_version()), (0, None));
+
+    machine.set_protocol(765);
+    assert_eq!(machine.codec().protocol_version(), Some(765));
+    machine.codec_mut().set_protocol_version(Some(766));
+    assert_eq!(machine.protocol(), 766);
+}
@@ -0,0 +1,217 @@
+pub mod codec;
+pub mod machine;
+pub mod pipeline;
+pub mod stats;
+
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc
+    }
+};
+
+use crate::network::{
+    buffer::Buffer,
+    connection::{
+        pipeline::framing::FrameEncoder,
+        socket::{ConnectionBuilder, SocketConnection},
+        Client, Connection, Pipeline, PipelineStage, Readable, Server, VarI32, VarI64, Writable
+    },
+    ByteOrder, Error, PacketDirection
+};
+
+#[test]
+fn test_client_role_directions() {
+    assert_eq!(<SocketConnection<Client> as Connection<TcpStream>>::read_direction(), PacketDirection::Clientbound);
+    assert_eq!(<SocketConnection<Client> as Connection<TcpStream>>::write_direction(), PacketDirection::Serverbound);
+}
+
+#[test]
+fn test_server_role_directions() {
+    assert_eq!(<SocketConnection<Server> as Connection<TcpStream>>::read_direction(), PacketDirection::Serverbound);
+    assert_eq!(<SocketConnection<Server> as Connection<TcpStream>>::write_direction(), PacketDirection::Clientbound);
+}
+
+#[test]
+fn test_drop_flushes_and_closes() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut connection = SocketConnection::<Client>::new(
+        TcpStream::connect(listener.local_addr().unwrap()).unwrap(),
+        Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing"))
+    );
+    let mut peer = SocketConnection::<Server>::new(listener.accept().unwrap().0, Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing")));
+
+    for id in 0..3 {
+        let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        buffer.write_var_i32(id).unwrap();
+        connection.write(buffer).unwrap();
+    }
+    drop(connection);
+
+    for id in 0..3 {
+        assert_eq!(peer.read_packet().unwrap().read_var_i32().unwrap(), id);
+    }
+    assert_eq!(peer.socket().read(&mut [0; 1]).unwrap(), 0);
+}
+
+#[test]
+fn test_fixed_array_round_trip() {
+    let mut buffer = [1, -2, i32::MAX].write(Buffer::empty(true, Some(ByteOrder::BigEndian))).unwrap();
+    assert_eq!(buffer.to_bytes().len(), 12);
+
+    buffer.reset();
+    assert_eq!(<[i32; 3]>::read(&mut buffer).unwrap(), [1, -2, i32::MAX]);
+    assert!(<[i32; 3]>::read(&mut Buffer::new(vec![0; 8], false, Some(ByteOrder::BigEndian))).is_err());
+}
+
+#[test]
+fn test_frame_length_too_long() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let mut connection = SocketConnection::<Server>::new(listener.accept().unwrap().0, Pipeline::new());
+
+    stream.write_all(&[0xFF, 0xFF, 0xFF, 0x01]).unwrap();
+    assert!(matches!(connection.read_packet(), Err(Error::Decode { offset: 2, .. })));
+}
+
+#[test]
+fn test_read_exact_buffer_over_chunks() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let mut connection = SocketConnection::<Server>::new(listener.accept().unwrap().0, Pipeline::new());
+
+    let payload: Vec<u8> = (0..=255).cycle().take(3000).collect();
+    let writer = std::thread::spawn(move || {
+        for chunk in payload.chunks(700) {
+            stream.write_all(chunk).unwrap();
+            stream.flush().unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        stream
+    });
+
+    let buffer = connection.read_exact_buffer(2500, ByteOrder::BigEndian).unwrap();
+    assert_eq!(buffer.to_bytes(), (0..=255).cycle().take(2500).collect::<Vec<u8>>());
+    let buffer = connection.read_exact_buffer(500, ByteOrder::BigEndian).unwrap();
+    assert_eq!(buffer.to_bytes(), (0..=255).cycle().skip(2500).take(500).collect::<Vec<u8>>());
+
+    let mut stream = writer.join().unwrap();
+    stream.write_all(&[1, 2]).unwrap();
+    drop(stream);
+    assert!(connection.read_exact_buffer(3, ByteOrder::BigEndian).is_err());
+}
+
+#[test]
+fn test_var_int_wrappers() {
+    let mut buffer = VarI32::new(25565).write(Buffer::empty(true, Some(ByteOrder::BigEndian))).unwrap();
+    buffer.reset();
+    let value = VarI32::read(&mut buffer).unwrap();
+    assert_eq!(value, 25565);
+    assert!(value > 0);
+    assert_eq!(value + 1, 25566);
+    assert_eq!(value - VarI32::from(65), VarI32::new(25500));
+    assert_eq!(i32::from(value), 25565);
+    assert_eq!(*value * 2, 51130);
+    assert_eq!(value.to_string(), "25565");
+
+    let mut buffer = VarI64::new(i64::MIN).write(Buffer::empty(true, Some(ByteOrder::BigEndian))).unwrap();
+    assert_eq!(buffer.len(), 10);
+    buffer.reset();
+    assert_eq!(VarI64::read(&mut buffer).unwrap(), i64::MIN);
+}
+
+#[test]
+fn test_compression_renegotiation() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client = SocketConnection::<Client>::new(
+        TcpStream::connect(listener.local_addr().unwrap()).unwrap(),
+        Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing"))
+    );
+    let mut server = SocketConnection::<Server>::new(listener.accept().unwrap().0, Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing")));
+
+    for threshold in [256, 256, 0, -1, 64] {
+        client.enable_compression(threshold);
+        server.enable_compression(threshold);
+        assert_eq!(client.pipeline().has_encoder("compression"), threshold >= 0);
+        assert_eq!(server.pipeline().has_decoder("decompression"), threshold >= 0);
+
+        for length in [1, 300] {
+            let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+            buffer.write_bytes(&vec![threshold as u8; length]).unwrap();
+            client.write(buffer).unwrap();
+            assert_eq!(server.read_packet().unwrap().to_bytes(), vec![threshold as u8; length]);
+        }
+    }
+
+    client.disable_compression();
+    assert!(!client.pipeline().has_encoder("compression"));
+    assert!(client.pipeline().has_encoder("framing"));
+}
+
+struct CountingStage {
+    invocations: Arc<AtomicUsize>,
+    processed: usize
+}
+
+impl PipelineStage for CountingStage {
+    fn process(&mut self, mut buffer: Buffer) -> Result<Buffer, Error> {
+        self.processed += 1;
+        self.invocations.store(self.processed, Ordering::SeqCst);
+        buffer.write_u8(self.processed as u8)?;
+        Ok(buffer)
+    }
+}
+
+#[test]
+fn test_stateful_pipeline_stage() {
+    let encoded = Arc::new(AtomicUsize::new(0));
+    let decoded = Arc::new(AtomicUsize::new(0));
+    let mut pipeline = Pipeline::new()
+        .add_last_encoder(
+            CountingStage {
+                invocations: encoded.clone(),
+                processed: 0
+            },
+            Some("counter")
+        )
+        .add_last_decoder(
+            CountingStage {
+                invocations: decoded.clone(),
+                processed: 0
+            },
+            Some("counter")
+        );
+
+    for expected in 1..=3u8 {
+        let mut buffer = pipeline.encode(Buffer::empty(true, Some(ByteOrder::BigEndian))).unwrap();
+        buffer.reset();
+        assert_eq!(buffer.read_u8().unwrap(), expected);
+    }
+    pipeline.decode(Buffer::empty(true, Some(ByteOrder::BigEndian))).unwrap();
+
+    assert_eq!(encoded.load(Ordering::SeqCst), 3);
+    assert_eq!(decoded.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_connection_builder_stage_order() {
+    let builder = ConnectionBuilder::new().with_encryption([3; 16]).with_compression(64).with_framing();
+    let pipeline = builder.pipeline();
+    assert_eq!(pipeline.encoder_names(), vec![Some("compression"), Some("framing")]);
+    assert_eq!(pipeline.decoder_names(), vec![Some("decompression")]);
+    assert!(ConnectionBuilder::new().with_compression(-1).with_framing().pipeline().decoder_names().is_empty());
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client = builder.build::<Client>(TcpStream::connect(listener.local_addr().unwrap()).unwrap());
+    let mut server = builder.build::<Server>(listener.accept().unwrap().0);
+    assert!(client.is_encrypted() && server.is_encrypted());
+
+    for length in [1, 300] {
+        let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        buffer.write_bytes(&vec![7; length]).unwrap();
+        client.write(buffer).unwrap();
+        assert_eq!(server.read_packet().unwrap().to_bytes(), vec![7; length]);
+    }
+}
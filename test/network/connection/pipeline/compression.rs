@@ -0,0 +1,69 @@
+use crate::network::{
+    buffer::Buffer,
+    connection::{
+        pipeline::compression::{CompressionDecoder, CompressionEncoder},
+        Writable
+    },
+    ByteOrder
+};
+
+fn packet(length: usize) -> Buffer {
+    Buffer::new(vec![7; length], true, Some(ByteOrder::BigEndian))
+}
+
+#[test]
+fn test_compression_statistics() {
+    let encoder = CompressionEncoder::with_statistics(64);
+    let statistics = encoder.statistics().unwrap();
+
+    let small = encoder.write(packet(10)).unwrap();
+    encoder.write(packet(20)).unwrap();
+    encoder.write(packet(1000)).unwrap();
+
+    assert_eq!(small.len(), 11);
+    assert_eq!(encoder.packets_compressed(), Some(1));
+    assert_eq!(encoder.packets_uncompressed(), Some(2));
+    assert_eq!(statistics.bytes_in(), 1030);
+    assert!(statistics.bytes_out() < statistics.bytes_in());
+    assert!(encoder.compression_ratio().unwrap() < 0.1);
+}
+
+#[test]
+fn test_compression_without_statistics() {
+    let encoder = CompressionEncoder::new(64);
+    encoder.write(packet(1000)).unwrap();
+    assert!(encoder.statistics().is_none());
+    assert_eq!(encoder.compression_ratio(), None);
+    assert_eq!(encoder.packets_compressed(), None);
+}
+
+#[test]
+fn test_compression_round_trip() {
+    let encoder = CompressionEncoder::new(64);
+    let decoder = CompressionDecoder::new(64);
+    for length in [10, 1000] {
+        let mut encoded = encoder.write(packet(length)).unwrap();
+        encoded.reset();
+        assert_eq!(decoder.write(encoded).unwrap().to_bytes(), vec![7; length]);
+    }
+}
+
+// Vanilla only sends uncompressed frames below the threshold, but accepts them at any size
+#[test]
+fn test_uncompressed_frame_above_threshold() {
+    let mut frame = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    frame.write_var_i32(0).unwrap();
+    frame.write_bytes(&[7; 1000]).unwrap();
+    frame.reset();
+    assert_eq!(CompressionDecoder::new(64).write(frame).unwrap().to_bytes(), vec![7; 1000]);
+}
+
+#[test]
+fn test_compression_threshold_zero() {
+    let mut encoded = CompressionEncoder::new(0).write(packet(1)).unwrap();
+    encoded.reset();
+    assert_eq!(encoded.read_var_i32().unwrap(), 1);
+
+    encoded.reset();
+    assert_eq!(CompressionDecoder::new(0).write(encoded).unwrap().to_bytes(), vec![7]);
+}
@@ -0,0 +1,55 @@
+use crate::network::{
+    buffer::Buffer,
+    connection::{
+        pipeline::framing::{FrameDecoder, FrameEncoder},
+        Writable
+    },
+    Error
+};
+
+#[test]
+fn test_frame_decoder_split_length() {
+    let payload = vec![0xAB; 20000];
+    let mut frame = Buffer::empty(true, None);
+    frame.write_var_i32(payload.len() as i32).unwrap();
+    frame.write_bytes(&payload).unwrap();
+    frame.write_var_i32(1).unwrap();
+    frame.write_u8(0x07).unwrap();
+    let bytes = frame.to_bytes();
+    assert_eq!(&bytes[..3], &[0xA0, 0x9C, 0x01]);
+
+    let mut decoder = FrameDecoder::new();
+    for byte in &bytes[..3] {
+        assert!(decoder.next_frame().unwrap().is_none());
+        decoder.extend(std::slice::from_ref(byte)).unwrap();
+    }
+    assert!(decoder.next_frame().unwrap().is_none());
+    assert_eq!(decoder.pending(), 3);
+
+    decoder.extend(&bytes[3..]).unwrap();
+    assert_eq!(decoder.next_frame().unwrap().unwrap().to_bytes(), payload);
+    assert_eq!(decoder.next_frame().unwrap().unwrap().to_bytes(), vec![0x07]);
+    assert!(decoder.next_frame().unwrap().is_none());
+    assert_eq!(decoder.pending(), 0);
+}
+
+#[test]
+fn test_frame_decoder_length_too_long() {
+    let mut decoder = FrameDecoder::new();
+    decoder.extend(&[0x80, 0x80]).unwrap();
+    assert!(decoder.next_frame().unwrap().is_none());
+
+    decoder.extend(&[0x80]).unwrap();
+    assert!(matches!(decoder.next_frame(), Err(Error::Decode { offset: 0, .. })));
+}
+
+#[test]
+fn test_frame_decoder_round_trip() {
+    let mut packet = Buffer::empty(true, None);
+    packet.write_str("hello").unwrap();
+    let frame = FrameEncoder::new().write(packet.clone()).unwrap();
+
+    let mut decoder = FrameDecoder::new();
+    decoder.extend(&frame.to_bytes()).unwrap();
+    assert_eq!(decoder.next_frame().unwrap().unwrap().to_bytes(), packet.to_bytes());
+}
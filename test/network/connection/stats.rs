@@ -0,0 +1,75 @@
+use std::{net::TcpListener, sync::Arc, thread};
+
+use crate::network::{
+    buffer::Buffer,
+    connection::{pipeline::framing::FrameEncoder, socket::SocketConnection, stats::PacketStats, Client, Connection, Pipeline, Server},
+    ByteOrder, PacketDirection, PacketState
+};
+
+fn names(state: PacketState, direction: PacketDirection, id: i32) -> Option<&'static str> {
+    match (state, direction, id) {
+        (PacketState::Play, PacketDirection::Clientbound, 0x25) => Some("ChunkData"),
+        (PacketState::Play, PacketDirection::Clientbound, 0x2B) => Some("UpdateEntityPosition"),
+        _ => None
+    }
+}
+
+#[test]
+fn test_packet_stats_aggregation() {
+    let stats = PacketStats::with_names(names);
+    for size in [8000, 12000, 10000] {
+        stats.record(PacketState::Play, PacketDirection::Clientbound, 0x25, size);
+    }
+
+    for _ in 0..100 {
+        stats.record(PacketState::Play, PacketDirection::Clientbound, 0x2B, 20);
+    }
+    stats.record(PacketState::Play, PacketDirection::Serverbound, 0x2B, 40);
+    stats.record(PacketState::Play, PacketDirection::Serverbound, 0x1000, 40);
+
+    let report = stats.report(2);
+    assert_eq!(report.len(), 2);
+    assert_eq!(report[0].name, Some("ChunkData"));
+    assert_eq!(report[0].count, 3);
+    assert_eq!(report[0].total_bytes, 30000);
+    assert_eq!(report[0].min_bytes, 8000);
+    assert_eq!(report[0].max_bytes, 12000);
+    assert_eq!(report[0].mean_bytes, 10000.0);
+    assert_eq!(report[1].name, Some("UpdateEntityPosition"));
+    assert_eq!(report[1].total_bytes, 2000);
+
+    let report = stats.report(10);
+    assert_eq!(report.len(), 3);
+    assert_eq!(report[2].direction, PacketDirection::Serverbound);
+    assert_eq!(report[2].name, None);
+    assert_eq!(stats.unknown(), 1);
+
+    let table = stats.to_string();
+    assert_eq!(table.lines().count(), 4);
+    assert!(table.lines().nth(1).unwrap().contains("ChunkData"));
+}
+
+#[test]
+fn test_packet_stats_connection() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = thread::spawn(move || {
+        let mut connection = SocketConnection::<Server>::new(listener.accept().unwrap().0, Pipeline::new());
+        connection.read_packet().unwrap();
+    });
+
+    let stats = Arc::new(PacketStats::new());
+    let pipeline = Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing"));
+    let mut connection = SocketConnection::<Client>::new(std::net::TcpStream::connect(("127.0.0.1", port)).unwrap(), pipeline);
+    connection.set_packet_stats(Some(stats.clone()));
+
+    let mut packet = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    packet.write_var_i32(0x00).unwrap();
+    packet.write_str("hello").unwrap();
+    connection.write(packet).unwrap();
+    server.join().unwrap();
+
+    let line = stats.get(PacketState::Handshaking, PacketDirection::Serverbound, 0x00).unwrap();
+    assert_eq!(line.count, 1);
+    assert_eq!(line.total_bytes, 7);
+}
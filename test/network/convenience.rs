@@ -0,0 +1,222 @@
+use crate::{
+    components::Component,
+    network::{
+        buffer::Buffer,
+        connection::{Connection, Readable},
+        convenience::{BossBar, BossBarColor, BossBarStyle, Sidebar},
+        nbt::{Compound, Tag},
+        types::Slot,
+        versions::{V1_18_2, V1_19, V1_20_1, V1_20_4, V1_21_1}
+    },
+    test::mock::MockConnection
+};
+
+fn connection() -> MockConnection {
+    MockConnection::server()
+}
+
+fn read_f32(buffer: &mut Buffer) -> f32 {
+    f32::from_bits(buffer.read_u32().unwrap())
+}
+
+#[test]
+fn test_boss_bar() {
+    let mut connection = connection();
+    let mut boss_bar = BossBar::create(&mut connection, &V1_20_1, "Position in queue: 42", BossBarColor::Yellow, BossBarStyle::Notched10).unwrap();
+
+    let mut packets = connection.take();
+    assert_eq!(packets.len(), 1);
+    let add = &mut packets[0];
+    assert_eq!(add.read_var_i32().unwrap(), 0x0B);
+    assert_eq!(uuid::Uuid::read(add).unwrap(), boss_bar.uuid());
+    assert_eq!(add.read_var_i32().unwrap(), 0);
+    assert_eq!(add.read_string().unwrap(), Component::text("Position in queue: 42").to_json());
+    assert_eq!(read_f32(add), 1.0);
+    assert_eq!(add.read_var_i32().unwrap(), 4);
+    assert_eq!(add.read_var_i32().unwrap(), 2);
+    assert_eq!(add.read_u8().unwrap(), 0);
+    assert_eq!(add.remaining(), 0);
+
+    boss_bar.set_progress(&mut connection, 0.5).unwrap();
+    boss_bar.set_progress(&mut connection, 0.5).unwrap();
+    boss_bar.set_title(&mut connection, "Position in queue: 41").unwrap();
+    boss_bar.remove(&mut connection).unwrap();
+    boss_bar.set_progress(&mut connection, 0.25).unwrap();
+
+    let mut packets = connection.take();
+    assert_eq!(packets.len(), 3);
+    let actions = packets
+        .iter_mut()
+        .map(|packet| {
+            assert_eq!(packet.read_var_i32().unwrap(), 0x0B);
+            uuid::Uuid::read(packet).unwrap();
+            packet.read_var_i32().unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(actions, vec![2, 3, 1]);
+    assert_eq!(read_f32(&mut packets[0]), 0.5);
+    assert_eq!(packets[1].read_string().unwrap(), Component::text("Position in queue: 41").to_json());
+    assert_eq!(packets[2].remaining(), 0);
+
+    boss_bar.resend(&mut connection).unwrap();
+    assert!(connection.take().is_empty());
+}
+
+#[test]
+fn test_boss_bar_ids() {
+    // 1.19 removed Spawn Living Entity, Spawn Painting and Sculk Vibration Signal, which moved Boss Bar down
+    for (version, id) in [(V1_18_2, 0x0D), (V1_19, 0x0A), (V1_20_1, 0x0B)] {
+        let mut connection = connection();
+        BossBar::create(&mut connection, &version, "Queue", BossBarColor::Blue, BossBarStyle::Progress).unwrap();
+        assert_eq!(connection.take()[0].read_var_i32().unwrap(), id);
+    }
+}
+
+#[test]
+fn test_boss_bar_resend_nbt_title() {
+    let mut connection = connection();
+    let mut boss_bar = BossBar::create(&mut connection, &V1_20_4, Component::text("Queue").color("gold"), BossBarColor::Blue, BossBarStyle::Progress).unwrap();
+    boss_bar.set_progress(&mut connection, 2.0).unwrap();
+    connection.take();
+
+    let mut reconnected = self::connection();
+    boss_bar.resend(&mut reconnected).unwrap();
+    let mut packets = reconnected.take();
+    assert_eq!(packets.len(), 1);
+    let add = &mut packets[0];
+    assert_eq!(add.read_var_i32().unwrap(), 0x0A);
+    assert_eq!(uuid::Uuid::read(add).unwrap(), boss_bar.uuid());
+    assert_eq!(add.read_var_i32().unwrap(), 0);
+    assert_eq!(
+        Compound::read(add).unwrap(),
+        Compound::new().with("text", Tag::String("Queue".to_string())).with("color", Tag::String("gold".to_string()))
+    );
+    assert_eq!(read_f32(add), 1.0);
+}
+
+fn assert_objective(packet: &mut Buffer, id: i32, objective: &str, mode: u8) {
+    assert_eq!(packet.read_var_i32().unwrap(), id);
+    assert_eq!(packet.read_string().unwrap(), objective);
+    assert_eq!(packet.read_u8().unwrap(), mode);
+}
+
+#[test]
+fn test_sidebar_legacy() {
+    let mut connection = connection();
+    let mut sidebar = Sidebar::create(&mut connection, &V1_20_1, "Lobby").unwrap();
+    let objective = sidebar.objective().to_string();
+
+    let mut packets = connection.take();
+    assert_eq!(packets.len(), 2);
+    assert_objective(&mut packets[0], 0x58, &objective, 0);
+    assert_eq!(packets[0].read_string().unwrap(), Component::text("Lobby").to_json());
+    assert_eq!(packets[0].read_var_i32().unwrap(), 0);
+    assert_eq!(packets[1].read_var_i32().unwrap(), 0x51);
+    assert_eq!(packets[1].read_u8().unwrap(), 1);
+    assert_eq!(packets[1].read_string().unwrap(), objective);
+
+    sidebar.set_line(&mut connection, 0, Component::text("Queue").color("red")).unwrap();
+    sidebar.set_line(&mut connection, 0, Component::text("Queue").color("red")).unwrap();
+    sidebar.set_line(&mut connection, 1, "42").unwrap();
+    sidebar.set_line(&mut connection, 1, "41").unwrap();
+    sidebar.set_title(&mut connection, "Lobby").unwrap();
+
+    let mut packets = connection.take();
+    let scores = packets
+        .iter_mut()
+        .map(|packet| {
+            assert_eq!(packet.read_var_i32().unwrap(), 0x5B);
+            let holder = packet.read_string().unwrap();
+            let action = packet.read_u8().unwrap();
+            assert_eq!(packet.read_string().unwrap(), objective);
+            let score = if action == 0 { Some(packet.read_var_i32().unwrap()) } else { None };
+            (holder, score)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        scores,
+        vec![
+            ("\u{a7}cQueue\u{a7}0".to_string(), Some(0)),
+            ("42\u{a7}1".to_string(), Some(-1)),
+            ("42\u{a7}1".to_string(), None),
+            ("41\u{a7}1".to_string(), Some(-1))
+        ]
+    );
+
+    sidebar.remove(&mut connection).unwrap();
+    let mut packets = connection.take();
+    assert_eq!(packets.len(), 1);
+    assert_objective(&mut packets[0], 0x58, &objective, 1);
+    assert_eq!(packets[0].remaining(), 0);
+    assert!(sidebar.set_line(&mut connection, 15, "overflow").is_err());
+}
+
+#[test]
+fn test_sidebar_resend() {
+    let mut connection = connection();
+    let mut sidebar = Sidebar::create(&mut connection, &V1_20_4, "Lobby").unwrap();
+    sidebar.set_line(&mut connection, 2, "Players: 3").unwrap();
+    sidebar.remove_line(&mut connection, 2).unwrap();
+    sidebar.set_line(&mut connection, 0, "Queue").unwrap();
+    sidebar.set_line(&mut connection, 0, "Queue: 41").unwrap();
+    let objective = sidebar.objective().to_string();
+
+    let mut packets = connection.take();
+    assert_eq!(packets.len(), 6);
+    assert_objective(&mut packets[0], 0x5C, &objective, 0);
+    Compound::read(&mut packets[0]).unwrap();
+    assert_eq!(packets[0].read_var_i32().unwrap(), 0);
+    assert!(packets[0].read_bool().unwrap());
+    assert_eq!(packets[0].read_var_i32().unwrap(), 0);
+    assert_eq!(packets[1].read_var_i32().unwrap(), 0x55);
+    assert_eq!(packets[1].read_var_i32().unwrap(), 1);
+
+    let mut reset = packets.remove(3);
+    assert_eq!(reset.read_var_i32().unwrap(), 0x42);
+    assert_eq!(reset.read_string().unwrap(), "line2");
+    assert!(reset.read_bool().unwrap());
+    assert_eq!(reset.read_string().unwrap(), objective);
+
+    for (packet, (holder, score, text)) in packets[2..].iter_mut().zip([("line2", -2, "Players: 3"), ("line0", 0, "Queue"), ("line0", 0, "Queue: 41")]) {
+        assert_eq!(packet.read_var_i32().unwrap(), 0x5F);
+        assert_eq!(packet.read_string().unwrap(), holder);
+        assert_eq!(packet.read_string().unwrap(), objective);
+        assert_eq!(packet.read_var_i32().unwrap(), score);
+        assert!(packet.read_bool().unwrap());
+        assert_eq!(Compound::read(packet).unwrap().get("text"), Some(&Tag::String(text.to_string())));
+        assert!(!packet.read_bool().unwrap());
+    }
+
+    let mut reconnected = self::connection();
+    sidebar.resend(&mut reconnected).unwrap();
+    let mut packets = reconnected.take();
+    assert_eq!(packets.len(), 3);
+    assert_objective(&mut packets[0], 0x5C, &objective, 0);
+    assert_eq!(packets[2].read_var_i32().unwrap(), 0x5F);
+    assert_eq!(packets[2].read_string().unwrap(), "line0");
+    assert_eq!(sidebar.line(0), Some(&Component::text("Queue: 41")));
+    assert_eq!(sidebar.line(2), None);
+}
+
+#[test]
+fn test_connection_protocol_version() {
+    let mut unknown = connection();
+    let error = BossBar::show(&mut unknown, "Queue", BossBarColor::Pink, BossBarStyle::Progress).unwrap_err();
+    assert_eq!(error.to_string(), "Unable to show boss bar => The protocol version of the connection isn't known yet");
+    assert!(unknown.write_versioned(&Slot::new(1, 1)).is_err());
+    assert!(unknown.take().is_empty());
+
+    let (mut old, mut new) = (connection(), connection());
+    old.set_protocol_version(Some(V1_20_1.protocol));
+    new.set_protocol_version(Some(V1_21_1.protocol));
+    for connection in [&mut old, &mut new] {
+        BossBar::show(connection, "Queue", BossBarColor::Pink, BossBarStyle::Progress).unwrap();
+        connection.write_versioned(&Slot::new(1, 1)).unwrap();
+    }
+
+    let (mut old, mut new) = (old.take(), new.take());
+    assert_eq!(old[0].read_var_i32().unwrap(), 0x0B);
+    assert_eq!(new[0].read_var_i32().unwrap(), 0x0A);
+    assert_eq!(old[1].to_bytes(), vec![0x01, 0x01, 0x01, 0x00]);
+    assert_eq!(new[1].to_bytes(), vec![0x01, 0x01, 0x00, 0x00]);
+}
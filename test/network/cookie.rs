@@ -0,0 +1,189 @@
+use std::sync::Arc;
+
+use tokio::net::TcpListener;
+
+use crate::network::{
+    buffer::Buffer,
+    connection::{async_socket::AsyncSocketConnection, pipeline::framing::FrameEncoder, Client, Pipeline, Readable, Server, Writable},
+    cookie::{follow_transfer_async, CookieJar, CookieMessage, CookiePacket, CookieRequest, CookieResponse, StoreCookie, Transfer, LATEST_COOKIE_PROTOCOL, MAX_COOKIE_LENGTH},
+    decode::{DecodeMode, DecodeReport},
+    handshake::{Handshake, NextState},
+    types::Identifier,
+    versions::{V1_20_4, V1_20_6},
+    ByteOrder, Error, PacketDirection, PacketState
+};
+
+fn pipeline() -> Pipeline<'static> {
+    Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing"))
+}
+
+fn key(key: &str) -> Identifier {
+    Identifier::parse(key).unwrap()
+}
+
+#[test]
+fn test_packet_ids() {
+    assert_eq!(CookiePacket::StoreCookie.id(PacketState::Configuration, V1_20_6.protocol).unwrap(), Some(0x0A));
+    assert_eq!(CookiePacket::Transfer.id(PacketState::Play, V1_20_6.protocol).unwrap(), Some(0x73));
+    assert_eq!(CookiePacket::Transfer.id(PacketState::Login, V1_20_6.protocol).unwrap(), None);
+    assert_eq!(CookiePacket::CookieRequest.id(PacketState::Play, V1_20_4.protocol).unwrap(), None);
+    assert_eq!(
+        CookiePacket::from_id(PacketState::Play, PacketDirection::Serverbound, V1_20_6.protocol, 0x11).unwrap(),
+        Some(CookiePacket::CookieResponse)
+    );
+    assert_eq!(CookiePacket::from_id(PacketState::Play, PacketDirection::Clientbound, V1_20_6.protocol, 0x11).unwrap(), None);
+
+    assert_eq!(CookiePacket::Transfer.id(PacketState::Play, LATEST_COOKIE_PROTOCOL).unwrap(), Some(0x73));
+    assert!(CookiePacket::Transfer.id(PacketState::Play, LATEST_COOKIE_PROTOCOL + 1).is_err());
+    assert!(CookiePacket::from_id(PacketState::Play, PacketDirection::Clientbound, LATEST_COOKIE_PROTOCOL + 1, 0x73).is_err());
+}
+
+#[test]
+fn test_payload_length() {
+    let cookie = StoreCookie {
+        key: key("minerust:session"),
+        payload: vec![7; MAX_COOKIE_LENGTH]
+    };
+    let mut buffer = cookie.encode(PacketState::Play, V1_20_6.protocol).unwrap();
+    buffer.reset();
+    assert_eq!(buffer.read_var_i32().unwrap(), 0x6B);
+    assert_eq!(StoreCookie::decode(&mut buffer).unwrap(), cookie);
+
+    let cookie = StoreCookie {
+        key: key("minerust:session"),
+        payload: vec![7; MAX_COOKIE_LENGTH + 1]
+    };
+    assert!(cookie.write(Buffer::empty(true, Some(ByteOrder::BigEndian))).is_err());
+    assert!(CookieJar::new(&V1_20_6).store(cookie.key.clone(), cookie.payload.clone()).is_err());
+
+    let mut buffer = key("minerust:session").write(Buffer::empty(true, Some(ByteOrder::BigEndian))).unwrap();
+    buffer.write_byte_array(&cookie.payload).unwrap();
+    buffer.reset();
+    match StoreCookie::read(&mut buffer) {
+        Err(Error::Decode { offset, .. }) => assert_eq!(offset, 17),
+        result => panic!("Unexpected result {:?}", result)
+    }
+}
+
+#[test]
+fn test_cookie_jar_handle() {
+    let jar = CookieJar::new(&V1_20_6);
+    let report = DecodeReport::new();
+    let mut store = StoreCookie {
+        key: key("minerust:session"),
+        payload: vec![1, 2, 3]
+    }
+    .encode(PacketState::Configuration, jar.protocol())
+    .unwrap();
+    store.reset();
+    assert!(jar
+        .handle(PacketState::Configuration, PacketDirection::Clientbound, &mut store, DecodeMode::Strict, &report)
+        .unwrap()
+        .is_none());
+    assert_eq!(store.position(), 0);
+    assert_eq!(jar.get(&key("minerust:session")), Some(vec![1, 2, 3]));
+
+    let mut request = CookieRequest { key: key("minerust:missing") }.encode(PacketState::Play, jar.protocol()).unwrap();
+    request.reset();
+    assert!(jar
+        .handle(PacketState::Play, PacketDirection::Serverbound, &mut request, DecodeMode::Strict, &report)
+        .unwrap()
+        .is_none());
+
+    let mut response = jar
+        .handle(PacketState::Play, PacketDirection::Clientbound, &mut request, DecodeMode::Strict, &report)
+        .unwrap()
+        .unwrap();
+    response.reset();
+    assert_eq!(response.read_var_i32().unwrap(), 0x11);
+    assert_eq!(
+        CookieResponse::decode(&mut response).unwrap(),
+        CookieResponse {
+            key: key("minerust:missing"),
+            payload: None
+        }
+    );
+}
+
+#[test]
+fn test_transfer_round_trip() {
+    let transfer = Transfer {
+        host: "lobby.example.com".to_string(),
+        port: 25566
+    };
+    let mut buffer = transfer.write(Buffer::empty(true, Some(ByteOrder::BigEndian))).unwrap();
+    buffer.reset();
+    assert_eq!(Transfer::decode(&mut buffer).unwrap(), transfer);
+
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_str("lobby.example.com").unwrap();
+    buffer.write_var_i32(70000).unwrap();
+    buffer.reset();
+    assert!(matches!(Transfer::read(&mut buffer), Err(Error::Decode { offset: 18, .. })));
+}
+
+#[tokio::test]
+async fn test_transfer_carries_cookies() {
+    let target = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let target_port = target.local_addr().unwrap().port();
+    let target = tokio::spawn(async move {
+        let mut connection = AsyncSocketConnection::<Server>::new(target.accept().await.unwrap().0, pipeline());
+        let handshake = Handshake::read(&mut connection.read_packet().await.unwrap()).unwrap();
+        assert_eq!(handshake.next_state, NextState::Transfer);
+        assert_eq!(handshake.protocol_version, V1_20_6.protocol);
+        connection.set_state(PacketState::Login);
+
+        let mut responses = Vec::new();
+        for name in ["minerust:session", "minerust:missing"] {
+            connection
+                .write(CookieRequest { key: key(name) }.encode(PacketState::Login, V1_20_6.protocol).unwrap())
+                .await
+                .unwrap();
+            let mut response = connection.read_packet().await.unwrap();
+            assert_eq!(response.read_var_i32().unwrap(), 0x04);
+            responses.push(CookieResponse::decode(&mut response).unwrap());
+        }
+        responses
+    });
+
+    let origin = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let origin_port = origin.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        let mut connection = AsyncSocketConnection::<Server>::new(origin.accept().await.unwrap().0, pipeline());
+        let cookie = StoreCookie {
+            key: key("minerust:session"),
+            payload: b"ticket".to_vec()
+        };
+        connection.write(cookie.encode(PacketState::Configuration, V1_20_6.protocol).unwrap()).await.unwrap();
+
+        let transfer = Transfer {
+            host: "127.0.0.1".to_string(),
+            port: target_port
+        };
+        connection.write(transfer.encode(PacketState::Configuration, V1_20_6.protocol).unwrap()).await.unwrap();
+    });
+
+    let jar = Arc::new(CookieJar::new(&V1_20_6));
+    let stream = tokio::net::TcpStream::connect(("127.0.0.1", origin_port)).await.unwrap();
+    let mut connection = AsyncSocketConnection::<Client>::new(stream, pipeline());
+    connection.set_state(PacketState::Configuration);
+    connection.set_cookie_jar(Some(jar.clone()));
+
+    connection.read_packet().await.unwrap();
+    assert_eq!(jar.get(&key("minerust:session")), Some(b"ticket".to_vec()));
+
+    let mut transfer = connection.read_packet().await.unwrap();
+    assert_eq!(transfer.read_var_i32().unwrap(), 0x0B);
+    let transfer = Transfer::decode(&mut transfer).unwrap();
+
+    let mut connection = follow_transfer_async(&transfer, jar, pipeline()).await.unwrap();
+    assert_eq!(connection.state(), PacketState::Login);
+    for _ in 0..2 {
+        let mut request = connection.read_packet().await.unwrap();
+        assert_eq!(request.read_var_i32().unwrap(), 0x05);
+    }
+
+    let responses = target.await.unwrap();
+    assert_eq!(responses[0].payload.as_deref(), Some(&b"ticket"[..]));
+    assert_eq!(responses[1].payload, None);
+}
@@ -0,0 +1,197 @@
+use std::{
+    net::{TcpListener, TcpStream},
+    thread,
+    time::Duration
+};
+
+use tokio::sync::oneshot::error::TryRecvError;
+
+use crate::network::{
+    buffer::Buffer,
+    connection::{pipeline::framing::FrameEncoder, socket::SocketConnection, Client, Connection, Pipeline, Readable, Server},
+    correlation::{AwaitError, ResponseCorrelator, SharedCorrelator},
+    ByteOrder, Error, Packet, PacketDescriptor, PacketDirection, PacketState
+};
+
+#[derive(Debug, PartialEq)]
+struct Pong(i32);
+
+impl Readable for Pong {
+    fn read(buffer: &mut Buffer) -> Result<Self, Error> {
+        buffer.read_var_i32()?;
+        Ok(Self(buffer.read_i32()?))
+    }
+}
+
+impl Packet for Pong {
+    fn state() -> PacketState {
+        PacketState::Play
+    }
+
+    fn id() -> i32 {
+        0x04
+    }
+}
+
+const RESPAWN: PacketDescriptor = PacketDescriptor {
+    name: "Respawn",
+    id: 0x10,
+    state: PacketState::Play,
+    direction: PacketDirection::Clientbound,
+    fields: &[]
+};
+
+fn packet(id: i32, payload: i32) -> Buffer {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(id).unwrap();
+    buffer.write_i32(payload).unwrap();
+    buffer.reset();
+    buffer
+}
+
+fn payload(mut packet: Buffer) -> (i32, i32) {
+    (packet.read_var_i32().unwrap(), packet.read_i32().unwrap())
+}
+
+#[test]
+fn test_correlator_interleaved_packets() {
+    let mut correlator = ResponseCorrelator::new();
+    let mut first = correlator.expect(PacketState::Play, 0x04);
+    let mut second = correlator.expect(PacketState::Play, 0x04);
+    let mut other = correlator.expect(PacketState::Play, 0x10);
+    assert_eq!(correlator.waiting(), 3);
+
+    let mut unrelated = Vec::new();
+    for (state, packet) in [
+        (PacketState::Play, packet(0x24, 1)),
+        (PacketState::Configuration, packet(0x04, 2)),
+        (PacketState::Play, packet(0x04, 3)),
+        (PacketState::Play, packet(0x33, 4)),
+        (PacketState::Play, packet(0x04, 5)),
+        (PacketState::Play, packet(0x04, 6))
+    ] {
+        unrelated.extend(correlator.offer(state, packet));
+    }
+
+    assert_eq!(payload(first.try_recv().unwrap()), (0x04, 3));
+    assert_eq!(payload(second.try_recv().unwrap()), (0x04, 5));
+    assert_eq!(unrelated.into_iter().map(payload).collect::<Vec<_>>(), vec![(0x24, 1), (0x04, 2), (0x33, 4), (0x04, 6)]);
+    assert!(matches!(other.try_recv(), Err(TryRecvError::Empty)));
+    assert!(!correlator.is_waiting(PacketState::Play, 0x04));
+    assert!(correlator.is_waiting(PacketState::Play, 0x10));
+
+    drop(other);
+    let mut next = correlator.expect(PacketState::Play, 0x10);
+    assert_eq!(correlator.waiting(), 1);
+    assert!(correlator.offer(PacketState::Play, packet(0x10, 7)).is_none());
+    assert_eq!(payload(next.try_recv().unwrap()), (0x10, 7));
+
+    let abandoned = correlator.expect(PacketState::Play, 0x10);
+    drop(abandoned);
+    assert!(correlator.offer(PacketState::Play, packet(0x10, 8)).is_some());
+    assert_eq!(correlator.waiting(), 0);
+}
+
+#[tokio::test]
+async fn test_await_packet() {
+    let correlator = SharedCorrelator::new();
+    let pong = correlator.await_packet::<Pong>(Duration::from_secs(5));
+    let any = correlator.await_any(&[RESPAWN, PacketDescriptor { id: 0x04, ..RESPAWN }], Duration::from_secs(5));
+
+    assert!(correlator.offer(PacketState::Play, packet(0x04, 1)).is_none());
+    assert!(correlator.offer(PacketState::Play, packet(0x10, 2)).is_none());
+    assert!(correlator.offer(PacketState::Play, packet(0x04, 3)).is_some());
+    assert_eq!(pong.await.unwrap(), Pong(1));
+    let respawn = any.await.unwrap();
+    assert_eq!((respawn.state, respawn.id, payload(respawn.buffer)), (PacketState::Play, 0x10, (0x10, 2)));
+    assert_eq!(correlator.lock().waiting(), 0);
+}
+
+#[tokio::test]
+async fn test_await_packet_timeout() {
+    let correlator = SharedCorrelator::new();
+    let result = correlator.await_packet::<Pong>(Duration::from_millis(20)).await;
+    assert!(matches!(result, Err(AwaitError::Timeout(timeout)) if timeout == Duration::from_millis(20)));
+
+    assert_eq!(correlator.lock().waiting(), 0);
+    assert!(correlator.offer(PacketState::Play, packet(0x04, 1)).is_some());
+
+    let truncated = correlator.await_packet::<Pong>(Duration::from_secs(5));
+    correlator.offer(PacketState::Play, Buffer::new(vec![0x04, 0x00], false, Some(ByteOrder::BigEndian)));
+    assert!(matches!(truncated.await, Err(AwaitError::Decode(_))));
+}
+
+#[tokio::test]
+async fn test_await_packet_closed() {
+    let correlator = SharedCorrelator::new();
+    let pending = correlator.await_any(&[RESPAWN], Duration::from_secs(5));
+    correlator.close();
+    assert!(matches!(pending.await, Err(AwaitError::Closed)));
+    assert!(matches!(correlator.await_packet::<Pong>(Duration::from_secs(5)).await, Err(AwaitError::Closed)));
+    assert!(matches!(correlator.await_packet_blocking::<Pong>(Duration::from_secs(5)).wait(), Err(AwaitError::Closed)));
+}
+
+#[test]
+fn test_blocking_waiters_pruned() {
+    let correlator = SharedCorrelator::new();
+    for _ in 0..3 {
+        let result = correlator.await_packet_blocking::<Pong>(Duration::from_millis(5)).wait();
+        assert!(matches!(result, Err(AwaitError::Timeout(_))));
+    }
+    drop(correlator.await_any_blocking(&[RESPAWN], Duration::from_secs(5)));
+    assert_eq!(correlator.lock().waiting(), 0);
+    assert!(!correlator.lock().is_waiting(PacketState::Play, 0x04));
+
+    // Nobody waits anymore, so the packet goes back to the read loop
+    assert!(correlator.offer(PacketState::Play, packet(0x04, 1)).is_some());
+}
+
+#[tokio::test]
+async fn test_read_error_closes() {
+    let correlator = SharedCorrelator::new();
+    let pending = correlator.await_any(&[RESPAWN], Duration::from_secs(5));
+    assert!(correlator.offer_read(PacketState::Play, Ok(packet(0x24, 1))).unwrap().is_some());
+
+    let eof = Error::IoError(std::io::ErrorKind::UnexpectedEof.into());
+    assert!(matches!(correlator.offer_read(PacketState::Play, Err(eof)), Err(Error::IoError(_))));
+    assert!(matches!(pending.await, Err(AwaitError::Closed)));
+    assert!(correlator.lock().is_closed());
+}
+
+#[test]
+fn test_await_packet_blocking_interleaved() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let mut connection = SocketConnection::<Server>::new(listener.accept().unwrap().0, Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing")));
+        for payload in 0..20 {
+            connection.write(packet(0x24, payload)).unwrap();
+        }
+        connection.write(packet(0x04, 42)).unwrap();
+    });
+
+    let correlator = SharedCorrelator::new();
+    let pong = correlator.await_packet_blocking::<Pong>(Duration::from_secs(5));
+    let any = correlator.await_any_blocking(&[RESPAWN], Duration::from_secs(5));
+
+    let read_loop = {
+        let correlator = correlator.clone();
+        let mut connection = SocketConnection::<Client>::new(TcpStream::connect(address).unwrap(), Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing")));
+        thread::spawn(move || {
+            let mut handled = Vec::new();
+            while let Ok(packet) = correlator.offer_read(PacketState::Play, connection.read_packet()) {
+                if let Some(packet) = packet {
+                    thread::sleep(Duration::from_millis(1));
+                    handled.push(payload(packet));
+                }
+            }
+            assert!(correlator.lock().is_closed());
+            handled
+        })
+    };
+
+    assert_eq!(pong.wait().unwrap(), Pong(42));
+    server.join().unwrap();
+    assert_eq!(read_loop.join().unwrap(), (0..20).map(|payload| (0x24, payload)).collect::<Vec<_>>());
+    assert!(matches!(any.wait(), Err(AwaitError::Closed)));
+}
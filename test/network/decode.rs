@@ -0,0 +1,135 @@
+use std::{
+    net::{TcpListener, TcpStream},
+    sync::Arc
+};
+
+use crate::network::{
+    buffer::Buffer,
+    connection::{pipeline::framing::FrameEncoder, socket::SocketConnection, Client, Connection, Pipeline, Server},
+    cookie::{CookieJar, CookieMessage, StoreCookie},
+    decode::{DecodeIssue, DecodeIssueLine, DecodeMode, DecodeReport},
+    statistics::{request_statistics, StatisticCategory},
+    tracker::{ClientStateMirror, Difficulty},
+    types::Identifier,
+    versions::V1_21_1,
+    ByteOrder, Error, PacketState
+};
+
+fn padded(id: i32) -> Buffer {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(id).unwrap();
+    buffer.write_u8(2).unwrap();
+    buffer.write_bytes(&[0xCA, 0xFE]).unwrap();
+    buffer.reset();
+    buffer
+}
+
+fn read_difficulty(packet: &mut Buffer, mode: DecodeMode, report: &DecodeReport) -> Result<Difficulty, Error> {
+    let id = packet.read_var_i32()?;
+    let difficulty = mode.check_variant(Difficulty::from_id(packet.read_u8()?), packet, PacketState::Play, id, report)?;
+    mode.check_trailing(packet, PacketState::Play, id, report)?;
+    Ok(difficulty)
+}
+
+#[test]
+fn test_decode_modes() {
+    let report = DecodeReport::new();
+    assert!(matches!(read_difficulty(&mut padded(0x0B), DecodeMode::Strict, &report), Err(Error::Decode { offset: 2, .. })));
+    assert!(report.is_empty());
+
+    let mut packet = padded(0x0B);
+    assert_eq!(read_difficulty(&mut packet, DecodeMode::Lenient, &report).unwrap(), Difficulty::Normal);
+    assert_eq!(packet.remaining(), 0);
+
+    let mut packet = Buffer::new(vec![0x0B, 0x07], false, Some(ByteOrder::BigEndian));
+    assert!(matches!(read_difficulty(&mut packet.clone(), DecodeMode::Strict, &report), Err(Error::Decode { offset: 2, .. })));
+    assert_eq!(read_difficulty(&mut packet, DecodeMode::Lenient, &report).unwrap(), Difficulty::Unknown(7));
+    read_difficulty(&mut padded(0x0B), DecodeMode::Lenient, &report).unwrap();
+
+    assert_eq!(
+        report.lines(),
+        vec![
+            DecodeIssueLine {
+                state: PacketState::Play,
+                id: 0x0B,
+                issue: DecodeIssue::TrailingBytes,
+                count: 2
+            },
+            DecodeIssueLine {
+                state: PacketState::Play,
+                id: 0x0B,
+                issue: DecodeIssue::UnknownVariant,
+                count: 1
+            }
+        ]
+    );
+    assert!(DecodeMode::Strict.check_variant(StatisticCategory::Custom, &packet, PacketState::Play, 0x0B, &report).is_ok());
+    report.clear();
+    assert!(report.is_empty());
+}
+
+fn connected() -> (SocketConnection<'static, Client>, SocketConnection<'static, Server>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = SocketConnection::<Client>::new(
+        TcpStream::connect(listener.local_addr().unwrap()).unwrap(),
+        Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing"))
+    );
+    let server = SocketConnection::<Server>::new(listener.accept().unwrap().0, Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing")));
+    (client, server)
+}
+
+fn raw(bytes: &[u8]) -> Buffer {
+    Buffer::new(bytes.to_vec(), false, Some(ByteOrder::BigEndian))
+}
+
+fn store_cookie() -> Buffer {
+    let mut store = StoreCookie {
+        key: Identifier::parse("minerust:session").unwrap(),
+        payload: vec![1, 2, 3]
+    }
+    .encode(PacketState::Play, V1_21_1.protocol)
+    .unwrap();
+    store.write_u8(0xFF).unwrap();
+    store
+}
+
+#[test]
+fn test_connection_decode_report() {
+    let (mut client, mut server) = connected();
+    client.set_state(PacketState::Play);
+    client.set_protocol_version(Some(V1_21_1.protocol));
+    client.set_cookie_jar(Some(Arc::new(CookieJar::new(&V1_21_1))));
+    assert_eq!(client.decode_mode(), DecodeMode::Strict);
+    client.set_decode_mode(DecodeMode::Lenient);
+
+    let report = client.decode_report().clone();
+    let mut mirror = ClientStateMirror::new(&V1_21_1).decoding(client.decode_mode(), report.clone());
+    server.write(raw(&[0x0B, 0x07, 0x00])).unwrap();
+    server.write(raw(&[0x0B, 0x02, 0x01, 0xCA, 0xFE])).unwrap();
+    server.write(store_cookie()).unwrap();
+    for _ in 0..3 {
+        mirror.handle(&mut client.read_packet().unwrap()).unwrap();
+    }
+    assert_eq!(mirror.difficulty(), Some(Difficulty::Normal));
+    assert_eq!(client.cookie_jar().unwrap().get(&Identifier::parse("minerust:session").unwrap()), Some(vec![1, 2, 3]));
+
+    // Award Statistics with a category that doesn't exist
+    server.write(raw(&[0x04, 0x01, 0x2A, 0x01, 0x05])).unwrap();
+    let statistics = request_statistics(&mut client, &V1_21_1, |_, _| Ok(())).unwrap();
+    assert_eq!(statistics.get(StatisticCategory::Unknown(42), 1), Some(5));
+
+    assert_eq!(report.count(PacketState::Play, 0x0B, DecodeIssue::UnknownVariant), 1);
+    assert_eq!(report.count(PacketState::Play, 0x0B, DecodeIssue::TrailingBytes), 1);
+    assert_eq!(report.count(PacketState::Play, 0x6B, DecodeIssue::TrailingBytes), 1);
+    assert_eq!(report.count(PacketState::Play, 0x04, DecodeIssue::UnknownVariant), 1);
+
+    client.set_decode_mode(DecodeMode::Strict);
+    let mut mirror = ClientStateMirror::new(&V1_21_1).decoding(client.decode_mode(), report.clone());
+    server.write(raw(&[0x0B, 0x07, 0x00])).unwrap();
+    assert!(matches!(mirror.handle(&mut client.read_packet().unwrap()), Err(Error::Decode { .. })));
+    server.write(raw(&[0x04, 0x01, 0x2A, 0x01, 0x05])).unwrap();
+    assert!(matches!(request_statistics(&mut client, &V1_21_1, |_, _| Ok(())), Err(Error::Decode { .. })));
+    server.write(store_cookie()).unwrap();
+    assert!(matches!(client.read_packet(), Err(Error::Decode { .. })));
+    assert_eq!(report.lines().iter().map(|line| line.count).sum::<u64>(), 4);
+}
@@ -0,0 +1,36 @@
+use rand::{rngs::StdRng, SeedableRng};
+use sha1::{Digest, Sha1};
+
+use crate::network::encryption::{generate_shared_secret, generate_shared_secret_with, minecraft_digest, Cipher};
+
+#[test]
+fn test_minecraft_digest() {
+    assert_eq!(minecraft_digest(Sha1::digest(b"Notch").into()), "4ed1f46bbe04bc756bcb17c0c7ce3e4632f06a48");
+    assert_eq!(minecraft_digest(Sha1::digest(b"jeb_").into()), "-7c9d5b0044c130109a5d7b5fb5c317c02b4e28c1");
+    assert_eq!(minecraft_digest(Sha1::digest(b"simon").into()), "88e16a1019277b15d58faf0541e11910eb756f6");
+}
+
+#[test]
+fn test_cipher_round_trip() {
+    let shared_secret = generate_shared_secret();
+    let mut encryptor = Cipher::new(&shared_secret);
+    let mut decryptor = Cipher::new(&shared_secret);
+
+    let mut first = b"Hello".to_vec();
+    let mut second = b"World".to_vec();
+    encryptor.encrypt(&mut first);
+    encryptor.encrypt(&mut second);
+    assert_ne!(first, b"Hello");
+
+    decryptor.decrypt(&mut first);
+    decryptor.decrypt(&mut second);
+    assert_eq!(first, b"Hello");
+    assert_eq!(second, b"World");
+}
+
+#[test]
+fn test_shared_secret_from_rng() {
+    let shared_secret = generate_shared_secret_with(&mut StdRng::seed_from_u64(1));
+    assert_eq!(shared_secret, generate_shared_secret_with(&mut StdRng::seed_from_u64(1)));
+    assert_ne!(shared_secret, generate_shared_secret_with(&mut StdRng::seed_from_u64(2)));
+}
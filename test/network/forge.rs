@@ -0,0 +1,141 @@
+use serde_json::{json, Value};
+
+use crate::network::{
+    forge::{decode_packed, encode_packed, ForgeChannel, ForgeMod, ForgeStatus},
+    status::StatusResponse
+};
+
+const FORGE_1_12: &str = include_str!("../fixtures/network/status/forge_1_12.json");
+// Hand-written after the status of a Forge 47.1.79 and a NeoForge 20.2.86 server, NeoForge renamed its mod and channels
+const FORGE_1_20: &str = include_str!("../fixtures/network/status/forge_1_20.json");
+const NEOFORGE_1_20_2: &str = include_str!("../fixtures/network/status/neoforge_1_20_2.json");
+const VANILLA: &str = include_str!("../fixtures/network/status/status.json");
+
+fn forge_mod(id: &str, version: Option<&str>) -> ForgeMod {
+    ForgeMod {
+        id: id.to_string(),
+        version: version.map(str::to_string)
+    }
+}
+
+fn channel(name: &str, version: &str, required: bool) -> ForgeChannel {
+    ForgeChannel {
+        name: name.to_string(),
+        version: version.to_string(),
+        required
+    }
+}
+
+#[test]
+fn test_forge_legacy_modinfo() {
+    let forge = StatusResponse::parse(FORGE_1_12).unwrap().forge().unwrap();
+    let ForgeStatus::Legacy { mod_type, mods } = &forge else {
+        panic!("Expected the legacy mod list, got {:?}", forge)
+    };
+    assert_eq!(mod_type, "FML");
+    assert_eq!(mods.len(), 5);
+    assert_eq!(mods[3], forge_mod("forge", Some("14.23.5.2860")));
+    assert!(forge.channels().is_empty());
+}
+
+#[test]
+fn test_forge_packed_forge_data() {
+    let forge = StatusResponse::parse(FORGE_1_20).unwrap().forge().unwrap();
+    let ForgeStatus::ForgeData { network_version, .. } = &forge else {
+        panic!("Expected forge data, got {:?}", forge)
+    };
+    assert_eq!(*network_version, 3);
+    assert!(!forge.is_truncated());
+    assert_eq!(
+        forge.mods(),
+        [
+            forge_mod("minecraft", Some("1.20.1")),
+            forge_mod("forge", Some("47.1.79")),
+            forge_mod("jei", Some("15.2.0.27")),
+            forge_mod("spark", None)
+        ]
+    );
+    assert_eq!(
+        forge.channels(),
+        [
+            channel("forge:tier_sorting", "1.0", false),
+            channel("forge:split", "1.1", true),
+            channel("jei:channel", "1.0.0", true),
+            channel("minecraft:register", "FML3", false),
+            channel("minecraft:unregister", "FML3", false)
+        ]
+    );
+}
+
+#[test]
+fn test_forge_neoforge_data() {
+    let forge = StatusResponse::parse(NEOFORGE_1_20_2).unwrap().forge().unwrap();
+    let ForgeStatus::ForgeData { network_version, .. } = &forge else {
+        panic!("Expected forge data, got {:?}", forge)
+    };
+    assert_eq!(*network_version, 4);
+    assert_eq!(
+        forge.mods(),
+        [
+            forge_mod("minecraft", Some("1.20.2")),
+            forge_mod("neoforge", Some("20.2.86")),
+            forge_mod("jei", Some("16.0.0.28")),
+            forge_mod("spark", None)
+        ]
+    );
+    assert_eq!(
+        forge.channels(),
+        [
+            channel("neoforge:tier_sorting", "1.0", false),
+            channel("neoforge:split", "1.1", true),
+            channel("jei:channel", "1.0.0", true),
+            channel("minecraft:register", "NEOFORGE", false),
+            channel("minecraft:unregister", "NEOFORGE", false)
+        ]
+    );
+}
+
+#[test]
+fn test_forge_vanilla() {
+    assert_eq!(StatusResponse::parse(VANILLA).unwrap().forge(), None);
+    assert_eq!(ForgeStatus::from_status_json(&serde_json::from_str(VANILLA).unwrap()), None);
+}
+
+#[test]
+fn test_forge_from_status_json() {
+    for fixture in [FORGE_1_20, NEOFORGE_1_20_2] {
+        let status = serde_json::from_str::<Value>(fixture).unwrap();
+        assert_eq!(ForgeStatus::from_status_json(&status), StatusResponse::parse(fixture).unwrap().forge());
+    }
+}
+
+#[test]
+fn test_forge_unlisted_forge_data() {
+    let status = json!({
+        "forgeData": {
+            "channels": [{ "res": "forge:handshake", "version": "FML2", "required": true }],
+            "mods": [{ "modId": "forge", "modmarker": "ANY" }],
+            "fmlNetworkVersion": 2
+        }
+    });
+    let forge = ForgeStatus::from_status_json(&status).unwrap();
+    assert_eq!(forge.mods(), [forge_mod("forge", Some("ANY"))]);
+    assert_eq!(forge.channels(), [channel("forge:handshake", "FML2", true)]);
+}
+
+#[test]
+fn test_forge_unknown_format() {
+    let forge_data = json!({ "fmlNetworkVersion": 9, "d": "\u{0001}" });
+    assert_eq!(ForgeStatus::from_status_json(&json!({ "forgeData": forge_data })), Some(ForgeStatus::Raw(forge_data)));
+    let modinfo = json!({ "type": "FML", "mods": [] });
+    assert_eq!(ForgeStatus::from_status_json(&json!({ "modinfo": modinfo })), Some(ForgeStatus::Raw(modinfo)));
+}
+
+#[test]
+fn test_forge_packed_round_trip() {
+    for length in 0..64 {
+        let bytes = (0..length).map(|byte| (byte * 37 + 11) as u8).collect::<Vec<_>>();
+        assert_eq!(decode_packed(&encode_packed(&bytes)).unwrap().to_bytes(), bytes);
+    }
+    assert!(decode_packed("\u{0010}\u{0000}\u{0001}").is_err());
+}
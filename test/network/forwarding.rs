@@ -0,0 +1,98 @@
+use uuid::Uuid;
+
+use crate::{
+    network::{
+        buffer::Buffer,
+        forwarding::{answer_velocity_request, read_velocity_response, velocity_request, ForwardedPlayer, VELOCITY_DEFAULT_VERSION, VELOCITY_LAZY_SESSION_VERSION},
+        login::{GameProfile, SERVERBOUND_LOGIN_PLUGIN_RESPONSE},
+        ByteOrder, Error
+    },
+    webapi::Property
+};
+
+const SECRET: &[u8] = b"forwarding-secret";
+
+fn player(properties: Vec<Property>) -> ForwardedPlayer {
+    ForwardedPlayer::new(
+        "127.0.0.1".parse().unwrap(),
+        GameProfile::new(Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap(), "Notch"),
+        properties
+    )
+}
+
+fn textures() -> Property {
+    Property {
+        name: "textures".to_string(),
+        value: "e30=".to_string(),
+        signature: Some("c2ln".to_string())
+    }
+}
+
+fn hex(bytes: &str) -> Vec<u8> {
+    (0..bytes.len()).step_by(2).map(|index| u8::from_str_radix(&bytes[index..index + 2], 16).unwrap()).collect()
+}
+
+#[test]
+fn test_legacy_address() {
+    assert_eq!(
+        player(Vec::new()).to_legacy_address("mc.example.com"),
+        "mc.example.com\x00127.0.0.1\x00069a79f444e94726a5befca90e38aaf5"
+    );
+
+    let address = "mc.example.com\x00127.0.0.1\x00069a79f444e94726a5befca90e38aaf5\x00[{\"name\":\"textures\",\"value\":\"e30=\",\"signature\":\"c2ln\"}]";
+    let (host, forwarded) = ForwardedPlayer::from_legacy_address(address, "Notch").unwrap();
+    assert_eq!(host, "mc.example.com");
+    assert_eq!(forwarded, player(vec![textures()]));
+
+    let (host, round_trip) = ForwardedPlayer::from_legacy_address(&forwarded.to_legacy_address("::1"), "Notch").unwrap();
+    assert_eq!((host.as_str(), round_trip), ("::1", forwarded));
+
+    assert!(matches!(ForwardedPlayer::from_legacy_address("mc.example.com", "Notch"), Err(Error::Decode { .. })));
+    assert!(ForwardedPlayer::from_legacy_address("mc.example.com\x00localhost\x00069a79f444e94726a5befca90e38aaf5", "Notch").is_err());
+}
+
+#[test]
+fn test_velocity_signature() {
+    let data = hex("01093132372e302e302e31069a79f444e94726a5befca90e38aaf5054e6f74636800");
+    let mut expected = hex("f5184e8d98a8e0df0961212549daf5c36d1c6ac2d18324f46fadcc41fe545d2a");
+    expected.extend(&data);
+
+    let signed = player(Vec::new()).to_velocity_data(SECRET, VELOCITY_DEFAULT_VERSION).unwrap();
+    assert_eq!(signed, expected);
+    assert_eq!(ForwardedPlayer::from_velocity_data(&signed, SECRET).unwrap(), (VELOCITY_DEFAULT_VERSION, player(Vec::new())));
+
+    let mut tampered = signed.clone();
+    *tampered.last_mut().unwrap() = 1;
+    assert!(matches!(ForwardedPlayer::from_velocity_data(&tampered, SECRET), Err(Error::Decode { offset: 0, .. })));
+    assert!(ForwardedPlayer::from_velocity_data(&signed, b"other-secret").is_err());
+    assert!(ForwardedPlayer::from_velocity_data(&signed[..16], SECRET).is_err());
+}
+
+#[test]
+fn test_velocity_login_plugin_exchange() {
+    let mut request = velocity_request(7, VELOCITY_LAZY_SESSION_VERSION).unwrap();
+    request.reset();
+    let mut response = answer_velocity_request(&mut request, SECRET, &player(vec![textures()])).unwrap().unwrap();
+    response.reset();
+    assert_eq!(response.read_var_i32().unwrap(), SERVERBOUND_LOGIN_PLUGIN_RESPONSE);
+    response.reset();
+    assert_eq!(
+        read_velocity_response(&mut response, 7, SECRET).unwrap(),
+        (VELOCITY_LAZY_SESSION_VERSION, player(vec![textures()]))
+    );
+
+    let mut request = velocity_request(8, 2).unwrap();
+    request.reset();
+    let mut response = answer_velocity_request(&mut request, SECRET, &player(Vec::new())).unwrap().unwrap();
+    response.reset();
+    assert_eq!(read_velocity_response(&mut response, 8, SECRET).unwrap().0, VELOCITY_DEFAULT_VERSION);
+    response.reset();
+    assert!(read_velocity_response(&mut response, 9, SECRET).is_err());
+
+    let mut other = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    other.write_var_i32(0x04).unwrap();
+    other.write_var_i32(1).unwrap();
+    other.write_str("fml:loginwrapper").unwrap();
+    other.reset();
+    assert!(answer_velocity_request(&mut other, SECRET, &player(Vec::new())).unwrap().is_none());
+}
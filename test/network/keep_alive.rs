@@ -0,0 +1,95 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    network::{
+        buffer::Buffer,
+        keep_alive::{KeepAlive, LatencyEstimator},
+        versions::V1_20_4,
+        ByteOrder, PacketState
+    },
+    test::mock::MockConnection
+};
+
+fn keep_alive_packet(packet_id: i32, id: i64) -> Buffer {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(packet_id).unwrap();
+    buffer.write_i64(id).unwrap();
+    buffer.reset();
+    buffer
+}
+
+fn millis(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+#[test]
+fn test_latency_estimator() {
+    let start = Instant::now();
+    let mut estimator = LatencyEstimator::new().with_smoothing(0.5).with_window(2);
+    assert_eq!(estimator.latency_at(start), None);
+
+    estimator.record(millis(100), start);
+    assert_eq!(estimator.latency_at(start), Some(millis(100)));
+    estimator.record(millis(200), start + Duration::from_secs(5));
+    assert_eq!(estimator.latency_at(start + Duration::from_secs(5)), Some(millis(150)));
+    estimator.record(millis(300), start + Duration::from_secs(10));
+    let now = start + Duration::from_secs(10);
+    assert_eq!(estimator.latency_at(now), Some(millis(225)));
+
+    assert_eq!((estimator.min_at(now), estimator.max_at(now)), (Some(millis(200)), Some(millis(300))));
+
+    let stale = now + Duration::from_secs(61);
+    assert!(estimator.is_stale_at(stale));
+    assert_eq!((estimator.latency_at(stale), estimator.min_at(stale), estimator.max_at(stale)), (None, None, None));
+    assert_eq!(estimator.latency_at(now + Duration::from_secs(60)), Some(millis(225)));
+}
+
+#[test]
+fn test_keep_alive_answers_and_reports() {
+    let mut connection = MockConnection::client();
+    let mut keep_alive = KeepAlive::new(&V1_20_4);
+
+    // Play and configuration use different ids for the same packet
+    let received = Instant::now();
+    let mut packet = keep_alive_packet(0x24, 42);
+    assert!(keep_alive.handle(&mut connection, PacketState::Play, &mut packet, received).unwrap());
+    assert_eq!(packet.position(), 0);
+    let mut configuration = keep_alive_packet(0x03, -7);
+    assert!(keep_alive.handle(&mut connection, PacketState::Configuration, &mut configuration, received).unwrap());
+    assert!(!keep_alive.handle(&mut connection, PacketState::Configuration, &mut keep_alive_packet(0x24, 1), received).unwrap());
+
+    let answers: Vec<_> = connection.packets.iter_mut().map(|packet| (packet.read_var_i32().unwrap(), packet.read_i64().unwrap())).collect();
+    assert_eq!(answers, vec![(0x15, 42), (0x03, -7)]);
+    assert_eq!(keep_alive.last_id(), Some(-7));
+    assert!(keep_alive.turnaround().latency().is_some());
+
+    assert_eq!(keep_alive.latency(), None);
+    let now = Instant::now();
+    keep_alive.record_reported(-1, now);
+    assert_eq!(keep_alive.latency(), None);
+    keep_alive.record_reported(80, now);
+    keep_alive.record_reported(160, now);
+    assert_eq!(keep_alive.latency(), Some(millis(90)));
+    assert_eq!(keep_alive.reported().max(), Some(millis(160)));
+}
+
+#[test]
+fn test_keep_alive_answers_pings() {
+    let mut connection = MockConnection::client();
+    let mut keep_alive = KeepAlive::new(&V1_20_4);
+
+    let mut ping = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    ping.write_var_i32(0x33).unwrap();
+    ping.write_i32(-12).unwrap();
+    ping.reset();
+    assert!(keep_alive.handle(&mut connection, PacketState::Play, &mut ping, Instant::now()).unwrap());
+    assert_eq!(ping.position(), 0);
+    assert_eq!(keep_alive.last_ping(), Some(-12));
+
+    let pong = &mut connection.packets[0];
+    assert_eq!((pong.read_var_i32().unwrap(), pong.read_i32().unwrap()), (0x24, -12));
+
+    let mut keep_alive = keep_alive.with_answer_pings(false);
+    assert!(!keep_alive.handle(&mut connection, PacketState::Play, &mut ping, Instant::now()).unwrap());
+    assert_eq!(connection.packets.len(), 1);
+}
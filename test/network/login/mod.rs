@@ -0,0 +1,484 @@
+pub mod replay;
+
+use std::{
+    net::TcpListener,
+    str::FromStr,
+    thread::{self, JoinHandle},
+    time::Duration
+};
+
+use uuid::Uuid;
+
+use crate::{
+    auth::{microsoft::MinecraftEdition, Session, TokenType},
+    network::{
+        buffer::Buffer,
+        connection::{pipeline::framing::FrameEncoder, socket::SocketConnection, Connection, Pipeline, Server, VersionedReadable, VersionedWritable},
+        encryption::{generate_key_pair, public_key_der, rsa_decrypt, server_hash},
+        forwarding::{read_velocity_response, velocity_login_handler, velocity_request, ForwardedPlayer, VELOCITY_CHANNEL, VELOCITY_LAZY_SESSION_VERSION},
+        login::{
+            accept_login, join_server_async_with_endpoints, join_server_async_with_login, join_server_with_endpoints, offline_uuid, ClientLogin, GameProfile, LoginAction, LoginSuccess,
+            ServerLoginConfig, VerifiedPlayer, MAX_LOGIN_PLUGIN_PAYLOAD
+        },
+        versions::{VersionInfo, V1_20_1, V1_20_2, V1_20_6},
+        ByteOrder, Error, PacketState
+    },
+    test::mock::{MockResponse, MockServer},
+    webapi::{Endpoints, ProfileResponse, Property}
+};
+
+const UUID: &str = "abe18c25-73dc-4f18-8638-adb604cb1d03";
+const TEXTURES: &str = "ewogICJ0aW1lc3RhbXAiIDogMTY3MDAwMDAwMDAwMCwKICAicHJvZmlsZUlkIiA6ICJhYmUxOGMyNTczZGM0ZjE4ODYzOGFkYjYwNGNiMWQwMyIK";
+
+fn pipeline() -> Pipeline<'static> {
+    Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing"))
+}
+
+fn session() -> Session {
+    Session {
+        username: Uuid::nil(),
+        roles: Vec::new(),
+        access_token: "access_token".to_string(),
+        token_type: TokenType::Bearer,
+        expires_in: Duration::from_secs(86400),
+        edition: MinecraftEdition::Java
+    }
+}
+
+fn profile() -> GameProfile {
+    GameProfile::new(Uuid::from_str(UUID).unwrap(), "Cach30verfl0w")
+}
+
+fn packet(id: i32) -> Buffer {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(id).unwrap();
+    buffer
+}
+
+fn fake_server(online: bool) -> (u16, JoinHandle<Option<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let handle = thread::spawn(move || {
+        let mut connection = SocketConnection::<Server>::new(listener.accept().unwrap().0, pipeline());
+
+        let mut handshake = connection.read_packet().unwrap();
+        assert_eq!(handshake.read_var_i32().unwrap(), 0x00);
+        assert_eq!(handshake.read_var_i32().unwrap(), V1_20_1.protocol);
+        assert_eq!(handshake.read_string().unwrap(), "127.0.0.1");
+        assert_eq!(handshake.read_u16().unwrap(), port);
+        assert_eq!(handshake.read_var_i32().unwrap(), 2);
+        connection.set_state(PacketState::Login);
+
+        let mut login_start = connection.read_packet().unwrap();
+        assert_eq!(login_start.read_var_i32().unwrap(), 0x00);
+        assert_eq!(login_start.read_string().unwrap(), "Cach30verfl0w");
+        assert!(login_start.read_bool().unwrap());
+        assert_eq!(Uuid::from_u64_pair(login_start.read_u64().unwrap(), login_start.read_u64().unwrap()).to_string(), UUID);
+
+        let mut hash = None;
+        if online {
+            let private_key = generate_key_pair(1024).unwrap();
+            let public_key = public_key_der(&private_key).unwrap();
+
+            let mut encryption_request = packet(0x01);
+            encryption_request.write_str("").unwrap();
+            encryption_request.write_byte_array(&public_key).unwrap();
+            encryption_request.write_byte_array(&[1, 2, 3, 4]).unwrap();
+            connection.write(encryption_request).unwrap();
+
+            let mut encryption_response = connection.read_packet().unwrap();
+            assert_eq!(encryption_response.read_var_i32().unwrap(), 0x01);
+            let shared_secret = rsa_decrypt(&private_key, &encryption_response.read_byte_array().unwrap()).unwrap();
+            let verify_token = rsa_decrypt(&private_key, &encryption_response.read_byte_array().unwrap()).unwrap();
+            assert_eq!(verify_token, vec![1, 2, 3, 4]);
+
+            connection.enable_encryption(&shared_secret.clone().try_into().unwrap());
+            hash = Some(server_hash("", &shared_secret, &public_key));
+        }
+
+        let mut set_compression = packet(0x03);
+        set_compression.write_var_i32(64).unwrap();
+        connection.write(set_compression).unwrap();
+        connection.enable_compression(64);
+
+        let mut login_success = packet(0x02);
+        let bits = Uuid::from_str(UUID).unwrap().as_u64_pair();
+        login_success.write_u64(bits.0).unwrap();
+        login_success.write_u64(bits.1).unwrap();
+        login_success.write_str("Cach30verfl0w").unwrap();
+        login_success.write_var_i32(1).unwrap();
+        login_success.write_str("textures").unwrap();
+        login_success.write_str(TEXTURES).unwrap();
+        login_success.write_bool(true).unwrap();
+        login_success.write_str("signature").unwrap();
+        connection.write(login_success).unwrap();
+        hash
+    });
+    (port, handle)
+}
+
+fn session_server() -> MockServer {
+    MockServer::builder().post("/session/minecraft/join", MockResponse::new(204, "")).start()
+}
+
+fn assert_join_request(server: &MockServer, hash: Option<String>) {
+    let requests = server.requests();
+    match hash {
+        Some(hash) => {
+            assert_eq!(requests.len(), 1);
+            assert_eq!(requests[0].method, "POST");
+            assert_eq!(requests[0].path, "/session/minecraft/join");
+            let body: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+            assert_eq!(body["accessToken"], "access_token");
+            assert_eq!(body["selectedProfile"], UUID.replace('-', ""));
+            assert_eq!(body["serverId"], hash);
+        }
+        None => assert!(requests.is_empty())
+    }
+}
+
+async fn blocking_login(online: bool) {
+    let session_server = session_server();
+    let endpoints = Endpoints::new(session_server.url(), session_server.url(), session_server.url());
+    let (port, server) = fake_server(online);
+
+    let (connection, login_success) = tokio::task::spawn_blocking(move || join_server_with_endpoints(&endpoints, "127.0.0.1", port, &session(), &profile(), V1_20_1, pipeline).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(connection.state(), PacketState::Play);
+    assert_eq!(login_success.uuid.to_string(), UUID);
+    assert_eq!(login_success.username, "Cach30verfl0w");
+    assert_eq!(login_success.properties.len(), 1);
+    assert_eq!(login_success.properties[0].value, TEXTURES);
+    assert_eq!(login_success.properties[0].signature.as_deref(), Some("signature"));
+    assert_join_request(&session_server, server.join().unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_join_server_online() {
+    blocking_login(true).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_join_server_offline() {
+    blocking_login(false).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_join_server_async_online() {
+    let session_server = session_server();
+    let endpoints = Endpoints::new(session_server.url(), session_server.url(), session_server.url());
+    let (port, server) = fake_server(true);
+
+    let (connection, login_success) = join_server_async_with_endpoints(&endpoints, "127.0.0.1", port, &session(), &profile(), V1_20_1, pipeline)
+        .await
+        .unwrap();
+
+    assert_eq!(connection.state(), PacketState::Play);
+    assert_eq!(login_success.username, "Cach30verfl0w");
+    assert_join_request(&session_server, server.join().unwrap());
+}
+
+fn accepting_server(config: ServerLoginConfig) -> (u16, JoinHandle<Result<VerifiedPlayer, Error>>) {
+    accepting_server_for(config, V1_20_1)
+}
+
+fn accepting_server_for(config: ServerLoginConfig, version: VersionInfo) -> (u16, JoinHandle<Result<VerifiedPlayer, Error>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let handle = thread::spawn(move || {
+        let mut connection = SocketConnection::<Server>::new(listener.accept().unwrap().0, pipeline());
+        let player = accept_login(&mut connection, &config);
+        if player.is_ok() {
+            assert_eq!(connection.state(), PacketState::Play);
+            assert_eq!(connection.protocol_version(), Some(version.protocol));
+        }
+        player
+    });
+    (port, handle)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_accept_login_online() {
+    let session_server = MockServer::builder()
+        .post("/session/minecraft/join", MockResponse::new(204, ""))
+        .get("/session/minecraft/hasJoined", MockResponse::ok(include_str!("../../fixtures/webapi/profile.json")))
+        .start();
+    let endpoints = Endpoints::new(session_server.url(), session_server.url(), session_server.url());
+    let config = ServerLoginConfig::new(true).unwrap().endpoints(endpoints.clone()).compression_threshold(256);
+    let (port, server) = accepting_server(config);
+
+    let (connection, login_success) = tokio::task::spawn_blocking(move || join_server_with_endpoints(&endpoints, "127.0.0.1", port, &session(), &profile(), V1_20_1, pipeline).unwrap())
+        .await
+        .unwrap();
+    let player = server.join().unwrap().unwrap();
+
+    assert_eq!(connection.state(), PacketState::Play);
+    assert_eq!(connection.protocol_version(), Some(V1_20_1.protocol));
+    assert!(connection.pipeline().has_decoder("decompression"));
+    assert_eq!(player.uuid.to_string(), UUID);
+    assert_eq!(player.name, "Cach30verfl0w");
+    assert_eq!(player.properties.len(), 1);
+    assert_eq!(login_success.uuid, player.uuid);
+    assert_eq!(login_success.properties[0].value, player.properties[0].value);
+
+    let requests = session_server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].method, "GET");
+    assert_eq!(requests[1].path, "/session/minecraft/hasJoined");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_accept_login_unverified() {
+    let session_server = MockServer::builder()
+        .post("/session/minecraft/join", MockResponse::new(204, ""))
+        .get("/session/minecraft/hasJoined", MockResponse::new(204, ""))
+        .start();
+    let endpoints = Endpoints::new(session_server.url(), session_server.url(), session_server.url());
+    let (port, server) = accepting_server(ServerLoginConfig::new(true).unwrap().endpoints(endpoints.clone()));
+
+    let result = tokio::task::spawn_blocking(move || join_server_with_endpoints(&endpoints, "127.0.0.1", port, &session(), &profile(), V1_20_1, pipeline))
+        .await
+        .unwrap();
+    assert!(matches!(result, Err(Error::Disconnected(_))));
+    assert!(matches!(server.join().unwrap(), Err(Error::Disconnected(_))));
+}
+
+#[test]
+fn test_accept_login_offline() {
+    let (port, server) = accepting_server(ServerLoginConfig::new(false).unwrap());
+
+    let (_, login_success) = join_server_with_endpoints(&Endpoints::default(), "127.0.0.1", port, &session(), &profile(), V1_20_1, pipeline).unwrap();
+    let player = server.join().unwrap().unwrap();
+
+    assert_eq!(player.name, "Cach30verfl0w");
+    assert_eq!(player.uuid, offline_uuid("Cach30verfl0w"));
+    assert!(player.properties.is_empty());
+    assert_eq!(login_success.uuid, player.uuid);
+}
+
+#[test]
+fn test_accept_login_veto() {
+    let config = ServerLoginConfig::new(false)
+        .unwrap()
+        .veto(|player| (player.name == "Cach30verfl0w").then(|| "You are banned".to_string()));
+    let (port, server) = accepting_server(config);
+
+    match join_server_with_endpoints(&Endpoints::default(), "127.0.0.1", port, &session(), &profile(), V1_20_1, pipeline) {
+        Err(Error::Disconnected(reason)) => assert_eq!(reason, r#"{"text":"You are banned"}"#),
+        result => panic!("Expected disconnect, got {:?}", result.map(|(_, login_success)| login_success))
+    }
+    assert!(matches!(server.join().unwrap(), Err(Error::Rejected(_))));
+}
+
+#[test]
+fn test_accept_login_configuration() {
+    for version in [V1_20_2, V1_20_6] {
+        let (port, server) = accepting_server_for(ServerLoginConfig::new(false).unwrap().compression_threshold(256), version);
+
+        let (connection, login_success) = join_server_with_endpoints(&Endpoints::default(), "127.0.0.1", port, &session(), &profile(), version, pipeline).unwrap();
+        let player = server.join().unwrap().unwrap();
+        assert_eq!(connection.state(), PacketState::Play);
+        assert_eq!(player.name, "Cach30verfl0w");
+        assert_eq!(login_success.uuid, offline_uuid("Cach30verfl0w"));
+    }
+}
+
+#[test]
+fn test_offline_uuid() {
+    assert_eq!(offline_uuid("Notch").to_string(), "b50ad385-829d-3141-a216-7e7d7539ba7f");
+}
+
+#[test]
+fn test_login_success_round_trip() {
+    let login_success = LoginSuccess {
+        uuid: Uuid::from_str(UUID).unwrap(),
+        username: "Cach30verfl0w".to_string(),
+        properties: vec![
+            Property {
+                name: "textures".to_string(),
+                value: TEXTURES.to_string(),
+                signature: Some("signature".to_string())
+            },
+            Property {
+                name: "unsigned".to_string(),
+                value: "value".to_string(),
+                signature: None
+            },
+        ]
+    };
+
+    for protocol in [763, 766, 768] {
+        let mut buffer = login_success.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), protocol).unwrap();
+        buffer.reset();
+        assert_eq!(LoginSuccess::decode_versioned(&mut buffer, protocol).unwrap(), login_success);
+    }
+
+    // 1.18.2 has neither the properties nor the strict error handling flag of 1.20.5
+    let legacy = login_success.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), 758).unwrap().to_bytes();
+    let mut buffer = Buffer::new(legacy.clone(), false, Some(ByteOrder::BigEndian));
+    let read = LoginSuccess::decode_versioned(&mut buffer, 758).unwrap();
+    assert_eq!((read.uuid, read.username.as_str(), read.properties.len()), (login_success.uuid, "Cach30verfl0w", 0));
+
+    let strict = login_success.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), 766).unwrap().to_bytes();
+    let current = login_success.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), 763).unwrap().to_bytes();
+    assert_eq!((legacy.len(), strict.len()), (1 + 16 + 14, current.len() + 1));
+    assert!(LoginSuccess::decode_versioned(&mut Buffer::new(strict, false, Some(ByteOrder::BigEndian)), 763).is_err());
+}
+
+#[test]
+fn test_login_success_from_profile() {
+    let profile = ProfileResponse {
+        id: UUID.replace('-', ""),
+        name: "Cach30verfl0w".to_string(),
+        properties: vec![Property {
+            name: "textures".to_string(),
+            value: TEXTURES.to_string(),
+            signature: Some("signature".to_string())
+        }]
+    };
+
+    let login_success = LoginSuccess::try_from(profile.clone()).unwrap();
+    assert_eq!(login_success.uuid, Uuid::from_str(UUID).unwrap());
+    assert_eq!(login_success.properties, profile.properties);
+
+    let converted = ProfileResponse::from(login_success);
+    assert_eq!((converted.id, converted.name, converted.properties), (profile.id, profile.name, profile.properties));
+
+    let invalid = ProfileResponse {
+        id: "invalid".to_string(),
+        name: "Cach30verfl0w".to_string(),
+        properties: Vec::new()
+    };
+    assert!(LoginSuccess::try_from(invalid.clone()).is_err());
+    assert!(VerifiedPlayer::try_from(invalid).is_err());
+}
+
+#[test]
+fn test_login_success_username_too_long() {
+    let mut buffer = packet(0x02);
+    buffer.write_u64(0).unwrap();
+    buffer.write_u64(0).unwrap();
+    buffer.write_str("ThisNameIsWayTooLong").unwrap();
+    buffer.write_var_i32(0).unwrap();
+    buffer.reset();
+    assert!(LoginSuccess::read_versioned(&mut buffer, 763).is_err());
+}
+
+#[test]
+fn test_login_success_truncated() {
+    let login_success = LoginSuccess {
+        uuid: Uuid::from_str(UUID).unwrap(),
+        username: "Cach30verfl0w".to_string(),
+        properties: Vec::new()
+    };
+    let bytes = login_success.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), 763).unwrap().to_bytes();
+
+    let mut buffer = Buffer::new(bytes[..24].to_vec(), false, Some(ByteOrder::BigEndian));
+    match LoginSuccess::decode_versioned(&mut buffer, 763) {
+        Err(Error::Decode { offset, .. }) => assert_eq!(offset, 18),
+        result => panic!("Expected decode error, got {:?}", result)
+    }
+
+    let mut bytes = bytes;
+    bytes.push(0x00);
+    let mut buffer = Buffer::new(bytes, false, Some(ByteOrder::BigEndian));
+    assert!(matches!(LoginSuccess::decode_versioned(&mut buffer, 763), Err(Error::Decode { offset: 32, .. })));
+}
+
+const FORWARDING_SECRET: &[u8] = b"forwarding-secret";
+
+fn plugin_request(message_id: i32, channel: &str, data: &[u8]) -> Buffer {
+    let mut request = packet(0x04);
+    request.write_var_i32(message_id).unwrap();
+    request.write_str(channel).unwrap();
+    request.write_bytes(data).unwrap();
+    request
+}
+
+fn read_plugin_response(connection: &mut SocketConnection<'_, Server>, message_id: i32) -> Option<Vec<u8>> {
+    let mut response = connection.read_packet().unwrap();
+    assert_eq!(response.read_var_i32().unwrap(), 0x02);
+    assert_eq!(response.read_var_i32().unwrap(), message_id);
+    match response.read_bool().unwrap() {
+        true => Some(response.read_bytes(response.remaining()).unwrap()),
+        false => None
+    }
+}
+
+fn plugin_server() -> (u16, JoinHandle<ForwardedPlayer>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let handle = thread::spawn(move || {
+        let mut connection = SocketConnection::<Server>::new(listener.accept().unwrap().0, pipeline());
+        connection.read_packet().unwrap();
+        connection.set_state(PacketState::Login);
+        connection.read_packet().unwrap();
+
+        connection.write(plugin_request(7, "fml:loginwrapper", b"\x0dfml:handshake\x01\x02")).unwrap();
+        assert_eq!(read_plugin_response(&mut connection, 7), None);
+
+        let large = (0..MAX_LOGIN_PLUGIN_PAYLOAD).map(|index| index as u8).collect::<Vec<_>>();
+        connection.write(plugin_request(8, "example:echo", &large)).unwrap();
+        assert_eq!(read_plugin_response(&mut connection, 8), Some((MAX_LOGIN_PLUGIN_PAYLOAD as u32).to_be_bytes().to_vec()));
+
+        connection.write(velocity_request(9, VELOCITY_LAZY_SESSION_VERSION).unwrap()).unwrap();
+        let mut response = connection.read_packet().unwrap();
+        let (version, player) = read_velocity_response(&mut response, 9, FORWARDING_SECRET).unwrap();
+        assert_eq!(version, VELOCITY_LAZY_SESSION_VERSION);
+
+        let login_success = LoginSuccess {
+            uuid: Uuid::from_str(UUID).unwrap(),
+            username: "Cach30verfl0w".to_string(),
+            properties: Vec::new()
+        };
+        connection
+            .write(login_success.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), V1_20_1.protocol).unwrap())
+            .unwrap();
+        player
+    });
+    (port, handle)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_join_server_login_plugin_requests() {
+    let (port, server) = plugin_server();
+    let profile = profile();
+    let player = ForwardedPlayer::new("127.0.0.1".parse().unwrap(), profile.clone(), Vec::new());
+    let login = ClientLogin::new(&profile, &V1_20_1)
+        .unwrap()
+        .register(VELOCITY_CHANNEL, velocity_login_handler(FORWARDING_SECRET.to_vec(), player.clone()))
+        .unwrap()
+        .register("example:echo", |data| Ok(Some((data.len() as u32).to_be_bytes().to_vec())))
+        .unwrap();
+
+    let (connection, login_success) = join_server_async_with_login(&Endpoints::default(), login, "127.0.0.1", port, &session(), pipeline).await.unwrap();
+    assert_eq!(connection.state(), PacketState::Play);
+    assert_eq!(login_success.username, "Cach30verfl0w");
+    assert_eq!(server.join().unwrap(), player);
+}
+
+#[test]
+fn test_login_plugin_request_limits() {
+    let profile = profile();
+    let mut login = ClientLogin::new(&profile, &V1_20_1).unwrap().register("example:echo", |data| Ok(Some(data.repeat(2)))).unwrap();
+
+    let mut request = plugin_request(3, "example:echo", &vec![0; MAX_LOGIN_PLUGIN_PAYLOAD + 1]);
+    request.reset();
+    assert!(matches!(login.handle(request), Err(Error::Decode { offset: 2, .. })));
+
+    let mut request = plugin_request(4, "example:echo", &vec![0; MAX_LOGIN_PLUGIN_PAYLOAD / 2 + 1]);
+    request.reset();
+    assert!(matches!(login.handle(request), Err(Error::Other(_))));
+
+    let mut request = plugin_request(5, "example:echo", &[1, 2]);
+    request.reset();
+    match login.handle(request).unwrap().as_slice() {
+        [LoginAction::Send(response)] => assert_eq!(response.clone().to_bytes(), vec![0x02, 0x05, 0x01, 1, 2, 1, 2]),
+        actions => panic!("Expected a single response, got {:?}", actions)
+    }
+}
@@ -0,0 +1,197 @@
+use std::sync::{Arc, Mutex};
+
+use uuid::Uuid;
+
+use super::{pipeline, profile, session, session_server};
+use crate::{
+    network::{
+        connection::{async_socket::AsyncSocketConnection, socket::SocketConnection, Client, Connection, Server},
+        login::{accept_login, join_server_async_with_endpoints, join_server_async_with_login, offline_uuid, ClientLogin, GameProfile, LoginSuccess, ServerLoginConfig},
+        proxy::{relay, RelayAction},
+        resource_pack::{ResourcePackAction, ResourcePackPolicy},
+        versions::VersionInfo,
+        Error, PacketState
+    },
+    test::{
+        mock::MockServer,
+        transcript::{byte_diff, Capture, ScriptedServer, Transcript}
+    },
+    webapi::Endpoints
+};
+
+const OFFLINE: &str = include_str!("../../fixtures/network/login/offline_1_20_1.transcript");
+const ONLINE: &str = include_str!("../../fixtures/network/login/online_1_20_1.transcript");
+const CONFIGURATION: &str = include_str!("../../fixtures/network/login/configuration_1_20_2.transcript");
+const FORCED_PACK_ACCEPT: &str = include_str!("../../fixtures/network/login/forced_pack_accept_1_20_4.transcript");
+const FORCED_PACK_DECLINE: &str = include_str!("../../fixtures/network/login/forced_pack_decline_1_20_4.transcript");
+
+async fn replay(fixture: &str, profile: &GameProfile) -> (MockServer, Result<LoginSuccess, String>) {
+    let transcript = Transcript::parse(fixture).unwrap();
+    let version = VersionInfo::from_protocol(transcript.protocol).unwrap();
+    let session_server = session_server();
+    let endpoints = Endpoints::new(session_server.url(), session_server.url(), session_server.url());
+
+    let server = ScriptedServer::start(transcript);
+    let joined = join_server_async_with_endpoints(&endpoints, "127.0.0.1", server.port(), &session(), profile, version, pipeline).await;
+    if let Err(diff) = tokio::task::spawn_blocking(move || server.finish()).await.unwrap() {
+        return (session_server, Err(diff))
+    }
+
+    let (connection, login_success) = joined.unwrap();
+    assert_eq!(connection.state(), PacketState::Play);
+    (session_server, Ok(login_success))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_replay_offline_login() {
+    let (session_server, login_success) = replay(OFFLINE, &profile()).await;
+    let login_success = login_success.unwrap();
+    assert_eq!((login_success.uuid, login_success.username.as_str()), (profile().id, "Cach30verfl0w"));
+    assert!(login_success.properties.is_empty());
+    assert!(session_server.requests().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_replay_online_login() {
+    let (session_server, login_success) = replay(ONLINE, &profile()).await;
+    let login_success = login_success.unwrap();
+    assert_eq!(login_success.properties[0].signature.as_deref(), Some("signature"));
+    assert_eq!(session_server.requests().len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_replay_configuration_login() {
+    let (session_server, login_success) = replay(CONFIGURATION, &profile()).await;
+    assert_eq!(login_success.unwrap().username, "Cach30verfl0w");
+    assert!(session_server.requests().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_replay_reports_deviation() {
+    let profile = GameProfile::new(profile().id, "Cach30verfl0x");
+    let diff = replay(OFFLINE, &profile).await.1.unwrap_err();
+    assert_eq!(
+        diff,
+        concat!(
+            "Serverbound packet on line 5 differs at byte 14 (expected 32 bytes, got 32)\n",
+            "expected: ... 33 30 76 65 72 66 6c 30 77 01 ab e1 8c 25 73 dc ...\n",
+            "actual:   ... 33 30 76 65 72 66 6c 30 78 01 ab e1 8c 25 73 dc ...\n",
+            "                                      ^^"
+        )
+    );
+}
+
+async fn replay_with_policy(fixture: &str, policy: ResourcePackPolicy) -> Result<LoginSuccess, Error> {
+    let transcript = Transcript::parse(fixture).unwrap();
+    let version = VersionInfo::from_protocol(transcript.protocol).unwrap();
+    let profile = profile();
+    let login = ClientLogin::new(&profile, version).unwrap().resource_pack_policy(policy);
+
+    let server = ScriptedServer::start(transcript);
+    let joined = join_server_async_with_login(&Endpoints::default(), login, "127.0.0.1", server.port(), &session(), pipeline).await;
+    tokio::task::spawn_blocking(move || server.finish()).await.unwrap().unwrap();
+    joined.map(|(_, login_success)| login_success)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_replay_forced_pack_accepted() {
+    assert_eq!(
+        replay_with_policy(FORCED_PACK_ACCEPT, ResourcePackPolicy::AcceptAndSucceed).await.unwrap().username,
+        "Cach30verfl0w"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_replay_forced_pack_declined() {
+    match replay_with_policy(FORCED_PACK_DECLINE, ResourcePackPolicy::Decline).await {
+        Err(Error::Disconnected(reason)) => {
+            assert_eq!(
+                reason,
+                r#"{"text":"You need the resource pack to play here"} (after the forced resource pack https://packs.example.com/server.zip wasn't loaded)"#
+            )
+        }
+        other => panic!("Expected a disconnect, got {:?}", other.map(|login_success| login_success.username))
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_replay_forced_pack_prompt() {
+    let prompted = Arc::new(Mutex::new(Vec::new()));
+    let recorder = prompted.clone();
+    let policy = ResourcePackPolicy::prompt(move |request| {
+        recorder.lock().unwrap().push((request.id, request.url.clone(), request.forced));
+        ResourcePackAction::Accept
+    });
+    replay_with_policy(FORCED_PACK_ACCEPT, policy).await.unwrap();
+    assert_eq!(
+        *prompted.lock().unwrap(),
+        vec![(
+            Some(Uuid::parse_str("3e5fb0b1a7c54c1e8c0f3d8d0a6b4f21").unwrap()),
+            "https://packs.example.com/server.zip".to_string(),
+            true
+        )]
+    );
+
+    let declined = ResourcePackPolicy::prompt(|_| ResourcePackAction::Decline);
+    assert!(matches!(replay_with_policy(FORCED_PACK_DECLINE, declined).await, Err(Error::Disconnected(_))));
+}
+
+#[test]
+fn test_byte_diff() {
+    assert_eq!(byte_diff(&[1, 2, 3], &[1, 2, 3]), None);
+    assert_eq!(
+        byte_diff(&[1, 2], &[1, 2, 3]).unwrap(),
+        "differs at byte 2 (expected 2 bytes, got 3)\nexpected: 01 02\nactual:   01 02 03\n                ^^"
+    );
+}
+
+#[test]
+fn test_transcript_parse_errors() {
+    assert_eq!(Transcript::parse("> 00").unwrap_err(), "The transcript names no protocol");
+    assert_eq!(
+        Transcript::parse("protocol 763\n< 00 {port}").unwrap_err(),
+        "Line 2: Clientbound packets can only use the {public_key} placeholder"
+    );
+    assert_eq!(Transcript::parse("protocol 763\n> * 00").unwrap_err(), "Line 2: * can only end a packet");
+    assert_eq!(Transcript::parse("protocol 763\n> 0").unwrap_err(), "Line 2: Odd number of hex digits in 0");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_capture_offline_login() {
+    let upstream = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let upstream_port = upstream.local_addr().unwrap().port();
+    let server = std::thread::spawn(move || {
+        let mut connection = SocketConnection::<Server>::new(upstream.accept().unwrap().0, pipeline());
+        accept_login(&mut connection, &ServerLoginConfig::new(false).unwrap().compression_threshold(256)).unwrap()
+    });
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let capture = Arc::new(Mutex::new(Capture::new()));
+    let recorder = capture.clone();
+    let proxy = tokio::spawn(async move {
+        let client = AsyncSocketConnection::<Server>::new(listener.accept().await?.0, pipeline());
+        let server = AsyncSocketConnection::<Client>::new(tokio::net::TcpStream::connect(("127.0.0.1", upstream_port)).await?, pipeline());
+        relay(client, server, |direction, state, packet| {
+            recorder.lock().unwrap().record(direction, state, packet);
+            RelayAction::Forward
+        })
+        .await
+    });
+
+    let player = GameProfile::new(offline_uuid("Notch"), "Notch");
+    let version = VersionInfo::from_protocol(763).unwrap();
+    let (connection, _) = join_server_async_with_endpoints(&Endpoints::default(), "127.0.0.1", port, &session(), &player, version, pipeline)
+        .await
+        .unwrap();
+    tokio::task::spawn_blocking(move || server.join().unwrap()).await.unwrap();
+    drop(connection);
+    proxy.await.unwrap().unwrap();
+
+    let capture = std::mem::take(&mut *capture.lock().unwrap());
+    let sanitized = capture
+        .replace_string("Notch", "Cach30verfl0w")
+        .replace(Uuid::as_bytes(&player.id), "abe18c2573dc4f188638adb604cb1d03")
+        .render();
+    assert_eq!(Transcript::parse(&sanitized).unwrap().steps, Transcript::parse(OFFLINE).unwrap().steps);
+}
@@ -0,0 +1,110 @@
+use crate::{
+    components::Component,
+    network::{
+        buffer::Buffer,
+        map::{MapCanvas, MapData, MapIcon, MapPalette, MapPatch, MAP_WIDTH},
+        ByteOrder
+    }
+};
+
+fn round_trip(data: &MapData, protocol: i32) -> MapData {
+    let mut buffer = data.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), protocol).unwrap();
+    buffer.reset();
+    let read = MapData::read_versioned(&mut buffer, protocol).unwrap();
+    assert_eq!(buffer.remaining(), 0);
+    read
+}
+
+#[test]
+fn test_map_data_round_trip() {
+    let icon = MapIcon {
+        kind: 0,
+        x: -12,
+        z: 127,
+        direction: 15,
+        display_name: Some(Component::text("Spawn"))
+    };
+    let data = MapData::new(3, 2).with_locked(true).with_icons(vec![icon]).with_patch(MapPatch::new(2, 1, 4, 5, vec![6, 7]));
+
+    for protocol in [754, 763, 765, 767] {
+        assert_eq!(round_trip(&data, protocol), data);
+    }
+
+    let data = MapData::new(3, 0);
+    assert_eq!(round_trip(&data, 767), data);
+    assert_eq!(round_trip(&data, 754).icons, Some(Vec::new()));
+    let tracked = MapData {
+        tracking_position: true,
+        ..data.clone().with_icons(Vec::new())
+    };
+    assert_eq!(round_trip(&tracked, 754), tracked);
+    assert!(!round_trip(&tracked, 767).tracking_position);
+}
+
+#[test]
+fn test_map_data_read() {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(42).unwrap();
+    buffer.write_u8(1).unwrap();
+    buffer.write_bool(false).unwrap();
+    buffer.write_bool(false).unwrap();
+    buffer.write_u8(1).unwrap();
+    buffer.write_u8(2).unwrap();
+    buffer.write_u8(64).unwrap();
+    buffer.write_u8(32).unwrap();
+    buffer.write_byte_array(&[18, 19]).unwrap();
+    buffer.reset();
+
+    let data = MapData::read_versioned(&mut buffer, 763).unwrap();
+    assert_eq!((data.map_id, data.scale, data.locked, data.icons), (42, 1, false, None));
+    assert_eq!(data.patch, Some(MapPatch::new(1, 2, 64, 32, vec![18, 19])));
+}
+
+#[test]
+fn test_map_canvas_patches() {
+    let palette = MapPalette::vanilla();
+    let mut canvas = MapCanvas::new();
+
+    canvas.apply_patch(&MapPatch::new(2, 2, 126, 127, vec![6, 6, 6, 6]));
+    assert_eq!(canvas.pixel(126, 127), Some(6));
+    assert_eq!(canvas.pixel(127, 127), Some(6));
+    assert_eq!(canvas.pixel(126, 126), Some(0));
+    assert_eq!(canvas.pixel(128, 127), None);
+    assert!(!canvas.is_complete());
+
+    canvas.apply_patch(&MapPatch::new(4, 4, 0, 0, vec![5, 5]));
+    assert_eq!(canvas.pixel(1, 0), Some(5));
+    assert_eq!(canvas.pixel(2, 0), Some(0));
+
+    canvas.apply(&MapData::new(1, 0).with_patch(MapPatch::full(vec![51; MAP_WIDTH * MAP_WIDTH])));
+    assert!(canvas.is_complete());
+    assert!(canvas.colors().iter().all(|color| *color == 51));
+
+    canvas.apply(&MapData::new(1, 0).with_locked(true).with_patch(MapPatch::new(1, 3, 10, 20, vec![6, 5, 0])));
+    assert!(canvas.is_locked());
+    assert_eq!(
+        (canvas.pixel(10, 20), canvas.pixel(10, 21), canvas.pixel(10, 22), canvas.pixel(11, 20)),
+        (Some(6), Some(5), Some(0), Some(51))
+    );
+
+    let rgba = canvas.to_rgba(&palette);
+    assert_eq!(rgba.len(), MAP_WIDTH * MAP_WIDTH * 4);
+    let pixel = |x: usize, z: usize| &rgba[(z * MAP_WIDTH + x) * 4..(z * MAP_WIDTH + x) * 4 + 4];
+    // Grass at full brightness, grass at 220 / 255, nothing and water at 135 / 255
+    assert_eq!(pixel(10, 20), [127, 178, 56, 255]);
+    assert_eq!(pixel(10, 21), [109, 153, 48, 255]);
+    assert_eq!(pixel(10, 22), [0, 0, 0, 0]);
+    assert_eq!(pixel(0, 0), [33, 33, 135, 255]);
+}
+
+#[test]
+fn test_map_palette() {
+    let palette = MapPalette::vanilla();
+    assert_eq!(palette.base_colors().len(), 62);
+    assert_eq!(palette.rgba(3), [0, 0, 0, 0]);
+    // Snow at 180 / 255 and the last shade of glow lichen
+    assert_eq!(palette.rgba(32), [180, 180, 180, 255]);
+    assert_eq!(palette.rgba(247), [67, 88, 79, 255]);
+    assert_eq!(palette.rgba(248), [0, 0, 0, 0]);
+    assert_eq!(palette.rgba(255), [0, 0, 0, 0]);
+}
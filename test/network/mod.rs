@@ -0,0 +1,31 @@
+pub mod buffer;
+pub mod bundle;
+pub mod chat;
+pub mod chunk;
+pub mod commands;
+pub mod connection;
+pub mod convenience;
+pub mod cookie;
+pub mod correlation;
+pub mod decode;
+pub mod encryption;
+pub mod forge;
+#[cfg(all(feature = "auth", feature = "webapi", feature = "std-net"))]
+pub mod forwarding;
+pub mod keep_alive;
+#[cfg(all(feature = "auth", feature = "webapi", feature = "std-net"))]
+pub mod login;
+pub mod map;
+pub mod nbt;
+pub mod plugin;
+pub mod proxy;
+pub mod registry_codec;
+pub mod resource_pack;
+pub mod session;
+pub mod snbt;
+pub mod statistics;
+pub mod status;
+pub mod tags;
+pub mod ticker;
+pub mod tracker;
+pub mod types;
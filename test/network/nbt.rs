@@ -0,0 +1,64 @@
+use crate::network::{
+    buffer::Buffer,
+    connection::{Readable, Writable},
+    nbt::{Compound, Tag, MAXIMUM_DEPTH},
+    ByteOrder, Error
+};
+
+#[test]
+fn test_compound_round_trip() {
+    let compound = Compound::new()
+        .with("byte", Tag::Byte(-1))
+        .with("short", Tag::Short(300))
+        .with("int", Tag::Int(-64))
+        .with("long", Tag::Long(18000))
+        .with("float", Tag::Float(0.1))
+        .with("double", Tag::Double(8.0))
+        .with("bytes", Tag::ByteArray(vec![1, 2, 3]))
+        .with("string", Tag::String("minecraft:overworld \u{0}".to_string()))
+        .with("list", Tag::List(vec![Tag::String("sender".to_string()), Tag::String("content".to_string())]))
+        .with("empty", Tag::List(Vec::new()))
+        .with("nested", Tag::Compound(Compound::new().with("value", Tag::Int(7))))
+        .with("ints", Tag::IntArray(vec![1, -1]))
+        .with("longs", Tag::LongArray(vec![i64::MAX]));
+
+    let mut buffer = compound.write(Buffer::empty(true, Some(ByteOrder::BigEndian))).unwrap();
+    let bytes = buffer.to_bytes();
+    assert_eq!(&bytes[..5], &[0x0A, 0x01, 0x00, 0x04, b'b']);
+
+    buffer.reset();
+    let read = Compound::decode(&mut buffer).unwrap();
+    assert_eq!(read, compound);
+    assert_eq!(read.get("nested").and_then(Tag::as_compound).and_then(|nested| nested.get("value")), Some(&Tag::Int(7)));
+    assert_eq!(read.get("byte").and_then(Tag::as_i32), Some(-1));
+}
+
+#[test]
+fn test_named_root() {
+    let compound = Compound::new().with("height", Tag::Int(384));
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    compound.write_named("", &mut buffer).unwrap();
+    assert_eq!(buffer.to_bytes()[..3], [0x0A, 0x00, 0x00]);
+
+    buffer.reset();
+    assert_eq!(Compound::read_named(&mut buffer).unwrap(), (String::new(), compound));
+}
+
+#[test]
+fn test_mixed_list() {
+    let compound = Compound::new().with("list", Tag::List(vec![Tag::Int(1), Tag::Byte(1)]));
+    assert!(compound.write(Buffer::empty(true, Some(ByteOrder::BigEndian))).is_err());
+}
+
+#[test]
+fn test_nesting_limit() {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_u8(0x0A).unwrap();
+    for _ in 0..MAXIMUM_DEPTH + 2 {
+        buffer.write_u8(0x0A).unwrap();
+        buffer.write_java_utf("a").unwrap();
+    }
+    buffer.reset();
+
+    assert!(matches!(Compound::read(&mut buffer), Err(Error::Decode { .. })));
+}
@@ -0,0 +1,53 @@
+use crate::network::{
+    buffer::Buffer,
+    connection::{Readable, Writable},
+    plugin::{BrandMessage, BungeeCordMessage, PluginChannels, PluginMessage, PluginPayload, BRAND_CHANNEL, BUNGEECORD_CHANNEL},
+    types::{Identifier, RemainingBytes},
+    ByteOrder
+};
+
+fn read_back(message: &PluginMessage) -> PluginMessage {
+    let mut buffer = message.write(Buffer::empty(true, Some(ByteOrder::BigEndian))).unwrap();
+    buffer.reset();
+    PluginMessage::read(&mut buffer).unwrap()
+}
+
+#[test]
+fn test_brand_round_trip() {
+    let message = read_back(&PluginMessage::encode(BRAND_CHANNEL, &BrandMessage("vanilla".to_string())).unwrap());
+    assert_eq!(message.channel, Identifier::new("minecraft", "brand").unwrap());
+    assert_eq!(message.data, b"\x07vanilla");
+
+    let payload = PluginChannels::default().decode(&message).unwrap();
+    assert_eq!(payload.downcast::<BrandMessage>(), Some(BrandMessage("vanilla".to_string())));
+}
+
+#[test]
+fn test_bungeecord_connect_round_trip() {
+    let message = read_back(&PluginMessage::encode(BUNGEECORD_CHANNEL, &BungeeCordMessage::Connect("lobby".to_string())).unwrap());
+    assert_eq!(message.data, b"\x00\x07Connect\x00\x05lobby");
+    assert_eq!(message.decode::<BungeeCordMessage>().unwrap(), BungeeCordMessage::Connect("lobby".to_string()));
+
+    let message = PluginMessage::encode(BUNGEECORD_CHANNEL, &BungeeCordMessage::GetServer(None)).unwrap();
+    assert_eq!(message.decode::<BungeeCordMessage>().unwrap(), BungeeCordMessage::GetServer(None));
+}
+
+#[test]
+fn test_unregistered_channel() {
+    let message = PluginMessage {
+        channel: Identifier::parse("example:custom").unwrap(),
+        data: vec![1, 2, 3]
+    };
+    assert!(matches!(PluginChannels::default().decode(&read_back(&message)).unwrap(), PluginPayload::Raw(data) if data == vec![1, 2, 3]));
+
+    let channels = PluginChannels::new().register::<RemainingBytes>("example:custom").unwrap();
+    assert_eq!(channels.decode(&message).unwrap().downcast::<RemainingBytes>(), Some(RemainingBytes(vec![1, 2, 3])));
+}
+
+#[test]
+fn test_identifier() {
+    assert_eq!(Identifier::parse("brand").unwrap().to_string(), "minecraft:brand");
+    assert_eq!(Identifier::parse("bungeecord:main").unwrap().namespace(), "bungeecord");
+    assert!(Identifier::parse("BungeeCord").is_err());
+    assert!(Identifier::parse("minecraft:").is_err());
+}
@@ -0,0 +1,195 @@
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::network::{
+    buffer::Buffer,
+    connection::{async_socket::AsyncSocketConnection, pipeline::framing::FrameEncoder, Client, Pipeline, Server},
+    proxy::{relay, RelayAction},
+    status::{request_status, StatusResponse},
+    versions::V1_20_1,
+    ByteOrder, Error, PacketDirection, PacketState
+};
+
+const STATUS: &str = r#"{ "version": { "name": "1.20.1", "protocol": 763 }, "description": { "text": "Upstream" } }"#;
+
+fn pipeline() -> Pipeline<'static> {
+    Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing"))
+}
+
+async fn status_server(listener: TcpListener) -> Result<(), Error> {
+    let mut connection = AsyncSocketConnection::<Server>::new(listener.accept().await?.0, pipeline());
+    connection.read_packet().await?;
+    loop {
+        let mut request = connection.read_packet().await?;
+        let mut response = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        match request.read_var_i32()? {
+            0x00 => {
+                response.write_var_i32(0x00)?;
+                response.write_str(STATUS)?;
+            }
+            _ => {
+                response.write_var_i32(0x01)?;
+                response.write_i64(request.read_i64()?)?;
+            }
+        }
+        connection.write(response).await?;
+    }
+}
+
+async fn proxy(listener: TcpListener, upstream: u16, inspector: impl FnMut(PacketDirection, PacketState, &mut Buffer) -> RelayAction) -> Result<(), Error> {
+    let client = AsyncSocketConnection::<Server>::new(listener.accept().await?.0, pipeline());
+    let server = AsyncSocketConnection::<Client>::new(TcpStream::connect(("127.0.0.1", upstream)).await?, pipeline());
+    relay(client, server, inspector).await
+}
+
+#[tokio::test]
+async fn test_relay_rewrites_motd() {
+    let upstream = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let upstream_port = upstream.local_addr().unwrap().port();
+    let server = tokio::spawn(status_server(upstream));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let proxy = tokio::spawn(proxy(listener, upstream_port, |direction, state, packet| {
+        if direction != PacketDirection::Clientbound || state != PacketState::Status || packet.read_var_i32().unwrap() != 0x00 {
+            return RelayAction::Forward
+        }
+
+        let mut status = StatusResponse::parse(&packet.read_string().unwrap()).unwrap();
+        status.description = Some(serde_json::json!({ "text": "Relayed" }));
+        let mut replacement = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        replacement.write_var_i32(0x00).unwrap();
+        replacement.write_string(status.to_json().unwrap()).unwrap();
+        RelayAction::Replace(replacement)
+    }));
+
+    let status = request_status("127.0.0.1", port, &V1_20_1).await.unwrap();
+    assert_eq!(status.motd(), "Relayed");
+    assert!(status.latency.is_some());
+
+    proxy.await.unwrap().unwrap();
+    assert!(matches!(server.await.unwrap(), Err(Error::IoError(_))));
+}
+
+#[cfg(all(feature = "auth", feature = "webapi", feature = "std-net"))]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_relay_login_with_compression() {
+    use std::{net::TcpListener as StdTcpListener, thread};
+
+    use uuid::Uuid;
+
+    use crate::{
+        auth::{microsoft::MinecraftEdition, Session, TokenType},
+        network::{
+            connection::{socket::SocketConnection, Connection},
+            login::{accept_login, join_server_async_with_endpoints, GameProfile, ServerLoginConfig}
+        },
+        webapi::Endpoints
+    };
+
+    let upstream = StdTcpListener::bind("127.0.0.1:0").unwrap();
+    let upstream_port = upstream.local_addr().unwrap().port();
+    let server = thread::spawn(move || {
+        let mut connection = SocketConnection::<Server>::new(upstream.accept().unwrap().0, pipeline());
+        let player = accept_login(&mut connection, &ServerLoginConfig::new(false).unwrap().compression_threshold(64)).unwrap();
+        (player, connection.read_packet().unwrap().to_bytes())
+    });
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let proxy = tokio::spawn(proxy(listener, upstream_port, |_, _, _| RelayAction::Forward));
+
+    let session = Session {
+        username: Uuid::nil(),
+        roles: Vec::new(),
+        access_token: "access_token".to_string(),
+        token_type: TokenType::Bearer,
+        expires_in: std::time::Duration::from_secs(86400),
+        edition: MinecraftEdition::Java
+    };
+    let profile = GameProfile::new(Uuid::nil(), "Cach30verfl0w");
+    let (mut connection, login_success) = join_server_async_with_endpoints(&Endpoints::default(), "127.0.0.1", port, &session, &profile, &V1_20_1, pipeline)
+        .await
+        .unwrap();
+    assert!(connection.pipeline().has_encoder("compression"));
+
+    let mut packet = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    packet.write_bytes(&[7; 300]).unwrap();
+    connection.write(packet).await.unwrap();
+
+    let (player, received) = tokio::task::spawn_blocking(move || server.join().unwrap()).await.unwrap();
+    assert_eq!(player.name, "Cach30verfl0w");
+    assert_eq!(login_success.uuid, player.uuid);
+    assert_eq!(received, vec![7; 300]);
+
+    drop(connection);
+    proxy.await.unwrap().unwrap();
+}
+
+#[cfg(all(feature = "auth", feature = "webapi", feature = "std-net"))]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_relay_online_mode() {
+    use std::{net::TcpListener as StdTcpListener, thread};
+
+    use uuid::Uuid;
+
+    use crate::{
+        auth::{microsoft::MinecraftEdition, Session, TokenType},
+        network::{
+            connection::{socket::SocketConnection, Connection},
+            login::{accept_login, join_server_async_with_endpoints, GameProfile, ServerLoginConfig},
+            proxy::{relay_online, RelayAccount}
+        },
+        test::mock::{MockResponse, MockServer},
+        webapi::Endpoints
+    };
+
+    let sessions = MockServer::builder()
+        .post("/session/minecraft/join", MockResponse::new(204, ""))
+        .get(
+            "/session/minecraft/hasJoined",
+            MockResponse::ok(r#"{"id":"abe18c2573dc4f188638adb604cb1d03","name":"Cach30verfl0w","properties":[]}"#)
+        )
+        .start();
+    let endpoints = Endpoints::new(sessions.url(), sessions.url(), sessions.url());
+
+    let upstream = StdTcpListener::bind("127.0.0.1:0").unwrap();
+    let upstream_port = upstream.local_addr().unwrap().port();
+    let config = ServerLoginConfig::new(true).unwrap().endpoints(endpoints.clone());
+    let server = thread::spawn(move || {
+        let mut connection = SocketConnection::<Server>::new(upstream.accept().unwrap().0, pipeline());
+        accept_login(&mut connection, &config).unwrap()
+    });
+
+    let session = Session {
+        username: Uuid::nil(),
+        roles: Vec::new(),
+        access_token: "access_token".to_string(),
+        token_type: TokenType::Bearer,
+        expires_in: std::time::Duration::from_secs(86400),
+        edition: MinecraftEdition::Java
+    };
+    let profile = GameProfile::new(Uuid::parse_str("abe18c2573dc4f188638adb604cb1d03").unwrap(), "Cach30verfl0w");
+    let account = RelayAccount::new(&session, &profile).endpoints(endpoints);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let proxy = tokio::spawn(async move {
+        let client = AsyncSocketConnection::<Server>::new(listener.accept().await?.0, pipeline());
+        let server = AsyncSocketConnection::<Client>::new(TcpStream::connect(("127.0.0.1", upstream_port)).await?, pipeline());
+        relay_online(client, server, account, |_, _, _| RelayAction::Forward).await
+    });
+
+    let offline = Endpoints::new("http://127.0.0.1:1", "http://127.0.0.1:1", "http://127.0.0.1:1");
+    let (connection, login_success) = join_server_async_with_endpoints(&offline, "127.0.0.1", port, &session, &profile, &V1_20_1, pipeline)
+        .await
+        .unwrap();
+    assert!(!connection.codec().is_encrypted());
+
+    let player = tokio::task::spawn_blocking(move || server.join().unwrap()).await.unwrap();
+    assert_eq!(player.name, "Cach30verfl0w");
+    assert_eq!(login_success.uuid, player.uuid);
+    assert!(sessions.requests().iter().any(|request| request.path == "/session/minecraft/join"));
+
+    drop(connection);
+    proxy.await.unwrap().unwrap();
+}
@@ -0,0 +1,145 @@
+use crate::network::{
+    buffer::Buffer,
+    connection::{Readable, VersionedReadable, VersionedWritable, Writable},
+    registry_codec::{RegistryCodec, RegistryCodecBuilder, RegistryData, BIOME_REGISTRY, DIMENSION_TYPE_REGISTRY},
+    types::Identifier,
+    ByteOrder, Error
+};
+
+const REGISTRY_DATA: &[u8] = include_bytes!("../fixtures/network/registry/registry_data.nbt");
+
+fn codec() -> RegistryCodec {
+    RegistryCodec::decode(&mut Buffer::new(REGISTRY_DATA.to_vec(), false, Some(ByteOrder::BigEndian))).unwrap()
+}
+
+#[test]
+fn test_dimension_types() {
+    let codec = codec();
+
+    let overworld = codec.dimension_type(&Identifier::parse("overworld").unwrap()).unwrap();
+    assert_eq!((overworld.min_y, overworld.height, overworld.logical_height), (-64, 384, 384));
+    assert_eq!(overworld.section_count(), 24);
+    assert!(overworld.has_skylight && overworld.natural && !overworld.has_ceiling);
+    assert_eq!(overworld.fixed_time, None);
+
+    let nether = codec.dimension_type(&Identifier::parse("minecraft:the_nether").unwrap()).unwrap();
+    assert_eq!((nether.min_y, nether.height, nether.logical_height), (0, 256, 128));
+    assert_eq!(nether.fixed_time, Some(18000));
+    assert_eq!(nether.coordinate_scale, 8.0);
+    assert_eq!(nether.ambient_light, 0.1);
+    assert_eq!(nether.effects, "minecraft:the_nether");
+    assert!(nether.ultrawarm && nether.has_ceiling);
+
+    let sky_islands = codec.dimension_type(&Identifier::parse("example:sky_islands").unwrap()).unwrap();
+    assert_eq!((sky_islands.min_y, sky_islands.height, sky_islands.logical_height), (-128, 512, 320));
+
+    assert_eq!(codec.dimension_type(&Identifier::parse("example:broken").unwrap()), None);
+    assert_eq!(codec.dimension_type(&Identifier::parse("example:unknown").unwrap()), None);
+    assert_eq!(codec.dimension_types().len(), 5);
+    assert_eq!(codec.entries(DIMENSION_TYPE_REGISTRY).len(), 6);
+}
+
+#[test]
+fn test_biomes_and_chat_types() {
+    let codec = codec();
+
+    let biomes = codec.biomes();
+    assert_eq!(
+        biomes.iter().map(|biome| (biome.name.to_string(), biome.id)).collect::<Vec<_>>(),
+        vec![
+            ("minecraft:badlands".to_string(), 0),
+            ("minecraft:plains".to_string(), 39),
+            ("minecraft:snowy_plains".to_string(), 51)
+        ]
+    );
+    assert!(!biomes[0].has_precipitation);
+    assert_eq!((biomes[1].temperature, biomes[1].downfall), (0.8, 0.4));
+
+    let chat_types = codec.chat_types();
+    assert_eq!(chat_types.len(), 3);
+    assert_eq!(chat_types[2].translation_key, "commands.message.display.incoming");
+    assert_eq!(chat_types[0].parameters, vec!["sender", "content"]);
+
+    assert!(codec.registries().any(|registry| registry == "minecraft:damage_type"));
+    assert!(codec.entries("minecraft:unknown").is_empty());
+}
+
+#[test]
+fn test_fixture_round_trip() {
+    let buffer = codec().write(Buffer::empty(true, Some(ByteOrder::BigEndian))).unwrap();
+    assert_eq!(buffer.to_bytes(), REGISTRY_DATA);
+}
+
+#[test]
+fn test_vanilla_codec() {
+    let mut buffer = RegistryCodec::vanilla().write(Buffer::empty(true, Some(ByteOrder::BigEndian))).unwrap();
+    buffer.reset();
+    let codec = RegistryCodec::decode(&mut buffer).unwrap();
+    assert_eq!(codec, RegistryCodec::vanilla());
+
+    let end = codec.dimension_type(&Identifier::parse("the_end").unwrap()).unwrap();
+    assert_eq!((end.min_y, end.height, end.fixed_time), (0, 256, Some(6000)));
+    assert_eq!(codec.dimension_types().len(), 3);
+    assert_eq!(codec.entries(BIOME_REGISTRY)[0].name, Identifier::parse("plains").unwrap());
+    assert_eq!(codec.chat_types()[0].translation_key, "chat.type.text");
+}
+
+#[test]
+fn test_versioned_codec() {
+    // 1.20.1 names the root compound, 1.20.2 sends it as network NBT
+    let named = codec().write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), 763).unwrap().to_bytes();
+    assert_eq!(&named[..3], &[0x0A, 0x00, 0x00]);
+    assert_eq!(&named[3..], &REGISTRY_DATA[1..]);
+    assert_eq!(codec().write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), 764).unwrap().to_bytes(), REGISTRY_DATA);
+
+    let codec = RegistryCodec::read_versioned(&mut Buffer::new(named, false, Some(ByteOrder::BigEndian)), 763).unwrap();
+    assert_eq!(codec, self::codec());
+    assert!(codec.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), 767).is_err());
+}
+
+#[test]
+fn test_registry_data() {
+    let vanilla = RegistryCodec::vanilla();
+    let packets = vanilla.registry_data().unwrap();
+    assert_eq!(packets.len(), vanilla.registries().count());
+
+    let mut builder = RegistryCodecBuilder::new();
+    for packet in packets {
+        let mut buffer = packet.write(Buffer::empty(true, Some(ByteOrder::BigEndian))).unwrap();
+        buffer.reset();
+        let read = RegistryData::read(&mut buffer).unwrap();
+        assert_eq!(read, packet);
+        builder.push(read);
+    }
+    assert_eq!(builder.build(), vanilla);
+}
+
+#[test]
+fn test_registry_data_known_packs() {
+    let dimension_type = RegistryCodec::vanilla()
+        .registry_data()
+        .unwrap()
+        .into_iter()
+        .find(|packet| packet.registry.to_string() == DIMENSION_TYPE_REGISTRY)
+        .unwrap();
+    let mut packet = RegistryData {
+        registry: dimension_type.registry.clone(),
+        entries: dimension_type.entries.clone()
+    };
+    packet.entries[0].data = None;
+
+    let mut buffer = packet.write(Buffer::empty(true, Some(ByteOrder::BigEndian))).unwrap();
+    buffer.reset();
+    let codec = RegistryCodec::read_versioned(&mut buffer, 766).unwrap();
+    assert_eq!(codec.registries().collect::<Vec<_>>(), vec![DIMENSION_TYPE_REGISTRY]);
+    assert_eq!(codec.dimension_type(&dimension_type.entries[0].id), None);
+    assert_eq!(codec.entries(DIMENSION_TYPE_REGISTRY).iter().map(|entry| entry.id).collect::<Vec<_>>(), vec![1, 2]);
+    assert_eq!(codec.registry_data().unwrap(), vec![packet.clone()]);
+    assert_eq!(codec.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), 767).unwrap().to_bytes(), buffer.to_bytes());
+
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer = packet.registry.write(buffer).unwrap();
+    buffer.write_var_i32(65537).unwrap();
+    buffer.reset();
+    assert!(matches!(RegistryData::read(&mut buffer), Err(Error::Decode { .. })));
+}
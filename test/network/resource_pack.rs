@@ -0,0 +1,80 @@
+use uuid::Uuid;
+
+use crate::network::{
+    buffer::Buffer,
+    resource_pack::{ResourcePackAction, ResourcePackPolicy, ResourcePackRequest, ResourcePacks},
+    versions::{V1_16_5, V1_20_1, V1_21_1},
+    ByteOrder, Error, PacketState
+};
+
+const PACK: [u8; 16] = [0x3E, 0x5F, 0xB0, 0xB1, 0xA7, 0xC5, 0x4C, 0x1E, 0x8C, 0x0F, 0x3D, 0x8D, 0x0A, 0x6B, 0x4F, 0x21];
+
+fn add_pack(id: i32, pack: Option<&[u8]>, forced: Option<bool>) -> Buffer {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(id).unwrap();
+    if let Some(pack) = pack {
+        buffer.write_bytes(pack).unwrap();
+    }
+    buffer.write_str("https://packs.example.com/server.zip").unwrap();
+    buffer.write_str("").unwrap();
+    if let Some(forced) = forced {
+        buffer.write_bool(forced).unwrap();
+        buffer.write_bool(false).unwrap();
+    }
+    buffer.reset();
+    buffer
+}
+
+fn statuses(responses: Vec<Buffer>) -> Vec<Vec<u8>> {
+    responses.into_iter().map(|response| response.to_bytes()).collect()
+}
+
+#[test]
+fn test_resource_pack_play() {
+    let mut packs = ResourcePacks::new(&V1_21_1);
+    let mut packet = add_pack(0x46, Some(&PACK), Some(true));
+    let responses = packs.respond(PacketState::Play, &mut packet).unwrap().unwrap();
+    assert_eq!(packet.position(), 0);
+    assert_eq!(statuses(responses), [3, 4, 0].map(|status| [&[0x2B][..], &PACK, &[status]].concat()).to_vec());
+    assert!(packs.declined_forced().is_none());
+
+    // Before 1.20.3 packs have no id and nothing is reported between accepting and loading
+    let mut packs = ResourcePacks::new(&V1_20_1);
+    assert_eq!(
+        statuses(packs.respond(PacketState::Play, &mut add_pack(0x40, None, Some(false))).unwrap().unwrap()),
+        vec![vec![0x24, 3], vec![0x24, 0]]
+    );
+    assert_eq!(packs.respond(PacketState::Play, &mut add_pack(0x41, None, Some(false))).unwrap().map(statuses), None);
+    assert_eq!(packs.respond(PacketState::Configuration, &mut add_pack(0x40, None, Some(false))).unwrap().map(statuses), None);
+}
+
+#[test]
+fn test_resource_pack_declined() {
+    let mut packs = ResourcePacks::new(&V1_16_5).with_policy(ResourcePackPolicy::Decline);
+    assert_eq!(statuses(packs.respond(PacketState::Play, &mut add_pack(0x38, None, None)).unwrap().unwrap()), vec![vec![0x21, 1]]);
+    assert!(packs.declined_forced().is_none());
+
+    let mut packs = ResourcePacks::new(&V1_21_1).with_policy(ResourcePackPolicy::prompt(|_| ResourcePackAction::FailDownload));
+    let responses = packs.respond(PacketState::Configuration, &mut add_pack(0x09, Some(&PACK), Some(true))).unwrap().unwrap();
+    assert_eq!(statuses(responses), [3, 2].map(|status| [&[0x06][..], &PACK, &[status]].concat()).to_vec());
+    assert_eq!(packs.declined_forced().unwrap().id, Some(Uuid::from_bytes(PACK)));
+    assert_eq!(
+        packs.explain(Error::Disconnected("Kicked".to_string())).to_string(),
+        "Disconnected => Kicked (after the forced resource pack https://packs.example.com/server.zip wasn't loaded)"
+    );
+    assert!(matches!(packs.explain(Error::Other("Other".to_string())), Error::Other(_)));
+}
+
+#[test]
+fn test_resource_pack_verify() {
+    let request = ResourcePackRequest {
+        id: None,
+        url: String::new(),
+        hash: "A9993E364706816ABA3E25717850C26C9CD0D89D".to_string(),
+        forced: false,
+        prompt: None
+    };
+    assert!(request.verify(b"abc"));
+    assert!(!request.verify(b"abd"));
+    assert!(ResourcePackRequest { hash: String::new(), ..request }.verify(b"abd"));
+}
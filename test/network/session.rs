@@ -0,0 +1,374 @@
+use std::{
+    net::{TcpListener, TcpStream},
+    thread,
+    time::{Duration, Instant, SystemTime}
+};
+
+use rsa::Pkcs1v15Sign;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    network::{
+        buffer::Buffer,
+        chat::MessageSigner,
+        connection::{pipeline::framing::FrameEncoder, socket::SocketConnection, Client, Connection, Pipeline, Server},
+        encryption::generate_key_pair,
+        session::{
+            ClientSession, CommandError, CommandRateLimit, PlayerTeleport, SessionSnapshot, MAX_MOVE_DISTANCE, MOVE_INTERVAL, RELATIVE_PITCH, RELATIVE_X, RELATIVE_YAW, RELATIVE_Z
+        },
+        tracker::SessionState,
+        versions::{V1_16_5, V1_19_2, V1_20_4, V1_21_1},
+        ByteOrder
+    },
+    test::mock::MockConnection
+};
+
+fn read_f64(buffer: &mut Buffer) -> f64 {
+    f64::from_bits(buffer.read_u64().unwrap())
+}
+
+fn read_position(buffer: &mut Buffer) -> (f64, f64, f64) {
+    (read_f64(buffer), read_f64(buffer), read_f64(buffer))
+}
+
+fn read_confirmation(connection: &mut MockConnection<Client>) -> (i32, (f64, f64, f64), (f32, f32)) {
+    let mut packets = connection.packets.drain(..);
+    let mut confirm = packets.next().unwrap();
+    assert_eq!(confirm.read_var_i32().unwrap(), 0x00);
+    let teleport_id = confirm.read_var_i32().unwrap();
+
+    let mut position = packets.next().unwrap();
+    assert_eq!(position.read_var_i32().unwrap(), 0x18);
+    let result = (
+        teleport_id,
+        read_position(&mut position),
+        (f32::from_bits(position.read_u32().unwrap()), f32::from_bits(position.read_u32().unwrap()))
+    );
+    assert!(!position.read_bool().unwrap());
+    assert!(packets.next().is_none());
+    result
+}
+
+fn teleport(position: (f64, f64, f64), rotation: (f32, f32), relative: u8, teleport_id: i32) -> PlayerTeleport {
+    PlayerTeleport {
+        position,
+        yaw: rotation.0,
+        pitch: rotation.1,
+        relative,
+        teleport_id,
+        dismount: false
+    }
+}
+
+#[test]
+fn test_teleport_confirmation() {
+    let mut connection = MockConnection::client();
+    let mut session = ClientSession::new(&V1_20_4);
+    assert_eq!(session.position(), None);
+
+    let mut packet = teleport((100.5, 64.0, -20.5), (90.0, 10.0), 0, 1).write_versioned(session.protocol()).unwrap();
+    packet.reset();
+    assert!(session.handle(&mut connection, &mut packet).unwrap());
+    assert_eq!(packet.position(), 0);
+    assert_eq!(read_confirmation(&mut connection), (1, (100.5, 64.0, -20.5), (90.0, 10.0)));
+
+    let mut packet = teleport((2.0, 70.0, -3.5), (45.0, -30.0), RELATIVE_X | RELATIVE_Z | RELATIVE_YAW, 2)
+        .write_versioned(session.protocol())
+        .unwrap();
+    packet.reset();
+    assert!(session.handle(&mut connection, &mut packet).unwrap());
+    assert_eq!(read_confirmation(&mut connection), (2, (102.5, 70.0, -24.0), (135.0, -30.0)));
+    assert_eq!(session.position(), Some((102.5, 70.0, -24.0)));
+    assert_eq!(session.rotation(), (135.0, -30.0));
+
+    let mut packet = teleport((0.0, 0.0, 0.0), (0.0, 0.0), RELATIVE_X | RELATIVE_Z | RELATIVE_YAW | RELATIVE_PITCH, 3)
+        .write_versioned(session.protocol())
+        .unwrap();
+    packet.reset();
+    session.handle(&mut connection, &mut packet).unwrap();
+    assert_eq!(read_confirmation(&mut connection), (3, (102.5, 0.0, -24.0), (135.0, -30.0)));
+
+    let mut other = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    other.write_var_i32(0x3F).unwrap();
+    other.reset();
+    assert!(!session.handle(&mut connection, &mut other).unwrap());
+    assert!(connection.packets.is_empty());
+}
+
+#[test]
+fn test_dismount_flag() {
+    let teleport = PlayerTeleport {
+        dismount: true,
+        ..teleport((1.0, 2.0, 3.0), (0.0, 0.0), 0, 9)
+    };
+    let mut packet = teleport.write_versioned(V1_19_2.protocol).unwrap();
+    packet.reset();
+    assert_eq!(packet.read_var_i32().unwrap(), 0x39);
+    assert_eq!(PlayerTeleport::read_versioned(&mut packet, V1_19_2.protocol).unwrap(), teleport);
+    assert_eq!(packet.remaining(), 0);
+}
+
+#[test]
+fn test_move_to() {
+    let mut connection = MockConnection::client();
+    let mut session = ClientSession::new(&V1_20_4);
+    assert!(session.move_to(&mut connection, 0.0, 0.0, 0.0).is_err());
+    session.teleport(&mut connection, &teleport((0.5, 64.0, 0.5), (0.0, 0.0), 0, 1)).unwrap();
+    connection.packets.clear();
+
+    let start = Instant::now();
+    session.move_to(&mut connection, 25.5, 64.0, 0.5).unwrap();
+    assert!(start.elapsed() >= MOVE_INTERVAL * 3);
+    assert_eq!(session.position(), Some((25.5, 64.0, 0.5)));
+
+    let mut last = 0.5;
+    let steps = connection.packets.iter_mut().map(|packet| {
+        assert_eq!(packet.read_var_i32().unwrap(), 0x17);
+        let position = read_position(packet);
+        assert!(!packet.read_bool().unwrap());
+        position
+    });
+    let steps = steps.collect::<Vec<_>>();
+    assert_eq!(steps.len(), 3);
+    for (x, y, z) in &steps {
+        assert!(x - last <= MAX_MOVE_DISTANCE);
+        assert_eq!((*y, *z), (64.0, 0.5));
+        last = *x;
+    }
+    assert_eq!(last, 25.5);
+}
+
+#[test]
+fn test_world_border() {
+    let mut connection = MockConnection::client();
+    let mut session = ClientSession::new(&V1_20_4);
+    assert!(session.world_border().is_none());
+
+    let mut packet = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    packet.write_var_i32(0x23).unwrap();
+    for value in [100.0, -50.0, 200.0, 100.0] {
+        packet.write_u64(f64::to_bits(value)).unwrap();
+    }
+    packet.write_var_i64(60_000).unwrap();
+    for value in [29_999_984, 8, 20] {
+        packet.write_var_i32(value).unwrap();
+    }
+    packet.reset();
+    assert!(session.handle(&mut connection, &mut packet).unwrap());
+    let border = session.world_border().unwrap();
+    assert_eq!((border.center, border.diameter, border.target_diameter, border.lerp_time), ((100.0, -50.0), 200.0, 100.0, 60_000));
+    assert_eq!((border.warning_blocks, border.warning_time), (8, 20));
+    assert!(border.contains(149.0, -99.0));
+    assert!(!border.contains(151.0, -50.0));
+
+    let mut packet = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    packet.write_var_i32(0x4D).unwrap();
+    packet.write_u64(f64::to_bits(16.0)).unwrap();
+    packet.reset();
+    session.handle(&mut connection, &mut packet).unwrap();
+    assert_eq!(session.world_border().unwrap().target_diameter, 16.0);
+    assert!(connection.packets.is_empty());
+
+    // 1.16.5 sends every change as an action of the same packet
+    let mut session = ClientSession::new(&V1_16_5);
+    let mut packet = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    packet.write_var_i32(0x3D).unwrap();
+    packet.write_var_i32(2).unwrap();
+    packet.write_u64(f64::to_bits(8.0)).unwrap();
+    packet.write_u64(f64::to_bits(-8.0)).unwrap();
+    packet.reset();
+    session.handle(&mut connection, &mut packet).unwrap();
+    assert_eq!(session.world_border().unwrap().center, (8.0, -8.0));
+    assert_eq!(session.world_border().unwrap().diameter, 59_999_968.0);
+}
+
+#[test]
+fn test_reconnect_keeps_position_hint() {
+    let mut connection = MockConnection::client();
+    let mut session = ClientSession::new(&V1_20_4);
+    session.teleport(&mut connection, &teleport((10.0, 64.0, 10.0), (90.0, 0.0), 0, 1)).unwrap();
+    let mut packet = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    packet.write_var_i32(0x4D).unwrap();
+    packet.write_u64(f64::to_bits(16.0)).unwrap();
+    packet.reset();
+    session.handle(&mut connection, &mut packet).unwrap();
+
+    let snapshot = session.snapshot();
+    assert_eq!(
+        snapshot,
+        SessionSnapshot {
+            position: Some((10.0, 64.0, 10.0)),
+            rotation: (90.0, 0.0)
+        }
+    );
+    session.reset_for_reconnect();
+    session.restore(snapshot);
+    assert_eq!(session.position(), None);
+    assert_eq!(session.position_hint(), Some((10.0, 64.0, 10.0)));
+    assert_eq!(session.rotation(), (90.0, 0.0));
+    assert!(session.world_border().is_none());
+    assert!(session.move_to(&mut connection, 11.0, 64.0, 10.0).is_err());
+
+    assert_eq!(session.snapshot(), snapshot);
+    session.teleport(&mut connection, &teleport((-5.0, 70.0, 3.0), (0.0, 0.0), 0, 1)).unwrap();
+    assert_eq!(session.position(), Some((-5.0, 70.0, 3.0)));
+    assert_eq!(session.position_hint(), None);
+
+    session.restore(snapshot);
+    assert_eq!(session.position_hint(), None);
+}
+
+fn commands_packet(id: i32) -> Buffer {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(id).unwrap();
+    buffer.write_var_i32(5).unwrap();
+    buffer.write_bytes(&[0x00, 2, 1, 3]).unwrap();
+    buffer.write_bytes(&[0x01, 1, 2]).unwrap();
+    buffer.write_str("say").unwrap();
+    buffer.write_bytes(&[0x02 | 0x04, 0]).unwrap();
+    buffer.write_str("message").unwrap();
+    buffer.write_var_i32(19).unwrap();
+    buffer.write_bytes(&[0x01, 1, 4]).unwrap();
+    buffer.write_str("gamemode").unwrap();
+    buffer.write_bytes(&[0x01 | 0x04, 0]).unwrap();
+    buffer.write_str("creative").unwrap();
+    buffer.write_var_i32(0).unwrap();
+    buffer.reset();
+    buffer
+}
+
+fn scripted_server(commands: usize) -> (SocketConnection<'static, Client>, thread::JoinHandle<Vec<Buffer>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let mut connection = SocketConnection::<Server>::new(listener.accept().unwrap().0, Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing")));
+        connection.write(commands_packet(0x11)).unwrap();
+        (0..commands).map(|_| connection.read_packet().unwrap()).collect()
+    });
+    (
+        SocketConnection::<Client>::new(TcpStream::connect(address).unwrap(), Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing"))),
+        server
+    )
+}
+
+fn join(connection: &mut SocketConnection<'static, Client>) -> ClientSession {
+    let mut session = ClientSession::new(&V1_20_4);
+    let mut packet = connection.read_packet().unwrap();
+    assert!(session.handle(connection, &mut packet).unwrap());
+    assert!(session.commands().is_some());
+    session
+}
+
+fn read_command(packet: &mut Buffer, signature_length: usize) -> (String, i64, i64, Vec<(String, Vec<u8>)>) {
+    assert_eq!(packet.read_var_i32().unwrap(), 0x04);
+    let (command, timestamp, salt) = (packet.read_string().unwrap(), packet.read_i64().unwrap(), packet.read_i64().unwrap());
+    let signatures = (0..packet.read_var_i32().unwrap())
+        .map(|_| (packet.read_string().unwrap(), packet.read_bytes(signature_length).unwrap()))
+        .collect();
+    assert_eq!(packet.read_var_i32().unwrap(), 0);
+    assert_eq!(packet.read_bytes(3).unwrap(), vec![0, 0, 0]);
+    (command, timestamp, salt, signatures)
+}
+
+#[test]
+fn test_send_signed_command() {
+    let (mut connection, server) = scripted_server(2);
+    let mut session = join(&mut connection);
+    let private_key = generate_key_pair(1024).unwrap();
+    let mut signer = MessageSigner::new(Uuid::from_u128(1), private_key.clone(), SystemTime::now() + Duration::from_secs(3600));
+    session.set_enforces_secure_chat(true);
+    session.send_command(&mut connection, "/say Hello there", Some(&mut signer)).unwrap();
+    session.send_command(&mut connection, "gamemode creative", Some(&mut signer)).unwrap();
+
+    let mut packets = server.join().unwrap().into_iter();
+    let (command, timestamp, salt, signatures) = read_command(&mut packets.next().unwrap(), 128);
+    assert_eq!(command, "say Hello there");
+    assert_eq!(signatures.len(), 1);
+    assert_eq!(signatures[0].0, "message");
+
+    let mut hasher = Sha256::new();
+    hasher.update(1_i32.to_be_bytes());
+    hasher.update(Uuid::from_u128(1).as_bytes());
+    hasher.update(signer.session_id().as_bytes());
+    hasher.update(0_i32.to_be_bytes());
+    hasher.update(salt.to_be_bytes());
+    hasher.update((timestamp / 1000).to_be_bytes());
+    hasher.update(11_i32.to_be_bytes());
+    hasher.update(b"Hello there");
+    hasher.update(0_i32.to_be_bytes());
+    private_key.to_public_key().verify(Pkcs1v15Sign::new::<Sha256>(), &hasher.finalize(), &signatures[0].1).unwrap();
+
+    let (command, _, salt, signatures) = read_command(&mut packets.next().unwrap(), 128);
+    assert_eq!(command, "gamemode creative");
+    assert_eq!(salt, 0);
+    assert!(signatures.is_empty());
+}
+
+#[test]
+fn test_send_unsigned_command() {
+    let (mut connection, server) = scripted_server(2);
+    let mut session = join(&mut connection);
+    session.send_command(&mut connection, "/say hi", None).unwrap();
+
+    session.set_enforces_secure_chat(true);
+    assert!(matches!(
+        session.send_command(&mut connection, "say hi", None),
+        Err(CommandError::MissingCertificates(command)) if command == "say hi"
+    ));
+    session.send_command(&mut connection, "/gamemode creative", None).unwrap();
+
+    let mut packets = server.join().unwrap().into_iter();
+    let (command, _, salt, signatures) = read_command(&mut packets.next().unwrap(), 0);
+    assert_eq!((command.as_str(), salt), ("say hi", 0));
+    assert!(signatures.is_empty());
+    assert_eq!(read_command(&mut packets.next().unwrap(), 0).0, "gamemode creative");
+}
+
+#[test]
+fn test_command_packets() {
+    let private_key = generate_key_pair(1024).unwrap();
+    let mut signer = MessageSigner::new(Uuid::from_u128(1), private_key, SystemTime::now() + Duration::from_secs(3600));
+    let mut connection = MockConnection::client();
+
+    // 1.20.5 split signed commands off into their own packet
+    let mut session = ClientSession::new(&V1_21_1);
+    session.handle(&mut connection, &mut commands_packet(0x11)).unwrap();
+    session.send_command(&mut connection, "say hi", Some(&mut signer)).unwrap();
+    session.send_command(&mut connection, "say hi", None).unwrap();
+    let mut packets = connection.packets.drain(..);
+    assert_eq!(packets.next().unwrap().read_var_i32().unwrap(), 0x05);
+    let mut unsigned = packets.next().unwrap();
+    assert_eq!((unsigned.read_var_i32().unwrap(), unsigned.read_string().unwrap()), (0x04, "say hi".to_string()));
+    assert_eq!(unsigned.remaining(), 0);
+    drop(packets);
+
+    // Before 1.19 commands were chat messages starting with a slash
+    let mut session = ClientSession::new(&V1_16_5);
+    session.send_command(&mut connection, "say hi", Some(&mut signer)).unwrap();
+    let mut chat = connection.packets.remove(0);
+    assert_eq!((chat.read_var_i32().unwrap(), chat.read_string().unwrap()), (0x03, "/say hi".to_string()));
+}
+
+#[test]
+fn test_command_rate_limit() {
+    let mut connection = MockConnection::client();
+    let mut session = ClientSession::new(&V1_20_4).with_command_rate_limit(Some(CommandRateLimit {
+        burst: 2,
+        interval: Duration::from_millis(50)
+    }));
+
+    let start = Instant::now();
+    for _ in 0..4 {
+        session.send_command(&mut connection, "gamemode creative", None).unwrap();
+    }
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    assert_eq!(connection.packets.len(), 4);
+
+    let mut session = ClientSession::new(&V1_20_4).with_command_rate_limit(None);
+    let start = Instant::now();
+    for _ in 0..20 {
+        session.send_command(&mut connection, "gamemode creative", None).unwrap();
+    }
+    assert!(start.elapsed() < Duration::from_millis(500));
+}
@@ -0,0 +1,134 @@
+use crate::network::{
+    nbt::{Compound, Tag, MAXIMUM_DEPTH},
+    snbt::SnbtError,
+    Error
+};
+
+fn string(value: &str) -> Tag {
+    Tag::String(value.to_string())
+}
+
+#[test]
+fn test_parse_item() {
+    let compound = Compound::from_snbt(r#"{display:{Name:'{"text":"hi"}'},Unbreakable:1b}"#).unwrap();
+    assert_eq!(
+        compound,
+        Compound::new()
+            .with("display", Tag::Compound(Compound::new().with("Name", string(r#"{"text":"hi"}"#))))
+            .with("Unbreakable", Tag::Byte(1))
+    );
+    assert_eq!(compound.to_snbt(), r#"{display:{Name:'{"text":"hi"}'},Unbreakable:1b}"#);
+}
+
+#[test]
+fn test_type_inference() {
+    let cases = [
+        ("1b", Tag::Byte(1)),
+        ("-128B", Tag::Byte(-128)),
+        ("300s", Tag::Short(300)),
+        ("-7", Tag::Int(-7)),
+        ("+7", Tag::Int(7)),
+        ("9000000000l", Tag::Long(9000000000)),
+        ("1L", Tag::Long(1)),
+        ("0.5f", Tag::Float(0.5)),
+        ("3F", Tag::Float(3.0)),
+        ("1e3f", Tag::Float(1000.0)),
+        ("2.5d", Tag::Double(2.5)),
+        ("2D", Tag::Double(2.0)),
+        ("1.5", Tag::Double(1.5)),
+        (".5", Tag::Double(0.5)),
+        ("1.", Tag::Double(1.0)),
+        ("1.5e-2", Tag::Double(0.015)),
+        ("true", Tag::Byte(1)),
+        ("FALSE", Tag::Byte(0)),
+        ("128b", string("128b")),
+        ("2147483648", string("2147483648")),
+        ("01", string("01")),
+        ("1e3", string("1e3")),
+        ("minecraft.stone", string("minecraft.stone")),
+        ("  \"quoted\"  ", string("quoted"))
+    ];
+
+    for (snbt, tag) in cases {
+        assert_eq!(Tag::from_snbt(snbt).unwrap(), tag, "{}", snbt);
+    }
+}
+
+#[test]
+fn test_round_trip_corpus() {
+    let corpus = [
+        r#"{}"#,
+        r#"[]"#,
+        r#"{a:1b,b:2s,c:3,d:4L,e:0.1f,f:0.25d,g:-1.5e-7d}"#,
+        r#"{"key with spaces":"value",'"quoted"':1}"#,
+        r#"{escaped:"say \"hi\"",single:'it\'s',both:"a'b\"c",backslash:"C:\\minecraft"}"#,
+        r#"{bytes:[B;1B,-2B],ints:[I;1,-2,3],longs:[L;1L,-9000000000L],empty:[I;]}"#,
+        r#"{list:[{id:"minecraft:stone",Count:1b},{id:"minecraft:dirt",Count:64b}],nested:[[1,2],[3]]}"#,
+        r#"{unicode:"äöü ✓","":"empty key"}"#,
+        r#"["a","b","c"]"#
+    ];
+
+    for snbt in corpus {
+        let tag = Tag::from_snbt(snbt).unwrap();
+        assert_eq!(Tag::from_snbt(&tag.to_snbt()).unwrap(), tag, "{}", snbt);
+    }
+
+    assert_eq!(
+        Tag::from_snbt(corpus[4]).unwrap().to_snbt(),
+        r#"{escaped:'say "hi"',single:"it's",both:"a'b\"c",backslash:"C:\\minecraft"}"#
+    );
+    assert_eq!(Tag::from_snbt(corpus[5]).unwrap().to_snbt(), corpus[5]);
+
+    let extremes = Compound::new()
+        .with("byte", Tag::Byte(i8::MIN))
+        .with("short", Tag::Short(i16::MAX))
+        .with("int", Tag::Int(i32::MIN))
+        .with("long", Tag::Long(i64::MAX))
+        .with("float", Tag::Float(f32::MAX))
+        .with("double", Tag::Double(f64::MIN_POSITIVE))
+        .with("whole", Tag::Double(1.0));
+    assert_eq!(Compound::from_snbt(&extremes.to_snbt()).unwrap(), extremes);
+}
+
+#[test]
+fn test_whitespace_and_trailing_separators() {
+    let tag = Tag::from_snbt(" { a : [ 1 , 2 , ] , b : [B; 1b, ] , } ").unwrap();
+    assert_eq!(
+        tag,
+        Tag::Compound(Compound::new().with("a", Tag::List(vec![Tag::Int(1), Tag::Int(2)])).with("b", Tag::ByteArray(vec![1])))
+    );
+}
+
+fn error(snbt: &str) -> SnbtError {
+    Tag::from_snbt(snbt).unwrap_err()
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(error("{a:1} b").position, 6);
+    assert_eq!(error("{a:1").position, 4);
+    assert_eq!(error("{:1}").position, 1);
+    assert_eq!(error("[1,2b]").position, 3);
+    assert_eq!(error("[I;1,2b]").position, 5);
+    assert_eq!(error("[Q;1]").position, 1);
+    assert_eq!(error(r#""a\nb""#).position, 3);
+    assert_eq!(error("\"open").position, 5);
+    assert_eq!(error("{a:}").position, 3);
+    assert_eq!(error("").reason, "Expected value");
+
+    let error = Error::from(error("[1,2b]"));
+    assert!(matches!(error, Error::Decode { offset: 3, .. }));
+}
+
+#[test]
+fn test_depth_limit() {
+    let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+    let handle = std::thread::Builder::new()
+        .stack_size(2 * 1024 * 1024)
+        .spawn(move || (Tag::from_snbt(&nested(MAXIMUM_DEPTH)).is_ok(), Tag::from_snbt(&nested(MAXIMUM_DEPTH + 2)).unwrap_err()))
+        .unwrap();
+
+    let (accepted, error) = handle.join().unwrap();
+    assert!(accepted);
+    assert!(error.reason.contains("nested deeper"));
+}
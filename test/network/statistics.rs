@@ -0,0 +1,71 @@
+use crate::{
+    network::{
+        buffer::Buffer,
+        connection::{Readable, Writable},
+        statistics::{request_statistics, StatisticCategory, Statistics, REQUEST_STATISTICS_ACTION},
+        versions::{V1_16_5, V1_20_4},
+        ByteOrder
+    },
+    test::mock::MockConnection
+};
+
+fn award_statistics(id: i32, statistics: &Statistics) -> Buffer {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(id).unwrap();
+    let mut buffer = statistics.write(buffer).unwrap();
+    buffer.reset();
+    buffer
+}
+
+fn keep_alive(id: i64) -> Buffer {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(0x24).unwrap();
+    buffer.write_i64(id).unwrap();
+    buffer.reset();
+    buffer
+}
+
+#[test]
+fn test_statistics_decoding() {
+    // 12 stone mined, 3 zombies killed and the custom statistic 5 at 1000
+    let mut buffer = Buffer::new(vec![0x03, 0x00, 0x01, 0x0C, 0x06, 0x75, 0x03, 0x08, 0x05, 0xE8, 0x07], false, Some(ByteOrder::BigEndian));
+    let statistics = Statistics::read(&mut buffer).unwrap();
+    assert_eq!(statistics.len(), 3);
+    assert_eq!(statistics.get(StatisticCategory::Mined, 1), Some(12));
+    assert_eq!(statistics.get(StatisticCategory::Killed, 117), Some(3));
+    assert_eq!(statistics.get(StatisticCategory::Custom, 5), Some(1000));
+    assert_eq!(statistics.category(StatisticCategory::Killed).collect::<Vec<_>>(), vec![(117, 3)]);
+    assert_eq!(StatisticCategory::Killed.registry(), Some("minecraft:entity_type"));
+    assert_eq!(StatisticCategory::from_id(9), StatisticCategory::Unknown(9));
+
+    let mut buffer = statistics.write(Buffer::empty(true, Some(ByteOrder::BigEndian))).unwrap();
+    buffer.reset();
+    assert_eq!(Statistics::read(&mut buffer).unwrap(), statistics);
+}
+
+#[test]
+fn test_request_statistics_amid_traffic() {
+    let mut statistics = Statistics::new();
+    statistics.insert(StatisticCategory::PickedUp, 42, 64);
+
+    let mut connection = MockConnection::client();
+    connection.incoming.extend([keep_alive(1), keep_alive(2), award_statistics(0x04, &statistics), keep_alive(3)]);
+
+    let mut unrelated = Vec::new();
+    let received = request_statistics(&mut connection, &V1_20_4, |_, mut packet| {
+        packet.read_var_i32()?;
+        unrelated.push(packet.read_i64()?);
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(received, statistics);
+    assert_eq!(unrelated, vec![1, 2]);
+    assert_eq!(connection.incoming.len(), 1);
+
+    let request = &mut connection.packets[0];
+    assert_eq!((request.read_var_i32().unwrap(), request.read_var_i32().unwrap()), (0x08, REQUEST_STATISTICS_ACTION));
+
+    let mut connection = MockConnection::client();
+    connection.incoming.push_back(keep_alive(1));
+    assert!(request_statistics(&mut connection, &V1_16_5, |_, _| Ok(())).is_err());
+}
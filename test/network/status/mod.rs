@@ -0,0 +1,433 @@
+#[cfg(feature = "webapi")]
+pub mod probe;
+
+use std::{
+    future::Future,
+    io,
+    time::{Duration, Instant}
+};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream}
+};
+use uuid::Uuid;
+
+use crate::{
+    components::Component,
+    network::{
+        buffer::Buffer,
+        connection::{async_socket::AsyncSocketConnection, pipeline::framing::FrameEncoder, Pipeline, Server},
+        handshake::Handshake,
+        status::{
+            detect_version, legacy::LegacyStatus, parse_address, ping, ping_with_timeouts, probe, query::QueryResponse, request_status, request_status_with_timeouts, select_version,
+            Probe, ProbeOptions, StatusResponse, StatusResponseBuilder, StatusVersion, DEFAULT_PONG_TIMEOUT, DEFAULT_SAMPLE_LIMIT
+        },
+        versions::{VersionInfo, VersionSelection, V1_19_2, V1_20_1, V1_20_4, V1_21_1},
+        ByteOrder, Error, PacketState
+    }
+};
+
+const STATUS: &str = include_str!("../../fixtures/network/status/status.json");
+
+#[tokio::test]
+async fn test_probe_legacy_fallback() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        drop(listener.accept().await.unwrap());
+
+        let mut socket = listener.accept().await.unwrap().0;
+        let mut request = [0; 3];
+        socket.read_exact(&mut request).await.unwrap();
+        assert_eq!(request, [0xFE, 0x01, 0xFA]);
+
+        let response = ["\u{a7}1", "127", "1.6.4", "A Legacy Server", "3", "20"].join("\0").encode_utf16().collect::<Vec<_>>();
+        let mut bytes = vec![0xFF];
+        bytes.extend_from_slice(&(response.len() as u16).to_be_bytes());
+        response.iter().for_each(|character| bytes.extend_from_slice(&character.to_be_bytes()));
+        socket.write_all(&bytes).await.unwrap();
+    });
+
+    let probe = probe(&format!("127.0.0.1:{}", port), ProbeOptions::new().legacy_fallback(true)).await.unwrap();
+    assert!(probe.status.is_none());
+    assert!(probe.error(Probe::Status).is_some());
+    assert_eq!(
+        probe.legacy,
+        Some(LegacyStatus {
+            protocol: Some(127),
+            version: Some("1.6.4".to_string()),
+            motd: "A Legacy Server".to_string(),
+            online_players: 3,
+            max_players: 20
+        })
+    );
+}
+
+#[test]
+fn test_status_response() {
+    let status = StatusResponse::parse(STATUS).unwrap();
+    assert_eq!(status.version.protocol, 763);
+    assert_eq!(status.players.as_ref().unwrap().sample[0].name, "Cach30verfl0w");
+    assert_eq!(status.motd(), "A Minecraft Server");
+    assert_eq!(status.favicon_bytes().unwrap(), vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]);
+    assert!(status.enforces_secure_chat);
+}
+
+#[test]
+fn test_status_sample_ids() {
+    let sample = &StatusResponse::parse(STATUS).unwrap().players.unwrap().sample[0];
+    assert_eq!(sample.id, Some(Uuid::from_u128(0xabe18c2573dc4f188638adb604cb1d03)));
+
+    let json = r#"{ "version": { "name": "1.20.1", "protocol": 763 }, "players": { "max": 1, "online": 1, "sample": [ { "name": "§aJoin now!", "id": "ad" } ] } }"#;
+    let status = StatusResponse::parse(json).unwrap();
+    let sample = &status.players.as_ref().unwrap().sample[0];
+    assert_eq!((sample.id, sample.raw_id.as_str()), (None, "ad"));
+    assert!(serde_json::to_string(&status).unwrap().contains(r#"{"name":"§aJoin now!","id":"ad"}"#));
+}
+
+#[test]
+fn test_legacy_status_beta() {
+    let status = LegacyStatus::parse("A \u{a7}Beta Server\u{a7}1\u{a7}10").unwrap();
+    assert_eq!(status.motd, "A \u{a7}Beta Server");
+    assert_eq!(status.online_players, 1);
+    assert_eq!(status.max_players, 10);
+    assert_eq!(status.protocol, None);
+}
+
+#[test]
+fn test_query_response() {
+    let response = QueryResponse::parse(b"splitnum\0\x80\0hostname\0A Minecraft Server\0numplayers\0\x32\0maxplayers\0\x320\0\0\x01player_\0\0Notch\0jeb_\0\0").unwrap();
+    assert_eq!(response.motd(), Some("A Minecraft Server"));
+    assert_eq!(response.online_players(), Some(2));
+    assert_eq!(response.max_players(), Some(20));
+    assert_eq!(response.players, vec!["Notch", "jeb_"]);
+}
+
+#[test]
+fn test_parse_address() {
+    assert_eq!(parse_address("example.com").unwrap(), ("example.com".to_string(), None));
+    assert_eq!(parse_address("example.com:25566").unwrap(), ("example.com".to_string(), Some(25566)));
+    assert_eq!(parse_address("[::1]:25565").unwrap(), ("::1".to_string(), Some(25565)));
+    assert_eq!(parse_address("::1").unwrap(), ("::1".to_string(), None));
+    assert!(parse_address("example.com:port").is_err());
+}
+
+#[test]
+fn test_status_response_sample_limit() {
+    let sample = (0..10000).map(|i| format!(r#"{{ "name": "Player{}", "id": "{}" }}"#, i, Uuid::nil())).collect::<Vec<_>>();
+    let json = format!(
+        r#"{{ "version": {{ "name": "1.20.1", "protocol": 763 }}, "players": {{ "max": 1, "online": 1, "sample": [{}] }} }}"#,
+        sample.join(",")
+    );
+
+    let players = StatusResponse::parse(&json).unwrap().players.unwrap();
+    assert_eq!(players.sample.len(), DEFAULT_SAMPLE_LIMIT);
+    assert_eq!(players.sample[99].name, "Player99");
+
+    let players = StatusResponse::parse_with_sample_limit(&json, 5).unwrap().players.unwrap();
+    assert_eq!(players.sample.len(), 5);
+    assert_eq!(players.max, 1);
+
+    let status = StatusResponse::parse(r#"{ "version": { "name": "1.20.1", "protocol": 763 }, "players": null }"#).unwrap();
+    assert!(status.players.is_none());
+}
+
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 13];
+    png.extend_from_slice(b"IHDR");
+    png.extend_from_slice(&width.to_be_bytes());
+    png.extend_from_slice(&height.to_be_bytes());
+    png.extend_from_slice(&[8, 6, 0, 0, 0]);
+    png
+}
+
+#[test]
+fn test_status_response_builder() {
+    let players = (0..10).map(|i| (format!("Player{}", i), Uuid::from_u128(i))).collect::<Vec<_>>();
+    let players = players.iter().map(|(name, id)| (name.as_str(), *id)).collect::<Vec<_>>();
+    let favicon = png(64, 64);
+
+    let status = StatusResponseBuilder::new()
+        .version("1.20.1", 763)
+        .players(10, 100)
+        .sample(&players)
+        .sample_limit(3)
+        .description(Component::text("A Minecraft ").append(Component::text("Server").bold(true)))
+        .favicon_from_png(&favicon)
+        .enforces_secure_chat(true)
+        .build()
+        .unwrap();
+
+    let parsed = StatusResponse::parse(&status.to_json().unwrap()).unwrap();
+    assert_eq!(parsed, status);
+    assert_eq!(parsed.version.protocol, 763);
+    assert_eq!(parsed.players.as_ref().unwrap().online, 10);
+    assert_eq!(parsed.players.as_ref().unwrap().sample.len(), 3);
+    assert_eq!(parsed.players.as_ref().unwrap().sample[2].id, Some(Uuid::from_u128(2)));
+    assert_eq!(parsed.motd(), "A Minecraft Server");
+    assert_eq!(parsed.favicon_bytes().unwrap(), favicon);
+    assert!(parsed.enforces_secure_chat);
+}
+
+#[test]
+fn test_status_response_builder_favicon_dimensions() {
+    assert!(StatusResponseBuilder::new().favicon_from_png(&png(64, 64)).build_json().is_ok());
+    assert!(StatusResponseBuilder::new().favicon_from_png(&png(128, 64)).build().is_err());
+    assert!(StatusResponseBuilder::new().favicon_from_png(b"not a png").build().is_err());
+}
+
+fn frame(packet: Buffer) -> Vec<u8> {
+    let bytes = packet.to_bytes();
+    let mut frame = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    frame.write_var_i32(bytes.len() as i32).unwrap();
+    frame.write_bytes(&bytes).unwrap();
+    frame.to_bytes()
+}
+
+fn status_frame() -> Vec<u8> {
+    let mut response = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    response.write_var_i32(0x00).unwrap();
+    response.write_str(STATUS).unwrap();
+    frame(response)
+}
+
+fn pong_frame(payload: i64) -> Vec<u8> {
+    let mut pong = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    pong.write_var_i32(0x01).unwrap();
+    pong.write_i64(payload).unwrap();
+    frame(pong)
+}
+
+async fn scripted_server<F: Future<Output = ()> + Send + 'static>(script: impl FnOnce(TcpStream, i64) -> F + Send + 'static) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        let mut socket = listener.accept().await.unwrap().0;
+        let mut packets = Vec::new();
+        for _ in 0..3 {
+            let length = socket.read_u8().await.unwrap() as usize;
+            let mut packet = vec![0; length];
+            socket.read_exact(&mut packet).await.unwrap();
+            packets.push(packet);
+        }
+
+        let mut ping = Buffer::new(packets.pop().unwrap(), true, Some(ByteOrder::BigEndian));
+        assert_eq!(ping.read_var_i32().unwrap(), 0x01);
+        script(socket, ping.read_i64().unwrap()).await;
+    });
+    port
+}
+
+#[tokio::test]
+async fn test_request_status_pong_first() {
+    let port = scripted_server(|mut socket, payload| {
+        async move {
+            socket.write_all(&pong_frame(payload)).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            socket.write_all(&status_frame()).await.unwrap();
+        }
+    })
+    .await;
+
+    let status = request_status("127.0.0.1", port, &V1_20_1).await.unwrap();
+    assert_eq!(status.motd(), "A Minecraft Server");
+    assert!(status.latency.is_some());
+}
+
+#[tokio::test]
+async fn test_request_status_single_segment() {
+    let port = scripted_server(|mut socket, payload| {
+        async move {
+            let mut bytes = status_frame();
+            bytes.extend(pong_frame(payload));
+            socket.write_all(&bytes).await.unwrap();
+        }
+    })
+    .await;
+
+    let status = request_status("127.0.0.1", port, &V1_20_1).await.unwrap();
+    assert_eq!(status.motd(), "A Minecraft Server");
+    assert!(status.latency.is_some());
+}
+
+#[tokio::test]
+async fn test_request_status_closed_without_pong() {
+    let port = scripted_server(|mut socket, _| {
+        async move {
+            socket.write_all(&status_frame()).await.unwrap();
+        }
+    })
+    .await;
+
+    let status = request_status("127.0.0.1", port, &V1_20_1).await.unwrap();
+    assert_eq!(status.motd(), "A Minecraft Server");
+    assert_eq!(status.latency, None);
+}
+
+#[tokio::test]
+async fn test_request_status_pong_timeout() {
+    let port = scripted_server(|mut socket, _| {
+        async move {
+            socket.write_all(&status_frame()).await.unwrap();
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        }
+    })
+    .await;
+
+    let start = Instant::now();
+    let status = request_status_with_timeouts("127.0.0.1", port, &V1_20_1, Duration::from_secs(2), Duration::from_millis(200))
+        .await
+        .unwrap();
+    assert_eq!(status.latency, None);
+    assert!(start.elapsed() < Duration::from_secs(2));
+}
+
+#[tokio::test]
+async fn test_request_status_slow_status() {
+    let port = scripted_server(|mut socket, payload| {
+        async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            socket.write_all(&status_frame()).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            socket.write_all(&pong_frame(payload)).await.unwrap();
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        }
+    })
+    .await;
+
+    // The pong comes 400ms after the ping but only 100ms after the status response
+    let status = request_status_with_timeouts("127.0.0.1", port, &V1_20_1, Duration::from_secs(2), Duration::from_millis(250))
+        .await
+        .unwrap();
+    assert!(status.latency.is_some_and(|latency| latency >= Duration::from_millis(400)));
+}
+
+#[tokio::test]
+async fn test_request_status_timeout() {
+    let port = scripted_server(|mut socket, payload| {
+        async move {
+            socket.write_all(&pong_frame(payload)).await.unwrap();
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        }
+    })
+    .await;
+
+    let error = request_status_with_timeouts("127.0.0.1", port, &V1_20_1, Duration::from_millis(200), Duration::from_millis(100))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::IoError(error) if error.kind() == io::ErrorKind::TimedOut));
+}
+
+#[tokio::test]
+async fn test_request_status_closed_early() {
+    let port = scripted_server(|mut socket, payload| {
+        async move {
+            socket.write_all(&pong_frame(payload)).await.unwrap();
+        }
+    })
+    .await;
+
+    let error = request_status("127.0.0.1", port, &V1_20_1).await.unwrap_err();
+    assert!(matches!(error, Error::Disconnected(_)));
+}
+
+async fn echo_server(delay: Duration) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        let pipeline = Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing"));
+        let mut connection = AsyncSocketConnection::<Server>::new(listener.accept().await.unwrap().0, pipeline);
+        Handshake::read(&mut connection.read_packet().await.unwrap()).unwrap();
+        connection.set_state(PacketState::Status);
+
+        assert_eq!(connection.read_packet().await.unwrap().read_var_i32().unwrap(), 0x00);
+        let mut response = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        response.write_var_i32(0x00).unwrap();
+        response.write_str(STATUS).unwrap();
+        connection.write(response).await.unwrap();
+
+        let mut ping = connection.read_packet().await.unwrap();
+        assert_eq!(ping.read_var_i32().unwrap(), 0x01);
+        let payload = ping.read_i64().unwrap();
+        tokio::time::sleep(delay).await;
+        for payload in [payload.wrapping_add(1), payload] {
+            let mut pong = Buffer::empty(true, Some(ByteOrder::BigEndian));
+            pong.write_var_i32(0x01).unwrap();
+            pong.write_i64(payload).unwrap();
+            connection.write(pong).await.unwrap();
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    });
+    port
+}
+
+#[tokio::test]
+async fn test_ping() {
+    let port = echo_server(Duration::from_millis(30)).await;
+
+    let result = ping("127.0.0.1", port, &V1_20_1).await.unwrap();
+    assert_eq!(result.status.motd(), "A Minecraft Server");
+    assert!(result.latency >= Duration::from_millis(30));
+    assert!(result.latency < DEFAULT_PONG_TIMEOUT);
+}
+
+#[tokio::test]
+async fn test_ping_pong_timeout() {
+    let port = echo_server(Duration::from_secs(1)).await;
+
+    let error = ping_with_timeouts("127.0.0.1", port, &V1_20_1, Duration::from_secs(1), Duration::from_millis(100))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::IoError(error) if error.kind() == io::ErrorKind::TimedOut));
+}
+
+async fn versioned_server(protocol: i32, name: &str) -> u16 {
+    let mut response = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    response.write_var_i32(0x00).unwrap();
+    response
+        .write_string(format!(r#"{{"version":{{"name":"{}","protocol":{}}},"description":"A Minecraft Server"}}"#, name, protocol))
+        .unwrap();
+    let status = frame(response);
+    scripted_server(move |mut socket, _| async move { socket.write_all(&status).await.unwrap() }).await
+}
+
+#[tokio::test]
+async fn test_detect_version() {
+    let port = versioned_server(763, "Paper 1.20.1").await;
+    assert_eq!(detect_version(&format!("127.0.0.1:{}", port)).await.unwrap(), V1_20_1);
+
+    let port = versioned_server(9999, "Paper 2.0").await;
+    match detect_version(&format!("127.0.0.1:{}", port)).await {
+        Err(Error::UnsupportedVersion { protocol, name }) => assert_eq!((protocol, name.as_str()), (9999, "Paper 2.0")),
+        result => panic!("Expected an unsupported version, got {:?}", result)
+    }
+}
+
+#[tokio::test]
+async fn test_select_version() {
+    let port = versioned_server(767, "Velocity 3.3.0").await;
+    assert_eq!(select_version("127.0.0.1", port, &VersionSelection::PreferRange(754..=765)).await.unwrap(), V1_20_4);
+    let port = versioned_server(760, "Spigot 1.19.2").await;
+    assert_eq!(select_version("127.0.0.1", port, &VersionSelection::PreferRange(754..=765)).await.unwrap(), V1_19_2);
+    let port = versioned_server(9999, "Paper 2.0").await;
+    assert_eq!(select_version("127.0.0.1", port, &VersionSelection::PreferRange(760..=780)).await.unwrap(), V1_21_1);
+
+    assert_eq!(select_version("127.0.0.1", 1, &VersionSelection::Exact(763)).await.unwrap(), V1_20_1);
+    assert!(matches!(select_version("127.0.0.1", 1, &VersionSelection::Exact(47)).await, Err(Error::Other(_))));
+
+    // Custom versions skip the registry and the probe
+    let snapshot = VersionInfo::new("24w14a", 0x40000000 | 190);
+    assert_eq!(VersionSelection::from(&snapshot), VersionSelection::Custom(snapshot));
+    assert_eq!(select_version("127.0.0.1", 1, &snapshot.into()).await.unwrap(), snapshot);
+    assert_eq!(VersionSelection::from(V1_20_1), VersionSelection::Exact(763));
+
+    let reported = StatusVersion {
+        name: "1.8.9".to_string(),
+        protocol: 47
+    };
+    assert!(matches!(VersionSelection::PreferRange(1..=100).select(Some(&reported)), Err(Error::Other(_))));
+    assert!(matches!(VersionSelection::Auto.select(None), Err(Error::Other(_))));
+}
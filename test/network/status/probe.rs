@@ -0,0 +1,80 @@
+use std::{io, time::Duration};
+
+use sha1::{Digest, Sha1};
+use tokio::net::{TcpListener, UdpSocket};
+
+use crate::{
+    network::{
+        buffer::Buffer,
+        connection::{async_socket::AsyncSocketConnection, pipeline::framing::FrameEncoder, Pipeline, Server},
+        handshake::{Handshake, NextState},
+        status::{probe, Probe, ProbeOptions},
+        versions::V1_20_1,
+        ByteOrder, Error, PacketState
+    },
+    test::mock::{MockResponse, MockServer},
+    webapi::Endpoints
+};
+
+const STATUS: &str = include_str!("../../fixtures/network/status/status.json");
+
+async fn status_server() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        let pipeline = Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing"));
+        let mut connection = AsyncSocketConnection::<Server>::new(listener.accept().await.unwrap().0, pipeline);
+
+        let handshake = Handshake::read(&mut connection.read_packet().await.unwrap()).unwrap();
+        assert_eq!(handshake.protocol_version, V1_20_1.protocol);
+        assert_eq!(handshake.next_state, NextState::Status);
+        connection.set_state(PacketState::Status);
+
+        assert_eq!(connection.read_packet().await.unwrap().read_var_i32().unwrap(), 0x00);
+        let mut response = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        response.write_var_i32(0x00).unwrap();
+        response.write_str(STATUS).unwrap();
+        connection.write(response).await.unwrap();
+
+        let mut ping = connection.read_packet().await.unwrap();
+        assert_eq!(ping.read_var_i32().unwrap(), 0x01);
+        let mut pong = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        pong.write_var_i32(0x01).unwrap();
+        pong.write_i64(ping.read_i64().unwrap()).unwrap();
+        connection.write(pong).await.unwrap();
+    });
+    port
+}
+
+fn hash(address: &str) -> String {
+    Sha1::digest(address.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[tokio::test]
+async fn test_probe_partial_failure() {
+    let port = status_server().await;
+    let silent_query = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let session_server = MockServer::builder()
+        .get("/blockedservers", MockResponse::ok(format!("{}\n{}", hash("example.com"), hash("127.0.*"))))
+        .start();
+
+    let options = ProbeOptions::new()
+        .version(&V1_20_1)
+        .timeout(Duration::from_millis(500))
+        .query(true)
+        .query_port(silent_query.local_addr().unwrap().port())
+        .blocklist(true)
+        .endpoints(Endpoints::new(session_server.url(), session_server.url(), session_server.url()));
+    let probe = probe(&format!("127.0.0.1:{}", port), options).await.unwrap();
+
+    assert_eq!(probe.host, "127.0.0.1");
+    assert_eq!(probe.port, port);
+    assert_eq!(probe.status.as_ref().unwrap().motd(), "A Minecraft Server");
+    assert!(probe.latency.is_some());
+    assert_eq!(probe.blocked, Some(true));
+    assert!(probe.query.is_none());
+    assert!(probe.legacy.is_none());
+    assert_eq!(probe.errors.len(), 1);
+    assert!(matches!(probe.error(Probe::Query), Some(Error::IoError(error)) if error.kind() == io::ErrorKind::TimedOut));
+}
@@ -0,0 +1,113 @@
+use crate::network::{
+    buffer::Buffer,
+    connection::{Readable, Writable},
+    tags::{packet_id, TagRegistry, MAX_TAGS},
+    types::Identifier,
+    ByteOrder, Error, PacketState
+};
+
+fn identifier(identifier: &str) -> Identifier {
+    Identifier::parse(identifier).unwrap()
+}
+
+fn write_tags(buffer: &mut Buffer, tags: &[(&str, &[i32])]) {
+    buffer.write_var_i32(tags.len() as i32).unwrap();
+    for (tag, ids) in tags {
+        buffer.write_str(tag).unwrap();
+        buffer.write_var_i32(ids.len() as i32).unwrap();
+        ids.iter().for_each(|id| buffer.write_var_i32(*id).unwrap());
+    }
+}
+
+fn vanilla_tags() -> Buffer {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(3).unwrap();
+    buffer.write_str("minecraft:block").unwrap();
+    write_tags(
+        &mut buffer,
+        &[("minecraft:logs", &[46, 47, 48, 49, 50, 51, 52, 53]), ("minecraft:mineable/pickaxe", &[1, 2, 3, 4, 5, 6, 7])]
+    );
+    buffer.write_str("minecraft:item").unwrap();
+    write_tags(&mut buffer, &[("minecraft:planks", &[36, 37, 38, 39, 40, 41, 42, 43, 44, 45])]);
+    buffer.write_str("minecraft:fluid").unwrap();
+    write_tags(&mut buffer, &[("minecraft:water", &[1, 2]), ("minecraft:lava", &[3, 4])]);
+    buffer.reset();
+    buffer
+}
+
+#[test]
+fn test_vanilla_tags() {
+    let tags = TagRegistry::decode(&mut vanilla_tags()).unwrap();
+    assert_eq!(tags.registries().len(), 3);
+    assert!(tags.contains(&identifier("block"), &identifier("logs"), 46));
+    assert!(!tags.contains(&identifier("block"), &identifier("logs"), 1));
+    assert!(tags.contains(&identifier("minecraft:block"), &identifier("minecraft:mineable/pickaxe"), 1));
+    assert!(!tags.contains(&identifier("item"), &identifier("logs"), 46));
+    assert_eq!(tags.tag(&identifier("fluid"), &identifier("water")), Some(&[1, 2][..]));
+    assert_eq!(tags.registry(&identifier("item")).unwrap().len(), 1);
+    assert!(tags.registry(&identifier("entity_type")).is_none());
+}
+
+#[test]
+fn test_tags_roundtrip() {
+    let mut tags = TagRegistry::new();
+    tags.insert(identifier("block"), identifier("logs"), vec![46, 47]);
+    tags.insert(identifier("block"), identifier("custom:ores"), Vec::new());
+    tags.insert(identifier("custom:spells"), identifier("custom:fire"), vec![0, 300, i32::MAX]);
+
+    let mut buffer = tags.write(Buffer::empty(true, Some(ByteOrder::BigEndian))).unwrap();
+    buffer.reset();
+    assert_eq!(TagRegistry::decode(&mut buffer).unwrap(), tags);
+
+    assert!(tags.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), 754).is_err());
+    let mut legacy = TagRegistry::new();
+    legacy.insert(identifier("block"), identifier("logs"), vec![46, 47]);
+    legacy.insert(identifier("entity_type"), identifier("skeletons"), vec![5]);
+    let mut buffer = legacy.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), 754).unwrap();
+    buffer.reset();
+
+    let read = TagRegistry::read_versioned(&mut buffer, 754).unwrap();
+    assert_eq!(buffer.remaining(), 0);
+    assert_eq!(read.registries().len(), 4);
+    assert!(read.contains(&identifier("entity_type"), &identifier("skeletons"), 5));
+    assert!(read.registry(&identifier("item")).unwrap().is_empty());
+}
+
+#[test]
+fn test_tags_apply_replaces_registries() {
+    let mut tags = TagRegistry::decode(&mut vanilla_tags()).unwrap();
+    let mut update = TagRegistry::new();
+    update.insert(identifier("block"), identifier("logs"), vec![1]);
+    tags.apply(update);
+
+    assert!(tags.contains(&identifier("block"), &identifier("logs"), 1));
+    assert!(tags.tag(&identifier("block"), &identifier("mineable/pickaxe")).is_none());
+    assert!(tags.contains(&identifier("item"), &identifier("planks"), 36));
+}
+
+#[test]
+fn test_tags_count_limits() {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(1).unwrap();
+    buffer.write_str("minecraft:block").unwrap();
+    buffer.write_var_i32(MAX_TAGS as i32 + 1).unwrap();
+    buffer.reset();
+    assert!(matches!(TagRegistry::read(&mut buffer), Err(Error::Decode { offset: 17, .. })));
+
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(-1).unwrap();
+    buffer.reset();
+    assert!(matches!(TagRegistry::read(&mut buffer), Err(Error::Decode { offset: 0, .. })));
+
+    let mut tags = TagRegistry::new();
+    (0..=MAX_TAGS).for_each(|tag| tags.insert(identifier("block"), identifier(&format!("tag_{}", tag)), Vec::new()));
+    assert!(tags.write(Buffer::empty(true, Some(ByteOrder::BigEndian))).is_err());
+}
+
+#[test]
+fn test_tags_packet_id() {
+    assert_eq!(packet_id(PacketState::Play, 754), Some(0x5B));
+    assert_eq!(packet_id(PacketState::Play, 767), Some(0x78));
+    assert_eq!(packet_id(PacketState::Configuration, 766), Some(0x0D));
+    assert_eq!(packet_id(PacketState::Configuration, 763), None);
+}
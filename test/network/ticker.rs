@@ -0,0 +1,73 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant}
+};
+
+use tokio::time::MissedTickBehavior;
+
+use crate::network::{ticker::Ticker, Error};
+
+#[test]
+fn test_ticker_cadence_and_fall_behind() {
+    let behind = Arc::new(Mutex::new(Vec::new()));
+    let reported = behind.clone();
+    let mut ticker = Ticker::<Vec<(char, u64)>>::new()
+        .with_behind_threshold(Duration::from_millis(100))
+        .on_behind(move |tick, lag| reported.lock().unwrap().push((tick, lag)))
+        .every(1, |tick, calls| {
+            calls.push(('a', tick));
+            Ok(())
+        })
+        .every(4, |tick, calls| {
+            calls.push(('b', tick));
+            Ok(())
+        });
+
+    // The clock is faked, every tick runs 10ms late except the sixth one which runs 150ms late
+    let start = Instant::now();
+    let mut calls = Vec::new();
+    for tick in 0..9 {
+        let scheduled = start + ticker.period() * tick;
+        let lag = Duration::from_millis(if tick == 5 { 150 } else { 10 });
+        assert_eq!(ticker.tick(scheduled, scheduled + lag, &mut calls).unwrap(), tick as u64);
+    }
+
+    let b: Vec<_> = calls.iter().filter(|(name, _)| *name == 'b').map(|(_, tick)| *tick).collect();
+    assert_eq!(b, vec![0, 4, 8]);
+    assert_eq!(calls.iter().filter(|(name, _)| *name == 'a').count(), 9);
+    assert_eq!(*behind.lock().unwrap(), vec![(5, Duration::from_millis(150))]);
+}
+
+#[test]
+fn test_ticker_missed_ticks() {
+    let start = Instant::now();
+    let late = start + Duration::from_millis(175);
+    let deadline = |behavior| Ticker::<()>::new().with_missed_tick_behavior(behavior).next_deadline(start, late);
+
+    assert_eq!(deadline(MissedTickBehavior::Burst), start + Duration::from_millis(50));
+    assert_eq!(deadline(MissedTickBehavior::Delay), late + Duration::from_millis(50));
+    assert_eq!(deadline(MissedTickBehavior::Skip), start + Duration::from_millis(200));
+
+    let ticker = Ticker::<()>::new().with_missed_tick_behavior(MissedTickBehavior::Delay);
+    assert_eq!(ticker.next_deadline(start, start + Duration::from_millis(20)), start + Duration::from_millis(50));
+}
+
+#[test]
+fn test_ticker_run_blocking() {
+    let ticker = Ticker::<u64>::new().with_period(Duration::from_millis(1));
+    let stop = ticker.stop_handle();
+    let mut ticker = ticker.every(1, move |tick, count| {
+        *count += 1;
+        if tick == 4 {
+            stop.stop();
+        }
+        Ok(())
+    });
+
+    let mut count = 0;
+    ticker.run_blocking(&mut count).unwrap();
+    assert_eq!(count, 5);
+
+    let mut ticker = Ticker::<u64>::new().every(1, |_, _| Err(Error::Other("stop".to_string())));
+    assert!(matches!(ticker.run_blocking(&mut count), Err(Error::Other(_))));
+}
@@ -0,0 +1,138 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    network::{
+        buffer::Buffer,
+        chunk::{
+            export::ChunkStore,
+            update::{pack_section_position, BlockChange, SectionBlocksUpdate},
+            ChunkColumn, ChunkSection, PalettedContainer
+        },
+        connection::Writable,
+        nbt::Compound,
+        registry_codec::{DimensionType, RegistryCodec},
+        tracker::{BlockRegion, BlockWatcher},
+        types::Identifier,
+        versions::V1_20_6,
+        ByteOrder
+    },
+    test::mock::feed
+};
+
+const BLOCK_UPDATE: i32 = 0x09;
+const SECTION_BLOCKS_UPDATE: i32 = 0x49;
+const STONE: i32 = 1;
+const GLASS: i32 = 187;
+
+type Changes = Arc<Mutex<Vec<((i32, i32, i32), Option<i32>, i32)>>>;
+
+fn overworld() -> DimensionType {
+    RegistryCodec::vanilla().dimension_type(&Identifier::parse("minecraft:overworld").unwrap()).unwrap()
+}
+
+fn block_update(x: i32, y: i32, z: i32, state: i32) -> Buffer {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(BLOCK_UPDATE).unwrap();
+    let mut buffer = BlockChange { x, y, z, state }.write(buffer).unwrap();
+    buffer.reset();
+    buffer
+}
+
+fn section_blocks_update(update: &SectionBlocksUpdate) -> Buffer {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(SECTION_BLOCKS_UPDATE).unwrap();
+    let mut buffer = update.write_versioned(buffer, V1_20_6.protocol).unwrap();
+    buffer.reset();
+    buffer
+}
+
+fn watcher(region: BlockRegion) -> (BlockWatcher, Changes) {
+    let changes = Changes::default();
+    let recorded = changes.clone();
+    let mut watcher = BlockWatcher::new(&V1_20_6);
+    watcher.watch(region, move |position, old, new| recorded.lock().unwrap().push((position, old, new)));
+    (watcher, changes)
+}
+
+#[test]
+fn test_block_watcher_chunk_boundaries() {
+    let (mut watcher, changes) = watcher(BlockRegion::new((16, 70, 16), (15, 60, 15)));
+
+    let mut update = SectionBlocksUpdate::new(0, 4, 0);
+    update.push(15, 0, 15, GLASS).unwrap();
+    update.push(14, 0, 15, GLASS).unwrap();
+    update.push(15, 15, 15, GLASS).unwrap();
+    assert!(feed(section_blocks_update(&update), |packet| watcher.handle(packet, None)));
+
+    let mut update = SectionBlocksUpdate::new(1, 4, 1);
+    update.push(0, 1, 0, STONE).unwrap();
+    update.push(1, 1, 0, STONE).unwrap();
+    assert!(feed(section_blocks_update(&update), |packet| watcher.handle(packet, None)));
+
+    assert!(feed(block_update(16, 60, 15, STONE), |packet| watcher.handle(packet, None)));
+    assert!(feed(block_update(16, 59, 15, STONE), |packet| watcher.handle(packet, None)));
+    assert!(feed(block_update(17, 60, 15, STONE), |packet| watcher.handle(packet, None)));
+
+    assert_eq!(
+        *changes.lock().unwrap(),
+        vec![((15, 64, 15), None, GLASS), ((16, 65, 16), None, STONE), ((16, 60, 15), None, STONE)]
+    );
+
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(SECTION_BLOCKS_UPDATE).unwrap();
+    buffer.write_i64(pack_section_position(2, 4, 0)).unwrap();
+    buffer.reset();
+    assert!(feed(buffer, |packet| watcher.handle(packet, None)));
+    assert_eq!(changes.lock().unwrap().len(), 3);
+
+    let mut other = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    other.write_var_i32(0x27).unwrap();
+    other.reset();
+    assert!(!watcher.handle(&mut other, None).unwrap());
+}
+
+#[test]
+fn test_block_watcher_old_states_from_store() {
+    let mut column = ChunkColumn::new(0, 0, &overworld(), Compound::new());
+    column.sections[4] = ChunkSection::new(PalettedContainer::single(STONE), PalettedContainer::single(0));
+    let mut packet = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    packet.write_var_i32(0x27).unwrap();
+    let mut packet = column.write_versioned(packet, V1_20_6.protocol).unwrap();
+    packet.reset();
+
+    let mut store = ChunkStore::new(&V1_20_6, overworld());
+    assert!(store.handle(&mut packet).unwrap());
+
+    let (mut watcher, changes) = watcher(BlockRegion::new((-16, 0, 0), (15, 15, 15)));
+    let second = changes.clone();
+    let id = watcher.watch(BlockRegion::new((0, 0, 0), (0, 0, 0)), move |position, old, new| {
+        second.lock().unwrap().push((position, old, new))
+    });
+
+    for state in [GLASS, GLASS, STONE] {
+        let mut packet = block_update(0, 0, 0, state);
+        assert!(feed(packet.clone(), |packet| watcher.handle(packet, Some(&store))));
+        assert!(store.handle(&mut packet).unwrap());
+    }
+    assert_eq!(store.block(0, 0, 0), Some(STONE));
+    assert_eq!(
+        *changes.lock().unwrap(),
+        vec![
+            ((0, 0, 0), Some(STONE), GLASS),
+            ((0, 0, 0), Some(STONE), GLASS),
+            ((0, 0, 0), Some(GLASS), STONE),
+            ((0, 0, 0), Some(GLASS), STONE)
+        ]
+    );
+
+    assert!(watcher.unwatch(id));
+    assert!(!watcher.unwatch(id));
+    changes.lock().unwrap().clear();
+    let mut update = SectionBlocksUpdate::new(0, 0, 0);
+    update.push(1, 2, 3, GLASS).unwrap();
+    assert!(feed(section_blocks_update(&update), |packet| watcher.handle(packet, Some(&store))));
+    let mut update = SectionBlocksUpdate::new(-1, 0, 0);
+    update.push(15, 0, 0, GLASS).unwrap();
+    assert!(feed(section_blocks_update(&update), |packet| watcher.handle(packet, Some(&store))));
+    assert_eq!(*changes.lock().unwrap(), vec![((1, 2, 3), Some(STONE), GLASS), ((-1, 0, 0), None, GLASS)]);
+}
@@ -0,0 +1,125 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    network::{
+        buffer::Buffer,
+        connection::Connection,
+        tracker::{Abilities, ClientStateMirror, Difficulty, SessionState, StateChange, ABILITY_ALLOW_FLYING, ABILITY_FLYING, ABILITY_INSTANT_BREAK, ABILITY_INVULNERABLE},
+        versions::{V1_16_5, V1_17_1, V1_20_4},
+        ByteOrder, Error
+    },
+    test::mock::{feed, MockConnection}
+};
+
+fn packet(id: i32, write: impl FnOnce(&mut Buffer)) -> Buffer {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(id).unwrap();
+    write(&mut buffer);
+    buffer.reset();
+    buffer
+}
+
+fn abilities_packet(id: i32, abilities: &Abilities) -> Buffer {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(id).unwrap();
+    let mut buffer = abilities.write(buffer).unwrap();
+    buffer.reset();
+    buffer
+}
+
+#[test]
+fn test_ability_flags() {
+    for flags in 0..16 {
+        assert_eq!(Abilities::from_flags(flags, 0.05, 0.1).flags(), flags);
+    }
+
+    let abilities = Abilities::from_flags(ABILITY_INVULNERABLE | ABILITY_ALLOW_FLYING | ABILITY_INSTANT_BREAK | 0x10, 0.1, 0.2);
+    assert!(abilities.invulnerable && abilities.allow_flying && abilities.instant_break && !abilities.flying);
+    assert_eq!(abilities.flags(), 0x0D);
+
+    let mut buffer = abilities.write(Buffer::empty(true, Some(ByteOrder::BigEndian))).unwrap();
+    buffer.reset();
+    assert_eq!(Abilities::read(&mut buffer).unwrap(), abilities);
+
+    let mut serverbound = Abilities { flying: true, ..abilities }.write_serverbound(V1_20_4.protocol).unwrap();
+    serverbound.reset();
+    assert_eq!(serverbound.read_var_i32().unwrap(), 0x20);
+    assert_eq!(serverbound.read_u8().unwrap(), ABILITY_FLYING);
+    assert_eq!(serverbound.remaining(), 0);
+}
+
+#[test]
+fn test_client_state_changes() {
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let recorded = changes.clone();
+    let mut mirror = ClientStateMirror::new(&V1_20_4).on_change(move |change| recorded.lock().unwrap().push(*change));
+    assert_eq!(mirror.difficulty(), None);
+
+    assert!(feed(
+        packet(0x0B, |buffer| {
+            buffer.write_u8(3).unwrap();
+            buffer.write_bool(true).unwrap();
+        }),
+        |packet| mirror.handle(packet)
+    ));
+    assert!(feed(packet(0x51, |buffer| buffer.write_u8(4).unwrap()), |packet| mirror.handle(packet)));
+    assert!(feed(packet(0x53, |buffer| buffer.write_var_i32(12).unwrap()), |packet| mirror.handle(packet)));
+    assert!(feed(packet(0x60, |buffer| buffer.write_var_i32(8).unwrap()), |packet| mirror.handle(packet)));
+    assert!(feed(packet(0x60, |buffer| buffer.write_var_i32(8).unwrap()), |packet| mirror.handle(packet)));
+    assert!(!feed(packet(0x24, |buffer| buffer.write_i64(1).unwrap()), |packet| mirror.handle(packet)));
+
+    assert_eq!((mirror.difficulty(), mirror.is_difficulty_locked()), (Some(Difficulty::Hard), true));
+    assert_eq!((mirror.held_item(), mirror.view_distance(), mirror.simulation_distance()), (Some(4), Some(12), Some(8)));
+
+    assert_eq!(
+        *changes.lock().unwrap(),
+        vec![
+            StateChange::Difficulty {
+                difficulty: Difficulty::Hard,
+                locked: true
+            },
+            StateChange::HeldItem(4),
+            StateChange::ViewDistance(12),
+            StateChange::SimulationDistance(8)
+        ]
+    );
+
+    mirror.reset_for_reconnect();
+    assert_eq!(mirror.simulation_distance(), None);
+
+    // 1.17 has no simulation distance, its id belongs to another packet there
+    let mut mirror = ClientStateMirror::new(&V1_17_1);
+    assert!(feed(packet(0x4A, |buffer| buffer.write_var_i32(10).unwrap()), |packet| mirror.handle(packet)));
+    assert_eq!(mirror.view_distance(), Some(10));
+    assert!(!feed(packet(0x57, |buffer| buffer.write_var_i32(10).unwrap()), |packet| mirror.handle(packet)));
+    assert_eq!(mirror.simulation_distance(), None);
+}
+
+#[test]
+fn test_set_flying() {
+    let mut connection = MockConnection::server();
+    let mut mirror = ClientStateMirror::new(&V1_16_5);
+
+    assert!(matches!(mirror.set_flying(&mut connection, true), Err(Error::Other(_))));
+    assert!(feed(abilities_packet(0x30, &Abilities::from_flags(ABILITY_INVULNERABLE, 0.05, 0.1)), |packet| {
+        mirror.handle(packet)
+    }));
+    assert!(mirror.set_flying(&mut connection, true).is_err());
+    assert!(connection.packets.is_empty());
+
+    let creative = Abilities::from_flags(ABILITY_INVULNERABLE | ABILITY_ALLOW_FLYING | ABILITY_INSTANT_BREAK, 0.05, 0.1);
+    assert!(feed(abilities_packet(0x30, &creative), |packet| mirror.handle(packet)));
+    // The packet is written for the protocol of the connection
+    assert!(mirror.set_flying(&mut connection, true).is_err());
+    connection.set_protocol_version(Some(V1_16_5.protocol));
+    mirror.set_flying(&mut connection, true).unwrap();
+    assert!(mirror.abilities().unwrap().flying);
+
+    let packet = &mut connection.packets[0];
+    assert_eq!(packet.read_var_i32().unwrap(), 0x1A);
+    assert_eq!(packet.read_u8().unwrap(), ABILITY_FLYING);
+
+    mirror.set_flying(&mut connection, false).unwrap();
+    assert_eq!(connection.packets[1].to_bytes(), vec![0x1A, 0x00]);
+    assert_eq!(mirror.abilities(), Some(&creative));
+}
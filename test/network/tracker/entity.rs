@@ -0,0 +1,188 @@
+use uuid::Uuid;
+
+use crate::{
+    network::{
+        buffer::Buffer,
+        connection::Writable,
+        tracker::{EntityKind, EntityTracker, EntityUpdate, SessionState},
+        versions::{V1_16_5, V1_19_4, V1_20_6},
+        ByteOrder
+    },
+    test::mock::feed
+};
+
+const ZOMBIE: EntityKind = EntityKind::Type(54);
+
+fn spawn(id: i32, kind: EntityKind, position: (f64, f64, f64)) -> EntityUpdate {
+    EntityUpdate::Spawn {
+        id,
+        uuid: Uuid::from_u128(id as u128),
+        kind,
+        position,
+        yaw: 0.0,
+        pitch: 0.0
+    }
+}
+
+fn moved(id: i32, delta: (i16, i16, i16)) -> EntityUpdate {
+    EntityUpdate::Move {
+        id,
+        delta: Some(delta),
+        rotation: None,
+        on_ground: true
+    }
+}
+
+#[test]
+fn test_delta_arithmetic() {
+    let mut tracker = EntityTracker::new(&V1_20_6);
+    assert!(feed(spawn(7, ZOMBIE, (0.1, 64.0, -3.7)).write_versioned(tracker.protocol()).unwrap(), |packet| {
+        tracker.handle(packet)
+    }));
+
+    // 0.1 * 4096 rounds to 410 and -3.7 * 4096 to -15155, the zero delta keeps y exactly as it was
+    assert!(feed(moved(7, (4096, 0, -2048)).write_versioned(tracker.protocol()).unwrap(), |packet| tracker.handle(packet)));
+    assert_eq!(tracker.get(7).unwrap().position, (4506.0 / 4096.0, 64.0, -17203.0 / 4096.0));
+
+    for _ in 0..1000 {
+        assert!(feed(moved(7, (1, -1, 0)).write_versioned(tracker.protocol()).unwrap(), |packet| tracker.handle(packet)));
+    }
+    assert_eq!(tracker.get(7).unwrap().position, (5506.0 / 4096.0, (64.0 * 4096.0 - 1000.0) / 4096.0, -17203.0 / 4096.0));
+    assert!(tracker.get(7).unwrap().on_ground);
+
+    assert!(feed(
+        EntityUpdate::Move {
+            id: 7,
+            delta: None,
+            rotation: Some((90.0, -45.0)),
+            on_ground: false
+        }
+        .write_versioned(tracker.protocol())
+        .unwrap(),
+        |packet| tracker.handle(packet)
+    ));
+    let zombie = tracker.get(7).unwrap();
+    assert_eq!((zombie.yaw, zombie.pitch, zombie.on_ground), (90.0, 315.0, false));
+    assert_eq!(zombie.position.0, 5506.0 / 4096.0);
+
+    assert!(feed(
+        EntityUpdate::Teleport {
+            id: 7,
+            position: (10.3, 70.0, 10.5),
+            yaw: 180.0,
+            pitch: 0.0,
+            on_ground: true
+        }
+        .write_versioned(tracker.protocol())
+        .unwrap(),
+        |packet| tracker.handle(packet)
+    ));
+    assert_eq!(tracker.get(7).unwrap().position, (10.3, 70.0, 10.5));
+
+    // 10.3 * 4096 = 42188.8 rounds to 42189
+    assert!(feed(
+        EntityUpdate::Move {
+            id: 7,
+            delta: Some((-1, 2048, 0)),
+            rotation: Some((0.0, 0.0)),
+            on_ground: true
+        }
+        .write_versioned(tracker.protocol())
+        .unwrap(),
+        |packet| tracker.handle(packet)
+    ));
+    assert_eq!(tracker.get(7).unwrap().position, (42188.0 / 4096.0, 70.5, 10.5));
+
+    assert!(feed(moved(8, (4096, 0, 0)).write_versioned(tracker.protocol()).unwrap(), |packet| tracker.handle(packet)));
+    assert_eq!(tracker.len(), 1);
+}
+
+#[test]
+fn test_respawn_clears_everything() {
+    let mut tracker = EntityTracker::new(&V1_20_6);
+    assert!(feed(spawn(1, ZOMBIE, (0.0, 64.0, 0.0)).write_versioned(tracker.protocol()).unwrap(), |packet| {
+        tracker.handle(packet)
+    }));
+    assert!(feed(spawn(2, EntityKind::Player, (5.0, 64.0, 5.0)).write_versioned(tracker.protocol()).unwrap(), |packet| {
+        tracker.handle(packet)
+    }));
+    assert!(feed(spawn(3, ZOMBIE, (9.0, 64.0, 9.0)).write_versioned(tracker.protocol()).unwrap(), |packet| {
+        tracker.handle(packet)
+    }));
+    assert!(feed(EntityUpdate::Remove(vec![3, 42]).write_versioned(tracker.protocol()).unwrap(), |packet| {
+        tracker.handle(packet)
+    }));
+    assert_eq!(tracker.len(), 2);
+
+    let mut respawn = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    respawn.write_var_i32(0x47).unwrap();
+    respawn.write_var_i32(0).unwrap();
+    respawn.write_str("minecraft:overworld").unwrap();
+    respawn.reset();
+    assert!(tracker.handle(&mut respawn).unwrap());
+    assert!(tracker.is_empty());
+
+    assert!(feed(moved(1, (4096, 0, 0)).write_versioned(tracker.protocol()).unwrap(), |packet| tracker.handle(packet)));
+    assert!(tracker.get(1).is_none());
+}
+
+#[test]
+fn test_nearest() {
+    let mut tracker = EntityTracker::new(&V1_19_4);
+    assert!(feed(spawn(1, ZOMBIE, (30.0, 64.0, 0.0)).write_versioned(tracker.protocol()).unwrap(), |packet| {
+        tracker.handle(packet)
+    }));
+    assert!(feed(spawn(2, ZOMBIE, (-10.0, 60.0, 5.0)).write_versioned(tracker.protocol()).unwrap(), |packet| {
+        tracker.handle(packet)
+    }));
+    assert!(feed(spawn(3, EntityKind::Player, (100.0, 64.0, 100.0)).write_versioned(tracker.protocol()).unwrap(), |packet| {
+        tracker.handle(packet)
+    }));
+
+    assert_eq!(tracker.nearest(ZOMBIE, (0.0, 64.0, 0.0)).map(|(id, _)| id), Some(2));
+    assert_eq!(tracker.nearest(ZOMBIE, (25.0, 64.0, 0.0)).map(|(id, _)| id), Some(1));
+    let (id, player) = tracker.nearest(EntityKind::Player, (0.0, 0.0, 0.0)).unwrap();
+    assert_eq!((id, player.uuid), (3, Uuid::from_u128(3)));
+    assert!(tracker.nearest(EntityKind::Type(1), (0.0, 0.0, 0.0)).is_none());
+}
+
+#[test]
+fn test_legacy_spawn_living_entity() {
+    let mut packet = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    packet.write_var_i32(0x02).unwrap();
+    packet.write_var_i32(12).unwrap();
+    let mut packet = Uuid::from_u128(12).write(packet).unwrap();
+    packet.write_var_i32(102).unwrap();
+    for value in [1.5, 65.0, -2.5] {
+        packet.write_u64(f64::to_bits(value)).unwrap();
+    }
+    for angle in [64, 32, 64] {
+        packet.write_u8(angle).unwrap();
+    }
+    packet.reset();
+
+    let mut tracker = EntityTracker::new(&V1_16_5);
+    assert!(tracker.handle(&mut packet).unwrap());
+    let entity = tracker.get(12).unwrap();
+    assert_eq!((entity.kind, entity.position, entity.yaw, entity.pitch), (EntityKind::Type(102), (1.5, 65.0, -2.5), 90.0, 45.0));
+}
+
+#[test]
+fn test_reconnect_drops_entities() {
+    let mut tracker = EntityTracker::new(&V1_20_6);
+    assert!(feed(spawn(1, ZOMBIE, (0.0, 64.0, 0.0)).write_versioned(tracker.protocol()).unwrap(), |packet| {
+        tracker.handle(packet)
+    }));
+    assert!(feed(spawn(2, EntityKind::Player, (5.0, 64.0, 5.0)).write_versioned(tracker.protocol()).unwrap(), |packet| {
+        tracker.handle(packet)
+    }));
+    tracker.reset_for_reconnect();
+    assert!(tracker.is_empty());
+
+    assert!(feed(moved(1, (4096, 0, 0)).write_versioned(tracker.protocol()).unwrap(), |packet| tracker.handle(packet)));
+    assert!(tracker.get(1).is_none());
+    assert!(feed(spawn(1, EntityKind::Player, (3.0, 64.0, 3.0)).write_versioned(tracker.protocol()).unwrap(), |packet| {
+        tracker.handle(packet)
+    }));
+    assert_eq!(tracker.get(1).unwrap().position, (3.0, 64.0, 3.0));
+}
@@ -0,0 +1,241 @@
+use crate::{
+    components::Component,
+    network::{
+        buffer::Buffer,
+        connection::Connection,
+        tracker::{InventoryTracker, InventoryUpdate, SessionState, CARRIED_SLOT, CLICK_PICKUP, OUTSIDE_SLOT, PLAYER_INVENTORY_SIZE},
+        types::Slot,
+        versions::{V1_20_4, V1_20_6}
+    },
+    test::mock::{feed, MockConnection}
+};
+
+const STONE: i32 = 1;
+const DIRT: i32 = 10;
+
+#[derive(Debug, PartialEq)]
+struct Click {
+    window_id: u8,
+    state_id: i32,
+    slot: i16,
+    button: i8,
+    changed: Vec<(i16, Slot)>,
+    carried: Slot
+}
+
+fn read_click(buffer: &mut Buffer, protocol: i32) -> Click {
+    assert_eq!(buffer.read_var_i32().unwrap(), 0x0D);
+    let (window_id, state_id, slot, button) = (buffer.read_u8().unwrap(), buffer.read_var_i32().unwrap(), buffer.read_i16().unwrap(), buffer.read_i8().unwrap());
+    assert_eq!(buffer.read_var_i32().unwrap(), CLICK_PICKUP);
+    let changed = (0..buffer.read_length().unwrap())
+        .map(|_| (buffer.read_i16().unwrap(), Slot::read_versioned(buffer, protocol).unwrap()))
+        .collect();
+    let click = Click {
+        window_id,
+        state_id,
+        slot,
+        button,
+        changed,
+        carried: Slot::read_versioned(buffer, protocol).unwrap()
+    };
+    assert_eq!(buffer.remaining(), 0);
+    click
+}
+
+fn chest_content(state_id: i32) -> InventoryUpdate {
+    let mut slots = vec![Slot::empty(); 63];
+    slots[0] = Slot::new(STONE, 16);
+    slots[54] = Slot::new(DIRT, 5);
+    InventoryUpdate::Content {
+        window_id: 3,
+        state_id,
+        slots,
+        carried: Slot::empty()
+    }
+}
+
+#[test]
+fn test_chest_click_sequence() {
+    let protocol = V1_20_4.protocol;
+    let mut connection = MockConnection::server();
+    connection.set_protocol_version(Some(protocol));
+    let mut tracker = InventoryTracker::new(&V1_20_4);
+    assert!(feed(
+        InventoryUpdate::Open {
+            window_id: 3,
+            kind: 2,
+            title: Component::text("Chest")
+        }
+        .write_versioned(tracker.protocol())
+        .unwrap(),
+        |packet| tracker.handle(packet)
+    ));
+    assert!(feed(chest_content(7).write_versioned(tracker.protocol()).unwrap(), |packet| tracker.handle(packet)));
+    assert_eq!(tracker.window().unwrap().container_size(), 27);
+    assert_eq!(tracker.inventory()[36], Slot::new(DIRT, 5));
+    assert_eq!(tracker.state_id(), 7);
+
+    tracker.click(&mut connection, 0, 0, CLICK_PICKUP).unwrap();
+    assert_eq!(
+        read_click(&mut connection.packets.remove(0), protocol),
+        Click {
+            window_id: 3,
+            state_id: 7,
+            slot: 0,
+            button: 0,
+            changed: vec![(0, Slot::empty())],
+            carried: Slot::new(STONE, 16)
+        }
+    );
+    assert_eq!(tracker.slots()[0], Slot::empty());
+
+    tracker.click(&mut connection, 27, 1, CLICK_PICKUP).unwrap();
+    assert_eq!(
+        read_click(&mut connection.packets.remove(0), protocol),
+        Click {
+            window_id: 3,
+            state_id: 7,
+            slot: 27,
+            button: 1,
+            changed: vec![(27, Slot::new(STONE, 1))],
+            carried: Slot::new(STONE, 15)
+        }
+    );
+    assert_eq!(tracker.inventory()[9], Slot::new(STONE, 1));
+
+    tracker.click(&mut connection, 54, 0, CLICK_PICKUP).unwrap();
+    let click = read_click(&mut connection.packets.remove(0), protocol);
+    assert_eq!((click.changed, click.carried), (vec![(54, Slot::new(STONE, 15))], Slot::new(DIRT, 5)));
+
+    assert!(feed(
+        InventoryUpdate::Slot {
+            window_id: 3,
+            state_id: 8,
+            slot: 54,
+            item: Slot::new(STONE, 8)
+        }
+        .write_versioned(tracker.protocol())
+        .unwrap(),
+        |packet| tracker.handle(packet)
+    ));
+    assert!(feed(
+        InventoryUpdate::Slot {
+            window_id: 3,
+            state_id: 8,
+            slot: 0,
+            item: Slot::new(STONE, 7)
+        }
+        .write_versioned(tracker.protocol())
+        .unwrap(),
+        |packet| tracker.handle(packet)
+    ));
+    assert!(feed(
+        InventoryUpdate::Slot {
+            window_id: -1,
+            state_id: 8,
+            slot: CARRIED_SLOT,
+            item: Slot::new(DIRT, 5)
+        }
+        .write_versioned(tracker.protocol())
+        .unwrap(),
+        |packet| tracker.handle(packet)
+    ));
+
+    let mut server = vec![Slot::empty(); 63];
+    server[0] = Slot::new(STONE, 7);
+    server[27] = Slot::new(STONE, 1);
+    server[54] = Slot::new(STONE, 8);
+    assert_eq!(tracker.slots(), server);
+    assert_eq!(tracker.inventory()[36], Slot::new(STONE, 8));
+    assert_eq!(tracker.carried(), &Slot::new(DIRT, 5));
+
+    tracker.click(&mut connection, OUTSIDE_SLOT, 1, CLICK_PICKUP).unwrap();
+    let click = read_click(&mut connection.packets.remove(0), protocol);
+    assert_eq!((click.state_id, click.changed, click.carried), (8, Vec::new(), Slot::new(DIRT, 4)));
+
+    assert!(feed(InventoryUpdate::Close { window_id: 3 }.write_versioned(tracker.protocol()).unwrap(), |packet| {
+        tracker.handle(packet)
+    }));
+    assert!(tracker.window().is_none());
+    assert_eq!(tracker.slots()[9], Slot::new(STONE, 1));
+    assert_eq!(tracker.slots()[36], Slot::new(STONE, 8));
+}
+
+#[test]
+fn test_click_errors() {
+    let mut connection = MockConnection::server();
+    connection.set_protocol_version(Some(V1_20_6.protocol));
+    let mut tracker = InventoryTracker::new(&V1_20_6);
+    assert!(tracker.click(&mut connection, 46, 0, CLICK_PICKUP).is_err());
+    assert!(tracker.click(&mut connection, 9, 0, 1).is_err());
+    assert!(tracker.click(&mut connection, 9, 2, CLICK_PICKUP).is_err());
+    assert!(connection.packets.is_empty());
+
+    for (slot, count) in [(9, 60), (10, 10)] {
+        assert!(feed(
+            InventoryUpdate::Slot {
+                window_id: -2,
+                state_id: 1,
+                slot,
+                item: Slot::new(STONE, count)
+            }
+            .write_versioned(tracker.protocol())
+            .unwrap(),
+            |packet| tracker.handle(packet)
+        ));
+    }
+    tracker.click(&mut connection, 10, 0, CLICK_PICKUP).unwrap();
+    tracker.click(&mut connection, 9, 0, CLICK_PICKUP).unwrap();
+    assert_eq!((&tracker.inventory()[9], tracker.carried()), (&Slot::new(STONE, 64), &Slot::new(STONE, 6)));
+    assert_eq!(connection.packets.len(), 2);
+}
+
+#[test]
+fn test_reconnect_closes_window() {
+    let mut connection = MockConnection::server();
+    connection.set_protocol_version(Some(V1_20_4.protocol));
+    let mut tracker = InventoryTracker::new(&V1_20_4);
+    assert!(feed(
+        InventoryUpdate::Open {
+            window_id: 3,
+            kind: 2,
+            title: Component::text("Chest")
+        }
+        .write_versioned(tracker.protocol())
+        .unwrap(),
+        |packet| tracker.handle(packet)
+    ));
+    assert!(feed(chest_content(7).write_versioned(tracker.protocol()).unwrap(), |packet| tracker.handle(packet)));
+    tracker.click(&mut connection, 0, 0, CLICK_PICKUP).unwrap();
+
+    let snapshot = tracker.snapshot();
+    tracker.reset_for_reconnect();
+    assert_eq!(snapshot.window.as_ref().unwrap().id, 3);
+    assert_eq!(snapshot.carried, Slot::new(STONE, 16));
+    assert_eq!(snapshot.inventory[36], Slot::new(DIRT, 5));
+    assert!(tracker.window().is_none());
+    assert!(tracker.carried().is_empty());
+    assert_eq!(tracker.state_id(), 0);
+    assert!(tracker.inventory().iter().all(Slot::is_empty));
+    assert_eq!(tracker.slots().len(), PLAYER_INVENTORY_SIZE);
+
+    tracker.restore(snapshot.clone());
+    let restored = tracker.snapshot();
+    assert_eq!(restored.inventory, snapshot.inventory);
+    assert!(restored.window.is_none());
+    assert!(restored.carried.is_empty());
+
+    tracker.reset_for_reconnect();
+    assert!(feed(
+        InventoryUpdate::Open {
+            window_id: 4,
+            kind: 2,
+            title: Component::text("Chest")
+        }
+        .write_versioned(tracker.protocol())
+        .unwrap(),
+        |packet| tracker.handle(packet)
+    ));
+    tracker.restore(snapshot);
+    assert!(tracker.inventory().iter().all(Slot::is_empty));
+}
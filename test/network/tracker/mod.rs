@@ -0,0 +1,6 @@
+pub mod block;
+pub mod client_state;
+pub mod entity;
+pub mod inventory;
+pub mod server_info;
+pub mod tab_list;
@@ -0,0 +1,110 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    components::Component,
+    network::{
+        buffer::Buffer,
+        connection::Writable,
+        plugin::{BrandMessage, PluginMessage, BRAND_CHANNEL},
+        tracker::{ServerData, ServerInfo},
+        versions::{V1_16_5, V1_19_2, V1_20_4, V1_20_6, V1_21_1},
+        ByteOrder, PacketState
+    },
+    test::mock::feed
+};
+
+const ICON: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+fn plugin_message(id: i32, channel: &str, brand: &str) -> Buffer {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(id).unwrap();
+    let mut buffer = PluginMessage::encode(channel, &BrandMessage(brand.to_string())).unwrap().write(buffer).unwrap();
+    buffer.reset();
+    buffer
+}
+
+fn server_data() -> ServerData {
+    ServerData {
+        motd: Some(Component::text("A Minecraft Server")),
+        icon: Some(ICON.to_vec()),
+        enforces_secure_chat: true
+    }
+}
+
+#[test]
+fn test_brand_and_server_data() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let (brands, data) = (events.clone(), events.clone());
+    let mut server_info = ServerInfo::new(&V1_20_4)
+        .on_brand(move |previous, brand| brands.lock().unwrap().push(format!("brand {:?} -> {}", previous, brand)))
+        .on_server_data(move |previous, server_data| data.lock().unwrap().push(format!("data {} {}", previous.is_some(), server_data.enforces_secure_chat)));
+    assert!(!server_info.is_modded_brand());
+
+    assert!(feed(plugin_message(0x00, BRAND_CHANNEL, "vanilla"), |packet| {
+        server_info.handle(PacketState::Configuration, packet)
+    }));
+    assert_eq!(server_info.brand(), Some("vanilla"));
+    assert!(!server_info.is_modded_brand());
+    assert!(feed(plugin_message(0x18, BRAND_CHANNEL, "vanilla"), |packet| server_info.handle(PacketState::Play, packet)));
+    assert!(feed(plugin_message(0x18, BRAND_CHANNEL, "Paper"), |packet| server_info.handle(PacketState::Play, packet)));
+    assert!(server_info.is_modded_brand());
+    assert!(!feed(plugin_message(0x18, "bungeecord:main", "Paper"), |packet| server_info.handle(PacketState::Play, packet)));
+
+    let mut packet = server_data().write_versioned(V1_20_4.protocol).unwrap();
+    packet.reset();
+    assert!(feed(packet.clone(), |packet| server_info.handle(PacketState::Play, packet)));
+    assert!(feed(packet, |packet| server_info.handle(PacketState::Play, packet)));
+    assert_eq!(server_info.motd(), Some(&Component::text("A Minecraft Server")));
+    assert_eq!(server_info.icon(), Some(&ICON[..]));
+    assert!(server_info.enforces_secure_chat());
+
+    assert_eq!(*events.lock().unwrap(), vec!["brand None -> vanilla", "brand Some(\"vanilla\") -> Paper", "data false true"]);
+}
+
+#[test]
+fn test_legacy_server_data() {
+    // 1.19.2 sends the icon as a data URL next to the chat preview flag
+    let mut server_info = ServerInfo::new(&V1_19_2);
+    let server_data = ServerData { motd: None, ..server_data() };
+    let mut packet = server_data.write_versioned(V1_19_2.protocol).unwrap();
+    packet.reset();
+    assert_eq!(packet.read_var_i32().unwrap(), 0x42);
+    assert!(!packet.read_bool().unwrap());
+    assert!(packet.read_bool().unwrap());
+    assert!(packet.read_string().unwrap().starts_with("data:image/png;base64,"));
+    packet.reset();
+    assert!(feed(packet, |packet| server_info.handle(PacketState::Play, packet)));
+    assert_eq!(server_info.server_data(), Some(&server_data));
+
+    // 1.16.5 has no Server Data, only the brand is tracked
+    let mut server_info = ServerInfo::new(&V1_16_5);
+    assert!(server_data.write_versioned(V1_16_5.protocol).is_err());
+    assert!(feed(plugin_message(0x17, BRAND_CHANNEL, "fabric"), |packet| server_info.handle(PacketState::Play, packet)));
+    assert!(!feed(plugin_message(0x00, BRAND_CHANNEL, "fabric"), |packet| {
+        server_info.handle(PacketState::Configuration, packet)
+    }));
+    assert_eq!(server_info.brand(), Some("fabric"));
+    assert!(!server_info.enforces_secure_chat());
+}
+
+#[test]
+fn test_server_data_without_secure_chat() {
+    // 1.20.5 moved the secure chat flag to Login (play), Server Data ends with the icon
+    let server_data = ServerData {
+        enforces_secure_chat: false,
+        ..server_data()
+    };
+    for version in [V1_20_6, V1_21_1] {
+        let mut server_info = ServerInfo::new(&version);
+        let mut packet = server_data.write_versioned(version.protocol).unwrap();
+        packet.reset();
+        assert_eq!(packet.read_var_i32().unwrap(), 0x4B);
+        assert_eq!(ServerData::read_versioned(&mut packet, version.protocol).unwrap(), server_data);
+        assert_eq!(packet.remaining(), 0);
+
+        packet.reset();
+        assert!(feed(packet, |packet| server_info.handle(PacketState::Play, packet)));
+        assert_eq!(server_info.server_data(), Some(&server_data));
+        assert!(!server_info.enforces_secure_chat());
+    }
+}
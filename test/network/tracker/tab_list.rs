@@ -0,0 +1,201 @@
+use std::sync::{Arc, Mutex};
+
+use uuid::Uuid;
+
+use crate::{
+    components::Component,
+    network::{
+        buffer::Buffer,
+        tracker::{
+            PlayerInfoEntry, PlayerInfoPacket, SessionState, TabList, ACTION_ADD_PLAYER, ACTION_INITIALIZE_CHAT, ACTION_UPDATE_DISPLAY_NAME, ACTION_UPDATE_GAME_MODE,
+            ACTION_UPDATE_LATENCY, ACTION_UPDATE_LISTED
+        },
+        types::GameMode,
+        versions::{VersionInfo, V1_16_5, V1_19_2, V1_20_6},
+        ByteOrder
+    },
+    profile::Property,
+    test::mock::feed
+};
+
+fn recording_tab_list(version: &VersionInfo) -> (TabList, Arc<Mutex<Vec<String>>>) {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let (join, leave, update) = (events.clone(), events.clone(), events.clone());
+    let tab_list = TabList::new(version)
+        .on_join(move |player| join.lock().unwrap().push(format!("join {}", player.name)))
+        .on_leave(move |player| leave.lock().unwrap().push(format!("leave {}", player.name)))
+        .on_update(move |previous, player| update.lock().unwrap().push(format!("update {} {} -> {}", player.name, previous.latency, player.latency)));
+    (tab_list, events)
+}
+
+fn add(uuid: Uuid, name: &str) -> PlayerInfoEntry {
+    PlayerInfoEntry {
+        name: Some(name.to_string()),
+        properties: vec![Property {
+            name: "textures".to_string(),
+            value: "e30=".to_string(),
+            signature: None
+        }],
+        game_mode: Some(GameMode::Creative),
+        listed: Some(true),
+        latency: Some(42),
+        display_name: Some(None),
+        ..PlayerInfoEntry::new(uuid)
+    }
+}
+
+#[test]
+fn test_bitfield_actions() {
+    let (alice, bob) = (Uuid::from_u128(1), Uuid::from_u128(2));
+    let (mut tab_list, events) = recording_tab_list(&V1_20_6);
+
+    assert!(feed(
+        PlayerInfoPacket::Update {
+            actions: ACTION_ADD_PLAYER | ACTION_INITIALIZE_CHAT | ACTION_UPDATE_GAME_MODE | ACTION_UPDATE_LISTED | ACTION_UPDATE_LATENCY,
+            entries: vec![add(alice, "Alice"), add(bob, "Bob")]
+        }
+        .write_versioned(tab_list.protocol())
+        .unwrap(),
+        |packet| tab_list.handle(packet)
+    ));
+    assert_eq!(tab_list.len(), 2);
+    let player = tab_list.get(&alice).unwrap();
+    assert_eq!((player.game_mode, player.latency, player.listed), (GameMode::Creative, 42, true));
+    assert_eq!(player.properties[0].name, "textures");
+
+    assert!(feed(
+        PlayerInfoPacket::Update {
+            actions: ACTION_UPDATE_LATENCY,
+            entries: vec![PlayerInfoEntry {
+                game_mode: Some(GameMode::Spectator),
+                latency: Some(150),
+                ..PlayerInfoEntry::new(alice)
+            }]
+        }
+        .write_versioned(tab_list.protocol())
+        .unwrap(),
+        |packet| tab_list.handle(packet)
+    ));
+    assert_eq!((tab_list.get(&alice).unwrap().game_mode, tab_list.get(&alice).unwrap().latency), (GameMode::Creative, 150));
+
+    assert!(feed(
+        PlayerInfoPacket::Update {
+            actions: ACTION_UPDATE_DISPLAY_NAME | ACTION_UPDATE_LISTED,
+            entries: vec![PlayerInfoEntry {
+                display_name: Some(Some(Component::text("Bobby").color("gold"))),
+                listed: Some(false),
+                ..PlayerInfoEntry::new(bob)
+            }]
+        }
+        .write_versioned(tab_list.protocol())
+        .unwrap(),
+        |packet| tab_list.handle(packet)
+    ));
+    let player = tab_list.get(&bob).unwrap();
+    assert_eq!(player.display_name, Some(Component::text("Bobby").color("gold")));
+    assert!(!player.listed);
+
+    assert!(feed(
+        PlayerInfoPacket::Update {
+            actions: ACTION_UPDATE_LATENCY,
+            entries: vec![PlayerInfoEntry::new(Uuid::from_u128(3))]
+        }
+        .write_versioned(tab_list.protocol())
+        .unwrap(),
+        |packet| tab_list.handle(packet)
+    ));
+    assert!(feed(
+        PlayerInfoPacket::Remove(vec![alice, Uuid::from_u128(3)]).write_versioned(tab_list.protocol()).unwrap(),
+        |packet| tab_list.handle(packet)
+    ));
+    assert_eq!(tab_list.players().keys().collect::<Vec<_>>(), vec![&bob]);
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec!["join Alice", "join Bob", "update Alice 42 -> 150", "update Bob 42 -> 42", "leave Alice"]
+    );
+}
+
+#[test]
+fn test_legacy_actions() {
+    for version in [V1_16_5, V1_19_2] {
+        let alice = Uuid::from_u128(1);
+        let (mut tab_list, events) = recording_tab_list(&version);
+
+        assert!(feed(
+            PlayerInfoPacket::Update {
+                actions: ACTION_ADD_PLAYER | ACTION_UPDATE_GAME_MODE | ACTION_UPDATE_LISTED | ACTION_UPDATE_LATENCY | ACTION_UPDATE_DISPLAY_NAME,
+                entries: vec![PlayerInfoEntry {
+                    display_name: Some(Some(Component::text("Al"))),
+                    ..add(alice, "Alice")
+                }]
+            }
+            .write_versioned(tab_list.protocol())
+            .unwrap(),
+            |packet| tab_list.handle(packet)
+        ));
+        let player = tab_list.get(&alice).unwrap();
+        assert_eq!((player.name.as_str(), player.latency, player.listed), ("Alice", 42, true));
+        assert_eq!(player.display_name, Some(Component::text("Al")));
+
+        assert!(feed(
+            PlayerInfoPacket::Update {
+                actions: ACTION_UPDATE_GAME_MODE,
+                entries: vec![PlayerInfoEntry {
+                    game_mode: Some(GameMode::Adventure),
+                    ..PlayerInfoEntry::new(alice)
+                }]
+            }
+            .write_versioned(tab_list.protocol())
+            .unwrap(),
+            |packet| tab_list.handle(packet)
+        ));
+        assert_eq!(tab_list.get(&alice).unwrap().game_mode, GameMode::Adventure);
+
+        assert!(feed(PlayerInfoPacket::Remove(vec![alice]).write_versioned(tab_list.protocol()).unwrap(), |packet| {
+            tab_list.handle(packet)
+        }));
+        assert!(tab_list.is_empty());
+        assert_eq!(*events.lock().unwrap(), vec!["join Alice", "update Alice 42 -> 42", "leave Alice"]);
+
+        let mixed = PlayerInfoPacket::Update {
+            actions: ACTION_UPDATE_GAME_MODE | ACTION_UPDATE_LATENCY,
+            entries: Vec::new()
+        };
+        assert!(mixed.write_versioned(version.protocol).is_err());
+    }
+}
+
+#[test]
+fn test_ignores_other_packets() {
+    let mut tab_list = TabList::new(&V1_20_6);
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_var_i32(0x3C).unwrap();
+    buffer.write_var_i32(5).unwrap();
+    buffer.reset();
+    assert!(!tab_list.handle(&mut buffer).unwrap());
+    assert_eq!(buffer.position(), 0);
+    assert!(PlayerInfoPacket::is_player_info(0x3D, V1_20_6.protocol));
+    assert!(!PlayerInfoPacket::is_player_info(0x3D, V1_16_5.protocol));
+}
+
+#[test]
+fn test_reconnect_clears_players() {
+    let (alice, bob) = (Uuid::from_u128(1), Uuid::from_u128(2));
+    let (mut tab_list, events) = recording_tab_list(&V1_20_6);
+    assert!(feed(
+        PlayerInfoPacket::Update {
+            actions: ACTION_ADD_PLAYER | ACTION_UPDATE_LATENCY,
+            entries: vec![add(alice, "Alice"), add(bob, "Bob")]
+        }
+        .write_versioned(tab_list.protocol())
+        .unwrap(),
+        |packet| tab_list.handle(packet)
+    ));
+    tab_list.reset_for_reconnect();
+    assert!(tab_list.is_empty());
+
+    let mut events = events.lock().unwrap().clone();
+    events.sort();
+    assert_eq!(events, vec!["join Alice", "join Bob", "leave Alice", "leave Bob"]);
+}
@@ -0,0 +1,236 @@
+use crate::{
+    network::{
+        buffer::Buffer,
+        connection::{Readable, VersionedReadable, Writable},
+        item::{ComponentKind, ComponentPatch, ComponentValue},
+        nbt::{Compound, Tag},
+        types::{ByteGameMode, Difficulty, FloatGameMode, GameMode, IdMap, Identifier, ParticleType, Slot, SoundEvent, WorldTime},
+        versions::{V1_19_4, V1_20_4, V1_20_6, V1_21_1},
+        ByteOrder, Error
+    },
+    test::mock::round_trip
+};
+
+#[test]
+fn test_world_time_frozen() {
+    let time = WorldTime::new(1000, 6000, true);
+    assert_eq!(time.time_of_day, -6000);
+    assert!(time.is_daylight_cycle_frozen());
+    assert_eq!(time.day_time(), 6000);
+
+    let time = WorldTime::new(1000, 0, true);
+    assert_eq!(time.time_of_day, -1);
+    assert!(time.is_daylight_cycle_frozen());
+
+    let time = WorldTime::new(1000, 30000, false);
+    assert!(!time.is_daylight_cycle_frozen());
+    assert_eq!(time.day(), 1);
+    assert_eq!(time.day_time(), 6000);
+
+    let (bytes, read) = round_trip(&WorldTime::new(1, 6000, true));
+    assert_eq!(bytes[8..], (-6000i64).to_be_bytes());
+    assert_eq!(read, WorldTime::new(1, 6000, true));
+}
+
+#[test]
+fn test_float_game_mode() {
+    assert_eq!(FloatGameMode::from_value(-1.0).unwrap(), FloatGameMode(None));
+    assert_eq!(FloatGameMode::from_value(3.0).unwrap(), FloatGameMode(Some(GameMode::Spectator)));
+    assert!(FloatGameMode::from_value(1.5).is_err());
+    assert!(FloatGameMode::from_value(4.0).is_err());
+
+    let (bytes, read) = round_trip(&FloatGameMode(None));
+    assert_eq!(bytes, (-1.0f32).to_be_bytes());
+    assert_eq!(read, FloatGameMode(None));
+    assert_eq!(round_trip(&FloatGameMode(Some(GameMode::Creative))).1, FloatGameMode(Some(GameMode::Creative)));
+}
+
+#[test]
+fn test_byte_game_mode() {
+    let (bytes, read) = round_trip(&ByteGameMode(None));
+    assert_eq!(bytes, vec![0xFF]);
+    assert_eq!(read, ByteGameMode(None));
+
+    let (bytes, read) = round_trip(&ByteGameMode(Some(GameMode::Adventure)));
+    assert_eq!(bytes, vec![2]);
+    assert_eq!(read, ByteGameMode(Some(GameMode::Adventure)));
+    assert!(ByteGameMode::read(&mut Buffer::new(vec![4], false, Some(ByteOrder::BigEndian))).is_err());
+}
+
+#[test]
+fn test_difficulty() {
+    let (bytes, read) = round_trip(&Difficulty::Hard);
+    assert_eq!(bytes, vec![3]);
+    assert_eq!(read, Difficulty::Hard);
+    assert!(Difficulty::read(&mut Buffer::new(vec![4], false, Some(ByteOrder::BigEndian))).is_err());
+}
+
+crate::protocol_enum_u8! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Weather {
+        Clear = 0,
+        Rain = 1,
+        Thunder = 4
+    }
+}
+
+#[test]
+fn test_exported_u8_enum() {
+    let (bytes, read) = round_trip(&Weather::Thunder);
+    assert_eq!((bytes, read), (vec![4], Weather::Thunder));
+    assert_eq!(Weather::from_id(1), Some(Weather::Rain));
+    assert!(Weather::read(&mut Buffer::new(vec![2], false, Some(ByteOrder::BigEndian))).is_err());
+    assert_eq!(Weather::Clear.id(), 0);
+}
+
+#[test]
+fn test_u8_enum() {
+    for game_mode in [GameMode::Survival, GameMode::Creative, GameMode::Adventure, GameMode::Spectator] {
+        let (bytes, read) = round_trip(&game_mode);
+        assert_eq!(bytes, vec![game_mode.id()]);
+        assert_eq!(read, game_mode);
+    }
+
+    let mut buffer = Buffer::new(vec![1, 0xFF], false, Some(ByteOrder::BigEndian));
+    assert_eq!(GameMode::read(&mut buffer).unwrap(), GameMode::Creative);
+    match GameMode::read(&mut buffer) {
+        Err(Error::Decode { offset, reason }) => assert_eq!((offset, reason.as_str()), (1, "Invalid GameMode 255")),
+        other => panic!("Expected a decode error, got {:?}", other)
+    }
+}
+
+#[test]
+fn test_slot() {
+    let enchanted = Slot::new(802, 1).with_nbt(Compound::new().with("Damage", Tag::Int(3)));
+    for protocol in [V1_19_4.protocol, V1_20_4.protocol] {
+        let mut buffer = enchanted.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), protocol).unwrap();
+        buffer.reset();
+        assert_eq!(Slot::read_versioned(&mut buffer, protocol).unwrap(), enchanted);
+        assert_eq!(buffer.remaining(), 0);
+    }
+
+    let (bytes, read) = slot_round_trip(&Slot::new(1, 64), V1_21_1.protocol);
+    assert_eq!(bytes, vec![64, 1, 0, 0]);
+    assert_eq!(read, Slot::new(1, 64));
+    assert_eq!(slot_round_trip(&Slot::new(1, 0), V1_21_1.protocol), (vec![0], Slot::empty()));
+    assert_eq!(Slot::new(1, -3), Slot::empty());
+
+    // 1.20.5 replaced NBT with data components
+    assert!(enchanted.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), V1_20_6.protocol).is_err());
+    assert!(Slot::new(1, 1)
+        .with_components(ComponentPatch::new().with(ComponentKind::Damage, ComponentValue::Damage(1)))
+        .write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), V1_20_4.protocol)
+        .is_err());
+}
+
+fn slot_round_trip(slot: &Slot, protocol: i32) -> (Vec<u8>, Slot) {
+    let mut buffer = slot.write_versioned(Buffer::empty(true, Some(ByteOrder::BigEndian)), protocol).unwrap();
+    let bytes = buffer.to_bytes();
+    buffer.reset();
+    let read = Slot::read_versioned(&mut buffer, protocol).unwrap();
+    assert_eq!(buffer.remaining(), 0);
+    (bytes, read)
+}
+
+#[test]
+fn test_slot_components() {
+    let components = ComponentPatch::new()
+        .with(ComponentKind::CustomName, ComponentValue::CustomName(Tag::String("Excalibur".to_string())))
+        .with(ComponentKind::Lore, ComponentValue::Lore(vec![Tag::String("Pulled from a stone".to_string())]))
+        .with(
+            ComponentKind::Enchantments,
+            ComponentValue::Enchantments {
+                levels: vec![(13, 5), (37, 3)],
+                show_in_tooltip: true
+            }
+        )
+        .with(ComponentKind::Damage, ComponentValue::Damage(42))
+        .with(ComponentKind::MaxDamage, ComponentValue::MaxDamage(2031))
+        .with(ComponentKind::Unbreakable, ComponentValue::Unbreakable { show_in_tooltip: false })
+        .with(
+            ComponentKind::CustomData,
+            ComponentValue::CustomData(Compound::new().with("owner", Tag::String("Arthur".to_string())))
+        )
+        .without(ComponentKind::Other(8));
+    let sword = Slot::new(802, 1).with_components(components);
+    for protocol in [V1_20_6.protocol, V1_21_1.protocol] {
+        assert_eq!(slot_round_trip(&sword, protocol).1, sword);
+    }
+
+    let damaged = Slot::new(802, 1).with_components(ComponentPatch::new().with(ComponentKind::Damage, ComponentValue::Damage(3)));
+    assert_eq!(slot_round_trip(&damaged, V1_21_1.protocol).0, vec![1, 0xA2, 0x06, 1, 0, 3, 3]);
+    assert!(!damaged.is_same_item(&Slot::new(802, 1)));
+}
+
+#[test]
+fn test_slot_raw_components() {
+    let bytes = vec![1, 0xA2, 0x06, 2, 1, 24, 0x00, 0xFF, 0x00, 0x00, 1, 16, 7, 3];
+    let slot = Slot::read_versioned(&mut Buffer::new(bytes.clone(), false, Some(ByteOrder::BigEndian)), V1_21_1.protocol).unwrap();
+    assert_eq!(slot.components.get(ComponentKind::Other(24)), Some(&ComponentValue::Raw(vec![0x00, 0xFF, 0x00, 0x00, 1])));
+    assert_eq!(slot.components.get(ComponentKind::Other(16)), Some(&ComponentValue::Raw(vec![7])));
+    assert_eq!(slot.components.removed, vec![ComponentKind::Damage]);
+    assert_eq!(slot_round_trip(&slot, V1_21_1.protocol).0, bytes);
+
+    // Food has a nested structure, so neither its value nor anything after it can be read
+    assert!(matches!(
+        Slot::read_versioned(&mut Buffer::new(vec![1, 1, 1, 0, 20, 4], false, Some(ByteOrder::BigEndian)), V1_21_1.protocol),
+        Err(Error::Decode { offset: 5, .. })
+    ));
+    assert!(matches!(
+        Slot::read_versioned(&mut Buffer::new(vec![1, 1, 1, 0, 99], false, Some(ByteOrder::BigEndian)), V1_21_1.protocol),
+        Err(Error::Decode { offset: 5, .. })
+    ));
+    assert_eq!(IdMap::data_components(V1_20_6.protocol).unwrap().id(&Identifier::parse("enchantments").unwrap()), Some(9));
+}
+
+#[test]
+fn test_sound_event() {
+    let sounds = IdMap::sounds(767).unwrap();
+    let cave = Identifier::parse("ambient.cave").unwrap();
+    let (bytes, _) = round_trip(&SoundEvent::Known { id: 7, name: cave.clone() });
+    assert_eq!(bytes, vec![8]);
+    assert_eq!(
+        SoundEvent::read_with(&mut Buffer::new(bytes, false, Some(ByteOrder::BigEndian)), &sounds).unwrap().name(),
+        Some(&cave)
+    );
+
+    let custom = SoundEvent::Custom {
+        name: Identifier::parse("custom:bell").unwrap(),
+        fixed_range: Some(16.0)
+    };
+    let (bytes, read) = round_trip(&custom);
+    assert_eq!(bytes[0], 0);
+    assert_eq!(bytes.len(), 1 + 12 + 1 + 4);
+    assert_eq!(read, custom);
+    assert_eq!(round_trip(&SoundEvent::Custom { name: cave, fixed_range: None }).0.len(), 1 + 23 + 1);
+
+    let mut buffer = SoundEvent::Unknown(1500).write(Buffer::empty(true, Some(ByteOrder::BigEndian))).unwrap();
+    buffer.reset();
+    let unknown = SoundEvent::read_with(&mut buffer, &sounds).unwrap();
+    assert_eq!(unknown, SoundEvent::Unknown(1500));
+    assert_eq!((unknown.id(), unknown.name()), (Some(1500), None));
+    assert_eq!(IdMap::sounds(766).unwrap(), sounds);
+    assert!(IdMap::sounds(754).is_err());
+    assert!(SoundEvent::read_versioned(&mut Buffer::new(vec![8], false, Some(ByteOrder::BigEndian)), V1_19_4.protocol).is_err());
+}
+
+#[test]
+fn test_particle_type() {
+    // 1.20.5 removed a particle in front of block, so the same name has different ids
+    let block = Identifier::parse("block").unwrap();
+    assert_eq!(IdMap::particles(765).unwrap().id(&block), Some(2));
+    assert_eq!(IdMap::particles(766).unwrap().id(&block), Some(1));
+    assert_eq!(IdMap::particles(767).unwrap().id(&block), Some(1));
+    assert!(IdMap::particles(763).is_err());
+
+    let mut particles = IdMap::particles(767).unwrap();
+    let (bytes, read) = round_trip(&ParticleType::from_id(1, &particles));
+    assert_eq!(bytes, vec![1]);
+    assert_eq!(read.resolve(&particles).name(), Some(&block));
+
+    let named = ParticleType::Named(Identifier::parse("custom:sparkle").unwrap());
+    assert!(named.write(Buffer::empty(true, Some(ByteOrder::BigEndian))).is_err());
+    assert_eq!(named.clone().resolve(&particles), named);
+    particles.insert(200, Identifier::parse("custom:sparkle").unwrap());
+    assert_eq!(round_trip(&named.resolve(&particles)).0, vec![0xC8, 0x01]);
+}
@@ -0,0 +1,56 @@
+use crate::{
+    network::{buffer::Buffer, ByteOrder},
+    profile::{read_login_properties, write_login_properties, Property, LOGIN_PROPERTIES_PROTOCOL},
+    test::mock::round_trip
+};
+
+#[test]
+fn test_property_signed() {
+    let property = Property {
+        name: "textures".to_string(),
+        value: "value".to_string(),
+        signature: Some("sig".to_string())
+    };
+    assert_eq!(round_trip(&property), (b"\x08textures\x05value\x01\x03sig".to_vec(), property.clone()));
+}
+
+#[test]
+fn test_property_unsigned() {
+    let property = Property {
+        name: "textures".to_string(),
+        value: "value".to_string(),
+        signature: None
+    };
+    assert_eq!(round_trip(&property), (b"\x08textures\x05value\x00".to_vec(), property.clone()));
+
+    // An empty signature is still sent, only a missing one clears the flag
+    let property = Property {
+        signature: Some(String::new()),
+        ..property
+    };
+    assert_eq!(round_trip(&property), (b"\x08textures\x05value\x01\x00".to_vec(), property.clone()));
+}
+
+#[test]
+fn test_login_properties_protocol() {
+    let properties = vec![Property {
+        name: "textures".to_string(),
+        value: "value".to_string(),
+        signature: None
+    }];
+
+    let buffer = write_login_properties(&properties, Buffer::empty(true, Some(ByteOrder::BigEndian)), LOGIN_PROPERTIES_PROTOCOL - 1).unwrap();
+    assert!(buffer.to_bytes().is_empty());
+    assert!(
+        read_login_properties(&mut Buffer::new(Vec::new(), false, Some(ByteOrder::BigEndian)), LOGIN_PROPERTIES_PROTOCOL - 1)
+            .unwrap()
+            .is_empty()
+    );
+
+    let bytes = write_login_properties(&properties, Buffer::empty(true, Some(ByteOrder::BigEndian)), LOGIN_PROPERTIES_PROTOCOL)
+        .unwrap()
+        .to_bytes();
+    assert_eq!(bytes, b"\x01\x08textures\x05value\x00");
+    let mut buffer = Buffer::new(bytes, false, Some(ByteOrder::BigEndian));
+    assert_eq!(read_login_properties(&mut buffer, LOGIN_PROPERTIES_PROTOCOL).unwrap(), properties);
+}
@@ -0,0 +1,343 @@
+use std::{
+    fmt::Write as _,
+    net::TcpListener,
+    thread::{self, JoinHandle},
+    time::Duration
+};
+
+use crate::network::{
+    buffer::Buffer,
+    connection::{pipeline::framing::FrameEncoder, socket::SocketConnection, Connection, Pipeline, Server},
+    encryption::{generate_key_pair, public_key_der, rsa_decrypt},
+    handshake::{Handshake, HANDSHAKE},
+    ByteOrder, PacketDirection, PacketState
+};
+
+// One step per line: "> id body" from the client, "< id body" from the server, "compress threshold" and "encrypt secret"
+// Bodies are hex bytes and length prefixed "strings", {address}, {port} and {public_key} are placeholders and * matches the rest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Bytes(Vec<u8>),
+    Address,
+    Port,
+    PublicKey,
+    Rest
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    Serverbound(Vec<Token>),
+    Clientbound(Vec<Token>),
+    Compress(i32),
+    Encrypt(Vec<u8>)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transcript {
+    pub protocol: i32,
+    pub steps: Vec<Step>,
+    pub lines: Vec<usize>
+}
+
+impl Transcript {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut protocol = None;
+        let (mut steps, mut lines) = (Vec::new(), Vec::new());
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |message: String| format!("Line {}: {}", index + 1, message);
+            let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+            let step = match keyword {
+                "protocol" => {
+                    protocol = Some(rest.trim().parse().map_err(|_| error(format!("Invalid protocol {}", rest)))?);
+                    continue;
+                }
+                ">" => Step::Serverbound(parse_tokens(rest).map_err(error)?),
+                "<" => {
+                    let tokens = parse_tokens(rest).map_err(error)?;
+                    if tokens.iter().any(|token| matches!(token, Token::Address | Token::Port | Token::Rest)) {
+                        return Err(error("Clientbound packets can only use the {public_key} placeholder".to_string()))
+                    }
+                    Step::Clientbound(tokens)
+                }
+                "compress" => Step::Compress(rest.trim().parse().map_err(|_| error(format!("Invalid threshold {}", rest)))?),
+                "encrypt" => Step::Encrypt(parse_hex(rest.trim()).map_err(error)?),
+                keyword => return Err(error(format!("Unknown step {}", keyword)))
+            };
+            steps.push(step);
+            lines.push(index + 1);
+        }
+
+        Ok(Self {
+            protocol: protocol.ok_or("The transcript names no protocol")?,
+            steps,
+            lines
+        })
+    }
+}
+
+fn parse_hex(text: &str) -> Result<Vec<u8>, String> {
+    if !text.len().is_multiple_of(2) {
+        return Err(format!("Odd number of hex digits in {}", text))
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&text[index..index + 2], 16).map_err(|_| format!("Invalid hex {}", text)))
+        .collect()
+}
+
+fn parse_tokens(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let push_bytes = |tokens: &mut Vec<Token>, bytes: Vec<u8>| {
+        match tokens.last_mut() {
+            Some(Token::Bytes(previous)) => previous.extend(bytes),
+            _ => tokens.push(Token::Bytes(bytes))
+        }
+    };
+
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').ok_or_else(|| format!("Unterminated string in {}", text))?;
+            let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+            buffer.write_str(&quoted[..end]).map_err(|error| error.to_string())?;
+            push_bytes(&mut tokens, buffer.to_bytes());
+            rest = quoted[end + 1..].trim_start();
+            continue;
+        }
+
+        let (token, remaining) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        match token {
+            "{address}" => tokens.push(Token::Address),
+            "{port}" => tokens.push(Token::Port),
+            "{public_key}" => tokens.push(Token::PublicKey),
+            "*" if remaining.trim().is_empty() => tokens.push(Token::Rest),
+            "*" => return Err("* can only end a packet".to_string()),
+            hex => push_bytes(&mut tokens, parse_hex(hex)?)
+        }
+        rest = remaining.trim_start();
+    }
+    Ok(tokens)
+}
+
+fn expected_bytes(tokens: &[Token], actual: &[u8], public_key: &[u8]) -> Vec<u8> {
+    let mut expected = Vec::new();
+    for token in tokens {
+        let matched = match token {
+            Token::Bytes(bytes) => {
+                expected.extend(bytes);
+                continue;
+            }
+            Token::PublicKey => {
+                expected.extend(byte_array(public_key));
+                continue;
+            }
+            Token::Port => 2,
+            Token::Rest => actual.len().saturating_sub(expected.len()),
+            Token::Address => {
+                let mut buffer = Buffer::new(actual.get(expected.len()..).unwrap_or_default().to_vec(), false, Some(ByteOrder::BigEndian));
+                buffer.read_string().map(|_| buffer.position()).unwrap_or(0)
+            }
+        };
+        expected.extend(actual.iter().skip(expected.len()).take(matched).collect::<Vec<_>>());
+    }
+    expected
+}
+
+fn byte_array(bytes: &[u8]) -> Vec<u8> {
+    let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+    buffer.write_byte_array(bytes).unwrap();
+    buffer.to_bytes()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect::<Vec<_>>().join(" ")
+}
+
+pub fn byte_diff(expected: &[u8], actual: &[u8]) -> Option<String> {
+    let offset = match expected.iter().zip(actual).position(|(expected, actual)| expected != actual) {
+        Some(offset) => offset,
+        None if expected.len() == actual.len() => return None,
+        None => expected.len().min(actual.len())
+    };
+
+    let start = offset.saturating_sub(8);
+    let window = |bytes: &[u8]| {
+        let end = bytes.len().min(offset + 8);
+        let prefix = if start > 0 { "... " } else { "" };
+        let suffix = if end < bytes.len() { " ..." } else { "" };
+        format!("{}{}{}", prefix, hex(&bytes[start.min(bytes.len())..end]), suffix)
+    };
+
+    let mut diff = format!("differs at byte {} (expected {} bytes, got {})\n", offset, expected.len(), actual.len());
+    writeln!(diff, "expected: {}", window(expected)).unwrap();
+    writeln!(diff, "actual:   {}", window(actual)).unwrap();
+    let marker = if start > 0 { 4 } else { 0 } + (offset - start) * 3;
+    write!(diff, "          {}^^", " ".repeat(marker)).unwrap();
+    Some(diff)
+}
+
+pub struct ScriptedServer {
+    port: u16,
+    handle: JoinHandle<Result<(), String>>
+}
+
+impl ScriptedServer {
+    pub fn start(transcript: Transcript) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = thread::spawn(move || {
+            let socket = listener.accept().unwrap().0;
+            socket.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+            play(
+                &transcript,
+                SocketConnection::<Server>::new(socket, Pipeline::new().add_last_encoder(FrameEncoder::new(), Some("framing")))
+            )
+        });
+        Self { port, handle }
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn finish(self) -> Result<(), String> {
+        self.handle.join().map_err(|_| "The scripted server panicked".to_string())?
+    }
+}
+
+fn play(transcript: &Transcript, mut connection: SocketConnection<'_, Server>) -> Result<(), String> {
+    let private_key = generate_key_pair(1024).map_err(|error| error.to_string())?;
+    let public_key = public_key_der(&private_key).map_err(|error| error.to_string())?;
+    let mut last_received = None;
+
+    for (step, line) in transcript.steps.iter().zip(&transcript.lines) {
+        match step {
+            Step::Serverbound(tokens) => {
+                let actual = connection
+                    .read_packet()
+                    .map_err(|error| format!("Expected the serverbound packet on line {} => {}", line, error))?
+                    .to_bytes();
+                if let Some(diff) = byte_diff(&expected_bytes(tokens, &actual, &public_key), &actual) {
+                    return Err(format!("Serverbound packet on line {} {}", line, diff))
+                }
+                last_received = Some(actual);
+            }
+            Step::Clientbound(tokens) => {
+                let mut packet = Buffer::empty(true, Some(ByteOrder::BigEndian));
+                packet.write_bytes(&expected_bytes(tokens, &[], &public_key)).unwrap();
+                connection
+                    .write(packet)
+                    .map_err(|error| format!("Unable to send the clientbound packet on line {} => {}", line, error))?;
+            }
+            Step::Compress(threshold) => connection.enable_compression(*threshold),
+            Step::Encrypt(verify_token) => {
+                let error = |error: crate::network::Error| format!("Invalid encryption response before line {} => {}", line, error);
+                let mut response = Buffer::new(last_received.clone().unwrap_or_default(), false, Some(ByteOrder::BigEndian));
+                response.read_var_i32().map_err(error)?;
+                let shared_secret = rsa_decrypt(&private_key, &response.read_byte_array().map_err(error)?).map_err(error)?;
+                if transcript.protocol == 759 || transcript.protocol == 760 {
+                    response.read_bool().map_err(error)?;
+                }
+                if rsa_decrypt(&private_key, &response.read_byte_array().map_err(error)?).map_err(error)? != *verify_token {
+                    return Err(format!("The encryption response before line {} carries another verify token", line))
+                }
+
+                let shared_secret: [u8; 16] = shared_secret.try_into().map_err(|_| format!("The shared secret before line {} isn't 16 bytes long", line))?;
+                connection.enable_encryption(&shared_secret);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default)]
+pub struct Capture {
+    packets: Vec<(PacketDirection, PacketState, Vec<u8>)>,
+    replacements: Vec<(Vec<u8>, String)>
+}
+
+impl Capture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, direction: PacketDirection, state: PacketState, packet: &Buffer) {
+        self.packets.push((direction, state, packet.to_bytes()));
+    }
+
+    pub fn replace(self, bytes: &[u8], text: impl Into<String>) -> Self {
+        let mut replacements = self.replacements;
+        replacements.push((bytes.to_vec(), text.into()));
+        Self { replacements, ..self }
+    }
+
+    pub fn replace_string(self, string: &str, replacement: &str) -> Self {
+        let mut buffer = Buffer::empty(true, Some(ByteOrder::BigEndian));
+        buffer.write_str(string).unwrap();
+        self.replace(&buffer.to_bytes(), format!("\"{}\"", replacement))
+    }
+
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+        for (direction, state, packet) in &self.packets {
+            if let (PacketState::Handshaking, Ok(handshake)) = (state, Self::handshake(packet)) {
+                writeln!(text, "protocol {}", handshake.protocol_version).unwrap();
+            }
+
+            let marker = match direction {
+                PacketDirection::Serverbound => ">",
+                PacketDirection::Clientbound => "<"
+            };
+            writeln!(text, "{} {}", marker, self.render_packet(*state, packet)).unwrap();
+
+            // Set Compression
+            if let (PacketDirection::Clientbound, PacketState::Login, [0x03, threshold @ ..]) = (direction, state, packet.as_slice()) {
+                let mut threshold = Buffer::new(threshold.to_vec(), false, Some(ByteOrder::BigEndian));
+                writeln!(text, "compress {}", threshold.read_var_i32().unwrap()).unwrap();
+            }
+        }
+        text
+    }
+
+    fn handshake(packet: &[u8]) -> Result<Handshake, crate::network::Error> {
+        let mut buffer = Buffer::new(packet.to_vec(), false, Some(ByteOrder::BigEndian));
+        Handshake::read(&mut buffer)
+    }
+
+    fn render_packet(&self, state: PacketState, packet: &[u8]) -> String {
+        if let (PacketState::Handshaking, Ok(handshake)) = (state, Self::handshake(packet)) {
+            let mut protocol = Buffer::empty(true, Some(ByteOrder::BigEndian));
+            protocol.write_var_i32(handshake.protocol_version).unwrap();
+            return format!(
+                "{:02x} {} {{address}} {{port}} {:02x}",
+                HANDSHAKE,
+                hex(&protocol.to_bytes()).replace(' ', ""),
+                handshake.next_state.id()
+            )
+        }
+
+        let mut tokens = Vec::new();
+        let mut bytes = String::new();
+        let mut offset = 0;
+        while offset < packet.len() {
+            match self.replacements.iter().find(|(from, _)| !from.is_empty() && packet[offset..].starts_with(from)) {
+                Some((from, to)) => {
+                    tokens.extend((!bytes.is_empty()).then(|| std::mem::take(&mut bytes)));
+                    tokens.push(to.clone());
+                    offset += from.len();
+                }
+                None => {
+                    write!(bytes, "{:02x}", packet[offset]).unwrap();
+                    offset += 1;
+                }
+            }
+        }
+        tokens.extend((!bytes.is_empty()).then_some(bytes));
+        tokens.join(" ")
+    }
+}
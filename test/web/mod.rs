@@ -0,0 +1,229 @@
+#[cfg(feature = "tracing")]
+pub mod trace;
+
+use std::{error::Error as _, net::TcpListener, time::Duration};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    test::mock::{MockResponse, MockServer},
+    web::{configure_client, ClientConfig, Error, ErrorKind, Requester}
+};
+
+#[tokio::test]
+async fn test_execute_connection_refused() {
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+
+    let error = Requester::get(format!("http://127.0.0.1:{}", port)).execute().await.unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Connect);
+    assert!(error.source().is_some());
+}
+
+#[tokio::test]
+async fn test_execute_timeout() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let error = Requester::get(format!("http://127.0.0.1:{}", port))
+        .timeout(Duration::from_millis(200))
+        .execute()
+        .await
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Timeout);
+    assert!(error.source().is_some());
+
+    let error = error.context("Unable to send request", 42);
+    assert_eq!(error.kind(), ErrorKind::Timeout);
+    assert_eq!(error.code(), 42);
+    assert!(error.message().starts_with("Unable to send request => "));
+}
+
+#[tokio::test]
+async fn test_execute_slow_response_timeout() {
+    let server = MockServer::builder().get("/slow", MockResponse::ok("").delay(Duration::from_secs(5))).start();
+
+    let error = Requester::get(format!("{}/slow", server.url())).timeout(Duration::from_millis(200)).execute().await.unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Timeout);
+}
+
+#[tokio::test]
+async fn test_execute_streamed_response_limit() {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener
+    };
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        assert!(socket.read(&mut [0; 1024]).await.unwrap() > 0);
+        socket.write_all(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n").await.unwrap();
+        let chunk = format!("2000\r\n{}\r\n", "a".repeat(0x2000));
+        for _ in 0..1024 {
+            if socket.write_all(chunk.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let error = Requester::get(format!("http://127.0.0.1:{}", port)).max_response_bytes(64 * 1024).execute().await.unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Body);
+    assert_eq!(error.code(), 38);
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Profile {
+    id: String,
+    name: String
+}
+
+#[tokio::test]
+async fn test_execute_json() {
+    let server = MockServer::builder()
+        .get("/profile", MockResponse::ok(r#"{"id":"069a79f444e94726a5befca90e38aaf5","name":"Notch"}"#))
+        .start();
+
+    let profile = Requester::get(format!("{}/profile", server.url())).execute_json::<Profile>().await.unwrap();
+    assert_eq!(
+        profile,
+        Profile {
+            id: "069a79f444e94726a5befca90e38aaf5".to_string(),
+            name: "Notch".to_string()
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_execute_json_wrong_shape() {
+    let body = format!(r#"{{"id":42,"padding":"{}"}}"#, "x".repeat(1024));
+    let server = MockServer::builder().get("/profile", MockResponse::ok(body)).start();
+
+    let error = Requester::get(format!("{}/profile", server.url())).execute_json::<Profile>().await.unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Decode);
+    assert!(error.message().contains(r#"{"id":42"#));
+    assert!(error.message().ends_with("..."));
+    assert!(error.message().len() < 512);
+    assert!(error.source().is_some());
+}
+
+#[tokio::test]
+async fn test_request_content_types() {
+    let server = MockServer::builder()
+        .post("/form", MockResponse::ok(""))
+        .post("/json", MockResponse::ok(""))
+        .post("/body", MockResponse::ok(""))
+        .post("/raw", MockResponse::ok(""))
+        .start();
+
+    Requester::post(format!("{}/form", server.url())).form(&json!({ "code": "abc" })).execute().await.unwrap();
+    Requester::post(format!("{}/json", server.url())).json(&json!({ "code": "abc" })).execute().await.unwrap();
+    Requester::post(format!("{}/body", server.url()))
+        .body("code abc".to_string())
+        .content_type("text/plain; charset=utf-8")
+        .accept("text/plain")
+        .execute()
+        .await
+        .unwrap();
+    Requester::post(format!("{}/raw", server.url())).body("code abc".to_string()).execute().await.unwrap();
+
+    let requests = server.requests();
+    let header = |index: usize, name: &str| requests[index].headers.get(name).map(|value| value.to_str().unwrap().to_string());
+    assert_eq!(header(0, "content-type").as_deref(), Some("application/x-www-form-urlencoded"));
+    assert_eq!(requests[0].body, "code=abc");
+    assert_eq!(header(1, "content-type").as_deref(), Some("application/json"));
+    assert_eq!(header(1, "accept").as_deref(), Some("application/json"));
+    assert_eq!(header(2, "content-type").as_deref(), Some("text/plain; charset=utf-8"));
+    assert_eq!(header(2, "accept").as_deref(), Some("text/plain"));
+    assert_eq!(header(3, "content-type"), None);
+}
+
+#[tokio::test]
+async fn test_request_header_overrides() {
+    let server = MockServer::builder().post("/json", MockResponse::ok("")).start();
+
+    let url = format!("{}/json", server.url());
+    Requester::post(url.clone())
+        .json(&json!({}))
+        .content_type("application/vnd.minerust+json")
+        .execute()
+        .await
+        .unwrap();
+    Requester::post(url.clone())
+        .content_type("application/vnd.minerust+json")
+        .json(&json!({}))
+        .execute()
+        .await
+        .unwrap();
+    Requester::post(url.clone()).json(&json!({})).accept("text/plain").execute().await.unwrap();
+    Requester::post(url.clone()).accept("text/plain").json(&json!({})).execute().await.unwrap();
+    Requester::post(url.clone()).form(&json!({ "code": "abc" })).content_type("text/plain").execute().await.unwrap();
+
+    let requests = server.requests();
+    let headers = |index: usize, name: &str| requests[index].headers.get_all(name).iter().map(|value| value.to_str().unwrap().to_string()).collect::<Vec<_>>();
+    for index in 0..2 {
+        assert_eq!(headers(index, "content-type"), vec!["application/vnd.minerust+json"]);
+        assert_eq!(headers(index, "accept"), vec!["application/json"]);
+    }
+    for index in 2..4 {
+        assert_eq!(headers(index, "content-type"), vec!["application/json"]);
+        assert_eq!(headers(index, "accept"), vec!["text/plain"]);
+    }
+    assert_eq!(headers(4, "content-type"), vec!["text/plain"]);
+
+    let error = Requester::post(url).accept("text/plain\n").execute().await.unwrap_err();
+    assert_eq!(error.message(), r#"Unable to send request => Invalid accept header "text/plain\n""#);
+}
+
+#[test]
+fn test_from_serde_json_error() {
+    let error = Error::from(serde_json::from_str::<Profile>(r#"{"id":"069a79f4"}"#).unwrap_err());
+    assert_eq!(error.kind(), ErrorKind::Decode);
+    assert!(error.message().contains("missing field `name`"));
+    assert!(error.source().is_some());
+
+    let error = error.context("Unable to parse profile", 16);
+    assert_eq!(error.code(), 16);
+    assert!(error.message().starts_with("Unable to parse profile => missing field `name`"));
+}
+
+#[tokio::test]
+async fn test_from_reqwest_error() {
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+
+    let error = Error::from(reqwest::get(format!("http://127.0.0.1:{}", port)).await.unwrap_err());
+    assert_eq!(error.kind(), ErrorKind::Connect);
+    assert!(error.message().contains(&format!("127.0.0.1:{}", port)));
+    assert!(error.source().is_some());
+}
+
+const ROOT_CA: &[u8] = include_bytes!("../fixtures/web/root_ca.pem");
+
+#[test]
+fn test_client_config_root_certificate() {
+    ClientConfig::new().root_certificate_pem(ROOT_CA).unwrap().build().unwrap();
+    ClientConfig::new().root_certificate_pem(ROOT_CA).unwrap().built_in_root_certificates(false).build().unwrap();
+
+    let pem = String::from_utf8(ROOT_CA.to_vec()).unwrap();
+    let body = pem.lines().filter(|line| !line.starts_with("-----")).collect::<String>();
+    ClientConfig::new().root_certificate_der(&STANDARD.decode(body).unwrap()).unwrap().build().unwrap();
+
+    // rustls only parses the certificate once the client is built
+    match ClientConfig::new().root_certificate_der(b"not a certificate") {
+        Ok(config) => assert_eq!(config.build().unwrap_err().code(), 30),
+        Err(error) => {
+            assert_eq!(error.code(), 29);
+            assert!(error.message().starts_with("Unable to parse root certificate => "));
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_configure_client() {
+    let server = MockServer::builder().get("/configured", MockResponse::ok("configured")).start();
+
+    configure_client(ClientConfig::new().root_certificate_pem(ROOT_CA).unwrap()).unwrap();
+    assert_eq!(Requester::get(format!("{}/configured", server.url())).execute().await.unwrap(), "configured");
+}
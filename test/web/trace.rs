@@ -0,0 +1,156 @@
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex
+    },
+    time::Duration
+};
+
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Instrument, Level, Metadata, Subscriber
+};
+
+use crate::{
+    test::mock::{MockResponse, MockServer},
+    web::{set_slow_call_threshold, slow_call_threshold},
+    webapi::Endpoints
+};
+
+#[derive(Debug, Clone)]
+struct CapturedSpan {
+    name: &'static str,
+    parent: Option<u64>,
+    fields: String
+}
+
+#[derive(Debug, Clone)]
+struct CapturedEvent {
+    level: Level,
+    span: Option<u64>,
+    fields: String
+}
+
+struct FieldWriter<'a>(&'a mut String);
+
+impl Visit for FieldWriter<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.push_str(&format!("{}={:?} ", field.name(), value));
+    }
+}
+
+#[derive(Clone, Default)]
+struct Capture {
+    next_id: Arc<AtomicU64>,
+    spans: Arc<Mutex<Vec<CapturedSpan>>>,
+    events: Arc<Mutex<Vec<CapturedEvent>>>,
+    stack: Arc<Mutex<Vec<u64>>>
+}
+
+impl Capture {
+    fn span(&self, name: &str) -> Option<(u64, CapturedSpan)> {
+        let spans = self.spans.lock().unwrap();
+        spans.iter().position(|span| span.name == name).map(|index| (index as u64 + 1, spans[index].clone()))
+    }
+}
+
+impl Subscriber for Capture {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+        let mut fields = String::new();
+        attributes.record(&mut FieldWriter(&mut fields));
+        let parent = match attributes.parent() {
+            Some(parent) => Some(parent.into_u64()),
+            None if attributes.is_contextual() => self.stack.lock().unwrap().last().copied(),
+            None => None
+        };
+
+        self.spans.lock().unwrap().push(CapturedSpan {
+            name: attributes.metadata().name(),
+            parent,
+            fields
+        });
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst) + 1)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        values.record(&mut FieldWriter(&mut self.spans.lock().unwrap()[span.into_u64() as usize - 1].fields));
+    }
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = String::new();
+        event.record(&mut FieldWriter(&mut fields));
+        self.events.lock().unwrap().push(CapturedEvent {
+            level: *event.metadata().level(),
+            span: self.stack.lock().unwrap().last().copied(),
+            fields
+        });
+    }
+
+    fn enter(&self, span: &Id) {
+        self.stack.lock().unwrap().push(span.into_u64());
+    }
+
+    fn exit(&self, _: &Id) {
+        self.stack.lock().unwrap().pop();
+    }
+}
+
+#[tokio::test]
+async fn test_request_spans_and_slow_call() {
+    let server = MockServer::builder()
+        .get(
+            "/users/profiles/minecraft/Cach30verfl0w",
+            MockResponse::ok(include_str!("../fixtures/webapi/username_to_uuid.json")).delay(Duration::from_millis(300))
+        )
+        .start();
+    set_slow_call_threshold(Duration::from_millis(100));
+    assert_eq!(slow_call_threshold(), Duration::from_millis(100));
+
+    let capture = Capture::default();
+    let _guard = tracing::subscriber::set_default(capture.clone());
+    Endpoints::new(server.url(), server.url(), server.url())
+        .uuid_from_username("Cach30verfl0w")
+        .instrument(tracing::info_span!("caller"))
+        .await
+        .unwrap();
+
+    let (caller, _) = capture.span("caller").unwrap();
+    let (operation, operation_span) = capture.span("webapi.uuid_from_username").unwrap();
+    let (request, request_span) = capture.span("web.request").unwrap();
+    assert_eq!(operation_span.parent, Some(caller));
+    assert_eq!(request_span.parent, Some(operation));
+    assert!(request_span.fields.contains("host=127.0.0.1"));
+    assert!(request_span.fields.contains("attempt=1"));
+    assert!(request_span.fields.contains("status=200"));
+    assert!(request_span.fields.contains("elapsed_ms="));
+
+    let events = capture.events.lock().unwrap();
+    let warning = events.iter().find(|event| event.level == Level::WARN).unwrap();
+    assert_eq!(warning.span, Some(request));
+    assert!(warning.fields.contains("Slow call"));
+}
+
+#[tokio::test]
+async fn test_request_error_without_token() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let url = format!("http://127.0.0.1:{}", port);
+
+    let capture = Capture::default();
+    let _guard = tracing::subscriber::set_default(capture.clone());
+    Endpoints::new(&url, &url, &url).join_server("secret_access_token", uuid::Uuid::nil(), "hash").await.unwrap_err();
+
+    assert!(capture.span("webapi.join_server").is_some());
+    let events = capture.events.lock().unwrap();
+    let error = events.iter().find(|event| event.level == Level::ERROR).unwrap();
+    assert!(error.fields.contains("kind=Connect"));
+    assert!(capture.spans.lock().unwrap().iter().all(|span| !span.fields.contains("secret_access_token")));
+    assert!(events.iter().all(|event| !event.fields.contains("secret_access_token")));
+}
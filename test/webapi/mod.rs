@@ -1,19 +1,221 @@
-use crate::webapi::{blocked_servers, uuid_from_username};
+use std::str::FromStr;
+#[cfg(feature = "encryption")]
+use std::time::{Duration, UNIX_EPOCH};
+
+#[cfg(feature = "encryption")]
+use rsa::pkcs8::{EncodePrivateKey, EncodePublicKey, LineEnding};
+#[cfg(feature = "encryption")]
+use serde_json::json;
+use sha1::{Digest, Sha1};
+use uuid::Uuid;
+
+#[cfg(feature = "encryption")]
+use crate::network::encryption::generate_key_pair;
+use crate::{
+    test::mock::{MockResponse, MockServer},
+    web::ErrorKind,
+    webapi::{blocked_servers, is_server_blocked, profile_from_uuid, uuid_from_username, Endpoints}
+};
+
+const UUID: &str = "abe18c25-73dc-4f18-8638-adb604cb1d03";
+
+fn endpoints(server: &MockServer) -> Endpoints {
+    Endpoints::new(server.url(), server.url(), server.url())
+}
 
 #[tokio::test]
 async fn test_username_to_uuid_valid() {
-    let uuid = uuid_from_username("Cach30verfl0w").await.expect("Unable to get uuid from name");
-    assert_eq!(uuid.to_string(), "abe18c25-73dc-4f18-8638-adb604cb1d03");
+    let server = MockServer::builder()
+        .get(
+            "/users/profiles/minecraft/Cach30verfl0w",
+            MockResponse::ok(include_str!("../fixtures/webapi/username_to_uuid.json"))
+        )
+        .start();
+
+    let uuid = endpoints(&server).uuid_from_username("Cach30verfl0w").await.expect("Unable to get uuid from name");
+    assert_eq!(uuid.to_string(), UUID);
 }
 
 #[tokio::test]
 async fn test_username_to_uuid_invalid() {
-    let uuid = uuid_from_username("NotExistingPlayer").await;
+    let server = MockServer::builder().get("/users/profiles/minecraft/NotExistingPlayer", MockResponse::new(204, "")).start();
+
+    let uuid = endpoints(&server).uuid_from_username("NotExistingPlayer").await;
     assert_eq!(uuid.err().unwrap().code(), 15);
 }
 
+#[tokio::test]
+async fn test_username_to_uuid_not_found() {
+    let server = MockServer::builder().get("/users/profiles/minecraft/NotExistingPlayer", MockResponse::new(404, "{}")).start();
+
+    let error = endpoints(&server).uuid_from_username("NotExistingPlayer").await.unwrap_err();
+    assert_eq!(error.code(), 15);
+    assert!(error.to_string().contains("doesn't exist"));
+}
+
+#[tokio::test]
+async fn test_username_to_uuid_server_error() {
+    let server = MockServer::builder()
+        .get("/users/profiles/minecraft/Cach30verfl0w", MockResponse::new(500, "Internal Server Error"))
+        .start();
+
+    let error = endpoints(&server).uuid_from_username("Cach30verfl0w").await.unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Status);
+    assert!(!error.to_string().contains("doesn't exist"));
+}
+
+#[tokio::test]
+async fn test_profile_from_uuid() {
+    let server = MockServer::builder()
+        .get(
+            "/session/minecraft/profile/abe18c25-73dc-4f18-8638-adb604cb1d03",
+            MockResponse::ok(include_str!("../fixtures/webapi/profile.json"))
+        )
+        .start();
+
+    let profile = endpoints(&server).profile_from_uuid(Uuid::from_str(UUID).unwrap()).await.expect("Unable to get profile");
+    assert_eq!(profile.name, "Cach30verfl0w");
+    assert_eq!(profile.properties.len(), 1);
+    assert_eq!(profile.properties[0].name, "textures");
+    assert!(profile.properties[0].signature.is_none());
+}
+
 #[tokio::test]
 async fn test_blocked_servers() {
-    let blocked_servers = blocked_servers().await;
-    assert_eq!(blocked_servers.unwrap().len(), 2295);
+    let server = MockServer::builder()
+        .get("/blockedservers", MockResponse::ok(include_str!("../fixtures/webapi/blocked_servers.txt")))
+        .start();
+
+    let blocked_servers = endpoints(&server).blocked_servers().await.expect("Unable to get blocked servers");
+    assert!(!blocked_servers.is_empty());
+    assert_eq!(blocked_servers[0], "6f2520f8bd70a718c568ab5274c56bdbbfc14ef4");
+    assert!(blocked_servers.iter().all(|hash| hash.len() == 40 && hash.chars().all(|character| character.is_ascii_hexdigit())));
+}
+
+#[tokio::test]
+async fn test_blocked_servers_skips_invalid_lines() {
+    let body = "6F2520F8BD70A718C568AB5274C56BDBBFC14EF4\r\n\n  7ea72de5f8e70a2ac45f1aa02b9c2f9d6b3f1c9e \nnot-a-hash\n0e4e8a5ef8d96e8a0b13e5b4c2a0d0b7a83c9f5\n";
+    let server = MockServer::builder().get("/blockedservers", MockResponse::ok(body)).start();
+
+    let blocked_servers = endpoints(&server).blocked_servers().await.unwrap();
+    assert_eq!(blocked_servers, vec!["6f2520f8bd70a718c568ab5274c56bdbbfc14ef4", "7ea72de5f8e70a2ac45f1aa02b9c2f9d6b3f1c9e"]);
+}
+
+#[tokio::test]
+async fn test_blocked_servers_response_limit() {
+    let server = MockServer::builder().get("/blockedservers", MockResponse::ok("a".repeat(4096))).start();
+
+    let error = endpoints(&server).with_max_response_bytes(1024).blocked_servers().await.unwrap_err();
+    assert_eq!(error.code(), 17);
+    assert_eq!(error.kind(), ErrorKind::Body);
+    assert!(endpoints(&server).with_max_response_bytes(4096).blocked_servers().await.is_ok());
+}
+
+#[tokio::test]
+#[ignore = "requires access to the live Mojang API"]
+async fn test_live_username_to_uuid() {
+    let uuid = uuid_from_username("Cach30verfl0w").await.expect("Unable to get uuid from name");
+    assert_eq!(uuid.to_string(), UUID);
+}
+
+#[tokio::test]
+#[ignore = "requires access to the live Mojang API"]
+async fn test_live_profile_from_uuid() {
+    let profile = profile_from_uuid(Uuid::from_str(UUID).unwrap()).await.expect("Unable to get profile");
+    assert_eq!(profile.id, UUID.replace('-', ""));
+}
+
+#[tokio::test]
+#[ignore = "requires access to the live Mojang API"]
+async fn test_live_blocked_servers() {
+    let blocked_servers = blocked_servers().await.expect("Unable to get blocked servers");
+    assert!(!blocked_servers.is_empty());
+}
+
+#[test]
+fn test_is_server_blocked() {
+    let hash = |address: &str| Sha1::digest(address.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+    let blocked = vec![hash("*.example.com"), hash("10.0.*")];
+
+    assert!(is_server_blocked("mc.example.com", &blocked));
+    assert!(is_server_blocked("MC.Example.com", &blocked));
+    assert!(!is_server_blocked("example.com", &blocked));
+    assert!(is_server_blocked("10.0.0.1", &blocked));
+    assert!(!is_server_blocked("10.1.0.1", &blocked));
+}
+
+#[cfg(feature = "encryption")]
+#[tokio::test]
+async fn test_player_certificates() {
+    let private_key = generate_key_pair(1024).unwrap();
+    let certificates = json!({
+        "keyPair": {
+            "privateKey": private_key.to_pkcs8_pem(LineEnding::LF).unwrap().replace("PRIVATE KEY", "RSA PRIVATE KEY"),
+            "publicKey": private_key.to_public_key().to_public_key_pem(LineEnding::LF).unwrap().replace("PUBLIC KEY", "RSA PUBLIC KEY")
+        },
+        "publicKeySignatureV2": "c2lnbmF0dXJl",
+        "expiresAt": "2022-06-20T08:28:10.123456Z",
+        "refreshedAfter": "2022-06-19T20:28:10.123456Z"
+    });
+    let server = MockServer::builder().post("/player/certificates", MockResponse::ok(certificates.to_string())).start();
+
+    let certificates = endpoints(&server).player_certificates("access_token").await.unwrap();
+    assert_eq!(certificates.private_key().unwrap(), private_key);
+    assert_eq!(certificates.public_key_der().unwrap(), private_key.to_public_key().to_public_key_der().unwrap().as_bytes());
+    assert_eq!(certificates.public_key_signature().unwrap(), b"signature");
+    assert_eq!(certificates.expires_at().unwrap(), UNIX_EPOCH + Duration::from_micros(1655713690123456));
+    assert!(certificates.refreshed_after().unwrap() < certificates.expires_at().unwrap());
+    assert_eq!(server.requests()[0].method, "POST");
+}
+
+#[tokio::test]
+async fn test_has_joined_malformed() {
+    let server = MockServer::builder()
+        .get("/session/minecraft/hasJoined", MockResponse::ok(r#"{"id":"abe18c2573dc4f188638adb604cb1d03"}"#))
+        .start();
+
+    let error = endpoints(&server).has_joined("Player", "hash").await.unwrap_err();
+    assert_eq!(error.code(), 22);
+    assert_eq!(error.kind(), ErrorKind::Decode);
+    assert!(error.message().starts_with("Unable to parse has joined response => missing field `name`"));
+}
+
+#[tokio::test]
+async fn test_has_joined_escapes_query() {
+    let server = MockServer::builder()
+        .get(
+            "/session/minecraft/hasJoined",
+            MockResponse::ok(r#"{"id":"abe18c2573dc4f188638adb604cb1d03","name":"Player_1","properties":[]}"#)
+        )
+        .start();
+
+    let profile = endpoints(&server).has_joined("Player_1", "-1a&x=1#%").await.unwrap().unwrap();
+    assert_eq!(profile.name, "Player_1");
+    assert_eq!(server.requests()[0].query, "username=Player_1&serverId=-1a%26x%3D1%23%25");
+}
+
+#[tokio::test]
+async fn test_has_joined_invalid_username() {
+    let server = MockServer::builder().start();
+
+    for username in ["", "Player&x=1", "SeventeenLetters_", "Pläyer"] {
+        let error = endpoints(&server).has_joined(username, "hash").await.unwrap_err();
+        assert_eq!(error.code(), 21);
+        assert!(error.message().starts_with("Unable to verify join => Invalid username"));
+    }
+    assert!(server.requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_has_joined_name_mismatch() {
+    let server = MockServer::builder()
+        .get(
+            "/session/minecraft/hasJoined",
+            MockResponse::ok(r#"{"id":"abe18c2573dc4f188638adb604cb1d03","name":"Other","properties":[]}"#)
+        )
+        .start();
+
+    let error = endpoints(&server).has_joined("Player", "hash").await.unwrap_err();
+    assert_eq!(error.code(), 21);
+    assert_eq!(error.message(), "Unable to verify join => Session server returned the profile of Other instead of Player");
 }